@@ -0,0 +1,197 @@
+/// Couleur SGR : index 0-255 (0-7 standard, 8-15 vives) ou truecolor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColor {
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnsiStyle {
+    pub fg: Option<AnsiColor>,
+    pub bg: Option<AnsiColor>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+/// Portion de ligne de meme style, sequences d'echappement retirees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiSpan {
+    pub text: String,
+    pub style: AnsiStyle,
+}
+
+const ESC: char = '\u{1b}';
+
+/// Decoupe une ligne de sortie (ruff, pytest, pip...) en portions stylees selon les
+/// sequences SGR (`ESC[...m`). Les autres sequences (curseur, effacement, titres OSC)
+/// sont retirees sans effet.
+pub fn parse_ansi(line: &str) -> Vec<AnsiSpan> {
+    let mut spans: Vec<AnsiSpan> = Vec::new();
+    let mut style = AnsiStyle::default();
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != ESC {
+            text.push(ch);
+            continue;
+        }
+        match chars.peek() {
+            Some('[') => {
+                chars.next();
+                let mut params = String::new();
+                let mut end = None;
+                for ch in chars.by_ref() {
+                    if ('@'..='~').contains(&ch) {
+                        end = Some(ch);
+                        break;
+                    }
+                    params.push(ch);
+                }
+                if end == Some('m') {
+                    push_span(&mut spans, &mut text, style);
+                    apply_sgr(&mut style, &params);
+                }
+            }
+            Some(']') => {
+                // OSC (titre de fenetre, liens) : jusqu'a BEL ou ESC \.
+                chars.next();
+                while let Some(ch) = chars.next() {
+                    if ch == '\u{7}' {
+                        break;
+                    }
+                    if ch == ESC && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            Some(_) => {
+                chars.next();
+            }
+            None => {}
+        }
+    }
+    push_span(&mut spans, &mut text, style);
+    spans
+}
+
+/// Texte brut de la ligne, sans aucune sequence d'echappement.
+pub fn strip_ansi(line: &str) -> String {
+    if !line.contains(ESC) {
+        return line.to_string();
+    }
+    parse_ansi(line).into_iter().map(|span| span.text).collect()
+}
+
+/// Vrai si du texte visible est colore avec l'un des index (ex. `[1, 9]` : rouge et rouge vif).
+pub fn has_fg(spans: &[AnsiSpan], indexes: &[u8]) -> bool {
+    spans.iter().any(|span| {
+        !span.text.trim().is_empty()
+            && matches!(span.style.fg, Some(AnsiColor::Indexed(index)) if indexes.contains(&index))
+    })
+}
+
+fn push_span(spans: &mut Vec<AnsiSpan>, text: &mut String, style: AnsiStyle) {
+    if text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(text),
+        _ => spans.push(AnsiSpan {
+            text: text.clone(),
+            style,
+        }),
+    }
+    text.clear();
+}
+
+fn apply_sgr(style: &mut AnsiStyle, params: &str) {
+    let codes: Vec<u16> = params
+        .split([';', ':'])
+        .map(|code| code.parse().unwrap_or(0))
+        .collect();
+    let mut idx = 0;
+    while idx < codes.len() {
+        match codes[idx] {
+            0 => *style = AnsiStyle::default(),
+            1 => style.bold = true,
+            2 => style.dim = true,
+            3 => style.italic = true,
+            4 => style.underline = true,
+            22 => {
+                style.bold = false;
+                style.dim = false;
+            }
+            23 => style.italic = false,
+            24 => style.underline = false,
+            code @ 30..=37 => style.fg = Some(AnsiColor::Indexed((code - 30) as u8)),
+            code @ 90..=97 => style.fg = Some(AnsiColor::Indexed((code - 90 + 8) as u8)),
+            39 => style.fg = None,
+            code @ 40..=47 => style.bg = Some(AnsiColor::Indexed((code - 40) as u8)),
+            code @ 100..=107 => style.bg = Some(AnsiColor::Indexed((code - 100 + 8) as u8)),
+            49 => style.bg = None,
+            code @ (38 | 48) => {
+                let (color, used) = extended_color(&codes[idx + 1..]);
+                idx += used;
+                if let Some(color) = color {
+                    if code == 38 {
+                        style.fg = Some(color);
+                    } else {
+                        style.bg = Some(color);
+                    }
+                }
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+}
+
+/// `5;n` (256 couleurs) ou `2;r;g;b` (truecolor) apres 38/48 ; renvoie aussi les codes consommes.
+fn extended_color(rest: &[u16]) -> (Option<AnsiColor>, usize) {
+    let byte = |value: &u16| u8::try_from(*value).ok();
+    match rest {
+        [5, n, ..] => (byte(n).map(AnsiColor::Indexed), 2),
+        [2, r, g, b, ..] => match (byte(r), byte(g), byte(b)) {
+            (Some(r), Some(g), Some(b)) => (Some(AnsiColor::Rgb(r, g, b)), 4),
+            _ => (None, 4),
+        },
+        _ => (None, rest.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn couleurs_standard_gras_et_reset() {
+        let spans = parse_ansi("\u{1b}[1;31merror\u{1b}[0m: ligne \u{1b}[92mok\u{1b}[m fin");
+        let texts: Vec<&str> = spans.iter().map(|span| span.text.as_str()).collect();
+        assert_eq!(texts, vec!["error", ": ligne ", "ok", " fin"]);
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Indexed(1)));
+        assert!(spans[0].style.bold);
+        assert_eq!(spans[1].style, AnsiStyle::default());
+        assert_eq!(spans[2].style.fg, Some(AnsiColor::Indexed(10)));
+        assert_eq!(spans[3].style, AnsiStyle::default());
+    }
+
+    #[test]
+    fn couleurs_256_et_truecolor() {
+        let spans = parse_ansi("\u{1b}[38;5;208ma\u{1b}[48;2;10;20;30mb");
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Indexed(208)));
+        assert_eq!(spans[1].style.fg, Some(AnsiColor::Indexed(208)));
+        assert_eq!(spans[1].style.bg, Some(AnsiColor::Rgb(10, 20, 30)));
+    }
+
+    #[test]
+    fn retire_sequences_non_sgr() {
+        assert_eq!(
+            strip_ansi("\u{1b}]0;titre\u{7}\u{1b}[2K\u{1b}[33mwarn\u{1b}[0m"),
+            "warn"
+        );
+        assert_eq!(strip_ansi("sans couleur"), "sans couleur");
+    }
+}
@@ -0,0 +1,125 @@
+use std::fs;
+
+use crate::workspace::WorkspacePaths;
+
+/// Nombre max de commandes gardees (les plus anciennes sont oubliees).
+pub const CMD_HISTORY_LIMIT: usize = 500;
+
+/// Historique des commandes shell, persiste dans `.usbide/cmd_history` (une commande par ligne).
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    pub entries: Vec<String>,
+    /// Position de navigation Haut/Bas (None : saisie en cours, hors historique).
+    index: Option<usize>,
+}
+
+impl CommandHistory {
+    pub fn load(workspace: &WorkspacePaths) -> Self {
+        let entries = fs::read_to_string(workspace.cmd_history_path())
+            .map(|raw| {
+                raw.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let skip = entries.len().saturating_sub(CMD_HISTORY_LIMIT);
+        Self {
+            entries: entries.into_iter().skip(skip).collect(),
+            index: None,
+        }
+    }
+
+    /// Ajoute une commande (sauf doublon consecutif) et reecrit le fichier.
+    pub fn push(&mut self, workspace: &WorkspacePaths, cmd: &str) -> std::io::Result<()> {
+        self.index = None;
+        // Une commande multi-ligne casserait le format "une par ligne".
+        let cmd = cmd.trim().replace(['\r', '\n'], " ");
+        if cmd.is_empty() || self.entries.last() == Some(&cmd) {
+            return Ok(());
+        }
+        self.entries.push(cmd);
+        if self.entries.len() > CMD_HISTORY_LIMIT {
+            let drain = self.entries.len() - CMD_HISTORY_LIMIT;
+            self.entries.drain(0..drain);
+        }
+        let path = workspace.cmd_history_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut text = self.entries.join("\n");
+        text.push('\n');
+        fs::write(path, text)
+    }
+
+    /// Commande precedente (Haut). Reste sur la plus ancienne une fois au debut.
+    pub fn older(&mut self) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let index = match self.index {
+            None => self.entries.len() - 1,
+            Some(idx) => idx.saturating_sub(1),
+        };
+        self.index = Some(index);
+        Some(&self.entries[index])
+    }
+
+    /// Commande suivante (Bas). None : retour a une saisie vide.
+    pub fn newer(&mut self) -> Option<&str> {
+        let idx = self.index?;
+        if idx + 1 >= self.entries.len() {
+            self.index = None;
+            return None;
+        }
+        self.index = Some(idx + 1);
+        Some(&self.entries[idx + 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn historique_sans_doublon_consecutif_et_persiste() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let mut history = CommandHistory::default();
+        history.push(&workspace, "ls").unwrap();
+        history.push(&workspace, "ls").unwrap();
+        history.push(&workspace, "python main.py").unwrap();
+        history.push(&workspace, "ls").unwrap();
+
+        let loaded = CommandHistory::load(&workspace);
+        assert_eq!(loaded.entries, vec!["ls", "python main.py", "ls"]);
+    }
+
+    #[test]
+    fn navigation_haut_bas() {
+        let mut history = CommandHistory {
+            entries: vec!["a".to_string(), "b".to_string()],
+            index: None,
+        };
+        assert_eq!(history.newer(), None);
+        assert_eq!(history.older(), Some("b"));
+        assert_eq!(history.older(), Some("a"));
+        assert_eq!(history.older(), Some("a"));
+        assert_eq!(history.newer(), Some("b"));
+        assert_eq!(history.newer(), None);
+        assert_eq!(history.older(), Some("b"));
+    }
+
+    #[test]
+    fn historique_plafonne() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let mut history = CommandHistory::default();
+        for idx in 0..(CMD_HISTORY_LIMIT + 3) {
+            history.push(&workspace, &format!("echo {idx}")).unwrap();
+        }
+        assert_eq!(history.entries.len(), CMD_HISTORY_LIMIT);
+        assert_eq!(history.entries[0], "echo 3");
+    }
+}
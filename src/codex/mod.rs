@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use regex::Regex;
 use serde_json::Value;
@@ -18,6 +19,8 @@ pub enum CodexError {
     EmptyPackages,
     #[error("script ne doit pas etre vide")]
     EmptyScript,
+    #[error("fichier requirements ne doit pas etre vide")]
+    EmptyRequirements,
     #[error("node portable introuvable")]
     NodeMissing,
     #[error("npm-cli.js introuvable")]
@@ -60,6 +63,68 @@ impl CodexApprovalPolicy {
     }
 }
 
+/// Niveau de detail de la sortie Codex, de la plus sobre a la ligne JSONL brute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodexView {
+    /// Messages utilisateur et assistant, erreurs et indications.
+    Compact,
+    /// Compact plus appels d'outils, reflexions et resultats d'outils.
+    Normal,
+    /// Chaque evenement JSONL tel quel.
+    Raw,
+}
+
+impl CodexView {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CodexView::Compact => "compact",
+            CodexView::Normal => "normal",
+            CodexView::Raw => "raw",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CodexView::Compact => "Compact",
+            CodexView::Normal => "Normal",
+            CodexView::Raw => "Brut",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            CodexView::Compact => CodexView::Normal,
+            CodexView::Normal => CodexView::Raw,
+            CodexView::Raw => CodexView::Compact,
+        }
+    }
+
+    /// Element de `extract_display_items` affiche a ce niveau (rien en brut).
+    pub fn shows(self, kind: &DisplayKind) -> bool {
+        match self {
+            CodexView::Compact => matches!(kind, DisplayKind::Assistant | DisplayKind::User),
+            CodexView::Normal => true,
+            CodexView::Raw => false,
+        }
+    }
+}
+
+pub fn parse_codex_view(value: &str) -> Option<CodexView> {
+    match value.trim().to_lowercase().as_str() {
+        "compact" | "compacte" => Some(CodexView::Compact),
+        "normal" | "normale" => Some(CodexView::Normal),
+        "raw" | "brut" | "jsonl" => Some(CodexView::Raw),
+        _ => None,
+    }
+}
+
+pub fn codex_view_from_env(fallback: CodexView) -> CodexView {
+    env::var("USBIDE_CODEX_VIEW")
+        .ok()
+        .and_then(|v| parse_codex_view(&v))
+        .unwrap_or(fallback)
+}
+
 pub fn parse_codex_sandbox_mode(value: &str) -> Option<CodexSandboxMode> {
     match value.trim().to_lowercase().as_str() {
         "read-only" | "readonly" | "ro" => Some(CodexSandboxMode::ReadOnly),
@@ -83,18 +148,26 @@ pub fn parse_codex_approval_policy(value: &str) -> Option<CodexApprovalPolicy> {
     }
 }
 
-pub fn codex_sandbox_mode_from_env() -> CodexSandboxMode {
+/// USBIDE_CODEX_SANDBOX si valide, sinon `fallback` (valeur des parametres).
+pub fn codex_sandbox_mode_from_env(fallback: CodexSandboxMode) -> CodexSandboxMode {
     env::var("USBIDE_CODEX_SANDBOX")
         .ok()
         .and_then(|v| parse_codex_sandbox_mode(&v))
-        .unwrap_or(CodexSandboxMode::WorkspaceWrite)
+        .unwrap_or(fallback)
 }
 
-pub fn codex_approval_policy_from_env() -> CodexApprovalPolicy {
+pub fn codex_approval_policy_from_env(fallback: CodexApprovalPolicy) -> CodexApprovalPolicy {
     env::var("USBIDE_CODEX_APPROVAL")
         .ok()
         .and_then(|v| parse_codex_approval_policy(&v))
-        .unwrap_or(CodexApprovalPolicy::Never)
+        .unwrap_or(fallback)
+}
+
+/// Modele Codex choisi via USBIDE_CODEX_MODEL, sinon `fallback` (vide : modele par defaut).
+pub fn codex_model_from_env(fallback: &str) -> String {
+    env::var("USBIDE_CODEX_MODEL")
+        .map(|v| v.trim().to_string())
+        .unwrap_or_else(|_| fallback.to_string())
 }
 
 pub fn translate_codex_line(line: &str) -> Option<String> {
@@ -199,6 +272,12 @@ fn find_in_path(cmd: &str, path: Option<&str>, is_windows: bool) -> Option<PathB
         extensions.push(String::new());
     }
 
+    // PATHEXT est en majuscules ; `codex.cmd` doit aussi etre trouve sur un systeme de
+    // fichiers sensible a la casse.
+    let extensions: Vec<String> = extensions
+        .iter()
+        .flat_map(|ext| [ext.clone(), ext.to_lowercase()])
+        .collect();
     for dir in path_iter {
         for ext in &extensions {
             let file_name = if ext.is_empty() {
@@ -207,7 +286,7 @@ fn find_in_path(cmd: &str, path: Option<&str>, is_windows: bool) -> Option<PathB
                 format!("{cmd}{ext}")
             };
             let candidate = dir.join(file_name);
-            if candidate.exists() {
+            if is_path_candidate(&candidate, is_windows) {
                 return Some(candidate);
             }
         }
@@ -215,6 +294,27 @@ fn find_in_path(cmd: &str, path: Option<&str>, is_windows: bool) -> Option<PathB
     None
 }
 
+/// Entree du PATH utilisable : hors Windows, fichier regulier executable (un `codex`
+/// non executable plus tot dans le PATH ne masque pas le vrai).
+fn is_path_candidate(path: &Path, is_windows: bool) -> bool {
+    if is_windows {
+        return path.exists();
+    }
+    is_executable_file(path)
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable_file(path: &Path) -> bool {
+    path.is_file()
+}
+
 fn env_value_from_map(
     env_map: Option<&HashMap<String, String>>,
     key: &str,
@@ -310,6 +410,14 @@ pub fn parse_tool_list(raw: &str) -> Vec<String> {
     cleaned
 }
 
+/// Commande installee par un paquet pip : "ruff==0.5" -> "ruff", "black[jupyter]" -> "black".
+pub fn tool_command_name(package: &str) -> &str {
+    let end = package
+        .find(|c: char| "=<>!~[;@ ".contains(c))
+        .unwrap_or(package.len());
+    package[..end].trim()
+}
+
 pub fn tool_available(
     tool: &str,
     root_dir: Option<&Path>,
@@ -334,6 +442,223 @@ pub fn pyinstaller_available(
     tool_available("pyinstaller", root_dir, env).unwrap_or(false)
 }
 
+/// Formateur de l'action "formater" (installe via les outils dev).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Formatter {
+    Black,
+    Ruff,
+}
+
+impl Formatter {
+    /// Nom de l'executable, aussi valeur de USBIDE_FORMATTER.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Formatter::Black => "black",
+            Formatter::Ruff => "ruff",
+        }
+    }
+}
+
+pub fn parse_formatter(value: &str) -> Option<Formatter> {
+    match value.trim().to_lowercase().as_str() {
+        "black" => Some(Formatter::Black),
+        "ruff" | "ruff format" | "ruff-format" => Some(Formatter::Ruff),
+        _ => None,
+    }
+}
+
+pub fn formatter_from_env(fallback: Formatter) -> Formatter {
+    env::var("USBIDE_FORMATTER")
+        .ok()
+        .and_then(|v| parse_formatter(&v))
+        .unwrap_or(fallback)
+}
+
+/// `black <script>` ou `ruff format <script>` (reecrit le fichier en place).
+pub fn format_argv(formatter: Formatter, script: &Path) -> Result<Vec<String>, CodexError> {
+    if script.as_os_str().is_empty() {
+        return Err(CodexError::EmptyScript);
+    }
+    let mut argv = vec![formatter.as_str().to_string()];
+    if formatter == Formatter::Ruff {
+        argv.push("format".to_string());
+    }
+    argv.push(path_for_cmd(script));
+    Ok(argv)
+}
+
+/// Outil de l'action "lint" ; les deux sont lances s'ils sont installes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Linter {
+    Ruff,
+    Mypy,
+}
+
+impl Linter {
+    pub const ALL: [Linter; 2] = [Linter::Ruff, Linter::Mypy];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Linter::Ruff => "ruff",
+            Linter::Mypy => "mypy",
+        }
+    }
+}
+
+/// `ruff check` / `mypy` sur un fichier, sortie au format `chemin:ligne:colonne: message`.
+pub fn lint_argv(linter: Linter, script: &Path) -> Result<Vec<String>, CodexError> {
+    if script.as_os_str().is_empty() {
+        return Err(CodexError::EmptyScript);
+    }
+    let mut argv: Vec<String> = match linter {
+        Linter::Ruff => vec!["ruff", "check", "--output-format", "concise", "--no-fix"],
+        Linter::Mypy => vec![
+            "mypy",
+            "--show-column-numbers",
+            "--no-error-summary",
+            "--no-color-output",
+        ],
+    }
+    .into_iter()
+    .map(String::from)
+    .collect();
+    argv.push(path_for_cmd(script));
+    Ok(argv)
+}
+
+/// Arguments ajoutes a pytest (USBIDE_PYTEST_ARGS, separes par des espaces).
+pub fn parse_pytest_args(raw: Option<&str>) -> Vec<String> {
+    raw.map(|raw| raw.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+pub fn pytest_args_from_env() -> Vec<String> {
+    parse_pytest_args(env::var("USBIDE_PYTEST_ARGS").ok().as_deref())
+}
+
+/// `pytest [args]`, avec `--lf` pour ne relancer que les tests en echec au dernier passage.
+pub fn pytest_argv(args: &[String], last_failed: bool) -> Vec<String> {
+    let mut argv = vec!["pytest".to_string()];
+    argv.extend(args.iter().cloned());
+    if last_failed
+        && !args
+            .iter()
+            .any(|arg| arg == "--lf" || arg == "--last-failed")
+    {
+        argv.push("--lf".to_string());
+    }
+    argv
+}
+
+/// Compteurs de la ligne de resume finale de pytest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PytestSummary {
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+    pub skipped: usize,
+}
+
+impl PytestSummary {
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && self.errors == 0
+    }
+
+    /// Texte de la barre d'etat : "Tests: 3 ok, 1 echec(s)".
+    pub fn label(&self) -> String {
+        let mut parts = vec![format!("{} ok", self.passed)];
+        if self.failed > 0 {
+            parts.push(format!("{} echec(s)", self.failed));
+        }
+        if self.errors > 0 {
+            parts.push(format!("{} erreur(s)", self.errors));
+        }
+        if self.skipped > 0 {
+            parts.push(format!("{} ignore(s)", self.skipped));
+        }
+        format!("Tests: {}", parts.join(", "))
+    }
+}
+
+/// Etape reconnue d'une installation pip/npm ("Collecting ruff", "added 12 packages in 3s"),
+/// resumee en une ligne ; None pour toute autre sortie.
+pub fn parse_install_progress(line: &str) -> Option<String> {
+    let line = crate::ansi::strip_ansi(line);
+    let line = line.trim();
+    let first = |rest: &str| {
+        rest.split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    if let Some(rest) = line.strip_prefix("Collecting ") {
+        return Some(format!("pip: collecte {}", first(rest)));
+    }
+    if let Some(rest) = line.strip_prefix("Downloading ") {
+        let target = first(rest);
+        let file = target.rsplit('/').next().unwrap_or_default();
+        return Some(format!("pip: telechargement {file}"));
+    }
+    if let Some(rest) = line.strip_prefix("Requirement already satisfied: ") {
+        return Some(format!("pip: {} deja present", first(rest)));
+    }
+    if let Some(rest) = line.strip_prefix("Installing collected packages: ") {
+        return Some(format!("pip: installation de {rest}"));
+    }
+    if let Some(rest) = line.strip_prefix("Successfully installed ") {
+        let count = rest.split_whitespace().count();
+        return Some(format!("pip: {count} paquet(s) installe(s)"));
+    }
+    for (verb, label) in [
+        ("added", "ajoute(s)"),
+        ("changed", "mis a jour"),
+        ("removed", "retire(s)"),
+    ] {
+        let Some(rest) = line
+            .strip_prefix(verb)
+            .and_then(|rest| rest.strip_prefix(' '))
+        else {
+            continue;
+        };
+        let count: usize = rest.split_whitespace().next()?.parse().ok()?;
+        return Some(format!("npm: {count} paquet(s) {label}"));
+    }
+    if line.starts_with("up to date") {
+        return Some("npm: deja a jour".to_string());
+    }
+    None
+}
+
+/// `==== 1 failed, 3 passed, 2 skipped in 0.12s ====` (ou sans `=` avec `-q`).
+pub fn parse_pytest_summary(line: &str) -> Option<PytestSummary> {
+    let line = crate::ansi::strip_ansi(line);
+    let line = line.trim().trim_matches('=').trim();
+    let (counts, duration) = line.rsplit_once(" in ")?;
+    if !duration
+        .trim_start()
+        .starts_with(|ch: char| ch.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut summary = PytestSummary::default();
+    if counts == "no tests ran" {
+        return Some(summary);
+    }
+    for item in counts.split(", ") {
+        let (count, word) = item.trim().split_once(' ')?;
+        let count: usize = count.parse().ok()?;
+        match word {
+            "passed" | "xpassed" => summary.passed += count,
+            "failed" => summary.failed += count,
+            "error" | "errors" => summary.errors += count,
+            "skipped" | "xfailed" | "deselected" => summary.skipped += count,
+            "warning" | "warnings" | "rerun" => {}
+            _ => return None,
+        }
+    }
+    Some(summary)
+}
+
 pub fn pip_install_argv(
     prefix: &Path,
     packages: &[String],
@@ -368,6 +693,36 @@ pub fn pip_install_argv(
     Ok(argv)
 }
 
+/// `pip install -r` d'un fichier requirements dans le prefix portable.
+pub fn pip_install_requirements_argv(
+    prefix: &Path,
+    req_path: &Path,
+    find_links: Option<&Path>,
+    no_index: bool,
+) -> Result<Vec<String>, CodexError> {
+    if req_path.as_os_str().is_empty() {
+        return Err(CodexError::EmptyRequirements);
+    }
+    let mut argv = vec![
+        "python".to_string(),
+        "-m".to_string(),
+        "pip".to_string(),
+        "install".to_string(),
+        "--prefix".to_string(),
+        path_for_cmd(prefix),
+    ];
+    if no_index {
+        argv.push("--no-index".to_string());
+    }
+    if let Some(links) = find_links {
+        argv.push("--find-links".to_string());
+        argv.push(path_for_cmd(links));
+    }
+    argv.push("-r".to_string());
+    argv.push(path_for_cmd(req_path));
+    Ok(argv)
+}
+
 pub fn pyinstaller_install_argv(
     prefix: &Path,
     find_links: Option<&Path>,
@@ -383,6 +738,8 @@ pub fn pyinstaller_build_argv(
     onefile: bool,
     work_dir: Option<&Path>,
     spec_dir: Option<&Path>,
+    icon: Option<&Path>,
+    name: Option<&str>,
 ) -> Result<Vec<String>, CodexError> {
     if script.as_os_str().is_empty() {
         return Err(CodexError::EmptyScript);
@@ -406,10 +763,43 @@ pub fn pyinstaller_build_argv(
         argv.push("--specpath".to_string());
         argv.push(path_for_cmd(spec));
     }
+    if let Some(icon) = icon {
+        argv.push("--icon".to_string());
+        argv.push(path_for_cmd(icon));
+    }
+    if let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) {
+        argv.push("--name".to_string());
+        argv.push(name.to_string());
+    }
     argv.push(path_for_cmd(script));
     Ok(argv)
 }
 
+/// Artefact produit par PyInstaller : `dist/<nom>/` (onedir) ou `dist/<nom>[.exe]` (onefile).
+/// Le nom est celui passe a `--name`, sinon celui du script.
+pub fn pyinstaller_output_path(
+    script: &Path,
+    dist_dir: &Path,
+    onefile: bool,
+    name: Option<&str>,
+) -> PathBuf {
+    let name = match name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => name.to_string(),
+        None => script
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    if !onefile {
+        return dist_dir.join(name);
+    }
+    if is_windows() {
+        dist_dir.join(format!("{name}.exe"))
+    } else {
+        dist_dir.join(name)
+    }
+}
+
 // =============================================================================
 // Codex CLI officiel (npm: @openai/codex)
 // =============================================================================
@@ -467,6 +857,58 @@ fn node_executable_with_os(
     None
 }
 
+pub fn python_tools_dir(root_dir: &Path) -> PathBuf {
+    root_dir.join("tools").join("python")
+}
+
+/// Python portable livre dans `tools/python` (python.exe sous Windows, bin/python3 ailleurs).
+pub fn portable_python(root_dir: &Path) -> Option<PathBuf> {
+    portable_python_with_os(root_dir, is_windows())
+}
+
+fn portable_python_with_os(root_dir: &Path, is_windows: bool) -> Option<PathBuf> {
+    let python_dir = python_tools_dir(root_dir);
+    let candidates = if is_windows {
+        vec![python_dir.join("python.exe")]
+    } else {
+        vec![
+            python_dir.join("bin").join("python3"),
+            python_dir.join("bin").join("python"),
+            python_dir.join("python3"),
+        ]
+    };
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+/// Python portable s'il existe, sinon `python` (ou `python3` hors Windows) du PATH.
+pub fn python_executable(
+    root_dir: &Path,
+    env_map: Option<&HashMap<String, String>>,
+) -> Option<PathBuf> {
+    python_executable_with_os(root_dir, env_map, is_windows())
+}
+
+fn python_executable_with_os(
+    root_dir: &Path,
+    env_map: Option<&HashMap<String, String>>,
+    is_windows: bool,
+) -> Option<PathBuf> {
+    if let Some(found) = portable_python_with_os(root_dir, is_windows) {
+        return Some(found);
+    }
+    let path_value = env_map
+        .and_then(|env| env_path_from_map(Some(env), is_windows))
+        .or_else(|| env::var("PATH").ok());
+    let names: &[&str] = if is_windows {
+        &["python"]
+    } else {
+        &["python3", "python"]
+    };
+    names
+        .iter()
+        .find_map(|name| find_in_path(name, path_value.as_deref(), is_windows))
+}
+
 pub fn npm_cli_js(root_dir: &Path, node: Option<&Path>) -> Option<PathBuf> {
     let node_path = match node {
         Some(node) => node.to_path_buf(),
@@ -615,10 +1057,11 @@ fn codex_base_argv_with_os(
         }
     }
 
-    if is_windows {
-        let search_path = env_path_from_map(env_map, is_windows).or_else(|| env::var("PATH").ok());
-        let resolved = find_in_path("codex", search_path.as_deref(), is_windows);
-        if let Some(resolved) = resolved {
+    // Le PATH de `env_map` (ex: prefixe portable ajoute par `codex_env`) n'est pas celui
+    // de l'IDE : le spawn ne trouverait pas `codex`, d'ou un chemin absolu.
+    let search_path = env_path_from_map(env_map, is_windows).or_else(|| env::var("PATH").ok());
+    if let Some(resolved) = find_in_path("codex", search_path.as_deref(), is_windows) {
+        if is_windows {
             let suffix = resolved
                 .extension()
                 .and_then(|s| s.to_str())
@@ -648,8 +1091,8 @@ fn codex_base_argv_with_os(
                     path_for_cmd(&resolved),
                 ];
             }
-            return vec![path_for_cmd(&resolved)];
         }
+        return vec![path_for_cmd(&resolved)];
     }
 
     vec!["codex".to_string()]
@@ -668,6 +1111,15 @@ pub fn codex_login_argv(
     argv
 }
 
+pub fn codex_logout_argv(
+    root_dir: Option<&Path>,
+    env_map: Option<&HashMap<String, String>>,
+) -> Vec<String> {
+    let mut argv = codex_base_argv_with_os(root_dir, env_map, is_windows());
+    argv.push("logout".to_string());
+    argv
+}
+
 pub fn codex_status_argv(
     root_dir: Option<&Path>,
     env_map: Option<&HashMap<String, String>>,
@@ -718,17 +1170,59 @@ pub fn codex_exec_help_argv(
     argv
 }
 
+/// Acces au registre pendant le `npm install` de Codex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NpmNetwork {
+    Online,
+    /// `--prefer-offline` : le cache npm d'abord (source locale dont les dependances y sont).
+    PreferOffline,
+    /// `--offline` : npm echoue tout de suite si un paquet manque dans son cache.
+    Offline,
+}
+
+/// Source locale de Codex (USBIDE_CODEX_NPM_SOURCE) : archive `.tgz`/`.tar.gz` ou dossier avec
+/// `package.json`, relatif a `root_dir` s'il n'est pas absolu. Ok(None) si elle n'est pas
+/// definie, Err(message) si elle est inutilisable.
+pub fn codex_npm_source(root_dir: &Path, raw: &str) -> Result<Option<PathBuf>, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    let path = root_dir.join(raw);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let package_dir = path.is_dir() && path.join("package.json").is_file();
+    let archive = path.is_file() && (name.ends_with(".tgz") || name.ends_with(".tar.gz"));
+    if package_dir || archive {
+        Ok(Some(path))
+    } else if path.exists() {
+        Err(format!(
+            "Source Codex locale ignoree (ni .tgz ni dossier avec package.json): {}",
+            path.display()
+        ))
+    } else {
+        Err(format!(
+            "Source Codex locale introuvable: {}",
+            path.display()
+        ))
+    }
+}
+
+/// `package` : nom npm ou chemin local (un dossier est copie, pas lie, via `--install-links`).
 pub fn codex_install_argv(
     root_dir: &Path,
     prefix: &Path,
     package: &str,
+    network: NpmNetwork,
 ) -> Result<Vec<String>, CodexError> {
     if package.trim().is_empty() {
         return Err(CodexError::EmptyPackage);
     }
     let node = node_executable(root_dir, None).ok_or(CodexError::NodeMissing)?;
     let npm = npm_cli_js(root_dir, Some(&node)).ok_or(CodexError::NpmMissing)?;
-    Ok(vec![
+    let mut argv = vec![
         path_for_cmd(&node),
         path_for_cmd(&npm),
         "install".to_string(),
@@ -736,11 +1230,98 @@ pub fn codex_install_argv(
         path_for_cmd(prefix),
         "--no-audit".to_string(),
         "--no-fund".to_string(),
-        package.to_string(),
-    ])
+    ];
+    match network {
+        NpmNetwork::Online => {}
+        NpmNetwork::PreferOffline => argv.push("--prefer-offline".to_string()),
+        NpmNetwork::Offline => argv.push("--offline".to_string()),
+    }
+    let local = Path::new(package);
+    if local.is_dir() {
+        argv.push("--install-links".to_string());
+    }
+    if local.exists() {
+        argv.push(path_for_cmd(local));
+    } else {
+        argv.push(package.to_string());
+    }
+    Ok(argv)
+}
+
+/// Outil Node dont la version est sondee avant d'installer Codex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeTool {
+    Node,
+    Npm,
+}
+
+impl NodeTool {
+    pub fn label(self) -> &'static str {
+        match self {
+            NodeTool::Node => "node",
+            NodeTool::Npm => "npm",
+        }
+    }
+
+    /// Version minimale connue pour installer et lancer Codex (majeure, mineure).
+    pub fn minimum(self) -> (u64, u64) {
+        match self {
+            NodeTool::Node => (18, 0),
+            NodeTool::Npm => (8, 0),
+        }
+    }
+}
+
+/// `node --version` et `node npm-cli.js --version` du Node utilise par l'installation.
+pub fn node_version_argvs(root_dir: &Path) -> Vec<(NodeTool, Vec<String>)> {
+    let Some(node) = node_executable(root_dir, None) else {
+        return Vec::new();
+    };
+    let mut probes = vec![(
+        NodeTool::Node,
+        vec![path_for_cmd(&node), "--version".to_string()],
+    )];
+    if let Some(npm) = npm_cli_js(root_dir, Some(&node)) {
+        probes.push((
+            NodeTool::Npm,
+            vec![
+                path_for_cmd(&node),
+                path_for_cmd(&npm),
+                "--version".to_string(),
+            ],
+        ));
+    }
+    probes
+}
+
+/// Version lue dans la sortie de `--version` ("v20.11.1" ou "10.2.4" -> "20.11.1").
+pub fn parse_node_version(line: &str) -> Option<String> {
+    let version = line.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    version_major_minor(version).map(|_| version.to_string())
+}
+
+fn version_major_minor(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Avertissement si `version` est sous le minimum connu de l'outil.
+pub fn node_version_warning(tool: NodeTool, version: &str) -> Option<String> {
+    let (major, minor) = tool.minimum();
+    let found = version_major_minor(version)?;
+    (found < (major, minor)).then(|| {
+        format!(
+            "{} {version} trop ancien : Codex demande {} >= {major}.{minor}. Mets a jour le Node portable (tools/node) avant d'installer Codex, sinon npm echouera.",
+            tool.label(),
+            tool.label()
+        )
+    })
 }
 
-fn prepend_path(env_map: &mut HashMap<String, String>, path: &Path) {
+pub fn prepend_path(env_map: &mut HashMap<String, String>, path: &Path) {
     normalize_path_key(env_map);
     let path_str = path.to_string_lossy();
     let current = env_map.get("PATH").cloned().unwrap_or_default();
@@ -787,6 +1368,10 @@ pub enum DisplayKind {
     Assistant,
     User,
     Action,
+    /// Resume de reflexion du modele ("reflexion: ...").
+    Reasoning,
+    /// Sortie d'une commande ou d'un outil, resumee ("resultat: ...").
+    ToolResult,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -817,7 +1402,7 @@ pub fn codex_hint_for_status(status: u16) -> Option<String> {
             "403 = acces interdit -> verifie login ChatGPT (pas API key) / droits / reseau."
                 .to_string(),
         ),
-        407 => Some("407 = proxy auth required -> configure HTTP_PROXY/HTTPS_PROXY.".to_string()),
+        407 => Some("407 = proxy auth required -> configure HTTP_PROXY/HTTPS_PROXY (http_proxy/https_proxy dans .usbide/settings.toml).".to_string()),
         429 => Some("429 = rate limit -> reessaie plus tard / ralentis.".to_string()),
         500..=599 => {
             Some("5xx = erreur serveur -> reessaie, possible incident cote OpenAI.".to_string())
@@ -826,6 +1411,85 @@ pub fn codex_hint_for_status(status: u16) -> Option<String> {
     }
 }
 
+/// Taille maximale (en caracteres) du fichier ou de la selection joint a un prompt.
+pub const CODEX_CONTEXT_MAX_CHARS: usize = 12_000;
+const CONTEXT_START: &str = "----- debut du contexte -----";
+const CONTEXT_END: &str = "----- fin du contexte -----";
+
+/// Prompt precede d'un contexte (`source` : "fichier x.py", "selection de x.py"), borne
+/// par des delimiteurs et tronque a `CODEX_CONTEXT_MAX_CHARS` avec une note.
+pub fn codex_context_prompt(source: &str, content: &str, question: &str) -> String {
+    let total = content.chars().count();
+    let mut out = format!("Contexte: {source}\n{CONTEXT_START}\n");
+    if total > CODEX_CONTEXT_MAX_CHARS {
+        out.extend(content.chars().take(CODEX_CONTEXT_MAX_CHARS));
+        out.push_str(&format!(
+            "\n[... contenu tronque : {CODEX_CONTEXT_MAX_CHARS} caracteres sur {total}]"
+        ));
+    } else {
+        out.push_str(content);
+    }
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(CONTEXT_END);
+    out.push('\n');
+    out.push_str(question.trim());
+    out
+}
+
+/// Prompt avec contexte resume pour l'affichage : source et question seulement.
+pub fn codex_prompt_display(prompt: &str) -> String {
+    match (
+        prompt.strip_prefix("Contexte: "),
+        prompt.split_once(CONTEXT_END),
+    ) {
+        (Some(rest), Some((_, question))) => {
+            let source = rest.lines().next().unwrap_or_default();
+            format!("[{source}] {}", question.trim())
+        }
+        _ => prompt.to_string(),
+    }
+}
+
+/// Nombre maximal de relances automatiques d'un prompt apres un 429/5xx.
+pub const CODEX_RETRY_MAX: u32 = 3;
+/// Plafond du delai entre deux relances.
+pub const CODEX_RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// 429 (rate limit) et 5xx (erreur serveur) sont passagers : le prompt peut etre relance.
+pub fn is_retryable_status(status: u16) -> bool {
+    status == 429 || (500..=599).contains(&status)
+}
+
+/// Delai avant la relance numero `attempt` (1, 2, 3...) : 5 s, 10 s, 20 s... plafonne.
+pub fn codex_retry_delay(attempt: u32) -> Duration {
+    let secs = 5u64.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    Duration::from_secs(secs).min(CODEX_RETRY_MAX_DELAY)
+}
+
+/// Variable booleenne active par defaut : seuls 0/false/no/off la desactivent.
+fn env_flag_default_true(name: &str) -> bool {
+    env::var(name)
+        .map(|v| {
+            !matches!(
+                v.trim().to_lowercase().as_str(),
+                "0" | "false" | "no" | "off"
+            )
+        })
+        .unwrap_or(true)
+}
+
+/// `USBIDE_CODEX_CONFIRM_DANGER=0` (ou false/no/off) : pas de confirmation en danger-full-access.
+pub fn codex_confirm_danger_from_env() -> bool {
+    env_flag_default_true("USBIDE_CODEX_CONFIRM_DANGER")
+}
+
+/// `USBIDE_CODEX_AUTO_RETRY=0` (ou false/no/off) : relance manuelle uniquement.
+pub fn codex_auto_retry_from_env() -> bool {
+    env_flag_default_true("USBIDE_CODEX_AUTO_RETRY")
+}
+
 fn extract_text_from_content(content: &Value) -> Vec<String> {
     let mut texts = Vec::new();
     match content {
@@ -1032,9 +1696,172 @@ fn format_action(payload: &Value) -> Option<String> {
     }
 }
 
-pub fn extract_display_items(obj: &Value) -> Vec<DisplayItem> {
-    let mut items = Vec::new();
-    let event_type = obj.get("type").and_then(Value::as_str);
+/// Lignes de sortie d'outil gardees dans le resume.
+const TOOL_RESULT_MAX_LINES: usize = 3;
+/// Longueur maximale (en caracteres) d'une ligne du resume.
+const TOOL_RESULT_MAX_CHARS: usize = 120;
+
+/// Texte d'un resume de reflexion : `text`, `summary` (chaine ou liste `summary_text`)
+/// ou `content`.
+fn reasoning_text(payload: &Value) -> Option<String> {
+    let mut parts = Vec::new();
+    for key in ["summary", "text", "content"] {
+        match payload.get(key) {
+            Some(Value::String(text)) => parts.push(text.clone()),
+            Some(Value::Array(list)) => {
+                for entry in list {
+                    match entry {
+                        Value::String(text) => parts.push(text.clone()),
+                        Value::Object(map) => {
+                            if let Some(Value::String(text)) = map.get("text") {
+                                parts.push(text.clone());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        if !parts.is_empty() {
+            break;
+        }
+    }
+    let text = parts
+        .iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+fn reasoning_item(payload: &Value) -> Option<DisplayItem> {
+    reasoning_text(payload).map(|text| DisplayItem {
+        kind: DisplayKind::Reasoning,
+        message: format!("reflexion: {text}"),
+    })
+}
+
+/// Sortie d'outil resumee : code de retour, premieres lignes non vides et nombre de
+/// lignes omises.
+pub fn summarize_tool_output(output: &str, exit_code: Option<i64>) -> String {
+    let lines: Vec<&str> = output
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let mut out = match exit_code {
+        Some(code) => format!("resultat (rc={code}):"),
+        None => "resultat:".to_string(),
+    };
+    if lines.is_empty() {
+        out.push_str(" (aucune sortie)");
+        return out;
+    }
+    let shown: Vec<String> = lines
+        .iter()
+        .take(TOOL_RESULT_MAX_LINES)
+        .map(|line| {
+            if line.chars().count() > TOOL_RESULT_MAX_CHARS {
+                let cut: String = line.chars().take(TOOL_RESULT_MAX_CHARS).collect();
+                format!("{cut}...")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    out.push(' ');
+    out.push_str(&shown.join(" | "));
+    if lines.len() > TOOL_RESULT_MAX_LINES {
+        out.push_str(&format!(
+            " (+{} lignes)",
+            lines.len() - TOOL_RESULT_MAX_LINES
+        ));
+    }
+    out
+}
+
+/// Resultat d'outil : `function_call_output` (sortie parfois encodee en JSON),
+/// `command_execution` termine et `exec_command_end`.
+fn tool_result_item(payload: &Value) -> Option<DisplayItem> {
+    let payload_type = payload.get("type").and_then(Value::as_str)?;
+    let (output, exit_code) = match payload_type {
+        "function_call_output" | "custom_tool_call_output" | "tool_result" => {
+            let raw = payload.get("output").or_else(|| payload.get("content"))?;
+            let decoded = match raw {
+                Value::String(text) => serde_json::from_str::<Value>(text)
+                    .ok()
+                    .filter(Value::is_object)
+                    .unwrap_or_else(|| raw.clone()),
+                other => other.clone(),
+            };
+            match &decoded {
+                Value::Object(map) => {
+                    let output = map
+                        .get("output")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string();
+                    let code = map
+                        .get("metadata")
+                        .and_then(|meta| meta.get("exit_code"))
+                        .and_then(Value::as_i64);
+                    (output, code)
+                }
+                Value::String(text) => (text.clone(), None),
+                Value::Array(_) => (extract_text_from_content(&decoded).join("\n"), None),
+                _ => return None,
+            }
+        }
+        "command_execution" => {
+            if payload.get("status").and_then(Value::as_str) == Some("in_progress") {
+                return None;
+            }
+            let output = payload
+                .get("aggregated_output")
+                .or_else(|| payload.get("output"))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            (output, payload.get("exit_code").and_then(Value::as_i64))
+        }
+        "exec_command_end" => {
+            let output = payload
+                .get("aggregated_output")
+                .or_else(|| payload.get("formatted_output"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    let stdout = payload.get("stdout").and_then(Value::as_str);
+                    let stderr = payload.get("stderr").and_then(Value::as_str);
+                    [stdout, stderr]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                });
+            (output, payload.get("exit_code").and_then(Value::as_i64))
+        }
+        _ => return None,
+    };
+    Some(DisplayItem {
+        kind: DisplayKind::ToolResult,
+        message: summarize_tool_output(&output, exit_code),
+    })
+}
+
+/// Reflexion ou resultat d'outil porte par `payload`, selon son `type`.
+fn extra_item(payload: &Value) -> Option<DisplayItem> {
+    match payload.get("type").and_then(Value::as_str) {
+        Some("reasoning" | "agent_reasoning" | "response.reasoning") => reasoning_item(payload),
+        _ => tool_result_item(payload),
+    }
+}
+
+pub fn extract_display_items(obj: &Value) -> Vec<DisplayItem> {
+    let mut items = Vec::new();
+    let event_type = obj.get("type").and_then(Value::as_str);
     let payload = obj.get("payload").unwrap_or(&Value::Null);
 
     if event_type == Some("event_msg")
@@ -1052,6 +1879,9 @@ pub fn extract_display_items(obj: &Value) -> Vec<DisplayItem> {
             Some("user_message") | Some("user") => {
                 push_item(&mut items, DisplayKind::User, msg);
             }
+            Some("agent_reasoning") | Some("exec_command_end") => {
+                items.extend(extra_item(payload));
+            }
             _ => {
                 if let Some(action) = format_action(payload) {
                     items.push(DisplayItem {
@@ -1065,6 +1895,7 @@ pub fn extract_display_items(obj: &Value) -> Vec<DisplayItem> {
 
     if event_type == Some("response_item") {
         items.extend(items_from_message_payload(payload));
+        items.extend(extra_item(payload));
         if let Some(action) = format_action(payload) {
             items.push(DisplayItem {
                 kind: DisplayKind::Action,
@@ -1084,9 +1915,19 @@ pub fn extract_display_items(obj: &Value) -> Vec<DisplayItem> {
         );
     }
 
+    if matches!(
+        event_type,
+        Some("reasoning")
+            | Some("response.reasoning")
+            | Some("response.reasoning_summary_text.done")
+    ) {
+        items.extend(reasoning_item(obj));
+    }
+
     let item = obj.get("item").unwrap_or(&Value::Null);
     if item.is_object() {
         items.extend(items_from_item_payload(item));
+        items.extend(extra_item(item));
         if let Some(action) = format_action(item) {
             items.push(DisplayItem {
                 kind: DisplayKind::Action,
@@ -1123,6 +1964,40 @@ pub fn extract_assistant_messages(obj: &Value) -> Vec<String> {
         .collect()
 }
 
+/// Langue annoncee par une barriere de bloc de code (```python -> "python", ``` -> "") ;
+/// `None` si la ligne n'est pas une barriere.
+pub fn code_fence(line: &str) -> Option<&str> {
+    line.trim_start()
+        .strip_prefix("```")
+        .map(|rest| rest.split_whitespace().next().unwrap_or(""))
+}
+
+/// Extension par defaut d'un bloc de code selon sa langue annoncee ("txt" si inconnue).
+pub fn code_block_extension(lang: &str) -> &'static str {
+    match lang.trim().to_lowercase().as_str() {
+        "python" | "py" | "python3" | "pycon" => "py",
+        "rust" | "rs" => "rs",
+        "javascript" | "js" | "node" => "js",
+        "typescript" | "ts" => "ts",
+        "json" => "json",
+        "toml" => "toml",
+        "yaml" | "yml" => "yml",
+        "bash" | "sh" | "shell" | "zsh" | "console" => "sh",
+        "powershell" | "ps1" | "pwsh" => "ps1",
+        "bat" | "batch" | "cmd" => "bat",
+        "html" => "html",
+        "css" => "css",
+        "sql" => "sql",
+        "markdown" | "md" => "md",
+        "c" => "c",
+        "cpp" | "c++" => "cpp",
+        "diff" | "patch" => "diff",
+        "ini" | "cfg" => "ini",
+        "xml" => "xml",
+        _ => "txt",
+    }
+}
+
 pub fn hard_wrap(line: &str, width: usize) -> Vec<String> {
     if width == 0 {
         return vec![line.to_string()];
@@ -1179,28 +2054,38 @@ fn wrap_line(line: &str, width: usize) -> Vec<String> {
 }
 
 pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    wrap_text_blocks(text, width)
+        .into_iter()
+        .map(|(line, _)| line)
+        .collect()
+}
+
+/// Comme `wrap_text`, chaque ligne marquee `true` si elle appartient a un bloc de code
+/// (barrieres ``` comprises) ; le code n'est coupe qu'a la largeur, tabulations developpees.
+pub fn wrap_text_blocks(text: &str, width: usize) -> Vec<(String, bool)> {
     let width = width.max(10);
     let mut lines = Vec::new();
     let mut in_code = false;
     for raw in text.lines() {
-        if raw.trim_start().starts_with("```") {
+        if code_fence(raw).is_some() {
             in_code = !in_code;
-            lines.push(raw.to_string());
+            lines.push((raw.to_string(), true));
             continue;
         }
         if in_code {
-            if raw.len() <= width {
-                lines.push(raw.to_string());
+            let raw = raw.replace('\t', "    ");
+            if raw.chars().count() <= width {
+                lines.push((raw, true));
             } else {
-                lines.extend(hard_wrap(raw, width));
+                lines.extend(hard_wrap(&raw, width).into_iter().map(|line| (line, true)));
             }
             continue;
         }
         if raw.trim().is_empty() {
-            lines.push(String::new());
+            lines.push((String::new(), false));
             continue;
         }
-        lines.extend(wrap_line(raw, width));
+        lines.extend(wrap_line(raw, width).into_iter().map(|line| (line, false)));
     }
     lines
 }
@@ -1246,6 +2131,18 @@ mod tests {
         entry_path
     }
 
+    fn create_portable_python(root_dir: &Path) -> PathBuf {
+        let python_dir = python_tools_dir(root_dir);
+        let python_path = if is_windows() {
+            python_dir.join("python.exe")
+        } else {
+            python_dir.join("bin").join("python3")
+        };
+        fs::create_dir_all(python_path.parent().unwrap()).unwrap();
+        fs::write(&python_path, "").unwrap();
+        python_path
+    }
+
     fn create_host_node_in_path(bin_dir: &Path) -> PathBuf {
         let node = if is_windows() {
             bin_dir.join("node.exe")
@@ -1253,10 +2150,20 @@ mod tests {
             bin_dir.join("node")
         };
         fs::create_dir_all(bin_dir).unwrap();
-        fs::write(&node, "").unwrap();
+        write_executable(&node);
         node
     }
 
+    /// Faux binaire trouvable dans le PATH (droit d'execution sous Unix).
+    fn write_executable(path: &Path) {
+        fs::write(path, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+
     #[test]
     fn codex_login_argv_default() {
         let mut env_map = HashMap::new();
@@ -1266,6 +2173,15 @@ mod tests {
         assert_eq!(&argv[1..], ["login"]);
     }
 
+    #[test]
+    fn codex_logout_argv_default() {
+        let mut env_map = HashMap::new();
+        env_map.insert("PATH".to_string(), String::new());
+        let argv = codex_logout_argv(None, Some(&env_map));
+        assert_eq!(argv[0], "codex");
+        assert_eq!(&argv[1..], ["logout"]);
+    }
+
     #[test]
     fn codex_login_argv_device_auth() {
         let argv = codex_login_argv(None, None, true);
@@ -1347,6 +2263,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn codex_shim_windows_en_minuscules_trouve() {
+        let dir = TempDir::new().unwrap();
+        let bin_dir = dir.path().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        let shim = bin_dir.join("codex.cmd");
+        fs::write(&shim, "").unwrap();
+        let found = find_in_path("codex", Some(&bin_dir.to_string_lossy()), true).unwrap();
+        assert!(
+            found
+                .to_string_lossy()
+                .to_lowercase()
+                .ends_with("codex.cmd")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn codex_argv_resolu_dans_le_path_de_l_env() {
+        let dir = TempDir::new().unwrap();
+        let bin_dir = dir.path().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        let codex_bin = bin_dir.join("codex");
+        write_executable(&codex_bin);
+        // Un `codex` non executable (ou un dossier) plus tot dans le PATH est ignore.
+        let shadow_dir = dir.path().join("shadow");
+        fs::create_dir_all(&shadow_dir).unwrap();
+        fs::write(shadow_dir.join("codex"), "").unwrap();
+        let folder_dir = dir.path().join("folder");
+        fs::create_dir_all(folder_dir.join("codex")).unwrap();
+        let mut env_map = HashMap::new();
+        env_map.insert(
+            "PATH".to_string(),
+            format!(
+                "/nonexistent:{}:{}:{}",
+                shadow_dir.display(),
+                folder_dir.display(),
+                bin_dir.display()
+            ),
+        );
+        let argv = codex_base_argv_with_os(None, Some(&env_map), false);
+        assert_eq!(argv, [codex_bin.to_string_lossy().to_string()]);
+
+        env_map.insert("PATH".to_string(), "/nonexistent".to_string());
+        assert_eq!(
+            codex_base_argv_with_os(None, Some(&env_map), false),
+            ["codex"]
+        );
+    }
+
     #[test]
     fn codex_exec_argv_rejecte_vide() {
         assert!(codex_exec_argv(" ", None, None, false, None).is_err());
@@ -1385,8 +2351,7 @@ mod tests {
             let node_bin = bin_dir.join("node.exe");
             fs::write(&node_bin, "").unwrap();
         } else {
-            let codex_bin = bin_dir.join("codex");
-            fs::write(&codex_bin, "").unwrap();
+            write_executable(&bin_dir.join("codex"));
         }
         assert!(codex_cli_available(None, Some(&env_map)));
     }
@@ -1424,6 +2389,36 @@ mod tests {
         assert_eq!(node_executable(root, None).unwrap(), node_path);
     }
 
+    #[test]
+    fn python_executable_prefers_portable() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let bin_dir = root.join("bin");
+        let host = if is_windows() {
+            bin_dir.join("python.exe")
+        } else {
+            bin_dir.join("python3")
+        };
+        fs::create_dir_all(&bin_dir).unwrap();
+        write_executable(&host);
+        let mut env_map = HashMap::new();
+        env_map.insert("PATH".to_string(), bin_dir.to_string_lossy().to_string());
+
+        assert!(portable_python(root).is_none());
+        let resolved = python_executable(root, Some(&env_map)).unwrap();
+        assert_eq!(
+            resolved.to_string_lossy().to_lowercase(),
+            host.to_string_lossy().to_lowercase()
+        );
+
+        let python_path = create_portable_python(root);
+        assert_eq!(portable_python(root).unwrap(), python_path);
+        assert_eq!(
+            python_executable(root, Some(&env_map)).unwrap(),
+            python_path
+        );
+    }
+
     #[test]
     fn node_executable_ignore_hote_par_defaut() {
         let dir = TempDir::new().unwrap();
@@ -1477,11 +2472,89 @@ mod tests {
         let node_path = create_portable_node(root);
         let npm_path = create_npm_cli(&node_path);
         let prefix = codex_install_prefix(root);
-        let argv = codex_install_argv(root, &prefix, "@openai/codex").unwrap();
+        let argv = codex_install_argv(root, &prefix, "@openai/codex", NpmNetwork::Online).unwrap();
         assert!(argv.contains(&node_path.to_string_lossy().to_string()));
         assert!(argv.contains(&npm_path.to_string_lossy().to_string()));
         assert!(argv.contains(&"--prefix".to_string()));
         assert!(argv.contains(&prefix.to_string_lossy().to_string()));
+        assert!(!argv.contains(&"--offline".to_string()));
+        let argv = codex_install_argv(root, &prefix, "@openai/codex", NpmNetwork::Offline).unwrap();
+        assert_eq!(&argv[argv.len() - 2..], ["--offline", "@openai/codex"]);
+    }
+
+    #[test]
+    fn source_codex_locale_tgz_ou_dossier() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        assert_eq!(codex_npm_source(root, " "), Ok(None));
+        let missing = codex_npm_source(root, "tools/codex.tgz").unwrap_err();
+        assert!(missing.contains("introuvable"));
+
+        let archive = root.join("tools").join("openai-codex-1.0.tgz");
+        fs::create_dir_all(archive.parent().unwrap()).unwrap();
+        fs::write(&archive, "").unwrap();
+        assert_eq!(
+            codex_npm_source(root, "tools/openai-codex-1.0.tgz"),
+            Ok(Some(archive.clone()))
+        );
+        assert!(codex_npm_source(root, "tools").is_err());
+
+        let package = root.join("tools").join("codex");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(package.join("package.json"), "{}").unwrap();
+        let source = codex_npm_source(root, &package.to_string_lossy()).unwrap();
+        assert_eq!(source.as_deref(), Some(package.as_path()));
+
+        let node_path = create_portable_node(root);
+        create_npm_cli(&node_path);
+        let prefix = codex_install_prefix(root);
+        let package_arg = package.to_string_lossy().to_string();
+        let argv =
+            codex_install_argv(root, &prefix, &package_arg, NpmNetwork::PreferOffline).unwrap();
+        assert_eq!(
+            &argv[argv.len() - 3..],
+            ["--prefer-offline", "--install-links", package_arg.as_str()]
+        );
+    }
+
+    #[test]
+    fn version_node_et_npm_avec_minimum() {
+        assert_eq!(
+            parse_node_version("v20.11.1\n"),
+            Some("20.11.1".to_string())
+        );
+        assert_eq!(parse_node_version("10.2.4"), Some("10.2.4".to_string()));
+        assert_eq!(parse_node_version("npm WARN config"), None);
+        assert_eq!(parse_node_version("v21"), None);
+
+        assert_eq!(node_version_warning(NodeTool::Node, "20.11.1"), None);
+        assert_eq!(node_version_warning(NodeTool::Node, "18.0.0"), None);
+        let warning = node_version_warning(NodeTool::Node, "16.20.2").unwrap();
+        assert!(warning.contains("node 16.20.2 trop ancien"));
+        assert!(warning.contains(">= 18.0"));
+        assert!(node_version_warning(NodeTool::Npm, "7.24.0").is_some());
+        assert_eq!(node_version_warning(NodeTool::Npm, "10.0.0-pre"), None);
+    }
+
+    #[test]
+    fn sondes_version_node_et_npm_portables() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let node_path = create_portable_node(root);
+        let node = node_path.to_string_lossy().to_string();
+        let probes = node_version_argvs(root);
+        assert_eq!(
+            probes,
+            [(NodeTool::Node, vec![node.clone(), "--version".into()])]
+        );
+
+        let npm_path = create_npm_cli(&node_path);
+        let npm = npm_path.to_string_lossy().to_string();
+        let probes = node_version_argvs(root);
+        assert_eq!(
+            probes[1],
+            (NodeTool::Npm, vec![node, npm, "--version".into()])
+        );
     }
 
     #[test]
@@ -1489,7 +2562,37 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let root = dir.path();
         let prefix = codex_install_prefix(root);
-        assert!(codex_install_argv(root, &prefix, " ").is_err());
+        assert!(codex_install_argv(root, &prefix, " ", NpmNetwork::Online).is_err());
+    }
+
+    #[test]
+    fn progression_pip_et_npm() {
+        assert_eq!(
+            parse_install_progress("Collecting ruff==0.5.0 (from -r requirements.txt)").as_deref(),
+            Some("pip: collecte ruff==0.5.0")
+        );
+        assert_eq!(
+            parse_install_progress(
+                "  Downloading https://files.example/ruff-0.5.0-py3-none-any.whl (10.2 MB)"
+            )
+            .as_deref(),
+            Some("pip: telechargement ruff-0.5.0-py3-none-any.whl")
+        );
+        assert_eq!(
+            parse_install_progress("Successfully installed black-24.1 ruff-0.5.0").as_deref(),
+            Some("pip: 2 paquet(s) installe(s)")
+        );
+        assert_eq!(
+            parse_install_progress("added 12 packages, and audited 13 packages in 3s").as_deref(),
+            Some("npm: 12 paquet(s) ajoute(s)")
+        );
+        assert_eq!(
+            parse_install_progress("up to date, audited 2 packages in 1s").as_deref(),
+            Some("npm: deja a jour")
+        );
+        assert_eq!(parse_install_progress("added nothing"), None);
+        assert_eq!(parse_install_progress("npm WARN deprecated"), None);
+        assert_eq!(parse_install_progress(""), None);
     }
 
     #[test]
@@ -1498,6 +2601,14 @@ mod tests {
         assert_eq!(tools, vec!["ruff", "black", "mypy", "pytest"]);
     }
 
+    #[test]
+    fn tool_command_name_retire_version_et_extras() {
+        assert_eq!(tool_command_name("ruff"), "ruff");
+        assert_eq!(tool_command_name("ruff==0.5.0"), "ruff");
+        assert_eq!(tool_command_name("black[jupyter]>=24"), "black");
+        assert_eq!(tool_command_name("mypy~=1.10"), "mypy");
+    }
+
     #[test]
     fn parse_codex_sandbox_mode_ok() {
         assert_eq!(
@@ -1625,11 +2736,99 @@ mod tests {
         assert!(pip_install_argv(prefix, &[], None, false).is_err());
     }
 
+    #[test]
+    fn pip_install_requirements_argv_offline() {
+        let prefix = Path::new("/tmp/usbide/.usbide/tools");
+        let req = Path::new("/tmp/usbide/requirements.txt");
+        let wheelhouse = Path::new("/tmp/usbide/tools/wheels");
+        let argv = pip_install_requirements_argv(prefix, req, Some(wheelhouse), true).unwrap();
+        assert!(argv.contains(&"--no-index".to_string()));
+        assert!(argv.contains(&wheelhouse.to_string_lossy().to_string()));
+        assert_eq!(
+            argv[argv.len() - 2..],
+            ["-r".to_string(), req.to_string_lossy().to_string()]
+        );
+        assert!(pip_install_requirements_argv(prefix, Path::new(""), None, false).is_err());
+    }
+
+    #[test]
+    fn format_argv_selon_formateur() {
+        let script = Path::new("src").join("main.py");
+        assert_eq!(
+            format_argv(Formatter::Black, &script).unwrap(),
+            vec!["black".to_string(), path_for_cmd(&script)]
+        );
+        assert_eq!(
+            format_argv(Formatter::Ruff, &script).unwrap()[..2],
+            ["ruff".to_string(), "format".to_string()]
+        );
+        assert!(format_argv(Formatter::Black, Path::new("")).is_err());
+        assert_eq!(parse_formatter(" Ruff format "), Some(Formatter::Ruff));
+        assert_eq!(parse_formatter("yapf"), None);
+    }
+
+    #[test]
+    fn lint_argv_ruff_et_mypy() {
+        let script = Path::new("src").join("main.py");
+        let ruff = lint_argv(Linter::Ruff, &script).unwrap();
+        assert_eq!(ruff[..2], ["ruff".to_string(), "check".to_string()]);
+        assert!(ruff.contains(&"concise".to_string()));
+        assert_eq!(ruff.last(), Some(&path_for_cmd(&script)));
+        let mypy = lint_argv(Linter::Mypy, &script).unwrap();
+        assert_eq!(mypy[0], "mypy");
+        assert!(mypy.contains(&"--show-column-numbers".to_string()));
+        assert!(lint_argv(Linter::Ruff, Path::new("")).is_err());
+    }
+
+    #[test]
+    fn pytest_argv_et_relance_des_echecs() {
+        let args = parse_pytest_args(Some(" -x  tests/ "));
+        assert_eq!(args, vec!["-x".to_string(), "tests/".to_string()]);
+        assert!(parse_pytest_args(None).is_empty());
+        assert_eq!(pytest_argv(&args, false), vec!["pytest", "-x", "tests/"]);
+        assert_eq!(pytest_argv(&[], true), vec!["pytest", "--lf"]);
+        assert_eq!(
+            pytest_argv(&["--lf".to_string()], true),
+            vec!["pytest", "--lf"]
+        );
+    }
+
+    #[test]
+    fn resume_pytest() {
+        let summary =
+            parse_pytest_summary("===== 1 failed, 3 passed, 2 skipped, 1 warning in 0.12s =====")
+                .unwrap();
+        assert_eq!(
+            summary,
+            PytestSummary {
+                passed: 3,
+                failed: 1,
+                errors: 0,
+                skipped: 2,
+            }
+        );
+        assert!(!summary.is_success());
+        assert_eq!(summary.label(), "Tests: 3 ok, 1 echec(s), 2 ignore(s)");
+        let quiet = parse_pytest_summary("\u{1b}[32m5 passed\u{1b}[0m in 1.02s (0:00:01)").unwrap();
+        assert_eq!(quiet.passed, 5);
+        assert!(quiet.is_success());
+        assert_eq!(
+            parse_pytest_summary("=== 2 errors in 0.30s ===").map(|s| s.errors),
+            Some(2)
+        );
+        assert_eq!(
+            parse_pytest_summary("==== no tests ran in 0.01s ===="),
+            Some(PytestSummary::default())
+        );
+        assert!(parse_pytest_summary("tests/test_a.py::test_x PASSED").is_none());
+        assert!(parse_pytest_summary("assert 1 in 2 things").is_none());
+    }
+
     #[test]
     fn pyinstaller_build_argv_ok() {
         let script = Path::new("/tmp/usbide/app.py");
         let dist_dir = Path::new("/tmp/usbide/dist");
-        let argv = pyinstaller_build_argv(script, dist_dir, true, None, None).unwrap();
+        let argv = pyinstaller_build_argv(script, dist_dir, true, None, None, None, None).unwrap();
         assert!(argv.contains(&script.to_string_lossy().to_string()));
         assert!(argv.contains(&dist_dir.to_string_lossy().to_string()));
         assert!(argv.contains(&"--onefile".to_string()));
@@ -1640,24 +2839,74 @@ mod tests {
     fn pyinstaller_build_argv_onedir_par_defaut() {
         let script = Path::new("/tmp/usbide/app.py");
         let dist_dir = Path::new("/tmp/usbide/dist");
-        let argv = pyinstaller_build_argv(script, dist_dir, false, None, None).unwrap();
+        let argv = pyinstaller_build_argv(script, dist_dir, false, None, None, None, None).unwrap();
         assert!(argv.contains(&"--onedir".to_string()));
     }
 
+    #[test]
+    fn pyinstaller_output_selon_mode() {
+        let script = Path::new("/tmp/usbide/app.py");
+        let dist_dir = Path::new("/tmp/usbide/dist");
+        assert_eq!(
+            pyinstaller_output_path(script, dist_dir, false, None),
+            dist_dir.join("app")
+        );
+        let onefile = pyinstaller_output_path(script, dist_dir, true, None);
+        let expected = if cfg!(windows) { "app.exe" } else { "app" };
+        assert_eq!(onefile, dist_dir.join(expected));
+    }
+
     #[test]
     fn pyinstaller_build_argv_work_spec() {
         let script = Path::new("/tmp/usbide/app.py");
         let dist_dir = Path::new("/tmp/usbide/dist");
         let work_dir = Path::new("/tmp/usbide/build");
         let spec_dir = Path::new("/tmp/usbide");
-        let argv = pyinstaller_build_argv(script, dist_dir, false, Some(work_dir), Some(spec_dir))
-            .unwrap();
+        let argv = pyinstaller_build_argv(
+            script,
+            dist_dir,
+            false,
+            Some(work_dir),
+            Some(spec_dir),
+            None,
+            None,
+        )
+        .unwrap();
         assert!(argv.contains(&"--workpath".to_string()));
         assert!(argv.contains(&work_dir.to_string_lossy().to_string()));
         assert!(argv.contains(&"--specpath".to_string()));
         assert!(argv.contains(&spec_dir.to_string_lossy().to_string()));
     }
 
+    #[test]
+    fn pyinstaller_build_argv_icone_et_nom() {
+        let script = Path::new("/tmp/usbide/app.py");
+        let dist_dir = Path::new("/tmp/usbide/dist");
+        let icon = Path::new("/tmp/usbide/app.ico");
+        let argv = pyinstaller_build_argv(
+            script,
+            dist_dir,
+            false,
+            None,
+            None,
+            Some(icon),
+            Some(" MonApp "),
+        )
+        .unwrap();
+        let pos = argv.iter().position(|arg| arg == "--icon").unwrap();
+        assert_eq!(argv[pos + 1], icon.to_string_lossy());
+        let pos = argv.iter().position(|arg| arg == "--name").unwrap();
+        assert_eq!(argv[pos + 1], "MonApp");
+        assert_eq!(
+            pyinstaller_output_path(script, dist_dir, false, Some("MonApp")),
+            dist_dir.join("MonApp")
+        );
+
+        let argv =
+            pyinstaller_build_argv(script, dist_dir, false, None, None, None, Some("")).unwrap();
+        assert!(!argv.contains(&"--name".to_string()));
+    }
+
     #[test]
     fn pyinstaller_build_argv_rejecte_vide() {
         assert!(
@@ -1666,6 +2915,8 @@ mod tests {
                 Path::new("/tmp/usbide/dist"),
                 false,
                 None,
+                None,
+                None,
                 None
             )
             .is_err()
@@ -1683,6 +2934,40 @@ mod tests {
         assert_eq!(extract_status_code("aucun code ici"), None);
     }
 
+    #[test]
+    fn prompt_avec_fichier_en_contexte() {
+        let prompt = codex_context_prompt("fichier src/a.py", "x = 1", " Que fait ce code ? ");
+        assert_eq!(
+            prompt,
+            "Contexte: fichier src/a.py\n----- debut du contexte -----\nx = 1\n----- fin du contexte -----\nQue fait ce code ?"
+        );
+        assert_eq!(
+            codex_prompt_display(&prompt),
+            "[fichier src/a.py] Que fait ce code ?"
+        );
+        assert_eq!(codex_prompt_display("bonjour"), "bonjour");
+
+        let big = "é".repeat(CODEX_CONTEXT_MAX_CHARS + 5);
+        let prompt = codex_context_prompt("fichier gros.txt", &big, "Resume");
+        assert!(prompt.contains(&format!(
+            "tronque : {CODEX_CONTEXT_MAX_CHARS} caracteres sur {}]",
+            CODEX_CONTEXT_MAX_CHARS + 5
+        )));
+        assert!(prompt.ends_with("----- fin du contexte -----\nResume"));
+    }
+
+    #[test]
+    fn relance_apres_rate_limit_ou_erreur_serveur() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(401));
+        assert!(!is_retryable_status(600));
+        assert_eq!(codex_retry_delay(1), Duration::from_secs(5));
+        assert_eq!(codex_retry_delay(2), Duration::from_secs(10));
+        assert_eq!(codex_retry_delay(3), Duration::from_secs(20));
+        assert_eq!(codex_retry_delay(10), CODEX_RETRY_MAX_DELAY);
+    }
+
     #[test]
     fn codex_hint_for_status_ok() {
         assert!(codex_hint_for_status(401).unwrap().contains("401"));
@@ -1756,6 +3041,86 @@ mod tests {
         );
     }
 
+    fn items_of(line: &str) -> Vec<DisplayItem> {
+        extract_display_items(&serde_json::from_str::<Value>(line).unwrap())
+    }
+
+    #[test]
+    fn codex_extract_display_items_reflexion() {
+        let lines = [
+            r#"{"type":"item.completed","item":{"id":"item_0","type":"reasoning","text":"**Lecture** du fichier main.py"}}"#,
+            r#"{"type":"response_item","payload":{"type":"reasoning","summary":[{"type":"summary_text","text":"**Lecture** du fichier main.py"}],"content":null,"encrypted_content":"gAAA"}}"#,
+            r#"{"type":"event_msg","payload":{"type":"agent_reasoning","text":"**Lecture** du fichier main.py"}}"#,
+            r#"{"type":"response.reasoning","text":"**Lecture** du fichier main.py"}"#,
+        ];
+        for line in lines {
+            assert_eq!(
+                items_of(line),
+                vec![DisplayItem {
+                    kind: DisplayKind::Reasoning,
+                    message: "reflexion: **Lecture** du fichier main.py".to_string(),
+                }],
+                "{line}"
+            );
+        }
+        // Reflexion chiffree sans resume : rien a afficher.
+        assert!(items_of(r#"{"type":"response_item","payload":{"type":"reasoning","summary":[],"encrypted_content":"gAAA"}}"#).is_empty());
+    }
+
+    #[test]
+    fn codex_extract_display_items_resultat_outil() {
+        let items = items_of(
+            r#"{"type":"item.completed","item":{"id":"item_1","type":"command_execution","command":"bash -lc ls","aggregated_output":"a.py\nb.py\n","exit_code":0,"status":"completed"}}"#,
+        );
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].kind, DisplayKind::ToolResult);
+        assert_eq!(items[0].message, "resultat (rc=0): a.py | b.py");
+
+        // Commande en cours : le resultat viendra avec item.completed.
+        assert!(items_of(r#"{"type":"item.started","item":{"type":"command_execution","command":"ls","aggregated_output":"","status":"in_progress"}}"#).is_empty());
+
+        let items = items_of(
+            r#"{"type":"response_item","payload":{"type":"function_call_output","call_id":"call_1","output":"{\"output\":\"1\\n2\\n3\\n4\\n5\\n\",\"metadata\":{\"exit_code\":1}}"}}"#,
+        );
+        assert_eq!(items[0].message, "resultat (rc=1): 1 | 2 | 3 (+2 lignes)");
+
+        let items = items_of(
+            r#"{"type":"event_msg","payload":{"type":"exec_command_end","call_id":"call_1","stdout":"","stderr":"introuvable\n","exit_code":127}}"#,
+        );
+        assert_eq!(items[0].message, "resultat (rc=127): introuvable");
+    }
+
+    #[test]
+    fn codex_niveaux_de_vue() {
+        let mut view = CodexView::Compact;
+        for expected in [CodexView::Normal, CodexView::Raw, CodexView::Compact] {
+            view = view.next();
+            assert_eq!(view, expected);
+            assert_eq!(parse_codex_view(view.as_str()), Some(view));
+        }
+        assert_eq!(parse_codex_view(" Brut "), Some(CodexView::Raw));
+        assert_eq!(parse_codex_view("bavard"), None);
+
+        assert!(CodexView::Compact.shows(&DisplayKind::Assistant));
+        assert!(!CodexView::Compact.shows(&DisplayKind::Action));
+        assert!(!CodexView::Compact.shows(&DisplayKind::Reasoning));
+        assert!(CodexView::Normal.shows(&DisplayKind::ToolResult));
+        assert!(!CodexView::Raw.shows(&DisplayKind::Assistant));
+    }
+
+    #[test]
+    fn codex_resume_sortie_outil() {
+        assert_eq!(
+            summarize_tool_output("\n  \n", Some(0)),
+            "resultat (rc=0): (aucune sortie)"
+        );
+        assert_eq!(summarize_tool_output("ok", None), "resultat: ok");
+        let long = "x".repeat(200);
+        let resume = summarize_tool_output(&long, None);
+        assert!(resume.ends_with("..."));
+        assert_eq!(resume.chars().count(), "resultat: ".len() + 120 + 3);
+    }
+
     #[test]
     fn codex_extract_text_filtre_types() {
         let content: Value = serde_json::json!([
@@ -1792,4 +3157,31 @@ mod tests {
         let lines = wrap_text(texte, 20);
         assert!(lines.iter().any(|line| line.contains("print('x' * 50)")));
     }
+
+    #[test]
+    fn barriere_et_extension_des_blocs_de_code() {
+        assert_eq!(code_fence("```python"), Some("python"));
+        assert_eq!(code_fence("  ``` rust title=main.rs"), Some("rust"));
+        assert_eq!(code_fence("```"), Some(""));
+        assert_eq!(code_fence("print('```')"), None);
+        assert_eq!(code_block_extension("Python"), "py");
+        assert_eq!(code_block_extension("sh"), "sh");
+        assert_eq!(code_block_extension(""), "txt");
+        assert_eq!(code_block_extension("brainfuck"), "txt");
+    }
+
+    #[test]
+    fn wrap_text_blocks_marque_le_code() {
+        let texte =
+            "Voici :\n```python\nif x:\n\treturn 'un texte bien trop long pour la ligne'\n```\nFin";
+        let lines = wrap_text_blocks(texte, 20);
+        assert_eq!(lines[0], ("Voici :".to_string(), false));
+        assert_eq!(lines[1], ("```python".to_string(), true));
+        assert_eq!(lines[2], ("if x:".to_string(), true));
+        // Coupure a la largeur, sans reflow des mots, tabulation developpee.
+        assert_eq!(lines[3], ("    return 'un texte".to_string(), true));
+        assert_eq!(lines[4], (" bien trop long pour".to_string(), true));
+        assert_eq!(lines[6], ("```".to_string(), true));
+        assert_eq!(lines[7], ("Fin".to_string(), false));
+    }
 }
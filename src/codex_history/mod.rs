@@ -0,0 +1,150 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use serde_json::{Value, json};
+
+use crate::workspace::WorkspacePaths;
+
+/// Nombre d'echanges precedents reinjectes quand la session est continuee.
+pub const CODEX_CONTEXT_TURNS: usize = 5;
+
+/// Taille max (caracteres) d'une reponse reinjectee, pour garder un prompt raisonnable.
+const CONTEXT_RESPONSE_LIMIT: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodexTurn {
+    pub prompt: String,
+    pub response: String,
+}
+
+/// Historique des echanges Codex, persiste dans `.usbide/codex_history.jsonl`.
+#[derive(Debug, Clone, Default)]
+pub struct CodexHistory {
+    pub turns: Vec<CodexTurn>,
+}
+
+impl CodexHistory {
+    /// Charge l'historique. Fichier absent : vide ; lignes illisibles ignorees.
+    pub fn load(workspace: &WorkspacePaths) -> Self {
+        let Ok(raw) = fs::read_to_string(workspace.codex_history_path()) else {
+            return Self::default();
+        };
+        let turns = raw
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter_map(|value| {
+                Some(CodexTurn {
+                    prompt: value.get("prompt")?.as_str()?.to_string(),
+                    response: value.get("response")?.as_str()?.to_string(),
+                })
+            })
+            .collect();
+        Self { turns }
+    }
+
+    /// Ajoute l'echange en memoire et en fin de fichier (une ligne JSON).
+    pub fn push(&mut self, workspace: &WorkspacePaths, turn: CodexTurn) -> std::io::Result<()> {
+        let path = workspace.codex_history_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let line = json!({
+            "ts": chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "prompt": turn.prompt,
+            "response": turn.response,
+        });
+        self.turns.push(turn);
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{line}")
+    }
+
+    /// Prompts distincts, du plus recent au plus ancien (pour les re-envoyer).
+    pub fn recent_prompts(&self) -> Vec<&str> {
+        let mut prompts: Vec<&str> = Vec::new();
+        for turn in self.turns.iter().rev() {
+            if !prompts.contains(&turn.prompt.as_str()) {
+                prompts.push(&turn.prompt);
+            }
+        }
+        prompts
+    }
+
+    /// Prompt enrichi des derniers echanges, pour que Codex ait le contexte de la session.
+    pub fn context_prompt(&self, prompt: &str) -> String {
+        let start = self.turns.len().saturating_sub(CODEX_CONTEXT_TURNS);
+        let recent = &self.turns[start..];
+        if recent.is_empty() {
+            return prompt.to_string();
+        }
+        let mut text = String::from("Historique de la conversation precedente (contexte) :\n");
+        for turn in recent {
+            let response: String = turn.response.chars().take(CONTEXT_RESPONSE_LIMIT).collect();
+            text.push_str(&format!(
+                "\n[Utilisateur]\n{}\n\n[Assistant]\n{}\n",
+                turn.prompt.trim(),
+                response.trim()
+            ));
+        }
+        text.push_str(&format!("\nNouvelle demande :\n{prompt}"));
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn turn(prompt: &str, response: &str) -> CodexTurn {
+        CodexTurn {
+            prompt: prompt.to_string(),
+            response: response.to_string(),
+        }
+    }
+
+    #[test]
+    fn historique_persiste_en_jsonl() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let mut history = CodexHistory::default();
+        history.push(&workspace, turn("bonjour", "salut")).unwrap();
+        history
+            .push(&workspace, turn("et ensuite ?", "ligne1\nligne2"))
+            .unwrap();
+        fs::write(
+            workspace.codex_history_path(),
+            format!(
+                "{}pas du json\n",
+                fs::read_to_string(workspace.codex_history_path()).unwrap()
+            ),
+        )
+        .unwrap();
+
+        let loaded = CodexHistory::load(&workspace);
+        assert_eq!(loaded.turns, history.turns);
+    }
+
+    #[test]
+    fn contexte_reinjecte_les_derniers_echanges() {
+        let mut history = CodexHistory::default();
+        assert_eq!(history.context_prompt("question"), "question");
+
+        for idx in 0..(CODEX_CONTEXT_TURNS + 1) {
+            history
+                .turns
+                .push(turn(&format!("q{idx}"), &format!("r{idx}")));
+        }
+        let prompt = history.context_prompt("question");
+        assert!(!prompt.contains("q0\n"));
+        assert!(prompt.contains(&format!("r{CODEX_CONTEXT_TURNS}")));
+        assert!(prompt.ends_with("Nouvelle demande :\nquestion"));
+    }
+
+    #[test]
+    fn prompts_recents_sans_doublon() {
+        let history = CodexHistory {
+            turns: vec![turn("a", "1"), turn("b", "2"), turn("a", "3")],
+        };
+        assert_eq!(history.recent_prompts(), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::ansi::strip_ansi;
+use crate::codex::Linter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl DiagnosticSeverity {
+    pub fn label(self) -> &'static str {
+        match self {
+            DiagnosticSeverity::Error => "erreur",
+            DiagnosticSeverity::Warning => "avertissement",
+            DiagnosticSeverity::Note => "note",
+        }
+    }
+}
+
+/// Probleme signale par un linter ; ligne et colonne 1-based comme dans la sortie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: Option<usize>,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub source: Linter,
+}
+
+impl Diagnostic {
+    /// `main.py:3:8 [ruff] F401 ...`, chemin relatif a `root` si possible.
+    pub fn label(&self, root: &Path) -> String {
+        let path = self.path.strip_prefix(root).unwrap_or(&self.path);
+        let position = match self.column {
+            Some(column) => format!("{}:{column}", self.line),
+            None => self.line.to_string(),
+        };
+        format!(
+            "{}:{position} [{}] {}",
+            path.display(),
+            self.source.as_str(),
+            self.message
+        )
+    }
+}
+
+/// Lit une ligne `chemin:ligne[:colonne]: message` (ruff concise, mypy) ; autre chose : None.
+/// Un chemin relatif est rattache a `cwd` (dossier de lancement du linter).
+pub fn parse_diagnostic(line: &str, source: Linter, cwd: &Path) -> Option<Diagnostic> {
+    let line = strip_ansi(line);
+    let line = line.trim_end();
+    // Le chemin peut contenir ':' (lecteur Windows) : premier ':' suivi d'un numero de ligne.
+    for (idx, _) in line.match_indices(':').filter(|(idx, _)| *idx > 0) {
+        let Some((number, column, rest)) = parse_position(&line[idx + 1..]) else {
+            continue;
+        };
+        let message = rest.trim();
+        if message.is_empty() || number == 0 {
+            continue;
+        }
+        let (severity, message) = severity_of(message, source);
+        let path = PathBuf::from(line[..idx].trim());
+        let path = if path.is_absolute() {
+            path
+        } else {
+            cwd.join(path)
+        };
+        return Some(Diagnostic {
+            path,
+            line: number,
+            column,
+            severity,
+            message: message.to_string(),
+            source,
+        });
+    }
+    None
+}
+
+/// `12:5: reste` ou `12: reste`.
+fn parse_position(text: &str) -> Option<(usize, Option<usize>, &str)> {
+    let (line, rest) = split_number(text)?;
+    let rest = rest.strip_prefix(':')?;
+    match split_number(rest) {
+        Some((column, after)) if after.starts_with(':') => Some((line, Some(column), &after[1..])),
+        _ => Some((line, None, rest)),
+    }
+}
+
+fn split_number(text: &str) -> Option<(usize, &str)> {
+    let end = text
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(text.len());
+    let number = text[..end].parse().ok()?;
+    Some((number, &text[end..]))
+}
+
+fn severity_of(message: &str, source: Linter) -> (DiagnosticSeverity, &str) {
+    for (prefix, severity) in [
+        ("error:", DiagnosticSeverity::Error),
+        ("warning:", DiagnosticSeverity::Warning),
+        ("note:", DiagnosticSeverity::Note),
+    ] {
+        if let Some(rest) = message.strip_prefix(prefix) {
+            return (severity, rest.trim_start());
+        }
+    }
+    let syntax = message.starts_with("SyntaxError") || message.starts_with("invalid-syntax");
+    match source {
+        Linter::Ruff if !syntax => (DiagnosticSeverity::Warning, message),
+        _ => (DiagnosticSeverity::Error, message),
+    }
+}
+
+/// Diagnostics du dernier lint, par fichier, tries par position.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    by_path: BTreeMap<PathBuf, Vec<Diagnostic>>,
+}
+
+impl Diagnostics {
+    /// Oublie les diagnostics d'un fichier (avant de le re-linter).
+    pub fn clear_path(&mut self, path: &Path) {
+        self.by_path.remove(path);
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        let list = self.by_path.entry(diagnostic.path.clone()).or_default();
+        if list.contains(&diagnostic) {
+            return;
+        }
+        let key = (diagnostic.line, diagnostic.column);
+        let pos = list.partition_point(|d| (d.line, d.column) <= key);
+        list.insert(pos, diagnostic);
+    }
+
+    pub fn for_path(&self, path: &Path) -> &[Diagnostic] {
+        self.by_path.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.by_path.values().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_path.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_path.values().all(Vec::is_empty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sortie_ruff_concise() {
+        let cwd = Path::new("/ws");
+        let diag = parse_diagnostic(
+            "src/main.py:3:8: F401 [*] `os` imported but unused",
+            Linter::Ruff,
+            cwd,
+        )
+        .unwrap();
+        assert_eq!(diag.path, cwd.join("src/main.py"));
+        assert_eq!((diag.line, diag.column), (3, Some(8)));
+        assert_eq!(diag.severity, DiagnosticSeverity::Warning);
+        assert_eq!(diag.message, "F401 [*] `os` imported but unused");
+        let syntax = parse_diagnostic(
+            "\u{1b}[1mmain.py\u{1b}[0m:1:5: SyntaxError: Expected an expression",
+            Linter::Ruff,
+            cwd,
+        )
+        .unwrap();
+        assert_eq!(syntax.severity, DiagnosticSeverity::Error);
+        assert!(parse_diagnostic("Found 2 errors.", Linter::Ruff, cwd).is_none());
+        assert!(
+            parse_diagnostic("[*] 1 fixable with the `--fix` option.", Linter::Ruff, cwd).is_none()
+        );
+    }
+
+    #[test]
+    fn sortie_mypy_avec_ou_sans_colonne() {
+        let cwd = Path::new("/ws");
+        let diag = parse_diagnostic(
+            "main.py:12:5: error: Incompatible types in assignment  [assignment]",
+            Linter::Mypy,
+            cwd,
+        )
+        .unwrap();
+        assert_eq!((diag.line, diag.column), (12, Some(5)));
+        assert_eq!(diag.severity, DiagnosticSeverity::Error);
+        assert_eq!(
+            diag.message,
+            "Incompatible types in assignment  [assignment]"
+        );
+        let note = parse_diagnostic("main.py:4: note: See docs", Linter::Mypy, cwd).unwrap();
+        assert_eq!((note.line, note.column), (4, None));
+        assert_eq!(note.severity, DiagnosticSeverity::Note);
+        assert!(parse_diagnostic("Success: no issues found", Linter::Mypy, cwd).is_none());
+    }
+
+    #[test]
+    fn chemin_windows_avec_lecteur() {
+        let diag = parse_diagnostic(
+            r"C:\ws\main.py:7:1: E999 oops",
+            Linter::Ruff,
+            Path::new("/ws"),
+        )
+        .unwrap();
+        assert!(diag.path.to_string_lossy().ends_with(r"C:\ws\main.py"));
+        assert_eq!((diag.line, diag.column), (7, Some(1)));
+    }
+
+    #[test]
+    fn stockage_par_fichier_trie_et_vide_au_relint() {
+        let cwd = Path::new("/ws");
+        let mut diags = Diagnostics::default();
+        for line in [
+            "a.py:9:1: E1 neuf",
+            "a.py:2:4: E2 deux",
+            "b.py:1: error: autre",
+            "a.py:2:4: E2 deux",
+        ] {
+            let source = if line.contains("error:") {
+                Linter::Mypy
+            } else {
+                Linter::Ruff
+            };
+            diags.push(parse_diagnostic(line, source, cwd).unwrap());
+        }
+        assert_eq!(diags.len(), 3);
+        let lines: Vec<usize> = diags
+            .for_path(&cwd.join("a.py"))
+            .iter()
+            .map(|d| d.line)
+            .collect();
+        assert_eq!(lines, vec![2, 9]);
+        assert_eq!(
+            diags.iter().next().unwrap().label(cwd),
+            "a.py:2:4 [ruff] E2 deux"
+        );
+        diags.clear_path(&cwd.join("a.py"));
+        assert_eq!(diags.len(), 1);
+        assert!(diags.for_path(&cwd.join("a.py")).is_empty());
+        diags.clear_path(&cwd.join("b.py"));
+        assert!(diags.is_empty());
+    }
+}
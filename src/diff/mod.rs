@@ -0,0 +1,444 @@
+/// Lignes de contexte autour de chaque changement.
+pub const DIFF_CONTEXT: usize = 3;
+
+/// Au-dela (lignes anciennes x nouvelles, hors debut/fin communs), le bloc du milieu est
+/// montre comme entierement remplace plutot que d'allouer une table LCS trop grosse.
+const DIFF_MAX_CELLS: usize = 2_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffRowKind {
+    Hunk,
+    Context,
+    Added,
+    Removed,
+}
+
+/// Ligne du diff unifie (`@@ ... @@`, ` contexte`, `+ajout`, `-suppression`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRow {
+    pub kind: DiffRowKind,
+    pub text: String,
+}
+
+impl DiffRow {
+    /// Ligne coloree (SGR) pour les journaux, comme `git diff --color`.
+    pub fn ansi(&self) -> String {
+        let code = match self.kind {
+            DiffRowKind::Hunk => "36",
+            DiffRowKind::Added => "32",
+            DiffRowKind::Removed => "31",
+            DiffRowKind::Context => return self.text.clone(),
+        };
+        format!("\u{1b}[{code}m{}\u{1b}[0m", self.text)
+    }
+}
+
+/// Diff unifie ligne a ligne de `old` vers `new` (fins de ligne LF/CRLF ignorees).
+/// Vide si les deux textes ont les memes lignes.
+pub fn diff_rows(old: &str, new: &str, context: usize) -> Vec<DiffRow> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old, &new);
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(..)))
+        .map(|(idx, _)| idx)
+        .collect();
+    let mut keep = vec![false; ops.len()];
+    for idx in changed {
+        let start = idx.saturating_sub(context);
+        let end = (idx + context + 1).min(ops.len());
+        keep[start..end].iter_mut().for_each(|flag| *flag = true);
+    }
+
+    let mut rows = Vec::new();
+    let (mut old_pos, mut new_pos) = (0, 0);
+    let mut idx = 0;
+    while idx < ops.len() {
+        if !keep[idx] {
+            if let DiffOp::Equal(..) = ops[idx] {
+                old_pos += 1;
+                new_pos += 1;
+            }
+            idx += 1;
+            continue;
+        }
+        let end = (idx..ops.len()).find(|&i| !keep[i]).unwrap_or(ops.len());
+        let hunk = &ops[idx..end];
+        let old_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Insert(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Delete(_)))
+            .count();
+        let start = |pos: usize, count: usize| if count > 0 { pos + 1 } else { pos };
+        rows.push(DiffRow {
+            kind: DiffRowKind::Hunk,
+            text: format!(
+                "@@ -{},{old_count} +{},{new_count} @@",
+                start(old_pos, old_count),
+                start(new_pos, new_count)
+            ),
+        });
+        for op in hunk {
+            let (kind, text) = match *op {
+                DiffOp::Equal(old_idx, _) => (DiffRowKind::Context, format!(" {}", old[old_idx])),
+                DiffOp::Delete(old_idx) => (DiffRowKind::Removed, format!("-{}", old[old_idx])),
+                DiffOp::Insert(new_idx) => (DiffRowKind::Added, format!("+{}", new[new_idx])),
+            };
+            rows.push(DiffRow { kind, text });
+        }
+        old_pos += old_count;
+        new_pos += new_count;
+        idx = end;
+    }
+    rows
+}
+
+/// Bloc `@@ -a,b +c,d @@` d'un patch : lignes attendues et lignes qui les remplacent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchHunk {
+    /// Premiere ligne attendue (1 = debut du fichier, 0 = insertion en tete).
+    pub old_start: usize,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+/// Diff unifie d'un fichier : chemin annonce par `+++` (sans `b/`) et ses hunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    pub path: Option<String>,
+    pub hunks: Vec<PatchHunk>,
+}
+
+/// Premier diff unifie d'un texte (bloc ```diff / ```patch prioritaire, sinon texte brut) ;
+/// seul le premier fichier est lu. `None` sans hunk `@@`.
+pub fn extract_unified_diff(text: &str) -> Option<FilePatch> {
+    let mut fenced = Vec::new();
+    let mut in_diff = false;
+    for line in text.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        match fence {
+            Some(lang) if !in_diff && matches!(lang.trim(), "diff" | "patch" | "udiff") => {
+                in_diff = true;
+            }
+            Some(_) if in_diff => break,
+            _ if in_diff => fenced.push(line),
+            _ => {}
+        }
+    }
+    if !fenced.is_empty()
+        && let Some(patch) = parse_unified_diff(&fenced)
+    {
+        return Some(patch);
+    }
+    parse_unified_diff(&text.lines().collect::<Vec<_>>())
+}
+
+fn parse_unified_diff(lines: &[&str]) -> Option<FilePatch> {
+    let mut path = None;
+    let mut hunks: Vec<PatchHunk> = Vec::new();
+    let mut in_hunk = false;
+    let mut idx = 0;
+    while idx < lines.len() {
+        let line = lines[idx].trim_end_matches('\r');
+        idx += 1;
+        if line.starts_with("--- ") && lines.get(idx).is_some_and(|next| next.starts_with("+++ ")) {
+            if !hunks.is_empty() {
+                break;
+            }
+            path = patch_path(&lines[idx][4..]);
+            idx += 1;
+            in_hunk = false;
+            continue;
+        }
+        if let Some(old_start) = hunk_old_start(line) {
+            hunks.push(PatchHunk {
+                old_start,
+                old: Vec::new(),
+                new: Vec::new(),
+            });
+            in_hunk = true;
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut().filter(|_| in_hunk) else {
+            continue;
+        };
+        if let Some(rest) = line.strip_prefix('+') {
+            hunk.new.push(rest.to_string());
+        } else if let Some(rest) = line.strip_prefix('-') {
+            hunk.old.push(rest.to_string());
+        } else if let Some(rest) = line.strip_prefix(' ') {
+            hunk.old.push(rest.to_string());
+            hunk.new.push(rest.to_string());
+        } else if line.is_empty() {
+            // Ligne de contexte vide dont l'espace initial a ete perdu.
+            hunk.old.push(String::new());
+            hunk.new.push(String::new());
+        } else if !line.starts_with('\\') {
+            in_hunk = false;
+        }
+    }
+    for hunk in &mut hunks {
+        // Lignes vides finales ajoutees par le bloc de code, pas par le diff.
+        while hunk.old.last().is_some_and(String::is_empty)
+            && hunk.new.last().is_some_and(String::is_empty)
+            && hunk.old.len() > 1
+        {
+            hunk.old.pop();
+            hunk.new.pop();
+        }
+    }
+    hunks.retain(|hunk| hunk.old != hunk.new);
+    (!hunks.is_empty()).then_some(FilePatch { path, hunks })
+}
+
+/// Chemin d'une ligne `+++` : prefixe `b/` et horodatage retires, `None` pour /dev/null.
+fn patch_path(raw: &str) -> Option<String> {
+    let raw = raw.split('\t').next().unwrap_or("").trim();
+    let raw = raw.strip_prefix("b/").unwrap_or(raw);
+    (!raw.is_empty() && raw != "/dev/null").then(|| raw.to_string())
+}
+
+/// Debut cote ancien d'une entete `@@ -a,b +c,d @@` (`@@` seul : inconnu, 1).
+fn hunk_old_start(line: &str) -> Option<usize> {
+    let rest = line.strip_prefix("@@")?;
+    let start = rest
+        .trim_start()
+        .strip_prefix('-')
+        .and_then(|range| range.split([',', ' ']).next())
+        .and_then(|start| start.parse().ok())
+        .unwrap_or(1);
+    Some(start)
+}
+
+/// Applique `patch` a `original` : chaque hunk doit retrouver ses lignes attendues (espaces
+/// de fin ignores), a sa position annoncee ou plus loin. Fin de ligne finale conservee.
+pub fn apply_patch(original: &str, patch: &FilePatch) -> Result<String, String> {
+    let mut lines: Vec<String> = original
+        .lines()
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .collect();
+    let same = |a: &[String], b: &[String]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.trim_end() == y.trim_end())
+    };
+    // Decalage cumule des hunks deja appliques et position minimale du suivant.
+    let mut delta: isize = 0;
+    let mut floor = 0;
+    for (number, hunk) in patch.hunks.iter().enumerate() {
+        // `@@ -N,0` (insertion pure) insere apres la ligne N, sinon le hunk commence a N.
+        let anchor = if hunk.old.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = (anchor as isize + delta).max(floor as isize);
+        let expected = (expected as usize).min(lines.len());
+        let fits = |pos: usize| {
+            pos + hunk.old.len() <= lines.len()
+                && same(&lines[pos..pos + hunk.old.len()], &hunk.old)
+        };
+        let pos = if hunk.old.is_empty() || fits(expected) {
+            Some(expected)
+        } else {
+            (floor..=lines.len().saturating_sub(hunk.old.len())).find(|&pos| fits(pos))
+        };
+        let Some(pos) = pos else {
+            return Err(format!(
+                "hunk {} (@@ -{}) ne correspond pas au fichier",
+                number + 1,
+                hunk.old_start
+            ));
+        };
+        lines.splice(pos..pos + hunk.old.len(), hunk.new.iter().cloned());
+        delta += hunk.new.len() as isize - hunk.old.len() as isize;
+        floor = pos + hunk.new.len();
+    }
+    let mut out = lines.join("\n");
+    if original.ends_with('\n') && !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Debut et fin communs retires, puis plus longue sous-sequence commune sur le milieu.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(|idx| DiffOp::Equal(idx, idx)).collect();
+    if old_mid.len().saturating_mul(new_mid.len()) > DIFF_MAX_CELLS {
+        ops.extend((0..old_mid.len()).map(|idx| DiffOp::Delete(prefix + idx)));
+        ops.extend((0..new_mid.len()).map(|idx| DiffOp::Insert(prefix + idx)));
+    } else {
+        ops.extend(lcs_ops(old_mid, new_mid).into_iter().map(|op| match op {
+            DiffOp::Equal(a, b) => DiffOp::Equal(prefix + a, prefix + b),
+            DiffOp::Delete(a) => DiffOp::Delete(prefix + a),
+            DiffOp::Insert(b) => DiffOp::Insert(prefix + b),
+        }));
+    }
+    let (old_tail, new_tail) = (old.len() - suffix, new.len() - suffix);
+    ops.extend((0..suffix).map(|idx| DiffOp::Equal(old_tail + idx, new_tail + idx)));
+    ops
+}
+
+fn lcs_ops(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let width = new.len() + 1;
+    // table[i * width + j] : longueur LCS de old[i..] et new[j..].
+    let mut table = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(DiffOp::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(DiffOp::Delete(i));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..old.len()).map(DiffOp::Delete));
+    ops.extend((j..new.len()).map(DiffOp::Insert));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(rows: &[DiffRow]) -> Vec<&str> {
+        rows.iter().map(|row| row.text.as_str()).collect()
+    }
+
+    #[test]
+    fn identiques_sans_diff() {
+        assert!(diff_rows("a\nb\n", "a\r\nb", DIFF_CONTEXT).is_empty());
+        assert!(diff_rows("", "", DIFF_CONTEXT).is_empty());
+    }
+
+    #[test]
+    fn modification_avec_contexte() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nCINQ\n6\n7\n8\n";
+        let rows = diff_rows(old, new, 1);
+        assert_eq!(
+            texts(&rows),
+            vec!["@@ -4,3 +4,3 @@", " 4", "-5", "+CINQ", " 6"]
+        );
+        assert_eq!(rows[2].kind, DiffRowKind::Removed);
+        assert_eq!(rows[3].kind, DiffRowKind::Added);
+        assert_eq!(rows[3].ansi(), "\u{1b}[32m+CINQ\u{1b}[0m");
+        assert_eq!(rows[1].ansi(), " 4");
+    }
+
+    #[test]
+    fn hunks_separes_et_fichier_absent() {
+        let old: String = (1..=20).map(|n| format!("{n}\n")).collect();
+        let new: String = (1..=20)
+            .filter(|n| *n != 19)
+            .map(|n| {
+                if n == 2 {
+                    "deux\n".to_string()
+                } else {
+                    format!("{n}\n")
+                }
+            })
+            .collect();
+        let rows = diff_rows(&old, &new, 1);
+        let hunks: Vec<&str> = rows
+            .iter()
+            .filter(|row| row.kind == DiffRowKind::Hunk)
+            .map(|row| row.text.as_str())
+            .collect();
+        assert_eq!(hunks, vec!["@@ -1,3 +1,3 @@", "@@ -18,3 +18,2 @@"]);
+
+        // Fichier supprime du disque : tout le buffer est un ajout.
+        let rows = diff_rows("", "a\nb", DIFF_CONTEXT);
+        assert_eq!(texts(&rows), vec!["@@ -0,0 +1,2 @@", "+a", "+b"]);
+    }
+
+    const REPONSE: &str = "Voici le correctif :\n\n```diff\n--- a/src/main.py\n+++ b/src/main.py\n@@ -1,4 +1,4 @@\n def f():\n-    return 1\n+    return 2\n \n print(f())\n@@ -8,2 +8,3 @@\n x = 1\n+y = 2\n z = 3\n```\n\nBonne journee.";
+
+    #[test]
+    fn extrait_le_diff_d_une_reponse() {
+        let patch = extract_unified_diff(REPONSE).unwrap();
+        assert_eq!(patch.path.as_deref(), Some("src/main.py"));
+        assert_eq!(patch.hunks.len(), 2);
+        assert_eq!(patch.hunks[0].old_start, 1);
+        assert_eq!(
+            patch.hunks[0].old,
+            ["def f():", "    return 1", "", "print(f())"]
+        );
+        assert_eq!(patch.hunks[1].new, ["x = 1", "y = 2", "z = 3"]);
+        assert!(extract_unified_diff("Rien a corriger.").is_none());
+
+        // Diff brut sans entete de fichier : cible laissee a l'appelant.
+        let patch = extract_unified_diff("@@ -2 +2 @@\n-b\n+B\n").unwrap();
+        assert_eq!(patch.path, None);
+        assert_eq!(patch.hunks[0].old_start, 2);
+    }
+
+    #[test]
+    fn applique_le_patch_avec_decalage() {
+        let patch = extract_unified_diff(REPONSE).unwrap();
+        let original = "def f():\n    return 1\n\nprint(f())\n\n\n\nx = 1\nz = 3\n";
+        assert_eq!(
+            apply_patch(original, &patch).unwrap(),
+            "def f():\n    return 2\n\nprint(f())\n\n\n\nx = 1\ny = 2\nz = 3\n"
+        );
+        // Deux lignes ajoutees en tete : les hunks sont retrouves plus bas.
+        let decale = format!("import os\nimport sys\n{original}");
+        assert!(
+            apply_patch(&decale, &patch)
+                .unwrap()
+                .ends_with("    return 2\n\nprint(f())\n\n\n\nx = 1\ny = 2\nz = 3\n")
+        );
+    }
+
+    #[test]
+    fn insertion_pure_apres_la_ligne_annoncee() {
+        let patch = extract_unified_diff("@@ -2,0 +3,1 @@\n+NEW\n").unwrap();
+        assert_eq!(apply_patch("1\n2\n3\n", &patch).unwrap(), "1\n2\nNEW\n3\n");
+        let patch = extract_unified_diff("@@ -0,0 +1,1 @@\n+NEW\n").unwrap();
+        assert_eq!(apply_patch("1\n2\n", &patch).unwrap(), "NEW\n1\n2\n");
+        let patch = extract_unified_diff("@@ -3,0 +4,1 @@\n+NEW\n").unwrap();
+        assert_eq!(apply_patch("1\n2\n3\n", &patch).unwrap(), "1\n2\n3\nNEW\n");
+    }
+
+    #[test]
+    fn refuse_un_hunk_qui_ne_correspond_pas() {
+        let patch = extract_unified_diff(REPONSE).unwrap();
+        let err = apply_patch("def f():\n    return 3\n", &patch).unwrap_err();
+        assert!(err.contains("hunk 1"), "{err}");
+    }
+}
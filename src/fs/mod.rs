@@ -1,6 +1,7 @@
 use std::fs;
 use std::io::{self, BufRead, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use anyhow::{Context, Result};
 use encoding_rs::Encoding;
@@ -8,6 +9,23 @@ use regex::Regex;
 
 const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
 
+/// Encodages proposes pour rouvrir/enregistrer (tout libelle connu d'encoding_rs reste accepte).
+pub const COMMON_ENCODINGS: [&str; 10] = [
+    "utf-8",
+    "utf-8-sig",
+    "windows-1252",
+    "latin1",
+    "iso-8859-15",
+    "utf-16le",
+    "utf-16be",
+    "shift_jis",
+    "gbk",
+    "euc-kr",
+];
+
+/// Suffixe des copies de secours ecrites a cote du fichier (jamais a sa place).
+pub const AUTOSAVE_SUFFIX: &str = ".autosave";
+
 fn pep263_encoding_line(line: &str) -> Option<String> {
     // La regex suit PEP 263 : "coding[:=] <encoding>".
     let re = Regex::new(r"(?i)coding[:=]\s*([-\w.]+)").ok()?;
@@ -15,9 +33,7 @@ fn pep263_encoding_line(line: &str) -> Option<String> {
         .and_then(|cap| cap.get(1).map(|m| m.as_str().to_string()))
 }
 
-fn detect_python_encoding(path: &Path) -> Option<String> {
-    let file = fs::File::open(path).ok()?;
-    let mut reader = io::BufReader::new(file);
+fn detect_python_encoding(mut reader: impl BufRead) -> Option<String> {
     let mut buf = Vec::new();
     for _ in 0..2 {
         buf.clear();
@@ -63,48 +79,225 @@ fn decode_with_encoding(bytes: &[u8], encoding: &str) -> Option<(String, bool)>
     Some((cow.into_owned(), had_errors))
 }
 
-/// Détecte un encodage raisonnable pour un fichier (PEP 263 pour .py).
-pub fn detect_text_encoding(path: &Path) -> String {
-    if path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .map(|s| s.eq_ignore_ascii_case("py"))
-        .unwrap_or(false)
-    {
-        if let Some(enc) = detect_python_encoding(path) {
-            return enc;
-        }
-        return "utf-8".to_string();
+/// Encodage annonce par un BOM (UTF-8, UTF-16 LE/BE).
+fn bom_encoding(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(UTF8_BOM) {
+        Some("utf-8-sig")
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        Some("utf-16le")
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        Some("utf-16be")
+    } else {
+        None
     }
+}
+
+/// Vrai si le fichier commence par un BOM (UTF-8, UTF-16 LE/BE).
+pub fn has_bom(path: &Path) -> bool {
+    read_head(path, UTF8_BOM.len()).is_ok_and(|bytes| bom_encoding(&bytes).is_some())
+}
 
-    let bytes = match fs::read(path) {
-        Ok(data) => data,
-        Err(_) => return "utf-8".to_string(),
+/// UTF-16 sans BOM : octets nuls concentres sur une parite (texte surtout ASCII).
+fn guess_utf16(bytes: &[u8]) -> Option<&'static str> {
+    let sample = &bytes[..bytes.len().min(4096)];
+    let zeros = |parity: usize| {
+        sample
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|byte| **byte == 0)
+            .count()
     };
+    let (even, odd) = (zeros(0), zeros(1));
+    let pairs = sample.len() / 2;
+    if pairs > 0 && odd * 4 >= pairs && even * 10 <= odd {
+        Some("utf-16le")
+    } else if pairs > 0 && even * 4 >= pairs && odd * 10 <= even {
+        Some("utf-16be")
+    } else {
+        None
+    }
+}
+
+fn is_python_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|s| s.eq_ignore_ascii_case("py"))
+        .unwrap_or(false)
+}
 
+fn fallback_encoding(bytes: &[u8]) -> String {
     for enc in ["utf-8", "utf-8-sig", "windows-1252", "latin-1"] {
-        if let Some((_, had_errors)) = decode_with_encoding(&bytes, enc)
+        if let Some((_, had_errors)) = decode_with_encoding(bytes, enc)
             && !had_errors
         {
             return enc.to_string();
         }
     }
-
     "utf-8".to_string()
 }
 
+/// Détecte un encodage raisonnable pour un fichier (BOM, UTF-16, PEP 263 pour .py).
+pub fn detect_text_encoding(path: &Path) -> String {
+    let Ok(head) = read_head(path, 4096) else {
+        return "utf-8".to_string();
+    };
+    if let Some(enc) = bom_encoding(&head).or_else(|| guess_utf16(&head)) {
+        return enc.to_string();
+    }
+
+    if is_python_path(path) {
+        return detect_python_encoding(&head[..]).unwrap_or_else(|| "utf-8".to_string());
+    }
+
+    match fs::read(path) {
+        Ok(bytes) => fallback_encoding(&bytes),
+        Err(_) => "utf-8".to_string(),
+    }
+}
+
+/// Comme [`detect_text_encoding`], sur le debut d'un fichier deja lu : une sequence UTF-8
+/// coupee en fin de tampon n'est pas une erreur.
+pub fn detect_text_encoding_bytes(path: &Path, bytes: &[u8]) -> String {
+    if let Some(enc) = bom_encoding(bytes).or_else(|| guess_utf16(bytes)) {
+        return enc.to_string();
+    }
+    if is_python_path(path) {
+        return detect_python_encoding(bytes).unwrap_or_else(|| "utf-8".to_string());
+    }
+    match std::str::from_utf8(bytes) {
+        Err(err) if err.error_len().is_none() => "utf-8".to_string(),
+        _ => fallback_encoding(bytes),
+    }
+}
+
+/// Fin de ligne d'un fichier texte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    /// Les deux coexistent : le fichier est normalise en LF a la sauvegarde, sauf choix explicite.
+    Mixed,
+}
+
+impl LineEnding {
+    pub fn label(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+            LineEnding::Mixed => "mixte",
+        }
+    }
+
+    /// Choix suivant pour le forcage manuel (LF <-> CRLF).
+    pub fn toggled(self) -> Self {
+        match self {
+            LineEnding::Crlf => LineEnding::Lf,
+            LineEnding::Lf | LineEnding::Mixed => LineEnding::Crlf,
+        }
+    }
+}
+
+/// Detecte la fin de ligne d'un texte decode (LF si aucune ligne).
+pub fn detect_line_ending(text: &str) -> LineEnding {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    match (crlf, lf) {
+        (0, _) => LineEnding::Lf,
+        (_, 0) => LineEnding::Crlf,
+        _ => LineEnding::Mixed,
+    }
+}
+
+/// Reapplique la fin de ligne voulue a un texte (quelle que soit sa forme actuelle).
+pub fn apply_line_ending(text: &str, ending: LineEnding) -> String {
+    let normalized = text.replace("\r\n", "\n");
+    match ending {
+        LineEnding::Crlf => normalized.replace('\n', "\r\n"),
+        LineEnding::Lf | LineEnding::Mixed => normalized,
+    }
+}
+
+/// Espaces de fin de ligne retires et un seul saut de ligne final (texte vide laisse vide) ;
+/// `None` si le texte est deja propre.
+pub fn normalize_whitespace(text: &str) -> Option<String> {
+    let mut out = text
+        .split('\n')
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    out.truncate(out.trim_end_matches('\n').len());
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    (out != text).then_some(out)
+}
+
+/// Libelle en minuscules si l'encodage est connu (`utf-8-sig` compris), sinon `None`.
+pub fn normalize_encoding(label: &str) -> Option<String> {
+    let lower = label.trim().to_lowercase();
+    (lower == "utf-8-sig" || Encoding::for_label(lower.as_bytes()).is_some()).then_some(lower)
+}
+
+/// Vrai si le texte s'ecrit sans perte dans cet encodage (sinon la sauvegarde repasse en UTF-8).
+pub fn can_encode(encoding: &str, content: &str) -> bool {
+    let lower = encoding.trim().to_lowercase();
+    if lower == "utf-8-sig" {
+        return true;
+    }
+    match Encoding::for_label(lower.as_bytes()) {
+        // UTF-8 et UTF-16 representent tout Unicode.
+        Some(enc) if enc.output_encoding() == encoding_rs::UTF_8 => true,
+        Some(enc) => !enc.encode(content).2,
+        None => false,
+    }
+}
+
 /// Lit un fichier texte avec un encodage donné (fallback lossy en cas d'erreur).
 pub fn read_text_with_encoding(path: &Path, encoding: &str) -> io::Result<String> {
-    let bytes = fs::read(path)?;
-    if let Some((text, _)) = decode_with_encoding(&bytes, encoding) {
-        return Ok(text);
+    Ok(decode_text(&fs::read(path)?, encoding))
+}
+
+/// Decode des octets avec un encodage donne (fallback lossy en cas d'erreur).
+pub fn decode_text(bytes: &[u8], encoding: &str) -> String {
+    match decode_with_encoding(bytes, encoding) {
+        Some((text, _)) => text,
+        None => String::from_utf8_lossy(bytes).to_string(),
     }
-    Ok(String::from_utf8_lossy(&bytes).to_string())
 }
 
 /// Ecrit un fichier texte avec l'encodage demande. En fallback, ecrit en UTF-8.
-pub fn write_text_with_encoding(path: &Path, encoding: &str, content: &str) -> Result<bool> {
+/// `bom` : BOM en tete de l'UTF-16 (l'UTF-8 le deduit du label `utf-8-sig`).
+pub fn write_text_with_encoding(
+    path: &Path,
+    encoding: &str,
+    content: &str,
+    bom: bool,
+) -> Result<bool> {
     let encoding_lower = encoding.to_lowercase();
+    // encoding_rs encode l'UTF-16 en UTF-8 : unites ecrites a la main.
+    let utf16_big_endian = match encoding_lower.as_str() {
+        "utf-16le" | "utf-16" => Some(false),
+        "utf-16be" => Some(true),
+        _ => None,
+    };
+    if let Some(big_endian) = utf16_big_endian {
+        let mut data = match (bom, big_endian) {
+            (false, _) => Vec::new(),
+            (true, true) => vec![0xFE, 0xFF],
+            (true, false) => vec![0xFF, 0xFE],
+        };
+        for unit in content.encode_utf16() {
+            let bytes = if big_endian {
+                unit.to_be_bytes()
+            } else {
+                unit.to_le_bytes()
+            };
+            data.extend_from_slice(&bytes);
+        }
+        fs::write(path, data).context("ecriture fichier")?;
+        return Ok(false);
+    }
     if encoding_lower == "utf-8" {
         fs::write(path, content.as_bytes()).context("ecriture fichier")?;
         return Ok(false);
@@ -128,6 +321,46 @@ pub fn write_text_with_encoding(path: &Path, encoding: &str, content: &str) -> R
     Ok(true)
 }
 
+/// `main.py` -> `main.py.autosave`.
+pub fn autosave_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(AUTOSAVE_SUFFIX);
+    PathBuf::from(name)
+}
+
+pub fn is_autosave_path(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(AUTOSAVE_SUFFIX)
+}
+
+/// Ecrit la copie de secours du buffer (UTF-8, fins de ligne LF) ; le fichier reste intact.
+pub fn write_autosave(path: &Path, content: &str) -> io::Result<PathBuf> {
+    let autosave = autosave_path(path);
+    fs::write(&autosave, content.as_bytes())?;
+    Ok(autosave)
+}
+
+pub fn read_autosave(path: &Path) -> io::Result<String> {
+    let bytes = fs::read(autosave_path(path))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Copie de secours plus recente que le fichier (travail non sauve avant un crash).
+pub fn newer_autosave(path: &Path) -> Option<PathBuf> {
+    let autosave = autosave_path(path);
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let saved = modified(&autosave)?;
+    let original = modified(path).unwrap_or(SystemTime::UNIX_EPOCH);
+    (saved > original).then_some(autosave)
+}
+
+/// Supprime la copie de secours (absente : rien a faire).
+pub fn remove_autosave(path: &Path) -> io::Result<()> {
+    match fs::remove_file(autosave_path(path)) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
 /// Heuristique simple pour éviter d'ouvrir des binaires dans l'éditeur.
 pub fn is_probably_binary(path: &Path, sniff_bytes: usize) -> io::Result<bool> {
     if sniff_bytes == 0 {
@@ -138,6 +371,10 @@ pub fn is_probably_binary(path: &Path, sniff_bytes: usize) -> io::Result<bool> {
     let read = file.read(&mut buf)?;
     buf.truncate(read);
 
+    // Un BOM (UTF-16 notamment) annonce du texte malgre les octets nuls.
+    if bom_encoding(&buf).is_some() {
+        return Ok(false);
+    }
     if buf.contains(&0) {
         return Ok(true);
     }
@@ -157,6 +394,47 @@ pub fn is_probably_binary(path: &Path, sniff_bytes: usize) -> io::Result<bool> {
     Ok((ctrl as f32 / buf.len() as f32) > 0.10)
 }
 
+/// Octets montres par l'apercu hexadecimal d'un binaire.
+pub const HEX_PREVIEW_BYTES: usize = 16 * 1024;
+
+/// Lit au plus `max` octets au debut du fichier.
+pub fn read_head(path: &Path, max: usize) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    fs::File::open(path)?
+        .take(max as u64)
+        .read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Vue hexadecimale : offset, 16 octets en hexa puis en ASCII (`.` si non imprimable).
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut lines = Vec::new();
+    for (idx, chunk) in bytes.chunks(16).enumerate() {
+        let mut hex = String::new();
+        for pos in 0..16 {
+            if pos == 8 {
+                hex.push(' ');
+            }
+            match chunk.get(pos) {
+                Some(byte) => hex.push_str(&format!("{byte:02x} ")),
+                None => hex.push_str("   "),
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        lines.push(format!("{:08x}  {hex} |{ascii}|", idx * 16));
+    }
+    lines.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,7 +490,7 @@ mod tests {
     fn ecrit_utf8_sig() {
         let dir = TempDir::new().unwrap();
         let path = dir.path().join("bom.txt");
-        let fallback = write_text_with_encoding(&path, "utf-8-sig", "Bonjour").unwrap();
+        let fallback = write_text_with_encoding(&path, "utf-8-sig", "Bonjour", false).unwrap();
         let bytes = fs::read(&path).unwrap();
 
         assert!(!fallback);
@@ -223,7 +501,7 @@ mod tests {
     fn ecrit_windows_1252() {
         let dir = TempDir::new().unwrap();
         let path = dir.path().join("latin.txt");
-        let fallback = write_text_with_encoding(&path, "windows-1252", "école").unwrap();
+        let fallback = write_text_with_encoding(&path, "windows-1252", "école", false).unwrap();
         let content = read_text_with_encoding(&path, "windows-1252").unwrap();
 
         assert!(!fallback);
@@ -234,10 +512,182 @@ mod tests {
     fn fallback_utf8_si_caractere_non_supporte() {
         let dir = TempDir::new().unwrap();
         let path = dir.path().join("fallback.txt");
-        let fallback = write_text_with_encoding(&path, "windows-1252", "Bonjour 🚀").unwrap();
+        let fallback =
+            write_text_with_encoding(&path, "windows-1252", "Bonjour 🚀", false).unwrap();
         let bytes = fs::read(&path).unwrap();
 
         assert!(fallback);
         assert_eq!(String::from_utf8(bytes).unwrap(), "Bonjour 🚀");
     }
+
+    #[test]
+    fn detecte_et_reapplique_fin_de_ligne() {
+        assert_eq!(detect_line_ending("a\r\nb\r\n"), LineEnding::Crlf);
+        assert_eq!(detect_line_ending("a\nb"), LineEnding::Lf);
+        assert_eq!(detect_line_ending("sans retour"), LineEnding::Lf);
+        assert_eq!(detect_line_ending("a\r\nb\n"), LineEnding::Mixed);
+
+        assert_eq!(apply_line_ending("a\nb", LineEnding::Crlf), "a\r\nb");
+        assert_eq!(apply_line_ending("a\r\nb\n", LineEnding::Mixed), "a\nb\n");
+        assert_eq!(LineEnding::Mixed.toggled(), LineEnding::Crlf);
+    }
+
+    #[test]
+    fn normalise_espaces_et_saut_final() {
+        assert_eq!(
+            normalize_whitespace("def f():  \n\treturn 1\t\n\n  \n").as_deref(),
+            Some("def f():\n\treturn 1\n")
+        );
+        assert_eq!(normalize_whitespace("x = 1").as_deref(), Some("x = 1\n"));
+        assert_eq!(
+            normalize_whitespace("a\r\nb \r\n").as_deref(),
+            Some("a\nb\n")
+        );
+        assert_eq!(normalize_whitespace("propre\n"), None);
+        assert_eq!(normalize_whitespace(""), None);
+        assert_eq!(normalize_whitespace("\n \n").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn utf16_avec_ou_sans_bom_est_du_texte() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        write_text_with_encoding(&path, "utf-16le", "héllo\nmonde", true).unwrap();
+        assert!(fs::read(&path).unwrap().starts_with(&[0xFF, 0xFE]));
+        assert!(!is_probably_binary(&path, 2048).unwrap());
+        assert_eq!(detect_text_encoding(&path), "utf-16le");
+        assert_eq!(
+            read_text_with_encoding(&path, "utf-16le").unwrap(),
+            "héllo\nmonde"
+        );
+
+        let be = dir.path().join("be.py");
+        write_text_with_encoding(&be, "utf-16be", "x = 1", true).unwrap();
+        assert_eq!(detect_text_encoding(&be), "utf-16be");
+        assert_eq!(read_text_with_encoding(&be, "utf-16be").unwrap(), "x = 1");
+
+        // Sans BOM : signale binaire, mais l'encodage devine reste UTF-16.
+        let raw = dir.path().join("raw.txt");
+        let bytes: Vec<u8> = "abc def"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        fs::write(&raw, bytes).unwrap();
+        assert!(is_probably_binary(&raw, 2048).unwrap());
+        assert_eq!(detect_text_encoding(&raw), "utf-16le");
+        assert_eq!(
+            read_text_with_encoding(&raw, "utf-16le").unwrap(),
+            "abc def"
+        );
+        // Reecrit sans BOM s'il n'en avait pas a l'ouverture.
+        let original = fs::read(&raw).unwrap();
+        assert!(!has_bom(&raw));
+        write_text_with_encoding(&raw, "utf-16le", "abc def", has_bom(&raw)).unwrap();
+        assert_eq!(fs::read(&raw).unwrap(), original);
+    }
+
+    #[test]
+    fn bom_detecte_et_conserve_a_la_sauvegarde() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("windows.txt");
+        let mut original = vec![0xFF, 0xFE];
+        original.extend("café\r\n".encode_utf16().flat_map(u16::to_le_bytes));
+        fs::write(&path, &original).unwrap();
+
+        let encoding = detect_text_encoding(&path);
+        assert_eq!(encoding, "utf-16le");
+        let text = read_text_with_encoding(&path, &encoding).unwrap();
+        assert_eq!(text, "café\r\n");
+        assert!(!write_text_with_encoding(&path, &encoding, &text, has_bom(&path)).unwrap());
+        assert_eq!(fs::read(&path).unwrap(), original);
+
+        // Le BOM passe avant la ligne PEP 263 des .py.
+        let py = dir.path().join("bom.py");
+        fs::write(&py, b"\xEF\xBB\xBFx = 1\n").unwrap();
+        assert_eq!(detect_text_encoding(&py), "utf-8-sig");
+        assert_eq!(
+            read_text_with_encoding(&py, "utf-8-sig").unwrap(),
+            "x = 1\n"
+        );
+    }
+
+    #[test]
+    fn encodages_connus_et_representables() {
+        assert_eq!(
+            normalize_encoding(" Shift_JIS "),
+            Some("shift_jis".to_string())
+        );
+        assert_eq!(
+            normalize_encoding("utf-8-sig"),
+            Some("utf-8-sig".to_string())
+        );
+        assert_eq!(normalize_encoding("klingon"), None);
+        assert!(
+            COMMON_ENCODINGS
+                .iter()
+                .all(|enc| normalize_encoding(enc).is_some())
+        );
+
+        assert!(can_encode("windows-1252", "école"));
+        assert!(!can_encode("windows-1252", "日本"));
+        assert!(can_encode("shift_jis", "日本"));
+        assert!(can_encode("utf-16le", "🚀"));
+        assert!(!can_encode("klingon", "a"));
+    }
+
+    #[test]
+    fn copie_de_secours_a_cote_du_fichier() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("main.py");
+        fs::write(&path, "a = 1").unwrap();
+        let old = SystemTime::now() - std::time::Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert!(newer_autosave(&path).is_none());
+
+        let autosave = write_autosave(&path, "a = 2").unwrap();
+        assert_eq!(autosave, dir.path().join("main.py.autosave"));
+        assert!(is_autosave_path(&autosave));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a = 1");
+        assert_eq!(newer_autosave(&path), Some(autosave.clone()));
+        assert_eq!(read_autosave(&path).unwrap(), "a = 2");
+
+        // Fichier sauve apres la copie : plus rien a recuperer.
+        fs::write(&path, "a = 3").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&autosave)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert!(newer_autosave(&path).is_none());
+
+        remove_autosave(&path).unwrap();
+        assert!(!autosave.exists());
+        remove_autosave(&path).unwrap();
+    }
+
+    #[test]
+    fn apercu_hexadecimal() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.bin");
+        let mut bytes = b"Hello\0world!".to_vec();
+        bytes.extend(0u8..20);
+        fs::write(&path, &bytes).unwrap();
+        let head = read_head(&path, 18).unwrap();
+        assert_eq!(head, &bytes[..18]);
+        let dump = hex_dump(&head);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines[0],
+            "00000000  48 65 6c 6c 6f 00 77 6f  72 6c 64 21 00 01 02 03  |Hello.world!....|"
+        );
+        assert!(lines[1].starts_with("00000010  04 05 "));
+        assert!(lines[1].ends_with("|..|"));
+        assert_eq!(hex_dump(&[]), "");
+    }
 }
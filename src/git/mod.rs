@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Etat git d'un fichier (sortie `git status --porcelain`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GitStatus {
+    Untracked,
+    Added,
+    Renamed,
+    Deleted,
+    Modified,
+    Conflict,
+}
+
+impl GitStatus {
+    /// Marqueur affiche a cote du nom dans l'arborescence.
+    pub fn marker(self) -> &'static str {
+        match self {
+            GitStatus::Untracked => "??",
+            GitStatus::Added => "A",
+            GitStatus::Renamed => "R",
+            GitStatus::Deleted => "D",
+            GitStatus::Modified => "M",
+            GitStatus::Conflict => "U",
+        }
+    }
+
+    /// Code `XY` du format porcelain v1 ; None pour les fichiers ignores ou inconnus.
+    fn from_xy(x: char, y: char) -> Option<Self> {
+        match (x, y) {
+            ('?', '?') => Some(GitStatus::Untracked),
+            ('!', '!') => None,
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => Some(GitStatus::Conflict),
+            ('R', _) | (_, 'R') => Some(GitStatus::Renamed),
+            ('A', _) => Some(GitStatus::Added),
+            ('D', _) | (_, 'D') => Some(GitStatus::Deleted),
+            (' ' | 'M' | 'T' | 'C', ' ' | 'M' | 'T') => Some(GitStatus::Modified),
+            _ => None,
+        }
+    }
+}
+
+/// `.git` a la racine : seul cas ou l'IDE interroge git.
+pub fn is_git_repo(root: &Path) -> bool {
+    root.join(".git").exists()
+}
+
+/// Chemins hors ASCII non echappes ; les fichiers non suivis sont listes un par un.
+pub fn git_status_argv() -> Vec<String> {
+    [
+        "git",
+        "-c",
+        "core.quotePath=false",
+        "status",
+        "--porcelain=v1",
+        "--untracked-files=all",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// Commande git lancee par les actions rapides (un process a la fois).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitCommand {
+    AddAll,
+    Commit,
+    Diff,
+}
+
+/// `git add -A` : tout indexer avant le commit.
+pub fn git_add_all_argv() -> Vec<String> {
+    ["git", "add", "-A"].into_iter().map(String::from).collect()
+}
+
+pub fn git_commit_argv(message: &str) -> Vec<String> {
+    ["git", "commit", "-m", message]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Differences du fichier avec HEAD (indexees ou non), colorees pour le journal.
+pub fn git_diff_argv(relative: &Path) -> Vec<String> {
+    ["git", "--no-pager", "diff", "--color=always", "HEAD", "--"]
+        .into_iter()
+        .map(String::from)
+        .chain(std::iter::once(
+            relative.to_string_lossy().replace('\\', "/"),
+        ))
+        .collect()
+}
+
+/// Hash court annonce par `git commit` : `[main 1a2b3c4] message` ou `[main (root-commit) 1a2b3c4] ...`.
+pub fn parse_commit_hash(line: &str) -> Option<String> {
+    let inner = line.trim().strip_prefix('[')?;
+    let (inner, _) = inner.split_once(']')?;
+    let hash = inner.split_whitespace().last()?;
+    (hash.len() >= 7 && hash.chars().all(|ch| ch.is_ascii_hexdigit())).then(|| hash.to_string())
+}
+
+/// Statuts git analyses une fois par `git status`, consultes a chaque affichage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitStatusMap {
+    files: HashMap<PathBuf, GitStatus>,
+    /// Dossiers contenant un changement : statut le plus important de leurs fichiers.
+    dirs: HashMap<PathBuf, GitStatus>,
+}
+
+impl GitStatusMap {
+    /// Lignes de `git status --porcelain=v1` lance a la racine du depot `root`.
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>, root: &Path) -> Self {
+        let mut map = Self::default();
+        for line in lines {
+            let mut chars = line.chars();
+            let (Some(x), Some(y), Some(' ')) = (chars.next(), chars.next(), chars.next()) else {
+                continue;
+            };
+            let Some(status) = GitStatus::from_xy(x, y) else {
+                continue;
+            };
+            let raw = &line[3..];
+            // Renommage : "ancien -> nouveau", seul le nouveau chemin existe.
+            let raw = raw.rsplit_once(" -> ").map_or(raw, |(_, new)| new);
+            let relative = unquote(raw);
+            let relative = relative.trim_end_matches('/');
+            if relative.is_empty() {
+                continue;
+            }
+            let path = relative
+                .split('/')
+                .fold(root.to_path_buf(), |path, part| path.join(part));
+            for dir in path.ancestors().skip(1) {
+                if !dir.starts_with(root) || dir == root {
+                    break;
+                }
+                let entry = map.dirs.entry(dir.to_path_buf()).or_insert(status);
+                *entry = (*entry).max(status);
+            }
+            map.files.insert(path, status);
+        }
+        map
+    }
+
+    pub fn get(&self, path: &Path, is_dir: bool) -> Option<GitStatus> {
+        if is_dir {
+            self.dirs.get(path).copied()
+        } else {
+            self.files.get(path).copied()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Chemin entre guillemets avec echappements C (`\"`, `\\`, `\t`, octal), tel que l'ecrit git.
+fn unquote(raw: &str) -> String {
+    let Some(inner) = raw
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    else {
+        return raw.to_string();
+    };
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('t') => bytes.push(b'\t'),
+            Some('n') => bytes.push(b'\n'),
+            Some(digit @ '0'..='7') => {
+                let mut value = digit.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    match chars.peek().and_then(|ch| ch.to_digit(8)) {
+                        Some(next) => {
+                            value = value * 8 + next;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_fichiers_et_dossiers() {
+        let root = Path::new("/ws");
+        let map = GitStatusMap::parse(
+            [
+                " M src/main.py",
+                "A  src/pkg/new.py",
+                "?? notes.txt",
+                "R  old.py -> src/renamed.py",
+                "UU conflit.py",
+                "!! build/",
+                "warning: could not open directory 'x/'",
+            ],
+            root,
+        );
+        let get = |rel: &str| map.get(&root.join(rel), false);
+        assert_eq!(get("src/main.py"), Some(GitStatus::Modified));
+        assert_eq!(get("src/pkg/new.py"), Some(GitStatus::Added));
+        assert_eq!(get("notes.txt"), Some(GitStatus::Untracked));
+        assert_eq!(get("src/renamed.py"), Some(GitStatus::Renamed));
+        assert_eq!(get("old.py"), None);
+        assert_eq!(get("conflit.py"), Some(GitStatus::Conflict));
+        assert_eq!(get("build"), None);
+        assert_eq!(map.get(&root.join("src"), true), Some(GitStatus::Modified));
+        assert_eq!(map.get(&root.join("src/pkg"), true), Some(GitStatus::Added));
+        assert_eq!(map.get(root, true), None);
+        assert_eq!(GitStatus::Untracked.marker(), "??");
+    }
+
+    #[test]
+    fn commandes_commit_et_diff() {
+        assert_eq!(git_add_all_argv(), vec!["git", "add", "-A"]);
+        assert_eq!(
+            git_commit_argv("Corrige \"x\""),
+            vec!["git", "commit", "-m", "Corrige \"x\""]
+        );
+        let diff = git_diff_argv(&Path::new("src").join("main.py"));
+        assert_eq!(diff.last().map(String::as_str), Some("src/main.py"));
+        assert!(diff.contains(&"HEAD".to_string()));
+        assert_eq!(
+            parse_commit_hash("[main 1a2b3c4] Corrige le build"),
+            Some("1a2b3c4".to_string())
+        );
+        assert_eq!(
+            parse_commit_hash("[master (root-commit) 0f1e2d3c] init"),
+            Some("0f1e2d3c".to_string())
+        );
+        assert_eq!(parse_commit_hash(" 1 file changed, 2 insertions(+)"), None);
+        assert_eq!(parse_commit_hash("[INFO] done"), None);
+    }
+
+    #[test]
+    fn chemins_entre_guillemets() {
+        let root = Path::new("/ws");
+        let map = GitStatusMap::parse(
+            [
+                "?? \"avec \\\"guillemets\\\".py\"",
+                "?? \"caf\\303\\251.py\"",
+            ],
+            root,
+        );
+        assert_eq!(
+            map.get(&root.join("avec \"guillemets\".py"), false),
+            Some(GitStatus::Untracked)
+        );
+        assert_eq!(
+            map.get(&root.join("café.py"), false),
+            Some(GitStatus::Untracked)
+        );
+        assert!(GitStatusMap::default().is_empty());
+    }
+}
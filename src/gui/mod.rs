@@ -1,29 +1,185 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use eframe::egui::{self, Color32, RichText, ScrollArea, TextEdit};
 
+use crate::ansi::{has_fg, parse_ansi};
 use crate::app_core::{
-    APP_NAME, AppCore, LOG_LIMIT, LogTarget, OpenFile, ProcessKind, RunningProcess,
-    codex_approval_label, codex_exec_extra_args, codex_sandbox_label, next_codex_approval_policy,
-    next_codex_sandbox_mode,
+    APP_NAME, AppCore, CODEX_DANGER_WARNING, CodexPatch, CodexRetry, DISK_CHECK_INTERVAL,
+    DevToolEntry, DocumentStats, EditorTab, EditorTabs, EnvOrigin, EnvProfile, LOG_LIMIT,
+    LogFilter, LogKind, LogLine, LogSearch, LogTarget, OpenFile, PROC_TIMEOUT_EXIT_CODE,
+    ProcessKind, PythonInstall, RunningProcess, SetupMissing, ToolStatus, append_log_lines,
+    cache_usage_label, char_index_at, codex_approval_label, codex_exec_extra_args,
+    codex_label_kind, codex_sandbox_label, codex_status_wait_label, dev_tools_to_install,
+    editor_position_label, find_icon_files, format_size, is_streamed_duplicate, log_export_lines,
+    next_codex_approval_policy, next_codex_sandbox_mode, parse_cd_command, parse_env_assignment,
+    parse_goto_line, resolve_shell_cwd, shell_command_risks, shell_cwd_label, validate_build_icon,
+    validate_startup_file,
 };
+use crate::cmd_history::CommandHistory;
 use crate::codex::{
-    CodexApprovalPolicy, CodexError, CodexSandboxMode, DisplayKind, codex_approval_policy_from_env,
-    codex_cli_available, codex_entrypoint_js, codex_exec_argv, codex_exec_help_argv,
-    codex_hint_for_status, codex_install_argv, codex_install_prefix, codex_login_argv,
-    codex_sandbox_mode_from_env, codex_status_argv, extract_display_items, extract_status_code,
-    node_executable, parse_tool_list, pip_install_argv, pyinstaller_available,
-    pyinstaller_build_argv, pyinstaller_install_argv, resolve_in_path, tools_install_prefix,
-    translate_codex_line,
+    CodexApprovalPolicy, CodexError, CodexSandboxMode, CodexView, DisplayKind, Formatter, Linter,
+    NodeTool, PytestSummary, code_block_extension, code_fence, codex_cli_available,
+    codex_confirm_danger_from_env, codex_context_prompt, codex_entrypoint_js, codex_exec_argv,
+    codex_exec_help_argv, codex_hint_for_status, codex_install_argv, codex_install_prefix,
+    codex_login_argv, codex_logout_argv, codex_prompt_display, codex_status_argv,
+    extract_display_items, extract_status_code, format_argv, lint_argv, node_executable,
+    parse_install_progress, parse_pytest_summary, pip_install_argv, pip_install_requirements_argv,
+    pyinstaller_available, pyinstaller_build_argv, pyinstaller_install_argv,
+    pyinstaller_output_path, pytest_args_from_env, pytest_argv, resolve_in_path, tool_available,
+    tools_install_prefix, translate_codex_line,
 };
-use crate::fs::write_text_with_encoding;
+use crate::codex_history::{CODEX_CONTEXT_TURNS, CodexHistory, CodexTurn};
+use crate::diagnostics::{Diagnostic, DiagnosticSeverity, Diagnostics, parse_diagnostic};
+use crate::diff::{DIFF_CONTEXT, DiffRowKind, diff_rows};
+use crate::fs::{
+    COMMON_ENCODINGS, LineEnding, apply_line_ending, can_encode, detect_line_ending, has_bom,
+    newer_autosave, normalize_encoding, normalize_whitespace, read_autosave,
+    read_text_with_encoding, remove_autosave, write_autosave, write_text_with_encoding,
+};
+use crate::git::{
+    GitCommand, GitStatus, GitStatusMap, git_add_all_argv, git_commit_argv, git_diff_argv,
+    git_status_argv, is_git_repo, parse_commit_hash,
+};
+use crate::highlight::{Language, TokenKind, highlight_line};
+use crate::keymap::{Action, ChordKey, KeyChord, Keymap, palette_matches};
 use crate::process::{
-    NativeProcessRunner, ProcEventKind, ProcessRunner, python_run_argv, windows_cmd_argv,
+    NativeProcessRunner, ProcEventKind, ProcessRunner, file_manager_argv, python_repl_argv,
+    python_run_argv, venv_create_argv, windows_cmd_argv,
 };
-use crate::workspace::{FileTreeData, OpenWorkspaceFileError, WorkspacePaths, open_workspace_file};
+use crate::quick_open::{FileIndex, QUICK_OPEN_RESULTS};
+use crate::session::{SESSION_SAVE_INTERVAL, Session};
+use crate::settings::{MAX_TAB_WIDTH, Settings, env_overrides};
+use crate::watcher::WorkspaceWatcher;
+use crate::workspace::{
+    EXPAND_ALL_MAX_DEPTH, FileTreeData, OpenWorkspaceFileError, OpenedWorkspaceFile, TreeEntry,
+    WorkspacePaths, create_entry, create_file_under, move_to_trash, open_workspace_file,
+    open_workspace_file_anyway, open_workspace_hex_preview, remap_path, rename_entry,
+    target_dir_for,
+};
+
+/// Au-dela, l'editeur n'est plus colore (la mise en forme serait refaite a chaque frappe).
+const HIGHLIGHT_MAX_BYTES: usize = 200_000;
+
+/// Modeles proposes dans le menu Codex (vide : modele par defaut de Codex).
+const CODEX_MODEL_PRESETS: [&str; 3] = ["", "o4-mini", "gpt-5"];
+
+/// Taille par defaut de la police monospace (editeur, logs) et bornes des boutons A-/A+.
+const DEFAULT_FONT_SIZE: f32 = 13.5;
+const FONT_SIZE_MIN: f32 = 9.0;
+const FONT_SIZE_MAX: f32 = 28.0;
+
+fn syntax_color(kind: Option<TokenKind>, dark: bool) -> Color32 {
+    let color = match kind {
+        None => Color32::from_gray(220),
+        Some(TokenKind::Keyword) => Color32::from_rgb(198, 120, 221),
+        Some(TokenKind::String) => Color32::from_rgb(152, 195, 121),
+        Some(TokenKind::Comment) => Color32::from_gray(120),
+        Some(TokenKind::Number) => Color32::from_rgb(209, 154, 102),
+    };
+    if dark { color } else { darken(color) }
+}
+
+fn highlight_job(
+    lang: Language,
+    text: &str,
+    font_id: egui::FontId,
+    dark: bool,
+) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    let append = |job: &mut egui::text::LayoutJob, text: &str, kind: Option<TokenKind>| {
+        let format = egui::TextFormat {
+            font_id: font_id.clone(),
+            color: syntax_color(kind, dark),
+            italics: kind == Some(TokenKind::Comment),
+            ..Default::default()
+        };
+        job.append(text, 0.0, format);
+    };
+    let mut state = None;
+    for (idx, line) in text.split('\n').enumerate() {
+        if idx > 0 {
+            append(&mut job, "\n", None);
+        }
+        let mut pos = 0;
+        for token in highlight_line(lang, line, &mut state) {
+            if token.start > pos {
+                append(&mut job, &line[pos..token.start], None);
+            }
+            append(&mut job, &line[token.start..token.end], Some(token.kind));
+            pos = token.end;
+        }
+        if pos < line.len() {
+            append(&mut job, &line[pos..], None);
+        }
+    }
+    job
+}
+
+/// Coloration memorisee d'une frame a l'autre (recalculee seulement si le texte change).
+#[derive(Default)]
+struct Highlighter;
+
+impl egui::util::cache::ComputerMut<(Language, &str, &egui::FontId, bool), egui::text::LayoutJob>
+    for Highlighter
+{
+    fn compute(
+        &mut self,
+        (lang, text, font_id, dark): (Language, &str, &egui::FontId, bool),
+    ) -> egui::text::LayoutJob {
+        highlight_job(lang, text, font_id.clone(), dark)
+    }
+}
+
+type HighlightCache = egui::util::cache::FrameCache<egui::text::LayoutJob, Highlighter>;
+
+/// epaint dessine toujours une tabulation sur `TAB_SIZE` espaces : decale de `extra`
+/// points le texte qui suit chaque tabulation pour respecter la largeur configuree.
+fn widen_tabs(job: &mut egui::text::LayoutJob, extra: f32) {
+    if extra == 0.0 || !job.text.contains('\t') {
+        return;
+    }
+    let mut sections = Vec::with_capacity(job.sections.len());
+    let mut carry = 0.0;
+    for section in std::mem::take(&mut job.sections) {
+        let range = section.byte_range.clone();
+        let text = &job.text[range.clone()];
+        let mut start = range.start;
+        let mut leading = section.leading_space + carry;
+        for (offset, _) in text.match_indices('\t') {
+            let end = range.start + offset + 1;
+            if end == range.end {
+                break;
+            }
+            sections.push(egui::text::LayoutSection {
+                leading_space: leading,
+                byte_range: start..end,
+                format: section.format.clone(),
+            });
+            start = end;
+            leading = extra;
+        }
+        carry = if text.ends_with('\t') { extra } else { 0.0 };
+        sections.push(egui::text::LayoutSection {
+            leading_space: leading,
+            byte_range: start..range.end,
+            format: section.format,
+        });
+    }
+    job.sections = sections;
+}
+
+/// Couleur d'une entree de l'arborescence selon son statut git.
+fn git_status_color(status: GitStatus) -> Color32 {
+    match status {
+        GitStatus::Modified => Color32::from_rgb(218, 165, 72),
+        GitStatus::Added | GitStatus::Untracked => Color32::from_rgb(120, 220, 160),
+        GitStatus::Renamed => Color32::from_rgb(120, 190, 255),
+        GitStatus::Deleted | GitStatus::Conflict => Color32::from_rgb(240, 100, 100),
+    }
+}
 
 fn accent_red() -> Color32 {
     Color32::from_rgb(229, 57, 53)
@@ -41,6 +197,34 @@ fn panel_border() -> Color32 {
     Color32::from_rgb(46, 54, 66)
 }
 
+/// Couleurs de texte pensees pour le fond sombre, assombries pour rester lisibles en theme clair.
+fn darken(color: Color32) -> Color32 {
+    let scale = |value: u8| (f32::from(value) * 0.45) as u8;
+    Color32::from_rgb(scale(color.r()), scale(color.g()), scale(color.b()))
+}
+
+fn themed_fg(visuals: &egui::Visuals, color: Color32) -> Color32 {
+    if visuals.dark_mode {
+        color
+    } else {
+        darken(color)
+    }
+}
+
+/// Vrai si la zone defilante montre la fin de son contenu (a quelques pixels pres).
+fn scrolled_to_bottom<R>(output: &egui::scroll_area::ScrollAreaOutput<R>) -> bool {
+    output.state.offset.y + output.inner_rect.height() >= output.content_size.y - 4.0
+}
+
+/// Fond ou bordure sombre rapproche du blanc en theme clair (`amount` : part de blanc).
+fn themed_bg(visuals: &egui::Visuals, color: Color32, amount: f32) -> Color32 {
+    if visuals.dark_mode || color == Color32::TRANSPARENT {
+        return color;
+    }
+    let mix = |value: u8| (f32::from(value) + (255.0 - f32::from(value)) * amount) as u8;
+    Color32::from_rgb(mix(color.r()), mix(color.g()), mix(color.b()))
+}
+
 fn codex_info_color() -> Color32 {
     Color32::from_gray(210)
 }
@@ -65,6 +249,39 @@ fn codex_hint_color() -> Color32 {
     Color32::from_rgb(240, 200, 120)
 }
 
+/// Sortie d'outil en texte brut (sans ANSI) ; le rouge devient une erreur, le jaune un
+/// avertissement, pour garder l'essentiel de la coloration dans le journal.
+fn ansi_log_line(line: &str) -> (String, LogKind) {
+    let spans = parse_ansi(line);
+    let kind = if has_fg(&spans, &[1, 9]) {
+        LogKind::Error
+    } else if has_fg(&spans, &[3, 11]) {
+        LogKind::Warn
+    } else {
+        LogKind::Info
+    };
+    let text = spans.into_iter().map(|span| span.text).collect();
+    (text, kind)
+}
+
+fn chord_pressed(input: &egui::InputState, chord: &KeyChord) -> bool {
+    let name = match chord.key {
+        ChordKey::Char(ch) => ch.to_ascii_uppercase().to_string(),
+        ChordKey::F(number) => format!("F{number}"),
+        ChordKey::Tab => "Tab".to_string(),
+    };
+    egui::Key::from_name(&name).is_some_and(|key| input.key_pressed(key))
+        && input.modifiers.ctrl == chord.ctrl
+        && input.modifiers.alt == chord.alt
+        && input.modifiers.shift == chord.shift
+}
+
+fn tab_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
 fn codex_label_bg(kind: LogKind) -> Color32 {
     match kind {
         LogKind::User => Color32::from_rgb(24, 40, 64),
@@ -74,20 +291,158 @@ fn codex_label_bg(kind: LogKind) -> Color32 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum LogKind {
-    Info,
-    Warn,
-    Error,
-    User,
-    Assistant,
-    Action,
+/// Morceau de la Sortie Codex dessine par son propre widget ; `first` : index de sa
+/// premiere ligne parmi les lignes filtrees (recherche).
+#[derive(Debug, Clone)]
+enum CodexLogSegment {
+    /// Texte selectionnable ; `kind` : message en cours a son debut.
+    Text {
+        first: usize,
+        text: String,
+        kind: Option<LogKind>,
+    },
+    /// Bloc ``` d'un message, sans ses barrieres, avec Copier / Enregistrer sous.
+    Code {
+        first: usize,
+        lines: usize,
+        lang: String,
+        code: String,
+    },
 }
 
-#[derive(Debug, Clone)]
-struct LogLine {
-    text: String,
-    kind: LogKind,
+/// Decoupe les lignes filtrees en texte et blocs de code des messages utilisateur/assistant.
+fn codex_log_segments(entries: &[LogLine], filter: LogFilter) -> Vec<CodexLogSegment> {
+    let mut segments = Vec::new();
+    let mut text: Vec<String> = Vec::new();
+    let mut text_first = 0;
+    let mut text_kind = None;
+    // Bloc ouvert : (premiere ligne, langue, lignes de code).
+    let mut block: Option<(usize, String, Vec<String>)> = None;
+    let shown = entries
+        .iter()
+        .filter(|entry| filter.accepts(entry.kind.level()));
+    for (idx, entry) in shown.enumerate() {
+        let in_message = matches!(entry.kind, LogKind::Assistant | LogKind::User);
+        let fence = code_fence(&entry.text).filter(|_| in_message);
+        if let Some((first, lang, mut code)) = block.take() {
+            if fence.is_some() || !in_message {
+                // La barriere fermante fait partie du bloc, une ligne hors message non.
+                let end = if fence.is_some() { idx + 1 } else { idx };
+                segments.push(CodexLogSegment::Code {
+                    first,
+                    lines: end - first,
+                    lang,
+                    code: code.join("\n"),
+                });
+                text_first = end;
+                text_kind = Some(entry.kind);
+                if fence.is_some() {
+                    continue;
+                }
+            } else {
+                code.push(entry.text.clone());
+                block = Some((first, lang, code));
+                continue;
+            }
+        }
+        if let Some(lang) = fence {
+            if !text.is_empty() {
+                segments.push(CodexLogSegment::Text {
+                    first: text_first,
+                    text: std::mem::take(&mut text).join("\n"),
+                    kind: text_kind,
+                });
+            }
+            block = Some((idx, lang.to_string(), Vec::new()));
+            continue;
+        }
+        if text.is_empty() {
+            text_first = idx;
+        }
+        text.push(entry.display_text());
+    }
+    if let Some((first, lang, code)) = block {
+        let lines = code.len() + 1;
+        segments.push(CodexLogSegment::Code {
+            first,
+            lines,
+            lang,
+            code: code.join("\n"),
+        });
+    }
+    if !text.is_empty() {
+        segments.push(CodexLogSegment::Text {
+            first: text_first,
+            text: text.join("\n"),
+            kind: text_kind,
+        });
+    }
+    segments
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PromptKind {
+    NewFile(PathBuf),
+    NewFolder(PathBuf),
+    Rename(PathBuf),
+    ConfirmDelete(PathBuf),
+    GotoLine,
+    QuickOpen,
+    CommandPalette,
+    UnsavedChanges(PendingAction),
+    RecoverAutosave(PathBuf),
+    DeletedOnDisk(PathBuf),
+    OpenAnyway(PathBuf),
+    ReopenEncoding,
+    SaveEncoding,
+    /// Encodage choisi incapable de representer le buffer : repli UTF-8 a confirmer.
+    ConfirmLossySave(String),
+    /// Nettoyage des caches pip/npm/pycache, tailles dans le libelle.
+    ConfirmClearCaches,
+    /// Commande shell signalee par le garde-fou, lancee seulement si confirmee.
+    ConfirmShell(String),
+    /// Dossier de sortie d'un build a ouvrir dans le gestionnaire de fichiers.
+    RevealFolder(PathBuf),
+    /// Patch de la derniere reponse Codex, apercu dans le journal, a ecrire sur disque.
+    ApplyCodexPatch(CodexPatch),
+    /// Bloc de code de la Sortie Codex a enregistrer (chemin relatif saisi).
+    SaveCodeBlock(String),
+    /// Prompt Codex en attente de confirmation (sandbox danger-full-access).
+    ConfirmDangerSandbox(String),
+    /// Question a Codex sur (source, contenu) : fichier courant ou selection.
+    CodexQuestion(String, String),
+    GitCommit,
+}
+
+/// Action differee tant que l'utilisateur n'a pas choisi de sauver/abandonner les modifs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PendingAction {
+    CloseTab(PathBuf),
+    ReloadTree,
+    Quit,
+}
+
+impl PromptKind {
+    fn is_confirmation(&self) -> bool {
+        matches!(
+            self,
+            PromptKind::ConfirmDelete(_)
+                | PromptKind::OpenAnyway(_)
+                | PromptKind::ConfirmLossySave(_)
+                | PromptKind::ConfirmDangerSandbox(_)
+                | PromptKind::ConfirmClearCaches
+                | PromptKind::ConfirmShell(_)
+                | PromptKind::RevealFolder(_)
+                | PromptKind::ApplyCodexPatch(_)
+        )
+    }
+}
+
+/// Fenetre de saisie ponctuelle (nom de fichier, confirmation...).
+struct GuiPrompt {
+    kind: PromptKind,
+    label: String,
+    input: String,
 }
 
 struct FileTree {
@@ -107,19 +462,31 @@ impl FileTree {
         tree
     }
 
+    /// Relit le disque ; la selection suit le chemin (ou son parent s'il a disparu).
     fn reload(&mut self, workspace: &WorkspacePaths) {
         self.data.reload(workspace);
-        if self.selected.is_none() {
-            self.selected = self.data.visible().first().map(|entry| entry.path.clone());
-        }
+        let idx = self
+            .selected
+            .as_deref()
+            .and_then(|path| self.data.reselect(path))
+            .unwrap_or(0);
+        self.selected = self.data.visible().get(idx).map(|entry| entry.path.clone());
     }
 
     fn toggle_dir(&mut self, path: &Path) {
         self.data.toggle_dir(path);
     }
+
+    fn selected_entry(&self) -> Option<&TreeEntry> {
+        let selected = self.selected.as_ref()?;
+        self.data
+            .visible()
+            .iter()
+            .find(|entry| &entry.path == selected)
+    }
 }
 
-pub fn run(root_dir: PathBuf) -> Result<()> {
+pub fn run(root_dir: PathBuf, file: Option<PathBuf>, watch: bool, bug_log: bool) -> Result<()> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1280.0, 820.0]),
         ..Default::default()
@@ -129,31 +496,55 @@ pub fn run(root_dir: PathBuf) -> Result<()> {
         APP_NAME,
         options,
         Box::new(move |cc| {
-            configure_style(&cc.egui_ctx);
-            Box::new(GuiApp::new(root))
+            let mut app = GuiApp::new(root);
+            configure_style(&cc.egui_ctx, app.light_theme, app.font_size);
+            if !bug_log {
+                app.core.set_bug_log_enabled(false);
+            }
+            if watch {
+                app.enable_watcher();
+            }
+            app.refresh_git_status();
+            app.detect_pythons();
+            app.detect_node();
+            if let Some(file) = file {
+                app.open_startup_file(&file);
+            }
+            app.setup_missing = app.core.setup_missing();
+            Box::new(app)
         }),
     )
     .map_err(|err| anyhow::anyhow!("Erreur interface GUI: {err}"))?;
     Ok(())
 }
 
-fn configure_style(ctx: &egui::Context) {
-    let mut visuals = egui::Visuals::dark();
-    visuals.override_text_color = Some(Color32::from_rgb(235, 238, 244));
-    visuals.window_fill = Color32::from_rgb(12, 14, 18);
-    visuals.panel_fill = Color32::from_rgb(14, 18, 24);
-    visuals.widgets.noninteractive.bg_fill = Color32::from_rgb(18, 22, 28);
-    visuals.widgets.inactive.bg_fill = Color32::from_rgb(26, 30, 38);
-    visuals.widgets.hovered.bg_fill = Color32::from_rgb(38, 30, 32);
+/// Theme sombre (accent rouge) ou clair, et tailles de police derivees de `font_size`.
+fn configure_style(ctx: &egui::Context, light: bool, font_size: f32) {
+    let mut visuals = if light {
+        let mut visuals = egui::Visuals::light();
+        visuals.widgets.hovered.bg_fill = Color32::from_rgb(250, 226, 226);
+        visuals.selection.bg_fill = Color32::from_rgb(250, 200, 200);
+        visuals.selection.stroke.color = accent_red_soft();
+        visuals
+    } else {
+        let mut visuals = egui::Visuals::dark();
+        visuals.override_text_color = Some(Color32::from_rgb(235, 238, 244));
+        visuals.window_fill = Color32::from_rgb(12, 14, 18);
+        visuals.panel_fill = Color32::from_rgb(14, 18, 24);
+        visuals.widgets.noninteractive.bg_fill = Color32::from_rgb(18, 22, 28);
+        visuals.widgets.inactive.bg_fill = Color32::from_rgb(26, 30, 38);
+        visuals.widgets.hovered.bg_fill = Color32::from_rgb(38, 30, 32);
+        visuals.selection.bg_fill = accent_red();
+        visuals.selection.stroke.color = Color32::from_rgb(255, 192, 192);
+        visuals.faint_bg_color = Color32::from_rgb(20, 24, 30);
+        visuals.code_bg_color = Color32::from_rgb(16, 20, 26);
+        visuals
+    };
     visuals.widgets.active.bg_fill = accent_red();
     visuals.widgets.noninteractive.rounding = egui::Rounding::same(6.0);
     visuals.widgets.inactive.rounding = egui::Rounding::same(6.0);
     visuals.widgets.hovered.rounding = egui::Rounding::same(6.0);
     visuals.widgets.active.rounding = egui::Rounding::same(6.0);
-    visuals.selection.bg_fill = accent_red();
-    visuals.selection.stroke.color = Color32::from_rgb(255, 192, 192);
-    visuals.faint_bg_color = Color32::from_rgb(20, 24, 30);
-    visuals.code_bg_color = Color32::from_rgb(16, 20, 26);
     ctx.set_visuals(visuals);
 
     let mut style = (*ctx.style()).clone();
@@ -163,15 +554,19 @@ fn configure_style(ctx: &egui::Context) {
     style.spacing.interact_size = egui::vec2(36.0, 24.0);
     style.text_styles.insert(
         egui::TextStyle::Heading,
-        egui::FontId::new(19.0, egui::FontFamily::Proportional),
+        egui::FontId::new(font_size + 5.5, egui::FontFamily::Proportional),
     );
     style.text_styles.insert(
         egui::TextStyle::Body,
-        egui::FontId::new(14.5, egui::FontFamily::Proportional),
+        egui::FontId::new(font_size + 1.0, egui::FontFamily::Proportional),
+    );
+    style.text_styles.insert(
+        egui::TextStyle::Button,
+        egui::FontId::new(font_size + 1.0, egui::FontFamily::Proportional),
     );
     style.text_styles.insert(
         egui::TextStyle::Monospace,
-        egui::FontId::new(13.5, egui::FontFamily::Monospace),
+        egui::FontId::new(font_size, egui::FontFamily::Monospace),
     );
     ctx.set_style(style);
 }
@@ -179,35 +574,138 @@ fn configure_style(ctx: &egui::Context) {
 struct GuiApp {
     root_dir: PathBuf,
     core: AppCore,
-    current: Option<OpenFile>,
-    editor_text: String,
+    tabs: EditorTabs<String>,
     tree: FileTree,
     cmd_input: String,
     codex_input: String,
     log: Vec<LogLine>,
     codex_log: Vec<LogLine>,
+    log_filter: LogFilter,
+    codex_log_filter: LogFilter,
     title: String,
     sub_title: String,
-    codex_compact_view: bool,
+    codex_view: CodexView,
     codex_sandbox_mode: CodexSandboxMode,
     codex_approval_policy: CodexApprovalPolicy,
+    codex_model: String,
     codex_sandbox_supported: Option<bool>,
     codex_approval_supported: Option<bool>,
     codex_exec_used_sandbox_flag: bool,
     codex_exec_used_approval_flag: bool,
     codex_last_prompt: Option<String>,
+    codex_history: CodexHistory,
+    cmd_history: CommandHistory,
+    shell_cwd: PathBuf,
+    /// Interpreteur Python affiche dans la barre d'etat (venv actif ou commande de base).
+    python_label: String,
+    /// Derniere etape pip/npm reconnue de l'installation en cours, et son panneau.
+    install_progress: Option<(LogTarget, String)>,
+    /// Outils dev / PyInstaller presents (libelles de la barre d'outils).
+    tool_status: ToolStatus,
+    /// Fait defiler l'arborescence jusqu'a la selection a la prochaine frame (Alt+R).
+    tree_scroll_to_selected: bool,
+    /// Build PyInstaller en un seul executable (--onefile) plutot qu'un dossier (--onedir).
+    build_onefile: bool,
+    /// Artefact attendu du build en cours, annonce a la fin du process.
+    build_output: Option<PathBuf>,
+    /// Fichier en cours de formatage, relu a la fin du process.
+    format_target: Option<PathBuf>,
+    /// Fichier du dernier lint ; ses diagnostics sont remplaces a chaque relance.
+    lint_target: Option<PathBuf>,
+    /// Resume du dernier passage pytest, affiche dans la barre d'etat.
+    pytest_summary: Option<PytestSummary>,
+    /// Sortie du `git status` en cours, analysee a la fin du process.
+    git_status_lines: Vec<String>,
+    git_status_running: bool,
+    /// `.git` present au dernier rafraichissement : affiche les boutons Git.
+    git_repo: bool,
+    /// Rafraichissement demande pendant un `git status` : relance a sa fin.
+    git_status_stale: bool,
+    /// Message a committer une fois `git add -A` termine.
+    git_commit_message: Option<String>,
+    git_commit_hash: Option<String>,
+    git_diff_lines: usize,
+    /// Diagnostics ruff/mypy, par fichier.
+    diagnostics: Diagnostics,
+    show_diagnostics: bool,
+    /// Icone (.ico/.icns) et nom optionnels du build PyInstaller.
+    build_icon: String,
+    build_name: String,
+    editor_highlight: bool,
+    /// Retour a la ligne automatique de l'editeur et des logs (persiste dans la session).
+    word_wrap: bool,
+    /// Theme clair et taille de police monospace (persistes dans la session).
+    light_theme: bool,
+    font_size: f32,
+    codex_continue_session: bool,
+    codex_turn_response: String,
     codex_retry_without_sandbox: bool,
     codex_retry_without_approval: bool,
+    /// Debut du pre-check `codex login status` en cours (indicateur d'attente).
+    codex_status_started: Option<Instant>,
     codex_caps_checked: bool,
     codex_caps_running: bool,
     codex_caps_buffer: String,
-    codex_log_buffer: String,
+    codex_log_segments: Vec<CodexLogSegment>,
     codex_log_dirty: bool,
     last_codex_message: Option<String>,
+    /// Dernier message assistant complet, pour la copie (Alt+C).
+    last_assistant_message: Option<String>,
     codex_assistant_buffer: String,
-    pending_codex_prompt: Option<String>,
+    /// Texte des deltas deja affiche pendant ce tour : le message final qui le repete est ignore.
+    codex_streamed_text: String,
     codex_follow_output: bool,
+    /// Journal / Sortie Codex affiches jusqu'en bas a la derniere frame (sinon : historique).
+    log_at_bottom: bool,
+    codex_log_at_bottom: bool,
+    /// Panneau (Commande ou Codex) dont la saisie a eu le focus en dernier, vise par Ctrl+L.
+    log_focus: LogTarget,
+    /// Fenetre de recherche dans les logs (Ctrl+Alt+F).
+    log_search: Option<LogSearch>,
+    /// Defiler jusqu'au resultat courant a la prochaine frame.
+    log_search_scroll: bool,
+    /// Mode zen (Alt+M) : arbre et panneaux du bas masques, l'editeur prend la fenetre.
+    zen_mode: bool,
     last_window_title: String,
+    prompt: Option<GuiPrompt>,
+    /// Fichiers pour Ctrl+P, construit a la premiere ouverture et vide a chaque reload.
+    file_index: Option<FileIndex>,
+    quick_open_selected: usize,
+    palette_selected: usize,
+    /// Copie editee dans la fenetre Parametres (None : fermee).
+    settings_draft: Option<Settings>,
+    /// Outils dev de la fenetre Ctrl+D (None : fermee) et paquets saisis en plus.
+    dev_tools: Option<Vec<DevToolEntry>>,
+    dev_tools_extra: String,
+    /// Rapport d'environnement affiche (None : fenetre fermee).
+    environment_report: Option<Vec<String>>,
+    /// Fenetre "Python" ouverte (Alt+Shift+P).
+    show_python_picker: bool,
+    /// Prerequis absents : fenetre "Premier lancement" tant que la liste n'est pas vide.
+    setup_missing: Vec<SetupMissing>,
+    /// Profil de la fenetre "Variables d'environnement" (None : fermee) et saisie CLE=valeur.
+    env_inspector: Option<EnvProfile>,
+    env_assignment: String,
+    /// Fenetre "Python REPL" ouverte, sa saisie et la sortie du REPL.
+    show_repl: bool,
+    repl_input: String,
+    repl_output: Vec<String>,
+    /// Ligne et colonne (0-based) ou placer le curseur au prochain affichage de l'editeur.
+    pending_goto: Option<(usize, usize)>,
+    editor_cursor: (usize, usize),
+    editor_line_count: usize,
+    editor_position: String,
+    /// Taille, mots du fichier courant (None pour un apercu ou sans fichier).
+    doc_stats: Option<DocumentStats>,
+    last_session_save: Instant,
+    /// Derniere verification des fichiers ouverts sans surveillance du workspace.
+    last_disk_check: Instant,
+    last_autosave: Instant,
+    files_panel_width: Option<f32>,
+    bottom_panel_height: Option<f32>,
+    watcher: Option<WorkspaceWatcher>,
+    quit_confirmed: bool,
+    keymap: Keymap,
 }
 
 impl GuiApp {
@@ -216,42 +714,121 @@ impl GuiApp {
             Ok(path) => path,
             Err(_) => root_dir,
         };
-        let core = AppCore::new(root_dir.clone());
+        let mut core = AppCore::new(root_dir.clone());
+        let settings_errors = core.load_settings();
         let tree = FileTree::new(core.workspace());
+        let codex_history = CodexHistory::load(core.workspace());
+        let cmd_history = CommandHistory::load(core.workspace());
+        let (keymap, keymap_errors) = Keymap::load(core.workspace().keymap_path());
+        let shell_cwd = root_dir.clone();
+        let python_label = core.python_status_label();
+        let tool_status = core.tool_status();
+        let codex_view = core.settings().codex_view();
+        let codex_sandbox_mode = core.settings().codex_sandbox();
+        let codex_approval_policy = core.settings().codex_approval();
+        let codex_model = core.settings().codex_model();
         let mut app = Self {
             root_dir,
             core,
-            current: None,
-            editor_text: String::new(),
+            tabs: EditorTabs::default(),
             tree,
             cmd_input: String::new(),
             codex_input: String::new(),
             log: Vec::new(),
             codex_log: Vec::new(),
+            log_filter: LogFilter::default(),
+            codex_log_filter: LogFilter::default(),
             title: APP_NAME.to_string(),
             sub_title: String::new(),
-            codex_compact_view: true,
-            codex_sandbox_mode: codex_sandbox_mode_from_env(),
-            codex_approval_policy: codex_approval_policy_from_env(),
+            codex_view,
+            codex_sandbox_mode,
+            codex_approval_policy,
+            codex_model,
             codex_sandbox_supported: None,
             codex_approval_supported: None,
             codex_exec_used_sandbox_flag: false,
             codex_exec_used_approval_flag: false,
             codex_last_prompt: None,
+            codex_history,
+            cmd_history,
+            shell_cwd,
+            python_label,
+            tool_status,
+            tree_scroll_to_selected: false,
+            build_onefile: false,
+            build_output: None,
+            format_target: None,
+            lint_target: None,
+            pytest_summary: None,
+            git_status_lines: Vec::new(),
+            git_status_running: false,
+            git_repo: false,
+            git_status_stale: false,
+            git_commit_message: None,
+            git_commit_hash: None,
+            git_diff_lines: 0,
+            diagnostics: Diagnostics::default(),
+            show_diagnostics: false,
+            build_icon: String::new(),
+            build_name: String::new(),
+            editor_highlight: true,
+            word_wrap: true,
+            light_theme: false,
+            font_size: DEFAULT_FONT_SIZE,
+            codex_continue_session: false,
+            codex_turn_response: String::new(),
             codex_retry_without_sandbox: false,
             codex_retry_without_approval: false,
+            codex_status_started: None,
             codex_caps_checked: false,
             codex_caps_running: false,
             codex_caps_buffer: String::new(),
-            codex_log_buffer: String::new(),
+            codex_log_segments: Vec::new(),
             codex_log_dirty: true,
             last_codex_message: None,
+            last_assistant_message: None,
             codex_assistant_buffer: String::new(),
-            pending_codex_prompt: None,
+            codex_streamed_text: String::new(),
             codex_follow_output: true,
+            log_at_bottom: true,
+            codex_log_at_bottom: true,
+            log_focus: LogTarget::Main,
+            log_search: None,
+            log_search_scroll: false,
+            zen_mode: false,
             last_window_title: String::new(),
+            prompt: None,
+            file_index: None,
+            quick_open_selected: 0,
+            palette_selected: 0,
+            settings_draft: None,
+            install_progress: None,
+            dev_tools: None,
+            dev_tools_extra: String::new(),
+            environment_report: None,
+            show_python_picker: false,
+            env_inspector: None,
+            env_assignment: String::new(),
+            show_repl: false,
+            repl_input: String::new(),
+            repl_output: Vec::new(),
+            setup_missing: Vec::new(),
+            pending_goto: None,
+            editor_cursor: (0, 0),
+            editor_line_count: 1,
+            editor_position: editor_position_label(0, 0, 1),
+            doc_stats: None,
+            last_session_save: Instant::now(),
+            last_disk_check: Instant::now(),
+            last_autosave: Instant::now(),
+            files_panel_width: None,
+            bottom_panel_height: None,
+            watcher: None,
+            quit_confirmed: false,
+            keymap,
         };
         app.core.ensure_portable_dirs();
+        app.restore_session();
         app.refresh_title();
         app.log_ui(format!(
             "{APP_NAME}\nRoot: {}\nAstuce: lance la version TUI avec --ui tui si besoin.\n",
@@ -265,8 +842,258 @@ impl GuiApp {
             "Approbations Codex: {}",
             codex_approval_label(app.codex_approval_policy)
         ));
+        if !app.codex_model.is_empty() {
+            app.codex_log_ui(format!("Modele Codex: {}", app.codex_model));
+        }
+        if let Some(msg) = app.core.external_venv_warning() {
+            app.log_issue(&msg, "avertissement", "venv", LogTarget::Main);
+        }
+        for err in settings_errors {
+            app.log_issue(
+                &format!("settings.toml ignore (defaut garde): {err}"),
+                "avertissement",
+                "settings",
+                LogTarget::Main,
+            );
+        }
+        for err in keymap_errors {
+            app.log_issue(
+                &format!("keymap.toml ignore (defaut garde): {err}"),
+                "avertissement",
+                "keymap",
+                LogTarget::Main,
+            );
+        }
         app
     }
+
+    /// Ouvre le fichier passe en ligne de commande (apres la restauration de session).
+    fn open_startup_file(&mut self, file: &Path) {
+        match validate_startup_file(&self.root_dir, file) {
+            Ok(path) => {
+                self.load_file(path.clone());
+                self.tree.selected = Some(path);
+            }
+            Err(msg) => self.log_issue(&msg, "erreur", "demarrage", LogTarget::Main),
+        }
+    }
+
+    fn enable_watcher(&mut self) {
+        match WorkspaceWatcher::new(self.core.workspace()) {
+            Ok(watcher) => {
+                self.watcher = Some(watcher);
+                self.log_ui("Surveillance du workspace active.".to_string());
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Surveillance du workspace impossible: {err}"),
+                    "avertissement",
+                    "surveillance",
+                    LogTarget::Main,
+                );
+            }
+        }
+    }
+
+    fn poll_watcher(&mut self) {
+        if self.watcher.is_none() {
+            // Sans --watch : seule la disparition des fichiers ouverts est verifiee.
+            if self.last_disk_check.elapsed() >= DISK_CHECK_INTERVAL {
+                self.last_disk_check = Instant::now();
+                self.check_missing_files();
+            }
+            return;
+        }
+        let Some(changed) = self.watcher.as_mut().and_then(WorkspaceWatcher::poll) else {
+            return;
+        };
+        self.tree.reload(self.core.workspace());
+        self.file_index = None;
+        self.refresh_git_status();
+        self.sync_tabs_with_disk(&changed);
+    }
+
+    /// Signale chaque onglet dont le fichier a disparu du disque (une fois par disparition).
+    fn check_missing_files(&mut self) {
+        for idx in self.tabs.newly_missing() {
+            if let Some(path) = self.tabs.get(idx).map(|tab| tab.file.path.clone()) {
+                self.on_file_deleted(idx, path);
+            }
+        }
+    }
+
+    fn sync_tabs_with_disk(&mut self, changed: &[PathBuf]) {
+        for idx in 0..self.tabs.len() {
+            self.sync_tab_with_disk(idx, changed);
+        }
+    }
+
+    /// Recharge l'onglet si son fichier a change sur disque (sauf modifications locales).
+    fn sync_tab_with_disk(&mut self, idx: usize, changed: &[PathBuf]) {
+        let Some(current) = self.tabs.get(idx).map(|tab| &tab.file) else {
+            return;
+        };
+        // Un evenement sur un dossier parent (supprime ou renomme) concerne aussi le fichier.
+        if !changed
+            .iter()
+            .any(|changed| current.path.starts_with(changed))
+        {
+            return;
+        }
+        if !current.path.exists() {
+            let path = current.path.clone();
+            if self.tabs.mark_missing(idx) {
+                self.on_file_deleted(idx, path);
+            }
+            return;
+        }
+        let exact = changed.contains(&current.path);
+        let Some(current) = self.tabs.get_mut(idx).map(|tab| &mut tab.file) else {
+            return;
+        };
+        current.missing_on_disk = false;
+        if !exact {
+            return;
+        }
+        if !current.path.is_file() {
+            return;
+        }
+        let path = current.path.clone();
+        let encoding = current.encoding.clone();
+        // Apercu fige : il se recharge en rouvrant le fichier.
+        if current.read_only() {
+            return;
+        }
+        if current.dirty {
+            self.log_issue(
+                &format!(
+                    "Fichier modifie sur disque: {} (modifications locales conservees)",
+                    path.display()
+                ),
+                "avertissement",
+                "surveillance",
+                LogTarget::Main,
+            );
+            return;
+        }
+        match read_text_with_encoding(&path, &encoding) {
+            Ok(text) => {
+                let Some(tab) = self.tabs.get_mut(idx) else {
+                    return;
+                };
+                tab.file.bom = has_bom(&path);
+                let normalized = apply_line_ending(&text, LineEnding::Lf);
+                if normalized == tab.buffer {
+                    return;
+                }
+                tab.file.line_ending = detect_line_ending(&text);
+                tab.buffer = normalized;
+                if self.tabs.active_index() == Some(idx) {
+                    self.on_tab_change();
+                }
+                self.log_ui(format!("Recharge depuis le disque: {}", path.display()));
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur relecture: {} ({err})", path.display()),
+                    "erreur",
+                    "surveillance",
+                    LogTarget::Main,
+                );
+            }
+        }
+    }
+
+    /// Fichier de l'onglet supprime hors de l'IDE (git checkout, Codex...) : le contenu
+    /// ne vit plus que dans l'editeur, on propose de le recreer ou de fermer l'onglet.
+    fn on_file_deleted(&mut self, idx: usize, path: PathBuf) {
+        if let Some(tab) = self.tabs.get_mut(idx) {
+            tab.file.dirty = true;
+        }
+        self.refresh_title();
+        self.log_issue(
+            &format!("Fichier supprime sur le disque: {}", path.display()),
+            "avertissement",
+            "surveillance",
+            LogTarget::Main,
+        );
+        if self.prompt.is_none() {
+            let label = format!("{} supprime sur le disque", tab_name(&path));
+            self.open_prompt(PromptKind::DeletedOnDisk(path), &label);
+        }
+    }
+
+    /// Reecrit le fichier supprime avec le contenu de l'onglet (dossiers parents recrees).
+    fn recreate_deleted(&mut self, path: &Path) {
+        let Some(idx) = self.tabs.position(path) else {
+            return;
+        };
+        if let Some(parent) = path.parent()
+            && let Err(err) = std::fs::create_dir_all(parent)
+        {
+            self.log_issue(
+                &format!("Impossible de recreer {}: {err}", parent.display()),
+                "erreur",
+                "sauvegarde",
+                LogTarget::Main,
+            );
+            return;
+        }
+        self.save_tab(idx);
+    }
+
+    fn close_deleted(&mut self, path: &Path) {
+        self.discard_autosave(path);
+        self.close_tab(path);
+    }
+
+    fn restore_session(&mut self) {
+        let session = Session::load(self.core.workspace());
+        self.word_wrap = session.word_wrap;
+        self.light_theme = session.light_theme;
+        self.font_size = session
+            .font_size
+            .unwrap_or(DEFAULT_FONT_SIZE)
+            .clamp(FONT_SIZE_MIN, FONT_SIZE_MAX);
+        self.tree.data.set_show_hidden(session.show_hidden);
+        self.tree.data.restore_expanded(&session.expanded);
+        self.files_panel_width = session.panel("files");
+        self.bottom_panel_height = session.panel("bottom");
+        if let Some(path) = session.existing_current_file() {
+            let path = path.to_path_buf();
+            self.load_file(path.clone());
+            self.tree.selected = Some(path);
+        }
+    }
+
+    fn save_session(&mut self) {
+        self.last_session_save = Instant::now();
+        let mut session = Session {
+            current_file: self.current().map(|current| current.path.clone()),
+            expanded: self.tree.data.expanded_paths(),
+            word_wrap: self.word_wrap,
+            show_hidden: self.tree.data.show_hidden(),
+            light_theme: self.light_theme,
+            font_size: (self.font_size != DEFAULT_FONT_SIZE).then_some(self.font_size),
+            // Garde les decoupages de la TUI (tui_tree, tui_editor).
+            panels: Session::load(self.core.workspace()).panels,
+        };
+        if let Some(width) = self.files_panel_width {
+            session.panels.insert("files".to_string(), width);
+        }
+        if let Some(height) = self.bottom_panel_height {
+            session.panels.insert("bottom".to_string(), height);
+        }
+        if let Err(err) = session.save(self.core.workspace()) {
+            self.log_issue(
+                &format!("Sauvegarde session impossible: {err}"),
+                "avertissement",
+                "session",
+                LogTarget::Main,
+            );
+        }
+    }
+
     fn update_window_title(&mut self, ctx: &egui::Context) {
         let title = if self.sub_title.is_empty() {
             self.title.clone()
@@ -280,53 +1107,144 @@ impl GuiApp {
     }
 
     fn handle_shortcuts(&mut self, ctx: &egui::Context) {
-        if ctx.input(|i| i.key_pressed(egui::Key::S) && i.modifiers.ctrl) {
-            self.action_save();
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::F5)) {
-            self.action_run();
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::L) && i.modifiers.ctrl) {
-            self.action_clear_log();
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::R) && i.modifiers.ctrl) {
-            self.action_reload_tree();
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::K) && i.modifiers.ctrl) {
-            self.action_codex_login();
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::T) && i.modifiers.ctrl) {
-            self.action_codex_check();
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::I) && i.modifiers.ctrl) {
-            self.action_codex_install();
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::M) && i.modifiers.ctrl) {
-            self.action_toggle_codex_view();
+        let actions: Vec<Action> = ctx.input(|i| {
+            self.keymap
+                .chords()
+                .filter(|(chord, _)| chord_pressed(i, chord))
+                .map(|(_, action)| *action)
+                .collect()
+        });
+        for action in actions {
+            self.run_action(ctx, action);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::E) && i.modifiers.ctrl) {
-            self.action_build_exe();
+        // F2/Suppr ne visent l'arborescence que si aucun champ texte n'a le focus.
+        if !ctx.wants_keyboard_input() {
+            if ctx.input(|i| i.key_pressed(egui::Key::F2)) {
+                self.action_rename_entry();
+            }
+            if ctx.input(|i| i.key_pressed(egui::Key::Delete)) {
+                self.action_delete_entry();
+            }
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::D) && i.modifiers.ctrl) {
-            self.action_dev_tools();
+    }
+
+    fn run_action(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
+            Action::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            Action::Save => self.action_save(),
+            Action::Run => self.action_run(),
+            Action::RunSelection => self.action_run_selection(ctx),
+            Action::CodexStop => self.action_codex_stop(),
+            Action::ClearLog => self.action_clear_log(),
+            Action::ClearMainLog => self.clear_log(LogTarget::Main),
+            Action::ClearCodexLog => self.clear_log(LogTarget::Codex),
+            Action::FindInLog => self.action_find_in_log(),
+            Action::ToggleZenMode => self.action_toggle_zen_mode(),
+            Action::ClearCodexQueue => {
+                if !self.clear_codex_queue() {
+                    self.codex_log_ui("File d'attente Codex vide.".to_string());
+                }
+            }
+            Action::ApplyCodexPatch => self.action_apply_codex_patch(),
+            Action::OpenContainingFolder => {
+                let dir = target_dir_for(self.tree.selected_entry(), &self.root_dir);
+                self.open_in_file_manager(&dir);
+            }
+            Action::ClearCaches => self.action_clear_caches(),
+            Action::SelectPython => self.show_python_picker = true,
+            Action::EnvInspector => self.env_inspector = Some(EnvProfile::Shell),
+            Action::PythonRepl => {
+                self.start_repl();
+                self.show_repl = true;
+            }
+            Action::CollapseTree => self.action_fold_tree(false),
+            Action::ExpandTree => self.action_fold_tree(true),
+            Action::ExportLogs => self.action_export_logs(),
+            Action::ReloadTree => self.action_reload_tree(),
+            Action::CodexLogin => self.action_codex_login(),
+            Action::CodexLogout => self.action_codex_logout(),
+            Action::KillAllProcesses => self.action_kill_all_processes(),
+            Action::CodexCheck => self.action_codex_check(),
+            Action::CodexInstall => self.action_codex_install(),
+            Action::ToggleCodexView => self.action_toggle_codex_view(),
+            Action::ToggleCodexSandbox => self.action_toggle_codex_sandbox(),
+            Action::ToggleCodexApproval => self.action_toggle_codex_approval(),
+            Action::ToggleCodexSession => self.action_toggle_codex_session(),
+            Action::QuickOpen => self.action_quick_open(),
+            Action::BuildExe => self.action_build_exe(),
+            Action::DevTools => self.action_dev_tools(),
+            // Recherche et remplacement : ceux de l'editeur egui.
+            Action::Search | Action::Replace => {}
+            Action::GotoLine => self.open_prompt(PromptKind::GotoLine, "Aller a la ligne"),
+            Action::NewFile => self.action_new_entry(false),
+            Action::NewFolder => self.action_new_entry(true),
+            Action::ToggleLineEnding => self.action_toggle_line_ending(),
+            Action::InstallRequirements => self.action_install_requirements(),
+            Action::CreateVenv => self.action_create_venv(),
+            Action::ToggleOnefile => self.action_toggle_build_onefile(),
+            Action::RevealCurrent => self.action_reveal_current(),
+            Action::ToggleShowHidden => self.action_toggle_show_hidden(),
+            Action::ToggleShowIgnored => self.action_toggle_show_ignored(),
+            Action::CopyLastAssistant => self.action_copy_last_assistant(ctx),
+            Action::CopyCodexLog => self.action_copy_codex_log(ctx),
+            Action::CycleLogFilter => self.action_cycle_log_filter(LogTarget::Main),
+            Action::CycleCodexLogFilter => self.action_cycle_log_filter(LogTarget::Codex),
+            Action::ToggleWordWrap => self.action_toggle_word_wrap(),
+            Action::Settings => self.action_settings(),
+            Action::NextTab => self.action_cycle_tab(true),
+            Action::PrevTab => self.action_cycle_tab(false),
+            Action::CloseTab => self.action_close_tab(),
+            Action::Format => self.action_format(),
+            Action::Lint => self.action_lint(),
+            Action::Diagnostics => self.action_diagnostics(),
+            Action::EnvironmentReport => self.action_environment_report(),
+            Action::RunTests => self.action_run_tests(false),
+            Action::RerunFailedTests => self.action_run_tests(true),
+            Action::StopTests => self.action_stop_tests(),
+            Action::GitCommit => self.action_git_commit(),
+            Action::GitDiff => self.action_git_diff(),
+            Action::DiffWithDisk => self.action_diff_with_disk(),
+            Action::AskCodexAboutFile => self.action_ask_codex_about_file(ctx),
+            Action::ExplainSelection => self.action_explain_selection(ctx),
+            Action::ReopenWithEncoding => self.action_encoding_prompt(PromptKind::ReopenEncoding),
+            Action::SaveWithEncoding => self.action_encoding_prompt(PromptKind::SaveEncoding),
+            Action::CommandPalette => self.action_command_palette(),
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::Q) && i.modifiers.ctrl) {
+    }
+
+    /// Intercepte la fermeture (Ctrl+Q, bouton Quitter, croix) si le fichier est modifie.
+    fn handle_close_request(&mut self, ctx: &egui::Context) {
+        if self.quit_confirmed {
             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+        if ctx.input(|i| i.viewport().close_requested())
+            && !self.confirm_discard(PendingAction::Quit)
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
         }
     }
 
     fn panel_frame(ui: &egui::Ui) -> egui::Frame {
+        let visuals = ui.visuals();
         egui::Frame::group(ui.style())
-            .fill(panel_bg())
-            .stroke(egui::Stroke::new(1.0, panel_border()))
+            .fill(themed_bg(visuals, panel_bg(), 0.92))
+            .stroke(egui::Stroke::new(
+                1.0,
+                themed_bg(visuals, panel_border(), 0.7),
+            ))
             .rounding(egui::Rounding::same(6.0))
             .inner_margin(egui::Margin::same(10.0))
     }
 
     fn toolbar_group<F: FnOnce(&mut egui::Ui)>(ui: &mut egui::Ui, add: F) {
+        let visuals = ui.visuals();
         egui::Frame::none()
-            .fill(Color32::from_rgb(20, 24, 30))
-            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(40, 46, 58)))
+            .fill(themed_bg(visuals, Color32::from_rgb(20, 24, 30), 0.92))
+            .stroke(egui::Stroke::new(
+                1.0,
+                themed_bg(visuals, Color32::from_rgb(40, 46, 58), 0.7),
+            ))
             .rounding(egui::Rounding::same(6.0))
             .inner_margin(egui::Margin::symmetric(8.0, 4.0))
             .show(ui, |ui| {
@@ -341,13 +1259,13 @@ impl GuiApp {
         ui.label(
             RichText::new(label)
                 .strong()
-                .color(Color32::from_rgb(235, 235, 240)),
+                .color(themed_fg(ui.visuals(), Color32::from_rgb(235, 235, 240))),
         );
     }
 
     fn draw_header(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            let title = if self.current.as_ref().map(|f| f.dirty).unwrap_or(false) {
+            let title = if self.current().map(|f| f.dirty).unwrap_or(false) {
                 format!("{APP_NAME} *")
             } else {
                 APP_NAME.to_string()
@@ -356,7 +1274,7 @@ impl GuiApp {
                 RichText::new(title)
                     .strong()
                     .size(20.0)
-                    .color(Color32::from_rgb(245, 245, 250)),
+                    .color(themed_fg(ui.visuals(), Color32::from_rgb(245, 245, 250))),
             );
             ui.add_space(8.0);
             ui.label(
@@ -364,6 +1282,51 @@ impl GuiApp {
                     .color(Color32::from_gray(150))
                     .monospace(),
             );
+            ui.add_space(8.0);
+            let python = ui
+                .add(
+                    egui::Label::new(
+                        RichText::new(format!("Python: {}", self.python_label))
+                            .color(Color32::from_gray(120))
+                            .monospace(),
+                    )
+                    .sense(egui::Sense::click()),
+                )
+                .on_hover_text("Choisir l'interpreteur (Alt+Shift+P)");
+            if python.clicked() {
+                self.show_python_picker = true;
+            }
+            if self.zen_mode {
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new(" zen ")
+                        .color(Color32::BLACK)
+                        .background_color(Color32::from_rgb(120, 200, 220)),
+                )
+                .on_hover_text("Alt+M pour retrouver l'arbre et les panneaux du bas");
+            }
+            if self.core.settings().offline() {
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new(" hors-ligne ")
+                        .color(Color32::BLACK)
+                        .background_color(Color32::from_rgb(230, 190, 80)),
+                )
+                .on_hover_text("pip limite au wheelhouse (tools/wheels), npm a son cache");
+            }
+            if let Some((label, ok)) = self.test_status() {
+                let color = if ok {
+                    Color32::from_rgb(120, 220, 160)
+                } else {
+                    Color32::from_rgb(240, 100, 100)
+                };
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new(label)
+                        .color(themed_fg(ui.visuals(), color))
+                        .monospace(),
+                );
+            }
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui
                     .add(
@@ -386,52 +1349,618 @@ impl GuiApp {
                 if ui.button("Sauver").clicked() {
                     self.action_save();
                 }
+                if ui
+                    .button("Diff disque")
+                    .on_hover_text(
+                        "Changements non enregistres par rapport au disque (Alt+Shift+D)",
+                    )
+                    .clicked()
+                {
+                    self.action_diff_with_disk();
+                }
                 if ui.button("Executer (F5)").clicked() {
                     self.action_run();
                 }
-                if ui.button("Reload").clicked() {
-                    self.action_reload_tree();
-                }
-                if ui.button("Vider logs").clicked() {
-                    self.action_clear_log();
-                }
-            });
-            Self::toolbar_group(ui, |ui| {
-                if ui.button("Codex login").clicked() {
-                    self.action_codex_login();
+                if ui
+                    .button("Formater")
+                    .on_hover_text("black ou ruff format sur le fichier courant (Alt+F)")
+                    .clicked()
+                {
+                    self.action_format();
                 }
-                if ui.button("Codex status").clicked() {
-                    self.action_codex_check();
+                if ui
+                    .button("Lint")
+                    .on_hover_text("ruff check et mypy sur le fichier courant (Alt+K)")
+                    .clicked()
+                {
+                    self.action_lint();
                 }
-                if ui.button("Codex install").clicked() {
-                    self.action_codex_install();
+                if self.tests_running() {
+                    if ui.button("Stop tests").clicked() {
+                        self.action_stop_tests();
+                    }
+                } else {
+                    if ui
+                        .button("Tests")
+                        .on_hover_text("pytest a la racine (Alt+T, USBIDE_PYTEST_ARGS)")
+                        .clicked()
+                    {
+                        self.action_run_tests(false);
+                    }
+                    if ui
+                        .button("Echecs")
+                        .on_hover_text("Relancer les tests en echec : pytest --lf (Alt+Shift+T)")
+                        .clicked()
+                    {
+                        self.action_run_tests(true);
+                    }
+                }
+                if self.git_repo {
+                    if ui
+                        .button("Commit")
+                        .on_hover_text("git add -A puis git commit (Alt+G)")
+                        .clicked()
+                    {
+                        self.action_git_commit();
+                    }
+                    if ui
+                        .button("Diff")
+                        .on_hover_text(
+                            "git diff HEAD du fichier courant dans le journal (Alt+Shift+G)",
+                        )
+                        .clicked()
+                    {
+                        self.action_git_diff();
+                    }
+                }
+                let label = format!("Diagnostics ({})", self.diagnostics.len());
+                if ui
+                    .selectable_label(self.show_diagnostics, label)
+                    .on_hover_text("Liste des diagnostics du dernier lint (Alt+D)")
+                    .clicked()
+                {
+                    self.show_diagnostics = !self.show_diagnostics;
+                }
+                if ui.button("Reload").clicked() {
+                    self.action_reload_tree();
+                }
+                if ui.button("Nouveau fichier").clicked() {
+                    self.action_new_entry(false);
+                }
+                if ui.button("Nouveau dossier").clicked() {
+                    self.action_new_entry(true);
+                }
+                if ui.button("Renommer (F2)").clicked() {
+                    self.action_rename_entry();
+                }
+                if ui.button("Corbeille").clicked() {
+                    self.action_delete_entry();
+                }
+                if ui.button("Vider logs").clicked() {
+                    self.action_clear_all_logs();
+                }
+                if ui.button("Exporter logs").clicked() {
+                    self.action_export_logs();
+                }
+                if ui
+                    .selectable_label(self.word_wrap, "Retour ligne")
+                    .on_hover_text("Retour a la ligne de l'editeur et des logs (Alt+Z)")
+                    .clicked()
+                {
+                    self.action_toggle_word_wrap();
+                }
+                if ui
+                    .selectable_label(self.zen_mode, "Zen")
+                    .on_hover_text("Editeur seul, sans arbre ni panneaux du bas (Alt+M)")
+                    .clicked()
+                {
+                    self.action_toggle_zen_mode();
+                }
+                if ui
+                    .small_button("A-")
+                    .on_hover_text("Reduire la police")
+                    .clicked()
+                {
+                    self.action_font_size(ui.ctx(), -1.0);
+                }
+                if ui
+                    .small_button("A+")
+                    .on_hover_text("Agrandir la police")
+                    .clicked()
+                {
+                    self.action_font_size(ui.ctx(), 1.0);
                 }
-                let mode_label = if self.codex_compact_view {
-                    "Codex: Compact"
+                let theme_label = if self.light_theme {
+                    "Theme: Clair"
                 } else {
-                    "Codex: Brut"
+                    "Theme: Sombre"
                 };
+                if ui.button(theme_label).clicked() {
+                    self.action_toggle_theme(ui.ctx());
+                }
+            });
+            Self::toolbar_group(ui, |ui| {
+                let running = self.core.running.len();
+                if ui
+                    .add_enabled(
+                        running > 0,
+                        egui::Button::new(format!("Tout arreter ({running})")),
+                    )
+                    .on_hover_text("Tue tous les process en cours (Ctrl+Shift+X)")
+                    .clicked()
+                {
+                    self.action_kill_all_processes();
+                }
+            });
+            Self::toolbar_group(ui, |ui| {
+                if ui.button("Codex login").clicked() {
+                    self.action_codex_login();
+                }
+                if ui.button("Codex logout").clicked() {
+                    self.action_codex_logout();
+                }
+                if ui.button("Codex status").clicked() {
+                    self.action_codex_check();
+                }
+                if ui.button("Codex install").clicked() {
+                    self.action_codex_install();
+                }
+                if ui
+                    .button("Environnement")
+                    .on_hover_text(
+                        "Chemins node/npm/Codex/Python et variables portables (Alt+Shift+E)",
+                    )
+                    .clicked()
+                {
+                    self.action_environment_report();
+                }
+                let mode_label = format!("Codex: {}", self.codex_view.label());
                 if ui.button(mode_label).clicked() {
                     self.action_toggle_codex_view();
                 }
             });
             Self::toolbar_group(ui, |ui| {
-                if ui.button("Outils dev").clicked() {
+                let dev_hint = if self.tool_status.dev_tools_missing == 0 {
+                    "Tous les outils de la liste sont presents"
+                } else {
+                    "Choisir les outils a installer (Ctrl+D)"
+                };
+                if ui
+                    .button(self.tool_status.dev_tools_label())
+                    .on_hover_text(dev_hint)
+                    .clicked()
+                {
                     self.action_dev_tools();
                 }
-                if ui.button("Build EXE").clicked() {
+                if ui
+                    .button("Parametres")
+                    .on_hover_text(".usbide/settings.toml")
+                    .clicked()
+                {
+                    self.action_settings();
+                }
+                if ui
+                    .button("Requirements")
+                    .on_hover_text("pip install -r requirements.txt (F8)")
+                    .clicked()
+                {
+                    self.action_install_requirements();
+                }
+                if ui
+                    .button("Creer venv")
+                    .on_hover_text("python -m venv .usbide/venv (F9)")
+                    .clicked()
+                {
+                    self.action_create_venv();
+                }
+                let mut onefile = self.build_onefile;
+                if ui
+                    .checkbox(&mut onefile, "Onefile")
+                    .on_hover_text("Un seul executable au lieu d'un dossier (Alt+E)")
+                    .changed()
+                {
+                    self.action_toggle_build_onefile();
+                }
+                let mode = if self.build_onefile {
+                    "onefile"
+                } else {
+                    "onedir"
+                };
+                let build_hint = if self.tool_status.pyinstaller {
+                    "PyInstaller present"
+                } else {
+                    "PyInstaller absent : installe avant le build"
+                };
+                if ui
+                    .button(self.tool_status.build_label(mode))
+                    .on_hover_text(build_hint)
+                    .clicked()
+                {
                     self.action_build_exe();
                 }
+                ui.menu_button("Options build", |ui| self.draw_build_options(ui));
+            });
+        });
+    }
+
+    fn draw_build_options(&mut self, ui: &mut egui::Ui) {
+        ui.label("Nom de l'executable (vide: nom du script)");
+        ui.add(
+            TextEdit::singleline(&mut self.build_name)
+                .hint_text("MonApp")
+                .desired_width(220.0),
+        );
+        ui.add_space(6.0);
+        ui.label("Icone .ico/.icns (relative au root)");
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.build_icon)
+                    .hint_text("assets/app.ico")
+                    .desired_width(180.0),
+            );
+            ui.menu_button("...", |ui| {
+                let icons = find_icon_files(self.core.workspace(), 50);
+                if icons.is_empty() {
+                    ui.label(RichText::new("Aucune icone trouvee.").color(Color32::from_gray(150)));
+                }
+                for icon in icons {
+                    let rel = icon
+                        .strip_prefix(&self.root_dir)
+                        .unwrap_or(&icon)
+                        .to_string_lossy()
+                        .replace('\\', "/");
+                    if ui.button(&rel).clicked() {
+                        self.build_icon = rel;
+                        ui.close_menu();
+                    }
+                }
+            });
+            if ui.small_button("x").on_hover_text("Sans icone").clicked() {
+                self.build_icon.clear();
+            }
+        });
+    }
+
+    fn draw_prompt(&mut self, ctx: &egui::Context) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        if prompt.kind == PromptKind::QuickOpen {
+            self.draw_quick_open(ctx);
+            return;
+        }
+        if prompt.kind == PromptKind::CommandPalette {
+            self.draw_command_palette(ctx);
+            return;
+        }
+        // Question a trois reponses : valider, abandonner (`discard`), remettre a plus tard.
+        let choice = match prompt.kind {
+            PromptKind::UnsavedChanges(_) => Some((
+                "Enregistrer les modifications avant de continuer ?",
+                ["Sauver", "Ne pas sauver", "Annuler"],
+            )),
+            PromptKind::RecoverAutosave(_) => Some((
+                "Copie plus recente que le fichier (arret brutal ?). Restaurer son contenu ?",
+                ["Restaurer", "Supprimer", "Plus tard"],
+            )),
+            PromptKind::DeletedOnDisk(_) => Some((
+                "Le contenu n'existe plus que dans l'editeur. Recreer le fichier ?",
+                ["Recreer", "Fermer l'onglet", "Garder l'onglet"],
+            )),
+            _ => None,
+        };
+        let mut submit = false;
+        let mut cancel = false;
+        let mut discard = false;
+        egui::Window::new(prompt.label.clone())
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                if let Some((question, [yes, no, later])) = choice {
+                    ui.label(question);
+                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        submit = true;
+                    }
+                    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        cancel = true;
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button(yes).clicked() {
+                            submit = true;
+                        }
+                        if ui.button(no).clicked() {
+                            discard = true;
+                        }
+                        if ui.button(later).clicked() {
+                            cancel = true;
+                        }
+                    });
+                    return;
+                }
+                if prompt.kind.is_confirmation() {
+                    if matches!(prompt.kind, PromptKind::ConfirmDangerSandbox(_)) {
+                        ui.colored_label(accent_red(), CODEX_DANGER_WARNING);
+                    }
+                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        submit = true;
+                    }
+                } else {
+                    if matches!(
+                        prompt.kind,
+                        PromptKind::ReopenEncoding | PromptKind::SaveEncoding
+                    ) {
+                        ui.horizontal_wrapped(|ui| {
+                            for encoding in COMMON_ENCODINGS {
+                                if ui
+                                    .selectable_label(prompt.input == encoding, encoding)
+                                    .clicked()
+                                {
+                                    prompt.input = encoding.to_string();
+                                }
+                            }
+                        });
+                    }
+                    let response =
+                        ui.add(TextEdit::singleline(&mut prompt.input).desired_width(320.0));
+                    if !response.has_focus() && !response.lost_focus() {
+                        response.request_focus();
+                    }
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        submit = true;
+                    }
+                }
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    cancel = true;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("OK").clicked() {
+                        submit = true;
+                    }
+                    if ui.button("Annuler").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if cancel {
+            self.prompt = None;
+        } else if discard && let Some(prompt) = self.prompt.take() {
+            match prompt.kind {
+                PromptKind::UnsavedChanges(action) => self.resolve_unsaved(action, false),
+                PromptKind::RecoverAutosave(path) => self.discard_autosave(&path),
+                PromptKind::DeletedOnDisk(path) => self.close_deleted(&path),
+                _ => {}
+            }
+        } else if submit && let Some(prompt) = self.prompt.take() {
+            self.submit_prompt(prompt.kind, prompt.input.trim().to_string());
+        }
+    }
+
+    fn action_quick_open(&mut self) {
+        if self.file_index.is_none() {
+            self.file_index = Some(FileIndex::scan(self.core.workspace()));
+        }
+        self.quick_open_selected = 0;
+        self.open_prompt(PromptKind::QuickOpen, "Ouvrir un fichier");
+    }
+
+    fn draw_quick_open(&mut self, ctx: &egui::Context) {
+        let (Some(prompt), Some(index)) = (self.prompt.as_mut(), self.file_index.as_ref()) else {
+            return;
+        };
+        // Haut/Bas deplacent la selection au lieu du curseur du champ.
+        let down = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown));
+        let up = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp));
+        let selected = &mut self.quick_open_selected;
+        let mut chosen: Option<String> = None;
+        let mut cancel = false;
+        egui::Window::new(prompt.label.clone())
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    TextEdit::singleline(&mut prompt.input)
+                        .hint_text("nom de fichier")
+                        .desired_width(420.0),
+                );
+                if !response.has_focus() && !response.lost_focus() {
+                    response.request_focus();
+                }
+                if response.changed() {
+                    *selected = 0;
+                }
+                let matches = index.matches(&prompt.input, QUICK_OPEN_RESULTS);
+                let last = matches.len().saturating_sub(1);
+                if down {
+                    *selected = (*selected + 1).min(last);
+                }
+                if up {
+                    *selected = selected.saturating_sub(1);
+                }
+                *selected = (*selected).min(last);
+                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    chosen = matches.get(*selected).map(|rel| rel.to_string());
+                }
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    cancel = true;
+                }
+                ui.label(
+                    egui::RichText::new(format!("{}/{} fichiers", matches.len(), index.len()))
+                        .weak(),
+                );
+                for (idx, rel) in matches.iter().enumerate() {
+                    if ui.selectable_label(idx == *selected, *rel).clicked() {
+                        chosen = Some(rel.to_string());
+                    }
+                }
+            });
+        if cancel {
+            self.prompt = None;
+        } else if let Some(rel) = chosen {
+            self.prompt = None;
+            let path = self.root_dir.join(rel);
+            // Ouvrir d'abord : un fichier cache n'est atteignable qu'une fois ouvert.
+            self.open_file(path.clone());
+            if self.tree.data.reveal(&path).is_some() {
+                self.tree.selected = Some(path);
+                self.tree_scroll_to_selected = true;
+            }
+        }
+    }
+
+    fn action_command_palette(&mut self) {
+        self.palette_selected = 0;
+        self.open_prompt(PromptKind::CommandPalette, "Palette de commandes");
+    }
+
+    /// Liste filtree des actions du keymap ; Entree ou clic lance l'action choisie.
+    fn draw_command_palette(&mut self, ctx: &egui::Context) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        let down = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown));
+        let up = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp));
+        let selected = &mut self.palette_selected;
+        let keymap = &self.keymap;
+        let mut chosen: Option<Action> = None;
+        let mut cancel = false;
+        egui::Window::new(prompt.label.clone())
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    TextEdit::singleline(&mut prompt.input)
+                        .hint_text("commande")
+                        .desired_width(420.0),
+                );
+                if !response.has_focus() && !response.lost_focus() {
+                    response.request_focus();
+                }
+                if response.changed() {
+                    *selected = 0;
+                }
+                let matches = palette_matches(&prompt.input);
+                let last = matches.len().saturating_sub(1);
+                if down {
+                    *selected = (*selected + 1).min(last);
+                }
+                if up {
+                    *selected = selected.saturating_sub(1);
+                }
+                *selected = (*selected).min(last);
+                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    chosen = matches.get(*selected).copied();
+                }
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    cancel = true;
+                }
+                for (idx, action) in matches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .selectable_label(idx == *selected, action.label())
+                            .clicked()
+                        {
+                            chosen = Some(*action);
+                        }
+                        ui.label(egui::RichText::new(keymap.chords_label(*action)).weak());
+                    });
+                }
             });
+        if cancel {
+            self.prompt = None;
+        } else if let Some(action) = chosen {
+            self.prompt = None;
+            self.run_action(ctx, action);
+        }
+    }
+
+    fn open_prompt(&mut self, kind: PromptKind, label: &str) {
+        self.open_prompt_with(kind, label, "");
+    }
+
+    fn open_prompt_with(&mut self, kind: PromptKind, label: &str, initial: &str) {
+        self.prompt = Some(GuiPrompt {
+            kind,
+            label: label.to_string(),
+            input: initial.to_string(),
         });
     }
 
+    fn submit_prompt(&mut self, kind: PromptKind, value: String) {
+        match kind {
+            PromptKind::NewFile(parent) => self.create_tree_entry(parent, &value, false),
+            PromptKind::NewFolder(parent) => self.create_tree_entry(parent, &value, true),
+            PromptKind::Rename(path) => self.rename_tree_entry(path, &value),
+            PromptKind::ConfirmDelete(path) => self.delete_tree_entry(path),
+            PromptKind::ConfirmClearCaches => self.clear_caches(),
+            PromptKind::ConfirmShell(cmd) => self.spawn_shell(cmd),
+            PromptKind::RevealFolder(dir) => self.open_in_file_manager(&dir),
+            PromptKind::ApplyCodexPatch(patch) => self.apply_codex_patch(patch),
+            PromptKind::SaveCodeBlock(code) => self.save_code_block(&code, &value),
+            PromptKind::GotoLine => self.goto_line(&value),
+            PromptKind::GitCommit => self.git_commit(&value),
+            PromptKind::ConfirmDangerSandbox(prompt) => self.run_codex(prompt),
+            PromptKind::CodexQuestion(source, content) => {
+                self.ask_codex_with_context(&source, &content, &value);
+            }
+            PromptKind::QuickOpen | PromptKind::CommandPalette => {}
+            PromptKind::UnsavedChanges(action) => self.resolve_unsaved(action, true),
+            PromptKind::RecoverAutosave(path) => self.restore_autosave(&path),
+            PromptKind::DeletedOnDisk(path) => self.recreate_deleted(&path),
+            PromptKind::OpenAnyway(path) => self.force_open(path),
+            PromptKind::ReopenEncoding => self.reopen_with_encoding(&value),
+            PromptKind::SaveEncoding => self.save_with_encoding(&value, false),
+            PromptKind::ConfirmLossySave(encoding) => self.save_with_encoding(&encoding, true),
+        }
+    }
+
     fn draw_file_tree(&mut self, ui: &mut egui::Ui) {
         Self::panel_frame(ui).show(ui, |ui| {
-            Self::section_title(ui, "Fichiers");
+            let mut toggle_ignored = false;
+            let mut toggle_hidden = false;
+            let mut fold = None;
+            ui.horizontal(|ui| {
+                Self::section_title(ui, "Fichiers");
+                if ui
+                    .small_button("-")
+                    .on_hover_text("Tout replier (Alt+-)")
+                    .clicked()
+                {
+                    fold = Some(false);
+                }
+                if ui
+                    .small_button("+")
+                    .on_hover_text("Tout deplier (Alt+=)")
+                    .clicked()
+                {
+                    fold = Some(true);
+                }
+                let mut show = self.tree.data.show_ignored();
+                toggle_ignored = ui
+                    .checkbox(&mut show, "Ignores")
+                    .on_hover_text("Afficher les fichiers ignores (Alt+I)")
+                    .changed();
+                let mut hidden = self.tree.data.show_hidden();
+                toggle_hidden = ui
+                    .checkbox(&mut hidden, "Caches")
+                    .on_hover_text("Afficher les fichiers commencant par '.' (Alt+H)")
+                    .changed();
+            });
+            if toggle_ignored {
+                self.action_toggle_show_ignored();
+            }
+            if toggle_hidden {
+                self.action_toggle_show_hidden();
+            }
+            if let Some(expand) = fold {
+                self.action_fold_tree(expand);
+            }
             ui.separator();
             let entries = self.tree.data.visible().to_vec();
             let available_height = ui.available_height();
+            let scroll_to_selected = std::mem::take(&mut self.tree_scroll_to_selected);
             ScrollArea::vertical()
                 .id_source("file_tree")
                 .auto_shrink([false, false])
@@ -459,13 +1988,25 @@ impl GuiApp {
                             } else {
                                 ui.add_space(18.0);
                             }
-                            let label = if entry.is_dir {
-                                format!("{}/", entry.name)
-                            } else {
-                                entry.name.clone()
+                            let label = match (entry.is_dir, entry.git) {
+                                (true, _) => format!("{}/", entry.name),
+                                (false, Some(status)) => {
+                                    format!("{}  {}", entry.name, status.marker())
+                                }
+                                (false, None) => entry.name.clone(),
                             };
-                            if ui.selectable_label(is_selected, label).clicked() {
+                            let mut label = RichText::new(label);
+                            if let Some(status) = entry.git {
+                                label =
+                                    label.color(themed_fg(ui.visuals(), git_status_color(status)));
+                            }
+                            let response = ui.selectable_label(is_selected, label);
+                            if is_selected && scroll_to_selected {
+                                response.scroll_to_me(Some(egui::Align::Center));
+                            }
+                            if response.clicked() {
                                 self.tree.selected = Some(entry.path.clone());
+                                self.follow_tree_selection();
                                 if entry.is_dir {
                                     self.tree.toggle_dir(&entry.path);
                                 } else {
@@ -479,8 +2020,35 @@ impl GuiApp {
     }
 
     fn draw_editor(&mut self, ui: &mut egui::Ui) {
+        let mut toggle_line_ending = false;
+        let mut activate_tab = None;
+        let mut close_tab = None;
         Self::panel_frame(ui).show(ui, |ui| {
-            if let Some(current) = &self.current {
+            if let Some(current) = self.current().cloned() {
+                let active = self.tabs.active_index();
+                ui.horizontal_wrapped(|ui| {
+                    for (idx, tab) in self.tabs.iter().enumerate() {
+                        let dirty = if tab.file.dirty { " *" } else { "" };
+                        let label = format!("{}{dirty}", tab_name(&tab.file.path));
+                        if ui
+                            .selectable_label(Some(idx) == active, label)
+                            .on_hover_text(tab.file.path.display().to_string())
+                            .clicked()
+                        {
+                            activate_tab = Some(idx);
+                        }
+                        if ui
+                            .small_button("x")
+                            .on_hover_text("Fermer l'onglet (Ctrl+W)")
+                            .clicked()
+                        {
+                            close_tab = Some(tab.file.path.clone());
+                        }
+                        ui.add_space(6.0);
+                    }
+                });
+                ui.add_space(4.0);
+                let editor_len = self.tabs.active().map_or(0, |tab| tab.buffer.len());
                 ui.horizontal(|ui| {
                     Self::section_title(ui, "Editeur");
                     ui.add_space(10.0);
@@ -492,34 +2060,181 @@ impl GuiApp {
                     ui.label(
                         RichText::new(current.encoding.clone()).color(Color32::from_gray(150)),
                     );
+                    ui.add_space(6.0);
+                    if ui
+                        .small_button(current.line_ending.label())
+                        .on_hover_text("Fin de ligne a la sauvegarde (F6 pour changer)")
+                        .clicked()
+                    {
+                        toggle_line_ending = true;
+                    }
+                    ui.add_space(12.0);
+                    ui.label(
+                        RichText::new(self.editor_position.as_str()).color(Color32::from_gray(150)),
+                    );
+                    if let Some(stats) = &self.doc_stats {
+                        ui.add_space(10.0);
+                        ui.label(RichText::new(stats.label()).color(Color32::from_gray(150)));
+                    }
                     if current.dirty {
                         ui.add_space(10.0);
                         ui.colored_label(accent_red(), "modifie");
                     }
+                    ui.add_space(12.0);
+                    ui.checkbox(&mut self.editor_highlight, "Coloration");
+                    if self.editor_highlight && editor_len > HIGHLIGHT_MAX_BYTES {
+                        ui.label(
+                            RichText::new("(fichier trop gros)").color(Color32::from_gray(150)),
+                        );
+                    }
                 });
                 ui.add_space(6.0);
                 ui.separator();
                 ui.add_space(6.0);
+                if let Some(preview) = current.preview {
+                    ui.label(
+                        RichText::new(preview.banner())
+                            .strong()
+                            .color(Color32::from_rgb(218, 165, 72)),
+                    );
+                    ui.add_space(4.0);
+                }
+                let read_only = current.read_only();
                 let available = ui.available_size();
-                let editor = TextEdit::multiline(&mut self.editor_text)
-                    .code_editor()
-                    .desired_width(f32::INFINITY)
-                    .lock_focus(true);
+                let goto = self.pending_goto.take().and_then(|(line, col)| {
+                    self.tabs
+                        .active()
+                        .map(|tab| char_index_at(&tab.buffer, line, col))
+                });
+                let lang = Language::from_path(&current.path)
+                    .filter(|_| self.editor_highlight && editor_len <= HIGHLIGHT_MAX_BYTES);
+                let word_wrap = self.word_wrap;
+                let settings = self.core.settings();
+                let tab_width = settings.tab_width();
+                let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+                    let space = ui.fonts(|fonts| fonts.glyph_width(&font_id, ' '));
+                    let mut job = match lang {
+                        Some(lang) => ui.memory_mut(|mem| {
+                            mem.caches.cache::<HighlightCache>().get((
+                                lang,
+                                text,
+                                &font_id,
+                                ui.visuals().dark_mode,
+                            ))
+                        }),
+                        None => egui::text::LayoutJob::simple(
+                            text.to_string(),
+                            font_id,
+                            syntax_color(None, ui.visuals().dark_mode),
+                            f32::INFINITY,
+                        ),
+                    };
+                    widen_tabs(
+                        &mut job,
+                        (tab_width as f32 - egui::epaint::text::TAB_SIZE as f32) * space,
+                    );
+                    job.wrap.max_width = if word_wrap { wrap_width } else { f32::INFINITY };
+                    ui.fonts(|fonts| fonts.layout_job(job))
+                };
+                let Some(tab) = self.tabs.active_mut() else {
+                    return;
+                };
+                // Un id par fichier : curseur et defilement propres a chaque onglet.
+                let editor_id = egui::Id::new(("editor", &current.path));
                 let response = ScrollArea::both()
-                    .id_source("editor_scroll")
+                    .id_source(("editor_scroll", &current.path))
                     .auto_shrink([false, false])
                     .max_height(available.y)
                     .max_width(available.x)
                     .show(ui, |ui| {
                         ui.set_min_size(available);
-                        ui.add_sized(available, editor)
+                        // Tab : espaces ou tabulation selon les parametres (au lieu du "\t" d'egui).
+                        let indent_key = !read_only
+                            && ui.memory(|mem| mem.has_focus(editor_id))
+                            && ui.input_mut(|i| {
+                                i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)
+                            });
+                        let mut show = |ui: &mut egui::Ui, text: &mut dyn egui::TextBuffer| {
+                            TextEdit::multiline(text)
+                                .id(editor_id)
+                                .code_editor()
+                                .desired_width(f32::INFINITY)
+                                .min_size(available)
+                                .lock_focus(true)
+                                .layouter(&mut layouter)
+                                .show(ui)
+                        };
+                        // Apercu : un &str rend le TextEdit non modifiable (selection et copie gardees).
+                        let mut output = if read_only {
+                            show(ui, &mut tab.buffer.as_str())
+                        } else {
+                            show(ui, &mut tab.buffer)
+                        };
+                        if indent_key && let Some(range) = output.cursor_range {
+                            let [start, end] = range.as_ccursor_range().sorted();
+                            let byte = |index: usize| {
+                                tab.buffer
+                                    .char_indices()
+                                    .nth(index)
+                                    .map_or(tab.buffer.len(), |(pos, _)| pos)
+                            };
+                            let (start_byte, end_byte) = (byte(start.index), byte(end.index));
+                            let line_start = tab.buffer[..start_byte]
+                                .rfind('\n')
+                                .map_or(0, |pos| pos + 1);
+                            let col = tab.buffer[line_start..start_byte].chars().count();
+                            let indent = settings.indent_text(Some(&current.path), col);
+                            tab.buffer.replace_range(start_byte..end_byte, &indent);
+                            let ccursor =
+                                egui::text::CCursor::new(start.index + indent.chars().count());
+                            output
+                                .state
+                                .cursor
+                                .set_char_range(Some(egui::text::CCursorRange::one(ccursor)));
+                            output.state.clone().store(ui.ctx(), editor_id);
+                            output.response.mark_changed();
+                            ui.ctx().request_repaint();
+                        }
+                        if let Some(index) = goto {
+                            let ccursor = egui::text::CCursor::new(index);
+                            let mut state = output.state.clone();
+                            state
+                                .cursor
+                                .set_char_range(Some(egui::text::CCursorRange::one(ccursor)));
+                            state.store(ui.ctx(), output.response.id);
+                            output.response.request_focus();
+                            let cursor = output.galley.from_ccursor(ccursor);
+                            let rect = output
+                                .galley
+                                .pos_from_cursor(&cursor)
+                                .translate(output.galley_pos.to_vec2());
+                            ui.scroll_to_rect(rect, Some(egui::Align::Center));
+                        }
+                        let cursor = output.cursor_range.map(|range| {
+                            let pcursor = range.primary.pcursor;
+                            (pcursor.paragraph, pcursor.offset)
+                        });
+                        (output.response, cursor)
                     })
                     .inner;
+                let (response, cursor) = response;
+                let line_count = if response.changed() {
+                    tab.file.dirty = true;
+                    tab.buffer.split('\n').count()
+                } else {
+                    self.editor_line_count
+                };
+                let row_moved = cursor.is_some_and(|cursor| cursor.0 != self.editor_cursor.0);
+                self.update_editor_position(cursor.unwrap_or(self.editor_cursor), line_count);
                 if response.changed() {
-                    if let Some(current) = self.current.as_mut() {
-                        current.dirty = true;
+                    // Annuler/retablir, coller... peuvent toucher d'autres lignes que celle du curseur.
+                    if ui.input(|input| input.modifiers.command || input.modifiers.alt) {
+                        self.doc_stats = None;
                     }
                     self.refresh_title();
+                } else if row_moved {
+                    self.refresh_doc_stats();
                 }
             } else {
                 ui.vertical_centered(|ui| {
@@ -532,23 +2247,43 @@ impl GuiApp {
                 });
             }
         });
+        if toggle_line_ending {
+            self.action_toggle_line_ending();
+        }
+        if let Some(idx) = activate_tab {
+            self.activate_tab(idx);
+        }
+        if let Some(path) = close_tab {
+            self.request_close_tab(path);
+        }
     }
 
     fn draw_logs(&mut self, ui: &mut egui::Ui, target: LogTarget, id_source: &str) {
-        let entries = match target {
-            LogTarget::Main => &self.log,
-            LogTarget::Codex => &self.codex_log,
+        let (entries, filter) = match target {
+            LogTarget::Main => (&self.log, self.log_filter),
+            LogTarget::Codex => (&self.codex_log, self.codex_log_filter),
         };
-        ScrollArea::vertical()
+        let word_wrap = self.word_wrap;
+        let search = self
+            .log_search
+            .as_ref()
+            .filter(|search| search.target == target);
+        let scroll_to_match = self.log_search_scroll && search.is_some();
+        // Pendant une recherche, le defilement vers le resultat l'emporte sur le suivi de la fin.
+        let output = ScrollArea::new([!word_wrap, true])
             .id_source(id_source)
-            .stick_to_bottom(true)
+            .stick_to_bottom(search.is_none())
             .auto_shrink([false, false])
             .max_height(ui.available_height())
             .show(ui, |ui| {
                 if entries.is_empty() {
                     ui.label(RichText::new("Aucun log.").color(Color32::from_gray(130)));
                 }
-                for entry in entries {
+                for (idx, entry) in entries
+                    .iter()
+                    .filter(|entry| filter.accepts(entry.kind.level()))
+                    .enumerate()
+                {
                     let color = match entry.kind {
                         LogKind::Info => Color32::from_gray(210),
                         LogKind::Warn => Color32::from_rgb(240, 200, 120),
@@ -557,39 +2292,189 @@ impl GuiApp {
                         LogKind::Assistant => Color32::from_rgb(120, 220, 160),
                         LogKind::Action => Color32::from_rgb(218, 165, 72),
                     };
-                    ui.label(RichText::new(&entry.text).color(color));
+                    let color = themed_fg(ui.visuals(), color);
+                    let mut text = RichText::new(entry.display_text()).color(color);
+                    let current = search.is_some_and(|search| search.current == Some(idx));
+                    if current {
+                        text = text.background_color(themed_bg(
+                            ui.visuals(),
+                            Color32::from_rgb(110, 90, 20),
+                            0.6,
+                        ));
+                    } else if search.is_some_and(|search| search.is_match(&entry.text)) {
+                        text = text.background_color(themed_bg(
+                            ui.visuals(),
+                            Color32::from_gray(60),
+                            0.6,
+                        ));
+                    }
+                    let response = ui.add(egui::Label::new(text).wrap(word_wrap));
+                    if current && scroll_to_match {
+                        response.scroll_to_me(Some(egui::Align::Center));
+                    }
                 }
             });
+        if scroll_to_match {
+            self.log_search_scroll = false;
+        }
+        let at_bottom = scrolled_to_bottom(&output);
+        match target {
+            LogTarget::Main => self.log_at_bottom = at_bottom,
+            LogTarget::Codex => self.codex_log_at_bottom = at_bottom,
+        }
     }
 
-    fn draw_command_panel(&mut self, ui: &mut egui::Ui) {
-        Self::panel_frame(ui).show(ui, |ui| {
-            Self::section_title(ui, "Commande");
+    /// Marqueur a cote du titre tant que le panneau ne suit plus la fin du log.
+    fn draw_scrollback_marker(ui: &mut egui::Ui, at_bottom: bool) {
+        if !at_bottom {
+            ui.add_space(10.0);
+            ui.label(RichText::new("-- historique --").color(Color32::from_gray(150)))
+                .on_hover_text("Defile jusqu'en bas pour suivre la sortie");
+        }
+    }
+
+    /// Etape pip/npm en cours, a cote du titre du panneau qui recoit la sortie.
+    fn draw_install_progress(&self, ui: &mut egui::Ui, target: LogTarget) {
+        if let Some((progress_target, progress)) = &self.install_progress
+            && *progress_target == target
+        {
+            ui.add_space(10.0);
+            ui.spinner();
+            ui.label(RichText::new(progress).color(Color32::from_gray(170)));
+        }
+    }
+
+    /// Arbre a gauche, Commande et Codex en bas (masques en mode zen).
+    fn draw_side_panels(&mut self, ctx: &egui::Context) {
+        let files = egui::SidePanel::left("files")
+            .resizable(true)
+            .default_width(self.files_panel_width.unwrap_or(280.0))
+            .min_width(220.0)
+            .max_width(420.0)
+            .show(ctx, |ui| self.draw_file_tree(ui));
+        self.files_panel_width = Some(files.response.rect.width());
+
+        let bottom = egui::TopBottomPanel::bottom("bottom")
+            .resizable(true)
+            .default_height(self.bottom_panel_height.unwrap_or_else(|| {
+                let h = ctx.input(|i| i.screen_rect().height());
+                (h * 0.30).clamp(240.0, 360.0)
+            }))
+            .min_height(220.0)
+            .max_height({
+                let h = ctx.input(|i| i.screen_rect().height());
+                (h * 0.45).clamp(280.0, 480.0)
+            })
+            .show(ctx, |ui| {
+                let height = ui.available_height();
+                ui.columns(2, |columns| {
+                    columns[0].set_min_height(height);
+                    columns[1].set_min_height(height);
+                    self.draw_command_panel(&mut columns[0]);
+                    self.draw_codex_panel(&mut columns[1]);
+                });
+            });
+        self.bottom_panel_height = Some(bottom.response.rect.height());
+    }
+
+    fn draw_command_panel(&mut self, ui: &mut egui::Ui) {
+        Self::panel_frame(ui).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                Self::section_title(ui, "Commande");
+                ui.add_space(10.0);
+                ui.label(
+                    RichText::new(shell_cwd_label(&self.root_dir, &self.shell_cwd))
+                        .color(Color32::from_gray(150)),
+                );
+                let label = format!("Filtre: {}", self.log_filter.label());
+                if ui
+                    .small_button(label)
+                    .on_hover_text("Niveau de log affiche (Alt+L)")
+                    .clicked()
+                {
+                    self.action_cycle_log_filter(LogTarget::Main);
+                }
+                if ui
+                    .small_button("Vider")
+                    .on_hover_text("Vider le Journal (Ctrl+Shift+L)")
+                    .clicked()
+                {
+                    self.clear_log(LogTarget::Main);
+                }
+                self.draw_install_progress(ui, LogTarget::Main);
+                Self::draw_scrollback_marker(ui, self.log_at_bottom);
+            });
+            let interactive = self.core.interactive_shell().is_some();
+            if interactive {
+                ui.label(
+                    RichText::new("Entree envoyee au process en cours")
+                        .color(Color32::from_rgb(230, 190, 80)),
+                );
+            }
             ui.add_space(6.0);
             let mut submit = false;
+            let mut eof = false;
             ui.horizontal(|ui| {
                 let button_width = 90.0;
+                let eof_width = if interactive { 50.0 } else { 0.0 };
                 let input_width =
-                    (ui.available_width() - button_width - ui.spacing().item_spacing.x).max(140.0);
+                    (ui.available_width() - button_width - eof_width - ui.spacing().item_spacing.x)
+                        .max(140.0);
                 let response = ui.add_sized(
                     [input_width, 0.0],
-                    TextEdit::singleline(&mut self.cmd_input).hint_text("Ex: python script.py"),
+                    TextEdit::singleline(&mut self.cmd_input).hint_text(if interactive {
+                        "Saisie pour le process (Entree: envoyer)"
+                    } else {
+                        "Ex: python script.py"
+                    }),
                 );
+                if response.has_focus() {
+                    self.log_focus = LogTarget::Main;
+                }
                 if response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
                     submit = true;
                 }
+                if response.has_focus() {
+                    let recalled = if ui.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
+                        Some(self.cmd_history.older().unwrap_or_default().to_string())
+                    } else if ui.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
+                        Some(self.cmd_history.newer().unwrap_or_default().to_string())
+                    } else {
+                        None
+                    };
+                    if let Some(recalled) = recalled {
+                        self.cmd_input = recalled;
+                        Self::move_cursor_to_end(ui, response.id, &self.cmd_input);
+                    }
+                }
+                let label = if interactive { "Envoyer" } else { "Executer" };
                 if ui
-                    .add_sized([button_width, 0.0], egui::Button::new("Executer"))
+                    .add_sized([button_width, 0.0], egui::Button::new(label))
                     .clicked()
                 {
                     submit = true;
                 }
+                if interactive
+                    && ui
+                        .button("EOF")
+                        .on_hover_text("Fermer l'entree du process")
+                        .clicked()
+                {
+                    eof = true;
+                }
             });
-            if submit {
+            if submit && interactive {
+                // Ligne brute (meme vide) : un prompt peut attendre une simple validation.
+                let line = std::mem::take(&mut self.cmd_input);
+                self.send_shell_input(line);
+            } else if submit {
                 let cmd = self.cmd_input.trim().to_string();
                 self.cmd_input.clear();
                 self.run_shell(cmd);
             }
+            if eof {
+                self.send_shell_eof();
+            }
             ui.add_space(8.0);
             let log_height = ui.available_height().max(80.0);
             ui.allocate_ui(egui::vec2(ui.available_width(), log_height), |ui| {
@@ -598,26 +2483,100 @@ impl GuiApp {
         });
     }
 
+    fn move_cursor_to_end(ui: &egui::Ui, id: egui::Id, text: &str) {
+        if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+            let ccursor = egui::text::CCursor::new(text.chars().count());
+            state
+                .cursor
+                .set_char_range(Some(egui::text::CCursorRange::one(ccursor)));
+            state.store(ui.ctx(), id);
+        }
+    }
+
     fn draw_codex_panel(&mut self, ui: &mut egui::Ui) {
         Self::panel_frame(ui).show(ui, |ui| {
-            Self::section_title(ui, "Codex");
+            ui.horizontal(|ui| {
+                Self::section_title(ui, &format!("Codex ({})", self.codex_view.label()));
+                ui.add_space(10.0);
+                let label = format!("Filtre: {}", self.codex_log_filter.label());
+                if ui
+                    .small_button(label)
+                    .on_hover_text("Niveau de log affiche (Alt+Shift+L)")
+                    .clicked()
+                {
+                    self.action_cycle_log_filter(LogTarget::Codex);
+                }
+                if ui
+                    .small_button("Vider")
+                    .on_hover_text("Vider la Sortie Codex (Ctrl+Alt+L)")
+                    .clicked()
+                {
+                    self.clear_log(LogTarget::Codex);
+                }
+                self.draw_install_progress(ui, LogTarget::Codex);
+                Self::draw_scrollback_marker(ui, self.codex_log_at_bottom);
+                if let Some(started) = self.codex_status_started {
+                    ui.add_space(10.0);
+                    ui.spinner();
+                    ui.label(
+                        RichText::new(codex_status_wait_label(started.elapsed()))
+                            .color(Color32::from_gray(170)),
+                    );
+                }
+                let queued = self.core.codex_queue_len();
+                if queued > 0 {
+                    ui.add_space(10.0);
+                    ui.label(
+                        RichText::new(format!("En attente : {queued}")).color(codex_hint_color()),
+                    );
+                    if ui
+                        .small_button("Vider la file")
+                        .on_hover_text("Alt+Shift+X")
+                        .clicked()
+                    {
+                        self.clear_codex_queue();
+                    }
+                }
+            });
             ui.add_space(6.0);
             ui.horizontal_wrapped(|ui| {
                 if ui.button("Login").clicked() {
                     self.action_codex_login();
                 }
+                if ui.button("Logout").clicked() {
+                    self.action_codex_logout();
+                }
                 if ui.button("Status").clicked() {
                     self.action_codex_check();
                 }
                 if ui.button("Installer").clicked() {
                     self.action_codex_install();
                 }
-                let label = if self.codex_compact_view {
-                    "Compact"
-                } else {
-                    "Brut"
-                };
-                if ui.button(label).clicked() {
+                if ui.button("Stop (Maj+F5)").clicked() {
+                    self.action_codex_stop();
+                }
+                if ui
+                    .button("Copier reponse")
+                    .on_hover_text("Derniere reponse de l'assistant (Alt+C)")
+                    .clicked()
+                {
+                    self.action_copy_last_assistant(ui.ctx());
+                }
+                if ui
+                    .button("Question fichier")
+                    .on_hover_text("Joint le fichier ou la selection au prompt (Alt+Q)")
+                    .clicked()
+                {
+                    self.action_ask_codex_about_file(ui.ctx());
+                }
+                if ui
+                    .button("Expliquer selection")
+                    .on_hover_text("Alt+Shift+Q")
+                    .clicked()
+                {
+                    self.action_explain_selection(ui.ctx());
+                }
+                if ui.button(self.codex_view.label()).clicked() {
                     self.action_toggle_codex_view();
                 }
                 let follow_label = if self.codex_follow_output {
@@ -633,7 +2592,13 @@ impl GuiApp {
             ui.horizontal_wrapped(|ui| {
                 let sandbox_label =
                     format!("Sandbox: {}", codex_sandbox_label(self.codex_sandbox_mode));
-                if ui.button(sandbox_label).clicked() {
+                let sandbox_text = if self.codex_sandbox_mode == CodexSandboxMode::DangerFullAccess
+                {
+                    RichText::new(sandbox_label).color(accent_red()).strong()
+                } else {
+                    RichText::new(sandbox_label)
+                };
+                if ui.button(sandbox_text).clicked() {
                     self.action_toggle_codex_sandbox();
                 }
                 let approval_label = format!(
@@ -643,7 +2608,53 @@ impl GuiApp {
                 if ui.button(approval_label).clicked() {
                     self.action_toggle_codex_approval();
                 }
+                ui.label("Modele:");
+                ui.add(
+                    TextEdit::singleline(&mut self.codex_model)
+                        .desired_width(110.0)
+                        .hint_text("defaut"),
+                );
+                ui.menu_button("v", |ui| {
+                    for preset in CODEX_MODEL_PRESETS {
+                        let label = if preset.is_empty() { "defaut" } else { preset };
+                        if ui.button(label).clicked() {
+                            self.codex_model = preset.to_string();
+                            ui.close_menu();
+                        }
+                    }
+                });
+                let mut continue_session = self.codex_continue_session;
+                if ui
+                    .checkbox(&mut continue_session, "Continuer la session")
+                    .changed()
+                {
+                    self.action_toggle_codex_session();
+                }
             });
+            let mut resend = None;
+            egui::CollapsingHeader::new(format!("Historique ({})", self.codex_history.turns.len()))
+                .id_source("codex_history")
+                .show(ui, |ui| {
+                    ScrollArea::vertical()
+                        .id_source("codex_history_scroll")
+                        .max_height(120.0)
+                        .show(ui, |ui| {
+                            for prompt in self.codex_history.recent_prompts() {
+                                let first_line = prompt.lines().next().unwrap_or_default();
+                                let label: String = first_line.chars().take(80).collect();
+                                if ui
+                                    .selectable_label(false, label)
+                                    .on_hover_text("Cliquer pour re-envoyer")
+                                    .clicked()
+                                {
+                                    resend = Some(prompt.to_string());
+                                }
+                            }
+                        });
+                });
+            if let Some(prompt) = resend {
+                self.send_codex_prompt(prompt);
+            }
             ui.add_space(4.0);
             let mut submit = false;
             ui.horizontal(|ui| {
@@ -655,6 +2666,9 @@ impl GuiApp {
                     TextEdit::singleline(&mut self.codex_input)
                         .hint_text("Ex: explique ce code..."),
                 );
+                if response.has_focus() {
+                    self.log_focus = LogTarget::Codex;
+                }
                 if response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
                     submit = true;
                 }
@@ -668,7 +2682,7 @@ impl GuiApp {
             if submit {
                 let prompt = self.codex_input.trim().to_string();
                 self.codex_input.clear();
-                self.run_codex(prompt);
+                self.send_codex_prompt(prompt);
             }
             ui.add_space(8.0);
             let log_height = ui.available_height().max(80.0);
@@ -680,49 +2694,151 @@ impl GuiApp {
 
     fn draw_codex_log(&mut self, ui: &mut egui::Ui) {
         if self.codex_log_dirty {
-            self.codex_log_buffer = self.render_plain_log(&self.codex_log);
+            self.codex_log_segments = codex_log_segments(&self.codex_log, self.codex_log_filter);
         }
         let available = ui.available_size();
         let follow = self.codex_follow_output;
-        let mut response_id = None;
-        let mut response_changed = false;
-        let need_scroll_to_end = self.codex_log_dirty && follow;
-        let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
-            let job = GuiApp::codex_log_layout_job(ui, text, wrap_width);
-            ui.fonts(|fonts| fonts.layout_job(job))
-        };
-        ScrollArea::vertical()
+        // Remonte dans l'historique : la nouvelle sortie ne ramene plus en bas.
+        let searching = self
+            .log_search
+            .as_ref()
+            .is_some_and(|search| search.target == LogTarget::Codex);
+        let need_scroll_to_end =
+            self.codex_log_dirty && follow && self.codex_log_at_bottom && !searching;
+        // Ligne (filtree) du resultat courant a amener dans la vue.
+        let match_line = self
+            .log_search
+            .as_ref()
+            .filter(|_| searching && self.log_search_scroll)
+            .and_then(|search| search.current);
+        let word_wrap = self.word_wrap;
+        let mut copy_block = None;
+        let mut save_block = None;
+        let output = ScrollArea::new([!word_wrap, true])
             .id_source("codex_log_scroll")
             .auto_shrink([false, false])
-            .stick_to_bottom(follow)
+            .stick_to_bottom(follow && !searching)
             .show(ui, |ui| {
                 ui.set_min_size(available);
-                let response = ui.add_sized(
-                    available,
-                    TextEdit::multiline(&mut self.codex_log_buffer)
-                        .desired_width(f32::INFINITY)
-                        .min_size(available)
-                        .lock_focus(false)
-                        .cursor_at_end(need_scroll_to_end)
-                        .layouter(&mut layouter),
-                );
-                response_id = Some(response.id);
-                response_changed = response.changed();
+                ui.spacing_mut().item_spacing.y = 0.0;
+                for segment in &self.codex_log_segments {
+                    match segment {
+                        CodexLogSegment::Text { first, text, kind } => {
+                            let kind = *kind;
+                            let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                                let wrap_width = if word_wrap { wrap_width } else { f32::INFINITY };
+                                let job = GuiApp::codex_log_layout_job(ui, text, wrap_width, kind);
+                                ui.fonts(|fonts| fonts.layout_job(job))
+                            };
+                            let output = TextEdit::multiline(&mut text.as_str())
+                                .desired_width(f32::INFINITY)
+                                .desired_rows(1)
+                                .frame(false)
+                                .lock_focus(false)
+                                .layouter(&mut layouter)
+                                .show(ui);
+                            let lines = text.split('\n').count();
+                            if let Some(line) = match_line
+                                && (*first..*first + lines).contains(&line)
+                            {
+                                // Debut (en caracteres) de la ligne du resultat dans le segment.
+                                let offset = text
+                                    .split('\n')
+                                    .take(line - first)
+                                    .map(|row| row.chars().count() + 1)
+                                    .sum::<usize>();
+                                let rect = output
+                                    .galley
+                                    .pos_from_ccursor(egui::text::CCursor::new(offset))
+                                    .translate(output.galley_pos.to_vec2());
+                                ui.scroll_to_rect(rect, Some(egui::Align::Center));
+                            }
+                        }
+                        CodexLogSegment::Code {
+                            first,
+                            lines,
+                            lang,
+                            code,
+                        } => {
+                            let response = egui::Frame::group(ui.style())
+                                .fill(themed_bg(ui.visuals(), Color32::from_rgb(18, 22, 28), 0.9))
+                                .show(ui, |ui| {
+                                    ui.spacing_mut().item_spacing.y = 4.0;
+                                    ui.horizontal(|ui| {
+                                        let label = if lang.is_empty() { "code" } else { lang };
+                                        ui.label(RichText::new(label).small().weak());
+                                        if ui.small_button("Copier").clicked() {
+                                            copy_block = Some(code.clone());
+                                        }
+                                        if ui.small_button("Enregistrer sous...").clicked() {
+                                            save_block = Some((lang.clone(), code.clone()));
+                                        }
+                                    });
+                                    ui.add(
+                                        egui::Label::new(
+                                            RichText::new(code.as_str()).monospace().color(
+                                                themed_fg(
+                                                    ui.visuals(),
+                                                    Color32::from_rgb(150, 210, 230),
+                                                ),
+                                            ),
+                                        )
+                                        .wrap(word_wrap),
+                                    );
+                                })
+                                .response;
+                            if let Some(line) = match_line
+                                && (*first..*first + lines).contains(&line)
+                            {
+                                ui.scroll_to_rect(response.rect, Some(egui::Align::Center));
+                            }
+                        }
+                    }
+                }
+                if need_scroll_to_end {
+                    ui.scroll_to_cursor(Some(egui::Align::BOTTOM));
+                }
             });
-        if need_scroll_to_end
-            && let Some(id) = response_id
-            && let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id)
-        {
-            let end = self.codex_log_buffer.chars().count();
-            let ccursor = egui::text::CCursor::new(end);
-            let range = egui::text::CCursorRange::one(ccursor);
-            state.cursor.set_char_range(Some(range));
-            state.store(ui.ctx(), id);
-        }
-        if response_changed {
-            self.codex_log_buffer = self.render_plain_log(&self.codex_log);
+        if match_line.is_some() {
+            self.log_search_scroll = false;
         }
+        self.codex_log_at_bottom = scrolled_to_bottom(&output);
         self.codex_log_dirty = false;
+        if let Some(code) = copy_block {
+            let chars = code.chars().count();
+            ui.ctx().copy_text(code);
+            self.log_ui(format!(
+                "Bloc de code copie dans le presse-papiers ({chars} caracteres)."
+            ));
+        }
+        if let Some((lang, code)) = save_block {
+            self.open_prompt_with(
+                PromptKind::SaveCodeBlock(code),
+                "Enregistrer le bloc de code sous (chemin relatif au workspace)",
+                &format!("extrait.{}", code_block_extension(&lang)),
+            );
+        }
+    }
+
+    /// Ecrit un bloc de code de la sortie Codex dans un nouveau fichier du workspace.
+    fn save_code_block(&mut self, code: &str, relative: &str) {
+        let mut content = code.to_string();
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        match create_file_under(&self.root_dir, relative, &content) {
+            Ok(path) => {
+                self.log_ui(format!("Bloc de code enregistre: {}", path.display()));
+                self.reload_tree();
+                if let Some(parent) = path.parent() {
+                    self.tree.data.expand(parent);
+                }
+                self.tree.selected = Some(path);
+            }
+            Err(err) => {
+                self.log_issue(&err.to_string(), "erreur", "bloc de code", LogTarget::Main);
+            }
+        }
     }
 
     fn handle_codex_caps_line(&mut self, line: &str) {
@@ -736,7 +2852,13 @@ impl GuiApp {
         self.codex_caps_buffer.push_str(trimmed);
     }
 
-    fn codex_log_layout_job(ui: &egui::Ui, text: &str, wrap_width: f32) -> egui::text::LayoutJob {
+    /// `kind` : message en cours au debut du texte (suite apres un bloc de code).
+    fn codex_log_layout_job(
+        ui: &egui::Ui,
+        text: &str,
+        wrap_width: f32,
+        kind: Option<LogKind>,
+    ) -> egui::text::LayoutJob {
         let mut job = egui::text::LayoutJob::default();
         job.wrap.max_width = wrap_width;
         job.wrap.break_anywhere = true;
@@ -749,7 +2871,7 @@ impl GuiApp {
             .unwrap_or_else(|| egui::FontId::new(13.0, egui::FontFamily::Monospace));
         let line_height = (font_id.size + 6.0).max(16.0);
 
-        let mut current_kind: Option<LogKind> = None;
+        let mut current_kind = kind;
         let mut lines = text.split('\n').peekable();
         while let Some(line) = lines.next() {
             let trimmed = line.trim();
@@ -759,7 +2881,7 @@ impl GuiApp {
                 ..Default::default()
             };
 
-            if let Some(kind) = GuiApp::codex_line_kind(trimmed) {
+            if let Some(kind) = codex_label_kind(trimmed) {
                 current_kind = Some(kind);
                 format.color = GuiApp::codex_color_for_kind(kind);
                 format.background = codex_label_bg(kind);
@@ -776,6 +2898,8 @@ impl GuiApp {
                 format.color = codex_info_color();
             }
 
+            format.color = themed_fg(ui.visuals(), format.color);
+            format.background = themed_bg(ui.visuals(), format.background, 0.85);
             job.append(line, 0.0, format.clone());
             if lines.peek().is_some() {
                 job.append("\n", 0.0, format);
@@ -784,15 +2908,6 @@ impl GuiApp {
         job
     }
 
-    fn codex_line_kind(line: &str) -> Option<LogKind> {
-        match line {
-            "Assistant" => Some(LogKind::Assistant),
-            "Utilisateur" => Some(LogKind::User),
-            "Action" => Some(LogKind::Action),
-            _ => None,
-        }
-    }
-
     fn codex_is_error_line(line: &str) -> bool {
         let lower = line.to_lowercase();
         lower.starts_with("erreur")
@@ -816,41 +2931,71 @@ impl GuiApp {
         }
     }
 
-    fn render_plain_log(&self, entries: &[LogLine]) -> String {
-        let mut out = String::new();
-        for (idx, entry) in entries.iter().enumerate() {
-            out.push_str(&entry.text);
-            if idx + 1 < entries.len() {
-                out.push('\n');
-            }
-        }
-        out
+    fn render_plain_log(&self, entries: &[LogLine], filter: LogFilter) -> String {
+        entries
+            .iter()
+            .filter(|entry| filter.accepts(entry.kind.level()))
+            .map(LogLine::display_text)
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 
     fn refresh_title(&mut self) {
-        if let Some(current) = &self.current {
+        if let Some(current) = self.tabs.active().map(|tab| &tab.file) {
             let dirty = if current.dirty { " *" } else { "" };
             self.title = format!("{APP_NAME}{dirty}");
-            self.sub_title = format!("{}  ({})", current.path.display(), current.encoding);
+            self.sub_title = format!(
+                "{}  ({}, {})",
+                current.path.display(),
+                current.encoding,
+                current.line_ending.label()
+            );
         } else {
             self.title = APP_NAME.to_string();
             self.sub_title = self.root_dir.display().to_string();
         }
+        self.refresh_doc_stats();
+    }
+
+    /// Statistiques de la barre d'etat : recomptage de la ligne du curseur si possible.
+    fn refresh_doc_stats(&mut self) {
+        let Some(tab) = self.tabs.active().filter(|tab| !tab.file.read_only()) else {
+            self.doc_stats = None;
+            return;
+        };
+        let row = self.editor_cursor.0;
+        let row_text = tab.buffer.split('\n').nth(row).unwrap_or("");
+        let fresh = self.doc_stats.as_mut().is_some_and(|stats| {
+            stats.update(
+                &tab.file.path,
+                tab.file.dirty,
+                self.editor_line_count,
+                tab.buffer.len(),
+                row,
+                row_text,
+            )
+        });
+        if !fresh {
+            let lines: Vec<&str> = tab.buffer.split('\n').collect();
+            self.doc_stats = Some(DocumentStats::scan(
+                &tab.file.path,
+                tab.file.dirty,
+                &lines,
+                row,
+            ));
+        }
     }
 
     fn push_log(&mut self, target: LogTarget, msg: String, kind: LogKind) {
-        let lines: Vec<String> = msg.split('\n').map(|s| s.to_string()).collect();
+        let lines = msg
+            .split('\n')
+            .map(|line| LogLine::new(line.to_string(), kind));
+        let collapse = self.core.settings().collapse_log_repeats();
         let store = match target {
             LogTarget::Main => &mut self.log,
             LogTarget::Codex => &mut self.codex_log,
         };
-        for line in lines {
-            store.push(LogLine { text: line, kind });
-        }
-        if store.len() > LOG_LIMIT {
-            let drain = store.len() - LOG_LIMIT;
-            store.drain(0..drain);
-        }
+        append_log_lines(store, lines, collapse, LogFilter::All);
         if matches!(target, LogTarget::Codex) {
             self.codex_log_dirty = true;
         }
@@ -865,12 +3010,7 @@ impl GuiApp {
     }
 
     fn log_issue(&mut self, msg: &str, niveau: &str, contexte: &str, target: LogTarget) {
-        let kind = if niveau == "avertissement" {
-            LogKind::Warn
-        } else {
-            LogKind::Error
-        };
-        self.push_log(target, msg.to_string(), kind);
+        self.push_log(target, msg.to_string(), LogKind::from_niveau(niveau));
         self.core.record_issue(niveau, msg, contexte, None);
     }
 
@@ -898,20 +3038,161 @@ impl GuiApp {
         self.core.tools_env()
     }
 
-    fn wheelhouse_path(&self) -> Option<PathBuf> {
-        self.core.wheelhouse_path()
+    fn current(&self) -> Option<&OpenFile> {
+        self.tabs.active().map(|tab| &tab.file)
+    }
+
+    fn current_mut(&mut self) -> Option<&mut OpenFile> {
+        self.tabs.active_mut().map(|tab| &mut tab.file)
+    }
+
+    /// Onglets modifies concernes par l'action (l'onglet a fermer, sinon tous).
+    fn unsaved_tabs(&self, action: &PendingAction) -> Vec<usize> {
+        match action {
+            PendingAction::CloseTab(path) => self
+                .tabs
+                .position(path)
+                .filter(|idx| self.tabs.get(*idx).is_some_and(|tab| tab.file.dirty))
+                .into_iter()
+                .collect(),
+            PendingAction::ReloadTree | PendingAction::Quit => self.tabs.dirty_indices(),
+        }
+    }
+
+    /// Vrai si l'action peut se faire tout de suite, sinon demande quoi faire des modifs.
+    fn confirm_discard(&mut self, action: PendingAction) -> bool {
+        let dirty = self.unsaved_tabs(&action);
+        let Some(current) = dirty.first().and_then(|idx| self.tabs.get(*idx)) else {
+            return true;
+        };
+        let name = tab_name(&current.file.path);
+        let label = match dirty.len() {
+            1 => format!("{name} modifie"),
+            count => format!("{name} et {} autre(s) modifies", count - 1),
+        };
+        self.open_prompt(PromptKind::UnsavedChanges(action), &label);
+        false
+    }
+
+    fn resolve_unsaved(&mut self, action: PendingAction, save: bool) {
+        let dirty = self.unsaved_tabs(&action);
+        if save {
+            for idx in dirty {
+                self.save_tab(idx);
+            }
+            if !self.unsaved_tabs(&action).is_empty() {
+                return;
+            }
+        } else {
+            // Modifications abandonnees : leurs copies de secours aussi.
+            let paths: Vec<PathBuf> = dirty
+                .iter()
+                .filter_map(|idx| self.tabs.get(*idx))
+                .map(|tab| tab.file.path.clone())
+                .collect();
+            for path in &paths {
+                self.discard_autosave(path);
+            }
+            if action == PendingAction::ReloadTree {
+                // Les onglets reviennent au contenu du disque.
+                let active = self.current().map(|current| current.path.clone());
+                for path in paths {
+                    self.load_file(path);
+                }
+                if let Some(idx) = active.and_then(|path| self.tabs.position(&path)) {
+                    self.tabs.activate(idx);
+                    self.on_tab_change();
+                }
+            }
+        }
+        match action {
+            PendingAction::CloseTab(path) => self.close_tab(&path),
+            PendingAction::ReloadTree => self.reload_tree(),
+            PendingAction::Quit => self.quit_confirmed = true,
+        }
     }
 
+    /// Active l'onglet du fichier s'il est deja ouvert, sinon l'ouvre dans un nouvel onglet.
     fn open_file(&mut self, path: PathBuf) {
-        let opened = match open_workspace_file(self.core.workspace(), path) {
+        match self.tabs.position(&path) {
+            Some(idx) => self.activate_tab(idx),
+            None => self.load_file(path),
+        }
+    }
+
+    fn activate_tab(&mut self, idx: usize) {
+        if self.tabs.activate(idx) {
+            self.on_tab_change();
+        }
+    }
+
+    fn action_cycle_tab(&mut self, forward: bool) {
+        if self.tabs.len() < 2 {
+            return;
+        }
+        self.tabs.cycle(forward);
+        self.on_tab_change();
+    }
+
+    fn action_close_tab(&mut self) {
+        if let Some(path) = self.current().map(|current| current.path.clone()) {
+            self.request_close_tab(path);
+        }
+    }
+
+    fn request_close_tab(&mut self, path: PathBuf) {
+        if self.confirm_discard(PendingAction::CloseTab(path.clone())) {
+            self.close_tab(&path);
+        }
+    }
+
+    fn close_tab(&mut self, path: &Path) {
+        if let Some(idx) = self.tabs.position(path) {
+            self.tabs.close(idx);
+            self.on_tab_change();
+        }
+    }
+
+    /// Apres un changement d'onglet : fichier garde visible dans l'arborescence, position, titre.
+    fn on_tab_change(&mut self) {
+        let path = self.current().map(|current| current.path.clone());
+        self.tree.data.set_keep_visible(path);
+        let line_count = self
+            .tabs
+            .active()
+            .map_or(0, |tab| tab.buffer.split('\n').count());
+        self.update_editor_position((0, 0), line_count);
+        self.doc_stats = None;
+        self.refresh_title();
+    }
+
+    fn load_file(&mut self, path: PathBuf) {
+        let result = open_workspace_file(self.core.workspace(), path);
+        self.open_in_tab(result);
+    }
+
+    /// Ouvre un fichier signale binaire avec l'encodage devine (UTF-16 sans BOM, CJK...).
+    fn force_open(&mut self, path: PathBuf) {
+        let result = open_workspace_file_anyway(self.core.workspace(), path);
+        self.open_in_tab(result);
+    }
+
+    fn open_in_tab(&mut self, result: Result<OpenedWorkspaceFile, OpenWorkspaceFileError>) {
+        let opened = match result {
             Ok(opened) => opened,
             Err(OpenWorkspaceFileError::Binary(path)) => {
                 self.log_issue(
-                    &format!("Binaire/non texte ignore: {}", path.display()),
+                    &format!("Binaire/non texte, apercu hexadecimal: {}", path.display()),
                     "avertissement",
                     "ouverture_fichier",
                     LogTarget::Main,
                 );
+                let preview = open_workspace_hex_preview(self.core.workspace(), path.clone());
+                self.open_in_tab(preview);
+                if self.prompt.is_none() {
+                    let label = format!("{} semble binaire. Ouvrir comme texte ?", tab_name(&path));
+                    self.open_prompt(PromptKind::OpenAnyway(path), &label);
+                }
                 return;
             }
             Err(OpenWorkspaceFileError::Hidden(path)) => {
@@ -942,70 +3223,971 @@ impl GuiApp {
                 return;
             }
         };
-        self.editor_text = opened.text;
-        self.current = Some(OpenFile {
-            path: opened.path,
-            encoding: opened.encoding,
-            dirty: false,
-        });
-        self.refresh_title();
-    }
-    fn action_save(&mut self) {
-        let (path, encoding, dirty) = match self.current.as_ref() {
-            Some(current) => (
-                current.path.clone(),
-                current.encoding.clone(),
-                current.dirty,
-            ),
+        let tab = EditorTab {
+            buffer: opened.text,
+            file: OpenFile {
+                path: opened.path,
+                encoding: opened.encoding,
+                bom: opened.bom,
+                line_ending: opened.line_ending,
+                dirty: false,
+                preview: opened.preview,
+                missing_on_disk: false,
+            },
+        };
+        match self.tabs.position(&tab.file.path) {
+            Some(idx) => {
+                if let Some(existing) = self.tabs.get_mut(idx) {
+                    *existing = tab;
+                }
+                self.tabs.activate(idx);
+            }
             None => {
-                self.log_issue(
-                    "Aucun fichier ouvert.",
-                    "avertissement",
-                    "sauvegarde",
-                    LogTarget::Main,
-                );
-                return;
+                self.tabs.open(tab);
             }
+        }
+        self.on_tab_change();
+        if let Some(path) = self.current().map(|current| current.path.clone()) {
+            self.offer_autosave_recovery(&path);
+        }
+    }
+
+    /// Propose de restaurer une copie de secours plus recente que le fichier (arret brutal).
+    fn offer_autosave_recovery(&mut self, path: &Path) {
+        let Some(autosave) = newer_autosave(path) else {
+            return;
         };
-        if !dirty {
+        if self.prompt.is_some() {
+            self.log_issue(
+                &format!("Copie de secours plus recente: {}", autosave.display()),
+                "avertissement",
+                "autosave",
+                LogTarget::Main,
+            );
             return;
         }
+        let label = format!("Copie de secours de {}", tab_name(path));
+        self.open_prompt(PromptKind::RecoverAutosave(path.to_path_buf()), &label);
+    }
 
-        let content = self.editor_text.clone();
-        let result = write_text_with_encoding(&path, &encoding, &content);
-        match result {
-            Ok(used_utf8_fallback) => {
-                if used_utf8_fallback {
-                    self.log_issue(
-                        &format!("Sauvegarde en UTF-8 (fallback) {}", path.display()),
-                        "avertissement",
-                        "sauvegarde",
-                        LogTarget::Main,
-                    );
-                } else {
-                    self.log_ui(format!("Sauvegarde {}", path.display()));
-                }
-                if let Some(current) = self.current.as_mut() {
-                    if used_utf8_fallback {
-                        current.encoding = "utf-8".to_string();
-                    }
-                    current.dirty = false;
+    /// Remplace le contenu de l'onglet par la copie de secours (fichier non modifie).
+    fn restore_autosave(&mut self, path: &Path) {
+        let Some(idx) = self.tabs.position(path) else {
+            return;
+        };
+        match read_autosave(path) {
+            Ok(text) => {
+                if let Some(tab) = self.tabs.get_mut(idx) {
+                    tab.buffer = text;
+                    tab.file.dirty = true;
                 }
-                self.refresh_title();
-            }
-            Err(err) => {
-                self.log_issue(
-                    &format!("Erreur sauvegarde: {} ({err})", path.display()),
-                    "erreur",
-                    "sauvegarde",
-                    LogTarget::Main,
-                );
+                self.tabs.activate(idx);
+                self.on_tab_change();
+                self.log_ui(format!(
+                    "Copie de secours restauree (Ctrl+S pour l'enregistrer): {}",
+                    path.display()
+                ));
             }
-        }
-    }
+            Err(err) => self.log_issue(
+                &format!("Copie de secours illisible {}: {err}", path.display()),
+                "erreur",
+                "autosave",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    fn discard_autosave(&mut self, path: &Path) {
+        if let Err(err) = remove_autosave(path) {
+            self.log_issue(
+                &format!("Copie de secours non supprimee {}: {err}", path.display()),
+                "avertissement",
+                "autosave",
+                LogTarget::Main,
+            );
+        }
+    }
+
+    /// Ecrit `fichier.autosave` pour chaque onglet modifie ; les fichiers restent intacts.
+    fn autosave_tabs(&mut self) {
+        self.last_autosave = Instant::now();
+        let errors: Vec<String> = self
+            .tabs
+            .iter()
+            .filter(|tab| tab.file.dirty)
+            .filter_map(|tab| {
+                write_autosave(&tab.file.path, &tab.buffer)
+                    .err()
+                    .map(|err| {
+                        format!(
+                            "Copie de secours impossible {}: {err}",
+                            tab.file.path.display()
+                        )
+                    })
+            })
+            .collect();
+        for msg in errors {
+            self.log_issue(&msg, "avertissement", "autosave", LogTarget::Main);
+        }
+    }
+
+    /// Selectionne le fichier ouvert dans l'arborescence (dossiers parents deplies).
+    fn action_reveal_current(&mut self) {
+        let Some(path) = self.current().map(|current| current.path.clone()) else {
+            return;
+        };
+        if self.tree.data.reveal(&path).is_some() {
+            self.tree.selected = Some(path);
+            self.tree_scroll_to_selected = true;
+        } else {
+            self.log_ui(format!("Absent de l'arborescence: {}", path.display()));
+        }
+    }
+
+    /// Affiche ou masque les fichiers ignores (.gitignore, .usbideignore, motifs par defaut).
+    fn action_toggle_show_ignored(&mut self) {
+        let show = !self.tree.data.show_ignored();
+        self.tree.data.set_show_ignored(show, self.core.workspace());
+        let label = if show { "affiches" } else { "masques" };
+        self.log_ui(format!("Fichiers ignores: {label}"));
+    }
+
+    /// Alt+- / Alt+= : replie ou deplie toute l'arborescence ; la selection reste sur son
+    /// chemin, ou sur le dossier replie qui le contient.
+    fn action_fold_tree(&mut self, expand: bool) {
+        let truncated = if expand {
+            self.tree.data.expand_all()
+        } else {
+            self.tree.data.collapse_all();
+            false
+        };
+        if let Some(idx) = self
+            .tree
+            .selected
+            .as_deref()
+            .and_then(|path| self.tree.data.reselect(path))
+        {
+            self.tree.selected = Some(self.tree.data.visible()[idx].path.clone());
+            self.tree_scroll_to_selected = true;
+        }
+        self.log_fold_tree(expand, truncated);
+    }
+
+    fn log_fold_tree(&mut self, expand: bool, truncated: bool) {
+        if truncated {
+            self.log_issue(
+                &format!(
+                    "Arborescence depliee sur {EXPAND_ALL_MAX_DEPTH} niveaux : les dossiers plus profonds restent replies."
+                ),
+                "avertissement",
+                "arborescence",
+                LogTarget::Main,
+            );
+        } else {
+            let label = if expand { "depliee" } else { "repliee" };
+            self.log_ui(format!("Arborescence {label}."));
+        }
+    }
+
+    /// Affiche ou masque les dotfiles (le fichier ouvert reste visible).
+    fn action_toggle_show_hidden(&mut self) {
+        let show = !self.tree.data.show_hidden();
+        self.tree.data.set_show_hidden(show);
+        let label = if show { "affiches" } else { "masques" };
+        self.log_ui(format!("Fichiers caches: {label}"));
+        self.save_session();
+    }
+
+    /// Seul le rendu est filtre : le log complet reste en memoire et dans l'export.
+    fn action_cycle_log_filter(&mut self, target: LogTarget) {
+        match target {
+            LogTarget::Main => self.log_filter = self.log_filter.next(),
+            LogTarget::Codex => {
+                self.codex_log_filter = self.codex_log_filter.next();
+                self.codex_log_dirty = true;
+            }
+        }
+    }
+
+    /// Alt+M : masque l'arbre et les panneaux du bas ; leurs tailles et l'etat de l'editeur restent.
+    fn action_toggle_zen_mode(&mut self) {
+        self.zen_mode = !self.zen_mode;
+        let label = if self.zen_mode { "actif" } else { "inactif" };
+        self.log_ui(format!("Mode zen: {label}"));
+    }
+
+    fn action_toggle_word_wrap(&mut self) {
+        self.word_wrap = !self.word_wrap;
+        let label = if self.word_wrap { "actif" } else { "inactif" };
+        self.log_ui(format!("Retour a la ligne: {label}"));
+        self.save_session();
+    }
+
+    fn action_font_size(&mut self, ctx: &egui::Context, delta: f32) {
+        let size = (self.font_size + delta).clamp(FONT_SIZE_MIN, FONT_SIZE_MAX);
+        if size == self.font_size {
+            return;
+        }
+        self.font_size = size;
+        configure_style(ctx, self.light_theme, self.font_size);
+        self.codex_log_dirty = true;
+        self.log_ui(format!("Taille de police: {size}"));
+        self.save_session();
+    }
+
+    fn action_toggle_theme(&mut self, ctx: &egui::Context) {
+        self.light_theme = !self.light_theme;
+        configure_style(ctx, self.light_theme, self.font_size);
+        self.codex_log_dirty = true;
+        let label = if self.light_theme { "clair" } else { "sombre" };
+        self.log_ui(format!("Theme: {label}"));
+        self.save_session();
+    }
+
+    /// Diagnostics du dernier lint ; un clic ouvre le fichier a la ligne signalee.
+    fn draw_diagnostics(&mut self, ctx: &egui::Context) {
+        if !self.show_diagnostics {
+            return;
+        }
+        let mut open = true;
+        let mut chosen: Option<Diagnostic> = None;
+        let root = &self.root_dir;
+        let diagnostics = &self.diagnostics;
+        egui::Window::new(format!("Diagnostics ({})", diagnostics.len()))
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                if diagnostics.is_empty() {
+                    ui.label(RichText::new("Aucun diagnostic.").weak());
+                    return;
+                }
+                ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for diagnostic in diagnostics.iter() {
+                        let color = match diagnostic.severity {
+                            DiagnosticSeverity::Error => Color32::from_rgb(240, 100, 100),
+                            DiagnosticSeverity::Warning => Color32::from_rgb(240, 200, 120),
+                            DiagnosticSeverity::Note => Color32::from_gray(210),
+                        };
+                        let color = themed_fg(ui.visuals(), color);
+                        let text = RichText::new(diagnostic.label(root))
+                            .monospace()
+                            .color(color);
+                        if ui
+                            .selectable_label(false, text)
+                            .on_hover_text(diagnostic.severity.label())
+                            .clicked()
+                        {
+                            chosen = Some(diagnostic.clone());
+                        }
+                    }
+                });
+            });
+        self.show_diagnostics = open;
+        if let Some(diagnostic) = chosen {
+            self.jump_to_diagnostic(diagnostic);
+        }
+    }
+
+    fn action_environment_report(&mut self) {
+        self.environment_report = Some(self.core.environment_report());
+    }
+
+    /// Fenetre "Environnement" : chemins resolus et variables portables, texte copiable.
+    fn draw_environment_report(&mut self, ctx: &egui::Context) {
+        let Some(lines) = self.environment_report.as_ref() else {
+            return;
+        };
+        let text = lines.join("\n");
+        let mut refresh = false;
+        let mut close = false;
+        egui::Window::new("Environnement")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            TextEdit::multiline(&mut text.as_str())
+                                .code_editor()
+                                .desired_width(560.0),
+                        );
+                    });
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Rafraichir").clicked() {
+                        refresh = true;
+                    }
+                    if ui.button("Copier").clicked() {
+                        ctx.copy_text(text.clone());
+                        self.log_ui(
+                            "Rapport d'environnement copie dans le presse-papiers.".to_string(),
+                        );
+                    }
+                    if ui.button("Fermer").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close {
+            self.environment_report = None;
+        } else if refresh {
+            self.action_environment_report();
+        }
+    }
+
+    /// Ctrl+Alt+F : cherche dans le log du dernier panneau utilise.
+    fn action_find_in_log(&mut self) {
+        self.log_search = Some(LogSearch::new(self.log_focus));
+        self.log_search_scroll = false;
+    }
+
+    fn log_search_matches_for(&self, search: &LogSearch) -> Vec<usize> {
+        let (entries, filter) = match search.target {
+            LogTarget::Main => (&self.log, self.log_filter),
+            LogTarget::Codex => (&self.codex_log, self.codex_log_filter),
+        };
+        search.matches(
+            entries
+                .iter()
+                .filter(|entry| filter.accepts(entry.kind.level()))
+                .map(|entry| entry.text.as_str()),
+        )
+    }
+
+    /// Fenetre de recherche : saisie en direct, Entree / Maj+Entree pour suivant / precedent.
+    fn draw_log_search(&mut self, ctx: &egui::Context) {
+        let Some(mut search) = self.log_search.take() else {
+            return;
+        };
+        let matches = self.log_search_matches_for(&search);
+        let mut step = None;
+        let mut close = false;
+        egui::Window::new("Chercher dans les logs")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-20.0, 60.0))
+            .show(ctx, |ui| {
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    changed |= ui
+                        .selectable_value(&mut search.target, LogTarget::Main, "Journal")
+                        .changed();
+                    changed |= ui
+                        .selectable_value(&mut search.target, LogTarget::Codex, "Sortie Codex")
+                        .changed();
+                });
+                let response = ui.add(
+                    TextEdit::singleline(&mut search.query)
+                        .hint_text("texte a chercher")
+                        .desired_width(320.0),
+                );
+                if !response.has_focus() && !response.lost_focus() {
+                    response.request_focus();
+                }
+                changed |= response.changed();
+                changed |= ui
+                    .checkbox(&mut search.case_sensitive, "Respecter la casse")
+                    .changed();
+                if changed {
+                    search.current = None;
+                    step = Some(false);
+                }
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    step = Some(!ui.input(|i| i.modifiers.shift));
+                    response.request_focus();
+                }
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Precedent").clicked() {
+                        step = Some(false);
+                    }
+                    if ui.button("Suivant").clicked() {
+                        step = Some(true);
+                    }
+                    if ui.button("Fermer").clicked() {
+                        close = true;
+                    }
+                    ui.label(RichText::new(search.status(&matches)).color(Color32::from_gray(150)));
+                });
+            });
+        if close {
+            return;
+        }
+        if let Some(forward) = step {
+            let matches = self.log_search_matches_for(&search);
+            search.step(&matches, forward);
+            self.log_search_scroll = search.current.is_some();
+        }
+        self.log_search = Some(search);
+    }
+
+    fn action_settings(&mut self) {
+        self.settings_draft = Some(self.core.settings().clone());
+    }
+
+    /// Fenetre Parametres : "Enregistrer" ecrit settings.toml et applique les valeurs.
+    fn draw_settings(&mut self, ctx: &egui::Context) {
+        let Some(draft) = self.settings_draft.as_mut() else {
+            return;
+        };
+        let mut save = false;
+        let mut cancel = false;
+        egui::Window::new("Parametres")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("settings_grid")
+                    .num_columns(3)
+                    .spacing([8.0, 6.0])
+                    .show(ui, |ui| {
+                        let text_row =
+                            |ui: &mut egui::Ui, label: &str, value: &mut String, env: &str| {
+                                ui.label(label);
+                                ui.add(TextEdit::singleline(value).desired_width(280.0));
+                                env_note(ui, env);
+                                ui.end_row();
+                            };
+                        text_row(ui, "Outils dev", &mut draft.dev_tools, "USBIDE_DEV_TOOLS");
+                        text_row(
+                            ui,
+                            "Package Codex",
+                            &mut draft.codex_package,
+                            "USBIDE_CODEX_NPM_PACKAGE",
+                        );
+                        text_row(
+                            ui,
+                            "Source Codex locale",
+                            &mut draft.codex_npm_source,
+                            "USBIDE_CODEX_NPM_SOURCE",
+                        );
+                        text_row(
+                            ui,
+                            "Dossier des caches",
+                            &mut draft.cache_dir,
+                            "USBIDE_CACHE_DIR",
+                        );
+                        text_row(
+                            ui,
+                            "Nettoyer a la sauvegarde",
+                            &mut draft.trim_on_save,
+                            "USBIDE_TRIM_ON_SAVE",
+                        );
+                        text_row(
+                            ui,
+                            "Modele Codex",
+                            &mut draft.codex_model,
+                            "USBIDE_CODEX_MODEL",
+                        );
+                        text_row(ui, "Python", &mut draft.python, "USBIDE_PYTHON");
+                        text_row(ui, "Proxy HTTP", &mut draft.http_proxy, "HTTP_PROXY");
+                        text_row(ui, "Proxy HTTPS", &mut draft.https_proxy, "HTTPS_PROXY");
+                        text_row(ui, "Sans proxy", &mut draft.no_proxy, "NO_PROXY");
+
+                        ui.label("Sandbox Codex");
+                        egui::ComboBox::from_id_source("settings_sandbox")
+                            .selected_text(codex_sandbox_label(draft.codex_sandbox))
+                            .show_ui(ui, |ui| {
+                                for mode in [
+                                    CodexSandboxMode::ReadOnly,
+                                    CodexSandboxMode::WorkspaceWrite,
+                                    CodexSandboxMode::DangerFullAccess,
+                                ] {
+                                    ui.selectable_value(
+                                        &mut draft.codex_sandbox,
+                                        mode,
+                                        codex_sandbox_label(mode),
+                                    );
+                                }
+                            });
+                        env_note(ui, "USBIDE_CODEX_SANDBOX");
+                        ui.end_row();
+
+                        ui.label("Approbation Codex");
+                        egui::ComboBox::from_id_source("settings_approval")
+                            .selected_text(codex_approval_label(draft.codex_approval))
+                            .show_ui(ui, |ui| {
+                                for policy in [
+                                    CodexApprovalPolicy::Untrusted,
+                                    CodexApprovalPolicy::OnFailure,
+                                    CodexApprovalPolicy::OnRequest,
+                                    CodexApprovalPolicy::Never,
+                                ] {
+                                    ui.selectable_value(
+                                        &mut draft.codex_approval,
+                                        policy,
+                                        codex_approval_label(policy),
+                                    );
+                                }
+                            });
+                        env_note(ui, "USBIDE_CODEX_APPROVAL");
+                        ui.end_row();
+
+                        ui.label("Vue Codex");
+                        egui::ComboBox::from_id_source("settings_codex_view")
+                            .selected_text(draft.codex_view.label())
+                            .show_ui(ui, |ui| {
+                                for view in [CodexView::Compact, CodexView::Normal, CodexView::Raw]
+                                {
+                                    ui.selectable_value(&mut draft.codex_view, view, view.label());
+                                }
+                            });
+                        env_note(ui, "USBIDE_CODEX_VIEW");
+                        ui.end_row();
+
+                        ui.label("Formateur");
+                        egui::ComboBox::from_id_source("settings_formatter")
+                            .selected_text(draft.formatter.as_str())
+                            .show_ui(ui, |ui| {
+                                for formatter in [Formatter::Black, Formatter::Ruff] {
+                                    ui.selectable_value(
+                                        &mut draft.formatter,
+                                        formatter,
+                                        formatter.as_str(),
+                                    );
+                                }
+                            });
+                        env_note(ui, "USBIDE_FORMATTER");
+                        ui.end_row();
+
+                        ui.label("");
+                        ui.checkbox(&mut draft.codex_allow_api_key, "Autoriser OPENAI_API_KEY");
+                        env_note(ui, "USBIDE_CODEX_ALLOW_API_KEY");
+                        ui.end_row();
+
+                        ui.label("");
+                        ui.checkbox(
+                            &mut draft.codex_allow_custom_base,
+                            "Autoriser OPENAI_BASE_URL",
+                        );
+                        env_note(ui, "USBIDE_CODEX_ALLOW_CUSTOM_BASE");
+                        ui.end_row();
+
+                        ui.label("Copie de secours");
+                        ui.add(
+                            egui::DragValue::new(&mut draft.autosave_secs)
+                                .clamp_range(0..=3600)
+                                .suffix(" s (0 : desactivee)"),
+                        );
+                        env_note(ui, "USBIDE_AUTOSAVE_SECS");
+                        ui.end_row();
+
+                        ui.label("Tabulation");
+                        ui.add(
+                            egui::DragValue::new(&mut draft.tab_width)
+                                .clamp_range(1..=MAX_TAB_WIDTH)
+                                .suffix(" colonnes"),
+                        );
+                        env_note(ui, "USBIDE_TAB_WIDTH");
+                        ui.end_row();
+
+                        ui.label("Touche Tab");
+                        egui::ComboBox::from_id_source("settings_insert_spaces")
+                            .selected_text(insert_spaces_label(draft.insert_spaces))
+                            .show_ui(ui, |ui| {
+                                for choice in [None, Some(true), Some(false)] {
+                                    ui.selectable_value(
+                                        &mut draft.insert_spaces,
+                                        choice,
+                                        insert_spaces_label(choice),
+                                    );
+                                }
+                            });
+                        env_note(ui, "USBIDE_INSERT_SPACES");
+                        ui.end_row();
+
+                        ui.label("");
+                        ui.checkbox(
+                            &mut draft.offline,
+                            "Hors-ligne strict (wheelhouse pip, cache npm)",
+                        );
+                        env_note(ui, "USBIDE_OFFLINE");
+                        ui.end_row();
+
+                        ui.label("");
+                        ui.checkbox(
+                            &mut draft.collapse_log_repeats,
+                            "Regrouper les lignes de log repetees (xN)",
+                        );
+                        env_note(ui, "USBIDE_LOG_COLLAPSE");
+                        ui.end_row();
+                        ui.label("");
+                        ui.checkbox(
+                            &mut draft.shell_guard,
+                            "Confirmer les commandes shell risquees",
+                        );
+                        env_note(ui, "USBIDE_SHELL_GUARD");
+                        ui.end_row();
+                    });
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    cancel = true;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Enregistrer").clicked() {
+                        save = true;
+                    }
+                    if ui.button("Annuler").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if cancel {
+            self.settings_draft = None;
+        } else if save && let Some(draft) = self.settings_draft.take() {
+            self.apply_settings(draft);
+        }
+    }
+
+    /// Gestionnaire de fichiers du systeme sur `dir`, sans attendre ; avertit s'il manque.
+    fn open_in_file_manager(&mut self, dir: &Path) {
+        let argv = file_manager_argv(dir);
+        let env_map = self.portable_env(std::env::vars().collect());
+        if resolve_in_path(&argv[0], &env_map).is_none() {
+            self.log_issue(
+                &format!(
+                    "{} introuvable : ouvre {} manuellement.",
+                    argv[0],
+                    dir.display()
+                ),
+                "avertissement",
+                "gestionnaire de fichiers",
+                LogTarget::Main,
+            );
+            return;
+        }
+        self.log_ui(format!("Ouverture de {}", dir.display()));
+        self.spawn_process(
+            argv,
+            env_map,
+            "gestionnaire de fichiers",
+            LogTarget::Main,
+            ProcessKind::OpenFolder,
+        );
+    }
+
+    fn action_clear_caches(&mut self) {
+        let label = cache_usage_label(&self.core.cache_usage());
+        self.open_prompt(
+            PromptKind::ConfirmClearCaches,
+            &format!("Nettoyer caches : {label} ?"),
+        );
+    }
+
+    fn clear_caches(&mut self) {
+        let (freed, errors) = self.core.clear_caches();
+        for error in errors {
+            self.log_issue(
+                &format!("Nettoyage du cache incomplet: {error}"),
+                "avertissement",
+                "caches",
+                LogTarget::Main,
+            );
+        }
+        self.log_ui(format!("Caches nettoyes : {} liberes.", format_size(freed)));
+    }
+
+    fn apply_settings(&mut self, settings: Settings) {
+        match self.core.save_settings(settings) {
+            Ok(path) => {
+                let settings = self.core.settings();
+                self.codex_view = settings.codex_view();
+                self.codex_sandbox_mode = settings.codex_sandbox();
+                self.codex_approval_policy = settings.codex_approval();
+                self.codex_model = settings.codex_model();
+                self.python_label = self.core.python_status_label();
+                self.tool_status = self.core.tool_status();
+                self.detect_pythons();
+                self.log_ui(format!("Parametres enregistres: {}", path.display()));
+            }
+            Err(err) => self.log_issue(
+                &format!("Impossible d'enregistrer les parametres: {err}"),
+                "erreur",
+                "settings",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    /// Faux (avec un avertissement) si l'onglet courant n'est qu'un apercu en lecture seule.
+    fn current_editable(&mut self) -> bool {
+        if !self.current().is_some_and(OpenFile::read_only) {
+            return true;
+        }
+        self.log_issue(
+            "Apercu en lecture seule : modification impossible.",
+            "avertissement",
+            "apercu",
+            LogTarget::Main,
+        );
+        false
+    }
+
+    /// Force la fin de ligne utilisee a la prochaine sauvegarde (LF <-> CRLF).
+    fn action_toggle_line_ending(&mut self) {
+        if !self.current_editable() {
+            return;
+        }
+        let Some(current) = self.current_mut() else {
+            return;
+        };
+        current.line_ending = current.line_ending.toggled();
+        current.dirty = true;
+        let label = current.line_ending.label();
+        self.log_ui(format!("Fin de ligne: {label} (appliquee a la sauvegarde)"));
+        self.refresh_title();
+    }
+
+    /// Demande l'encodage (pre-rempli avec celui du fichier) pour rouvrir ou enregistrer.
+    fn action_encoding_prompt(&mut self, kind: PromptKind) {
+        let Some(encoding) = self.current().map(|current| current.encoding.clone()) else {
+            self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "encodage",
+                LogTarget::Main,
+            );
+            return;
+        };
+        let verb = if kind == PromptKind::ReopenEncoding {
+            "Rouvrir"
+        } else {
+            "Enregistrer"
+        };
+        let label = format!("{verb} avec l'encodage ({})", COMMON_ENCODINGS.join(", "));
+        self.open_prompt_with(kind, &label, &encoding);
+    }
+
+    fn checked_encoding(&mut self, value: &str) -> Option<String> {
+        let encoding = normalize_encoding(value);
+        if encoding.is_none() {
+            self.log_issue(
+                &format!("Encodage inconnu: {value}"),
+                "avertissement",
+                "encodage",
+                LogTarget::Main,
+            );
+        }
+        encoding
+    }
+
+    /// Relit le fichier courant avec l'encodage choisi (detection trompee).
+    fn reopen_with_encoding(&mut self, value: &str) {
+        let Some(encoding) = self.checked_encoding(value) else {
+            return;
+        };
+        let Some(idx) = self.tabs.active_index() else {
+            return;
+        };
+        let Some(tab) = self.tabs.get(idx) else {
+            return;
+        };
+        if tab.file.dirty {
+            self.log_issue(
+                "Modifications non sauvees : enregistrer ou fermer l'onglet avant de rouvrir.",
+                "avertissement",
+                "encodage",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let path = tab.file.path.clone();
+        match read_text_with_encoding(&path, &encoding) {
+            Ok(text) => {
+                if let Some(tab) = self.tabs.get_mut(idx) {
+                    tab.file.line_ending = detect_line_ending(&text);
+                    tab.file.encoding = encoding.clone();
+                    tab.file.bom = has_bom(&path);
+                    tab.file.preview = None;
+                    tab.buffer = apply_line_ending(&text, LineEnding::Lf);
+                }
+                self.on_tab_change();
+                self.log_ui(format!("Rouvert en {encoding}: {}", path.display()));
+            }
+            Err(err) => self.log_issue(
+                &format!("Erreur relecture: {} ({err})", path.display()),
+                "erreur",
+                "encodage",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    /// Enregistre le fichier courant dans l'encodage choisi ; previent avant un repli UTF-8.
+    fn save_with_encoding(&mut self, value: &str, confirmed: bool) {
+        if !self.current_editable() {
+            return;
+        }
+        let Some(encoding) = self.checked_encoding(value) else {
+            return;
+        };
+        let Some(idx) = self.tabs.active_index() else {
+            return;
+        };
+        let Some(tab) = self.tabs.get(idx) else {
+            return;
+        };
+        if !confirmed && !can_encode(&encoding, &tab.buffer.clone()) {
+            let label = format!(
+                "{encoding} ne peut pas representer tout le texte : enregistrer en UTF-8 ?"
+            );
+            self.open_prompt(PromptKind::ConfirmLossySave(encoding), &label);
+            return;
+        }
+        if let Some(tab) = self.tabs.get_mut(idx) {
+            // Conversion : BOM ecrit pour que l'UTF-16 soit reconnu a la reouverture.
+            if tab.file.encoding != encoding {
+                tab.file.bom = true;
+            }
+            tab.file.encoding = encoding;
+            tab.file.dirty = true;
+        }
+        self.save_tab(idx);
+    }
+
+    fn action_save(&mut self) {
+        match self.tabs.active_index() {
+            Some(idx) => self.save_tab(idx),
+            None => self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "sauvegarde",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    fn save_tab(&mut self, idx: usize) {
+        // Fichier supprime hors de l'IDE : proposer de le recreer plutot que l'ecrire en
+        // silence ; une fois la disparition signalee, la sauvegarde le recree.
+        if let Some(path) = self.tabs.get(idx).map(|tab| tab.file.path.clone())
+            && !path.exists()
+            && self.tabs.mark_missing(idx)
+        {
+            self.on_file_deleted(idx, path);
+            return;
+        }
+        let Some(tab) = self.tabs.get(idx) else {
+            return;
+        };
+        // Un apercu (vue hexa, debut d'un gros fichier) ne doit jamais ecraser le fichier.
+        if !tab.file.dirty || tab.file.read_only() {
+            return;
+        }
+        let path = tab.file.path.clone();
+        let encoding = tab.file.encoding.clone();
+        let bom = tab.file.bom;
+        let line_ending = tab.file.line_ending;
+        if self.core.settings().trim_on_save(&path)
+            && let Some(normalized) = normalize_whitespace(&tab.buffer)
+        {
+            if self.tabs.active_index() == Some(idx) {
+                let line_count = normalized.split('\n').count();
+                let (row, col) = self.editor_cursor;
+                let cursor = (row.min(line_count - 1), col);
+                self.pending_goto = Some(cursor);
+                self.update_editor_position(cursor, line_count);
+            }
+            if let Some(tab) = self.tabs.get_mut(idx) {
+                tab.buffer = normalized;
+            }
+            self.doc_stats = None;
+            self.log_ui(format!(
+                "Espaces de fin de ligne et saut de ligne final normalises: {}",
+                path.display()
+            ));
+        }
+        let Some(tab) = self.tabs.get(idx) else {
+            return;
+        };
+        let content = apply_line_ending(&tab.buffer, line_ending);
+        let result = write_text_with_encoding(&path, &encoding, &content, bom);
+        match result {
+            Ok(used_utf8_fallback) => {
+                if used_utf8_fallback {
+                    self.log_issue(
+                        &format!("Sauvegarde en UTF-8 (fallback) {}", path.display()),
+                        "avertissement",
+                        "sauvegarde",
+                        LogTarget::Main,
+                    );
+                } else {
+                    self.log_ui(format!("Sauvegarde {}", path.display()));
+                }
+                if let Some(tab) = self.tabs.get_mut(idx) {
+                    if used_utf8_fallback {
+                        tab.file.encoding = "utf-8".to_string();
+                    }
+                    tab.file.dirty = false;
+                    tab.file.missing_on_disk = false;
+                }
+                self.discard_autosave(&path);
+                self.refresh_title();
+                self.refresh_git_status();
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur sauvegarde: {} ({err})", path.display()),
+                    "erreur",
+                    "sauvegarde",
+                    LogTarget::Main,
+                );
+            }
+        }
+    }
+
+    /// Ctrl+F5 : execute la selection via un script de `tmp/` ; sans selection, comme F5.
+    fn action_run_selection(&mut self, ctx: &egui::Context) {
+        let Some(selection) = self.editor_selection(ctx) else {
+            self.action_run();
+            return;
+        };
+        let script = match self.core.write_selection_script(&selection) {
+            Ok(script) => script,
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur ecriture de la selection: {err}"),
+                    "erreur",
+                    "execution_python",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let argv = python_run_argv(
+            &script,
+            self.core.run_venv().as_deref(),
+            &self.core.base_python(),
+        );
+        let lines = selection.lines().count();
+        self.log_ui(format!(
+            "$ {} ({lines} lignes selectionnees)",
+            argv.join(" ")
+        ));
+        let env_map = self.portable_env(std::env::vars().collect());
+        let before = self.core.running.len();
+        self.spawn_process(
+            argv,
+            env_map,
+            "execution selection",
+            LogTarget::Main,
+            ProcessKind::PythonRun,
+        );
+        match self.core.running.get_mut(before) {
+            Some(proc) => proc.temp_file = Some(script),
+            None => {
+                let _ = std::fs::remove_file(&script);
+            }
+        }
+    }
 
     fn action_run(&mut self) {
-        let (path, dirty) = match self.current.as_ref() {
+        let (path, dirty) = match self.current() {
             Some(current) => (current.path.clone(), current.dirty),
             None => {
                 self.log_issue(
@@ -1017,57 +4199,948 @@ impl GuiApp {
                 return;
             }
         };
-        let is_py = path
-            .extension()
-            .and_then(|s| s.to_str())
-            .map(|s| s.eq_ignore_ascii_case("py"))
-            .unwrap_or(false);
-        if !is_py {
+        let is_py = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.eq_ignore_ascii_case("py"))
+            .unwrap_or(false);
+        if !is_py {
+            self.log_issue(
+                "Ouvre un fichier .py.",
+                "avertissement",
+                "execution_python",
+                LogTarget::Main,
+            );
+            return;
+        }
+        if dirty {
+            self.action_save();
+        }
+        let argv = python_run_argv(
+            &path,
+            self.core.run_venv().as_deref(),
+            &self.core.base_python(),
+        );
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        let env_map = self.portable_env(std::env::vars().collect());
+        self.spawn_process(
+            argv,
+            env_map,
+            "execution python",
+            LogTarget::Main,
+            ProcessKind::PythonRun,
+        );
+    }
+
+    /// Passe par le presse-papiers d'egui (la selection du log Codex se copie aussi avec Ctrl+C).
+    fn action_copy_last_assistant(&mut self, ctx: &egui::Context) {
+        match self.last_assistant_message.clone() {
+            Some(text) => {
+                let chars = text.chars().count();
+                ctx.copy_text(text);
+                self.log_ui(format!(
+                    "Derniere reponse Codex copiee dans le presse-papiers ({chars} caracteres)."
+                ));
+            }
+            None => self.log_ui("Aucune reponse Codex a copier.".to_string()),
+        }
+    }
+
+    fn action_copy_codex_log(&mut self, ctx: &egui::Context) {
+        let text = self.render_plain_log(&self.codex_log, self.codex_log_filter);
+        if text.trim().is_empty() {
+            self.log_ui("Sortie Codex vide, rien a copier.".to_string());
+            return;
+        }
+        let chars = text.chars().count();
+        ctx.copy_text(text);
+        self.log_ui(format!(
+            "Sortie Codex copiee dans le presse-papiers ({chars} caracteres)."
+        ));
+    }
+
+    fn action_export_logs(&mut self) {
+        let sections = [
+            ("Journal", log_export_lines(&self.log)),
+            ("Codex", log_export_lines(&self.codex_log)),
+        ];
+        match crate::app_core::export_logs(self.core.workspace(), &sections) {
+            Ok(paths) => {
+                for path in paths {
+                    self.log_ui(format!("Logs exportes: {}", path.display()));
+                }
+            }
+            Err(err) => self.log_issue(
+                &format!("Erreur export logs: {err}"),
+                "erreur",
+                "export_logs",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    /// Ctrl+L : vide le log du dernier panneau utilise (Commande ou Codex).
+    fn action_clear_log(&mut self) {
+        self.clear_log(self.log_focus);
+    }
+
+    fn action_clear_all_logs(&mut self) {
+        self.log.clear();
+        self.codex_log.clear();
+        self.last_codex_message = None;
+        self.codex_log_dirty = true;
+        self.log_ui("journaux effaces".to_string());
+    }
+
+    /// Vide un seul panneau de log et le note dans ce panneau.
+    fn clear_log(&mut self, target: LogTarget) {
+        match target {
+            LogTarget::Main => {
+                self.log.clear();
+                self.log_ui("Journal efface.".to_string());
+            }
+            LogTarget::Codex => {
+                self.codex_log.clear();
+                self.last_codex_message = None;
+                self.codex_log_dirty = true;
+                self.codex_log_ui("Sortie Codex effacee.".to_string());
+            }
+        }
+    }
+
+    fn action_reload_tree(&mut self) {
+        if self.confirm_discard(PendingAction::ReloadTree) {
+            self.reload_tree();
+        }
+    }
+
+    fn reload_tree(&mut self) {
+        self.tree.reload(self.core.workspace());
+        self.file_index = None;
+        self.refresh_git_status();
+        self.log_ui("arborescence rechargee".to_string());
+    }
+
+    /// Env des commandes git si le root est un depot et git dans le PATH ; sinon explique pourquoi.
+    fn git_env(&mut self) -> Option<HashMap<String, String>> {
+        if !is_git_repo(&self.root_dir) {
+            self.log_issue(
+                "Pas de depot git a la racine (.git absent).",
+                "avertissement",
+                "git",
+                LogTarget::Main,
+            );
+            return None;
+        }
+        if self
+            .core
+            .running
+            .iter()
+            .any(|proc| matches!(proc.kind, ProcessKind::Git(_)))
+        {
+            self.log_ui("Commande git deja en cours.".to_string());
+            return None;
+        }
+        let env_map = self.tools_env();
+        if !tool_available("git", Some(&self.root_dir), Some(&env_map)).unwrap_or(false) {
+            self.log_issue(
+                "git introuvable dans le PATH.",
+                "avertissement",
+                "git",
+                LogTarget::Main,
+            );
+            return None;
+        }
+        Some(env_map)
+    }
+
+    fn run_git(
+        &mut self,
+        command: GitCommand,
+        argv: Vec<String>,
+        env_map: HashMap<String, String>,
+    ) {
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "git",
+            LogTarget::Main,
+            ProcessKind::Git(command),
+        );
+    }
+
+    fn action_git_commit(&mut self) {
+        if self.git_env().is_some() {
+            self.open_prompt(PromptKind::GitCommit, "Message du commit (tout indexer)");
+        }
+    }
+
+    /// Sauve les onglets puis indexe tout (`git add -A`) ; le commit suit la fin de l'ajout.
+    fn git_commit(&mut self, message: &str) {
+        if message.is_empty() {
+            self.log_issue(
+                "Message de commit vide.",
+                "avertissement",
+                "git",
+                LogTarget::Main,
+            );
+            return;
+        }
+        for idx in self.tabs.dirty_indices() {
+            self.save_tab(idx);
+        }
+        let Some(env_map) = self.git_env() else {
+            return;
+        };
+        self.git_commit_message = Some(message.to_string());
+        self.run_git(GitCommand::AddAll, git_add_all_argv(), env_map);
+    }
+
+    /// Lignes changees du buffer par rapport au fichier sur disque (sans git), dans le journal.
+    fn action_diff_with_disk(&mut self) {
+        let Some(tab) = self.tabs.active() else {
+            self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "diff",
+                LogTarget::Main,
+            );
+            return;
+        };
+        let path = tab.file.path.clone();
+        let buffer = tab.buffer.clone();
+        let disk = match read_text_with_encoding(&path, &tab.file.encoding) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                self.log_issue(
+                    &format!(
+                        "{} n'existe plus sur disque : tout le buffer est nouveau.",
+                        path.display()
+                    ),
+                    "avertissement",
+                    "diff",
+                    LogTarget::Main,
+                );
+                String::new()
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Lecture impossible: {} ({err})", path.display()),
+                    "erreur",
+                    "diff",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let rows = diff_rows(&disk, &buffer, DIFF_CONTEXT);
+        if rows.is_empty() {
+            self.log_ui(format!(
+                "Aucune difference avec le disque: {}",
+                path.display()
+            ));
+            return;
+        }
+        let count = |kind: DiffRowKind| rows.iter().filter(|row| row.kind == kind).count();
+        let (added, removed) = (count(DiffRowKind::Added), count(DiffRowKind::Removed));
+        self.log_ui(format!(
+            "Diff disque -> editeur {} : +{added} -{removed}",
+            path.display()
+        ));
+        for row in &rows {
+            let (text, kind) = ansi_log_line(&row.ansi());
+            self.push_log(LogTarget::Main, text, kind);
+        }
+    }
+
+    /// Diff unifie de la derniere reponse Codex : apercu dans le journal puis confirmation.
+    fn action_apply_codex_patch(&mut self) {
+        let Some(message) = self.last_assistant_message.clone() else {
+            self.log_issue(
+                "Aucune reponse Codex a appliquer.",
+                "avertissement",
+                "patch",
+                LogTarget::Main,
+            );
+            return;
+        };
+        let current = self.tabs.active().map(|tab| tab.file.path.clone());
+        let open_files: Vec<&OpenFile> = self.tabs.iter().map(|tab| &tab.file).collect();
+        let patch = match self
+            .core
+            .prepare_codex_patch(&message, current.as_deref(), &open_files)
+        {
+            Ok(patch) => patch,
+            Err(err) => {
+                self.log_issue(&err, "avertissement", "patch", LogTarget::Main);
+                return;
+            }
+        };
+        if !self.patch_target_clean(&patch.path) {
+            return;
+        }
+        let rows = diff_rows(&patch.original, &patch.patched, DIFF_CONTEXT);
+        let count = |kind: DiffRowKind| rows.iter().filter(|row| row.kind == kind).count();
+        let (added, removed) = (count(DiffRowKind::Added), count(DiffRowKind::Removed));
+        let relative = patch
+            .path
+            .strip_prefix(&self.root_dir)
+            .unwrap_or(&patch.path)
+            .display()
+            .to_string();
+        self.log_ui(format!(
+            "Apercu du patch Codex {relative} : +{added} -{removed}"
+        ));
+        for row in &rows {
+            let (text, kind) = ansi_log_line(&row.ansi());
+            self.push_log(LogTarget::Main, text, kind);
+        }
+        self.open_prompt(
+            PromptKind::ApplyCodexPatch(patch),
+            &format!("Appliquer le patch Codex a {relative} (original dans .usbide/trash) ?"),
+        );
+    }
+
+    /// Faux (avec un avertissement) si l'onglet du fichier a des modifications non sauvees.
+    fn patch_target_clean(&mut self, path: &Path) -> bool {
+        let dirty = self
+            .tabs
+            .position(path)
+            .and_then(|idx| self.tabs.get(idx))
+            .is_some_and(|tab| tab.file.dirty);
+        if dirty {
             self.log_issue(
-                "Ouvre un fichier .py.",
+                &format!(
+                    "Modifications non sauvees dans {} : enregistrer avant d'appliquer le patch.",
+                    path.display()
+                ),
                 "avertissement",
-                "execution_python",
+                "patch",
                 LogTarget::Main,
             );
+        }
+        !dirty
+    }
+
+    fn apply_codex_patch(&mut self, patch: CodexPatch) {
+        if !self.patch_target_clean(&patch.path) {
             return;
         }
-        if dirty {
-            self.action_save();
+        match self.core.apply_codex_patch(&patch) {
+            Ok(backup) => {
+                self.log_ui(format!(
+                    "Patch Codex applique: {} (original: {})",
+                    patch.path.display(),
+                    backup.display()
+                ));
+                self.sync_tabs_with_disk(std::slice::from_ref(&patch.path));
+            }
+            Err(err) => self.log_issue(&err, "erreur", "patch", LogTarget::Main),
         }
-        let argv = python_run_argv(&path);
-        self.log_ui(format!("$ {}", argv.join(" ")));
+    }
+
+    /// `git diff HEAD` du fichier courant, colore dans le journal.
+    fn action_git_diff(&mut self) {
+        let Some(path) = self.current().map(|current| current.path.clone()) else {
+            self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "git",
+                LogTarget::Main,
+            );
+            return;
+        };
+        let Some(env_map) = self.git_env() else {
+            return;
+        };
+        let relative = path.strip_prefix(&self.root_dir).unwrap_or(&path);
+        let argv = git_diff_argv(relative);
+        self.git_diff_lines = 0;
+        self.run_git(GitCommand::Diff, argv, env_map);
+    }
+
+    /// Sonde `--version` des interpreteurs candidats ; les absents sont ignores sans bruit.
+    fn detect_pythons(&mut self) {
+        let env_map = self.portable_env(std::env::vars().collect());
+        for (idx, argv) in self.core.start_python_detection() {
+            match NativeProcessRunner.spawn(&argv, Some(&self.root_dir), Some(&env_map)) {
+                Ok(handle) => self.core.running.push(RunningProcess::new(
+                    handle,
+                    ProcessKind::PythonVersion(idx),
+                    LogTarget::Main,
+                    "python_version",
+                )),
+                Err(_) => self.finish_python_probe(),
+            }
+        }
+    }
+
+    /// Sonde `--version` de node et npm une fois ; version trop ancienne : avertissement.
+    fn detect_node(&mut self) {
+        let env_map = self.codex_env();
+        for (tool, argv) in self.core.start_node_detection() {
+            if let Ok(handle) =
+                NativeProcessRunner.spawn(&argv, Some(&self.root_dir), Some(&env_map))
+            {
+                self.core.running.push(RunningProcess::new(
+                    handle,
+                    ProcessKind::NodeVersion(tool),
+                    LogTarget::Main,
+                    "node_version",
+                ));
+            }
+        }
+    }
+
+    fn finish_node_probe(&mut self, tool: NodeTool) {
+        if let Some(warning) = self.core.node_version_warning(tool) {
+            self.log_issue(&warning, "avertissement", "node", LogTarget::Main);
+        }
+    }
+
+    fn finish_python_probe(&mut self) {
+        match self.core.finish_python_probe() {
+            Some(Ok(message)) => self.log_ui(message),
+            Some(Err(warning)) => {
+                self.log_issue(&warning, "avertissement", "python", LogTarget::Main);
+            }
+            None => {}
+        }
+        self.python_label = self.core.python_status_label();
+    }
+
+    fn choose_python(&mut self, python: &PythonInstall) {
+        self.core.select_python(&python.exe);
+        self.python_label = self.core.python_status_label();
+        self.log_ui(format!("Interpreteur choisi: {}", python.label()));
+        if python.is_python2() {
+            self.log_issue(
+                "Python 2 choisi : les scripts Python 3 echoueront.",
+                "avertissement",
+                "python",
+                LogTarget::Main,
+            );
+        }
+    }
+
+    /// Fenetre "Premier lancement" : ce qui manque, ou le placer, et l'installation de Codex.
+    fn draw_setup(&mut self, ctx: &egui::Context) {
+        if self.setup_missing.is_empty() {
+            return;
+        }
+        let installable = self.setup_missing.iter().any(SetupMissing::installable);
+        let mut install = false;
+        let mut recheck = false;
+        let mut close = false;
+        egui::Window::new("Premier lancement")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("Elements manquants sur ce support :");
+                ui.add_space(4.0);
+                for missing in &self.setup_missing {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(RichText::new(missing.title()).strong());
+                        ui.label(missing.hint());
+                    });
+                }
+                ui.add_space(4.0);
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+                ui.horizontal(|ui| {
+                    if installable && ui.button("Installer Codex").clicked() {
+                        install = true;
+                    }
+                    if ui.button("Reverifier").clicked() {
+                        recheck = true;
+                    }
+                    if ui.button("Continuer").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if install {
+            self.setup_missing.clear();
+            self.action_codex_install();
+        } else if recheck {
+            self.setup_missing = self.core.setup_missing();
+            if self.setup_missing.is_empty() {
+                self.log_ui("Prerequis en place : Node, Python et Codex.".to_string());
+            }
+        } else if close {
+            self.setup_missing.clear();
+        }
+    }
+
+    /// Fenetre "Variables d'environnement" : environnement calcule par profil, ajout temporaire.
+    fn draw_env_inspector(&mut self, ctx: &egui::Context) {
+        let Some(mut profile) = self.env_inspector else {
+            return;
+        };
+        let entries = self.core.env_entries(profile);
+        let mut add = false;
+        let mut clear = false;
+        let mut close = false;
+        egui::Window::new("Variables d'environnement")
+            .collapsible(false)
+            .default_width(640.0)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for choice in [EnvProfile::Shell, EnvProfile::Codex, EnvProfile::Tools] {
+                        ui.selectable_value(&mut profile, choice, choice.label());
+                    }
+                });
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("env_inspector")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for entry in &entries {
+                                    let color = match entry.origin {
+                                        EnvOrigin::Host => ui.visuals().text_color(),
+                                        EnvOrigin::Portable => Color32::from_rgb(90, 190, 220),
+                                        EnvOrigin::Temporary => Color32::from_rgb(220, 190, 80),
+                                    };
+                                    ui.label(
+                                        RichText::new(&entry.key).monospace().strong().color(color),
+                                    );
+                                    ui.label(RichText::new(&entry.value).monospace().color(color));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    let field = ui.add(
+                        egui::TextEdit::singleline(&mut self.env_assignment)
+                            .hint_text("CLE=valeur"),
+                    );
+                    if field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        add = true;
+                    }
+                    if ui.button("Pour le prochain process").clicked() {
+                        add = true;
+                    }
+                    if !self.core.pending_env().is_empty() && ui.button("Retirer").clicked() {
+                        clear = true;
+                    }
+                    if ui.button("Fermer").clicked() {
+                        close = true;
+                    }
+                });
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+            });
+        if add {
+            match parse_env_assignment(&self.env_assignment) {
+                Some((key, value)) => {
+                    self.log_ui(format!("Variable {key} ajoutee pour le prochain process."));
+                    self.core.set_pending_env(key, value);
+                    self.env_assignment.clear();
+                }
+                None => self.log_ui("Format attendu : CLE=valeur.".to_string()),
+            }
+        }
+        if clear {
+            self.core.clear_pending_env();
+        }
+        self.env_inspector = (!close).then_some(profile);
+    }
+
+    /// Fenetre "Python REPL" : sortie du REPL et ligne de saisie ; la fermer garde le REPL.
+    fn draw_repl(&mut self, ctx: &egui::Context) {
+        if !self.show_repl {
+            return;
+        }
+        let running = self.core.repl().is_some();
+        let mut send = false;
+        let mut restart = false;
+        let mut stop = false;
+        let mut close = false;
+        egui::Window::new("Python REPL")
+            .collapsible(false)
+            .default_width(640.0)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in &self.repl_output {
+                            let mut text = RichText::new(line).monospace();
+                            if line.starts_with(">>> ") {
+                                text = text.color(Color32::from_rgb(90, 190, 220));
+                            } else if line.starts_with('[') {
+                                text = text.color(Color32::from_gray(130));
+                            }
+                            ui.label(text);
+                        }
+                    });
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(">>>").monospace());
+                    let field = ui.add(
+                        TextEdit::singleline(&mut self.repl_input)
+                            .code_editor()
+                            .desired_width(480.0),
+                    );
+                    if field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        send = true;
+                        field.request_focus();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(if running { "actif" } else { "arrete" });
+                    if ui.button("Redemarrer").clicked() {
+                        restart = true;
+                    }
+                    if running && ui.button("Arreter").clicked() {
+                        stop = true;
+                    }
+                    if ui.button("Effacer").clicked() {
+                        self.repl_output.clear();
+                    }
+                    if ui.button("Fermer").clicked() {
+                        close = true;
+                    }
+                });
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+            });
+        if send {
+            let line = std::mem::take(&mut self.repl_input);
+            self.send_repl_line(line);
+        }
+        if restart {
+            self.restart_repl();
+        }
+        if stop && self.core.stop_repl() {
+            self.push_repl_output("[REPL arrete]".to_string());
+        }
+        if close {
+            self.show_repl = false;
+        }
+    }
+
+    /// Lance le REPL Python (interpreteur choisi, venv actif) s'il ne tourne pas deja.
+    fn start_repl(&mut self) {
+        if self.core.repl().is_some() {
+            return;
+        }
+        let argv = python_repl_argv(self.core.run_venv().as_deref(), &self.core.base_python());
+        self.push_repl_output(format!("[{}]", argv[0]));
         let env_map = self.portable_env(std::env::vars().collect());
         self.spawn_process(
             argv,
             env_map,
-            "execution python",
+            "REPL Python",
             LogTarget::Main,
-            ProcessKind::PythonRun,
+            ProcessKind::Repl,
         );
     }
 
-    fn action_clear_log(&mut self) {
-        self.log.clear();
-        self.codex_log.clear();
-        self.last_codex_message = None;
-        self.codex_log_dirty = true;
-        self.log_ui("journaux effaces".to_string());
+    fn restart_repl(&mut self) {
+        self.core.stop_repl();
+        self.push_repl_output("[REPL redemarre]".to_string());
+        self.start_repl();
     }
 
-    fn action_reload_tree(&mut self) {
-        self.tree.reload(self.core.workspace());
-        self.log_ui("arborescence rechargee".to_string());
+    /// Envoie une ligne (meme vide : fin d'un bloc) au REPL, relance s'il s'est arrete.
+    fn send_repl_line(&mut self, line: String) {
+        if self.core.repl().is_none() {
+            self.start_repl();
+        }
+        self.push_repl_output(format!(">>> {line}"));
+        let result = self.core.repl().map(|handle| handle.write_line(&line));
+        if let Some(Err(err)) = result {
+            self.log_issue(
+                &format!("Envoi au REPL impossible: {err}"),
+                "erreur",
+                "REPL Python",
+                LogTarget::Main,
+            );
+        }
+    }
+
+    fn push_repl_output(&mut self, line: String) {
+        self.repl_output.push(line);
+        if self.repl_output.len() > LOG_LIMIT {
+            let drain = self.repl_output.len() - LOG_LIMIT;
+            self.repl_output.drain(0..drain);
+        }
+    }
+
+    /// Fenetre "Python" : interpreteurs detectes, le clic choisit celui de run/pip/venv.
+    fn draw_python_picker(&mut self, ctx: &egui::Context) {
+        if !self.show_python_picker {
+            return;
+        }
+        let pythons = self.core.pythons();
+        let current = self.core.python_interpreter();
+        let mut chosen = None;
+        let mut detect = false;
+        let mut close = false;
+        egui::Window::new("Python")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                if pythons.is_empty() {
+                    ui.label("Aucun interpreteur detecte.");
+                }
+                for python in &pythons {
+                    let mut text = RichText::new(python.label()).monospace();
+                    if python.is_python2() {
+                        text = text.color(Color32::from_gray(130));
+                    }
+                    if ui.selectable_label(python.exe == current, text).clicked() {
+                        chosen = Some(python.clone());
+                    }
+                }
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Relancer la detection").clicked() {
+                        detect = true;
+                    }
+                    if ui.button("Fermer").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if let Some(python) = chosen {
+            self.choose_python(&python);
+            close = true;
+        }
+        if detect {
+            self.detect_pythons();
+        }
+        if close {
+            self.show_python_picker = false;
+        }
+    }
+
+    /// Relance `git status` en arriere-plan si le root est un depot ; sans git, rien.
+    fn refresh_git_status(&mut self) {
+        self.git_repo = is_git_repo(&self.root_dir);
+        if !self.git_repo {
+            return;
+        }
+        if self.git_status_running {
+            self.git_status_stale = true;
+            return;
+        }
+        let mut env_map = self.tools_env();
+        if !tool_available("git", Some(&self.root_dir), Some(&env_map)).unwrap_or(false) {
+            return;
+        }
+        // Sans verrou sur l'index : ne bloque pas un git lance dans le shell.
+        env_map.insert("GIT_OPTIONAL_LOCKS".to_string(), "0".to_string());
+        self.git_status_stale = false;
+        self.git_status_lines.clear();
+        self.spawn_process(
+            git_status_argv(),
+            env_map,
+            "git_status",
+            LogTarget::Main,
+            ProcessKind::GitStatus,
+        );
+        self.git_status_running = self
+            .core
+            .running
+            .iter()
+            .any(|proc| proc.kind == ProcessKind::GitStatus);
+    }
+
+    fn action_new_entry(&mut self, is_dir: bool) {
+        let parent = target_dir_for(self.tree.selected_entry(), &self.root_dir);
+        let relative = parent
+            .strip_prefix(&self.root_dir)
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let place = if relative.is_empty() {
+            ".".to_string()
+        } else {
+            relative
+        };
+        if is_dir {
+            self.open_prompt(
+                PromptKind::NewFolder(parent),
+                &format!("Nouveau dossier dans {place}"),
+            );
+        } else {
+            self.open_prompt(
+                PromptKind::NewFile(parent),
+                &format!("Nouveau fichier dans {place}"),
+            );
+        }
+    }
+
+    fn create_tree_entry(&mut self, parent: PathBuf, name: &str, is_dir: bool) {
+        match create_entry(&parent, name, is_dir) {
+            Ok(path) => {
+                let kind = if is_dir { "Dossier" } else { "Fichier" };
+                self.log_ui(format!("{kind} cree: {}", path.display()));
+                self.reload_tree();
+                self.tree.data.expand(&parent);
+                self.tree.selected = Some(path);
+            }
+            Err(err) => {
+                self.log_issue(
+                    &err.to_string(),
+                    "erreur",
+                    "creation_fichier",
+                    LogTarget::Main,
+                );
+            }
+        }
+    }
+
+    fn action_rename_entry(&mut self) {
+        let Some(entry) = self.tree.selected_entry().cloned() else {
+            return;
+        };
+        if entry.path == self.root_dir {
+            return;
+        }
+        self.open_prompt_with(
+            PromptKind::Rename(entry.path),
+            &format!("Renommer {}", entry.name),
+            &entry.name,
+        );
+    }
+
+    fn action_delete_entry(&mut self) {
+        let Some(entry) = self.tree.selected_entry().cloned() else {
+            return;
+        };
+        if entry.path == self.root_dir {
+            return;
+        }
+        self.open_prompt(
+            PromptKind::ConfirmDelete(entry.path),
+            &format!("Deplacer {} vers la corbeille ?", entry.name),
+        );
+    }
+
+    fn rename_tree_entry(&mut self, path: PathBuf, new_name: &str) {
+        match rename_entry(&path, new_name) {
+            Ok(target) => {
+                self.log_ui(format!(
+                    "Renomme: {} -> {}",
+                    path.display(),
+                    target.display()
+                ));
+                for tab in self.tabs.iter_mut() {
+                    if let Some(remapped) = remap_path(&tab.file.path, &path, &target) {
+                        tab.file.path = remapped;
+                    }
+                }
+                let current = self.current().map(|current| current.path.clone());
+                self.tree.data.set_keep_visible(current);
+                self.refresh_title();
+                self.reload_tree();
+                self.tree.selected = Some(target);
+            }
+            Err(err) => {
+                self.log_issue(&err.to_string(), "erreur", "renommage", LogTarget::Main);
+            }
+        }
+    }
+
+    fn delete_tree_entry(&mut self, path: PathBuf) {
+        match move_to_trash(self.core.workspace(), &path) {
+            Ok(target) => {
+                self.log_ui(format!(
+                    "Deplace vers la corbeille: {} -> {}",
+                    path.display(),
+                    target.display()
+                ));
+                let affected: Vec<usize> = (0..self.tabs.len())
+                    .filter(|idx| {
+                        self.tabs
+                            .get(*idx)
+                            .is_some_and(|tab| tab.file.path.starts_with(&path))
+                    })
+                    .collect();
+                // Onglets modifies gardes (fichier absent) : leurs modifications restent a sauver.
+                let (kept, deleted): (Vec<usize>, Vec<usize>) = affected
+                    .into_iter()
+                    .partition(|idx| self.tabs.get(*idx).is_some_and(|tab| tab.file.dirty));
+                for idx in kept {
+                    self.tabs.mark_missing(idx);
+                    if let Some(tab) = self.tabs.get(idx) {
+                        let message = format!(
+                            "Onglet modifie conserve, fichier supprime: {}",
+                            tab.file.path.display()
+                        );
+                        self.log_issue(&message, "avertissement", "suppression", LogTarget::Main);
+                    }
+                }
+                for idx in deleted.iter().rev() {
+                    self.tabs.close(*idx);
+                }
+                if !deleted.is_empty() {
+                    self.on_tab_change();
+                }
+                self.tree.selected = None;
+                self.reload_tree();
+            }
+            Err(err) => {
+                self.log_issue(&err.to_string(), "erreur", "suppression", LogTarget::Main);
+            }
+        }
+    }
+
+    /// Ne reformate le libelle que si la position change (pas d'allocation par frame).
+    fn update_editor_position(&mut self, cursor: (usize, usize), line_count: usize) {
+        if cursor == self.editor_cursor && line_count == self.editor_line_count {
+            return;
+        }
+        self.editor_cursor = cursor;
+        self.editor_line_count = line_count;
+        self.editor_position = editor_position_label(cursor.0, cursor.1, line_count);
+    }
+
+    fn goto_line(&mut self, value: &str) {
+        if self.current().is_none() {
+            return;
+        }
+        let line_count = self
+            .tabs
+            .active()
+            .map_or(0, |tab| tab.buffer.split('\n').count());
+        match parse_goto_line(value, line_count) {
+            Some(line) => self.pending_goto = Some((line, 0)),
+            None => self.log_issue(
+                &format!("Numero de ligne invalide: {value}"),
+                "avertissement",
+                "aller_a_la_ligne",
+                LogTarget::Main,
+            ),
+        }
     }
 
     fn action_toggle_codex_view(&mut self) {
-        self.codex_compact_view = !self.codex_compact_view;
+        self.codex_view = self.codex_view.next();
         self.last_codex_message = None;
-        let mode = if self.codex_compact_view {
-            "Compact"
-        } else {
-            "Brut"
-        };
-        self.codex_log_ui(format!("Mode Codex: {mode}"));
+        self.codex_log_ui(format!("Mode Codex: {}", self.codex_view.label()));
+        if let Err(err) = self.core.save_codex_view(self.codex_view) {
+            self.log_issue(
+                &format!("Niveau de vue non enregistre: {err}"),
+                "avertissement",
+                "settings",
+                LogTarget::Codex,
+            );
+        }
     }
 
     fn action_toggle_codex_sandbox(&mut self) {
@@ -1086,6 +5159,16 @@ impl GuiApp {
         ));
     }
 
+    fn action_toggle_codex_session(&mut self) {
+        self.codex_continue_session = !self.codex_continue_session;
+        let label = if self.codex_continue_session {
+            format!("suite (contexte des {CODEX_CONTEXT_TURNS} derniers echanges)")
+        } else {
+            "nouvelle (sans contexte)".to_string()
+        };
+        self.codex_log_ui(format!("Session Codex: {label}"));
+    }
+
     fn action_codex_install(&mut self) {
         let _ = self.install_codex(true, LogTarget::Codex);
     }
@@ -1096,6 +5179,7 @@ impl GuiApp {
             self.codex_sandbox_mode,
             self.codex_approval_supported,
             self.codex_approval_policy,
+            &self.codex_model,
         )
     }
 
@@ -1130,6 +5214,76 @@ impl GuiApp {
         false
     }
 
+    /// Tour termine : lance le prompt suivant de la file d'attente.
+    fn run_next_queued_codex(&mut self) {
+        if let Some(prompt) = self.core.next_queued_codex() {
+            self.run_codex(prompt);
+        }
+    }
+
+    /// Vide la file d'attente ; faux si elle l'etait deja.
+    fn clear_codex_queue(&mut self) -> bool {
+        let count = self.core.clear_codex_queue();
+        if count == 0 {
+            return false;
+        }
+        self.codex_log_action(&format!("File d'attente Codex videe ({count} prompts)."));
+        true
+    }
+
+    fn action_codex_stop(&mut self) {
+        let killed = self.core.kill_processes(ProcessKind::CodexExec);
+        let pending =
+            self.core.take_pending_codex_prompt().is_some() | self.core.cancel_codex_retry();
+        self.codex_status_started = None;
+        self.codex_assistant_buffer.clear();
+        self.codex_streamed_text.clear();
+        self.codex_turn_response.clear();
+        self.codex_last_prompt = None;
+        self.codex_retry_without_sandbox = false;
+        self.codex_retry_without_approval = false;
+        let pending = self.clear_codex_queue() | pending;
+        if killed == 0 && !pending {
+            self.codex_log_ui("Aucun Codex en cours.".to_string());
+            return;
+        }
+        self.codex_log_action("Codex interrompu");
+    }
+
+    /// Arret d'urgence : tous les process (installs, builds, Codex, tests...) d'un coup.
+    fn action_kill_all_processes(&mut self) {
+        let (stopped, lingering) = self.core.kill_all_processes();
+        self.core.take_pending_codex_prompt();
+        self.core.clear_codex_queue();
+        self.core.cancel_codex_retry();
+        self.codex_status_started = None;
+        self.codex_caps_running = false;
+        self.codex_assistant_buffer.clear();
+        self.codex_streamed_text.clear();
+        self.codex_turn_response.clear();
+        self.install_progress = None;
+        if stopped == 0 {
+            self.log_ui("Aucun process en cours.".to_string());
+            return;
+        }
+        self.log_issue(
+            &format!("Arret d'urgence : {stopped} process arrete(s)."),
+            "avertissement",
+            "arret_urgence",
+            LogTarget::Main,
+        );
+        if lingering > 0 {
+            self.log_issue(
+                &format!(
+                    "{lingering} process gardent leur sortie ouverte (sous-process encore actif ?)."
+                ),
+                "avertissement",
+                "arret_urgence",
+                LogTarget::Main,
+            );
+        }
+    }
+
     fn action_codex_login(&mut self) {
         let env_map = self.codex_env();
         if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
@@ -1162,6 +5316,30 @@ impl GuiApp {
         );
     }
 
+    /// `codex logout` dans le CODEX_HOME portable (changement de compte).
+    fn action_codex_logout(&mut self) {
+        let env_map = self.codex_env();
+        if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
+            self.log_issue(
+                "Codex non installe.",
+                "avertissement",
+                "codex_logout",
+                LogTarget::Codex,
+            );
+            return;
+        }
+        self.core.invalidate_codex_status();
+        let argv = codex_logout_argv(Some(&self.root_dir), Some(&env_map));
+        self.codex_log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "deconnexion Codex",
+            LogTarget::Codex,
+            ProcessKind::CodexLogout,
+        );
+    }
+
     fn action_codex_check(&mut self) {
         let env_map = self.codex_env();
         if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
@@ -1208,13 +5386,68 @@ impl GuiApp {
         );
     }
 
+    /// Fenetre des outils dev (Ctrl+D) : disponibilite, cases a cocher et paquets en plus.
     fn action_dev_tools(&mut self) {
-        let raw = std::env::var("USBIDE_DEV_TOOLS")
-            .unwrap_or_else(|_| "ruff black mypy pytest".to_string());
-        let tools = parse_tool_list(&raw);
+        self.dev_tools = Some(self.core.dev_tool_entries());
+    }
+
+    fn draw_dev_tools(&mut self, ctx: &egui::Context) {
+        let Some(entries) = self.dev_tools.as_mut() else {
+            return;
+        };
+        let extra = &mut self.dev_tools_extra;
+        let mut install = false;
+        let mut close = false;
+        egui::Window::new("Outils dev")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                if entries.is_empty() {
+                    ui.label("Liste outils vide (parametre dev_tools).");
+                }
+                for entry in entries.iter_mut() {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut entry.selected, entry.package.as_str());
+                        if entry.available {
+                            ui.colored_label(Color32::from_rgb(120, 220, 160), "present");
+                        } else {
+                            ui.colored_label(Color32::from_gray(150), "absent");
+                        }
+                    });
+                }
+                ui.add_space(6.0);
+                ui.label("Paquets en plus :");
+                ui.add(
+                    TextEdit::singleline(extra)
+                        .hint_text("ex: httpx, rich")
+                        .desired_width(320.0),
+                );
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Installer la selection").clicked() {
+                        install = true;
+                    }
+                    if ui.button("Fermer").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close {
+            self.dev_tools = None;
+        } else if install && let Some(entries) = self.dev_tools.take() {
+            let tools = dev_tools_to_install(&entries, &std::mem::take(&mut self.dev_tools_extra));
+            self.install_dev_tools(tools);
+        }
+    }
+
+    /// pip install des paquets choisis dans le prefixe portable des outils.
+    fn install_dev_tools(&mut self, tools: Vec<String>) {
         if tools.is_empty() {
             self.log_issue(
-                "Liste outils vide.",
+                "Aucun outil selectionne.",
                 "avertissement",
                 "outils_dev",
                 LogTarget::Main,
@@ -1224,10 +5457,16 @@ impl GuiApp {
         let env_map = self.tools_env();
         let prefix = tools_install_prefix(&self.root_dir);
         let _ = std::fs::create_dir_all(&prefix);
-        let wheelhouse = self.wheelhouse_path();
+        let wheelhouse = match self.core.pip_wheelhouse() {
+            Ok(wheelhouse) => wheelhouse,
+            Err(message) => {
+                self.log_issue(&message, "erreur", "hors_ligne", LogTarget::Main);
+                return;
+            }
+        };
         let argv =
             match pip_install_argv(&prefix, &tools, wheelhouse.as_deref(), wheelhouse.is_some()) {
-                Ok(argv) => argv,
+                Ok(argv) => self.core.with_python(argv),
                 Err(err) => {
                     self.log_issue(
                         &format!("Impossible d'installer outils: {err}"),
@@ -1248,8 +5487,333 @@ impl GuiApp {
         );
     }
 
+    fn action_install_requirements(&mut self) {
+        let req_path = self.root_dir.join("requirements.txt");
+        if !req_path.is_file() {
+            self.log_issue(
+                "Aucun requirements.txt a la racine du workspace.",
+                "avertissement",
+                "requirements",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let env_map = self.tools_env();
+        // Avec un venv actif, les dependances du projet vont dans le venv.
+        let prefix = self
+            .core
+            .active_venv()
+            .unwrap_or_else(|| tools_install_prefix(&self.root_dir));
+        let _ = std::fs::create_dir_all(&prefix);
+        let wheelhouse = match self.core.pip_wheelhouse() {
+            Ok(wheelhouse) => wheelhouse,
+            Err(message) => {
+                self.log_issue(&message, "erreur", "hors_ligne", LogTarget::Main);
+                return;
+            }
+        };
+        let argv = match pip_install_requirements_argv(
+            &prefix,
+            &req_path,
+            wheelhouse.as_deref(),
+            wheelhouse.is_some(),
+        ) {
+            Ok(argv) => self.core.with_python(argv),
+            Err(err) => {
+                self.log_issue(
+                    &format!("Impossible d'installer les dependances: {err}"),
+                    "erreur",
+                    "requirements",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let source = match wheelhouse.as_deref() {
+            Some(wheels) => format!("wheelhouse {}", wheels.display()),
+            None => "index pip".to_string(),
+        };
+        self.log_ui(format!(
+            "Installation des dependances de {} ({source})",
+            req_path.display()
+        ));
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "installation requirements",
+            LogTarget::Main,
+            ProcessKind::Requirements,
+        );
+    }
+
+    fn action_create_venv(&mut self) {
+        if let Some(venv) = self.core.active_venv() {
+            self.log_ui(format!("Venv deja actif: {}", venv.display()));
+            return;
+        }
+        let venv = self.core.workspace().venv_dir().to_path_buf();
+        let argv = venv_create_argv(&venv, &self.core.base_python());
+        self.log_ui(format!("Creation du venv: {}", venv.display()));
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        let env_map = self.portable_env(std::env::vars().collect());
+        self.spawn_process(
+            argv,
+            env_map,
+            "creation venv",
+            LogTarget::Main,
+            ProcessKind::Venv,
+        );
+    }
+
+    /// Icone du formulaire de build (relative au root), None si vide ou invalide.
+    fn build_icon_path(&mut self) -> Option<PathBuf> {
+        let raw = self.build_icon.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        let path = self.root_dir.join(raw);
+        match validate_build_icon(&path) {
+            Ok(()) => Some(path),
+            Err(msg) => {
+                self.log_issue(&msg, "avertissement", "build_exe", LogTarget::Main);
+                None
+            }
+        }
+    }
+
+    fn action_toggle_build_onefile(&mut self) {
+        self.build_onefile = !self.build_onefile;
+        let label = if self.build_onefile {
+            "onefile (un seul executable)"
+        } else {
+            "onedir (dossier)"
+        };
+        self.log_ui(format!("Build PyInstaller: {label}"));
+    }
+
+    /// Sauve puis formate le fichier .py courant (black ou ruff format, USBIDE_FORMATTER).
+    fn action_format(&mut self) {
+        let path = self.current().map(|current| current.path.clone());
+        let Some(path) = path.filter(|path| {
+            path.extension()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.eq_ignore_ascii_case("py"))
+        }) else {
+            self.log_issue(
+                "Ouvre un fichier .py.",
+                "avertissement",
+                "formatage",
+                LogTarget::Main,
+            );
+            return;
+        };
+        if self
+            .core
+            .running
+            .iter()
+            .any(|proc| proc.kind == ProcessKind::Format)
+        {
+            self.log_ui("Formatage deja en cours.".to_string());
+            return;
+        }
+        self.action_save();
+        if self.current().is_some_and(|current| current.dirty) {
+            return;
+        }
+        let formatter = self.core.settings().formatter();
+        let env_map = self.tools_env();
+        if !tool_available(formatter.as_str(), Some(&self.root_dir), Some(&env_map))
+            .unwrap_or(false)
+        {
+            self.log_issue(
+                &format!(
+                    "{} introuvable : ajoute-le aux outils dev (Ctrl+D) ou change USBIDE_FORMATTER.",
+                    formatter.as_str()
+                ),
+                "avertissement",
+                "formatage",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let argv = match format_argv(formatter, &path) {
+            Ok(argv) => argv,
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur formatage: {err}"),
+                    "erreur",
+                    "formatage",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        self.format_target = Some(path);
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "formatage",
+            LogTarget::Main,
+            ProcessKind::Format,
+        );
+    }
+
+    /// Sauve puis lance ruff et mypy (ceux installes) sur le fichier .py courant.
+    fn action_lint(&mut self) {
+        let path = self.current().map(|current| current.path.clone());
+        let Some(path) = path.filter(|path| {
+            path.extension()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.eq_ignore_ascii_case("py"))
+        }) else {
+            self.log_issue(
+                "Ouvre un fichier .py.",
+                "avertissement",
+                "lint",
+                LogTarget::Main,
+            );
+            return;
+        };
+        if self
+            .core
+            .running
+            .iter()
+            .any(|proc| matches!(proc.kind, ProcessKind::Lint(_)))
+        {
+            self.log_ui("Lint deja en cours.".to_string());
+            return;
+        }
+        self.action_save();
+        if self.current().is_some_and(|current| current.dirty) {
+            return;
+        }
+        let env_map = self.tools_env();
+        let linters: Vec<Linter> = Linter::ALL
+            .into_iter()
+            .filter(|linter| {
+                tool_available(linter.as_str(), Some(&self.root_dir), Some(&env_map))
+                    .unwrap_or(false)
+            })
+            .collect();
+        if linters.is_empty() {
+            self.log_issue(
+                "ruff et mypy introuvables : ajoute-les aux outils dev (Ctrl+D).",
+                "avertissement",
+                "lint",
+                LogTarget::Main,
+            );
+            return;
+        }
+        self.diagnostics.clear_path(&path);
+        self.lint_target = Some(path.clone());
+        for linter in linters {
+            match lint_argv(linter, &path) {
+                Ok(argv) => {
+                    self.log_ui(format!("$ {}", argv.join(" ")));
+                    self.spawn_process(
+                        argv,
+                        env_map.clone(),
+                        "lint",
+                        LogTarget::Main,
+                        ProcessKind::Lint(linter),
+                    );
+                }
+                Err(err) => self.log_issue(
+                    &format!("Erreur lint: {err}"),
+                    "erreur",
+                    "lint",
+                    LogTarget::Main,
+                ),
+            }
+        }
+    }
+
+    /// Ligne de sortie d'un linter : diagnostic rattache au chemin tel qu'ouvert dans les onglets.
+    fn record_lint_line(&mut self, linter: Linter, line: &str) {
+        if let Some(mut diagnostic) = parse_diagnostic(line, linter, &self.root_dir) {
+            if let Ok(path) = diagnostic.path.canonicalize() {
+                diagnostic.path = path;
+            }
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn action_diagnostics(&mut self) {
+        if self.diagnostics.is_empty() {
+            self.log_ui("Aucun diagnostic : lance un lint (Alt+K).".to_string());
+            return;
+        }
+        self.show_diagnostics = true;
+    }
+
+    /// Ouvre le fichier du diagnostic et place le curseur sur sa ligne et sa colonne.
+    fn jump_to_diagnostic(&mut self, diagnostic: Diagnostic) {
+        self.open_file(diagnostic.path.clone());
+        if self
+            .current()
+            .is_none_or(|current| current.path != diagnostic.path)
+        {
+            return;
+        }
+        self.pending_goto = Some((
+            diagnostic.line.saturating_sub(1),
+            diagnostic.column.unwrap_or(1).saturating_sub(1),
+        ));
+    }
+
+    /// Sauve les onglets modifies puis lance pytest a la racine (USBIDE_PYTEST_ARGS, `--lf`).
+    fn action_run_tests(&mut self, last_failed: bool) {
+        if self.tests_running() {
+            self.log_ui("Tests deja en cours.".to_string());
+            return;
+        }
+        for idx in self.tabs.dirty_indices() {
+            self.save_tab(idx);
+        }
+        let env_map = self.tools_env();
+        if !tool_available("pytest", Some(&self.root_dir), Some(&env_map)).unwrap_or(false) {
+            self.log_issue(
+                "pytest introuvable : ajoute-le aux outils dev (Ctrl+D).",
+                "avertissement",
+                "tests",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let argv = pytest_argv(&pytest_args_from_env(), last_failed);
+        self.pytest_summary = None;
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(argv, env_map, "tests", LogTarget::Main, ProcessKind::Pytest);
+    }
+
+    fn action_stop_tests(&mut self) {
+        if self.core.kill_processes(ProcessKind::Pytest) == 0 {
+            self.log_ui("Aucun test en cours.".to_string());
+            return;
+        }
+        self.log_ui("Tests interrompus.".to_string());
+    }
+
+    fn tests_running(&self) -> bool {
+        self.core
+            .running
+            .iter()
+            .any(|proc| proc.kind == ProcessKind::Pytest)
+    }
+
+    /// Barre d'etat : tests en cours ou resume du dernier passage (et s'il est vert).
+    fn test_status(&self) -> Option<(String, bool)> {
+        if self.tests_running() {
+            return Some(("Tests: en cours...".to_string(), true));
+        }
+        self.pytest_summary
+            .map(|summary| (summary.label(), summary.is_success()))
+    }
+
     fn action_build_exe(&mut self) {
-        let (path, dirty) = match self.current.as_ref() {
+        let (path, dirty) = match self.current() {
             Some(current) => (current.path.clone(), current.dirty),
             None => {
                 self.log_issue(
@@ -1290,14 +5854,20 @@ impl GuiApp {
             );
             return;
         }
+        let icon = self.build_icon_path();
+        let name = Some(self.build_name.trim())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
         let dist_dir = self.root_dir.join("dist");
         let _ = std::fs::create_dir_all(&dist_dir);
         let argv = match pyinstaller_build_argv(
             &path,
             &dist_dir,
-            false,
+            self.build_onefile,
             Some(&self.root_dir.join("tmp")),
             None,
+            icon.as_deref(),
+            name.as_deref(),
         ) {
             Ok(argv) => argv,
             Err(err) => {
@@ -1310,6 +5880,12 @@ impl GuiApp {
                 return;
             }
         };
+        self.build_output = Some(pyinstaller_output_path(
+            &path,
+            &dist_dir,
+            self.build_onefile,
+            name.as_deref(),
+        ));
         self.log_ui(format!("$ {}", argv.join(" ")));
         self.spawn_process(
             argv,
@@ -1330,10 +5906,16 @@ impl GuiApp {
         self.core.pyinstaller_install_attempted = true;
         let prefix = tools_install_prefix(&self.root_dir);
         let _ = std::fs::create_dir_all(&prefix);
-        let wheelhouse = self.wheelhouse_path();
+        let wheelhouse = match self.core.pip_wheelhouse() {
+            Ok(wheelhouse) => wheelhouse,
+            Err(message) => {
+                self.log_issue(&message, "erreur", "hors_ligne", LogTarget::Main);
+                return false;
+            }
+        };
         let argv =
             match pyinstaller_install_argv(&prefix, wheelhouse.as_deref(), wheelhouse.is_some()) {
-                Ok(argv) => argv,
+                Ok(argv) => self.core.with_python(argv),
                 Err(err) => {
                     self.log_issue(
                         &format!("Impossible d'installer PyInstaller: {err}"),
@@ -1393,9 +5975,14 @@ impl GuiApp {
         if !self.ensure_node_available(&env_map, target) {
             return false;
         }
+        for warning in self.core.node_version_warnings() {
+            self.log_issue(&warning, "avertissement", "installation_codex", target);
+        }
         self.core.codex_install_attempted = true;
-        let package = std::env::var("USBIDE_CODEX_NPM_PACKAGE")
-            .unwrap_or_else(|_| "@openai/codex".to_string());
+        let (package, network, warning) = self.core.codex_install_source();
+        if let Some(warning) = warning {
+            self.log_issue(&warning, "avertissement", "installation_codex", target);
+        }
         let prefix = codex_install_prefix(&self.root_dir);
         if let Err(err) = std::fs::create_dir_all(&prefix) {
             self.log_issue(
@@ -1409,7 +5996,7 @@ impl GuiApp {
             );
             return false;
         }
-        let argv = match codex_install_argv(&self.root_dir, &prefix, &package) {
+        let argv = match codex_install_argv(&self.root_dir, &prefix, &package, network) {
             Ok(argv) => argv,
             Err(CodexError::NodeMissing) => {
                 self.log_issue(
@@ -1454,8 +6041,57 @@ impl GuiApp {
             "installation Codex",
             target,
             ProcessKind::CodexInstall,
-        );
-        true
+        )
+    }
+
+    fn change_shell_cwd(&mut self, target: &str) {
+        match resolve_shell_cwd(&self.root_dir, &self.shell_cwd, target) {
+            Some(dir) => {
+                self.shell_cwd = dir;
+                self.log_ui(format!(
+                    "Dossier courant: {}",
+                    shell_cwd_label(&self.root_dir, &self.shell_cwd)
+                ));
+            }
+            None => self.log_issue(
+                &format!("cd refuse: '{target}' introuvable ou hors du workspace."),
+                "avertissement",
+                "commande shell",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    /// Le dossier courant du shell suit la selection de l'arborescence.
+    fn follow_tree_selection(&mut self) {
+        self.shell_cwd = target_dir_for(self.tree.selected_entry(), &self.root_dir);
+    }
+
+    /// Envoie la saisie au stdin de la commande shell en cours (au lieu d'en lancer une).
+    fn send_shell_input(&mut self, line: String) {
+        let Some(result) = self
+            .core
+            .interactive_shell()
+            .map(|handle| handle.write_line(&line))
+        else {
+            return;
+        };
+        match result {
+            Ok(()) => self.log_ui(format!("> {line}")),
+            Err(err) => self.log_issue(
+                &format!("Envoi au process impossible: {err}"),
+                "erreur",
+                "commande shell",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    fn send_shell_eof(&mut self) {
+        if let Some(handle) = self.core.interactive_shell() {
+            handle.close_stdin();
+            self.log_ui("EOF envoye au process".to_string());
+        }
     }
 
     fn run_shell(&mut self, cmd: String) {
@@ -1463,27 +6099,158 @@ impl GuiApp {
             return;
         }
         self.log_ui(format!("$ {cmd}"));
+        if let Err(err) = self.cmd_history.push(self.core.workspace(), &cmd) {
+            self.log_issue(
+                &format!("Erreur ecriture historique commandes: {err}"),
+                "erreur",
+                "cmd_history",
+                LogTarget::Main,
+            );
+        }
+        if let Some(target) = parse_cd_command(&cmd) {
+            self.change_shell_cwd(target);
+            return;
+        }
+        if self.core.settings().shell_guard() {
+            let risks = shell_command_risks(&cmd, &self.root_dir, &self.shell_cwd);
+            if !risks.is_empty() {
+                self.log_issue(
+                    &format!("Commande shell a confirmer: {}", risks.join(", ")),
+                    "avertissement",
+                    "garde-fou shell",
+                    LogTarget::Main,
+                );
+                let label = format!(
+                    "Commande risquee ({}) : executer quand meme ? (o/n)",
+                    risks.join(", ")
+                );
+                self.open_prompt(PromptKind::ConfirmShell(cmd), &label);
+                return;
+            }
+        }
+        self.spawn_shell(cmd);
+    }
+
+    fn spawn_shell(&mut self, cmd: String) {
         let argv = if cfg!(windows) {
             windows_cmd_argv(&cmd)
         } else {
             vec!["sh".to_string(), "-lc".to_string(), cmd]
         };
         let env_map = self.portable_env(std::env::vars().collect());
-        self.spawn_process(
+        if !self.shell_cwd.is_dir() {
+            // Dossier renomme/supprime depuis : retour au root.
+            self.shell_cwd = self.root_dir.clone();
+        }
+        let cwd = self.shell_cwd.clone();
+        self.spawn_process_in(
             argv,
             env_map,
             "commande shell",
             LogTarget::Main,
             ProcessKind::Shell,
+            &cwd,
         );
     }
 
+    /// Texte selectionne dans l'editeur (etat du TextEdit, None sans selection).
+    fn editor_selection(&self, ctx: &egui::Context) -> Option<String> {
+        let tab = self.tabs.active()?;
+        let state = TextEdit::load_state(ctx, egui::Id::new(("editor", &tab.file.path)))?;
+        let [start, end] = state.cursor.char_range()?.sorted();
+        let selected: String = tab
+            .buffer
+            .chars()
+            .skip(start.index)
+            .take(end.index - start.index)
+            .collect();
+        Some(selected).filter(|text| !text.is_empty())
+    }
+
+    /// Selection, sinon fichier courant entier (sauf `selection_only`) : (source, contenu).
+    fn codex_context(&self, ctx: &egui::Context, selection_only: bool) -> Option<(String, String)> {
+        let tab = self.tabs.active()?;
+        let relative = tab
+            .file
+            .path
+            .strip_prefix(&self.root_dir)
+            .unwrap_or(&tab.file.path);
+        match self.editor_selection(ctx) {
+            Some(selected) => Some((format!("selection de {}", relative.display()), selected)),
+            None if !selection_only => Some((
+                format!("fichier {}", relative.display()),
+                tab.buffer.clone(),
+            )),
+            None => None,
+        }
+    }
+
+    fn action_ask_codex_about_file(&mut self, ctx: &egui::Context) {
+        let Some((source, content)) = self.codex_context(ctx, false) else {
+            self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "codex",
+                LogTarget::Codex,
+            );
+            return;
+        };
+        let label = format!("Question a Codex ({source}, vide : explication)");
+        self.open_prompt(PromptKind::CodexQuestion(source, content), &label);
+    }
+
+    fn action_explain_selection(&mut self, ctx: &egui::Context) {
+        let Some((source, content)) = self.codex_context(ctx, true) else {
+            self.log_issue(
+                "Aucune selection dans l'editeur.",
+                "avertissement",
+                "codex",
+                LogTarget::Codex,
+            );
+            return;
+        };
+        self.ask_codex_with_context(&source, &content, "");
+    }
+
+    /// Envoie la question avec le contexte joint (question vide : demande d'explication).
+    fn ask_codex_with_context(&mut self, source: &str, content: &str, question: &str) {
+        let question = match question.trim() {
+            "" => "Explique ce code.",
+            question => question,
+        };
+        self.send_codex_prompt(codex_context_prompt(source, content, question));
+    }
+
+    /// Prompt saisi par l'utilisateur : confirmation d'abord en sandbox danger-full-access.
+    fn send_codex_prompt(&mut self, prompt: String) {
+        if prompt.is_empty() {
+            return;
+        }
+        if self.codex_sandbox_mode == CodexSandboxMode::DangerFullAccess
+            && codex_confirm_danger_from_env()
+        {
+            self.open_prompt(
+                PromptKind::ConfirmDangerSandbox(prompt),
+                "Lancer Codex sans sandbox ?",
+            );
+            return;
+        }
+        self.run_codex(prompt);
+    }
+
     fn run_codex(&mut self, prompt: String) {
         if prompt.is_empty() {
             return;
         }
-        if self.codex_compact_view {
-            self.codex_log_user_message(&prompt);
+        if self.core.codex_turn_active() {
+            let display = codex_prompt_display(&prompt);
+            let queued = self.core.queue_codex_prompt(prompt);
+            self.codex_log_action(&format!("En attente ({queued} en file) : {display}"));
+            return;
+        }
+        self.core.cancel_codex_retry();
+        if self.codex_view != CodexView::Raw {
+            self.codex_log_user_message(&codex_prompt_display(&prompt));
         }
         let env_map = self.codex_env();
         if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
@@ -1491,38 +6258,50 @@ impl GuiApp {
                 return;
             }
             if self.install_codex(false, LogTarget::Codex) {
-                self.pending_codex_prompt = Some(prompt);
+                self.core.set_pending_codex_prompt(prompt);
             }
             return;
         }
 
         if !self.codex_caps_checked {
-            self.pending_codex_prompt = Some(prompt);
             if self.codex_caps_running {
+                self.core.set_pending_codex_prompt(prompt);
                 return;
             }
             self.codex_caps_running = true;
             self.codex_caps_buffer.clear();
             let argv = codex_exec_help_argv(Some(&self.root_dir), Some(&env_map));
-            self.spawn_process(
+            if self.spawn_process(
                 argv,
                 env_map,
                 "codex_caps",
                 LogTarget::Codex,
                 ProcessKind::CodexCaps,
-            );
+            ) {
+                self.core.set_pending_codex_prompt(prompt);
+            } else {
+                self.codex_caps_running = false;
+            }
             return;
         }
 
-        self.pending_codex_prompt = Some(prompt);
+        if self.core.codex_status_cached() {
+            self.codex_last_prompt = Some(prompt.clone());
+            self.spawn_codex_exec(&prompt);
+            return;
+        }
+        self.codex_log_action("Verification de la connexion Codex...");
         let argv = codex_status_argv(Some(&self.root_dir), Some(&env_map));
-        self.spawn_process(
+        if self.spawn_process(
             argv,
             env_map,
             "codex_status",
             LogTarget::Codex,
             ProcessKind::CodexStatus,
-        );
+        ) {
+            self.codex_status_started = Some(Instant::now());
+            self.core.set_pending_codex_prompt(prompt);
+        }
     }
 
     fn spawn_process(
@@ -1532,15 +6311,40 @@ impl GuiApp {
         contexte: &str,
         target: LogTarget,
         kind: ProcessKind,
-    ) {
-        match NativeProcessRunner.spawn(&argv, Some(&self.root_dir), Some(&env_map)) {
+    ) -> bool {
+        let root_dir = self.root_dir.clone();
+        self.spawn_process_in(argv, env_map, contexte, target, kind, &root_dir)
+    }
+
+    fn spawn_process_in(
+        &mut self,
+        argv: Vec<String>,
+        env_map: HashMap<String, String>,
+        contexte: &str,
+        target: LogTarget,
+        kind: ProcessKind,
+        cwd: &Path,
+    ) -> bool {
+        let mut env_map = env_map;
+        let temporaires = self.core.apply_pending_env(kind, &mut env_map);
+        if !temporaires.is_empty() {
+            self.log_ui(format!(
+                "Variables temporaires pour {contexte}: {}",
+                temporaires.join(", ")
+            ));
+        }
+        // Les commandes shell gardent un stdin ouvert (REPL, prompts) alimente par la saisie.
+        let spawned = if matches!(kind, ProcessKind::Shell | ProcessKind::Repl) {
+            NativeProcessRunner.spawn_interactive(&argv, Some(cwd), Some(&env_map))
+        } else {
+            NativeProcessRunner.spawn(&argv, Some(cwd), Some(&env_map))
+        };
+        match spawned {
             Ok(handle) => {
-                self.core.running.push(RunningProcess {
-                    handle,
-                    kind,
-                    target,
-                    contexte: contexte.to_string(),
-                });
+                self.core
+                    .running
+                    .push(RunningProcess::new(handle, kind, target, contexte));
+                true
             }
             Err(err) => {
                 self.log_issue(
@@ -1549,6 +6353,7 @@ impl GuiApp {
                     contexte,
                     target,
                 );
+                false
             }
         }
     }
@@ -1560,6 +6365,7 @@ impl GuiApp {
         for mut proc in active.drain(..) {
             let mut finished = false;
             while let Ok(event) = proc.handle.rx.try_recv() {
+                proc.touch();
                 match event.kind {
                     ProcEventKind::Line => {
                         self.handle_process_line(&mut proc, &event.text);
@@ -1574,6 +6380,17 @@ impl GuiApp {
                                         || self.codex_retry_without_approval)
                                 }
                                 ProcessKind::CodexCaps => false,
+                                // rc=1 : problemes trouves, deja listes en diagnostics.
+                                ProcessKind::Lint(_) => code > 1,
+                                // 1 : tests en echec (resume), 5 : aucun test collecte.
+                                ProcessKind::Pytest => !matches!(code, 1 | 5),
+                                // Hors depot ou git trop ancien : pas de decorations, sans bruit.
+                                ProcessKind::GitStatus
+                                | ProcessKind::PythonVersion(_)
+                                | ProcessKind::NodeVersion(_)
+                                | ProcessKind::Repl
+                                // explorer renvoie 1 meme quand le dossier s'ouvre.
+                                | ProcessKind::OpenFolder => false,
                                 _ => true,
                             };
                             if should_log {
@@ -1584,6 +6401,14 @@ impl GuiApp {
                                     proc.target,
                                 );
                             }
+                        } else if event.returncode.is_none() && proc.kind != ProcessKind::CodexCaps
+                        {
+                            self.log_issue(
+                                &format!("{} interrompue: {}", proc.contexte, event.text),
+                                "avertissement",
+                                &proc.contexte,
+                                proc.target,
+                            );
                         }
                         self.handle_process_exit(&mut proc, event.returncode);
                         finished = true;
@@ -1592,7 +6417,17 @@ impl GuiApp {
                 }
             }
 
-            if finished {
+            if !finished && proc.timed_out() {
+                // Pas de join : un petit-enfant peut garder les pipes ouverts apres le kill.
+                let _ = proc.handle.kill();
+                self.log_issue(
+                    &proc.timeout_message(),
+                    "erreur",
+                    &proc.contexte,
+                    proc.target,
+                );
+                self.handle_process_exit(&mut proc, Some(PROC_TIMEOUT_EXIT_CODE));
+            } else if finished {
                 proc.handle.join();
             } else {
                 remaining.push(proc);
@@ -1604,57 +6439,91 @@ impl GuiApp {
         self.core.running = remaining;
     }
     fn handle_process_line(&mut self, proc: &mut RunningProcess, line: &str) {
+        if proc.kind.reports_install_progress()
+            && let Some(progress) = parse_install_progress(line)
+        {
+            self.install_progress = Some((proc.target, progress));
+        }
         match proc.kind {
             ProcessKind::CodexExec => self.handle_codex_line(line),
             ProcessKind::CodexCaps => self.handle_codex_caps_line(line),
-            _ => self.push_log(proc.target, line.to_string(), LogKind::Info),
+            ProcessKind::Lint(linter) => {
+                self.record_lint_line(linter, line);
+                let (text, kind) = ansi_log_line(line);
+                self.push_log(proc.target, text, kind);
+            }
+            ProcessKind::GitStatus => self.git_status_lines.push(line.to_string()),
+            ProcessKind::PythonVersion(idx) => self.core.record_python_version(idx, line),
+            ProcessKind::NodeVersion(tool) => self.core.record_node_version(tool, line),
+            ProcessKind::Repl => self.push_repl_output(line.to_string()),
+            ProcessKind::Git(command) => {
+                match command {
+                    GitCommand::Commit => {
+                        if let Some(hash) = parse_commit_hash(line) {
+                            self.git_commit_hash = Some(hash);
+                        }
+                    }
+                    GitCommand::Diff => self.git_diff_lines += 1,
+                    GitCommand::AddAll => {}
+                }
+                let (text, kind) = ansi_log_line(line);
+                self.push_log(proc.target, text, kind);
+            }
+            ProcessKind::Pytest => {
+                if let Some(summary) = parse_pytest_summary(line) {
+                    self.pytest_summary = Some(summary);
+                }
+                let (text, kind) = ansi_log_line(line);
+                self.push_log(proc.target, text, kind);
+            }
+            _ => {
+                let (text, kind) = ansi_log_line(line);
+                self.push_log(proc.target, text, kind);
+            }
         }
     }
 
     fn handle_process_exit(&mut self, proc: &mut RunningProcess, code: Option<i32>) {
+        proc.remove_temp_file();
+        if proc.kind.reports_install_progress() {
+            self.install_progress = None;
+            if code != Some(0) && self.core.settings().offline() {
+                self.log_issue(
+                    "Mode hors-ligne : paquet absent du wheelhouse (tools/wheels) ou du cache npm (cache/npm).",
+                    "avertissement",
+                    "hors_ligne",
+                    proc.target,
+                );
+            }
+        }
+        if proc.kind.installs_tools() {
+            self.tool_status = self.core.tool_status();
+        }
         match proc.kind {
+            ProcessKind::CodexLogout => {
+                self.core.invalidate_codex_status();
+                if code == Some(0) {
+                    self.codex_log_action(
+                        "Deconnexion Codex effectuee. Refais Login (Ctrl+K) avant le prochain prompt.",
+                    );
+                } else {
+                    self.codex_log_action(
+                        "Echec de la deconnexion Codex : verifie l'etat avec Status (Ctrl+T).",
+                    );
+                }
+            }
             ProcessKind::CodexStatus => {
-                if let Some(prompt) = self.pending_codex_prompt.take() {
+                self.codex_status_started = None;
+                self.core.record_codex_status(code == Some(0));
+                if let Some(prompt) = self.core.take_pending_codex_prompt() {
                     if code == Some(0) {
-                        let env_map = self.codex_env();
-                        let extra_args = self.codex_exec_extra_args();
-                        self.codex_exec_used_sandbox_flag =
-                            extra_args.iter().any(|arg| arg == "--sandbox");
-                        self.codex_exec_used_approval_flag =
-                            extra_args.iter().any(|arg| arg == "--ask-for-approval");
                         self.codex_last_prompt = Some(prompt.clone());
-                        match codex_exec_argv(
-                            &prompt,
-                            Some(&self.root_dir),
-                            Some(&env_map),
-                            true,
-                            Some(&extra_args),
-                        ) {
-                            Ok(argv) => {
-                                if !self.codex_compact_view {
-                                    self.codex_log_ui(format!("$ {}", argv.join(" ")));
-                                }
-                                self.spawn_process(
-                                    argv,
-                                    env_map,
-                                    "codex_exec",
-                                    LogTarget::Codex,
-                                    ProcessKind::CodexExec,
-                                );
-                            }
-                            Err(err) => {
-                                self.log_issue(
-                                    &format!("Erreur Codex: {err}"),
-                                    "erreur",
-                                    "codex_exec",
-                                    LogTarget::Codex,
-                                );
-                            }
-                        }
+                        self.spawn_codex_exec(&prompt);
                     } else {
                         self.codex_log_action(
                             "Echec de la verification du login Codex (status en erreur).",
                         );
+                        self.clear_codex_queue();
                         self.codex_log_action(
                             "Si tu n'es pas authentifie, fais Login puis recommence.",
                         );
@@ -1689,51 +6558,144 @@ impl GuiApp {
                     }
                 }
                 self.codex_caps_buffer.clear();
-                if let Some(prompt) = self.pending_codex_prompt.take() {
+                if let Some(prompt) = self.core.take_pending_codex_prompt() {
                     self.run_codex(prompt);
                 }
             }
             ProcessKind::CodexExec => {
-                if self.codex_compact_view && !self.codex_assistant_buffer.is_empty() {
-                    let message = std::mem::take(&mut self.codex_assistant_buffer);
-                    self.codex_log_message(&message);
+                if self.codex_view != CodexView::Raw {
+                    self.flush_codex_assistant_buffer();
                 }
                 if self.codex_retry_without_sandbox || self.codex_retry_without_approval {
                     self.codex_retry_without_sandbox = false;
                     self.codex_retry_without_approval = false;
                     if let Some(prompt) = self.codex_last_prompt.clone() {
-                        let env_map = self.codex_env();
-                        let extra_args = self.codex_exec_extra_args();
-                        self.codex_exec_used_sandbox_flag =
-                            extra_args.iter().any(|arg| arg == "--sandbox");
-                        self.codex_exec_used_approval_flag =
-                            extra_args.iter().any(|arg| arg == "--ask-for-approval");
-                        if let Ok(argv) = codex_exec_argv(
-                            &prompt,
-                            Some(&self.root_dir),
-                            Some(&env_map),
-                            true,
-                            Some(&extra_args),
-                        ) {
-                            self.codex_log_ui(format!("$ {}", argv.join(" ")));
-                            self.spawn_process(
-                                argv,
-                                env_map,
-                                "codex_exec",
-                                LogTarget::Codex,
-                                ProcessKind::CodexExec,
-                            );
-                        }
+                        self.spawn_codex_exec(&prompt);
+                    }
+                } else {
+                    let retry = self
+                        .core
+                        .finish_codex_exec(code == Some(0), self.codex_last_prompt.is_some());
+                    let retrying = matches!(retry, CodexRetry::Scheduled(_));
+                    if let CodexRetry::Scheduled(msg) | CodexRetry::GaveUp(msg) = retry {
+                        self.codex_log_action(&msg);
+                    }
+                    if !retrying {
+                        self.record_codex_turn();
+                        self.run_next_queued_codex();
+                    }
+                }
+            }
+            ProcessKind::GitStatus => {
+                self.git_status_running = false;
+                let lines = std::mem::take(&mut self.git_status_lines);
+                let status = if code == Some(0) {
+                    GitStatusMap::parse(lines.iter().map(String::as_str), &self.root_dir)
+                } else {
+                    GitStatusMap::default()
+                };
+                self.tree.data.set_git_status(status);
+                if self.git_status_stale {
+                    self.refresh_git_status();
+                }
+            }
+            ProcessKind::Git(GitCommand::AddAll) => {
+                if let Some(message) = self.git_commit_message.take()
+                    && code == Some(0)
+                {
+                    self.git_commit_hash = None;
+                    let env_map = self.tools_env();
+                    self.run_git(GitCommand::Commit, git_commit_argv(&message), env_map);
+                } else {
+                    self.refresh_git_status();
+                }
+            }
+            ProcessKind::Git(GitCommand::Commit) => {
+                if code == Some(0) {
+                    match self.git_commit_hash.take() {
+                        Some(hash) => self.log_ui(format!("Commit {hash} cree.")),
+                        None => self.log_ui("Commit cree.".to_string()),
+                    }
+                }
+                self.refresh_git_status();
+            }
+            ProcessKind::Git(GitCommand::Diff) if code == Some(0) && self.git_diff_lines == 0 => {
+                self.log_ui("Aucune difference avec HEAD.".to_string());
+            }
+            // Un `git commit` ou `git checkout` a pu etre lance dans le shell.
+            ProcessKind::Shell => self.refresh_git_status(),
+            ProcessKind::Pytest => match self.pytest_summary {
+                Some(summary) if !summary.is_success() => self.log_issue(
+                    &format!("{} (Alt+Shift+T: relancer les echecs)", summary.label()),
+                    "avertissement",
+                    "tests",
+                    LogTarget::Main,
+                ),
+                Some(summary) => self.log_ui(summary.label()),
+                None if code == Some(5) => self.log_ui("Aucun test trouve.".to_string()),
+                None => {}
+            },
+            ProcessKind::Lint(linter) => {
+                if let Some(path) = self.lint_target.clone() {
+                    let count = self
+                        .diagnostics
+                        .for_path(&path)
+                        .iter()
+                        .filter(|diagnostic| diagnostic.source == linter)
+                        .count();
+                    self.log_ui(format!(
+                        "Lint {} termine: {count} diagnostic(s) (Alt+D: liste).",
+                        linter.as_str()
+                    ));
+                }
+            }
+            ProcessKind::Format => {
+                if let Some(path) = self.format_target.take()
+                    && code == Some(0)
+                    && let Some(idx) = self.tabs.position(&path)
+                {
+                    self.sync_tab_with_disk(idx, &[path]);
+                }
+            }
+            ProcessKind::PyInstallerBuild => {
+                if let Some(output) = self.build_output.take()
+                    && code == Some(0)
+                {
+                    self.log_ui(format!("Build termine: {}", output.display()));
+                    if let Some(dist) = output.parent() {
+                        self.open_prompt(
+                            PromptKind::RevealFolder(dist.to_path_buf()),
+                            &format!(
+                                "Ouvrir {} dans le gestionnaire de fichiers ?",
+                                dist.display()
+                            ),
+                        );
                     }
                 }
             }
+            ProcessKind::Venv => {
+                self.python_label = self.core.python_status_label();
+                if code == Some(0) {
+                    self.log_ui(format!("Interpreteur actif: {}", self.python_label));
+                    self.detect_pythons();
+                }
+            }
+            ProcessKind::PythonVersion(_) => self.finish_python_probe(),
+            ProcessKind::NodeVersion(tool) => self.finish_node_probe(tool),
+            ProcessKind::Repl => {
+                let rc = code.map_or("?".to_string(), |code| code.to_string());
+                self.push_repl_output(format!("[REPL termine (rc={rc})]"));
+            }
             ProcessKind::CodexInstall => {
                 let env_map = self.codex_env();
                 if codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
                     self.codex_log_ui("Codex installe.".to_string());
-                    if let Some(prompt) = self.pending_codex_prompt.take() {
+                    if let Some(prompt) = self.core.take_pending_codex_prompt() {
                         self.run_codex(prompt);
                     }
+                } else if self.core.take_pending_codex_prompt().is_some() {
+                    self.codex_log_action("Echec de l'installation Codex : prompt abandonne.");
+                    self.clear_codex_queue();
                 }
             }
             _ => {}
@@ -1752,7 +6714,7 @@ impl GuiApp {
             return;
         }
         if let Some(translated) = translate_codex_line(trimmed) {
-            if self.codex_compact_view {
+            if self.codex_view != CodexView::Raw {
                 self.codex_log_action(&translated);
             } else {
                 self.codex_log_ui(translated);
@@ -1763,7 +6725,7 @@ impl GuiApp {
         let value: serde_json::Value = match serde_json::from_str(trimmed) {
             Ok(val) => val,
             Err(_) => {
-                if self.codex_compact_view {
+                if self.codex_view != CodexView::Raw {
                     self.codex_log_action(trimmed);
                 } else {
                     self.codex_log_ui(trimmed.to_string());
@@ -1776,7 +6738,7 @@ impl GuiApp {
             .get("type")
             .and_then(serde_json::Value::as_str)
             .unwrap_or("");
-        if self.codex_compact_view {
+        if self.codex_view != CodexView::Raw {
             if matches!(
                 event_type,
                 "response.output_text.delta" | "response.output_text"
@@ -1795,10 +6757,7 @@ impl GuiApp {
                 event_type,
                 "response.output_text.done" | "response.output_item.done" | "response.completed"
             ) {
-                if !self.codex_assistant_buffer.is_empty() {
-                    let message = std::mem::take(&mut self.codex_assistant_buffer);
-                    self.codex_log_message(&message);
-                }
+                self.flush_codex_assistant_buffer();
                 return;
             }
         }
@@ -1808,7 +6767,8 @@ impl GuiApp {
                 .get("message")
                 .and_then(serde_json::Value::as_str)
                 .unwrap_or("");
-            if self.codex_compact_view {
+            self.note_codex_failure(msg);
+            if self.codex_view != CodexView::Raw {
                 if let Some(translated) = translate_codex_line(msg) {
                     self.codex_log_action(&translated);
                 } else if let Some(status) = extract_status_code(msg) {
@@ -1833,7 +6793,8 @@ impl GuiApp {
                 .and_then(|err| err.get("message").or_else(|| err.get("text")))
                 .and_then(serde_json::Value::as_str)
                 .unwrap_or("");
-            if self.codex_compact_view {
+            self.note_codex_failure(msg);
+            if self.codex_view != CodexView::Raw {
                 if let Some(translated) = translate_codex_line(msg) {
                     self.codex_log_action(&translated);
                 } else if let Some(status) = extract_status_code(msg) {
@@ -1850,12 +6811,27 @@ impl GuiApp {
             return;
         }
 
-        if self.codex_compact_view {
+        if self.codex_view == CodexView::Raw {
+            // En vue brute, la reponse est quand meme gardee pour l'historique.
+            for item in extract_display_items(&value) {
+                if item.kind == DisplayKind::Assistant {
+                    self.record_codex_response(&item.message);
+                }
+            }
+        }
+        if self.codex_view != CodexView::Raw {
             for item in extract_display_items(&value) {
+                if !self.codex_view.shows(&item.kind) {
+                    continue;
+                }
                 match item.kind {
+                    DisplayKind::Assistant
+                        if is_streamed_duplicate(&self.codex_streamed_text, &item.message) => {}
                     DisplayKind::Assistant => self.codex_log_message(&item.message),
                     DisplayKind::User => self.codex_log_user_message(&item.message),
-                    DisplayKind::Action => self.codex_log_action(&item.message),
+                    DisplayKind::Action | DisplayKind::Reasoning | DisplayKind::ToolResult => {
+                        self.codex_log_action(&item.message)
+                    }
                 }
             }
         } else if let Some(event_type) = value.get("type").and_then(serde_json::Value::as_str) {
@@ -1888,53 +6864,192 @@ impl GuiApp {
         self.codex_log_entry(msg, "Action", LogKind::Action);
     }
 
+    /// Affiche le message reconstitue des deltas et le retient pour ignorer sa repetition.
+    fn flush_codex_assistant_buffer(&mut self) {
+        if self.codex_assistant_buffer.is_empty() {
+            return;
+        }
+        let message = std::mem::take(&mut self.codex_assistant_buffer);
+        self.codex_log_message(&message);
+        if !self.codex_streamed_text.is_empty() {
+            self.codex_streamed_text.push('\n');
+        }
+        self.codex_streamed_text.push_str(&message);
+    }
+
     fn codex_log_message(&mut self, msg: &str) {
+        self.record_codex_response(msg);
+        let cleaned = msg.trim();
+        if !cleaned.is_empty() {
+            self.last_assistant_message = Some(cleaned.to_string());
+        }
         self.codex_log_entry(msg, "Assistant", LogKind::Assistant);
     }
+
+    fn record_codex_response(&mut self, msg: &str) {
+        let cleaned = msg.trim();
+        if cleaned.is_empty() || self.codex_turn_response.ends_with(cleaned) {
+            return;
+        }
+        if !self.codex_turn_response.is_empty() {
+            self.codex_turn_response.push_str("\n\n");
+        }
+        self.codex_turn_response.push_str(cleaned);
+    }
+
+    /// Lance `codex exec` pour le prompt (options sandbox/approbations selon la version).
+    fn spawn_codex_exec(&mut self, prompt: &str) {
+        let env_map = self.codex_env();
+        let extra_args = self.codex_exec_extra_args();
+        self.codex_exec_used_sandbox_flag = extra_args.iter().any(|arg| arg == "--sandbox");
+        self.codex_exec_used_approval_flag =
+            extra_args.iter().any(|arg| arg == "--ask-for-approval");
+        self.codex_turn_response.clear();
+        self.codex_streamed_text.clear();
+        self.core.begin_codex_exec();
+        match codex_exec_argv(
+            &self.codex_exec_prompt(prompt),
+            Some(&self.root_dir),
+            Some(&env_map),
+            true,
+            Some(&extra_args),
+        ) {
+            Ok(argv) => {
+                if self.codex_view == CodexView::Raw {
+                    self.codex_log_ui(format!("$ {}", argv.join(" ")));
+                }
+                self.spawn_process(
+                    argv,
+                    env_map,
+                    "codex_exec",
+                    LogTarget::Codex,
+                    ProcessKind::CodexExec,
+                );
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur Codex: {err}"),
+                    "erreur",
+                    "codex_exec",
+                    LogTarget::Codex,
+                );
+            }
+        }
+    }
+
+    /// Transmet au core le statut HTTP d'une erreur Codex (relance, cache du pre-check).
+    fn note_codex_failure(&mut self, msg: &str) {
+        if let Some(status) = extract_status_code(msg) {
+            self.core.note_codex_http_status(status);
+        }
+    }
+
+    fn poll_codex_retry(&mut self) {
+        if !self.core.codex_retry_due() {
+            return;
+        }
+        if let Some(prompt) = self.codex_last_prompt.clone() {
+            self.spawn_codex_exec(&prompt);
+        }
+    }
+
+    /// Enregistre l'echange termine (prompt utilisateur + reponse) dans l'historique.
+    fn record_codex_turn(&mut self) {
+        let response = std::mem::take(&mut self.codex_turn_response);
+        let Some(prompt) = self.codex_last_prompt.take() else {
+            return;
+        };
+        if response.is_empty() {
+            return;
+        }
+        // Le fichier joint ne va pas dans l'historique (rappel du prompt, contexte de suite).
+        let turn = CodexTurn {
+            prompt: codex_prompt_display(&prompt),
+            response,
+        };
+        if let Err(err) = self.codex_history.push(self.core.workspace(), turn) {
+            self.log_issue(
+                &format!("Erreur ecriture historique Codex: {err}"),
+                "erreur",
+                "codex_history",
+                LogTarget::Codex,
+            );
+        }
+    }
+
+    /// Prompt envoye a Codex : enrichi des derniers echanges si la session est continuee.
+    fn codex_exec_prompt(&self, prompt: &str) -> String {
+        if self.codex_continue_session {
+            self.codex_history.context_prompt(prompt)
+        } else {
+            prompt.to_string()
+        }
+    }
 }
 
 impl eframe::App for GuiApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.drain_process_events();
+        self.poll_watcher();
+        self.poll_codex_retry();
         self.handle_shortcuts(ctx);
+        self.handle_close_request(ctx);
         self.update_window_title(ctx);
 
         egui::TopBottomPanel::top("header")
             .resizable(false)
             .show(ctx, |ui| self.draw_header(ui));
 
-        egui::SidePanel::left("files")
-            .resizable(true)
-            .default_width(280.0)
-            .min_width(220.0)
-            .max_width(420.0)
-            .show(ctx, |ui| self.draw_file_tree(ui));
-
-        egui::TopBottomPanel::bottom("bottom")
-            .resizable(true)
-            .default_height({
-                let h = ctx.input(|i| i.screen_rect().height());
-                (h * 0.30).clamp(240.0, 360.0)
-            })
-            .min_height(220.0)
-            .max_height({
-                let h = ctx.input(|i| i.screen_rect().height());
-                (h * 0.45).clamp(280.0, 480.0)
-            })
-            .show(ctx, |ui| {
-                let height = ui.available_height();
-                ui.columns(2, |columns| {
-                    columns[0].set_min_height(height);
-                    columns[1].set_min_height(height);
-                    self.draw_command_panel(&mut columns[0]);
-                    self.draw_codex_panel(&mut columns[1]);
-                });
-            });
+        if !self.zen_mode {
+            self.draw_side_panels(ctx);
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
             self.draw_editor(ui);
         });
 
+        self.draw_prompt(ctx);
+        self.draw_settings(ctx);
+        self.draw_environment_report(ctx);
+        self.draw_python_picker(ctx);
+        self.draw_setup(ctx);
+        self.draw_env_inspector(ctx);
+        self.draw_repl(ctx);
+        self.draw_log_search(ctx);
+        self.draw_dev_tools(ctx);
+        self.draw_diagnostics(ctx);
+
+        if self.last_session_save.elapsed() >= SESSION_SAVE_INTERVAL {
+            self.save_session();
+        }
+        if let Some(interval) = self.core.settings().autosave_interval()
+            && self.last_autosave.elapsed() >= interval
+        {
+            self.autosave_tabs();
+        }
+
         ctx.request_repaint_after(Duration::from_millis(33));
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_session();
+    }
+}
+
+/// Libelle du reglage d'indentation (`None` : selon l'extension du fichier).
+fn insert_spaces_label(choice: Option<bool>) -> &'static str {
+    match choice {
+        None => "Auto (espaces pour .py)",
+        Some(true) => "Espaces",
+        Some(false) => "Tabulation",
+    }
+}
+
+/// Signale qu'une variable d'environnement remplace la valeur du fichier.
+fn env_note(ui: &mut egui::Ui, env: &str) {
+    if env_overrides(env) {
+        ui.label(egui::RichText::new(format!("{env} actif")).weak());
+    } else {
+        ui.label("");
+    }
 }
@@ -0,0 +1,290 @@
+use std::path::Path;
+
+/// Langages colores par l'editeur (deduits de l'extension du fichier ouvert).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Python,
+    Toml,
+    Json,
+}
+
+impl Language {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "py" | "pyw" => Some(Language::Python),
+            "toml" => Some(Language::Toml),
+            "json" => Some(Language::Json),
+            _ => None,
+        }
+    }
+
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Language::Python => PYTHON_KEYWORDS,
+            Language::Toml => &["true", "false"],
+            Language::Json => &["true", "false", "null"],
+        }
+    }
+
+    fn has_comments(self) -> bool {
+        self != Language::Json
+    }
+
+    fn has_triple_strings(self) -> bool {
+        self != Language::Json
+    }
+}
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Prefixes de chaines Python (f"...", rb'...').
+const PYTHON_STRING_PREFIXES: &[&str] = &["r", "b", "f", "u", "rb", "br", "fr", "rf"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+/// Portion coloree d'une ligne (offsets en octets). Le reste de la ligne est du texte simple.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Etat transmis d'une ligne a la suivante : delimiteur de chaine triple encore ouverte.
+pub type LineState = Option<&'static str>;
+
+/// Decoupe une ligne en tokens colores et met a jour l'etat pour la ligne suivante.
+pub fn highlight_line(lang: Language, line: &str, state: &mut LineState) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    if let Some(delim) = *state {
+        match line.find(delim) {
+            Some(idx) => {
+                pos = idx + delim.len();
+                *state = None;
+            }
+            None => pos = line.len(),
+        }
+        push_token(&mut tokens, TokenKind::String, 0, pos);
+    }
+
+    if lang == Language::Toml && pos == 0 && line.trim_start().starts_with('[') {
+        let start = line.len() - line.trim_start().len();
+        let end = line.find(']').map_or(line.len(), |idx| idx + 1);
+        push_token(&mut tokens, TokenKind::Keyword, start, end);
+        pos = end;
+    }
+
+    while pos < line.len() {
+        let rest = &line[pos..];
+        let Some(ch) = rest.chars().next() else {
+            break;
+        };
+        if ch == '#' && lang.has_comments() {
+            push_token(&mut tokens, TokenKind::Comment, pos, line.len());
+            break;
+        }
+        if ch == '"' || (ch == '\'' && lang != Language::Json) {
+            pos = scan_string(lang, line, pos, pos, state, &mut tokens);
+            continue;
+        }
+        if ch.is_ascii_digit() {
+            let end = pos + word_len(rest, |c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+            push_token(&mut tokens, TokenKind::Number, pos, end);
+            pos = end;
+            continue;
+        }
+        if ch.is_alphabetic() || ch == '_' {
+            let end = pos + word_len(rest, |c| c.is_alphanumeric() || c == '_');
+            let word = &line[pos..end];
+            let next = line[end..].chars().next();
+            if lang == Language::Python
+                && matches!(next, Some('"') | Some('\''))
+                && PYTHON_STRING_PREFIXES.contains(&word.to_ascii_lowercase().as_str())
+            {
+                pos = scan_string(lang, line, pos, end, state, &mut tokens);
+            } else {
+                if lang.keywords().contains(&word) {
+                    push_token(&mut tokens, TokenKind::Keyword, pos, end);
+                }
+                pos = end;
+            }
+            continue;
+        }
+        pos += ch.len_utf8();
+    }
+    tokens
+}
+
+/// Met a jour l'etat sans produire de tokens (lignes hors ecran), en ne cherchant que
+/// les delimiteurs triples et les commentaires : approximatif mais tres rapide.
+pub fn carry_state(lang: Language, line: &str, state: &mut LineState) {
+    if !lang.has_triple_strings() {
+        return;
+    }
+    let mut pos = 0;
+    loop {
+        let rest = &line[pos..];
+        if let Some(delim) = *state {
+            match rest.find(delim) {
+                Some(idx) => {
+                    pos += idx + delim.len();
+                    *state = None;
+                }
+                None => return,
+            }
+            continue;
+        }
+        let next = ["\"\"\"", "'''", "#"]
+            .into_iter()
+            .filter_map(|pat| rest.find(pat).map(|idx| (idx, pat)))
+            .min_by_key(|(idx, _)| *idx);
+        match next {
+            Some((_, "#")) | None => return,
+            Some((idx, delim)) => {
+                *state = Some(if delim == "'''" { "'''" } else { "\"\"\"" });
+                pos += idx + delim.len();
+            }
+        }
+    }
+}
+
+fn scan_string(
+    lang: Language,
+    line: &str,
+    start: usize,
+    quote_pos: usize,
+    state: &mut LineState,
+    tokens: &mut Vec<Token>,
+) -> usize {
+    let rest = &line[quote_pos..];
+    if lang.has_triple_strings() {
+        for delim in ["\"\"\"", "'''"] {
+            if rest.starts_with(delim) {
+                let body = quote_pos + delim.len();
+                let end = match line[body..].find(delim) {
+                    Some(idx) => body + idx + delim.len(),
+                    None => {
+                        *state = Some(delim);
+                        line.len()
+                    }
+                };
+                push_token(tokens, TokenKind::String, start, end);
+                return end;
+            }
+        }
+    }
+    let quote = rest.chars().next().unwrap_or('"');
+    let mut escaped = false;
+    let mut end = line.len();
+    for (idx, ch) in rest.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == quote {
+            end = quote_pos + idx + ch.len_utf8();
+            break;
+        }
+    }
+    push_token(tokens, TokenKind::String, start, end);
+    end
+}
+
+fn word_len(text: &str, accept: impl Fn(char) -> bool) -> usize {
+    text.char_indices()
+        .find(|(_, ch)| !accept(*ch))
+        .map_or(text.len(), |(idx, _)| idx)
+}
+
+fn push_token(tokens: &mut Vec<Token>, kind: TokenKind, start: usize, end: usize) {
+    if end > start {
+        tokens.push(Token { kind, start, end });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(lang: Language, line: &str, state: &mut LineState) -> Vec<(TokenKind, String)> {
+        highlight_line(lang, line, state)
+            .into_iter()
+            .map(|token| (token.kind, line[token.start..token.end].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn python_mots_cles_chaines_commentaires_nombres() {
+        let mut state = None;
+        let tokens = kinds(
+            Language::Python,
+            "def f(x=42): return f'é{x}' # fin",
+            &mut state,
+        );
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "def".to_string()),
+                (TokenKind::Number, "42".to_string()),
+                (TokenKind::Keyword, "return".to_string()),
+                (TokenKind::String, "f'é{x}'".to_string()),
+                (TokenKind::Comment, "# fin".to_string()),
+            ]
+        );
+        assert_eq!(state, None);
+    }
+
+    #[test]
+    fn chaine_triple_sur_plusieurs_lignes() {
+        let mut state = None;
+        kinds(Language::Python, "doc = \"\"\"debut", &mut state);
+        assert_eq!(state, Some("\"\"\""));
+        let tokens = kinds(Language::Python, "fin\"\"\" if x", &mut state);
+        assert_eq!(tokens[0], (TokenKind::String, "fin\"\"\"".to_string()));
+        assert_eq!(tokens[1], (TokenKind::Keyword, "if".to_string()));
+        assert_eq!(state, None);
+
+        let mut carried = None;
+        carry_state(Language::Python, "x = '''a''' # '''", &mut carried);
+        assert_eq!(carried, None);
+        carry_state(Language::Python, "y = '''", &mut carried);
+        assert_eq!(carried, Some("'''"));
+    }
+
+    #[test]
+    fn json_et_toml() {
+        let mut state = None;
+        let tokens = kinds(Language::Json, "{\"a#\": true, \"n\": null}", &mut state);
+        assert_eq!(
+            tokens.iter().map(|(kind, _)| *kind).collect::<Vec<_>>(),
+            vec![
+                TokenKind::String,
+                TokenKind::Keyword,
+                TokenKind::String,
+                TokenKind::Keyword
+            ]
+        );
+        let tokens = kinds(Language::Toml, "[package] # meta", &mut state);
+        assert_eq!(tokens[0], (TokenKind::Keyword, "[package]".to_string()));
+        assert_eq!(tokens[1], (TokenKind::Comment, "# meta".to_string()));
+        assert_eq!(
+            Language::from_path(Path::new("src/Main.PY")),
+            Some(Language::Python)
+        );
+        assert_eq!(Language::from_path(Path::new("notes.txt")), None);
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs;
+use std::path::Path;
+
+/// Fichiers d'ignore lus a la racine du workspace (dans cet ordre).
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".usbideignore"];
+
+/// Motifs toujours appliques (en plus des dossiers internes, masques dans tous les cas).
+const DEFAULT_IGNORE: [&str; 10] = [
+    "node_modules/",
+    "__pycache__/",
+    "*.pyc",
+    "*.pyo",
+    ".venv/",
+    ".mypy_cache/",
+    ".pytest_cache/",
+    ".ruff_cache/",
+    ".DS_Store",
+    "*.autosave",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IgnorePattern {
+    glob: String,
+    /// Motif avec un '/' interne : compare au chemin relatif complet, sinon au seul nom.
+    anchored: bool,
+    dir_only: bool,
+    negated: bool,
+}
+
+impl IgnorePattern {
+    /// Ligne au format .gitignore simplifie (`*`, `?`, `**`, `!motif`, `dossier/`).
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let glob = line.trim_start_matches('/').to_string();
+        if glob.is_empty() {
+            return None;
+        }
+        Some(Self {
+            glob,
+            anchored,
+            dir_only,
+            negated,
+        })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            glob_match(&self.glob, relative)
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            glob_match(&self.glob, name)
+        }
+    }
+}
+
+/// Regles d'ignore de l'arborescence : motifs par defaut puis .gitignore et .usbideignore.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreRules {
+    /// Charge les motifs du root. Fichiers absents ou illisibles : motifs par defaut seuls.
+    pub fn load(root: &Path) -> Self {
+        let mut rules = Self::from_lines(DEFAULT_IGNORE);
+        for name in IGNORE_FILES {
+            if let Ok(raw) = fs::read_to_string(root.join(name)) {
+                rules
+                    .patterns
+                    .extend(raw.lines().filter_map(IgnorePattern::parse));
+            }
+        }
+        rules
+    }
+
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            patterns: lines.into_iter().filter_map(IgnorePattern::parse).collect(),
+        }
+    }
+
+    /// Chemin relatif au root (separateurs '/' ou '\'). Le dernier motif qui correspond l'emporte.
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let relative = relative.trim_matches('/');
+        if relative.is_empty() {
+            return false;
+        }
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(relative, is_dir))
+            .is_some_and(|pattern| !pattern.negated)
+    }
+}
+
+/// Glob simple : `*` et `?` hors '/', `**` traverse les dossiers.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // "**/x" accepte aussi "x" a la racine.
+            if rest.first() == Some(&'/') && glob_match_from(&rest[1..], text) {
+                return true;
+            }
+            (0..=text.len()).any(|idx| glob_match_from(rest, &text[idx..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for idx in 0..=text.len() {
+                if glob_match_from(rest, &text[idx..]) {
+                    return true;
+                }
+                if text.get(idx) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => {
+            text.first().is_some_and(|ch| *ch != '/') && glob_match_from(&pattern[1..], &text[1..])
+        }
+        Some(ch) => text.first() == Some(ch) && glob_match_from(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn glob_etoiles_et_dossiers() {
+        assert!(glob_match("*.pyc", "mod.pyc"));
+        assert!(!glob_match("*.pyc", "mod.py"));
+        assert!(glob_match("te?t.py", "test.py"));
+        assert!(!glob_match("docs/*.md", "docs/api/x.md"));
+        assert!(glob_match("docs/**/*.md", "docs/api/x.md"));
+        assert!(glob_match("**/build", "build"));
+        assert!(glob_match("**/build", "a/b/build"));
+    }
+
+    #[test]
+    fn regles_gitignore_simplifiees() {
+        let rules = IgnoreRules::from_lines([
+            "# commentaire",
+            "*.log",
+            "!garder.log",
+            "build/",
+            "/docs/brouillon.md",
+        ]);
+        assert!(rules.is_ignored(Path::new("src/debug.log"), false));
+        assert!(!rules.is_ignored(Path::new("garder.log"), false));
+        assert!(rules.is_ignored(Path::new("pkg/build"), true));
+        // "build/" ne vise que les dossiers.
+        assert!(!rules.is_ignored(Path::new("build"), false));
+        assert!(rules.is_ignored(Path::new("docs/brouillon.md"), false));
+        assert!(!rules.is_ignored(Path::new("src/docs/brouillon.md"), false));
+    }
+
+    #[test]
+    fn charge_defauts_et_fichiers_du_root() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".usbideignore"), "secret.txt\n").unwrap();
+        let rules = IgnoreRules::load(dir.path());
+        assert!(rules.is_ignored(Path::new("node_modules"), true));
+        assert!(rules.is_ignored(Path::new("pkg/__pycache__"), true));
+        assert!(rules.is_ignored(Path::new("secret.txt"), false));
+        assert!(!rules.is_ignored(Path::new("main.py"), false));
+    }
+}
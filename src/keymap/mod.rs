@@ -0,0 +1,710 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::quick_open::fuzzy_score;
+use crate::settings::{TomlValue, toml_entries};
+
+/// Actions des raccourcis globaux, nommees comme dans `.usbide/keymap.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Save,
+    Run,
+    CodexStop,
+    ClearLog,
+    ExportLogs,
+    ReloadTree,
+    CodexLogin,
+    CodexCheck,
+    CodexInstall,
+    ToggleCodexView,
+    ToggleCodexSandbox,
+    ToggleCodexApproval,
+    ToggleCodexSession,
+    QuickOpen,
+    BuildExe,
+    DevTools,
+    Search,
+    Replace,
+    GotoLine,
+    NewFile,
+    NewFolder,
+    ToggleLineEnding,
+    InstallRequirements,
+    CreateVenv,
+    ToggleOnefile,
+    RevealCurrent,
+    ToggleShowHidden,
+    ToggleShowIgnored,
+    CopyLastAssistant,
+    CopyCodexLog,
+    CycleLogFilter,
+    CycleCodexLogFilter,
+    ToggleWordWrap,
+    Settings,
+    NextTab,
+    PrevTab,
+    CloseTab,
+    CommandPalette,
+    ReopenWithEncoding,
+    SaveWithEncoding,
+    Format,
+    Lint,
+    Diagnostics,
+    RunTests,
+    RerunFailedTests,
+    StopTests,
+    GitCommit,
+    GitDiff,
+    DiffWithDisk,
+    AskCodexAboutFile,
+    ExplainSelection,
+    EnvironmentReport,
+    CodexLogout,
+    KillAllProcesses,
+    ClearMainLog,
+    ClearCodexLog,
+    FindInLog,
+    ToggleZenMode,
+    CollapseTree,
+    ExpandTree,
+    SelectPython,
+    EnvInspector,
+    ClearCaches,
+    RunSelection,
+    PythonRepl,
+    OpenContainingFolder,
+    ApplyCodexPatch,
+    ClearCodexQueue,
+}
+
+const NONE: u8 = 0;
+const CTRL: u8 = 1;
+const ALT: u8 = 2;
+const SHIFT: u8 = 4;
+
+/// Raccourci par defaut, deja normalise (lettre minuscule, Shift explicite).
+const fn chord(mods: u8, key: ChordKey) -> KeyChord {
+    KeyChord {
+        ctrl: mods & CTRL != 0,
+        alt: mods & ALT != 0,
+        shift: mods & SHIFT != 0,
+        key,
+    }
+}
+
+const fn ch(mods: u8, key: char) -> KeyChord {
+    chord(mods, ChordKey::Char(key))
+}
+
+/// Raccourcis par defaut ; une entree du fichier remplace ceux de son action.
+const DEFAULT_BINDINGS: [(Action, KeyChord); 76] = [
+    (Action::Quit, ch(CTRL, 'q')),
+    (Action::Save, ch(CTRL, 's')),
+    (Action::Run, chord(NONE, ChordKey::F(5))),
+    (Action::CodexStop, chord(SHIFT, ChordKey::F(5))),
+    (Action::ClearLog, ch(CTRL, 'l')),
+    (Action::ExportLogs, ch(CTRL, 'b')),
+    (Action::ReloadTree, ch(CTRL, 'r')),
+    (Action::CodexLogin, ch(CTRL, 'k')),
+    (Action::CodexCheck, ch(CTRL, 't')),
+    (Action::CodexInstall, ch(CTRL, 'i')),
+    (Action::ToggleCodexView, ch(CTRL, 'm')),
+    (Action::ToggleCodexSandbox, ch(CTRL, 'o')),
+    (Action::ToggleCodexApproval, ch(ALT, 'a')),
+    (Action::ToggleCodexSession, ch(CTRL, 'u')),
+    (Action::QuickOpen, ch(CTRL, 'p')),
+    (Action::BuildExe, ch(CTRL, 'e')),
+    (Action::DevTools, ch(CTRL, 'd')),
+    (Action::Search, ch(CTRL, 'f')),
+    (Action::Replace, ch(CTRL, 'h')),
+    (Action::GotoLine, ch(CTRL, 'g')),
+    (Action::NewFile, ch(CTRL, 'n')),
+    (Action::NewFolder, ch(CTRL | SHIFT, 'n')),
+    // Sans Shift pour les terminaux qui envoient Ctrl+Shift+lettre comme Ctrl+lettre.
+    (Action::NewFolder, ch(ALT, 'n')),
+    (Action::ToggleLineEnding, chord(NONE, ChordKey::F(6))),
+    (Action::InstallRequirements, chord(NONE, ChordKey::F(8))),
+    (Action::CreateVenv, chord(NONE, ChordKey::F(9))),
+    (Action::ToggleOnefile, ch(ALT, 'e')),
+    (Action::RevealCurrent, ch(ALT, 'r')),
+    (Action::ToggleShowHidden, ch(ALT, 'h')),
+    (Action::ToggleShowIgnored, ch(ALT, 'i')),
+    (Action::CopyLastAssistant, ch(ALT, 'c')),
+    (Action::CopyCodexLog, ch(ALT, 'y')),
+    (Action::CycleLogFilter, ch(ALT, 'l')),
+    (Action::CycleCodexLogFilter, ch(ALT | SHIFT, 'l')),
+    (Action::ToggleWordWrap, ch(ALT, 'z')),
+    (Action::Settings, ch(ALT, 'p')),
+    (Action::NextTab, chord(CTRL, ChordKey::Tab)),
+    (Action::PrevTab, chord(CTRL | SHIFT, ChordKey::Tab)),
+    // Beaucoup de terminaux n'envoient pas Ctrl+Tab.
+    (Action::NextTab, ch(ALT, '.')),
+    (Action::PrevTab, ch(ALT, ',')),
+    (Action::CloseTab, ch(CTRL, 'w')),
+    (Action::CommandPalette, ch(CTRL | SHIFT, 'p')),
+    // Certains terminaux ne distinguent pas Ctrl+Shift+P de Ctrl+P.
+    (Action::CommandPalette, chord(NONE, ChordKey::F(1))),
+    (Action::ReopenWithEncoding, ch(ALT, 'o')),
+    (Action::SaveWithEncoding, ch(ALT, 's')),
+    (Action::Format, ch(ALT, 'f')),
+    (Action::Lint, ch(ALT, 'k')),
+    (Action::Diagnostics, ch(ALT, 'd')),
+    (Action::RunTests, ch(ALT, 't')),
+    (Action::RerunFailedTests, ch(ALT | SHIFT, 't')),
+    (Action::StopTests, ch(ALT, 'x')),
+    (Action::GitCommit, ch(ALT, 'g')),
+    (Action::GitDiff, ch(ALT | SHIFT, 'g')),
+    (Action::DiffWithDisk, ch(ALT | SHIFT, 'd')),
+    (Action::AskCodexAboutFile, ch(ALT, 'q')),
+    (Action::ExplainSelection, ch(ALT | SHIFT, 'q')),
+    (Action::EnvironmentReport, ch(ALT | SHIFT, 'e')),
+    (Action::CodexLogout, ch(CTRL | SHIFT, 'k')),
+    (Action::CodexLogout, ch(CTRL | ALT, 'k')),
+    (Action::KillAllProcesses, ch(CTRL | SHIFT, 'x')),
+    (Action::KillAllProcesses, ch(CTRL | ALT, 'x')),
+    (Action::ClearMainLog, ch(CTRL | SHIFT, 'l')),
+    (Action::ClearMainLog, ch(CTRL | ALT, 'j')),
+    (Action::ClearCodexLog, ch(CTRL | ALT, 'l')),
+    (Action::FindInLog, ch(CTRL | ALT, 'f')),
+    (Action::ToggleZenMode, ch(ALT, 'm')),
+    (Action::CollapseTree, ch(ALT, '-')),
+    (Action::ExpandTree, ch(ALT, '=')),
+    (Action::SelectPython, ch(ALT | SHIFT, 'p')),
+    (Action::EnvInspector, ch(ALT | SHIFT, 'v')),
+    (Action::ClearCaches, ch(ALT | SHIFT, 'c')),
+    (Action::RunSelection, chord(CTRL, ChordKey::F(5))),
+    (Action::PythonRepl, ch(ALT | SHIFT, 'r')),
+    (Action::OpenContainingFolder, ch(ALT | SHIFT, 'o')),
+    (Action::ApplyCodexPatch, ch(ALT | SHIFT, 'a')),
+    (Action::ClearCodexQueue, ch(ALT | SHIFT, 'x')),
+];
+
+impl Action {
+    pub const ALL: [Action; 69] = [
+        Action::Quit,
+        Action::Save,
+        Action::Run,
+        Action::CodexStop,
+        Action::ClearLog,
+        Action::ExportLogs,
+        Action::ReloadTree,
+        Action::CodexLogin,
+        Action::CodexCheck,
+        Action::CodexInstall,
+        Action::ToggleCodexView,
+        Action::ToggleCodexSandbox,
+        Action::ToggleCodexApproval,
+        Action::ToggleCodexSession,
+        Action::QuickOpen,
+        Action::BuildExe,
+        Action::DevTools,
+        Action::Search,
+        Action::Replace,
+        Action::GotoLine,
+        Action::NewFile,
+        Action::NewFolder,
+        Action::ToggleLineEnding,
+        Action::InstallRequirements,
+        Action::CreateVenv,
+        Action::ToggleOnefile,
+        Action::RevealCurrent,
+        Action::ToggleShowHidden,
+        Action::ToggleShowIgnored,
+        Action::CopyLastAssistant,
+        Action::CopyCodexLog,
+        Action::CycleLogFilter,
+        Action::CycleCodexLogFilter,
+        Action::ToggleWordWrap,
+        Action::Settings,
+        Action::NextTab,
+        Action::PrevTab,
+        Action::CloseTab,
+        Action::CommandPalette,
+        Action::ReopenWithEncoding,
+        Action::SaveWithEncoding,
+        Action::Format,
+        Action::Lint,
+        Action::Diagnostics,
+        Action::RunTests,
+        Action::RerunFailedTests,
+        Action::StopTests,
+        Action::GitCommit,
+        Action::GitDiff,
+        Action::DiffWithDisk,
+        Action::AskCodexAboutFile,
+        Action::ExplainSelection,
+        Action::EnvironmentReport,
+        Action::CodexLogout,
+        Action::KillAllProcesses,
+        Action::ClearMainLog,
+        Action::ClearCodexLog,
+        Action::FindInLog,
+        Action::ToggleZenMode,
+        Action::CollapseTree,
+        Action::ExpandTree,
+        Action::SelectPython,
+        Action::EnvInspector,
+        Action::ClearCaches,
+        Action::RunSelection,
+        Action::PythonRepl,
+        Action::OpenContainingFolder,
+        Action::ApplyCodexPatch,
+        Action::ClearCodexQueue,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Save => "save",
+            Action::Run => "run",
+            Action::CodexStop => "codex_stop",
+            Action::ClearLog => "clear_log",
+            Action::ExportLogs => "export_logs",
+            Action::ReloadTree => "reload_tree",
+            Action::CodexLogin => "codex_login",
+            Action::CodexCheck => "codex_check",
+            Action::CodexInstall => "codex_install",
+            Action::ToggleCodexView => "toggle_codex_view",
+            Action::ToggleCodexSandbox => "toggle_codex_sandbox",
+            Action::ToggleCodexApproval => "toggle_codex_approval",
+            Action::ToggleCodexSession => "toggle_codex_session",
+            Action::QuickOpen => "quick_open",
+            Action::BuildExe => "build_exe",
+            Action::DevTools => "dev_tools",
+            Action::Search => "search",
+            Action::Replace => "replace",
+            Action::GotoLine => "goto_line",
+            Action::NewFile => "new_file",
+            Action::NewFolder => "new_folder",
+            Action::ToggleLineEnding => "toggle_line_ending",
+            Action::InstallRequirements => "install_requirements",
+            Action::CreateVenv => "create_venv",
+            Action::ToggleOnefile => "toggle_onefile",
+            Action::RevealCurrent => "reveal_current",
+            Action::ToggleShowHidden => "toggle_show_hidden",
+            Action::ToggleShowIgnored => "toggle_show_ignored",
+            Action::CopyLastAssistant => "copy_last_assistant",
+            Action::CopyCodexLog => "copy_codex_log",
+            Action::CycleLogFilter => "cycle_log_filter",
+            Action::CycleCodexLogFilter => "cycle_codex_log_filter",
+            Action::ToggleWordWrap => "toggle_word_wrap",
+            Action::Settings => "settings",
+            Action::NextTab => "next_tab",
+            Action::PrevTab => "prev_tab",
+            Action::CloseTab => "close_tab",
+            Action::CommandPalette => "command_palette",
+            Action::ReopenWithEncoding => "reopen_with_encoding",
+            Action::SaveWithEncoding => "save_with_encoding",
+            Action::Format => "format",
+            Action::Lint => "lint",
+            Action::Diagnostics => "diagnostics",
+            Action::RunTests => "run_tests",
+            Action::RerunFailedTests => "rerun_failed_tests",
+            Action::StopTests => "stop_tests",
+            Action::GitCommit => "git_commit",
+            Action::GitDiff => "git_diff",
+            Action::DiffWithDisk => "diff_with_disk",
+            Action::AskCodexAboutFile => "codex_ask_file",
+            Action::ExplainSelection => "codex_explain_selection",
+            Action::EnvironmentReport => "environment_report",
+            Action::CodexLogout => "codex_logout",
+            Action::KillAllProcesses => "kill_all_processes",
+            Action::ClearMainLog => "clear_main_log",
+            Action::ClearCodexLog => "clear_codex_log",
+            Action::FindInLog => "find_in_log",
+            Action::ToggleZenMode => "toggle_zen_mode",
+            Action::CollapseTree => "collapse_tree",
+            Action::ExpandTree => "expand_tree",
+            Action::SelectPython => "select_python",
+            Action::EnvInspector => "env_inspector",
+            Action::ClearCaches => "clear_caches",
+            Action::RunSelection => "run_selection",
+            Action::PythonRepl => "python_repl",
+            Action::OpenContainingFolder => "open_containing_folder",
+            Action::ApplyCodexPatch => "apply_codex_patch",
+            Action::ClearCodexQueue => "clear_codex_queue",
+        }
+    }
+
+    /// Libelle de la palette de commandes.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Quit => "Quitter",
+            Action::Save => "Sauver le fichier",
+            Action::Run => "Executer le fichier courant",
+            Action::CodexStop => "Arreter Codex",
+            Action::ClearLog => "Vider le journal du panneau actif",
+            Action::ExportLogs => "Exporter les logs",
+            Action::ReloadTree => "Recharger l'arborescence",
+            Action::CodexLogin => "Codex : login",
+            Action::CodexCheck => "Codex : status",
+            Action::CodexInstall => "Codex : installer",
+            Action::ToggleCodexView => "Codex : vue compacte/brute",
+            Action::ToggleCodexSandbox => "Codex : changer le sandbox",
+            Action::ToggleCodexApproval => "Codex : changer la politique d'approbation",
+            Action::ToggleCodexSession => "Codex : continuer la session",
+            Action::QuickOpen => "Ouvrir un fichier",
+            Action::BuildExe => "PyInstaller : construire l'executable",
+            Action::DevTools => "Installer les outils dev (ruff, pytest...)",
+            Action::Search => "Chercher dans le fichier",
+            Action::Replace => "Remplacer dans le fichier",
+            Action::GotoLine => "Aller a la ligne",
+            Action::NewFile => "Nouveau fichier",
+            Action::NewFolder => "Nouveau dossier",
+            Action::ToggleLineEnding => "Basculer LF/CRLF",
+            Action::InstallRequirements => "Installer requirements.txt",
+            Action::CreateVenv => "Creer le venv",
+            Action::ToggleOnefile => "PyInstaller : basculer onefile",
+            Action::RevealCurrent => "Reveler le fichier dans l'arborescence",
+            Action::ToggleShowHidden => "Afficher/masquer les fichiers caches",
+            Action::ToggleShowIgnored => "Afficher/masquer les fichiers ignores",
+            Action::CopyLastAssistant => "Copier la derniere reponse Codex",
+            Action::CopyCodexLog => "Copier la sortie Codex",
+            Action::CycleLogFilter => "Filtre du journal",
+            Action::CycleCodexLogFilter => "Filtre de la sortie Codex",
+            Action::ToggleWordWrap => "Retour a la ligne",
+            Action::Settings => "Parametres (settings.toml)",
+            Action::NextTab => "Onglet suivant",
+            Action::PrevTab => "Onglet precedent",
+            Action::CloseTab => "Fermer l'onglet",
+            Action::CommandPalette => "Palette de commandes",
+            Action::ReopenWithEncoding => "Rouvrir avec un encodage",
+            Action::SaveWithEncoding => "Enregistrer avec un encodage",
+            Action::Format => "Formater le fichier (black/ruff)",
+            Action::Lint => "Linter le fichier (ruff/mypy)",
+            Action::Diagnostics => "Liste des diagnostics",
+            Action::RunTests => "Lancer les tests (pytest)",
+            Action::RerunFailedTests => "Relancer les tests en echec",
+            Action::StopTests => "Arreter les tests",
+            Action::GitCommit => "Git : tout indexer et committer",
+            Action::GitDiff => "Git : diff du fichier courant",
+            Action::DiffWithDisk => "Diff du buffer avec le fichier sur disque",
+            Action::AskCodexAboutFile => "Question a Codex sur le fichier ou la selection",
+            Action::ExplainSelection => "Expliquer la selection avec Codex",
+            Action::EnvironmentReport => "Rapport d'environnement",
+            Action::CodexLogout => "Codex : logout",
+            Action::KillAllProcesses => "Arreter tous les process",
+            Action::ClearMainLog => "Vider le Journal",
+            Action::ClearCodexLog => "Vider la Sortie Codex",
+            Action::FindInLog => "Chercher dans les logs",
+            Action::ToggleZenMode => "Mode zen (editeur seul)",
+            Action::CollapseTree => "Replier toute l'arborescence",
+            Action::ExpandTree => "Deplier toute l'arborescence",
+            Action::SelectPython => "Choisir l'interpreteur Python",
+            Action::EnvInspector => "Inspecter les variables d'environnement",
+            Action::ClearCaches => "Nettoyer caches",
+            Action::RunSelection => "Executer la selection Python",
+            Action::PythonRepl => "Console Python (REPL)",
+            Action::OpenContainingFolder => "Ouvrir le dossier dans l'explorateur",
+            Action::ApplyCodexPatch => "Codex : appliquer le patch de la derniere reponse",
+            Action::ClearCodexQueue => "Codex : vider la file d'attente",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// Touche d'un raccourci : lettre/chiffre/symbole (minuscule), touche de fonction ou Tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChordKey {
+    Char(char),
+    F(u8),
+    Tab,
+}
+
+/// Combinaison de touches ("Ctrl+Shift+P", "F5", "Alt+Z").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub key: ChordKey,
+}
+
+impl KeyChord {
+    /// Normalise : lettre en minuscule (une majuscule implique Shift), Shift ignore sur les
+    /// symboles (il depend de la disposition du clavier).
+    pub fn new(ctrl: bool, alt: bool, shift: bool, key: ChordKey) -> Self {
+        let (shift, key) = match key {
+            ChordKey::Char(ch) if ch.is_ascii_uppercase() => {
+                (true, ChordKey::Char(ch.to_ascii_lowercase()))
+            }
+            ChordKey::Char(ch) if !ch.is_ascii_alphabetic() => (false, ChordKey::Char(ch)),
+            key => (shift, key),
+        };
+        Self {
+            ctrl,
+            alt,
+            shift,
+            key,
+        }
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let mut ctrl = false;
+        let mut alt = false;
+        let mut shift = false;
+        let mut key = None;
+        for part in raw.split('+').map(str::trim) {
+            if key.is_some() {
+                return Err(format!("touche avant la fin: '{raw}'"));
+            }
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ctrl = true,
+                "alt" => alt = true,
+                "shift" | "maj" => shift = true,
+                lower => {
+                    key = Some(parse_key(part, lower).ok_or(format!("touche inconnue: '{part}'"))?)
+                }
+            }
+        }
+        let key = key.ok_or(format!("aucune touche: '{raw}'"))?;
+        Ok(Self::new(ctrl, alt, shift, key))
+    }
+}
+
+fn parse_key(part: &str, lower: &str) -> Option<ChordKey> {
+    let mut chars = part.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return (!ch.is_whitespace()).then(|| ChordKey::Char(ch.to_ascii_lowercase()));
+    }
+    if lower == "tab" {
+        return Some(ChordKey::Tab);
+    }
+    let number: u8 = lower.strip_prefix('f')?.parse().ok()?;
+    (1..=24).contains(&number).then_some(ChordKey::F(number))
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (enabled, label) in [
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.shift, "Shift+"),
+        ] {
+            if enabled {
+                f.write_str(label)?;
+            }
+        }
+        match self.key {
+            ChordKey::Char(ch) => write!(f, "{}", ch.to_ascii_uppercase()),
+            ChordKey::F(number) => write!(f, "F{number}"),
+            ChordKey::Tab => f.write_str("Tab"),
+        }
+    }
+}
+
+/// Raccourcis globaux : defauts, puis entrees `action = "Ctrl+X"` de `.usbide/keymap.toml`.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeyChord, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|(action, chord)| (*chord, *action))
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Fichier absent : defauts. Chaque entree invalide garde le defaut et produit un message.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match fs::read_to_string(path) {
+            Ok(raw) => Self::from_toml(&raw),
+            Err(_) => (Self::default(), Vec::new()),
+        }
+    }
+
+    /// Entrees `action = "Ctrl+X"` (voir `toml_entries`).
+    pub fn from_toml(raw: &str) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut errors = Vec::new();
+        for entry in toml_entries(raw) {
+            let (lineno, name, value) = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+            let Some(action) = Action::from_name(&name) else {
+                errors.push(format!("ligne {lineno}: action inconnue '{name}'"));
+                continue;
+            };
+            let TomlValue::Str(value) = value else {
+                errors.push(format!(
+                    "ligne {lineno}: valeur entre guillemets attendue pour {name}"
+                ));
+                continue;
+            };
+            match KeyChord::parse(&value) {
+                Ok(chord) => keymap.bind(chord, action),
+                Err(err) => errors.push(format!("ligne {lineno}: {name}: {err}")),
+            }
+        }
+        (keymap, errors)
+    }
+
+    /// Remplace les raccourcis de `action` ; le raccourci est retire de l'action qui l'avait.
+    pub fn bind(&mut self, chord: KeyChord, action: Action) {
+        self.bindings.retain(|_, bound| *bound != action);
+        self.bindings.insert(chord, action);
+    }
+
+    pub fn action_for(&self, chord: &KeyChord) -> Option<Action> {
+        self.bindings.get(chord).copied()
+    }
+
+    pub fn chords(&self) -> impl Iterator<Item = (&KeyChord, &Action)> {
+        self.bindings.iter()
+    }
+
+    /// Raccourcis actuels d'une action, tries (pour les libelles d'aide).
+    pub fn chords_for(&self, action: Action) -> Vec<KeyChord> {
+        let mut chords: Vec<KeyChord> = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(chord, _)| *chord)
+            .collect();
+        chords.sort_by_key(|chord| chord.to_string());
+        chords
+    }
+
+    /// "Ctrl+Shift+P, F1" ; vide si l'action n'a pas de raccourci.
+    pub fn chords_label(&self, action: Action) -> String {
+        self.chords_for(action)
+            .iter()
+            .map(KeyChord::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Actions de la palette pour la saisie (toutes, dans l'ordre du registre, si elle est vide).
+pub fn palette_matches(query: &str) -> Vec<Action> {
+    let mut scored: Vec<(i64, usize, Action)> = Action::ALL
+        .into_iter()
+        .enumerate()
+        .filter(|(_, action)| *action != Action::CommandPalette)
+        .filter_map(|(idx, action)| {
+            let score = fuzzy_score(query, action.label())
+                .into_iter()
+                .chain(fuzzy_score(query, action.name()))
+                .max()?;
+            Some((score, idx, action))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, _, action)| action).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn analyse_des_raccourcis() {
+        let chord = KeyChord::parse("Ctrl+Shift+P").unwrap();
+        assert_eq!(
+            chord,
+            KeyChord::new(true, false, false, ChordKey::Char('P'))
+        );
+        assert_eq!(chord.to_string(), "Ctrl+Shift+P");
+        assert_eq!(
+            KeyChord::parse("shift + f5").unwrap().to_string(),
+            "Shift+F5"
+        );
+        assert_eq!(
+            KeyChord::parse("ctrl+shift+tab").unwrap().to_string(),
+            "Ctrl+Shift+Tab"
+        );
+        assert!(KeyChord::parse("Ctrl+").is_err());
+        assert!(KeyChord::parse("Ctrl+Espace").is_err());
+        assert!(KeyChord::parse("F5+Ctrl").is_err());
+    }
+
+    #[test]
+    fn defauts_sans_fichier() {
+        let dir = TempDir::new().unwrap();
+        let (keymap, errors) = Keymap::load(&dir.path().join("keymap.toml"));
+        assert!(errors.is_empty());
+        for action in Action::ALL {
+            assert!(!keymap.chords_for(action).is_empty(), "{}", action.name());
+        }
+        let ctrl_s = KeyChord::parse("Ctrl+S").unwrap();
+        assert_eq!(keymap.action_for(&ctrl_s), Some(Action::Save));
+    }
+
+    #[test]
+    fn defauts_normalises_et_sans_doublon() {
+        let mut seen = HashMap::new();
+        for (action, chord) in DEFAULT_BINDINGS {
+            assert_eq!(KeyChord::parse(&chord.to_string()), Ok(chord));
+            assert_eq!(seen.insert(chord, action), None, "{chord}");
+        }
+    }
+
+    #[test]
+    fn ctrl_shift_a_un_repli_sans_shift() {
+        let keymap = Keymap::default();
+        for (action, chord) in DEFAULT_BINDINGS {
+            if chord.ctrl && chord.shift && matches!(chord.key, ChordKey::Char(_)) {
+                assert!(
+                    keymap.chords_for(action).iter().any(|chord| !chord.shift),
+                    "{}",
+                    action.name()
+                );
+            }
+        }
+        assert_eq!(
+            keymap.chords_label(Action::CodexLogout),
+            "Ctrl+Alt+K, Ctrl+Shift+K"
+        );
+    }
+
+    #[test]
+    fn fichier_remplace_et_signale_les_erreurs() {
+        let (keymap, errors) = Keymap::from_toml(
+            "# raccourcis\n[keys]\nsave = \"Alt+S\"\nrun = \"Ctrl+Hyper\"\ninconnue = \"F1\"\ncodex_login = F2\nquick_open = \"Ctrl+S\" # remplace save\n",
+        );
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(errors[0].contains("ligne 4"));
+        assert!(errors[1].contains("inconnue"));
+        let chord = |raw| KeyChord::parse(raw).unwrap();
+        assert_eq!(keymap.action_for(&chord("Alt+S")), Some(Action::Save));
+        assert_eq!(keymap.action_for(&chord("Ctrl+S")), Some(Action::QuickOpen));
+        assert_eq!(keymap.action_for(&chord("Ctrl+P")), None);
+        // Entree invalide : le defaut reste actif.
+        assert_eq!(keymap.action_for(&chord("F5")), Some(Action::Run));
+        assert_eq!(
+            keymap.action_for(&chord("Ctrl+K")),
+            Some(Action::CodexLogin)
+        );
+    }
+
+    #[test]
+    fn palette_filtre_et_affiche_les_raccourcis() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.chords_label(Action::CommandPalette),
+            "Ctrl+Shift+P, F1"
+        );
+        let all = palette_matches("");
+        assert_eq!(all.len(), Action::ALL.len() - 1);
+        assert_eq!(all[0], Action::Quit);
+        assert_eq!(palette_matches("pyinst")[0], Action::BuildExe);
+        assert_eq!(palette_matches("codex_login"), vec![Action::CodexLogin]);
+        assert!(palette_matches("zzzz").is_empty());
+    }
+}
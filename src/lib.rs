@@ -1,7 +1,20 @@
+pub mod ansi;
 pub mod app_core;
+pub mod cmd_history;
 pub mod codex;
+pub mod codex_history;
+pub mod diagnostics;
+pub mod diff;
 pub mod fs;
+pub mod git;
 pub mod gui;
+pub mod highlight;
+pub mod ignore;
+pub mod keymap;
 pub mod process;
+pub mod quick_open;
+pub mod session;
+pub mod settings;
 pub mod ui;
+pub mod watcher;
 pub mod workspace;
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::{Parser, ValueEnum};
+use ide_usb::app_core::startup_paths;
 
 #[derive(ValueEnum, Clone, Debug)]
 enum UiMode {
@@ -13,18 +14,30 @@ enum UiMode {
 #[derive(Parser)]
 #[command(name = "usbide", about = "Mini IDE terminal portable (Rust).")]
 struct Args {
-    /// Dossier racine du workspace (par defaut: repertoire courant).
-    #[arg(long, default_value = ".")]
-    root: PathBuf,
+    /// Fichier a ouvrir au demarrage (un dossier devient le root).
+    file: Option<PathBuf>,
+    /// Dossier racine du workspace (par defaut: dossier du fichier, sinon repertoire courant).
+    #[arg(long)]
+    root: Option<PathBuf>,
     /// Type d'interface: gui (fenetre) ou tui (terminal).
     #[arg(long, value_enum, default_value_t = UiMode::Gui)]
     ui: UiMode,
+    /// Surveille le workspace et rafraichit l'arborescence sur changement externe.
+    #[arg(long)]
+    watch: bool,
+    /// N'ecrit pas les incidents dans .usbide/bug.md (equivalent a USBIDE_BUG_LOG=0).
+    #[arg(long)]
+    no_bug_log: bool,
+    /// TUI : active la souris (clic pour le focus et l'arbre, molette dans les logs).
+    #[arg(long)]
+    mouse: bool,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
+    let (root, file) = startup_paths(args.root, args.file);
     match args.ui {
-        UiMode::Gui => ide_usb::gui::run(args.root),
+        UiMode::Gui => ide_usb::gui::run(root, file, args.watch, !args.no_bug_log),
         UiMode::Tui => {
             if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
                 eprintln!("Interface terminal (TUI) : aucun TTY detecte.");
@@ -36,7 +49,7 @@ fn main() -> Result<()> {
                 );
                 return Ok(());
             }
-            ide_usb::ui::run(args.root)
+            ide_usb::ui::run(root, file, args.watch, !args.no_bug_log, args.mouse)
         }
     }
 }
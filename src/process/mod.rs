@@ -1,9 +1,12 @@
 use std::collections::HashMap;
-use std::io::{self, BufRead};
-use std::path::Path;
-use std::process::{Command, Stdio};
-use std::sync::mpsc::{self, Receiver};
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use thiserror::Error;
 
@@ -24,20 +27,144 @@ pub struct ProcEvent {
 pub enum ProcessError {
     #[error("argv ne doit pas etre vide")]
     EmptyArgv,
-    #[error("echec lancement process: {0}")]
-    Spawn(#[from] io::Error),
+    #[error("echec lancement process '{program}' (cwd: {cwd}): {}", spawn_hint(.source))]
+    Spawn {
+        program: String,
+        cwd: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Oriente l'utilisateur : PATH/chemin pour un executable introuvable, droits sinon.
+fn spawn_hint(err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => {
+            format!("introuvable, verifier le PATH ou le dossier de travail ({err})")
+        }
+        io::ErrorKind::PermissionDenied => {
+            format!("permission refusee, verifier les droits d'execution ({err})")
+        }
+        _ => err.to_string(),
+    }
 }
 
 #[derive(Debug)]
 pub struct ProcHandle {
     pub rx: Receiver<ProcEvent>,
     join: thread::JoinHandle<()>,
+    child: Arc<Mutex<Child>>,
+    /// Entree du process (lancements interactifs seulement). None une fois l'EOF envoye.
+    stdin: Mutex<Option<ChildStdin>>,
+    /// Chef du groupe deja recolte et groupe vise une fois : l'identifiant peut etre
+    /// reutilise par un autre process, plus aucun signal n'est envoye.
+    group_done: AtomicBool,
 }
 
 impl ProcHandle {
     pub fn join(self) {
         let _ = self.join.join();
     }
+
+    /// Attend la fin des threads de lecture au plus `timeout` (un petit-enfant qui garde la
+    /// sortie ouverte ne doit pas bloquer l'appelant). Faux si les threads restent detaches.
+    pub fn join_timeout(self, timeout: Duration) -> bool {
+        self.join_until(Instant::now() + timeout)
+    }
+
+    /// Comme `join_timeout`, avec une echeance partagee par plusieurs process.
+    pub fn join_until(self, deadline: Instant) -> bool {
+        while !self.join.is_finished() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.join.join();
+        true
+    }
+
+    /// Vrai si le process a ete lance avec un stdin en pipe encore ouvert.
+    pub fn accepts_input(&self) -> bool {
+        self.stdin
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .is_some()
+    }
+
+    /// Ecrit une ligne (terminee par '\n') sur le stdin du process.
+    pub fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut stdin = self.stdin.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(pipe) = stdin.as_mut() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "stdin du process ferme",
+            ));
+        };
+        pipe.write_all(line.as_bytes())?;
+        pipe.write_all(b"\n")?;
+        pipe.flush()
+    }
+
+    /// Ferme le stdin : le process lit EOF (fin d'un REPL, d'un `cat`...).
+    pub fn close_stdin(&self) {
+        self.stdin
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+    }
+
+    /// Tue le process et ses descendants : arbre du PID sous Windows (un shim .cmd lance
+    /// node), groupe de process ailleurs (pipeline de `sh -lc`, `npm run`, serveur).
+    pub fn kill(&self) -> io::Result<()> {
+        let mut child = self.child.lock().unwrap_or_else(|err| err.into_inner());
+        // Meme fini, le process peut laisser des petits-enfants qui gardent les pipes ;
+        // lecteurs termines : plus aucun membre du groupe a viser.
+        let exited = child.try_wait()?.is_some();
+        if exited && (self.join.is_finished() || self.group_done.load(Ordering::SeqCst)) {
+            return Ok(());
+        }
+        let mut tree_kill = if cfg!(windows) {
+            let mut cmd = Command::new("taskkill");
+            cmd.args(["/PID", &child.id().to_string(), "/T", "/F"]);
+            cmd
+        } else {
+            // Lance avec `process_group(0)` : le groupe a l'identifiant du process.
+            let mut cmd = Command::new("kill");
+            cmd.args(["-KILL", "--", &format!("-{}", child.id())]);
+            cmd
+        };
+        let status = tree_kill
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if exited {
+            // Groupe tue ou deja vide (ESRCH) : termine dans les deux cas.
+            self.group_done.store(true, Ordering::SeqCst);
+            return Ok(());
+        }
+        if status.is_ok_and(|status| status.success()) {
+            return Ok(());
+        }
+        child.kill()
+    }
+}
+
+/// Intervalle de verification de fin du process (le lock doit rester libre pour `kill`).
+const WAIT_POLL: Duration = Duration::from_millis(50);
+
+fn wait_child(child: &Mutex<Child>) -> Option<std::process::ExitStatus> {
+    loop {
+        {
+            let mut child = child.lock().unwrap_or_else(|err| err.into_inner());
+            match child.try_wait() {
+                Ok(Some(status)) => return Some(status),
+                Ok(None) => {}
+                Err(_) => return None,
+            }
+        }
+        thread::sleep(WAIT_POLL);
+    }
 }
 
 pub trait ProcessRunner {
@@ -59,76 +186,137 @@ impl ProcessRunner for NativeProcessRunner {
         cwd: Option<&Path>,
         env: Option<&HashMap<String, String>>,
     ) -> Result<ProcHandle, ProcessError> {
-        if argv.is_empty() {
-            return Err(ProcessError::EmptyArgv);
-        }
+        spawn_native(argv, cwd, env, false)
+    }
+}
+
+impl NativeProcessRunner {
+    /// Comme `spawn`, avec un stdin en pipe pour `ProcHandle::write_line` (REPL, prompts pip...).
+    pub fn spawn_interactive(
+        &self,
+        argv: &[String],
+        cwd: Option<&Path>,
+        env: Option<&HashMap<String, String>>,
+    ) -> Result<ProcHandle, ProcessError> {
+        spawn_native(argv, cwd, env, true)
+    }
+}
 
-        let mut cmd = Command::new(&argv[0]);
-        if argv.len() > 1 {
-            cmd.args(&argv[1..]);
+fn spawn_native(
+    argv: &[String],
+    cwd: Option<&Path>,
+    env: Option<&HashMap<String, String>>,
+    interactive: bool,
+) -> Result<ProcHandle, ProcessError> {
+    if argv.is_empty() {
+        return Err(ProcessError::EmptyArgv);
+    }
+
+    let mut cmd = Command::new(&argv[0]);
+    if argv.len() > 1 {
+        cmd.args(&argv[1..]);
+    }
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    if let Some(env) = env {
+        cmd.env_clear();
+        for (key, value) in env {
+            cmd.env(key, value);
         }
-        if let Some(cwd) = cwd {
-            cmd.current_dir(cwd);
+    }
+    // Groupe de process propre : `kill` atteint aussi les petits-enfants.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    // Hors saisie interactive, pas de terminal : un process d'un autre groupe qui le lirait
+    // serait stoppe (SIGTTIN) ou volerait les touches du TUI.
+    cmd.stdin(if interactive {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    });
+
+    let mut child = cmd.spawn().map_err(|source| ProcessError::Spawn {
+        program: argv[0].clone(),
+        cwd: cwd.map_or_else(|| "(courant)".to_string(), |cwd| cwd.display().to_string()),
+        source,
+    })?;
+    let program = argv[0].clone();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let stdin = Mutex::new(child.stdin.take());
+    let (tx, rx) = mpsc::channel::<ProcEvent>();
+    let child = Arc::new(Mutex::new(child));
+    let waited = Arc::clone(&child);
+
+    let join = thread::spawn(move || {
+        let mut handles = Vec::new();
+
+        let spawn_reader = |stream: Box<dyn Read + Send>, tx: Sender<ProcEvent>| {
+            thread::spawn(move || forward_lines(stream, &tx))
+        };
+
+        if let Some(out) = stdout {
+            handles.push(spawn_reader(Box::new(out), tx.clone()));
         }
-        if let Some(env) = env {
-            cmd.env_clear();
-            for (key, value) in env {
-                cmd.env(key, value);
-            }
+        if let Some(err) = stderr {
+            handles.push(spawn_reader(Box::new(err), tx.clone()));
         }
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-
-        let mut child = cmd.spawn().map_err(ProcessError::Spawn)?;
-        let stdout = child.stdout.take();
-        let stderr = child.stderr.take();
-        let (tx, rx) = mpsc::channel::<ProcEvent>();
-
-        let join = thread::spawn(move || {
-            let mut handles = Vec::new();
-
-            let spawn_reader = |stream: Box<dyn io::Read + Send>, tx: mpsc::Sender<ProcEvent>| {
-                thread::spawn(move || {
-                    let mut reader = io::BufReader::new(stream);
-                    let mut line = String::new();
-                    loop {
-                        line.clear();
-                        match reader.read_line(&mut line) {
-                            Ok(0) => break,
-                            Ok(_) => {
-                                let text = line.trim_end_matches(['\n', '\r']).to_string();
-                                let _ = tx.send(ProcEvent {
-                                    kind: ProcEventKind::Line,
-                                    text,
-                                    returncode: None,
-                                });
-                            }
-                            Err(_) => break,
-                        }
-                    }
-                })
-            };
-
-            if let Some(out) = stdout {
-                handles.push(spawn_reader(Box::new(out), tx.clone()));
-            }
-            if let Some(err) = stderr {
-                handles.push(spawn_reader(Box::new(err), tx.clone()));
-            }
 
-            let status = child.wait().ok();
-            for handle in handles {
-                let _ = handle.join();
-            }
-            let code = status.and_then(|status| status.code());
-            let _ = tx.send(ProcEvent {
-                kind: ProcEventKind::Exit,
-                text: format!("exit {}", code.unwrap_or(-1)),
-                returncode: code,
-            });
+        let status = wait_child(&waited);
+        for handle in handles {
+            let _ = handle.join();
+        }
+        let code = status.and_then(|status| status.code());
+        let text = match code {
+            Some(code) => format!("exit {code}"),
+            // Tue par un signal ou attente impossible : pas de code, on nomme le programme.
+            None => format!("exit -1 ({program}: fin sans code de sortie)"),
+        };
+        let _ = tx.send(ProcEvent {
+            kind: ProcEventKind::Exit,
+            text,
+            returncode: code,
         });
+    });
+
+    Ok(ProcHandle {
+        rx,
+        join,
+        child,
+        stdin,
+        group_done: AtomicBool::new(false),
+    })
+}
 
-        Ok(ProcHandle { rx, join })
+/// Envoie chaque ligne de `stream` comme `ProcEvent::Line`. Decodage par ligne entiere :
+/// un caractere UTF-8 coupe entre deux lectures reste intact, un octet invalide devient U+FFFD.
+fn forward_lines(stream: impl Read, tx: &Sender<ProcEvent>) {
+    let mut reader = io::BufReader::new(stream);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let text = String::from_utf8_lossy(&line)
+                    .trim_end_matches(['\n', '\r'])
+                    .to_string();
+                let event = ProcEvent {
+                    kind: ProcEventKind::Line,
+                    text,
+                    returncode: None,
+                };
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        }
     }
 }
 
@@ -153,12 +341,104 @@ pub fn windows_cmd_argv(command: &str) -> Vec<String> {
     ]
 }
 
-/// Commande pour executer un script Python avec l'interpreteur courant (ou "python" par defaut).
-pub fn python_run_argv(script: &Path) -> Vec<String> {
-    let exe = std::env::var("USBIDE_PYTHON")
-        .or_else(|_| std::env::var("PYTHON"))
-        .unwrap_or_else(|_| "python".to_string());
-    vec![exe, path_for_cmd(script)]
+/// Interpreteur de base : USBIDE_PYTHON, `configured` (parametres) s'il est renseigne,
+/// PYTHON, le Python portable de `tools/python`, sinon "python".
+pub fn base_python(configured: &str, portable: Option<&Path>) -> String {
+    if let Ok(python) = std::env::var("USBIDE_PYTHON") {
+        return python;
+    }
+    if !configured.trim().is_empty() {
+        return configured.trim().to_string();
+    }
+    std::env::var("PYTHON").unwrap_or_else(|_| match portable {
+        Some(portable) => portable.display().to_string(),
+        None => "python".to_string(),
+    })
+}
+
+/// Interpreteur d'un venv (Scripts\python.exe sous Windows, bin/python ailleurs).
+pub fn venv_python(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts").join("python.exe")
+    } else {
+        venv.join("bin").join("python")
+    }
+}
+
+/// Commande pour executer un script Python : interpreteur du venv actif, sinon celui de base.
+pub fn python_run_argv(script: &Path, venv: Option<&Path>, python: &str) -> Vec<String> {
+    vec![python_exe(venv, python), path_for_cmd(script)]
+}
+
+/// Ouvre `dir` dans le gestionnaire de fichiers du systeme (explorer, Finder, xdg-open).
+pub fn file_manager_argv(dir: &Path) -> Vec<String> {
+    let opener = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    vec![opener.to_string(), path_for_cmd(dir)]
+}
+
+/// REPL Python (`-i`) sans banniere ni invites `>>>` (elles arriveraient sans fin de
+/// ligne sur stderr), sorties non bufferisees pour un affichage immediat.
+pub fn python_repl_argv(venv: Option<&Path>, python: &str) -> Vec<String> {
+    vec![
+        python_exe(venv, python),
+        "-q".to_string(),
+        "-u".to_string(),
+        "-i".to_string(),
+        "-c".to_string(),
+        "import sys; sys.ps1 = sys.ps2 = ''".to_string(),
+    ]
+}
+
+fn python_exe(venv: Option<&Path>, python: &str) -> String {
+    match venv {
+        Some(venv) => path_for_cmd(&venv_python(venv)),
+        None => python.to_string(),
+    }
+}
+
+/// Interpreteurs a sonder, sans doublon : venv actif s'il a un interpreteur, Python
+/// portable, puis l'interpreteur de base, `python3` et `python`.
+pub fn python_candidates(base: &str, venv: Option<&Path>, portable: Option<&Path>) -> Vec<String> {
+    let mut candidates = Vec::new();
+    let local = venv
+        .map(venv_python)
+        .filter(|exe| exe.is_file())
+        .into_iter()
+        .chain(portable.map(Path::to_path_buf))
+        .map(|exe| exe.display().to_string());
+    for exe in local.chain([base, "python3", "python"].map(str::to_string)) {
+        if !candidates.contains(&exe) {
+            candidates.push(exe);
+        }
+    }
+    candidates
+}
+
+pub fn python_version_argv(python: &str) -> Vec<String> {
+    vec![python.to_string(), "--version".to_string()]
+}
+
+/// Version lue dans la sortie de `python --version` ("Python 3.11.4" -> "3.11.4").
+pub fn parse_python_version(line: &str) -> Option<String> {
+    let version = line.trim().strip_prefix("Python ")?.trim();
+    let major = version.split('.').next()?;
+    (!major.is_empty() && major.chars().all(|c| c.is_ascii_digit())).then(|| version.to_string())
+}
+
+/// `python -m venv <dossier>` avec l'interpreteur de base.
+pub fn venv_create_argv(venv: &Path, python: &str) -> Vec<String> {
+    vec![
+        python.to_string(),
+        "-m".to_string(),
+        "venv".to_string(),
+        path_for_cmd(venv),
+    ]
 }
 
 fn path_for_cmd(path: &Path) -> String {
@@ -188,8 +468,25 @@ mod tests {
     #[test]
     fn commande_invalide_declenche_erreur_spawn() {
         let argv = vec!["commande-introuvable-usbide-12345".to_string()];
-        let res = stream_subprocess(&argv, None, None);
-        assert!(matches!(res, Err(ProcessError::Spawn(_))));
+        let err = stream_subprocess(&argv, Some(Path::new(".")), None).unwrap_err();
+        assert!(matches!(err, ProcessError::Spawn { .. }));
+        let message = err.to_string();
+        assert!(message.contains("'commande-introuvable-usbide-12345'"));
+        assert!(message.contains("cwd: ."));
+        assert!(message.contains("PATH"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn executable_sans_droit_signale_permission() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("outil.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let argv = vec![script.to_string_lossy().to_string()];
+        let err = stream_subprocess(&argv, None, None).unwrap_err();
+        assert!(err.to_string().contains("permission refusee"));
     }
 
     #[test]
@@ -222,4 +519,187 @@ mod tests {
 
         assert!(lines.iter().any(|line| line.contains("bonjour")));
     }
+
+    /// Lecteur qui rend les octets par petits morceaux (coupe les caracteres multi-octets).
+    struct Morceaux {
+        data: Vec<u8>,
+        pos: usize,
+        taille: usize,
+    }
+
+    impl Read for Morceaux {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let end = (self.pos + self.taille).min(self.data.len());
+            let n = (end - self.pos).min(buf.len());
+            buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn lignes_utf8_coupees_entre_lectures() {
+        let mut data = "Modèle prêt: déjà\r\nfin".as_bytes().to_vec();
+        data.extend_from_slice(b"\n\xff ok\n");
+        let stream = Morceaux {
+            data,
+            pos: 0,
+            taille: 3,
+        };
+        let (tx, rx) = mpsc::channel();
+        forward_lines(stream, &tx);
+        drop(tx);
+        let lines: Vec<String> = rx.iter().map(|event| event.text).collect();
+        assert_eq!(lines, vec!["Modèle prêt: déjà", "fin", "\u{FFFD} ok"]);
+    }
+
+    #[test]
+    fn kill_puis_join_avec_delai() {
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("ping -n 30 127.0.0.1 > nul")
+        } else {
+            vec!["sleep".to_string(), "30".to_string()]
+        };
+        let handle = stream_subprocess(&argv, None, None).unwrap();
+        handle.kill().unwrap();
+        assert!(handle.join_timeout(Duration::from_secs(10)));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn kill_atteint_les_petits_enfants() {
+        // Le `sleep` en arriere-plan garde stdout ouvert : sans kill du groupe, les
+        // threads de lecture resteraient bloques.
+        let argv = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "sleep 30 & sleep 30".to_string(),
+        ];
+        let handle = stream_subprocess(&argv, None, None).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        handle.kill().unwrap();
+        assert!(handle.join_timeout(Duration::from_secs(5)));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn kill_atteint_les_petits_enfants_apres_la_fin_du_parent() {
+        let argv = vec!["sh".to_string(), "-c".to_string(), "sleep 30 &".to_string()];
+        let handle = stream_subprocess(&argv, None, None).unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        handle.kill().unwrap();
+        assert!(handle.group_done.load(Ordering::SeqCst));
+        assert!(handle.join_timeout(Duration::from_secs(5)));
+
+        // Process et lecteurs termines : aucun signal vers un groupe peut-etre reutilise.
+        let argv = vec!["true".to_string()];
+        let handle = stream_subprocess(&argv, None, None).unwrap();
+        let start = Instant::now();
+        while !handle.join.is_finished() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        handle.kill().unwrap();
+        assert!(!handle.group_done.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn stdin_interactif_puis_eof() {
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("sort")
+        } else {
+            vec!["cat".to_string()]
+        };
+        let handle = NativeProcessRunner
+            .spawn_interactive(&argv, None, None)
+            .unwrap();
+        assert!(handle.accepts_input());
+        handle.write_line("bonjour stdin").unwrap();
+        handle.close_stdin();
+        assert!(!handle.accepts_input());
+        assert!(handle.write_line("trop tard").is_err());
+
+        let mut lines = Vec::new();
+        while let Ok(event) = handle.rx.recv_timeout(Duration::from_secs(10)) {
+            match event.kind {
+                ProcEventKind::Line => lines.push(event.text),
+                ProcEventKind::Exit => break,
+            }
+        }
+        handle.join();
+        assert_eq!(lines, vec!["bonjour stdin"]);
+    }
+
+    #[test]
+    fn kill_interrompt_le_process() {
+        let argv = if cfg!(windows) {
+            vec![
+                "cmd.exe".to_string(),
+                "/d".to_string(),
+                "/s".to_string(),
+                "/c".to_string(),
+                "ping -n 30 127.0.0.1 > nul".to_string(),
+            ]
+        } else {
+            vec!["sleep".to_string(), "30".to_string()]
+        };
+
+        let handle = stream_subprocess(&argv, None, None).unwrap();
+        handle.kill().unwrap();
+        let exit = handle
+            .rx
+            .recv_timeout(Duration::from_secs(10))
+            .expect("le process doit se terminer apres kill");
+        assert_eq!(exit.kind, ProcEventKind::Exit);
+        assert_ne!(exit.returncode, Some(0));
+        handle.join();
+    }
+
+    #[test]
+    fn python_du_venv_prioritaire() {
+        let venv = Path::new("/tmp/projet/.usbide/venv");
+        let argv = python_run_argv(Path::new("main.py"), Some(venv), "python");
+        assert_eq!(argv[0], venv_python(venv).to_string_lossy());
+        assert!(argv[0].starts_with(&*venv.to_string_lossy()));
+        assert_eq!(argv[1], "main.py");
+
+        let argv = file_manager_argv(Path::new("dist"));
+        assert_eq!(argv.len(), 2);
+        assert_eq!(argv[1], "dist");
+
+        let argv = python_repl_argv(None, "python3");
+        assert_eq!(argv[..4], ["python3", "-q", "-u", "-i"]);
+
+        let argv = venv_create_argv(venv, "python");
+        assert_eq!(argv[1..3], ["-m".to_string(), "venv".to_string()]);
+    }
+
+    #[test]
+    fn candidats_python_sans_doublon() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let venv = dir.path().join("venv");
+        assert_eq!(
+            python_candidates("python", Some(&venv), None),
+            ["python".to_string(), "python3".to_string()]
+        );
+
+        let portable = dir.path().join("tools").join("python").join("python.exe");
+        let candidates = python_candidates("/opt/py/bin/python3.12", Some(&venv), Some(&portable));
+        assert_eq!(candidates[0], portable.display().to_string());
+        assert_eq!(candidates[1], "/opt/py/bin/python3.12");
+        assert_eq!(candidates.len(), 4);
+    }
+
+    #[test]
+    fn version_python_lue() {
+        assert_eq!(
+            parse_python_version("Python 3.11.4\n").as_deref(),
+            Some("3.11.4")
+        );
+        assert_eq!(
+            parse_python_version("Python 2.7.18").as_deref(),
+            Some("2.7.18")
+        );
+        assert_eq!(parse_python_version("python: command not found"), None);
+        assert_eq!(parse_python_version("Python "), None);
+    }
 }
@@ -0,0 +1,156 @@
+use std::fs;
+
+use crate::ignore::IgnoreRules;
+use crate::workspace::WorkspacePaths;
+
+/// Nombre max de fichiers indexes (garde l'ouverture rapide sur un gros depot).
+pub const QUICK_OPEN_FILE_LIMIT: usize = 20_000;
+
+/// Nombre de resultats proposes.
+pub const QUICK_OPEN_RESULTS: usize = 20;
+
+/// Fichiers du workspace (chemins relatifs en '/'), gardes en cache jusqu'au prochain reload.
+#[derive(Debug, Clone, Default)]
+pub struct FileIndex {
+    files: Vec<String>,
+}
+
+impl FileIndex {
+    pub fn scan(workspace: &WorkspacePaths) -> Self {
+        let root = workspace.root_dir();
+        let rules = IgnoreRules::load(root);
+        let mut files = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                let Ok(rel) = path.strip_prefix(root) else {
+                    continue;
+                };
+                if workspace.is_internal_path(&path) || rules.is_ignored(rel, file_type.is_dir()) {
+                    continue;
+                }
+                if file_type.is_dir() {
+                    pending.push(path);
+                } else {
+                    files.push(rel.to_string_lossy().replace('\\', "/"));
+                    if files.len() >= QUICK_OPEN_FILE_LIMIT {
+                        pending.clear();
+                        break;
+                    }
+                }
+            }
+        }
+        files.sort();
+        Self { files }
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Meilleurs fichiers pour la requete (tous, par ordre alphabetique, si elle est vide).
+    pub fn matches(&self, query: &str, limit: usize) -> Vec<&str> {
+        let mut scored: Vec<(i64, &str)> = self
+            .files
+            .iter()
+            .filter_map(|file| fuzzy_score(query, file).map(|score| (score, file.as_str())))
+            .collect();
+        scored.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then(a.1.len().cmp(&b.1.len()))
+                .then(a.1.cmp(b.1))
+        });
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, file)| file)
+            .collect()
+    }
+}
+
+/// Score fuzzy : les lettres de la requete doivent apparaitre dans l'ordre (casse ignoree).
+/// Bonus aux lettres consecutives, aux debuts de mot et au nom de fichier ; None si pas de match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let name_start = candidate.rfind('/').map_or(0, |idx| idx + 1);
+    let mut score = 0i64;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+    let mut prev: Option<char> = None;
+    for (idx, ch) in candidate.char_indices() {
+        if next < query.len() && ch.to_lowercase().eq(std::iter::once(query[next])) {
+            score += 1;
+            if let Some(last) = last_match {
+                if last + prev.map_or(1, char::len_utf8) == idx {
+                    score += 5;
+                } else {
+                    score -= 1;
+                }
+            }
+            if prev.is_none_or(|prev| matches!(prev, '/' | '_' | '-' | '.' | ' ')) {
+                score += 8;
+            }
+            if idx >= name_start {
+                score += 3;
+            }
+            last_match = Some(idx);
+            next += 1;
+        }
+        prev = Some(ch);
+    }
+    (next == query.len()).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn score_fuzzy_dans_l_ordre() {
+        assert!(fuzzy_score("mpy", "src/main.py").is_some());
+        assert!(fuzzy_score("ypm", "src/main.py").is_none());
+        assert_eq!(fuzzy_score("", "a.py"), Some(0));
+        assert!(fuzzy_score("MAIN", "src/main.py").is_some());
+        // Lettres consecutives dans le nom de fichier > lettres eparpillees dans le chemin.
+        assert!(fuzzy_score("main", "src/main.py") > fuzzy_score("main", "m/a/i/n.py"));
+    }
+
+    #[test]
+    fn index_ignore_dossiers_internes_et_inutiles() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        for sub in ["src", ".git", "node_modules/lib", "cache", ".usbide"] {
+            fs::create_dir_all(root.join(sub)).unwrap();
+        }
+        fs::write(root.join("src").join("main.py"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+        fs::write(root.join(".git").join("HEAD"), "").unwrap();
+        fs::write(root.join("node_modules/lib").join("x.js"), "").unwrap();
+        fs::write(root.join("cache").join("c.txt"), "").unwrap();
+        fs::write(root.join(".usbide").join("session.json"), "").unwrap();
+
+        let index = FileIndex::scan(&WorkspacePaths::new(root.to_path_buf()));
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.matches("", 10), vec!["README.md", "src/main.py"]);
+        assert_eq!(index.matches("smp", 10), vec!["src/main.py"]);
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::{Map, Value, json};
+
+use crate::workspace::WorkspacePaths;
+
+/// Intervalle de sauvegarde periodique de la session.
+pub const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Etat restaure au demarrage : fichier ouvert, dossiers deplies, tailles de panneaux, theme
+/// et taille de police (GUI), retour a la ligne, affichage des fichiers caches.
+///
+/// Les chemins sont stockes relativement au root pour survivre a un changement de lettre
+/// de lecteur de la cle USB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub current_file: Option<PathBuf>,
+    pub expanded: Vec<PathBuf>,
+    pub panels: BTreeMap<String, f32>,
+    /// Retour a la ligne automatique (editeur et logs). Actif par defaut.
+    pub word_wrap: bool,
+    /// Affiche les dotfiles dans l'arborescence. Masques par defaut.
+    pub show_hidden: bool,
+    /// Theme clair du GUI (sombre par defaut).
+    pub light_theme: bool,
+    /// Taille de la police monospace du GUI (None : taille par defaut).
+    pub font_size: Option<f32>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            current_file: None,
+            expanded: Vec::new(),
+            panels: BTreeMap::new(),
+            word_wrap: true,
+            show_hidden: false,
+            light_theme: false,
+            font_size: None,
+        }
+    }
+}
+
+/// Chemin de la session sous le root ; `None` s'il est absolu ou remonte avec `..`
+/// (session editee a la main ou copiee depuis un autre poste).
+fn join_relative(root_dir: &Path, rel: &str) -> Option<PathBuf> {
+    let rel = Path::new(rel);
+    rel.components()
+        .all(|part| matches!(part, Component::Normal(_) | Component::CurDir))
+        .then(|| root_dir.join(rel))
+}
+
+impl Session {
+    /// Charge `.usbide/session.json`. Fichier absent ou JSON corrompu : session vide.
+    pub fn load(workspace: &WorkspacePaths) -> Self {
+        let Ok(raw) = fs::read_to_string(workspace.session_path()) else {
+            return Self::default();
+        };
+        let Ok(value) = serde_json::from_str::<Value>(&raw) else {
+            return Self::default();
+        };
+        Self::from_json(&value, workspace.root_dir())
+    }
+
+    pub fn save(&self, workspace: &WorkspacePaths) -> std::io::Result<()> {
+        let path = workspace.session_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let value = self.to_json(workspace.root_dir());
+        let text = serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string());
+        fs::write(path, text)
+    }
+
+    /// Fichier a rouvrir, seulement s'il existe encore.
+    pub fn existing_current_file(&self) -> Option<&Path> {
+        self.current_file.as_deref().filter(|path| path.is_file())
+    }
+
+    pub fn panel(&self, name: &str) -> Option<f32> {
+        self.panels.get(name).copied()
+    }
+
+    fn from_json(value: &Value, root_dir: &Path) -> Self {
+        let current_file = value
+            .get("current_file")
+            .and_then(Value::as_str)
+            .and_then(|rel| join_relative(root_dir, rel));
+        let expanded = value
+            .get("expanded")
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .filter_map(|rel| join_relative(root_dir, rel))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let panels = value
+            .get("panels")
+            .and_then(Value::as_object)
+            .map(|map| {
+                map.iter()
+                    .filter_map(|(key, size)| Some((key.clone(), size.as_f64()? as f32)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let word_wrap = value
+            .get("word_wrap")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        let show_hidden = value
+            .get("show_hidden")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let light_theme = value
+            .get("light_theme")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let font_size = value
+            .get("font_size")
+            .and_then(Value::as_f64)
+            .map(|size| size as f32);
+        Self {
+            current_file,
+            expanded,
+            panels,
+            word_wrap,
+            show_hidden,
+            light_theme,
+            font_size,
+        }
+    }
+
+    fn to_json(&self, root_dir: &Path) -> Value {
+        let relative = |path: &Path| -> Option<String> {
+            path.strip_prefix(root_dir)
+                .ok()
+                .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        };
+        let panels: Map<String, Value> = self
+            .panels
+            .iter()
+            .map(|(key, size)| (key.clone(), json!(size)))
+            .collect();
+        json!({
+            "current_file": self.current_file.as_deref().and_then(relative),
+            "expanded": self
+                .expanded
+                .iter()
+                .filter_map(|path| relative(path))
+                .collect::<Vec<_>>(),
+            "panels": panels,
+            "word_wrap": self.word_wrap,
+            "show_hidden": self.show_hidden,
+            "light_theme": self.light_theme,
+            "font_size": self.font_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn aller_retour_session() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let session = Session {
+            current_file: Some(dir.path().join("src").join("main.py")),
+            expanded: vec![dir.path().join("src")],
+            panels: BTreeMap::from([("files".to_string(), 300.0)]),
+            word_wrap: false,
+            show_hidden: true,
+            light_theme: true,
+            font_size: Some(16.0),
+        };
+        session.save(&workspace).unwrap();
+
+        let loaded = Session::load(&workspace);
+        assert_eq!(loaded, session);
+        assert!(dir.path().join(".usbide").join("session.json").is_file());
+    }
+
+    #[test]
+    fn json_corrompu_donne_session_vide() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        fs::create_dir_all(dir.path().join(".usbide")).unwrap();
+        fs::write(workspace.session_path(), "{pas du json").unwrap();
+
+        assert_eq!(Session::load(&workspace), Session::default());
+        assert!(Session::load(&workspace).word_wrap);
+    }
+
+    #[test]
+    fn fichier_supprime_non_restaure() {
+        let dir = TempDir::new().unwrap();
+        let session = Session {
+            current_file: Some(dir.path().join("absent.py")),
+            ..Session::default()
+        };
+        assert!(session.existing_current_file().is_none());
+    }
+
+    #[test]
+    fn chemins_hors_workspace_ignores() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        fs::create_dir_all(dir.path().join(".usbide")).unwrap();
+        let raw = json!({
+            "current_file": "../x",
+            "expanded": ["/etc/passwd", "src/../..", "./src"],
+        });
+        fs::write(workspace.session_path(), raw.to_string()).unwrap();
+
+        let loaded = Session::load(&workspace);
+        assert!(loaded.current_file.is_none());
+        assert_eq!(loaded.expanded, vec![dir.path().join("./src")]);
+
+        fs::write(
+            workspace.session_path(),
+            json!({"current_file": "/etc/passwd"}).to_string(),
+        )
+        .unwrap();
+        assert!(Session::load(&workspace).current_file.is_none());
+    }
+}
@@ -0,0 +1,674 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::codex::{
+    CodexApprovalPolicy, CodexSandboxMode, CodexView, Formatter, codex_approval_policy_from_env,
+    codex_model_from_env, codex_sandbox_mode_from_env, codex_view_from_env, formatter_from_env,
+    parse_codex_approval_policy, parse_codex_sandbox_mode, parse_codex_view, parse_formatter,
+};
+
+/// Valeur d'une entree `cle = valeur` : chaine entre guillemets, booleen ou entier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TomlValue {
+    Str(String),
+    Bool(bool),
+    Int(i64),
+}
+
+/// Sous-ensemble TOML des fichiers `.usbide/*.toml` : `cle = "texte"`, `cle = true` ou `cle = 30`,
+/// commentaires `#` et en-tetes `[section]` ignores. Renvoie (ligne, cle, valeur) ou l'erreur.
+pub fn toml_entries(raw: &str) -> Vec<Result<(usize, String, TomlValue), String>> {
+    raw.lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+                return None;
+            }
+            let lineno = idx + 1;
+            let Some((key, value)) = line.split_once('=') else {
+                return Some(Err(format!("ligne {lineno}: 'cle = valeur' attendu")));
+            };
+            let key = key.trim().trim_matches('"').to_string();
+            Some(
+                parse_toml_value(value.trim())
+                    .map(|value| (lineno, key.clone(), value))
+                    .map_err(|err| format!("ligne {lineno}: {key}: {err}")),
+            )
+        })
+        .collect()
+}
+
+fn parse_toml_value(value: &str) -> Result<TomlValue, String> {
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut text = String::new();
+        let mut chars = rest.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' => {
+                    let tail = chars.as_str().trim();
+                    if !tail.is_empty() && !tail.starts_with('#') {
+                        return Err(format!("texte inattendu apres la valeur: '{tail}'"));
+                    }
+                    return Ok(TomlValue::Str(text));
+                }
+                '\\' => match chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(other) => text.push(other),
+                    None => break,
+                },
+                other => text.push(other),
+            }
+        }
+        return Err("guillemet fermant manquant".to_string());
+    }
+    match value.split('#').next().unwrap_or_default().trim() {
+        "true" => Ok(TomlValue::Bool(true)),
+        "false" => Ok(TomlValue::Bool(false)),
+        other => other.parse().map(TomlValue::Int).map_err(|_| {
+            format!("valeur entre guillemets, true/false ou entier attendue: '{other}'")
+        }),
+    }
+}
+
+fn toml_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{escaped}\"")
+}
+
+/// Preferences du workspace (`.usbide/settings.toml`). Les variables d'environnement
+/// correspondantes restent prioritaires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Outils installes par "outils dev" (USBIDE_DEV_TOOLS).
+    pub dev_tools: String,
+    /// Package npm de Codex (USBIDE_CODEX_NPM_PACKAGE).
+    pub codex_package: String,
+    /// Vide : registre npm. Sinon `.tgz` ou dossier local installe a la place du package,
+    /// relatif a la racine du workspace (USBIDE_CODEX_NPM_SOURCE).
+    pub codex_npm_source: String,
+    /// Vide : modele par defaut de Codex (USBIDE_CODEX_MODEL).
+    pub codex_model: String,
+    pub codex_sandbox: CodexSandboxMode,
+    pub codex_approval: CodexApprovalPolicy,
+    /// Vide : PYTHON, `tools/python` puis "python" (USBIDE_PYTHON).
+    pub python: String,
+    pub codex_allow_api_key: bool,
+    pub codex_allow_custom_base: bool,
+    /// Copie de secours des onglets modifies toutes les N secondes, 0 : desactivee
+    /// (USBIDE_AUTOSAVE_SECS).
+    pub autosave_secs: u64,
+    /// Formateur du fichier courant (USBIDE_FORMATTER).
+    pub formatter: Formatter,
+    /// Largeur d'une tabulation dans l'editeur (USBIDE_TAB_WIDTH).
+    pub tab_width: u64,
+    /// Tab insere des espaces (true) ou une tabulation (false) pour tous les fichiers ;
+    /// None ("auto") : espaces pour les `.py` (PEP 8), tabulation ailleurs
+    /// (USBIDE_INSERT_SPACES).
+    pub insert_spaces: Option<bool>,
+    /// Hors-ligne strict : pip uniquement depuis le wheelhouse, npm depuis son cache
+    /// (USBIDE_OFFLINE).
+    pub offline: bool,
+    /// Lignes de log identiques consecutives regroupees en "texte (xN)"
+    /// (USBIDE_LOG_COLLAPSE).
+    pub collapse_log_repeats: bool,
+    /// Vide : `cache/` du workspace. Sinon dossier du workspace (relatif a la racine) qui
+    /// accueille les caches pip, npm et pycache (USBIDE_CACHE_DIR) ; hors du root : refuse.
+    pub cache_dir: String,
+    /// Confirmation avant une commande shell qui vise un chemin hors du workspace ou
+    /// ressemble a `rm -rf /` (USBIDE_SHELL_GUARD).
+    pub shell_guard: bool,
+    /// Extensions dont la sauvegarde retire les espaces de fin de ligne et garde un seul
+    /// saut de ligne final ; "*" : tous les fichiers, vide : jamais (USBIDE_TRIM_ON_SAVE).
+    pub trim_on_save: String,
+    /// Niveau de detail de la sortie Codex : compact, normal ou raw (USBIDE_CODEX_VIEW).
+    pub codex_view: CodexView,
+    /// Proxy des process lances (Codex, outils, shell) quand l'environnement n'en
+    /// definit pas (HTTP_PROXY).
+    pub http_proxy: String,
+    /// Idem pour HTTPS_PROXY.
+    pub https_proxy: String,
+    /// Hotes exclus du proxy (NO_PROXY).
+    pub no_proxy: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            dev_tools: "ruff black mypy pytest".to_string(),
+            codex_package: "@openai/codex".to_string(),
+            codex_npm_source: String::new(),
+            codex_model: String::new(),
+            codex_sandbox: CodexSandboxMode::WorkspaceWrite,
+            codex_approval: CodexApprovalPolicy::Never,
+            python: String::new(),
+            codex_allow_api_key: false,
+            codex_allow_custom_base: false,
+            autosave_secs: 30,
+            formatter: Formatter::Black,
+            tab_width: 4,
+            insert_spaces: None,
+            offline: false,
+            collapse_log_repeats: true,
+            cache_dir: String::new(),
+            shell_guard: true,
+            trim_on_save: "py pyw".to_string(),
+            codex_view: CodexView::Normal,
+            http_proxy: String::new(),
+            https_proxy: String::new(),
+            no_proxy: String::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Fichier absent : defauts. Chaque entree invalide garde le defaut et produit un message.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match fs::read_to_string(path) {
+            Ok(raw) => Self::from_toml(&raw),
+            Err(_) => (Self::default(), Vec::new()),
+        }
+    }
+
+    pub fn from_toml(raw: &str) -> (Self, Vec<String>) {
+        let mut settings = Self::default();
+        let mut errors = Vec::new();
+        for entry in toml_entries(raw) {
+            let (lineno, key, value) = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+            if let Err(err) = settings.apply(&key, value) {
+                errors.push(format!("ligne {lineno}: {key}: {err}"));
+            }
+        }
+        (settings, errors)
+    }
+
+    fn apply(&mut self, key: &str, value: TomlValue) -> Result<(), String> {
+        match (key, value) {
+            ("dev_tools", TomlValue::Str(value)) => self.dev_tools = value,
+            ("codex_package", TomlValue::Str(value)) => self.codex_package = value,
+            ("codex_npm_source", TomlValue::Str(value)) => {
+                self.codex_npm_source = value.trim().to_string();
+            }
+            ("codex_model", TomlValue::Str(value)) => self.codex_model = value.trim().to_string(),
+            ("cache_dir", TomlValue::Str(value)) => self.cache_dir = value.trim().to_string(),
+            ("trim_on_save", TomlValue::Str(value)) => self.trim_on_save = value,
+            ("http_proxy", TomlValue::Str(value)) => self.http_proxy = value.trim().to_string(),
+            ("https_proxy", TomlValue::Str(value)) => self.https_proxy = value.trim().to_string(),
+            ("no_proxy", TomlValue::Str(value)) => self.no_proxy = value.trim().to_string(),
+            ("python", TomlValue::Str(value)) => self.python = value.trim().to_string(),
+            ("codex_sandbox", TomlValue::Str(value)) => {
+                self.codex_sandbox =
+                    parse_codex_sandbox_mode(&value).ok_or(format!("mode inconnu '{value}'"))?;
+            }
+            ("codex_approval", TomlValue::Str(value)) => {
+                self.codex_approval = parse_codex_approval_policy(&value)
+                    .ok_or(format!("politique inconnue '{value}'"))?;
+            }
+            ("codex_allow_api_key", TomlValue::Bool(value)) => self.codex_allow_api_key = value,
+            ("codex_allow_custom_base", TomlValue::Bool(value)) => {
+                self.codex_allow_custom_base = value;
+            }
+            ("formatter", TomlValue::Str(value)) => {
+                self.formatter =
+                    parse_formatter(&value).ok_or(format!("formateur inconnu '{value}'"))?;
+            }
+            ("autosave_secs", TomlValue::Int(value)) => {
+                self.autosave_secs =
+                    u64::try_from(value).map_err(|_| "entier positif attendu".to_string())?;
+            }
+            ("tab_width", TomlValue::Int(value)) => {
+                self.tab_width = u64::try_from(value)
+                    .ok()
+                    .filter(|width| (1..=MAX_TAB_WIDTH).contains(width))
+                    .ok_or(format!("entier entre 1 et {MAX_TAB_WIDTH} attendu"))?;
+            }
+            ("insert_spaces", TomlValue::Bool(value)) => self.insert_spaces = Some(value),
+            ("insert_spaces", TomlValue::Str(value)) => {
+                self.insert_spaces = parse_insert_spaces(&value)
+                    .ok_or(format!("true, false ou \"auto\" attendu: '{value}'"))?;
+            }
+            ("offline", TomlValue::Bool(value)) => self.offline = value,
+            ("collapse_log_repeats", TomlValue::Bool(value)) => self.collapse_log_repeats = value,
+            ("shell_guard", TomlValue::Bool(value)) => self.shell_guard = value,
+            ("codex_view", TomlValue::Str(value)) => {
+                self.codex_view =
+                    parse_codex_view(&value).ok_or(format!("niveau inconnu '{value}'"))?;
+            }
+            (
+                "dev_tools"
+                | "codex_package"
+                | "codex_npm_source"
+                | "cache_dir"
+                | "trim_on_save"
+                | "codex_model"
+                | "python"
+                | "codex_sandbox"
+                | "codex_approval"
+                | "codex_allow_api_key"
+                | "codex_allow_custom_base"
+                | "autosave_secs"
+                | "formatter"
+                | "tab_width"
+                | "insert_spaces"
+                | "offline"
+                | "collapse_log_repeats"
+                | "shell_guard"
+                | "codex_view"
+                | "http_proxy"
+                | "https_proxy"
+                | "no_proxy",
+                _,
+            ) => return Err("type de valeur incorrect".to_string()),
+            _ => return Err("cle inconnue".to_string()),
+        }
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut out = String::from(
+            "# Preferences USB-IDE (editables aussi depuis la fenetre Parametres du GUI).\n\
+             # Les variables d'environnement indiquees restent prioritaires.\n\n",
+        );
+        let entries = [
+            (
+                "USBIDE_DEV_TOOLS",
+                "dev_tools",
+                toml_string(&self.dev_tools),
+            ),
+            (
+                "USBIDE_CODEX_NPM_PACKAGE",
+                "codex_package",
+                toml_string(&self.codex_package),
+            ),
+            (
+                "USBIDE_CODEX_NPM_SOURCE",
+                "codex_npm_source",
+                toml_string(&self.codex_npm_source),
+            ),
+            (
+                "USBIDE_CACHE_DIR",
+                "cache_dir",
+                toml_string(&self.cache_dir),
+            ),
+            (
+                "USBIDE_CODEX_MODEL",
+                "codex_model",
+                toml_string(&self.codex_model),
+            ),
+            (
+                "USBIDE_CODEX_SANDBOX",
+                "codex_sandbox",
+                toml_string(self.codex_sandbox.as_str()),
+            ),
+            (
+                "USBIDE_CODEX_APPROVAL",
+                "codex_approval",
+                toml_string(self.codex_approval.as_str()),
+            ),
+            ("USBIDE_PYTHON", "python", toml_string(&self.python)),
+            (
+                "USBIDE_CODEX_ALLOW_API_KEY",
+                "codex_allow_api_key",
+                self.codex_allow_api_key.to_string(),
+            ),
+            (
+                "USBIDE_CODEX_ALLOW_CUSTOM_BASE",
+                "codex_allow_custom_base",
+                self.codex_allow_custom_base.to_string(),
+            ),
+            (
+                "USBIDE_AUTOSAVE_SECS",
+                "autosave_secs",
+                self.autosave_secs.to_string(),
+            ),
+            (
+                "USBIDE_FORMATTER",
+                "formatter",
+                toml_string(self.formatter.as_str()),
+            ),
+            ("USBIDE_TAB_WIDTH", "tab_width", self.tab_width.to_string()),
+            (
+                "USBIDE_INSERT_SPACES",
+                "insert_spaces",
+                match self.insert_spaces {
+                    Some(value) => value.to_string(),
+                    None => toml_string("auto"),
+                },
+            ),
+            ("USBIDE_OFFLINE", "offline", self.offline.to_string()),
+            (
+                "USBIDE_LOG_COLLAPSE",
+                "collapse_log_repeats",
+                self.collapse_log_repeats.to_string(),
+            ),
+            (
+                "USBIDE_TRIM_ON_SAVE",
+                "trim_on_save",
+                toml_string(&self.trim_on_save),
+            ),
+            (
+                "USBIDE_SHELL_GUARD",
+                "shell_guard",
+                self.shell_guard.to_string(),
+            ),
+            (
+                "USBIDE_CODEX_VIEW",
+                "codex_view",
+                toml_string(self.codex_view.as_str()),
+            ),
+            ("HTTP_PROXY", "http_proxy", toml_string(&self.http_proxy)),
+            ("HTTPS_PROXY", "https_proxy", toml_string(&self.https_proxy)),
+            ("NO_PROXY", "no_proxy", toml_string(&self.no_proxy)),
+        ];
+        for (env, key, value) in entries {
+            out.push_str(&format!("# {env}\n{key} = {value}\n"));
+        }
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+
+    pub fn dev_tools(&self) -> String {
+        std::env::var("USBIDE_DEV_TOOLS").unwrap_or_else(|_| self.dev_tools.clone())
+    }
+
+    pub fn codex_package(&self) -> String {
+        std::env::var("USBIDE_CODEX_NPM_PACKAGE").unwrap_or_else(|_| self.codex_package.clone())
+    }
+
+    /// Source locale de Codex (vide si non definie).
+    pub fn codex_npm_source(&self) -> String {
+        std::env::var("USBIDE_CODEX_NPM_SOURCE")
+            .map(|value| value.trim().to_string())
+            .unwrap_or_else(|_| self.codex_npm_source.clone())
+    }
+
+    /// Dossier des caches pip/npm/pycache (vide : `cache/` du workspace).
+    pub fn cache_dir(&self) -> String {
+        std::env::var("USBIDE_CACHE_DIR")
+            .map(|value| value.trim().to_string())
+            .unwrap_or_else(|_| self.cache_dir.clone())
+    }
+
+    pub fn codex_model(&self) -> String {
+        codex_model_from_env(&self.codex_model)
+    }
+
+    pub fn codex_sandbox(&self) -> CodexSandboxMode {
+        codex_sandbox_mode_from_env(self.codex_sandbox)
+    }
+
+    pub fn codex_approval(&self) -> CodexApprovalPolicy {
+        codex_approval_policy_from_env(self.codex_approval)
+    }
+
+    /// Interpreteur configure (vide si non defini).
+    pub fn python(&self) -> &str {
+        &self.python
+    }
+
+    pub fn codex_allow_api_key(&self) -> bool {
+        env_flag("USBIDE_CODEX_ALLOW_API_KEY").unwrap_or(self.codex_allow_api_key)
+    }
+
+    pub fn codex_allow_custom_base(&self) -> bool {
+        env_flag("USBIDE_CODEX_ALLOW_CUSTOM_BASE").unwrap_or(self.codex_allow_custom_base)
+    }
+
+    pub fn formatter(&self) -> Formatter {
+        formatter_from_env(self.formatter)
+    }
+
+    pub fn offline(&self) -> bool {
+        env_flag("USBIDE_OFFLINE").unwrap_or(self.offline)
+    }
+
+    pub fn collapse_log_repeats(&self) -> bool {
+        env_flag("USBIDE_LOG_COLLAPSE").unwrap_or(self.collapse_log_repeats)
+    }
+
+    pub fn shell_guard(&self) -> bool {
+        env_flag("USBIDE_SHELL_GUARD").unwrap_or(self.shell_guard)
+    }
+
+    pub fn codex_view(&self) -> CodexView {
+        codex_view_from_env(self.codex_view)
+    }
+
+    /// Variables proxy renseignees dans les parametres (vides ignorees).
+    pub fn proxy_vars(&self) -> Vec<(&'static str, String)> {
+        [
+            ("HTTP_PROXY", &self.http_proxy),
+            ("HTTPS_PROXY", &self.https_proxy),
+            ("NO_PROXY", &self.no_proxy),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .map(|(key, value)| (key, value.trim().to_string()))
+        .collect()
+    }
+
+    /// Intervalle de la copie de secours (`None` si desactivee).
+    pub fn autosave_interval(&self) -> Option<Duration> {
+        let secs = std::env::var("USBIDE_AUTOSAVE_SECS")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(self.autosave_secs);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
+    /// Largeur d'affichage d'une tabulation (1 a `MAX_TAB_WIDTH`).
+    pub fn tab_width(&self) -> usize {
+        let width = std::env::var("USBIDE_TAB_WIDTH")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(self.tab_width);
+        width.clamp(1, MAX_TAB_WIDTH) as usize
+    }
+
+    /// Vrai si la sauvegarde de `path` normalise les espaces de fin de ligne.
+    pub fn trim_on_save(&self, path: &Path) -> bool {
+        let list =
+            std::env::var("USBIDE_TRIM_ON_SAVE").unwrap_or_else(|_| self.trim_on_save.clone());
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        list.split([' ', ','])
+            .map(|item| item.trim().trim_start_matches('.').to_lowercase())
+            .any(|item| item == "*" || ext.as_deref() == Some(item.as_str()))
+    }
+
+    /// Choix espaces/tabulation : USBIDE_INSERT_SPACES puis `insert_spaces` ; None : auto.
+    pub fn insert_spaces(&self) -> Option<bool> {
+        self.insert_spaces_with(std::env::var("USBIDE_INSERT_SPACES").ok().as_deref())
+    }
+
+    /// Valeur d'environnement invalide ignoree : celle du fichier s'applique.
+    fn insert_spaces_with(&self, raw: Option<&str>) -> Option<bool> {
+        raw.and_then(parse_insert_spaces)
+            .unwrap_or(self.insert_spaces)
+    }
+
+    /// Texte insere par Tab : des espaces jusqu'au prochain taquet (colonne `col`) ou une
+    /// vraie tabulation ; en auto, espaces pour Python seulement.
+    pub fn indent_text(&self, path: Option<&Path>, col: usize) -> String {
+        let python = path
+            .and_then(|path| path.extension())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("py") || ext.eq_ignore_ascii_case("pyw"));
+        if self.insert_spaces().unwrap_or(python) {
+            let width = self.tab_width();
+            " ".repeat(width - col % width)
+        } else {
+            "\t".to_string()
+        }
+    }
+}
+
+/// Largeur de tabulation maximale acceptee.
+pub const MAX_TAB_WIDTH: u64 = 16;
+
+/// "auto" (ou vide) -> None, booleen usuel -> Some ; autre texte : invalide.
+fn parse_insert_spaces(raw: &str) -> Option<Option<bool>> {
+    match raw.trim().to_lowercase().as_str() {
+        "" | "auto" => Some(None),
+        "1" | "true" | "yes" | "on" => Some(Some(true)),
+        "0" | "false" | "no" | "off" => Some(Some(false)),
+        _ => None,
+    }
+}
+
+fn env_flag(name: &str) -> Option<bool> {
+    let value = std::env::var(name).ok()?;
+    Some(matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    ))
+}
+
+/// Vrai si la variable d'environnement remplace la valeur du fichier (affiche dans le GUI).
+pub fn env_overrides(name: &str) -> bool {
+    std::env::var_os(name).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn entrees_toml_simples() {
+        let entries = toml_entries(
+            "# commentaire\n[section]\na = \"x # pas un commentaire\" # commentaire\nb = true\nc = 3\nd = \"non ferme\ne = trois\n",
+        );
+        assert_eq!(
+            entries[0],
+            Ok((
+                3,
+                "a".to_string(),
+                TomlValue::Str("x # pas un commentaire".into())
+            ))
+        );
+        assert_eq!(entries[1], Ok((4, "b".to_string(), TomlValue::Bool(true))));
+        assert_eq!(entries[2], Ok((5, "c".to_string(), TomlValue::Int(3))));
+        assert!(entries[3].as_ref().unwrap_err().contains("guillemet"));
+        assert!(entries[4].as_ref().unwrap_err().contains("ligne 7"));
+    }
+
+    #[test]
+    fn aller_retour_fichier() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".usbide").join("settings.toml");
+        let settings = Settings {
+            codex_model: "gpt-5".to_string(),
+            codex_sandbox: CodexSandboxMode::ReadOnly,
+            python: "C:\\Python312\\python.exe".to_string(),
+            codex_npm_source: "tools/codex/openai-codex-0.1.tgz".to_string(),
+            cache_dir: "caches usb".to_string(),
+            codex_allow_api_key: true,
+            autosave_secs: 0,
+            formatter: Formatter::Ruff,
+            tab_width: 2,
+            insert_spaces: Some(true),
+            offline: true,
+            collapse_log_repeats: false,
+            shell_guard: false,
+            trim_on_save: "py, md".to_string(),
+            codex_view: CodexView::Raw,
+            https_proxy: "http://proxy.corp:3128".to_string(),
+            no_proxy: "localhost,.corp".to_string(),
+            ..Settings::default()
+        };
+        settings.save(&path).unwrap();
+        let (loaded, errors) = Settings::load(&path);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(loaded, settings);
+        assert_eq!(loaded.autosave_interval(), None);
+    }
+
+    #[test]
+    fn entrees_invalides_gardent_le_defaut() {
+        let (settings, errors) = Settings::from_toml(
+            "codex_sandbox = \"partout\"\ncodex_allow_api_key = \"oui\"\ninconnue = true\ndev_tools = \"ruff\"\nautosave_secs = -5\ntab_width = 0\n",
+        );
+        assert_eq!(errors.len(), 5, "{errors:?}");
+        assert_eq!(settings.autosave_secs, 30);
+        assert_eq!(settings.tab_width, 4);
+        assert_eq!(settings.codex_sandbox, CodexSandboxMode::WorkspaceWrite);
+        assert!(!settings.codex_allow_api_key);
+        assert_eq!(settings.dev_tools, "ruff");
+    }
+
+    #[test]
+    fn tabulation_en_espaces_pour_python() {
+        let settings = Settings::default();
+        assert_eq!(settings.indent_text(Some(Path::new("a.py")), 0), "    ");
+        assert_eq!(settings.indent_text(Some(Path::new("a.py")), 5), "   ");
+        assert_eq!(settings.indent_text(Some(Path::new("Makefile")), 0), "\t");
+        assert_eq!(settings.indent_text(None, 0), "\t");
+        let settings = Settings {
+            tab_width: 2,
+            insert_spaces: Some(true),
+            ..Settings::default()
+        };
+        assert_eq!(settings.indent_text(Some(Path::new("notes.txt")), 3), " ");
+        // Projet indente en tabulations : le choix explicite vaut aussi pour Python.
+        let settings = Settings {
+            insert_spaces: Some(false),
+            ..Settings::default()
+        };
+        assert_eq!(settings.indent_text(Some(Path::new("a.py")), 0), "\t");
+
+        let (settings, errors) = Settings::from_toml("insert_spaces = \"auto\"\n");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(settings.insert_spaces, None);
+        assert_eq!(Settings::from_toml(&settings.to_toml()).0, settings);
+        let (_, errors) = Settings::from_toml("insert_spaces = \"parfois\"\n");
+        assert_eq!(errors.len(), 1);
+
+        // USBIDE_INSERT_SPACES : "auto" l'emporte, une faute de frappe est ignoree.
+        let settings = Settings {
+            insert_spaces: Some(true),
+            ..Settings::default()
+        };
+        assert_eq!(settings.insert_spaces_with(Some("off")), Some(false));
+        assert_eq!(settings.insert_spaces_with(Some("auto")), None);
+        assert_eq!(settings.insert_spaces_with(Some("2")), Some(true));
+        assert_eq!(settings.insert_spaces_with(Some("ture")), Some(true));
+        assert_eq!(settings.insert_spaces_with(None), Some(true));
+    }
+
+    #[test]
+    fn normalisation_a_la_sauvegarde_par_extension() {
+        let settings = Settings::default();
+        assert!(settings.trim_on_save(Path::new("src/main.PY")));
+        assert!(!settings.trim_on_save(Path::new("README.md")));
+        assert!(!settings.trim_on_save(Path::new("Makefile")));
+        let settings = Settings {
+            trim_on_save: ".md, *".to_string(),
+            ..Settings::default()
+        };
+        assert!(settings.trim_on_save(Path::new("Makefile")));
+        let settings = Settings {
+            trim_on_save: String::new(),
+            ..Settings::default()
+        };
+        assert!(!settings.trim_on_save(Path::new("a.py")));
+    }
+}
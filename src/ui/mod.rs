@@ -1,58 +1,192 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use crossterm::ExecutableCommand;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    MouseButton, MouseEvent, MouseEventKind,
+};
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
-use tui_textarea::{Input, TextArea};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use tui_textarea::{CursorMove, Input, TextArea};
 
+use crate::ansi::{AnsiColor, AnsiStyle, has_fg, parse_ansi};
 use crate::app_core::{
-    APP_NAME, AppCore, LOG_LIMIT, LogTarget, OpenFile, ProcessKind, RunningProcess,
-    codex_approval_label, codex_exec_extra_args, codex_sandbox_label, next_codex_approval_policy,
-    next_codex_sandbox_mode,
+    APP_NAME, AppCore, CODEX_DANGER_WARNING, CodexPatch, CodexRetry, DISK_CHECK_INTERVAL,
+    DevToolEntry, DocumentStats, EditorTab, EditorTabs, EnvOrigin, EnvProfile, LOG_LIMIT,
+    LogFilter, LogKind, LogSearch, LogTarget, OpenFile, PROC_TIMEOUT_EXIT_CODE, ProcessKind,
+    PythonInstall, RunningProcess, SetupMissing, append_log_lines, cache_usage_label,
+    codex_approval_label, codex_exec_extra_args, codex_label_kind, codex_sandbox_label,
+    codex_status_wait_label, dev_tools_to_install, editor_position_label, format_size,
+    is_streamed_duplicate, log_export_lines, next_codex_approval_policy, next_codex_sandbox_mode,
+    parse_cd_command, parse_env_assignment, parse_goto_line, repeat_suffix, resolve_shell_cwd,
+    shell_command_risks, shell_cwd_label, spinner_frame, text_bytes, validate_startup_file,
 };
+use crate::cmd_history::CommandHistory;
 use crate::codex::{
-    CodexApprovalPolicy, CodexError, CodexSandboxMode, DisplayKind, codex_approval_policy_from_env,
-    codex_cli_available, codex_entrypoint_js, codex_exec_argv, codex_exec_help_argv,
-    codex_hint_for_status, codex_install_argv, codex_install_prefix, codex_login_argv,
-    codex_sandbox_mode_from_env, codex_status_argv, extract_display_items, extract_status_code,
-    node_executable, parse_tool_list, pip_install_argv, pyinstaller_available,
-    pyinstaller_build_argv, pyinstaller_install_argv, resolve_in_path, tools_install_prefix,
-    translate_codex_line,
+    CodexApprovalPolicy, CodexError, CodexSandboxMode, CodexView, DisplayKind, Linter, NodeTool,
+    PytestSummary, codex_cli_available, codex_confirm_danger_from_env, codex_context_prompt,
+    codex_entrypoint_js, codex_exec_argv, codex_exec_help_argv, codex_hint_for_status,
+    codex_install_argv, codex_install_prefix, codex_login_argv, codex_logout_argv,
+    codex_prompt_display, codex_status_argv, extract_display_items, extract_status_code,
+    format_argv, lint_argv, node_executable, parse_install_progress, parse_pytest_summary,
+    pip_install_argv, pip_install_requirements_argv, pyinstaller_available, pyinstaller_build_argv,
+    pyinstaller_install_argv, pyinstaller_output_path, pytest_args_from_env, pytest_argv,
+    resolve_in_path, tool_available, tools_install_prefix, translate_codex_line,
+};
+use crate::codex_history::{CODEX_CONTEXT_TURNS, CodexHistory, CodexTurn};
+use crate::diagnostics::{Diagnostic, DiagnosticSeverity, Diagnostics, parse_diagnostic};
+use crate::diff::{DIFF_CONTEXT, DiffRowKind, diff_rows};
+use crate::fs::{
+    COMMON_ENCODINGS, apply_line_ending, can_encode, detect_line_ending, has_bom, newer_autosave,
+    normalize_encoding, normalize_whitespace, read_autosave, read_text_with_encoding,
+    remove_autosave, write_autosave, write_text_with_encoding,
 };
-use crate::fs::write_text_with_encoding;
+use crate::git::{
+    GitCommand, GitStatus, GitStatusMap, git_add_all_argv, git_commit_argv, git_diff_argv,
+    git_status_argv, is_git_repo, parse_commit_hash,
+};
+use crate::highlight::{Language, LineState, TokenKind, carry_state, highlight_line};
+use crate::keymap::{Action, ChordKey, KeyChord, Keymap, palette_matches};
 use crate::process::{
-    NativeProcessRunner, ProcEventKind, ProcessRunner, python_run_argv, windows_cmd_argv,
+    NativeProcessRunner, ProcEventKind, ProcessRunner, file_manager_argv, python_repl_argv,
+    python_run_argv, venv_create_argv, windows_cmd_argv,
 };
+use crate::quick_open::{FileIndex, QUICK_OPEN_RESULTS};
+use crate::session::{SESSION_SAVE_INTERVAL, Session};
+use crate::watcher::WorkspaceWatcher;
 use crate::workspace::{
-    FileTreeData, OpenWorkspaceFileError, TreeEntry, WorkspacePaths, open_workspace_file,
+    EXPAND_ALL_MAX_DEPTH, FileTreeData, OpenWorkspaceFileError, OpenedWorkspaceFile, TreeEntry,
+    WorkspacePaths, create_entry, move_to_trash, open_workspace_file, open_workspace_file_anyway,
+    open_workspace_hex_preview, remap_path, rename_entry, target_dir_for,
 };
 
-#[derive(Debug, Clone)]
-struct LogLine {
-    text: String,
-    style: Style,
+/// Part de l'arbre dans la largeur (en %) : defaut, minimum, maximum.
+const TREE_RATIO: (u16, u16, u16) = (30, 15, 60);
+/// Part de l'editeur dans la hauteur de droite (en %) : defaut, minimum, maximum.
+const EDITOR_RATIO: (u16, u16, u16) = (60, 25, 85);
+/// Pas de redimensionnement (Alt+Maj+fleches), en %.
+const RATIO_STEP: u16 = 5;
+
+/// Zones des panneaux a la derniere frame, pour la souris (`--mouse`).
+#[derive(Debug, Clone, Copy, Default)]
+struct PanelAreas {
+    tree: Rect,
+    editor: Rect,
+    cmd: Rect,
+    log: Rect,
+    codex: Rect,
+    codex_log: Rect,
 }
 
+/// Ligne de log du TUI : portions colorees en styles ratatui.
+type LogLine = crate::app_core::LogLine<Style>;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Focus {
     Tree,
     Editor,
     Cmd,
+    Log,
     Codex,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PromptKind {
+    NewFile(PathBuf),
+    NewFolder(PathBuf),
+    Rename(PathBuf),
+    ConfirmDelete(PathBuf),
+    Search,
+    ReplaceFind,
+    Replace(String),
+    GotoLine,
+    QuickOpen,
+    CommandPalette,
+    UnsavedChanges(PendingAction),
+    RecoverAutosave(PathBuf),
+    DeletedOnDisk(PathBuf),
+    OpenAnyway(PathBuf),
+    ReopenEncoding,
+    SaveEncoding,
+    /// Encodage choisi incapable de representer le buffer : repli UTF-8 a confirmer.
+    ConfirmLossySave(String),
+    /// Nettoyage des caches pip/npm/pycache, tailles dans le libelle.
+    ConfirmClearCaches,
+    /// Commande shell signalee par le garde-fou, lancee seulement si confirmee.
+    ConfirmShell(String),
+    /// Dossier de sortie d'un build a ouvrir dans le gestionnaire de fichiers.
+    RevealFolder(PathBuf),
+    /// Patch de la derniere reponse Codex, apercu dans le journal, a ecrire sur disque.
+    ApplyCodexPatch(CodexPatch),
+    /// Prompt Codex en attente de confirmation (sandbox danger-full-access).
+    ConfirmDangerSandbox(String),
+    /// Question a Codex sur (source, contenu) : fichier courant ou selection.
+    CodexQuestion(String, String),
+    Diagnostics,
+    /// Outils dev a installer (Ctrl+D) ; la saisie ajoute des paquets.
+    DevTools,
+    /// Rapport d'environnement (Alt+Shift+E) : r rafraichit, c copie.
+    EnvironmentReport,
+    /// Recherche dans un panneau de log (Ctrl+Alt+F), mise a jour a chaque touche.
+    LogSearch,
+    /// Interpreteurs Python detectes (Alt+Shift+P) : Entree choisit, r relance la detection.
+    PythonPicker,
+    /// Assistant de premier lancement : i installe Codex, r reverifie.
+    Setup,
+    /// Variables d'environnement (Alt+Shift+V) : Tab change de profil, Entree ajoute CLE=valeur.
+    EnvInspector,
+    /// Console Python (Alt+Shift+R) : Entree envoie la ligne, Ctrl+R redemarre, Ctrl+L efface.
+    Repl,
+    GitCommit,
+}
+
+/// Action differee tant que l'utilisateur n'a pas choisi de sauver/abandonner les modifs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PendingAction {
+    CloseTab(PathBuf),
+    ReloadTree,
+    Quit,
+}
+
+impl PromptKind {
+    fn is_confirmation(&self) -> bool {
+        matches!(
+            self,
+            PromptKind::ConfirmDelete(_)
+                | PromptKind::OpenAnyway(_)
+                | PromptKind::ConfirmLossySave(_)
+                | PromptKind::ConfirmDangerSandbox(_)
+                | PromptKind::ConfirmClearCaches
+                | PromptKind::ConfirmShell(_)
+                | PromptKind::RevealFolder(_)
+                | PromptKind::ApplyCodexPatch(_)
+        )
+    }
+
+    /// Prompts qui restent ouverts apres Entree (navigation entre occurrences).
+    fn is_search(&self) -> bool {
+        matches!(self, PromptKind::Search | PromptKind::Replace(_))
+    }
+}
+
+/// Saisie ponctuelle affichee dans le pied de page (nom de fichier, confirmation...).
+struct Prompt {
+    kind: PromptKind,
+    label: String,
+    input: InputField,
+}
+
 struct InputField {
     value: String,
     cursor: usize,
@@ -66,6 +200,13 @@ impl InputField {
         }
     }
 
+    fn with_value(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            cursor: value.chars().count(),
+        }
+    }
+
     fn clear(&mut self) {
         self.value.clear();
         self.cursor = 0;
@@ -174,13 +315,14 @@ impl FileTree {
         tree
     }
 
+    /// Relit le disque ; la selection suit le chemin (ou son parent s'il a disparu).
     fn reload(&mut self, workspace: &WorkspacePaths) {
+        let previous = self.selected_entry().map(|entry| entry.path.clone());
         self.data.reload(workspace);
-        if self.data.visible().is_empty() {
-            self.state.select(None);
-        } else if self.state.selected().unwrap_or(0) >= self.data.visible().len() {
-            self.state.select(Some(self.data.visible().len() - 1));
-        }
+        let idx = previous
+            .and_then(|path| self.data.reselect(&path))
+            .or_else(|| (!self.data.visible().is_empty()).then_some(0));
+        self.state.select(idx);
     }
 
     fn selected_entry(&self) -> Option<&TreeEntry> {
@@ -211,6 +353,12 @@ impl FileTree {
         self.state.select(Some(prev));
     }
 
+    fn select_path(&mut self, path: &Path) {
+        if let Some(idx) = self.data.position_of(path) {
+            self.state.select(Some(idx));
+        }
+    }
+
     fn toggle_dir(&mut self) {
         let path = match self.selected_entry() {
             Some(entry) if entry.is_dir => entry.path.clone(),
@@ -220,16 +368,43 @@ impl FileTree {
     }
 }
 
-pub fn run(root_dir: PathBuf) -> Result<()> {
+pub fn run(
+    root_dir: PathBuf,
+    file: Option<PathBuf>,
+    watch: bool,
+    bug_log: bool,
+    mouse: bool,
+) -> Result<()> {
     let mut stdout = std::io::stdout();
     enable_raw_mode().context("impossible d'activer le mode raw")?;
     stdout.execute(EnterAlternateScreen)?;
+    if mouse {
+        stdout.execute(EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     let mut app = App::new(root_dir)?;
+    if !bug_log {
+        app.core.set_bug_log_enabled(false);
+    }
+    if watch {
+        app.enable_watcher();
+    }
+    app.refresh_git_status();
+    app.detect_pythons();
+    app.detect_node();
+    if let Some(file) = file {
+        app.open_startup_file(&file);
+    }
+    if app.prompt.is_none() {
+        app.show_setup_if_needed();
+    }
     let res = app.run(&mut terminal);
     disable_raw_mode().ok();
     let mut stdout = std::io::stdout();
+    if mouse {
+        stdout.execute(DisableMouseCapture).ok();
+    }
     stdout.execute(LeaveAlternateScreen).ok();
     res
 }
@@ -237,75 +412,227 @@ pub fn run(root_dir: PathBuf) -> Result<()> {
 struct App {
     root_dir: PathBuf,
     core: AppCore,
-    current: Option<OpenFile>,
+    tabs: EditorTabs<TextArea<'static>>,
     tree: FileTree,
-    editor: TextArea<'static>,
+    /// Editeur affiche quand aucun onglet n'est ouvert.
+    empty_editor: TextArea<'static>,
     cmd_input: InputField,
     codex_input: InputField,
     log: Vec<LogLine>,
     codex_log: Vec<LogLine>,
+    log_filter: LogFilter,
+    codex_log_filter: LogFilter,
     focus: Focus,
     title: String,
     sub_title: String,
-    codex_compact_view: bool,
+    /// Taille, lignes et mots du fichier courant (None pour un apercu ou sans fichier).
+    doc_stats: Option<DocumentStats>,
+    codex_view: CodexView,
     codex_sandbox_mode: CodexSandboxMode,
     codex_approval_policy: CodexApprovalPolicy,
+    codex_model: String,
     codex_sandbox_supported: Option<bool>,
     codex_approval_supported: Option<bool>,
     codex_exec_used_sandbox_flag: bool,
     codex_exec_used_approval_flag: bool,
     codex_last_prompt: Option<String>,
+    codex_history: CodexHistory,
+    cmd_history: CommandHistory,
+    shell_cwd: PathBuf,
+    /// Interpreteur Python affiche dans la barre d'etat (venv actif ou commande de base).
+    python_label: String,
+    /// Build PyInstaller en un seul executable (--onefile) plutot qu'un dossier (--onedir).
+    build_onefile: bool,
+    /// Artefact attendu du build en cours, annonce a la fin du process.
+    build_output: Option<PathBuf>,
+    /// Fichier en cours de formatage, relu a la fin du process.
+    format_target: Option<PathBuf>,
+    /// Fichier du dernier lint ; ses diagnostics sont remplaces a chaque relance.
+    lint_target: Option<PathBuf>,
+    /// Resume du dernier passage pytest, affiche dans la barre d'etat.
+    pytest_summary: Option<PytestSummary>,
+    /// Sortie du `git status` en cours, analysee a la fin du process.
+    git_status_lines: Vec<String>,
+    git_status_running: bool,
+    /// Rafraichissement demande pendant un `git status` : relance a sa fin.
+    git_status_stale: bool,
+    /// Message a committer une fois `git add -A` termine.
+    git_commit_message: Option<String>,
+    git_commit_hash: Option<String>,
+    git_diff_lines: usize,
+    /// Diagnostics ruff/mypy, par fichier.
+    diagnostics: Diagnostics,
+    diagnostics_selected: usize,
+    /// Derniere etape pip/npm reconnue de l'installation en cours, et son panneau.
+    install_progress: Option<(LogTarget, String)>,
+    /// Outils dev listes par Ctrl+D.
+    dev_tools: Vec<DevToolEntry>,
+    dev_tools_selected: usize,
+    /// Dernier rapport d'environnement (Alt+Shift+E).
+    environment_report: Vec<String>,
+    /// Prerequis absents listes par l'assistant de premier lancement.
+    setup_missing: Vec<SetupMissing>,
+    /// Profil affiche par l'inspecteur d'environnement et premiere ligne visible.
+    env_profile: EnvProfile,
+    env_scroll: usize,
+    /// Sortie du REPL Python (Alt+Shift+R), gardee quand la console est fermee.
+    repl_output: Vec<String>,
+    /// Premiere ligne / colonne visibles quand l'editeur est dessine par l'app (coloration, repli).
+    editor_scroll: (usize, usize),
+    /// Retour a la ligne automatique de l'editeur et des logs (persiste dans la session).
+    word_wrap: bool,
+    /// Defilement du Journal (focus Log) : lignes remontees depuis la fin, premiere colonne.
+    log_scroll: (usize, usize),
+    /// Lignes de la Sortie Codex remontees depuis la fin (0 : suit la sortie).
+    codex_log_scroll: usize,
+    /// Recherche en cours dans un log (surligne les resultats au rendu).
+    log_search: Option<LogSearch>,
+    /// Mode zen (Alt+M) : l'editeur occupe tout le corps ; garde le focus d'avant pour le retour.
+    zen_mode: Option<Focus>,
+    /// Decoupage arbre / droite et editeur / bas, en % (Alt+Maj+fleches, persiste en session).
+    tree_ratio: u16,
+    editor_ratio: u16,
+    areas: PanelAreas,
+    codex_continue_session: bool,
+    codex_turn_response: String,
+    codex_history_index: Option<usize>,
     codex_retry_without_sandbox: bool,
     codex_retry_without_approval: bool,
+    /// Debut du pre-check `codex login status` en cours (indicateur d'attente).
+    codex_status_started: Option<Instant>,
     codex_caps_checked: bool,
     codex_caps_running: bool,
     codex_caps_buffer: String,
     last_codex_message: Option<String>,
+    /// Dernier message assistant complet, pour la copie (Alt+C).
+    last_assistant_message: Option<String>,
+    /// Garde le contenu copie disponible (sous X11 il disparait avec le `Clipboard`).
+    clipboard: Option<arboard::Clipboard>,
     codex_assistant_buffer: String,
+    /// Texte des deltas deja affiche pendant ce tour : le message final qui le repete est ignore.
+    codex_streamed_text: String,
     last_codex_width: u16,
-    pending_codex_prompt: Option<String>,
+    prompt: Option<Prompt>,
+    /// Fichiers pour Ctrl+P, construit a la premiere ouverture et vide a chaque reload.
+    file_index: Option<FileIndex>,
+    quick_open_selected: usize,
+    python_selected: usize,
+    palette_selected: usize,
+    last_session_save: Instant,
+    /// Derniere verification des fichiers ouverts sans surveillance du workspace.
+    last_disk_check: Instant,
+    last_autosave: Instant,
+    watcher: Option<WorkspaceWatcher>,
+    quit_requested: bool,
+    keymap: Keymap,
 }
 
 impl App {
     fn new(root_dir: PathBuf) -> Result<Self> {
         let root_dir = root_dir.canonicalize().unwrap_or(root_dir);
-        let core = AppCore::new(root_dir.clone());
+        let mut core = AppCore::new(root_dir.clone());
+        let settings_errors = core.load_settings();
         let tree = FileTree::new(core.workspace());
+        let codex_history = CodexHistory::load(core.workspace());
+        let cmd_history = CommandHistory::load(core.workspace());
+        let shell_cwd = root_dir.clone();
+        let python_label = core.python_status_label();
+        let codex_view = core.settings().codex_view();
+        let codex_sandbox_mode = core.settings().codex_sandbox();
+        let codex_approval_policy = core.settings().codex_approval();
+        let codex_model = core.settings().codex_model();
+        let (keymap, keymap_errors) = Keymap::load(core.workspace().keymap_path());
         let mut app = Self {
             root_dir,
             core,
-            current: None,
+            tabs: EditorTabs::default(),
             tree,
-            editor: Self::make_editor(),
+            empty_editor: Self::make_editor(),
             cmd_input: InputField::new(),
             codex_input: InputField::new(),
             log: Vec::new(),
             codex_log: Vec::new(),
+            log_filter: LogFilter::default(),
+            codex_log_filter: LogFilter::default(),
             focus: Focus::Tree,
             title: APP_NAME.to_string(),
             sub_title: String::new(),
-            codex_compact_view: true,
-            codex_sandbox_mode: codex_sandbox_mode_from_env(),
-            codex_approval_policy: codex_approval_policy_from_env(),
+            doc_stats: None,
+            codex_view,
+            codex_sandbox_mode,
+            codex_approval_policy,
+            codex_model,
             codex_sandbox_supported: None,
             codex_approval_supported: None,
             codex_exec_used_sandbox_flag: false,
             codex_exec_used_approval_flag: false,
             codex_last_prompt: None,
+            codex_history,
+            cmd_history,
+            shell_cwd,
+            python_label,
+            build_onefile: false,
+            build_output: None,
+            format_target: None,
+            lint_target: None,
+            pytest_summary: None,
+            git_status_lines: Vec::new(),
+            git_status_running: false,
+            git_status_stale: false,
+            git_commit_message: None,
+            git_commit_hash: None,
+            git_diff_lines: 0,
+            diagnostics: Diagnostics::default(),
+            diagnostics_selected: 0,
+            install_progress: None,
+            dev_tools: Vec::new(),
+            dev_tools_selected: 0,
+            environment_report: Vec::new(),
+            setup_missing: Vec::new(),
+            env_profile: EnvProfile::Shell,
+            env_scroll: 0,
+            repl_output: Vec::new(),
+            editor_scroll: (0, 0),
+            word_wrap: true,
+            log_scroll: (0, 0),
+            codex_log_scroll: 0,
+            log_search: None,
+            zen_mode: None,
+            tree_ratio: TREE_RATIO.0,
+            editor_ratio: EDITOR_RATIO.0,
+            areas: PanelAreas::default(),
+            codex_continue_session: false,
+            codex_turn_response: String::new(),
+            codex_history_index: None,
             codex_retry_without_sandbox: false,
             codex_retry_without_approval: false,
+            codex_status_started: None,
             codex_caps_checked: false,
             codex_caps_running: false,
             codex_caps_buffer: String::new(),
             last_codex_message: None,
+            last_assistant_message: None,
+            clipboard: None,
             codex_assistant_buffer: String::new(),
+            codex_streamed_text: String::new(),
             last_codex_width: 80,
-            pending_codex_prompt: None,
+            prompt: None,
+            file_index: None,
+            quick_open_selected: 0,
+            python_selected: 0,
+            palette_selected: 0,
+            last_session_save: Instant::now(),
+            last_disk_check: Instant::now(),
+            last_autosave: Instant::now(),
+            watcher: None,
+            quit_requested: false,
+            keymap,
         };
         app.core.ensure_portable_dirs();
+        app.restore_session();
         app.refresh_title();
         app.log_ui(format!(
-            "{APP_NAME}\nRoot: {}\nShell: champ 'Commande' - Codex: champ 'Codex' - Ctrl+K login - Ctrl+I install - Ctrl+O sandbox - Ctrl+P approb\n",
+            "{APP_NAME}\nRoot: {}\nShell: champ 'Commande' - Codex: champ 'Codex' - Ctrl+K login - Ctrl+Alt+K logout - Ctrl+I install - Ctrl+O sandbox - Alt+A approb - F1 commandes\n",
             app.root_dir.display()
         ));
         app.codex_log_ui(format!(
@@ -316,1643 +643,8078 @@ impl App {
             "Approbations Codex: {}",
             codex_approval_label(app.codex_approval_policy)
         ));
+        if !app.codex_model.is_empty() {
+            app.codex_log_ui(format!("Modele Codex: {}", app.codex_model));
+        }
+        if let Some(msg) = app.core.external_venv_warning() {
+            app.log_issue(&msg, "avertissement", "venv", LogTarget::Main);
+        }
+        for err in settings_errors {
+            app.log_issue(
+                &format!("settings.toml ignore (defaut garde): {err}"),
+                "avertissement",
+                "settings",
+                LogTarget::Main,
+            );
+        }
+        for err in keymap_errors {
+            app.log_issue(
+                &format!("keymap.toml ignore (defaut garde): {err}"),
+                "avertissement",
+                "keymap",
+                LogTarget::Main,
+            );
+        }
         Ok(app)
     }
 
     fn make_editor() -> TextArea<'static> {
-        let mut editor = TextArea::default();
+        Self::text_editor("")
+    }
+
+    fn text_editor(text: &str) -> TextArea<'static> {
+        let mut editor = TextArea::from(text_lines(text));
         editor.set_block(Block::default().borders(Borders::ALL).title("Editeur"));
         editor
     }
 
+    fn current(&self) -> Option<&OpenFile> {
+        self.tabs.active().map(|tab| &tab.file)
+    }
+
+    fn current_mut(&mut self) -> Option<&mut OpenFile> {
+        self.tabs.active_mut().map(|tab| &mut tab.file)
+    }
+
+    /// Editeur de l'onglet actif.
+    fn editor(&self) -> &TextArea<'static> {
+        self.tabs
+            .active()
+            .map_or(&self.empty_editor, |tab| &tab.buffer)
+    }
+
+    fn editor_mut(&mut self) -> &mut TextArea<'static> {
+        match self.tabs.active_mut() {
+            Some(tab) => &mut tab.buffer,
+            None => &mut self.empty_editor,
+        }
+    }
+
     fn run(&mut self, terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) -> Result<()> {
         let tick_rate = Duration::from_millis(50);
         let mut last_tick = Instant::now();
         loop {
             terminal.draw(|f| self.draw(f))?;
             self.drain_process_events();
+            self.poll_watcher();
+            self.poll_codex_retry();
 
             let timeout = tick_rate.saturating_sub(last_tick.elapsed());
-            if event::poll(timeout)?
-                && let Event::Key(key) = event::read()?
-                && self.handle_key(key)
-            {
-                break;
+            if event::poll(timeout)? {
+                match event::read()? {
+                    Event::Key(key) if self.handle_key(key) => break,
+                    Event::Mouse(mouse) => self.handle_mouse(mouse),
+                    _ => {}
+                }
             }
             if last_tick.elapsed() >= tick_rate {
                 last_tick = Instant::now();
             }
+            if self.last_session_save.elapsed() >= SESSION_SAVE_INTERVAL {
+                self.save_session();
+            }
+            if let Some(interval) = self.core.settings().autosave_interval()
+                && self.last_autosave.elapsed() >= interval
+            {
+                self.autosave_tabs();
+            }
         }
+        self.save_session();
         Ok(())
     }
 
-    fn draw(&mut self, f: &mut ratatui::Frame<'_>) {
-        let area = f.area();
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(1),
-                Constraint::Min(0),
-                Constraint::Length(1),
-            ])
-            .split(area);
-
-        self.draw_header(f, layout[0]);
-        self.draw_body(f, layout[1]);
-        self.draw_footer(f, layout[2]);
+    /// Ouvre le fichier passe en ligne de commande (apres la restauration de session).
+    fn open_startup_file(&mut self, file: &Path) {
+        match validate_startup_file(&self.root_dir, file) {
+            Ok(path) => {
+                self.load_file(path.clone());
+                self.tree.select_path(&path);
+            }
+            Err(msg) => self.log_issue(&msg, "erreur", "demarrage", LogTarget::Main),
+        }
     }
 
-    fn draw_header(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let title = Line::from(vec![
-            Span::styled(&self.title, Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw("  "),
-            Span::styled(&self.sub_title, Style::default().fg(Color::Gray)),
-        ]);
-        let header = Paragraph::new(Text::from(title));
-        f.render_widget(header, area);
+    fn enable_watcher(&mut self) {
+        match WorkspaceWatcher::new(self.core.workspace()) {
+            Ok(watcher) => {
+                self.watcher = Some(watcher);
+                self.log_ui("Surveillance du workspace active.".to_string());
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Surveillance du workspace impossible: {err}"),
+                    "avertissement",
+                    "surveillance",
+                    LogTarget::Main,
+                );
+            }
+        }
     }
 
-    fn draw_footer(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let help = "Ctrl+S sauver | F5 executer | Ctrl+O sandbox | Ctrl+P approb | Ctrl+Q quitter | Tab focus";
-        let footer = Paragraph::new(help).style(Style::default().fg(Color::DarkGray));
-        f.render_widget(footer, area);
+    fn poll_watcher(&mut self) {
+        if self.watcher.is_none() {
+            // Sans --watch : seule la disparition des fichiers ouverts est verifiee.
+            if self.last_disk_check.elapsed() >= DISK_CHECK_INTERVAL {
+                self.last_disk_check = Instant::now();
+                self.check_missing_files();
+            }
+            return;
+        }
+        let Some(changed) = self.watcher.as_mut().and_then(WorkspaceWatcher::poll) else {
+            return;
+        };
+        let selected = self.tree.selected_entry().map(|entry| entry.path.clone());
+        self.tree.reload(self.core.workspace());
+        self.file_index = None;
+        if let Some(path) = selected {
+            self.tree.select_path(&path);
+        }
+        self.refresh_git_status();
+        self.sync_tabs_with_disk(&changed);
     }
 
-    fn draw_body(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-            .split(area);
-
-        self.draw_tree(f, chunks[0]);
-        self.draw_right(f, chunks[1]);
+    /// Signale chaque onglet dont le fichier a disparu du disque (une fois par disparition).
+    fn check_missing_files(&mut self) {
+        for idx in self.tabs.newly_missing() {
+            if let Some(path) = self.tabs.get(idx).map(|tab| tab.file.path.clone()) {
+                self.on_file_deleted(idx, path);
+            }
+        }
     }
 
-    fn draw_tree(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let mut items = Vec::new();
-        for entry in self.tree.data.visible() {
-            let indent = "  ".repeat(entry.depth);
-            let is_expanded = self.tree.data.is_expanded(&entry.path);
-            let icon = if entry.is_dir {
-                if is_expanded { "-" } else { "+" }
-            } else {
-                " "
-            };
-            let text = format!("{indent}{icon} {}", entry.name);
-            items.push(ListItem::new(Line::from(text)));
+    fn sync_tabs_with_disk(&mut self, changed: &[PathBuf]) {
+        for idx in 0..self.tabs.len() {
+            self.sync_tab_with_disk(idx, changed);
         }
-        let block = Self::block_with_focus("Fichiers", self.focus == Focus::Tree);
-        let list = List::new(items)
-            .block(block)
-            .highlight_style(Style::default().bg(Color::Blue));
-        f.render_stateful_widget(list, area, &mut self.tree.state);
     }
 
-    fn draw_right(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
-            .split(area);
-
-        self.draw_editor(f, chunks[0]);
-        self.draw_bottom(f, chunks[1]);
+    /// Recharge l'onglet si son fichier a change sur disque (sauf modifications locales).
+    fn sync_tab_with_disk(&mut self, idx: usize, changed: &[PathBuf]) {
+        let Some(current) = self.tabs.get(idx).map(|tab| &tab.file) else {
+            return;
+        };
+        // Un evenement sur un dossier parent (supprime ou renomme) concerne aussi le fichier.
+        if !changed
+            .iter()
+            .any(|changed| current.path.starts_with(changed))
+        {
+            return;
+        }
+        if !current.path.exists() {
+            let path = current.path.clone();
+            if self.tabs.mark_missing(idx) {
+                self.on_file_deleted(idx, path);
+            }
+            return;
+        }
+        let exact = changed.contains(&current.path);
+        let Some(current) = self.tabs.get_mut(idx).map(|tab| &mut tab.file) else {
+            return;
+        };
+        current.missing_on_disk = false;
+        if !exact {
+            return;
+        }
+        if !current.path.is_file() {
+            return;
+        }
+        let path = current.path.clone();
+        let encoding = current.encoding.clone();
+        // Apercu fige : il se recharge en rouvrant le fichier.
+        if current.read_only() {
+            return;
+        }
+        if current.dirty {
+            self.log_issue(
+                &format!(
+                    "Fichier modifie sur disque: {} (modifications locales conservees)",
+                    path.display()
+                ),
+                "avertissement",
+                "surveillance",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let text = match read_text_with_encoding(&path, &encoding) {
+            Ok(text) => text,
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur relecture: {} ({err})", path.display()),
+                    "erreur",
+                    "surveillance",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let Some(tab) = self.tabs.get_mut(idx) else {
+            return;
+        };
+        tab.file.line_ending = detect_line_ending(&text);
+        tab.file.bom = has_bom(&path);
+        if text_lines(&text) == tab.buffer.lines() {
+            return;
+        }
+        let (row, col) = tab.buffer.cursor();
+        tab.buffer = Self::text_editor(&text);
+        tab.buffer
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+        self.log_ui(format!("Recharge depuis le disque: {}", path.display()));
     }
 
-    fn draw_editor(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let block = Self::block_with_focus("Editeur", self.focus == Focus::Editor);
-        self.editor.set_block(block);
-        f.render_widget(self.editor.widget(), area);
-        if self.focus == Focus::Editor {
-            let (row, col) = self.editor.cursor();
-            let x = area.x + col as u16 + 1;
-            let y = area.y + row as u16 + 1;
-            f.set_cursor_position((x, y));
+    /// Fichier de l'onglet supprime hors de l'IDE (git checkout, Codex...) : le contenu
+    /// ne vit plus que dans l'editeur, on propose de le recreer ou de fermer l'onglet.
+    fn on_file_deleted(&mut self, idx: usize, path: PathBuf) {
+        if let Some(tab) = self.tabs.get_mut(idx) {
+            tab.file.dirty = true;
+        }
+        self.refresh_title();
+        self.log_issue(
+            &format!("Fichier supprime sur le disque: {}", path.display()),
+            "avertissement",
+            "surveillance",
+            LogTarget::Main,
+        );
+        if self.prompt.is_none() {
+            let label = format!(
+                "{} supprime sur le disque. Recreer ? (o: recreer, f: fermer, Echap: garder l'onglet)",
+                tab_name(&path)
+            );
+            self.open_prompt(PromptKind::DeletedOnDisk(path), &label);
         }
     }
 
-    fn draw_bottom(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(area);
-
-        self.draw_shell(f, chunks[0]);
-        self.draw_codex(f, chunks[1]);
+    /// Reecrit le fichier supprime avec le contenu de l'onglet (dossiers parents recrees).
+    fn recreate_deleted(&mut self, path: &Path) {
+        let Some(idx) = self.tabs.position(path) else {
+            return;
+        };
+        if let Some(parent) = path.parent()
+            && let Err(err) = fs::create_dir_all(parent)
+        {
+            self.log_issue(
+                &format!("Impossible de recreer {}: {err}", parent.display()),
+                "erreur",
+                "sauvegarde",
+                LogTarget::Main,
+            );
+            return;
+        }
+        self.save_tab(idx);
     }
 
-    fn draw_shell(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(0)])
-            .split(area);
+    fn close_deleted(&mut self, path: &Path) {
+        self.discard_autosave(path);
+        self.close_tab(path);
+    }
 
-        let input_block = Self::block_with_focus("Commande", self.focus == Focus::Cmd);
-        let input = Paragraph::new(self.cmd_input.value.as_str()).block(input_block);
-        f.render_widget(input, chunks[0]);
-        if self.focus == Focus::Cmd {
-            let cursor_x = chunks[0].x + 1 + self.cmd_input.cursor as u16;
-            let cursor_y = chunks[0].y + 1;
-            f.set_cursor_position((cursor_x, cursor_y));
+    fn restore_session(&mut self) {
+        let session = Session::load(self.core.workspace());
+        self.word_wrap = session.word_wrap;
+        self.tree_ratio = session_ratio(session.panel("tui_tree"), TREE_RATIO);
+        self.editor_ratio = session_ratio(session.panel("tui_editor"), EDITOR_RATIO);
+        self.tree.data.set_show_hidden(session.show_hidden);
+        self.tree.data.restore_expanded(&session.expanded);
+        if let Some(path) = session.existing_current_file() {
+            let path = path.to_path_buf();
+            self.load_file(path.clone());
+            self.tree.select_path(&path);
         }
+    }
 
-        let log_block = Block::default().borders(Borders::ALL).title("Journal");
-        let log_text = self.render_log(&self.log, chunks[1].height.saturating_sub(2) as usize);
-        let log = Paragraph::new(log_text)
-            .block(log_block)
-            .wrap(Wrap { trim: false });
-        f.render_widget(log, chunks[1]);
+    fn save_session(&mut self) {
+        self.last_session_save = Instant::now();
+        let mut session = Session {
+            current_file: self.current().map(|current| current.path.clone()),
+            expanded: self.tree.data.expanded_paths(),
+            word_wrap: self.word_wrap,
+            show_hidden: self.tree.data.show_hidden(),
+            // Garde les preferences propres au GUI (panneaux, theme, police).
+            ..Session::load(self.core.workspace())
+        };
+        session
+            .panels
+            .insert("tui_tree".to_string(), f32::from(self.tree_ratio));
+        session
+            .panels
+            .insert("tui_editor".to_string(), f32::from(self.editor_ratio));
+        if let Err(err) = session.save(self.core.workspace()) {
+            self.log_issue(
+                &format!("Sauvegarde session impossible: {err}"),
+                "avertissement",
+                "session",
+                LogTarget::Main,
+            );
+        }
     }
 
-    fn draw_codex(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
-        let chunks = Layout::default()
+    fn draw(&mut self, f: &mut ratatui::Frame<'_>) {
+        let area = f.area();
+        let layout = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
             .split(area);
 
-        let input_block = Self::block_with_focus("Codex", self.focus == Focus::Codex);
-        let input = Paragraph::new(self.codex_input.value.as_str()).block(input_block);
-        f.render_widget(input, chunks[0]);
-        if self.focus == Focus::Codex {
-            let cursor_x = chunks[0].x + 1 + self.codex_input.cursor as u16;
-            let cursor_y = chunks[0].y + 1;
-            f.set_cursor_position((cursor_x, cursor_y));
+        self.draw_header(f, layout[0]);
+        self.draw_body(f, layout[1]);
+        self.draw_footer(f, layout[2]);
+        match self.prompt.as_ref().map(|prompt| &prompt.kind) {
+            Some(PromptKind::QuickOpen) => self.draw_quick_open(f, layout[1]),
+            Some(PromptKind::CommandPalette) => self.draw_command_palette(f, layout[1]),
+            Some(PromptKind::Diagnostics) => self.draw_diagnostics(f, layout[1]),
+            Some(PromptKind::DevTools) => self.draw_dev_tools(f, layout[1]),
+            Some(PromptKind::EnvironmentReport) => self.draw_environment_report(f, layout[1]),
+            Some(PromptKind::PythonPicker) => self.draw_python_picker(f, layout[1]),
+            Some(PromptKind::Setup) => self.draw_setup(f, layout[1]),
+            Some(PromptKind::EnvInspector) => self.draw_env_inspector(f, layout[1]),
+            Some(PromptKind::Repl) => self.draw_repl(f, layout[1]),
+            _ => {}
         }
-
-        let log_block = Block::default().borders(Borders::ALL).title("Sortie Codex");
-        self.last_codex_width = chunks[1].width;
-        let log_text =
-            self.render_log(&self.codex_log, chunks[1].height.saturating_sub(2) as usize);
-        let log = Paragraph::new(log_text)
-            .block(log_block)
-            .wrap(Wrap { trim: false });
-        f.render_widget(log, chunks[1]);
     }
 
-    fn render_log(&self, log: &[LogLine], max_lines: usize) -> Text<'_> {
-        let start = log.len().saturating_sub(max_lines);
-        let lines: Vec<Line> = log[start..]
+    /// Actions filtrees par la saisie, avec leurs raccourcis actuels.
+    fn draw_command_palette(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let matches = self.palette_matches();
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        let height = (matches.len() as u16 + 2).clamp(3, area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height,
+        };
+        let items: Vec<ListItem> = matches
             .iter()
-            .map(|entry| Line::from(Span::styled(entry.text.clone(), entry.style)))
+            .map(|action| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(action.label()),
+                    Span::raw("  "),
+                    Span::styled(
+                        self.keymap.chords_label(*action),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]))
+            })
             .collect();
-        Text::from(lines)
+        let mut state = ListState::default();
+        if !matches.is_empty() {
+            state.select(Some(self.palette_selected.min(matches.len() - 1)));
+        }
+        let title = format!("Commandes ({})", matches.len());
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::Blue));
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut state);
     }
 
-    fn block_with_focus<'a>(title: &'a str, focused: bool) -> Block<'a> {
-        let style = if focused {
-            Style::default().fg(Color::Yellow)
-        } else {
-            Style::default()
+    /// Diagnostics du dernier lint (Alt+D), colores selon la gravite.
+    fn draw_diagnostics(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let width = (area.width * 4 / 5).max(40).min(area.width);
+        let height = (self.diagnostics.len() as u16 + 2).clamp(3, area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height,
         };
-        Block::default()
-            .borders(Borders::ALL)
-            .title(title)
-            .style(style)
+        let items: Vec<ListItem> = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let color = match diagnostic.severity {
+                    DiagnosticSeverity::Error => Color::Red,
+                    DiagnosticSeverity::Warning => Color::Yellow,
+                    DiagnosticSeverity::Note => Color::Gray,
+                };
+                ListItem::new(Line::from(Span::styled(
+                    diagnostic.label(&self.root_dir),
+                    Style::default().fg(color),
+                )))
+            })
+            .collect();
+        let mut state = ListState::default();
+        if !self.diagnostics.is_empty() {
+            state.select(Some(
+                self.diagnostics_selected.min(self.diagnostics.len() - 1),
+            ));
+        }
+        let title = format!("Diagnostics ({}) - Entree: aller", self.diagnostics.len());
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::Blue));
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut state);
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> bool {
-        if self.handle_global_shortcut(key) {
-            return true;
+    /// Outils dev (Ctrl+D) : case cochee = a installer, "present" si deja disponible.
+    fn draw_dev_tools(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        let height = (self.dev_tools.len() as u16 + 2).clamp(3, area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height,
+        };
+        let items: Vec<ListItem> = self
+            .dev_tools
+            .iter()
+            .map(|entry| {
+                let mark = if entry.selected { "[x]" } else { "[ ]" };
+                let (status, color) = if entry.available {
+                    ("present", Color::Green)
+                } else {
+                    ("absent", Color::DarkGray)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{mark} {}  ", entry.package)),
+                    Span::styled(status, Style::default().fg(color)),
+                ]))
+            })
+            .collect();
+        let mut state = ListState::default();
+        if !self.dev_tools.is_empty() {
+            state.select(Some(self.dev_tools_selected.min(self.dev_tools.len() - 1)));
         }
+        let title = format!("Outils dev ({})", self.dev_tools.len());
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::Blue));
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut state);
+    }
 
-        match self.focus {
-            Focus::Tree => self.handle_tree_key(key),
-            Focus::Editor => self.handle_editor_key(key),
-            Focus::Cmd => self.handle_cmd_key(key),
-            Focus::Codex => self.handle_codex_key(key),
-        }
+    /// Rapport d'environnement (Alt+Shift+E), par-dessus le corps de la fenetre.
+    fn draw_environment_report(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let width = (area.width * 4 / 5).max(40).min(area.width);
+        let height = (self.environment_report.len() as u16 + 2).clamp(3, area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height,
+        };
+        let lines: Vec<Line> = self
+            .environment_report
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Environnement - r: rafraichir, c: copier, Echap: fermer");
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+    }
 
-        false
+    /// Assistant de premier lancement : un prerequis absent par ligne, avec ou le placer.
+    fn draw_setup(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let width = (area.width * 4 / 5).max(40).min(area.width);
+        let height = (self.setup_missing.len() as u16 + 4).clamp(3, area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height,
+        };
+        let mut lines: Vec<Line> = self
+            .setup_missing
+            .iter()
+            .map(|missing| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}: ", missing.title()),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(missing.hint()),
+                ])
+            })
+            .collect();
+        lines.push(Line::from(""));
+        let install = if self.setup_missing.iter().any(SetupMissing::installable) {
+            "i: installer Codex, "
+        } else {
+            ""
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{install}r: reverifier, Echap: continuer"),
+            Style::default().fg(Color::DarkGray),
+        )));
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Premier lancement - elements manquants");
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            popup,
+        );
     }
 
-    fn handle_global_shortcut(&mut self, key: KeyEvent) -> bool {
-        if key.modifiers.contains(KeyModifiers::CONTROL) {
-            match key.code {
-                KeyCode::Char('q') => return true,
-                KeyCode::Char('s') => {
-                    self.action_save();
-                    return false;
-                }
-                KeyCode::Char('l') => {
-                    self.action_clear_log();
-                    return false;
-                }
-                KeyCode::Char('r') => {
-                    self.action_reload_tree();
-                    return false;
-                }
-                KeyCode::Char('k') => {
-                    self.action_codex_login();
-                    return false;
-                }
-                KeyCode::Char('t') => {
-                    self.action_codex_check();
-                    return false;
-                }
-                KeyCode::Char('i') => {
-                    self.action_codex_install();
-                    return false;
-                }
-                KeyCode::Char('m') => {
-                    self.action_toggle_codex_view();
-                    return false;
-                }
-                KeyCode::Char('o') => {
-                    self.action_toggle_codex_sandbox();
-                    return false;
-                }
-                KeyCode::Char('p') => {
-                    self.action_toggle_codex_approval();
-                    return false;
-                }
-                KeyCode::Char('e') => {
-                    self.action_build_exe();
-                    return false;
-                }
-                KeyCode::Char('d') => {
-                    self.action_dev_tools();
-                    return false;
-                }
-                _ => {}
-            }
-        }
+    /// Environnement d'un profil : portable en cyan, temporaire en jaune, secrets masques.
+    fn draw_env_inspector(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let entries = self.core.env_entries(self.env_profile);
+        let width = (area.width * 4 / 5).max(40).min(area.width);
+        let height = area.height;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height,
+        };
+        let lines: Vec<Line> = entries
+            .iter()
+            .skip(self.env_scroll)
+            .map(|entry| {
+                let color = match entry.origin {
+                    EnvOrigin::Host => Color::Reset,
+                    EnvOrigin::Portable => Color::Cyan,
+                    EnvOrigin::Temporary => Color::Yellow,
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}=", entry.key),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(entry.value.clone(), Style::default().fg(color)),
+                ])
+            })
+            .collect();
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            "Environnement {} - Tab: profil, Entree: CLE=valeur pour le prochain process, Echap: fermer",
+            self.env_profile.label()
+        ));
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+    }
 
-        match key.code {
-            KeyCode::F(5) => {
-                self.action_run();
-                false
-            }
-            KeyCode::Tab => {
-                self.focus = match self.focus {
-                    Focus::Tree => Focus::Editor,
-                    Focus::Editor => Focus::Cmd,
-                    Focus::Cmd => Focus::Codex,
-                    Focus::Codex => Focus::Tree,
+    /// Console Python : dernieres lignes du REPL, la saisie est sur la ligne du prompt.
+    fn draw_repl(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let width = (area.width * 4 / 5).max(40).min(area.width);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height: area.height,
+        };
+        let visible = area.height.saturating_sub(2) as usize;
+        let skip = self.repl_output.len().saturating_sub(visible);
+        let lines: Vec<Line> = self.repl_output[skip..]
+            .iter()
+            .map(|line| {
+                let style = if line.starts_with(">>> ") {
+                    Style::default().fg(Color::Cyan)
+                } else if line.starts_with('[') {
+                    Style::default().fg(Color::DarkGray)
+                } else {
+                    Style::default()
                 };
-                false
-            }
-            KeyCode::BackTab => {
-                self.focus = match self.focus {
-                    Focus::Tree => Focus::Codex,
-                    Focus::Editor => Focus::Tree,
-                    Focus::Cmd => Focus::Editor,
-                    Focus::Codex => Focus::Cmd,
+                Line::from(Span::styled(line.as_str(), style))
+            })
+            .collect();
+        let state = if self.core.repl().is_some() {
+            "actif"
+        } else {
+            "arrete"
+        };
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            "Python REPL ({state}) - Entree: envoyer, Ctrl+R: redemarrer, Ctrl+L: effacer, Echap: masquer"
+        ));
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+    }
+
+    /// Interpreteurs detectes (Alt+Shift+P), l'actif marque d'une etoile.
+    fn draw_python_picker(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let pythons = self.core.pythons();
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        let height = (pythons.len() as u16 + 2).clamp(3, area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height,
+        };
+        let current = self.core.python_interpreter();
+        let items: Vec<ListItem> = pythons
+            .iter()
+            .map(|python| {
+                let mark = if python.exe == current { "* " } else { "  " };
+                let style = if python.is_python2() {
+                    Style::default().fg(Color::DarkGray)
+                } else {
+                    Style::default()
                 };
-                false
-            }
-            _ => false,
+                ListItem::new(Line::from(Span::styled(
+                    format!("{mark}{}", python.label()),
+                    style,
+                )))
+            })
+            .collect();
+        let mut state = ListState::default();
+        if !pythons.is_empty() {
+            state.select(Some(self.python_selected.min(pythons.len() - 1)));
         }
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Python - Entree: choisir, r: relancer la detection"),
+            )
+            .highlight_style(Style::default().bg(Color::Blue));
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut state);
     }
 
-    fn handle_tree_key(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Up => self.tree.select_prev(),
-            KeyCode::Down => self.tree.select_next(),
-            KeyCode::Right | KeyCode::Enter => {
-                if let Some(entry) = self.tree.selected_entry() {
-                    if entry.is_dir {
-                        self.tree.toggle_dir();
-                    } else {
-                        self.open_file(entry.path.clone());
-                    }
-                }
-            }
-            KeyCode::Left => self.tree.toggle_dir(),
-            _ => {}
+    /// Liste des fichiers trouves par Ctrl+P, par-dessus le corps de la fenetre.
+    fn draw_quick_open(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let matches = self.quick_open_matches();
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        let height = (QUICK_OPEN_RESULTS as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y,
+            width,
+            height,
+        };
+        let total = self.file_index.as_ref().map_or(0, FileIndex::len);
+        let title = format!("Fichiers ({}/{total})", matches.len());
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|rel| ListItem::new(Line::from(rel.to_string())))
+            .collect();
+        let mut state = ListState::default();
+        if !matches.is_empty() {
+            state.select(Some(self.quick_open_selected.min(matches.len() - 1)));
         }
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::Blue));
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut state);
     }
 
-    fn handle_editor_key(&mut self, key: KeyEvent) {
-        let mut changed = false;
-        if matches!(
-            key.code,
-            KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete | KeyCode::Enter | KeyCode::Tab
-        ) {
-            changed = true;
+    fn draw_header(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let mut spans = vec![
+            Span::styled(&self.title, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("  "),
+            Span::styled(&self.sub_title, Style::default().fg(Color::Gray)),
+            Span::raw("  "),
+            Span::styled(
+                format!("Python: {}", self.python_label),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ];
+        let sandbox_style = if self.codex_sandbox_mode == CodexSandboxMode::DangerFullAccess {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!("Sandbox: {}", codex_sandbox_label(self.codex_sandbox_mode)),
+            sandbox_style,
+        ));
+        if self.core.settings().offline() {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                "hors-ligne",
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            ));
         }
-        let input = Input::from(key);
-        self.editor.input(input);
-        if changed && let Some(current) = self.current.as_mut() {
-            current.dirty = true;
-            self.refresh_title();
+        if self.zen_mode.is_some() {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                "zen (Alt+M)",
+                Style::default().fg(Color::Black).bg(Color::Cyan),
+            ));
         }
-    }
-
-    fn handle_cmd_key(&mut self, key: KeyEvent) {
-        if let Some(cmd) = self.cmd_input.handle_key(key) {
-            self.run_shell(cmd);
+        if let Some((label, ok)) = self.test_status() {
+            let color = if ok { Color::Green } else { Color::Red };
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(label, Style::default().fg(color)));
         }
+        let title = Line::from(spans);
+        let header = Paragraph::new(Text::from(title));
+        f.render_widget(header, area);
     }
 
-    fn handle_codex_key(&mut self, key: KeyEvent) {
-        if let Some(prompt) = self.codex_input.handle_key(key) {
-            self.run_codex(prompt);
+    fn draw_footer(&self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        if let Some(prompt) = &self.prompt {
+            let line = Line::from(vec![
+                Span::styled(
+                    format!("{}: ", prompt.label),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(prompt.input.value.as_str()),
+            ]);
+            f.render_widget(Paragraph::new(line), area);
+            let cursor_x =
+                area.x + prompt.label.chars().count() as u16 + 2 + prompt.input.cursor as u16;
+            f.set_cursor_position((cursor_x, area.y));
+            return;
         }
+        // Quelques touches essentielles : la palette (F1) liste tous les raccourcis.
+        let help = "F1 commandes (tous les raccourcis) | Ctrl+S sauver | F5 executer | Ctrl+P ouvrir | Ctrl+F chercher | Tab focus | Ctrl+Q quitter";
+        let footer = Paragraph::new(help).style(Style::default().fg(Color::DarkGray));
+        f.render_widget(footer, area);
     }
 
-    fn refresh_title(&mut self) {
-        if let Some(current) = &self.current {
-            let dirty = if current.dirty { " *" } else { "" };
-            self.title = format!("{APP_NAME}{dirty}");
-            self.sub_title = format!("{}  ({})", current.path.display(), current.encoding);
-        } else {
-            self.title = APP_NAME.to_string();
-            self.sub_title = self.root_dir.display().to_string();
+    fn draw_body(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        self.areas = PanelAreas::default();
+        if self.zen_mode.is_some() {
+            self.draw_editor(f, area);
+            return;
         }
-    }
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(self.tree_ratio),
+                Constraint::Percentage(100 - self.tree_ratio),
+            ])
+            .split(area);
 
-    fn log_ui(&mut self, msg: String) {
-        self.push_log(LogTarget::Main, msg, Style::default());
+        self.draw_tree(f, chunks[0]);
+        self.draw_right(f, chunks[1]);
     }
 
-    fn codex_log_ui(&mut self, msg: String) {
-        self.push_log(LogTarget::Codex, msg, Style::default());
+    fn draw_tree(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        self.areas.tree = area;
+        let mut items = Vec::new();
+        for entry in self.tree.data.visible() {
+            let indent = "  ".repeat(entry.depth);
+            let is_expanded = self.tree.data.is_expanded(&entry.path);
+            let icon = if entry.is_dir {
+                if is_expanded { "-" } else { "+" }
+            } else {
+                " "
+            };
+            let text = format!("{indent}{icon} {}", entry.name);
+            let line = match entry.git {
+                Some(status) => {
+                    let style = Style::default().fg(git_status_color(status));
+                    let mut spans = vec![Span::styled(text, style)];
+                    if !entry.is_dir {
+                        spans.push(Span::styled(format!(" {}", status.marker()), style));
+                    }
+                    Line::from(spans)
+                }
+                None => Line::from(text),
+            };
+            items.push(ListItem::new(line));
+        }
+        let block = Self::block_with_focus("Fichiers", self.focus == Focus::Tree);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().bg(Color::Blue));
+        f.render_stateful_widget(list, area, &mut self.tree.state);
     }
 
-    fn codex_log_output(&mut self, msg: String) {
-        self.push_log(LogTarget::Codex, msg, Style::default());
+    fn draw_right(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(self.editor_ratio),
+                Constraint::Percentage(100 - self.editor_ratio),
+            ])
+            .split(area);
+
+        self.draw_editor(f, chunks[0]);
+        self.draw_bottom(f, chunks[1]);
     }
 
-    fn push_log(&mut self, target: LogTarget, msg: String, style: Style) {
-        let lines: Vec<String> = msg.split('\n').map(|s| s.to_string()).collect();
-        let store = match target {
-            LogTarget::Main => &mut self.log,
-            LogTarget::Codex => &mut self.codex_log,
+    fn draw_editor(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        self.areas.editor = area;
+        let area = match self.current().and_then(|current| current.preview) {
+            Some(preview) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(1), Constraint::Min(0)])
+                    .split(area);
+                let banner = Paragraph::new(preview.banner())
+                    .style(Style::default().fg(Color::Black).bg(Color::Yellow));
+                f.render_widget(banner, chunks[0]);
+                chunks[1]
+            }
+            None => area,
         };
-        for line in lines {
-            store.push(LogLine { text: line, style });
+        let block = Self::block_with_focus(self.editor_title(), self.focus == Focus::Editor);
+        // Recherche et selection sont dessinees par tui-textarea : ni coloration ni repli dans ce cas.
+        let styled = self.editor().search_pattern().is_none() && !self.editor().is_selecting();
+        let lang = self
+            .current()
+            .and_then(|current| Language::from_path(&current.path));
+        if styled && (lang.is_some() || self.word_wrap) {
+            self.draw_styled_editor(f, area, block, lang);
+            return;
         }
-        if store.len() > LOG_LIMIT {
-            let drain = store.len() - LOG_LIMIT;
-            store.drain(0..drain);
+        let tab = self.core.settings().tab_width();
+        self.editor_mut().set_block(block);
+        self.editor_mut().set_tab_length(tab as u8);
+        f.render_widget(self.editor().widget(), area);
+        if self.focus == Focus::Editor {
+            let (row, col) = self.editor().cursor();
+            let line = self.editor().lines().get(row).map_or("", String::as_str);
+            let x = area.x + display_width(line, col, tab) as u16 + 1;
+            let y = area.y + row as u16 + 1;
+            f.set_cursor_position((x, y));
         }
     }
 
-    fn log_issue(&mut self, msg: &str, niveau: &str, contexte: &str, target: LogTarget) {
-        let styled = Style::default().fg(Color::Red);
-        self.push_log(target, msg.to_string(), styled);
-        self.core.record_issue(niveau, msg, contexte, None);
-    }
-
-    fn portable_env(&self, mut env_map: HashMap<String, String>) -> HashMap<String, String> {
-        self.core.portable_env(std::mem::take(&mut env_map))
-    }
-
-    fn codex_env(&self) -> HashMap<String, String> {
-        self.core.codex_env()
-    }
-
-    fn ensure_node_available(
+    /// Dessine seulement les lignes visibles, colorees et/ou repliees ; tui-textarea garde la saisie.
+    fn draw_styled_editor(
         &mut self,
-        env_map: &HashMap<String, String>,
-        target: LogTarget,
-    ) -> bool {
-        if let Some(message) = self.core.ensure_node_available_message(env_map) {
-            self.log_issue(&message, "erreur", "node", target);
-            return false;
+        f: &mut ratatui::Frame<'_>,
+        area: Rect,
+        block: Block<'_>,
+        lang: Option<Language>,
+    ) {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let editor = self
+            .tabs
+            .active()
+            .map_or(&self.empty_editor, |tab| &tab.buffer);
+        let (row, col) = editor.cursor();
+        let lines = editor.lines();
+        let tab = self.core.settings().tab_width();
+        let display_col = display_width(lines.get(row).map_or("", String::as_str), col, tab);
+        let height = inner.height as usize;
+        let width = (inner.width as usize).max(1);
+        let (top, left) = self.editor_scroll;
+        let (top, left) = if self.word_wrap {
+            (
+                wrapped_top(lines, top, row, display_col, width, height, tab),
+                0,
+            )
+        } else {
+            (
+                follow_cursor(top, row, height),
+                follow_cursor(left, display_col, width),
+            )
+        };
+        self.editor_scroll = (top, left);
+
+        let mut state: LineState = None;
+        if let Some(lang) = lang {
+            for line in lines.iter().take(top) {
+                carry_state(lang, line, &mut state);
+            }
+        }
+        let mut styled = |line: &String| match lang {
+            Some(lang) => highlighted_line(lang, line, &mut state, tab),
+            None => Line::raw(line.replace('\t', &" ".repeat(tab))),
+        };
+        let visible: Vec<Line> = if self.word_wrap {
+            lines
+                .iter()
+                .skip(top)
+                .flat_map(|line| wrap_styled_line(styled(line), width))
+                .take(height)
+                .collect()
+        } else {
+            lines.iter().skip(top).take(height).map(styled).collect()
+        };
+        f.render_widget(Paragraph::new(visible).scroll((0, left as u16)), inner);
+        if self.focus == Focus::Editor {
+            let (x, y) = if self.word_wrap {
+                let above: usize = lines[top..row]
+                    .iter()
+                    .map(|line| wrapped_rows(line, width, tab))
+                    .sum();
+                (display_col % width, above + display_col / width)
+            } else {
+                (display_col - left, row - top)
+            };
+            let y = y.min(height.saturating_sub(1));
+            f.set_cursor_position((inner.x + x as u16, inner.y + y as u16));
         }
-        true
     }
 
-    fn tools_env(&self) -> HashMap<String, String> {
-        self.core.tools_env()
-    }
+    fn draw_bottom(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
 
-    fn wheelhouse_path(&self) -> Option<PathBuf> {
-        self.core.wheelhouse_path()
+        self.draw_shell(f, chunks[0]);
+        self.draw_codex(f, chunks[1]);
     }
 
-    fn open_file(&mut self, path: PathBuf) {
-        let opened = match open_workspace_file(self.core.workspace(), path) {
-            Ok(opened) => opened,
-            Err(OpenWorkspaceFileError::Binary(path)) => {
-                self.log_issue(
-                    &format!("Binaire/non texte ignore: {}", path.display()),
-                    "avertissement",
-                    "ouverture_fichier",
-                    LogTarget::Main,
-                );
-                return;
-            }
-            Err(OpenWorkspaceFileError::Hidden(path)) => {
-                self.log_issue(
-                    &format!("Fichier interne masque: {}", path.display()),
-                    "avertissement",
-                    "ouverture_fichier",
-                    LogTarget::Main,
-                );
-                return;
-            }
-            Err(OpenWorkspaceFileError::Sensitive(path)) => {
-                self.log_issue(
-                    &format!("Fichier sensible protege: {}", path.display()),
-                    "erreur",
-                    "ouverture_fichier",
-                    LogTarget::Main,
-                );
-                return;
-            }
-            Err(err) => {
-                self.log_issue(
-                    &err.to_string(),
-                    "erreur",
-                    "ouverture_fichier",
-                    LogTarget::Main,
-                );
-                return;
-            }
+    fn draw_shell(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+        (self.areas.cmd, self.areas.log) = (chunks[0], chunks[1]);
+
+        let title = if self.core.interactive_shell().is_some() {
+            "Entree -> process en cours (Ctrl+D: EOF)".to_string()
+        } else {
+            format!(
+                "Commande ({})",
+                shell_cwd_label(&self.root_dir, &self.shell_cwd)
+            )
         };
+        let input_block = Self::block_with_focus(title.as_str(), self.focus == Focus::Cmd);
+        let input = Paragraph::new(self.cmd_input.value.as_str()).block(input_block);
+        f.render_widget(input, chunks[0]);
+        if self.focus == Focus::Cmd {
+            let cursor_x = chunks[0].x + 1 + self.cmd_input.cursor as u16;
+            let cursor_y = chunks[0].y + 1;
+            f.set_cursor_position((cursor_x, cursor_y));
+        }
 
-        let mut lines: Vec<String> = opened.text.lines().map(|s| s.to_string()).collect();
-        if lines.is_empty() {
-            lines.push(String::new());
+        let mut log_title = format!("Journal [{}]", self.log_filter.label());
+        if let Some(progress) = self.install_progress_label(LogTarget::Main) {
+            log_title.push_str(&format!(" - {progress}"));
         }
-        let mut editor = TextArea::from(lines);
-        editor.set_block(Block::default().borders(Borders::ALL).title("Editeur"));
-        self.editor = editor;
-        self.current = Some(OpenFile {
-            path: opened.path,
-            encoding: opened.encoding,
-            dirty: false,
-        });
-        self.refresh_title();
+        let (rows_up, column) = self.log_scroll;
+        if rows_up > 0 {
+            log_title.push_str(&scrollback_marker(rows_up));
+        }
+        if column > 0 && !self.word_wrap {
+            log_title.push_str(&format!(" [col {}]", column + 1));
+        }
+        let log_block = Self::block_with_focus(log_title, self.focus == Focus::Log);
+        let log_text = self.render_log(
+            LogTarget::Main,
+            chunks[1].height.saturating_sub(2) as usize,
+            rows_up,
+        );
+        f.render_widget(self.log_paragraph(log_text, log_block, column), chunks[1]);
     }
 
-    fn action_save(&mut self) {
-        let (path, encoding, dirty) = match self.current.as_ref() {
-            Some(current) => (
-                current.path.clone(),
-                current.encoding.clone(),
-                current.dirty,
-            ),
-            None => {
-                self.log_issue(
-                    "Aucun fichier ouvert.",
-                    "avertissement",
-                    "sauvegarde",
-                    LogTarget::Main,
-                );
-                return;
-            }
+    fn draw_codex(&mut self, f: &mut ratatui::Frame<'_>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+        (self.areas.codex, self.areas.codex_log) = (chunks[0], chunks[1]);
+
+        let title = if self.codex_continue_session {
+            "Codex (suite)"
+        } else {
+            "Codex"
         };
-        if !dirty {
-            return;
+        let input_block = Self::block_with_focus(title, self.focus == Focus::Codex);
+        let input = Paragraph::new(self.codex_input.value.as_str()).block(input_block);
+        f.render_widget(input, chunks[0]);
+        if self.focus == Focus::Codex {
+            let cursor_x = chunks[0].x + 1 + self.codex_input.cursor as u16;
+            let cursor_y = chunks[0].y + 1;
+            f.set_cursor_position((cursor_x, cursor_y));
         }
 
-        let content = self.editor.lines().join("\n");
-        let result = write_text_with_encoding(&path, &encoding, &content);
-        match result {
-            Ok(used_utf8_fallback) => {
-                if used_utf8_fallback {
-                    self.log_issue(
-                        &format!("Sauvegarde en UTF-8 (fallback) {}", path.display()),
-                        "avertissement",
-                        "sauvegarde",
-                        LogTarget::Main,
-                    );
-                } else {
-                    self.log_ui(format!("Sauvegarde {}", path.display()));
-                }
-                if let Some(current) = self.current.as_mut() {
-                    if used_utf8_fallback {
-                        current.encoding = "utf-8".to_string();
-                    }
-                    current.dirty = false;
-                }
-                self.refresh_title();
-            }
-            Err(err) => {
-                self.log_issue(
-                    &format!("Erreur sauvegarde: {} ({err})", path.display()),
-                    "erreur",
-                    "sauvegarde",
-                    LogTarget::Main,
-                );
-            }
+        let mut log_title = format!(
+            "Sortie Codex {} [{}]",
+            self.codex_view.label(),
+            self.codex_log_filter.label()
+        );
+        if let Some(started) = self.codex_status_started {
+            let elapsed = started.elapsed();
+            log_title.push_str(&format!(
+                " {} {}",
+                spinner_frame(elapsed),
+                codex_status_wait_label(elapsed)
+            ));
         }
-    }
-
-    fn action_run(&mut self) {
-        let (path, dirty) = match self.current.as_ref() {
-            Some(current) => (current.path.clone(), current.dirty),
-            None => {
-                self.log_issue(
-                    "Ouvre un fichier .py.",
-                    "avertissement",
-                    "execution_python",
-                    LogTarget::Main,
-                );
-                return;
-            }
-        };
-        let is_py = path
-            .extension()
-            .and_then(|s| s.to_str())
-            .map(|s| s.eq_ignore_ascii_case("py"))
-            .unwrap_or(false);
-        if !is_py {
-            self.log_issue(
-                "Ouvre un fichier .py.",
-                "avertissement",
-                "execution_python",
-                LogTarget::Main,
-            );
-            return;
+        let queued = self.core.codex_queue_len();
+        if queued > 0 {
+            log_title.push_str(&format!(" - en attente: {queued}"));
         }
-        if dirty {
-            self.action_save();
+        if let Some(progress) = self.install_progress_label(LogTarget::Codex) {
+            log_title.push_str(&format!(" - {progress}"));
         }
-        let argv = python_run_argv(&path);
-        self.log_ui(format!("$ {}", argv.join(" ")));
-        let env_map = self.portable_env(std::env::vars().collect());
-        self.spawn_process(
-            argv,
-            env_map,
-            "execution python",
-            LogTarget::Main,
-            ProcessKind::PythonRun,
+        if self.codex_log_scroll > 0 {
+            log_title.push_str(&scrollback_marker(self.codex_log_scroll));
+        }
+        let log_block = Block::default().borders(Borders::ALL).title(log_title);
+        self.last_codex_width = chunks[1].width;
+        let log_text = self.render_log(
+            LogTarget::Codex,
+            chunks[1].height.saturating_sub(2) as usize,
+            self.codex_log_scroll,
         );
+        f.render_widget(self.log_paragraph(log_text, log_block, 0), chunks[1]);
     }
 
-    fn action_clear_log(&mut self) {
-        self.log.clear();
-        self.codex_log.clear();
-        self.last_codex_message = None;
-        self.log_ui("journaux effaces".to_string());
-    }
-
-    fn action_reload_tree(&mut self) {
-        self.tree.reload(self.core.workspace());
-        self.log_ui("arborescence rechargee".to_string());
+    fn install_progress_label(&self, target: LogTarget) -> Option<&str> {
+        self.install_progress
+            .as_ref()
+            .filter(|(progress_target, _)| *progress_target == target)
+            .map(|(_, progress)| progress.as_str())
     }
 
-    fn action_toggle_codex_view(&mut self) {
-        self.codex_compact_view = !self.codex_compact_view;
-        self.last_codex_message = None;
-        let mode = if self.codex_compact_view {
-            "Compact"
+    /// Panneau de log : replie selon `word_wrap`, sinon decale a partir de la colonne `column`.
+    fn log_paragraph<'a>(&self, text: Text<'a>, block: Block<'a>, column: usize) -> Paragraph<'a> {
+        let paragraph = Paragraph::new(text).block(block);
+        if self.word_wrap {
+            paragraph.wrap(Wrap { trim: false })
         } else {
-            "Brut"
-        };
-        self.codex_log_ui(format!("Mode Codex: {mode}"));
+            paragraph.scroll((0, column.min(u16::MAX as usize) as u16))
+        }
     }
 
-    fn action_toggle_codex_sandbox(&mut self) {
-        self.codex_sandbox_mode = next_codex_sandbox_mode(self.codex_sandbox_mode);
-        self.codex_log_ui(format!(
-            "Sandbox Codex: {}",
-            codex_sandbox_label(self.codex_sandbox_mode)
-        ));
+    /// Lignes acceptees par le filtre, en remontant de `rows_up` depuis la fin
+    /// (le log complet reste en memoire) ; les resultats de la recherche sont surlignes.
+    fn render_log(&self, target: LogTarget, max_lines: usize, rows_up: usize) -> Text<'_> {
+        let shown = self.visible_log(target);
+        let search = self
+            .log_search
+            .as_ref()
+            .filter(|search| search.target == target);
+        let end = shown.len().saturating_sub(rows_up);
+        let start = end.saturating_sub(max_lines);
+        let lines: Vec<Line> = shown[start..end]
+            .iter()
+            .enumerate()
+            .map(|(offset, entry)| {
+                let highlight = match search {
+                    Some(search) if search.current == Some(start + offset) => {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    }
+                    Some(search) if search.is_match(&entry.text) => {
+                        Style::default().bg(Color::DarkGray)
+                    }
+                    _ => Style::default(),
+                };
+                let mut spans = if entry.runs.is_empty() {
+                    vec![Span::styled(
+                        entry.text.clone(),
+                        log_style(entry).patch(highlight),
+                    )]
+                } else {
+                    entry
+                        .runs
+                        .iter()
+                        .map(|(text, style)| Span::styled(text.clone(), style.patch(highlight)))
+                        .collect()
+                };
+                if entry.repeats > 1 {
+                    spans.push(Span::styled(
+                        repeat_suffix(entry.repeats),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        Text::from(lines)
     }
 
-    fn action_toggle_codex_approval(&mut self) {
-        self.codex_approval_policy = next_codex_approval_policy(self.codex_approval_policy);
-        self.codex_log_ui(format!(
-            "Approbations Codex: {}",
-            codex_approval_label(self.codex_approval_policy)
-        ));
+    /// Lignes du log acceptees par son filtre courant.
+    fn visible_log(&self, target: LogTarget) -> Vec<&LogLine> {
+        let (log, filter) = match target {
+            LogTarget::Main => (&self.log, self.log_filter),
+            LogTarget::Codex => (&self.codex_log, self.codex_log_filter),
+        };
+        log.iter()
+            .filter(|entry| filter.accepts(entry.kind.level()))
+            .collect()
     }
 
-    fn action_codex_install(&mut self) {
-        let _ = self.install_codex(true, LogTarget::Codex);
+    fn block_with_focus<'a>(title: impl Into<Line<'a>>, focused: bool) -> Block<'a> {
+        let style = if focused {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .style(style)
     }
 
-    fn codex_exec_extra_args(&self) -> Vec<String> {
-        codex_exec_extra_args(
-            self.codex_sandbox_supported,
-            self.codex_sandbox_mode,
-            self.codex_approval_supported,
-            self.codex_approval_policy,
-        )
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let quit = self.dispatch_key(key);
+        if self.current().is_some() {
+            // Garde "Ln/Col" a jour apres chaque touche (deplacement, recherche, saut...).
+            self.refresh_title();
+        }
+        quit || self.quit_requested
     }
 
-    fn handle_approval_flag_line(&mut self, line: &str) -> bool {
-        if !self.codex_exec_used_approval_flag || !crate::app_core::approval_flag_error(line) {
+    fn dispatch_key(&mut self, key: KeyEvent) -> bool {
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key);
             return false;
         }
-        if self.codex_approval_supported != Some(false) {
-            self.codex_approval_supported = Some(false);
-            self.codex_log_action(
-                "Option --ask-for-approval non supportee par cette version Codex. Relance sans approbations.",
-            );
+        // Ctrl+D est l'EOF du process en cours tant qu'il lit la saisie (sinon outils dev).
+        if self.focus == Focus::Cmd
+            && key.code == KeyCode::Char('d')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+            && self.core.interactive_shell().is_some()
+        {
+            self.send_shell_eof();
+            return false;
         }
-        self.codex_retry_without_approval = true;
-        true
-    }
-
-    fn handle_sandbox_flag_line(&mut self, line: &str) -> bool {
-        if !self.codex_exec_used_sandbox_flag {
+        if key.modifiers == KeyModifiers::ALT | KeyModifiers::SHIFT
+            && matches!(
+                key.code,
+                KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down
+            )
+        {
+            self.resize_panels(key.code);
             return false;
         }
-        if crate::app_core::sandbox_flag_error(line) || crate::app_core::sandbox_value_error(line) {
-            if self.codex_sandbox_supported != Some(false) {
-                self.codex_sandbox_supported = Some(false);
-                self.codex_log_action(
-                    "Option --sandbox non supportee par cette version Codex. Relance sans sandbox (mode par defaut).",
-                );
-            }
-            self.codex_retry_without_sandbox = true;
+        if self.handle_global_shortcut(key) {
             return true;
         }
+
+        match self.focus {
+            Focus::Tree => self.handle_tree_key(key),
+            Focus::Editor => self.handle_editor_key(key),
+            Focus::Cmd => self.handle_cmd_key(key),
+            Focus::Log => self.handle_log_key(key),
+            Focus::Codex => self.handle_codex_key(key),
+        }
+
         false
     }
 
-    fn action_codex_login(&mut self) {
-        let env_map = self.codex_env();
-        if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
-            if !self.ensure_node_available(&env_map, LogTarget::Codex) {
-                return;
+    fn handle_global_shortcut(&mut self, key: KeyEvent) -> bool {
+        if let Some(action) = key_chord(key).and_then(|chord| self.keymap.action_for(&chord)) {
+            return self.run_action(action);
+        }
+        match key.code {
+            // Dans l'editeur, Tab indente ; Maj+Tab en sort.
+            KeyCode::Tab if self.focus == Focus::Editor && key.modifiers.is_empty() => false,
+            // En mode zen, seul l'editeur est affiche : le focus y reste.
+            KeyCode::Tab | KeyCode::BackTab if self.zen_mode.is_some() => false,
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Tree => Focus::Editor,
+                    Focus::Editor => Focus::Cmd,
+                    Focus::Cmd => Focus::Log,
+                    Focus::Log => Focus::Codex,
+                    Focus::Codex => Focus::Tree,
+                };
+                false
             }
-            if !self.install_codex(false, LogTarget::Codex) {
-                return;
+            KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Tree => Focus::Codex,
+                    Focus::Editor => Focus::Tree,
+                    Focus::Cmd => Focus::Editor,
+                    Focus::Log => Focus::Cmd,
+                    Focus::Codex => Focus::Log,
+                };
+                false
             }
+            _ => false,
         }
-        self.codex_log_ui("Login Codex : navigateur/Device auth selon config.".to_string());
-        if !self.codex_device_auth_enabled() {
-            self.codex_log_ui(
-                "Astuce: si le navigateur ne s'ouvre pas, definis USBIDE_CODEX_DEVICE_AUTH=1 puis relance Ctrl+K."
-                    .to_string(),
-            );
-        }
-        let argv = codex_login_argv(
-            Some(&self.root_dir),
-            Some(&env_map),
-            self.codex_device_auth_enabled(),
-        );
-        self.codex_log_ui(format!("$ {}", argv.join(" ")));
-        self.spawn_process(
-            argv,
-            env_map,
-            "login Codex",
-            LogTarget::Codex,
-            ProcessKind::CodexLogin,
-        );
     }
 
-    fn action_codex_check(&mut self) {
-        let env_map = self.codex_env();
-        if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
-            if !self.ensure_node_available(&env_map, LogTarget::Codex) {
-                return;
+    /// Action d'un raccourci du keymap ; vrai pour quitter.
+    fn run_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Quit => return self.confirm_discard(PendingAction::Quit),
+            Action::Save => self.action_save(),
+            Action::Run => self.action_run(),
+            Action::RunSelection => self.action_run_selection(),
+            Action::CodexStop => self.action_codex_stop(),
+            Action::ClearLog => self.action_clear_log(),
+            Action::ClearMainLog => self.clear_log(LogTarget::Main),
+            Action::ClearCodexLog => self.clear_log(LogTarget::Codex),
+            Action::FindInLog => self.action_find_in_log(),
+            Action::ToggleZenMode => self.action_toggle_zen_mode(),
+            Action::ClearCodexQueue => {
+                if !self.clear_codex_queue() {
+                    self.codex_log_ui("File d'attente Codex vide.".to_string());
+                }
             }
-            self.log_issue(
-                "Codex non installe.",
-                "avertissement",
-                "codex_status",
-                LogTarget::Codex,
-            );
-            return;
+            Action::ApplyCodexPatch => self.action_apply_codex_patch(),
+            Action::OpenContainingFolder => {
+                let dir = target_dir_for(self.tree.selected_entry(), &self.root_dir);
+                self.open_in_file_manager(&dir);
+            }
+            Action::ClearCaches => self.action_clear_caches(),
+            Action::EnvInspector => self.action_env_inspector(),
+            Action::PythonRepl => self.action_python_repl(),
+            Action::SelectPython => self.action_select_python(),
+            Action::CollapseTree => self.action_fold_tree(false),
+            Action::ExpandTree => self.action_fold_tree(true),
+            Action::ExportLogs => self.action_export_logs(),
+            Action::ReloadTree => self.action_reload_tree(),
+            Action::CodexLogin => self.action_codex_login(),
+            Action::CodexLogout => self.action_codex_logout(),
+            Action::KillAllProcesses => self.action_kill_all_processes(),
+            Action::CodexCheck => self.action_codex_check(),
+            Action::CodexInstall => self.action_codex_install(),
+            Action::ToggleCodexView => self.action_toggle_codex_view(),
+            Action::ToggleCodexSandbox => self.action_toggle_codex_sandbox(),
+            Action::ToggleCodexApproval => self.action_toggle_codex_approval(),
+            Action::ToggleCodexSession => self.action_toggle_codex_session(),
+            Action::QuickOpen => self.action_quick_open(),
+            Action::BuildExe => self.action_build_exe(),
+            Action::DevTools => self.action_dev_tools(),
+            Action::Search => self.action_search(),
+            Action::Replace => self.action_replace(),
+            Action::GotoLine => self.open_prompt(PromptKind::GotoLine, "Aller a la ligne"),
+            Action::NewFile => self.action_new_entry(false),
+            Action::NewFolder => self.action_new_entry(true),
+            Action::ToggleLineEnding => self.action_toggle_line_ending(),
+            Action::InstallRequirements => self.action_install_requirements(),
+            Action::CreateVenv => self.action_create_venv(),
+            Action::ToggleOnefile => self.action_toggle_build_onefile(),
+            Action::RevealCurrent => self.action_reveal_current(),
+            Action::ToggleShowHidden => self.action_toggle_show_hidden(),
+            Action::ToggleShowIgnored => self.action_toggle_show_ignored(),
+            Action::CopyLastAssistant => self.action_copy_last_assistant(),
+            Action::CopyCodexLog => self.action_copy_codex_log(),
+            Action::CycleLogFilter => {
+                let target = if self.focus == Focus::Codex {
+                    LogTarget::Codex
+                } else {
+                    LogTarget::Main
+                };
+                self.action_cycle_log_filter(target);
+            }
+            Action::CycleCodexLogFilter => self.action_cycle_log_filter(LogTarget::Codex),
+            Action::ToggleWordWrap => self.action_toggle_word_wrap(),
+            Action::Settings => self.action_settings(),
+            Action::NextTab => self.action_cycle_tab(true),
+            Action::PrevTab => self.action_cycle_tab(false),
+            Action::CloseTab => self.action_close_tab(),
+            Action::Format => self.action_format(),
+            Action::Lint => self.action_lint(),
+            Action::Diagnostics => self.action_diagnostics(),
+            Action::EnvironmentReport => self.action_environment_report(),
+            Action::RunTests => self.action_run_tests(false),
+            Action::RerunFailedTests => self.action_run_tests(true),
+            Action::StopTests => self.action_stop_tests(),
+            Action::GitCommit => self.action_git_commit(),
+            Action::GitDiff => self.action_git_diff(),
+            Action::DiffWithDisk => self.action_diff_with_disk(),
+            Action::AskCodexAboutFile => self.action_ask_codex_about_file(),
+            Action::ExplainSelection => self.action_explain_selection(),
+            Action::ReopenWithEncoding => self.action_encoding_prompt(PromptKind::ReopenEncoding),
+            Action::SaveWithEncoding => self.action_encoding_prompt(PromptKind::SaveEncoding),
+            Action::CommandPalette => self.action_command_palette(),
         }
-        let node_path = node_executable(&self.root_dir, Some(&env_map));
-        let entry_path = codex_entrypoint_js(&codex_install_prefix(&self.root_dir));
-        let resolved = resolve_in_path("codex", &env_map);
-        self.codex_log_ui(format!(
-            "node: {}",
-            node_path
-                .map(|p| p.display().to_string())
-                .unwrap_or("absent".into())
-        ));
-        self.codex_log_ui(format!(
-            "entrypoint: {}",
-            entry_path
-                .map(|p| p.display().to_string())
-                .unwrap_or("absent".into())
-        ));
-        self.codex_log_ui(format!(
-            "codex (PATH): {}",
-            resolved
-                .map(|p| p.display().to_string())
-                .unwrap_or("absent".into())
-        ));
-        let argv = codex_status_argv(Some(&self.root_dir), Some(&env_map));
-        self.codex_log_ui(format!("$ {}", argv.join(" ")));
-        self.spawn_process(
-            argv,
-            env_map,
-            "verification Codex",
-            LogTarget::Codex,
-            ProcessKind::CodexStatus,
-        );
+        false
     }
 
-    fn action_dev_tools(&mut self) {
-        let raw = std::env::var("USBIDE_DEV_TOOLS")
-            .unwrap_or_else(|_| "ruff black mypy pytest".to_string());
-        let tools = parse_tool_list(&raw);
-        if tools.is_empty() {
-            self.log_issue(
-                "Liste outils vide.",
-                "avertissement",
-                "outils_dev",
-                LogTarget::Main,
-            );
+    fn handle_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        if let PromptKind::UnsavedChanges(action) = &prompt.kind {
+            let action = action.clone();
+            self.prompt = None;
+            match key.code {
+                KeyCode::Char('o' | 'O' | 'y' | 'Y') => self.resolve_unsaved(action, true),
+                KeyCode::Char('n' | 'N') => self.resolve_unsaved(action, false),
+                _ => {}
+            }
             return;
         }
-        let env_map = self.tools_env();
-        let prefix = tools_install_prefix(&self.root_dir);
-        let _ = fs::create_dir_all(&prefix);
-        let wheelhouse = self.wheelhouse_path();
-        let argv =
-            match pip_install_argv(&prefix, &tools, wheelhouse.as_deref(), wheelhouse.is_some()) {
-                Ok(argv) => argv,
-                Err(err) => {
-                    self.log_issue(
-                        &format!("Impossible d'installer outils: {err}"),
-                        "erreur",
-                        "outils_dev",
-                        LogTarget::Main,
-                    );
-                    return;
-                }
-            };
-        self.log_ui(format!("$ {}", argv.join(" ")));
-        self.spawn_process(
-            argv,
-            env_map,
-            "installation outils dev",
-            LogTarget::Main,
-            ProcessKind::DevTools,
-        );
-    }
-
-    fn action_build_exe(&mut self) {
-        let (path, dirty) = match self.current.as_ref() {
-            Some(current) => (current.path.clone(), current.dirty),
-            None => {
-                self.log_issue(
-                    "Ouvre un fichier .py.",
-                    "avertissement",
-                    "build_exe",
-                    LogTarget::Main,
-                );
-                return;
+        if let PromptKind::RecoverAutosave(path) = &prompt.kind {
+            let path = path.clone();
+            self.prompt = None;
+            match key.code {
+                KeyCode::Char('o' | 'O' | 'y' | 'Y') => self.restore_autosave(&path),
+                KeyCode::Char('n' | 'N') => self.discard_autosave(&path),
+                _ => {}
             }
-        };
-        let is_py = path
-            .extension()
-            .and_then(|s| s.to_str())
-            .map(|s| s.eq_ignore_ascii_case("py"))
-            .unwrap_or(false);
-        if !is_py {
-            self.log_issue(
-                "Ouvre un fichier .py.",
-                "avertissement",
-                "build_exe",
-                LogTarget::Main,
-            );
             return;
         }
-        if dirty {
-            self.action_save();
+        if let PromptKind::DeletedOnDisk(path) = &prompt.kind {
+            let path = path.clone();
+            self.prompt = None;
+            match key.code {
+                KeyCode::Char('o' | 'O' | 'y' | 'Y') => self.recreate_deleted(&path),
+                KeyCode::Char('f' | 'F') => self.close_deleted(&path),
+                _ => {}
+            }
+            return;
         }
-        let env_map = self.tools_env();
-        if !pyinstaller_available(Some(&self.root_dir), Some(&env_map))
-            && !self.install_pyinstaller(false)
-        {
-            self.log_issue(
-                "PyInstaller indisponible.",
-                "erreur",
-                "build_exe",
-                LogTarget::Main,
-            );
+        if prompt.kind.is_confirmation() {
+            let kind = prompt.kind.clone();
+            self.prompt = None;
+            if matches!(key.code, KeyCode::Char('o' | 'O' | 'y' | 'Y')) {
+                self.submit_prompt(kind, String::new());
+            }
             return;
         }
-        let dist_dir = self.root_dir.join("dist");
-        let _ = fs::create_dir_all(&dist_dir);
-        let argv = match pyinstaller_build_argv(
-            &path,
-            &dist_dir,
-            false,
-            Some(&self.root_dir.join("tmp")),
-            None,
-        ) {
-            Ok(argv) => argv,
-            Err(err) => {
-                self.log_issue(
-                    &format!("Erreur build: {err}"),
-                    "erreur",
-                    "build_exe",
-                    LogTarget::Main,
-                );
-                return;
+        if prompt.kind.is_search() {
+            self.handle_search_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::QuickOpen {
+            self.handle_quick_open_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::CommandPalette {
+            self.handle_palette_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::DevTools {
+            self.handle_dev_tools_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::EnvironmentReport {
+            self.handle_environment_report_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::Diagnostics {
+            self.handle_diagnostics_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::LogSearch {
+            self.handle_log_search_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::PythonPicker {
+            self.handle_python_picker_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::Setup {
+            self.handle_setup_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::EnvInspector {
+            self.handle_env_inspector_key(key);
+            return;
+        }
+        if prompt.kind == PromptKind::Repl {
+            self.handle_repl_key(key);
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.prompt = None;
             }
-        };
-        self.log_ui(format!("$ {}", argv.join(" ")));
-        self.spawn_process(
-            argv,
-            env_map,
-            "construction exe",
-            LogTarget::Main,
-            ProcessKind::PyInstallerBuild,
-        );
+            KeyCode::Enter => {
+                let value = prompt.input.value.trim().to_string();
+                let kind = prompt.kind.clone();
+                self.prompt = None;
+                self.submit_prompt(kind, value);
+            }
+            _ => {
+                prompt.input.handle_key(key);
+            }
+        }
     }
 
-    fn install_pyinstaller(&mut self, force: bool) -> bool {
-        let env_map = self.tools_env();
-        if !force && pyinstaller_available(Some(&self.root_dir), Some(&env_map)) {
-            return true;
-        }
-        if !force && self.core.pyinstaller_install_attempted {
-            return false;
-        }
-        self.core.pyinstaller_install_attempted = true;
-        let prefix = tools_install_prefix(&self.root_dir);
-        let _ = fs::create_dir_all(&prefix);
-        let wheelhouse = self.wheelhouse_path();
-        let argv =
-            match pyinstaller_install_argv(&prefix, wheelhouse.as_deref(), wheelhouse.is_some()) {
-                Ok(argv) => argv,
-                Err(err) => {
-                    self.log_issue(
-                        &format!("Impossible d'installer PyInstaller: {err}"),
-                        "erreur",
-                        "installation_pyinstaller",
-                        LogTarget::Main,
-                    );
-                    return false;
-                }
-            };
-        self.log_ui(format!(
-            "Installation PyInstaller (bin={})",
-            prefix.display()
-        ));
-        self.log_ui(format!("$ {}", argv.join(" ")));
-        self.spawn_process(
-            argv,
-            env_map,
-            "installation PyInstaller",
-            LogTarget::Main,
-            ProcessKind::PyInstallerInstall,
-        );
-        true
+    fn open_prompt(&mut self, kind: PromptKind, label: &str) {
+        self.open_prompt_with(kind, label, "");
     }
 
-    fn codex_device_auth_enabled(&self) -> bool {
-        self.core.codex_device_auth_enabled()
+    fn open_prompt_with(&mut self, kind: PromptKind, label: &str, initial: &str) {
+        self.prompt = Some(Prompt {
+            kind,
+            label: label.to_string(),
+            input: InputField::with_value(initial),
+        });
     }
 
-    fn codex_auto_install_enabled(&self) -> bool {
-        self.core.codex_auto_install_enabled()
+    fn submit_prompt(&mut self, kind: PromptKind, value: String) {
+        match kind {
+            PromptKind::NewFile(parent) => self.create_tree_entry(parent, &value, false),
+            PromptKind::NewFolder(parent) => self.create_tree_entry(parent, &value, true),
+            PromptKind::Rename(path) => self.rename_tree_entry(path, &value),
+            PromptKind::ConfirmDelete(path) => self.delete_tree_entry(path),
+            PromptKind::ConfirmClearCaches => self.clear_caches(),
+            PromptKind::ConfirmShell(cmd) => self.spawn_shell(cmd),
+            PromptKind::RevealFolder(dir) => self.open_in_file_manager(&dir),
+            PromptKind::ApplyCodexPatch(patch) => self.apply_codex_patch(patch),
+            PromptKind::OpenAnyway(path) => self.force_open(path),
+            PromptKind::ReopenEncoding => self.reopen_with_encoding(&value),
+            PromptKind::SaveEncoding => self.save_with_encoding(&value, false),
+            PromptKind::ConfirmLossySave(encoding) => self.save_with_encoding(&encoding, true),
+            PromptKind::ReplaceFind => self.open_replace(value),
+            PromptKind::GotoLine => self.goto_line(&value),
+            PromptKind::GitCommit => self.git_commit(&value),
+            PromptKind::ConfirmDangerSandbox(prompt) => self.run_codex(prompt),
+            PromptKind::CodexQuestion(source, content) => {
+                self.ask_codex_with_context(&source, &content, &value);
+            }
+            PromptKind::Search
+            | PromptKind::Replace(_)
+            | PromptKind::QuickOpen
+            | PromptKind::CommandPalette
+            | PromptKind::Diagnostics
+            | PromptKind::DevTools
+            | PromptKind::EnvironmentReport
+            | PromptKind::LogSearch
+            | PromptKind::PythonPicker
+            | PromptKind::Setup
+            | PromptKind::EnvInspector
+            | PromptKind::Repl
+            | PromptKind::UnsavedChanges(_)
+            | PromptKind::RecoverAutosave(_)
+            | PromptKind::DeletedOnDisk(_) => {}
+        }
     }
 
-    fn install_codex(&mut self, force: bool, target: LogTarget) -> bool {
-        let env_map = self.codex_env();
-        if !force && codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
-            return true;
+    fn action_quick_open(&mut self) {
+        if self.file_index.is_none() {
+            self.file_index = Some(FileIndex::scan(self.core.workspace()));
         }
-        if !force && self.core.codex_install_attempted {
-            self.log_issue(
-                "Installation Codex deja tentee. (Ctrl+I pour forcer)",
-                "avertissement",
-                "installation_codex",
-                target,
-            );
-            return false;
+        self.quick_open_selected = 0;
+        self.open_prompt(PromptKind::QuickOpen, "Ouvrir");
+    }
+
+    /// Fichiers proposes pour la saisie courante du prompt Ctrl+P.
+    fn quick_open_matches(&self) -> Vec<&str> {
+        let (Some(prompt), Some(index)) = (&self.prompt, &self.file_index) else {
+            return Vec::new();
+        };
+        if prompt.kind != PromptKind::QuickOpen {
+            return Vec::new();
         }
-        if !force && !self.codex_auto_install_enabled() {
-            self.log_issue(
-                "Auto-install Codex desactive. (Ctrl+I pour installer)",
-                "avertissement",
-                "installation_codex",
-                target,
-            );
-            return false;
-        }
-        if !self.ensure_node_available(&env_map, target) {
-            return false;
-        }
-        self.core.codex_install_attempted = true;
-        let package = std::env::var("USBIDE_CODEX_NPM_PACKAGE")
-            .unwrap_or_else(|_| "@openai/codex".to_string());
-        let prefix = codex_install_prefix(&self.root_dir);
-        if let Err(err) = fs::create_dir_all(&prefix) {
-            self.log_issue(
-                &format!(
-                    "Impossible de creer le dossier d'installation Codex: {} ({err})",
-                    prefix.display()
-                ),
-                "erreur",
-                "installation_codex",
-                target,
-            );
-            return false;
-        }
-        let argv = match codex_install_argv(&self.root_dir, &prefix, &package) {
-            Ok(argv) => argv,
-            Err(CodexError::NodeMissing) => {
-                self.log_issue(
-                    "Node portable introuvable. Place node dans tools/node (ex: node.exe). Fallback Node hote possible via USBIDE_CODEX_ALLOW_HOST_NODE=1.",
-                    "erreur",
-                    "installation_codex",
-                    target,
-                );
-                return false;
+        index.matches(&prompt.input.value, QUICK_OPEN_RESULTS)
+    }
+
+    /// Ctrl+Alt+F : cherche dans le log du panneau actif (Sortie Codex depuis Codex, sinon Journal).
+    fn action_find_in_log(&mut self) {
+        let target = if self.focus == Focus::Codex {
+            LogTarget::Codex
+        } else {
+            LogTarget::Main
+        };
+        self.log_search = Some(LogSearch::new(target));
+        self.open_prompt(PromptKind::LogSearch, "");
+        self.refresh_log_search();
+    }
+
+    /// Saisie : recherche en direct ; Entree/Bas suivant, Maj+Entree/Haut precedent, Alt+C casse.
+    fn handle_log_search_key(&mut self, key: KeyEvent) {
+        let Some(search) = self.log_search.as_mut() else {
+            self.prompt = None;
+            return;
+        };
+        let forward = match key.code {
+            KeyCode::Esc => {
+                self.prompt = None;
+                self.log_search = None;
+                return;
             }
-            Err(CodexError::NpmMissing) => {
-                self.log_issue(
-                    "npm-cli.js introuvable. Verifie ton Node portable (npm inclus).",
-                    "erreur",
-                    "installation_codex",
-                    target,
-                );
-                return false;
+            KeyCode::Enter => Some(!key.modifiers.contains(KeyModifiers::SHIFT)),
+            KeyCode::Down => Some(true),
+            KeyCode::Up => Some(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::ALT) => {
+                search.case_sensitive = !search.case_sensitive;
+                search.current = None;
+                Some(false)
             }
-            Err(err) => {
-                self.log_issue(
-                    &format!("Impossible d'installer Codex: {err}"),
-                    "erreur",
-                    "installation_codex",
-                    target,
-                );
-                return false;
+            _ => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.input.handle_key(key);
+                    search.query = prompt.input.value.clone();
+                }
+                search.current = None;
+                Some(false)
             }
         };
-        self.push_log(
-            target,
-            format!(
-                "Installation Codex package={package} prefix={}",
-                prefix.display()
-            ),
-            Style::default(),
-        );
-        self.push_log(target, format!("$ {}", argv.join(" ")), Style::default());
-        self.spawn_process(
-            argv,
-            env_map,
-            "installation Codex",
-            target,
-            ProcessKind::CodexInstall,
-        );
-        true
+        if let Some(forward) = forward {
+            let matches = self.log_search_matches();
+            if let Some(search) = self.log_search.as_mut() {
+                search.step(&matches, forward);
+            }
+        }
+        self.refresh_log_search();
     }
 
-    fn run_shell(&mut self, cmd: String) {
-        if cmd.is_empty() {
-            return;
-        }
-        self.log_ui(format!("$ {cmd}"));
-        let argv = if cfg!(windows) {
-            windows_cmd_argv(&cmd)
-        } else {
-            vec!["sh".to_string(), "-lc".to_string(), cmd]
+    fn log_search_matches(&self) -> Vec<usize> {
+        let Some(search) = self.log_search.as_ref() else {
+            return Vec::new();
         };
-        let env_map = self.portable_env(std::env::vars().collect());
-        self.spawn_process(
-            argv,
-            env_map,
-            "commande shell",
-            LogTarget::Main,
-            ProcessKind::Shell,
-        );
+        search.matches(
+            self.visible_log(search.target)
+                .iter()
+                .map(|entry| entry.text.as_str()),
+        )
     }
 
-    fn run_codex(&mut self, prompt: String) {
-        if prompt.is_empty() {
+    /// Libelle de la saisie (panneau, casse, position) et defilement jusqu'au resultat courant.
+    fn refresh_log_search(&mut self) {
+        let Some(search) = self.log_search.as_ref() else {
             return;
+        };
+        let matches = self.log_search_matches();
+        let panel = match search.target {
+            LogTarget::Main => "Journal",
+            LogTarget::Codex => "Sortie Codex",
+        };
+        let case = if search.case_sensitive {
+            "casse respectee"
+        } else {
+            "casse ignoree"
+        };
+        let status = search.status(&matches);
+        let mut label = format!("Chercher dans {panel} ({case}, Alt+C)");
+        if !status.is_empty() {
+            label.push_str(&format!(" {status}"));
         }
-        if self.codex_compact_view {
-            self.codex_log_user_message(&prompt);
+        let (target, current) = (search.target, search.current);
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.label = label;
         }
-        let env_map = self.codex_env();
-        if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
-            if !self.ensure_node_available(&env_map, LogTarget::Codex) {
-                return;
-            }
-            if self.install_codex(false, LogTarget::Codex) {
-                self.pending_codex_prompt = Some(prompt);
+        if let Some(current) = current {
+            let rows_up = self.visible_log_len(target).saturating_sub(current + 1);
+            match target {
+                LogTarget::Main => self.log_scroll.0 = rows_up,
+                LogTarget::Codex => self.codex_log_scroll = rows_up,
             }
-            return;
         }
+    }
 
-        if !self.codex_caps_checked {
-            self.pending_codex_prompt = Some(prompt);
-            if self.codex_caps_running {
-                return;
+    fn handle_quick_open_key(&mut self, key: KeyEvent) {
+        let count = self.quick_open_matches().len();
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Up => self.quick_open_selected = self.quick_open_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.quick_open_selected =
+                    (self.quick_open_selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                let choice = self
+                    .quick_open_matches()
+                    .get(self.quick_open_selected)
+                    .map(|rel| self.root_dir.join(rel));
+                self.prompt = None;
+                if let Some(path) = choice {
+                    // Ouvrir d'abord : un fichier cache n'est atteignable qu'une fois ouvert.
+                    self.open_file(path.clone());
+                    if let Some(idx) = self.tree.data.reveal(&path) {
+                        self.tree.state.select(Some(idx));
+                    }
+                }
+            }
+            _ => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.input.handle_key(key);
+                }
+                self.quick_open_selected = 0;
             }
-            self.codex_caps_running = true;
-            self.codex_caps_buffer.clear();
-            let argv = codex_exec_help_argv(Some(&self.root_dir), Some(&env_map));
-            self.spawn_process(
-                argv,
-                env_map,
-                "codex_caps",
-                LogTarget::Codex,
-                ProcessKind::CodexCaps,
-            );
-            return;
         }
+    }
 
-        self.pending_codex_prompt = Some(prompt);
-        let argv = codex_status_argv(Some(&self.root_dir), Some(&env_map));
-        self.spawn_process(
-            argv,
-            env_map,
-            "codex_status",
-            LogTarget::Codex,
-            ProcessKind::CodexStatus,
-        );
+    fn action_command_palette(&mut self) {
+        self.palette_selected = 0;
+        self.open_prompt(PromptKind::CommandPalette, "Commande");
     }
 
-    fn spawn_process(
-        &mut self,
-        argv: Vec<String>,
-        env_map: HashMap<String, String>,
-        contexte: &str,
-        target: LogTarget,
-        kind: ProcessKind,
-    ) {
-        match NativeProcessRunner.spawn(&argv, Some(&self.root_dir), Some(&env_map)) {
-            Ok(handle) => {
-                self.core.running.push(RunningProcess {
-                    handle,
-                    kind,
-                    target,
-                    contexte: contexte.to_string(),
-                });
-            }
-            Err(err) => {
-                self.log_issue(
-                    &format!("Erreur execution {contexte}: {err}"),
-                    "erreur",
-                    contexte,
-                    target,
-                );
+    fn palette_matches(&self) -> Vec<Action> {
+        match &self.prompt {
+            Some(prompt) if prompt.kind == PromptKind::CommandPalette => {
+                palette_matches(&prompt.input.value)
             }
+            _ => Vec::new(),
         }
     }
 
-    fn drain_process_events(&mut self) {
-        let mut active = std::mem::take(&mut self.core.running);
-        let mut remaining = Vec::new();
-
-        for mut proc in active.drain(..) {
-            let mut finished = false;
-            while let Ok(event) = proc.handle.rx.try_recv() {
-                match event.kind {
-                    ProcEventKind::Line => {
-                        self.handle_process_line(&mut proc, &event.text);
-                    }
-                    ProcEventKind::Exit => {
-                        if let Some(code) = event.returncode
-                            && code != 0
-                        {
-                            let should_log = match proc.kind {
-                                ProcessKind::CodexExec => {
-                                    !(self.codex_retry_without_sandbox
-                                        || self.codex_retry_without_approval)
-                                }
-                                ProcessKind::CodexCaps => false,
-                                _ => true,
-                            };
-                            if should_log {
-                                self.log_issue(
-                                    &format!("{} terminee en erreur (rc={code}).", proc.contexte),
-                                    "erreur",
-                                    &proc.contexte,
-                                    proc.target,
-                                );
-                            }
-                        }
-                        self.handle_process_exit(&mut proc, event.returncode);
-                        finished = true;
-                        break;
-                    }
+    fn handle_palette_key(&mut self, key: KeyEvent) {
+        let count = self.palette_matches().len();
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Up => self.palette_selected = self.palette_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.palette_selected = (self.palette_selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                let choice = self.palette_matches().get(self.palette_selected).copied();
+                self.prompt = None;
+                if let Some(action) = choice
+                    && self.run_action(action)
+                {
+                    self.quit_requested = true;
+                }
+            }
+            _ => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.input.handle_key(key);
                 }
+                self.palette_selected = 0;
             }
+        }
+    }
 
-            if finished {
-                proc.handle.join();
-            } else {
-                remaining.push(proc);
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        let kind = prompt.kind.clone();
+        match (kind, key.code) {
+            (_, KeyCode::Esc) => {
+                self.prompt = None;
+                let _ = self.editor_mut().set_search_pattern("");
+            }
+            (PromptKind::Search, KeyCode::Enter) => {
+                let query = prompt.input.value.clone();
+                let found = if key.modifiers.contains(KeyModifiers::SHIFT) {
+                    self.editor_mut().search_back(false)
+                } else {
+                    self.editor_mut().search_forward(false)
+                };
+                if !found {
+                    self.log_ui(format!("Aucune occurrence: {query}"));
+                }
+            }
+            (PromptKind::Search, _) => {
+                let before = prompt.input.value.clone();
+                prompt.input.handle_key(key);
+                let query = prompt.input.value.clone();
+                if query != before {
+                    self.set_search_query(&query);
+                    self.editor_mut().search_forward(true);
+                }
+            }
+            (PromptKind::Replace(query), KeyCode::Enter) => {
+                let replacement = prompt.input.value.clone();
+                if !self.replace_next(&query, &replacement) {
+                    self.log_ui(format!("Aucune occurrence: {query}"));
+                }
+            }
+            (PromptKind::Replace(query), KeyCode::Char('a'))
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                let replacement = prompt.input.value.clone();
+                self.prompt = None;
+                let count = self.replace_all(&query, &replacement);
+                self.log_ui(format!("{count} remplacement(s): {query} -> {replacement}"));
+                let _ = self.editor_mut().set_search_pattern("");
+            }
+            _ => {
+                prompt.input.handle_key(key);
             }
         }
+    }
 
-        let mut spawned = std::mem::take(&mut self.core.running);
-        remaining.append(&mut spawned);
-        self.core.running = remaining;
+    fn action_search(&mut self) {
+        self.focus = Focus::Editor;
+        self.open_prompt(
+            PromptKind::Search,
+            "Rechercher (Entree: suivant, Maj+Entree: precedent)",
+        );
     }
 
-    fn handle_process_line(&mut self, proc: &mut RunningProcess, line: &str) {
-        match proc.kind {
-            ProcessKind::CodexExec => self.handle_codex_line(line),
-            ProcessKind::CodexCaps => self.handle_codex_caps_line(line),
-            _ => self.push_log(proc.target, line.to_string(), Style::default()),
+    fn action_replace(&mut self) {
+        self.focus = Focus::Editor;
+        self.open_prompt(PromptKind::ReplaceFind, "Remplacer: texte a chercher");
+    }
+
+    fn open_replace(&mut self, query: String) {
+        self.set_search_query(&query);
+        self.editor_mut().search_forward(true);
+        let label = format!("Remplacer '{query}' par (Entree: suivant, Ctrl+A: tout)");
+        self.open_prompt(PromptKind::Replace(query), &label);
+    }
+
+    /// Recherche litterale : le texte saisi est echappe avant de devenir une regex.
+    fn set_search_query(&mut self, query: &str) {
+        let _ = self.editor_mut().set_search_pattern(regex::escape(query));
+    }
+
+    fn replace_next(&mut self, query: &str, replacement: &str) -> bool {
+        if query.is_empty() || !self.current_editable() {
+            return false;
+        }
+        self.set_search_query(query);
+        if !self.editor_mut().search_forward(true) {
+            return false;
         }
+        self.editor_mut().delete_str(query.chars().count());
+        self.editor_mut().insert_str(replacement);
+        self.mark_dirty();
+        true
     }
 
-    fn handle_process_exit(&mut self, proc: &mut RunningProcess, code: Option<i32>) {
-        match proc.kind {
-            ProcessKind::CodexStatus => {
-                if let Some(prompt) = self.pending_codex_prompt.take() {
-                    if code == Some(0) {
-                        let env_map = self.codex_env();
-                        let extra_args = self.codex_exec_extra_args();
-                        self.codex_exec_used_sandbox_flag =
-                            extra_args.iter().any(|arg| arg == "--sandbox");
-                        self.codex_exec_used_approval_flag =
-                            extra_args.iter().any(|arg| arg == "--ask-for-approval");
-                        self.codex_last_prompt = Some(prompt.clone());
-                        match codex_exec_argv(
-                            &prompt,
-                            Some(&self.root_dir),
-                            Some(&env_map),
-                            true,
-                            Some(&extra_args),
-                        ) {
-                            Ok(argv) => {
-                                if !self.codex_compact_view {
-                                    self.codex_log_ui(format!("$ {}", argv.join(" ")));
-                                }
-                                self.spawn_process(
-                                    argv,
-                                    env_map,
-                                    "codex_exec",
-                                    LogTarget::Codex,
-                                    ProcessKind::CodexExec,
-                                );
-                            }
-                            Err(err) => {
-                                self.log_issue(
-                                    &format!("Erreur Codex: {err}"),
-                                    "erreur",
-                                    "codex_exec",
-                                    LogTarget::Codex,
-                                );
-                            }
-                        }
-                    } else {
-                        self.codex_log_action(
-                            "Echec de la verification du login Codex (status en erreur).",
-                        );
-                        self.codex_log_action(
-                            "Si tu n'es pas authentifie, fais Login puis recommence.",
-                        );
-                        self.codex_log_action(
-                            "Si tu es deja authentifie, verifie l'installation et la connexion.",
-                        );
-                        if !self.codex_device_auth_enabled() {
-                            self.codex_log_action(
-                                "Astuce: si le navigateur ne s'ouvre pas, definis USBIDE_CODEX_DEVICE_AUTH=1 puis Ctrl+K.",
-                            );
-                        }
-                    }
-                }
-            }
-            ProcessKind::CodexCaps => {
-                self.codex_caps_running = false;
-                self.codex_caps_checked = true;
-                let lower = self.codex_caps_buffer.to_lowercase();
-                if !lower.is_empty() {
-                    if !lower.contains("--sandbox") {
-                        self.codex_sandbox_supported = Some(false);
-                    }
-                    if !lower.contains("--ask-for-approval") {
-                        self.codex_approval_supported = Some(false);
-                    }
-                    if self.codex_sandbox_supported == Some(false)
-                        || self.codex_approval_supported == Some(false)
-                    {
-                        self.codex_log_action(
-                            "Version Codex ancienne: sandbox/approbations indisponibles. Mets a jour pour un mode agent complet.",
-                        );
-                    }
-                }
-                self.codex_caps_buffer.clear();
-                if let Some(prompt) = self.pending_codex_prompt.take() {
-                    self.run_codex(prompt);
+    fn replace_all(&mut self, query: &str, replacement: &str) -> usize {
+        if !self.current_editable() {
+            return 0;
+        }
+        let (lines, count) = replace_all_in_lines(self.editor().lines(), query, replacement);
+        if count == 0 {
+            return 0;
+        }
+        let (row, col) = self.editor().cursor();
+        self.editor_mut().select_all();
+        self.editor_mut().insert_str(lines.join("\n"));
+        self.editor_mut()
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+        // Lignes modifiees hors du curseur : le comptage incremental ne suffit plus.
+        self.doc_stats = None;
+        self.mark_dirty();
+        count
+    }
+
+    fn goto_line(&mut self, value: &str) {
+        let Some(row) = parse_goto_line(value, self.editor().lines().len()) else {
+            self.log_issue(
+                &format!("Numero de ligne invalide: {value}"),
+                "avertissement",
+                "aller_a_la_ligne",
+                LogTarget::Main,
+            );
+            return;
+        };
+        self.editor_mut()
+            .move_cursor(CursorMove::Jump(row as u16, 0));
+        self.focus = Focus::Editor;
+    }
+
+    fn mark_dirty(&mut self) {
+        if let Some(current) = self.current_mut()
+            && !current.read_only()
+        {
+            current.dirty = true;
+            self.refresh_title();
+        }
+    }
+
+    /// Souris (`--mouse`) : un clic donne le focus au panneau (et ouvre l'entree cliquee de
+    /// l'arbre), la molette fait defiler le log survole.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.prompt.is_some() {
+            return;
+        }
+        const WHEEL_ROWS: usize = 3;
+        let position = Position::new(mouse.column, mouse.row);
+        let areas = self.areas;
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if areas.tree.contains(position) {
+                    self.focus = Focus::Tree;
+                    self.click_tree_row(position.y.saturating_sub(areas.tree.y + 1));
+                } else if areas.editor.contains(position) {
+                    self.focus = Focus::Editor;
+                } else if areas.cmd.contains(position) {
+                    self.focus = Focus::Cmd;
+                } else if areas.log.contains(position) {
+                    self.focus = Focus::Log;
+                } else if areas.codex.contains(position) || areas.codex_log.contains(position) {
+                    self.focus = Focus::Codex;
                 }
             }
-            ProcessKind::CodexExec => {
-                if self.codex_compact_view && !self.codex_assistant_buffer.is_empty() {
-                    let message = std::mem::take(&mut self.codex_assistant_buffer);
-                    self.codex_log_message(&message);
-                }
-                if self.codex_retry_without_sandbox || self.codex_retry_without_approval {
-                    self.codex_retry_without_sandbox = false;
-                    self.codex_retry_without_approval = false;
-                    if let Some(prompt) = self.codex_last_prompt.clone() {
-                        let env_map = self.codex_env();
-                        let extra_args = self.codex_exec_extra_args();
-                        self.codex_exec_used_sandbox_flag =
-                            extra_args.iter().any(|arg| arg == "--sandbox");
-                        self.codex_exec_used_approval_flag =
-                            extra_args.iter().any(|arg| arg == "--ask-for-approval");
-                        if let Ok(argv) = codex_exec_argv(
-                            &prompt,
-                            Some(&self.root_dir),
-                            Some(&env_map),
-                            true,
-                            Some(&extra_args),
-                        ) {
-                            self.codex_log_ui(format!("$ {}", argv.join(" ")));
-                            self.spawn_process(
-                                argv,
-                                env_map,
-                                "codex_exec",
-                                LogTarget::Codex,
-                                ProcessKind::CodexExec,
-                            );
-                        }
-                    }
-                }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let up = mouse.kind == MouseEventKind::ScrollUp;
+                let target = if areas.codex_log.contains(position) {
+                    LogTarget::Codex
+                } else if areas.log.contains(position) {
+                    LogTarget::Main
+                } else {
+                    return;
+                };
+                let total = self.visible_log_len(target);
+                let rows_up = match target {
+                    LogTarget::Main => &mut self.log_scroll.0,
+                    LogTarget::Codex => &mut self.codex_log_scroll,
+                };
+                *rows_up = if up {
+                    (*rows_up + WHEEL_ROWS).min(total.saturating_sub(1))
+                } else {
+                    rows_up.saturating_sub(WHEEL_ROWS)
+                };
             }
-            ProcessKind::CodexInstall => {
-                let env_map = self.codex_env();
-                if codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
-                    self.codex_log_ui("Codex installe.".to_string());
-                    if let Some(prompt) = self.pending_codex_prompt.take() {
-                        self.run_codex(prompt);
+            _ => {}
+        }
+    }
+
+    /// Clic sur la ligne `row` (sous la bordure) de l'arbre : selectionne puis ouvre / deplie.
+    fn click_tree_row(&mut self, row: u16) {
+        let idx = self.tree.state.offset() + row as usize;
+        if idx >= self.tree.data.visible().len() {
+            return;
+        }
+        self.tree.state.select(Some(idx));
+        self.handle_tree_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    }
+
+    fn handle_tree_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up => self.tree.select_prev(),
+            KeyCode::Down => self.tree.select_next(),
+            KeyCode::Right | KeyCode::Enter => {
+                if let Some(entry) = self.tree.selected_entry() {
+                    if entry.is_dir {
+                        self.tree.toggle_dir();
+                    } else {
+                        self.open_file(entry.path.clone());
                     }
                 }
             }
+            KeyCode::Left => self.tree.toggle_dir(),
+            KeyCode::F(2) => self.action_rename_entry(),
+            KeyCode::Delete => self.action_delete_entry(),
             _ => {}
         }
+        self.follow_tree_selection();
     }
 
-    fn handle_codex_line(&mut self, line: &str) {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
+    fn handle_editor_key(&mut self, key: KeyEvent) {
+        // Apercu en lecture seule : deplacement et selection uniquement.
+        if self.current().is_some_and(OpenFile::read_only) {
+            if matches!(
+                key.code,
+                KeyCode::Up
+                    | KeyCode::Down
+                    | KeyCode::Left
+                    | KeyCode::Right
+                    | KeyCode::PageUp
+                    | KeyCode::PageDown
+                    | KeyCode::Home
+                    | KeyCode::End
+            ) {
+                self.editor_mut().input(Input::from(key));
+            }
             return;
         }
-        if self.handle_sandbox_flag_line(trimmed) || self.handle_approval_flag_line(trimmed) {
+        if key.code == KeyCode::Tab && key.modifiers.is_empty() {
+            let col = self.editor().cursor().1;
+            let path = self.current().map(|current| current.path.clone());
+            let indent = self.core.settings().indent_text(path.as_deref(), col);
+            self.editor_mut().insert_str(indent);
+            self.mark_dirty();
             return;
         }
-        if self.codex_retry_without_sandbox || self.codex_retry_without_approval {
-            return;
+        let mut changed = false;
+        if matches!(
+            key.code,
+            KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete | KeyCode::Enter | KeyCode::Tab
+        ) {
+            changed = true;
         }
-        if let Some(translated) = translate_codex_line(trimmed) {
-            if self.codex_compact_view {
-                self.codex_log_action(&translated);
-            } else {
-                self.codex_log_ui(translated);
+        let input = Input::from(key);
+        self.editor_mut().input(input);
+        if changed {
+            // Annuler/retablir, coller... peuvent toucher d'autres lignes que celle du curseur.
+            if key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            {
+                self.doc_stats = None;
             }
+            self.mark_dirty();
+        }
+    }
+
+    fn handle_cmd_key(&mut self, key: KeyEvent) {
+        let recalled = match key.code {
+            KeyCode::Up => Some(self.cmd_history.older()),
+            KeyCode::Down => Some(self.cmd_history.newer()),
+            _ => None,
+        };
+        if let Some(recalled) = recalled {
+            self.cmd_input = recalled.map_or_else(InputField::new, InputField::with_value);
             return;
         }
+        if key.code == KeyCode::Enter && self.core.interactive_shell().is_some() {
+            // Ligne brute (meme vide) : un prompt peut attendre une simple validation.
+            let line = std::mem::take(&mut self.cmd_input.value);
+            self.cmd_input.clear();
+            self.send_shell_input(line);
+            return;
+        }
+        if let Some(cmd) = self.cmd_input.handle_key(key) {
+            self.run_shell(cmd);
+        }
+    }
 
-        let value: serde_json::Value = match serde_json::from_str(trimmed) {
-            Ok(val) => val,
-            Err(_) => {
-                if self.codex_compact_view {
-                    self.codex_log_action(trimmed);
-                } else {
-                    self.codex_log_output(trimmed.to_string());
-                }
-                return;
-            }
+    /// Journal : fleches / PgPrec / PgSuiv pour l'historique, gauche / droite sans retour ligne.
+    fn handle_log_key(&mut self, key: KeyEvent) {
+        const COLUMNS: usize = 8;
+        let total = self.visible_log_len(LogTarget::Main);
+        let (rows_up, column) = self.log_scroll;
+        if let Some(rows_up) = scrolled_rows(rows_up, key.code, total) {
+            self.log_scroll.0 = rows_up;
+            return;
+        }
+        self.log_scroll = match key.code {
+            KeyCode::Left => (rows_up, column.saturating_sub(COLUMNS)),
+            KeyCode::Right if !self.word_wrap => (rows_up, column + COLUMNS),
+            KeyCode::Esc => (0, 0),
+            _ => (rows_up, column),
         };
+    }
 
-        let event_type = value
-            .get("type")
-            .and_then(serde_json::Value::as_str)
-            .unwrap_or("");
-        if self.codex_compact_view {
-            if matches!(
-                event_type,
-                "response.output_text.delta" | "response.output_text"
-            ) {
-                let delta = value
-                    .get("delta")
-                    .or_else(|| value.get("text"))
-                    .and_then(serde_json::Value::as_str)
-                    .unwrap_or("");
-                if !delta.is_empty() {
-                    self.codex_assistant_buffer.push_str(delta);
+    fn visible_log_len(&self, target: LogTarget) -> usize {
+        self.visible_log(target).len()
+    }
+
+    fn handle_codex_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up => return self.recall_codex_prompt(true),
+            KeyCode::Down => return self.recall_codex_prompt(false),
+            // Haut/Bas et Debut/Fin restent au champ : la Sortie Codex defile par page.
+            KeyCode::PageUp | KeyCode::PageDown => {
+                let total = self.visible_log_len(LogTarget::Codex);
+                if let Some(rows_up) = scrolled_rows(self.codex_log_scroll, key.code, total) {
+                    self.codex_log_scroll = rows_up;
                 }
                 return;
             }
-            if matches!(
-                event_type,
-                "response.output_text.done" | "response.output_item.done" | "response.completed"
-            ) {
-                if !self.codex_assistant_buffer.is_empty() {
-                    let message = std::mem::take(&mut self.codex_assistant_buffer);
-                    self.codex_log_message(&message);
+            KeyCode::Home | KeyCode::End if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let total = self.visible_log_len(LogTarget::Codex);
+                if let Some(rows_up) = scrolled_rows(self.codex_log_scroll, key.code, total) {
+                    self.codex_log_scroll = rows_up;
                 }
                 return;
             }
+            _ => {}
         }
-
-        if event_type == "error" {
-            let msg = value
-                .get("message")
-                .and_then(serde_json::Value::as_str)
-                .unwrap_or("");
-            if self.codex_compact_view {
-                if let Some(translated) = translate_codex_line(msg) {
-                    self.codex_log_action(&translated);
-                } else if let Some(status) = extract_status_code(msg) {
-                    self.codex_log_action(&format!("Erreur Codex HTTP {status}."));
-                    if let Some(hint) = codex_hint_for_status(status) {
-                        self.codex_log_action(&hint);
-                    }
-                } else {
-                    self.codex_log_action(
-                        "Erreur Codex: une erreur est survenue. Consulte le journal ou relance.",
-                    );
-                }
-            } else {
-                self.codex_log_ui("Erreur Codex: une erreur est survenue.".to_string());
-            }
-            return;
+        if let Some(prompt) = self.codex_input.handle_key(key) {
+            self.codex_history_index = None;
+            self.send_codex_prompt(prompt);
         }
+    }
 
-        if event_type == "turn.failed" {
-            let msg = value
-                .get("error")
-                .and_then(|err| err.get("message").or_else(|| err.get("text")))
-                .and_then(serde_json::Value::as_str)
-                .unwrap_or("");
-            if self.codex_compact_view {
-                if let Some(translated) = translate_codex_line(msg) {
-                    self.codex_log_action(&translated);
-                } else if let Some(status) = extract_status_code(msg) {
-                    self.codex_log_action(&format!("Tache echouee HTTP {status}."));
-                    if let Some(hint) = codex_hint_for_status(status) {
-                        self.codex_log_action(&hint);
-                    }
-                } else {
-                    self.codex_log_action("Tache echouee: une erreur est survenue.");
-                }
-            } else {
-                self.codex_log_ui("Tache echouee.".to_string());
-            }
+    /// Haut/Bas dans le champ Codex : parcourt les prompts de l'historique (Entree re-envoie).
+    fn recall_codex_prompt(&mut self, older: bool) {
+        let prompts = self.codex_history.recent_prompts();
+        if prompts.is_empty() {
             return;
         }
+        let index = match (self.codex_history_index, older) {
+            (None, true) => Some(0),
+            (None, false) => None,
+            (Some(idx), true) => Some((idx + 1).min(prompts.len() - 1)),
+            (Some(0), false) => None,
+            (Some(idx), false) => Some(idx - 1),
+        };
+        self.codex_input = match index {
+            Some(idx) => InputField::with_value(prompts[idx]),
+            None => InputField::new(),
+        };
+        self.codex_history_index = index;
+    }
 
-        if self.codex_compact_view {
-            for item in extract_display_items(&value) {
-                match item.kind {
-                    DisplayKind::Assistant => self.codex_log_message(&item.message),
-                    DisplayKind::User => self.codex_log_user_message(&item.message),
-                    DisplayKind::Action => self.codex_log_action(&item.message),
+    fn refresh_title(&mut self) {
+        if let Some(tab) = self.tabs.active() {
+            let current = &tab.file;
+            let dirty = if current.dirty { " *" } else { "" };
+            self.title = format!("{APP_NAME}{dirty}");
+            let (row, col) = tab.buffer.cursor();
+            let lines = tab.buffer.lines();
+            if current.read_only() {
+                self.doc_stats = None;
+            } else {
+                let row_text = lines.get(row).map_or("", String::as_str);
+                let fresh = self.doc_stats.as_mut().is_some_and(|stats| {
+                    stats.update(
+                        &current.path,
+                        current.dirty,
+                        lines.len(),
+                        text_bytes(lines),
+                        row,
+                        row_text,
+                    )
+                });
+                if !fresh {
+                    self.doc_stats = Some(DocumentStats::scan(
+                        &current.path,
+                        current.dirty,
+                        lines,
+                        row,
+                    ));
                 }
             }
-        } else if let Some(event_type) = value.get("type").and_then(serde_json::Value::as_str) {
-            self.codex_log_output(format!("[{event_type}] {value}"));
+            let stats = self
+                .doc_stats
+                .as_ref()
+                .map(|stats| format!("  {}", stats.label()))
+                .unwrap_or_default();
+            let modified = if current.dirty { "  modifie" } else { "" };
+            self.sub_title = format!(
+                "{}  ({}, {})  {}{stats}{modified}",
+                current.path.display(),
+                current.encoding,
+                current.line_ending.label(),
+                editor_position_label(row, col, lines.len())
+            );
         } else {
-            self.codex_log_output(value.to_string());
+            self.title = APP_NAME.to_string();
+            self.sub_title = self.root_dir.display().to_string();
+            self.doc_stats = None;
         }
     }
 
-    fn handle_codex_caps_line(&mut self, line: &str) {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            return;
-        }
-        if !self.codex_caps_buffer.is_empty() {
-            self.codex_caps_buffer.push('\n');
-        }
-        self.codex_caps_buffer.push_str(trimmed);
+    fn log_ui(&mut self, msg: String) {
+        self.push_log(LogTarget::Main, msg, LogKind::Info);
     }
 
-    fn codex_log_entry(&mut self, msg: &str, label: &str, kind: &str) {
-        let cleaned = msg.trim();
-        if cleaned.is_empty() {
-            return;
-        }
-        let fingerprint = format!("{kind}:{cleaned}");
-        if self.last_codex_message.as_deref() == Some(&fingerprint) {
-            return;
-        }
-        self.last_codex_message = Some(fingerprint);
-        let (label_style, line_style) = match kind {
-            "assistant" => (
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
-                Style::default().fg(Color::Green),
-            ),
-            "user" => (
-                Style::default()
-                    .fg(Color::Blue)
-                    .add_modifier(Modifier::BOLD),
-                Style::default().fg(Color::Blue),
-            ),
-            "action" => (
-                Style::default()
-                    .fg(Color::DarkGray)
-                    .add_modifier(Modifier::BOLD),
-                Style::default().fg(Color::DarkGray),
-            ),
-            _ => (
-                Style::default().add_modifier(Modifier::BOLD),
-                Style::default(),
-            ),
+    fn codex_log_ui(&mut self, msg: String) {
+        self.push_log(LogTarget::Codex, msg, LogKind::Info);
+    }
+
+    fn codex_log_output(&mut self, msg: String) {
+        self.push_log(LogTarget::Codex, msg, LogKind::Info);
+    }
+
+    fn push_log(&mut self, target: LogTarget, msg: String, kind: LogKind) {
+        let lines = msg
+            .split('\n')
+            .map(|line| LogLine::new(line.to_string(), kind));
+        self.push_log_lines(target, lines);
+    }
+
+    /// Ligne d'un bloc de code Codex : fond distinct sur toute la largeur, `text` intact
+    /// pour l'export et la recherche.
+    fn push_code_log(&mut self, text: String, kind: LogKind, width: usize) {
+        let style = if text.trim_start().starts_with("```") {
+            Style::default().fg(Color::DarkGray).bg(Color::Black)
+        } else {
+            Style::default().fg(Color::LightCyan).bg(Color::Black)
         };
-        self.push_log(LogTarget::Codex, label.to_string(), label_style);
-        let width = self.last_codex_width.saturating_sub(4) as usize;
-        for line in crate::codex::wrap_text(msg, width) {
-            if line.is_empty() {
-                self.push_log(LogTarget::Codex, String::new(), Style::default());
+        let line = LogLine {
+            runs: vec![(format!("{text:<width$}"), style)],
+            text,
+            kind,
+            repeats: 1,
+        };
+        self.push_log_lines(LogTarget::Codex, std::iter::once(line));
+    }
+
+    /// Ligne de sortie d'un outil : les couleurs ANSI deviennent des styles ratatui.
+    fn push_ansi_log(&mut self, target: LogTarget, line: &str) {
+        let spans = parse_ansi(line);
+        let text: String = spans.iter().map(|span| span.text.as_str()).collect();
+        let kind = if has_fg(&spans, &[1, 9]) {
+            LogKind::Error
+        } else if has_fg(&spans, &[3, 11]) {
+            LogKind::Warn
+        } else {
+            LogKind::Info
+        };
+        let runs = if spans.iter().all(|span| span.style == AnsiStyle::default()) {
+            Vec::new()
+        } else {
+            spans
+                .into_iter()
+                .map(|span| (span.text, ansi_style(span.style)))
+                .collect()
+        };
+        let line = LogLine {
+            text,
+            kind,
+            runs,
+            repeats: 1,
+        };
+        self.push_log_lines(target, std::iter::once(line));
+    }
+
+    fn push_log_lines(&mut self, target: LogTarget, lines: impl Iterator<Item = LogLine>) {
+        let collapse = self.core.settings().collapse_log_repeats();
+        let (store, rows_up, filter) = match target {
+            LogTarget::Main => (&mut self.log, &mut self.log_scroll.0, self.log_filter),
+            LogTarget::Codex => (
+                &mut self.codex_log,
+                &mut self.codex_log_scroll,
+                self.codex_log_filter,
+            ),
+        };
+        let added = append_log_lines(store, lines, collapse, filter);
+        // Remonte dans l'historique : la vue reste sur les memes lignes.
+        if *rows_up > 0 {
+            *rows_up += added;
+        }
+    }
+
+    fn log_issue(&mut self, msg: &str, niveau: &str, contexte: &str, target: LogTarget) {
+        self.push_log(target, msg.to_string(), LogKind::from_niveau(niveau));
+        self.core.record_issue(niveau, msg, contexte, None);
+    }
+
+    fn portable_env(&self, mut env_map: HashMap<String, String>) -> HashMap<String, String> {
+        self.core.portable_env(std::mem::take(&mut env_map))
+    }
+
+    fn codex_env(&self) -> HashMap<String, String> {
+        self.core.codex_env()
+    }
+
+    fn ensure_node_available(
+        &mut self,
+        env_map: &HashMap<String, String>,
+        target: LogTarget,
+    ) -> bool {
+        if let Some(message) = self.core.ensure_node_available_message(env_map) {
+            self.log_issue(&message, "erreur", "node", target);
+            return false;
+        }
+        true
+    }
+
+    fn tools_env(&self) -> HashMap<String, String> {
+        self.core.tools_env()
+    }
+
+    /// Onglets modifies concernes par l'action (l'onglet a fermer, sinon tous).
+    fn unsaved_tabs(&self, action: &PendingAction) -> Vec<usize> {
+        match action {
+            PendingAction::CloseTab(path) => self
+                .tabs
+                .position(path)
+                .filter(|idx| self.tabs.get(*idx).is_some_and(|tab| tab.file.dirty))
+                .into_iter()
+                .collect(),
+            PendingAction::ReloadTree | PendingAction::Quit => self.tabs.dirty_indices(),
+        }
+    }
+
+    /// Vrai si l'action peut se faire tout de suite, sinon demande quoi faire des modifs.
+    fn confirm_discard(&mut self, action: PendingAction) -> bool {
+        let dirty = self.unsaved_tabs(&action);
+        let Some(current) = dirty.first().and_then(|idx| self.tabs.get(*idx)) else {
+            return true;
+        };
+        let name = tab_name(&current.file.path);
+        let label = match dirty.len() {
+            1 => format!("{name} modifie. Sauver ? (o: oui, n: non, Echap: annuler)"),
+            count => format!(
+                "{name} et {} autre(s) modifies. Tout sauver ? (o: oui, n: non, Echap: annuler)",
+                count - 1
+            ),
+        };
+        self.open_prompt(PromptKind::UnsavedChanges(action), &label);
+        false
+    }
+
+    fn resolve_unsaved(&mut self, action: PendingAction, save: bool) {
+        let dirty = self.unsaved_tabs(&action);
+        if save {
+            for idx in dirty {
+                self.save_tab(idx);
+            }
+            if !self.unsaved_tabs(&action).is_empty() {
+                return;
+            }
+        } else {
+            // Modifications abandonnees : leurs copies de secours aussi.
+            let paths: Vec<PathBuf> = dirty
+                .iter()
+                .filter_map(|idx| self.tabs.get(*idx))
+                .map(|tab| tab.file.path.clone())
+                .collect();
+            for path in &paths {
+                self.discard_autosave(path);
+            }
+            if action == PendingAction::ReloadTree {
+                // Les onglets reviennent au contenu du disque.
+                let active = self.current().map(|current| current.path.clone());
+                for path in paths {
+                    self.load_file(path);
+                }
+                if let Some(idx) = active.and_then(|path| self.tabs.position(&path)) {
+                    self.tabs.activate(idx);
+                    self.on_tab_change();
+                }
+            }
+        }
+        match action {
+            PendingAction::CloseTab(path) => self.close_tab(&path),
+            PendingAction::ReloadTree => self.reload_tree(),
+            PendingAction::Quit => self.quit_requested = true,
+        }
+    }
+
+    /// Active l'onglet du fichier s'il est deja ouvert, sinon l'ouvre dans un nouvel onglet.
+    fn open_file(&mut self, path: PathBuf) {
+        match self.tabs.position(&path) {
+            Some(idx) => {
+                self.tabs.activate(idx);
+                self.on_tab_change();
+            }
+            None => self.load_file(path),
+        }
+    }
+
+    fn action_cycle_tab(&mut self, forward: bool) {
+        if self.tabs.len() < 2 {
+            return;
+        }
+        self.tabs.cycle(forward);
+        self.on_tab_change();
+    }
+
+    fn action_close_tab(&mut self) {
+        let Some(path) = self.current().map(|current| current.path.clone()) else {
+            return;
+        };
+        if self.confirm_discard(PendingAction::CloseTab(path.clone())) {
+            self.close_tab(&path);
+        }
+    }
+
+    fn close_tab(&mut self, path: &Path) {
+        if let Some(idx) = self.tabs.position(path) {
+            self.tabs.close(idx);
+            self.on_tab_change();
+        }
+    }
+
+    /// Apres un changement d'onglet : fichier garde visible dans l'arborescence, titre.
+    fn on_tab_change(&mut self) {
+        let path = self.current().map(|current| current.path.clone());
+        self.tree.data.set_keep_visible(path);
+        self.editor_scroll = (0, 0);
+        self.doc_stats = None;
+        self.refresh_title();
+    }
+
+    /// "Editeur" suivi des onglets : l'actif entre crochets, `*` si modifie.
+    fn editor_title(&self) -> String {
+        let active = self.tabs.active_index();
+        let names: Vec<String> = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(idx, tab)| {
+                let dirty = if tab.file.dirty { "*" } else { "" };
+                let name = tab_name(&tab.file.path);
+                if Some(idx) == active {
+                    format!("[{name}{dirty}]")
+                } else {
+                    format!("{name}{dirty}")
+                }
+            })
+            .collect();
+        if names.is_empty() {
+            "Editeur".to_string()
+        } else {
+            format!("Editeur: {}", names.join(" | "))
+        }
+    }
+
+    fn load_file(&mut self, path: PathBuf) {
+        let result = open_workspace_file(self.core.workspace(), path);
+        self.open_in_tab(result);
+    }
+
+    /// Ouvre un fichier signale binaire avec l'encodage devine (UTF-16 sans BOM, CJK...).
+    fn force_open(&mut self, path: PathBuf) {
+        let result = open_workspace_file_anyway(self.core.workspace(), path);
+        self.open_in_tab(result);
+    }
+
+    fn open_in_tab(&mut self, result: Result<OpenedWorkspaceFile, OpenWorkspaceFileError>) {
+        let opened = match result {
+            Ok(opened) => opened,
+            Err(OpenWorkspaceFileError::Binary(path)) => {
+                self.log_issue(
+                    &format!("Binaire/non texte, apercu hexadecimal: {}", path.display()),
+                    "avertissement",
+                    "ouverture_fichier",
+                    LogTarget::Main,
+                );
+                let preview = open_workspace_hex_preview(self.core.workspace(), path.clone());
+                self.open_in_tab(preview);
+                if self.prompt.is_none() {
+                    let label = format!(
+                        "{} semble binaire. Ouvrir comme texte ? (o/n)",
+                        tab_name(&path)
+                    );
+                    self.open_prompt(PromptKind::OpenAnyway(path), &label);
+                }
+                return;
+            }
+            Err(OpenWorkspaceFileError::Hidden(path)) => {
+                self.log_issue(
+                    &format!("Fichier interne masque: {}", path.display()),
+                    "avertissement",
+                    "ouverture_fichier",
+                    LogTarget::Main,
+                );
+                return;
+            }
+            Err(OpenWorkspaceFileError::Sensitive(path)) => {
+                self.log_issue(
+                    &format!("Fichier sensible protege: {}", path.display()),
+                    "erreur",
+                    "ouverture_fichier",
+                    LogTarget::Main,
+                );
+                return;
+            }
+            Err(err) => {
+                self.log_issue(
+                    &err.to_string(),
+                    "erreur",
+                    "ouverture_fichier",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+
+        let tab = EditorTab {
+            buffer: Self::text_editor(&opened.text),
+            file: OpenFile {
+                path: opened.path,
+                encoding: opened.encoding,
+                bom: opened.bom,
+                line_ending: opened.line_ending,
+                dirty: false,
+                preview: opened.preview,
+                missing_on_disk: false,
+            },
+        };
+        match self.tabs.position(&tab.file.path) {
+            Some(idx) => {
+                if let Some(existing) = self.tabs.get_mut(idx) {
+                    *existing = tab;
+                }
+                self.tabs.activate(idx);
+            }
+            None => {
+                self.tabs.open(tab);
+            }
+        }
+        self.on_tab_change();
+        if let Some(path) = self.current().map(|current| current.path.clone()) {
+            self.offer_autosave_recovery(&path);
+        }
+    }
+
+    /// Propose de restaurer une copie de secours plus recente que le fichier (arret brutal).
+    fn offer_autosave_recovery(&mut self, path: &Path) {
+        let Some(autosave) = newer_autosave(path) else {
+            return;
+        };
+        if self.prompt.is_some() {
+            self.log_issue(
+                &format!("Copie de secours plus recente: {}", autosave.display()),
+                "avertissement",
+                "autosave",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let label = format!(
+            "Copie de secours de {} plus recente. Restaurer ? (o: oui, n: supprimer, Echap: plus tard)",
+            tab_name(path)
+        );
+        self.open_prompt(PromptKind::RecoverAutosave(path.to_path_buf()), &label);
+    }
+
+    /// Remplace le contenu de l'onglet par la copie de secours (fichier non modifie).
+    fn restore_autosave(&mut self, path: &Path) {
+        let Some(idx) = self.tabs.position(path) else {
+            return;
+        };
+        match read_autosave(path) {
+            Ok(text) => {
+                if let Some(tab) = self.tabs.get_mut(idx) {
+                    tab.buffer = Self::text_editor(&text);
+                    tab.file.dirty = true;
+                }
+                self.tabs.activate(idx);
+                self.on_tab_change();
+                self.log_ui(format!(
+                    "Copie de secours restauree (Ctrl+S pour l'enregistrer): {}",
+                    path.display()
+                ));
+            }
+            Err(err) => self.log_issue(
+                &format!("Copie de secours illisible {}: {err}", path.display()),
+                "erreur",
+                "autosave",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    fn discard_autosave(&mut self, path: &Path) {
+        if let Err(err) = remove_autosave(path) {
+            self.log_issue(
+                &format!("Copie de secours non supprimee {}: {err}", path.display()),
+                "avertissement",
+                "autosave",
+                LogTarget::Main,
+            );
+        }
+    }
+
+    /// Ecrit `fichier.autosave` pour chaque onglet modifie ; les fichiers restent intacts.
+    fn autosave_tabs(&mut self) {
+        self.last_autosave = Instant::now();
+        let errors: Vec<String> = self
+            .tabs
+            .iter()
+            .filter(|tab| tab.file.dirty)
+            .filter_map(|tab| {
+                write_autosave(&tab.file.path, &tab.buffer.lines().join("\n"))
+                    .err()
+                    .map(|err| {
+                        format!(
+                            "Copie de secours impossible {}: {err}",
+                            tab.file.path.display()
+                        )
+                    })
+            })
+            .collect();
+        for msg in errors {
+            self.log_issue(&msg, "avertissement", "autosave", LogTarget::Main);
+        }
+    }
+
+    /// Faux (avec un avertissement) si l'onglet courant n'est qu'un apercu en lecture seule.
+    fn current_editable(&mut self) -> bool {
+        if !self.current().is_some_and(OpenFile::read_only) {
+            return true;
+        }
+        self.log_issue(
+            "Apercu en lecture seule : modification impossible.",
+            "avertissement",
+            "apercu",
+            LogTarget::Main,
+        );
+        false
+    }
+
+    /// Force la fin de ligne utilisee a la prochaine sauvegarde (LF <-> CRLF).
+    fn action_toggle_line_ending(&mut self) {
+        if !self.current_editable() {
+            return;
+        }
+        let Some(current) = self.current_mut() else {
+            return;
+        };
+        current.line_ending = current.line_ending.toggled();
+        current.dirty = true;
+        let label = current.line_ending.label();
+        self.log_ui(format!("Fin de ligne: {label} (appliquee a la sauvegarde)"));
+        self.refresh_title();
+    }
+
+    /// Selectionne le fichier ouvert dans l'arborescence (dossiers parents deplies).
+    fn action_reveal_current(&mut self) {
+        let Some(path) = self.current().map(|current| current.path.clone()) else {
+            return;
+        };
+        match self.tree.data.reveal(&path) {
+            Some(idx) => self.tree.state.select(Some(idx)),
+            None => self.log_ui(format!("Absent de l'arborescence: {}", path.display())),
+        }
+    }
+
+    /// Affiche ou masque les fichiers ignores (.gitignore, .usbideignore, motifs par defaut).
+    fn action_toggle_show_ignored(&mut self) {
+        let show = !self.tree.data.show_ignored();
+        let selected = self.tree.selected_entry().map(|entry| entry.path.clone());
+        self.tree.data.set_show_ignored(show, self.core.workspace());
+        if let Some(path) = selected {
+            self.tree.select_path(&path);
+        }
+        let label = if show { "affiches" } else { "masques" };
+        self.log_ui(format!("Fichiers ignores: {label}"));
+    }
+
+    /// Alt+- / Alt+= : replie ou deplie toute l'arborescence ; la selection reste sur son
+    /// chemin, ou sur le dossier replie qui le contient.
+    fn action_fold_tree(&mut self, expand: bool) {
+        let selected = self.tree.selected_entry().map(|entry| entry.path.clone());
+        let truncated = if expand {
+            self.tree.data.expand_all()
+        } else {
+            self.tree.data.collapse_all();
+            false
+        };
+        let idx = selected
+            .and_then(|path| self.tree.data.reselect(&path))
+            .or_else(|| (!self.tree.data.visible().is_empty()).then_some(0));
+        self.tree.state.select(idx);
+        self.log_fold_tree(expand, truncated);
+    }
+
+    fn log_fold_tree(&mut self, expand: bool, truncated: bool) {
+        if truncated {
+            self.log_issue(
+                &format!(
+                    "Arborescence depliee sur {EXPAND_ALL_MAX_DEPTH} niveaux : les dossiers plus profonds restent replies."
+                ),
+                "avertissement",
+                "arborescence",
+                LogTarget::Main,
+            );
+        } else {
+            let label = if expand { "depliee" } else { "repliee" };
+            self.log_ui(format!("Arborescence {label}."));
+        }
+    }
+
+    /// Affiche ou masque les dotfiles (le fichier ouvert reste visible).
+    fn action_toggle_show_hidden(&mut self) {
+        let show = !self.tree.data.show_hidden();
+        let selected = self.tree.selected_entry().map(|entry| entry.path.clone());
+        self.tree.data.set_show_hidden(show);
+        if let Some(path) = selected {
+            self.tree.select_path(&path);
+        }
+        let label = if show { "affiches" } else { "masques" };
+        self.log_ui(format!("Fichiers caches: {label}"));
+        self.save_session();
+    }
+
+    fn action_cycle_log_filter(&mut self, target: LogTarget) {
+        let filter = match target {
+            LogTarget::Main => &mut self.log_filter,
+            LogTarget::Codex => &mut self.codex_log_filter,
+        };
+        *filter = filter.next();
+    }
+
+    /// Alt+M : l'editeur prend tout l'ecran, puis le focus precedent revient.
+    fn action_toggle_zen_mode(&mut self) {
+        match self.zen_mode.take() {
+            Some(focus) => {
+                self.focus = focus;
+                self.log_ui("Mode zen inactif.".to_string());
+            }
+            None => {
+                self.zen_mode = Some(self.focus);
+                self.focus = Focus::Editor;
+                self.log_ui("Mode zen actif (Alt+M pour revenir).".to_string());
+            }
+        }
+    }
+
+    /// Alt+Maj+gauche/droite : largeur de l'arbre ; Alt+Maj+haut/bas : hauteur de l'editeur.
+    fn resize_panels(&mut self, code: KeyCode) {
+        let step = |ratio: u16, grow: bool, (_, min, max): (u16, u16, u16)| {
+            let ratio = if grow {
+                ratio + RATIO_STEP
+            } else {
+                ratio.saturating_sub(RATIO_STEP)
+            };
+            ratio.clamp(min, max)
+        };
+        match code {
+            KeyCode::Left => self.tree_ratio = step(self.tree_ratio, false, TREE_RATIO),
+            KeyCode::Right => self.tree_ratio = step(self.tree_ratio, true, TREE_RATIO),
+            KeyCode::Up => self.editor_ratio = step(self.editor_ratio, false, EDITOR_RATIO),
+            KeyCode::Down => self.editor_ratio = step(self.editor_ratio, true, EDITOR_RATIO),
+            _ => return,
+        }
+        self.save_session();
+    }
+
+    fn action_toggle_word_wrap(&mut self) {
+        self.word_wrap = !self.word_wrap;
+        let label = if self.word_wrap { "actif" } else { "inactif" };
+        self.log_ui(format!("Retour a la ligne: {label}"));
+        self.save_session();
+    }
+
+    /// Ouvre `.usbide/settings.toml` dans l'editeur (la fenetre Parametres est propre au GUI).
+    fn action_settings(&mut self) {
+        let path = self.core.workspace().settings_path().to_path_buf();
+        self.log_ui(format!(
+            "Parametres: {} (pris en compte au prochain lancement)",
+            path.display()
+        ));
+        self.open_file(path);
+    }
+
+    /// Demande l'encodage (pre-rempli avec celui du fichier) pour rouvrir ou enregistrer.
+    fn action_encoding_prompt(&mut self, kind: PromptKind) {
+        let Some(encoding) = self.current().map(|current| current.encoding.clone()) else {
+            self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "encodage",
+                LogTarget::Main,
+            );
+            return;
+        };
+        let verb = if kind == PromptKind::ReopenEncoding {
+            "Rouvrir"
+        } else {
+            "Enregistrer"
+        };
+        let label = format!("{verb} avec l'encodage ({})", COMMON_ENCODINGS.join(", "));
+        self.open_prompt_with(kind, &label, &encoding);
+    }
+
+    fn checked_encoding(&mut self, value: &str) -> Option<String> {
+        let encoding = normalize_encoding(value);
+        if encoding.is_none() {
+            self.log_issue(
+                &format!("Encodage inconnu: {value}"),
+                "avertissement",
+                "encodage",
+                LogTarget::Main,
+            );
+        }
+        encoding
+    }
+
+    /// Relit le fichier courant avec l'encodage choisi (detection trompee).
+    fn reopen_with_encoding(&mut self, value: &str) {
+        let Some(encoding) = self.checked_encoding(value) else {
+            return;
+        };
+        let Some(idx) = self.tabs.active_index() else {
+            return;
+        };
+        let Some(tab) = self.tabs.get(idx) else {
+            return;
+        };
+        if tab.file.dirty {
+            self.log_issue(
+                "Modifications non sauvees : enregistrer ou fermer l'onglet avant de rouvrir.",
+                "avertissement",
+                "encodage",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let path = tab.file.path.clone();
+        match read_text_with_encoding(&path, &encoding) {
+            Ok(text) => {
+                if let Some(tab) = self.tabs.get_mut(idx) {
+                    tab.file.line_ending = detect_line_ending(&text);
+                    tab.file.encoding = encoding.clone();
+                    tab.file.bom = has_bom(&path);
+                    tab.file.preview = None;
+                    tab.buffer = Self::text_editor(&text);
+                }
+                self.on_tab_change();
+                self.log_ui(format!("Rouvert en {encoding}: {}", path.display()));
+            }
+            Err(err) => self.log_issue(
+                &format!("Erreur relecture: {} ({err})", path.display()),
+                "erreur",
+                "encodage",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    /// Enregistre le fichier courant dans l'encodage choisi ; previent avant un repli UTF-8.
+    fn save_with_encoding(&mut self, value: &str, confirmed: bool) {
+        if !self.current_editable() {
+            return;
+        }
+        let Some(encoding) = self.checked_encoding(value) else {
+            return;
+        };
+        let Some(idx) = self.tabs.active_index() else {
+            return;
+        };
+        let Some(tab) = self.tabs.get(idx) else {
+            return;
+        };
+        if !confirmed && !can_encode(&encoding, &tab.buffer.lines().join("\n")) {
+            let label = format!(
+                "{encoding} ne peut pas representer tout le texte : enregistrer en UTF-8 ? (o/n)"
+            );
+            self.open_prompt(PromptKind::ConfirmLossySave(encoding), &label);
+            return;
+        }
+        if let Some(tab) = self.tabs.get_mut(idx) {
+            // Conversion : BOM ecrit pour que l'UTF-16 soit reconnu a la reouverture.
+            if tab.file.encoding != encoding {
+                tab.file.bom = true;
+            }
+            tab.file.encoding = encoding;
+            tab.file.dirty = true;
+        }
+        self.save_tab(idx);
+    }
+
+    fn action_save(&mut self) {
+        match self.tabs.active_index() {
+            Some(idx) => self.save_tab(idx),
+            None => self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "sauvegarde",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    fn save_tab(&mut self, idx: usize) {
+        // Fichier supprime hors de l'IDE : proposer de le recreer plutot que l'ecrire en
+        // silence ; une fois la disparition signalee, la sauvegarde le recree.
+        if let Some(path) = self.tabs.get(idx).map(|tab| tab.file.path.clone())
+            && !path.exists()
+            && self.tabs.mark_missing(idx)
+        {
+            self.on_file_deleted(idx, path);
+            return;
+        }
+        let Some(tab) = self.tabs.get(idx) else {
+            return;
+        };
+        // Un apercu (vue hexa, debut d'un gros fichier) ne doit jamais ecraser le fichier.
+        if !tab.file.dirty || tab.file.read_only() {
+            return;
+        }
+        let path = tab.file.path.clone();
+        let encoding = tab.file.encoding.clone();
+        let bom = tab.file.bom;
+        let line_ending = tab.file.line_ending;
+        let mut text = tab.buffer.lines().join("\n");
+        if self.core.settings().trim_on_save(&path)
+            && let Some(normalized) = normalize_whitespace(&text)
+            && let Some(tab) = self.tabs.get_mut(idx)
+        {
+            // Meme remplacement que "remplacer tout" : annulable, curseur garde (borne).
+            let (row, col) = tab.buffer.cursor();
+            tab.buffer.select_all();
+            tab.buffer.insert_str(&normalized);
+            tab.buffer
+                .move_cursor(CursorMove::Jump(row as u16, col as u16));
+            self.doc_stats = None;
+            self.log_ui(format!(
+                "Espaces de fin de ligne et saut de ligne final normalises: {}",
+                path.display()
+            ));
+            text = normalized;
+        }
+        let content = apply_line_ending(&text, line_ending);
+        let result = write_text_with_encoding(&path, &encoding, &content, bom);
+        match result {
+            Ok(used_utf8_fallback) => {
+                if used_utf8_fallback {
+                    self.log_issue(
+                        &format!("Sauvegarde en UTF-8 (fallback) {}", path.display()),
+                        "avertissement",
+                        "sauvegarde",
+                        LogTarget::Main,
+                    );
+                } else {
+                    self.log_ui(format!("Sauvegarde {}", path.display()));
+                }
+                if let Some(tab) = self.tabs.get_mut(idx) {
+                    if used_utf8_fallback {
+                        tab.file.encoding = "utf-8".to_string();
+                    }
+                    tab.file.dirty = false;
+                    tab.file.missing_on_disk = false;
+                }
+                self.discard_autosave(&path);
+                self.refresh_title();
+                self.refresh_git_status();
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur sauvegarde: {} ({err})", path.display()),
+                    "erreur",
+                    "sauvegarde",
+                    LogTarget::Main,
+                );
+            }
+        }
+    }
+
+    fn action_run(&mut self) {
+        let (path, dirty) = match self.current() {
+            Some(current) => (current.path.clone(), current.dirty),
+            None => {
+                self.log_issue(
+                    "Ouvre un fichier .py.",
+                    "avertissement",
+                    "execution_python",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let is_py = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.eq_ignore_ascii_case("py"))
+            .unwrap_or(false);
+        if !is_py {
+            self.log_issue(
+                "Ouvre un fichier .py.",
+                "avertissement",
+                "execution_python",
+                LogTarget::Main,
+            );
+            return;
+        }
+        if dirty {
+            self.action_save();
+        }
+        let argv = python_run_argv(
+            &path,
+            self.core.run_venv().as_deref(),
+            &self.core.base_python(),
+        );
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        let env_map = self.portable_env(std::env::vars().collect());
+        self.spawn_process(
+            argv,
+            env_map,
+            "execution python",
+            LogTarget::Main,
+            ProcessKind::PythonRun,
+        );
+    }
+
+    /// Ctrl+F5 : execute la selection via un script de `tmp/` ; sans selection, comme F5.
+    fn action_run_selection(&mut self) {
+        let Some(selection) = self.editor_selection() else {
+            self.action_run();
+            return;
+        };
+        let script = match self.core.write_selection_script(&selection) {
+            Ok(script) => script,
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur ecriture de la selection: {err}"),
+                    "erreur",
+                    "execution_python",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let argv = python_run_argv(
+            &script,
+            self.core.run_venv().as_deref(),
+            &self.core.base_python(),
+        );
+        let lines = selection.lines().count();
+        self.log_ui(format!(
+            "$ {} ({lines} lignes selectionnees)",
+            argv.join(" ")
+        ));
+        let env_map = self.portable_env(std::env::vars().collect());
+        let before = self.core.running.len();
+        self.spawn_process(
+            argv,
+            env_map,
+            "execution selection",
+            LogTarget::Main,
+            ProcessKind::PythonRun,
+        );
+        match self.core.running.get_mut(before) {
+            Some(proc) => proc.temp_file = Some(script),
+            None => {
+                let _ = fs::remove_file(&script);
+            }
+        }
+    }
+
+    /// Ctrl+L : vide le log du panneau qui a le focus (les deux depuis l'arbre ou l'editeur).
+    fn action_clear_log(&mut self) {
+        match self.focus {
+            Focus::Cmd | Focus::Log => self.clear_log(LogTarget::Main),
+            Focus::Codex => self.clear_log(LogTarget::Codex),
+            Focus::Tree | Focus::Editor => {
+                self.log.clear();
+                self.codex_log.clear();
+                self.log_scroll = (0, 0);
+                self.codex_log_scroll = 0;
+                self.last_codex_message = None;
+                self.log_ui("journaux effaces".to_string());
+            }
+        }
+    }
+
+    /// Vide un seul panneau de log et le note dans ce panneau.
+    fn clear_log(&mut self, target: LogTarget) {
+        match target {
+            LogTarget::Main => {
+                self.log.clear();
+                self.log_scroll = (0, 0);
+                self.log_ui("Journal efface.".to_string());
+            }
+            LogTarget::Codex => {
+                self.codex_log.clear();
+                self.codex_log_scroll = 0;
+                self.last_codex_message = None;
+                self.codex_log_ui("Sortie Codex effacee.".to_string());
+            }
+        }
+    }
+
+    fn action_copy_last_assistant(&mut self) {
+        match self.last_assistant_message.clone() {
+            Some(text) => self.copy_to_clipboard(text, "Derniere reponse Codex"),
+            None => self.log_ui("Aucune reponse Codex a copier.".to_string()),
+        }
+    }
+
+    fn action_copy_codex_log(&mut self) {
+        let text = self.visible_codex_log_text();
+        if text.trim().is_empty() {
+            self.log_ui("Sortie Codex vide, rien a copier.".to_string());
+            return;
+        }
+        self.copy_to_clipboard(text, "Sortie Codex");
+    }
+
+    /// Lignes du panneau Codex acceptees par son filtre, en texte brut.
+    fn visible_codex_log_text(&self) -> String {
+        self.codex_log
+            .iter()
+            .filter(|line| self.codex_log_filter.accepts(line.kind.level()))
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn copy_to_clipboard(&mut self, text: String, label: &str) {
+        let clipboard = match self.clipboard.take() {
+            Some(clipboard) => Ok(clipboard),
+            None => arboard::Clipboard::new(),
+        };
+        let chars = text.chars().count();
+        let result = clipboard.and_then(|mut clipboard| {
+            let result = clipboard.set_text(text);
+            self.clipboard = Some(clipboard);
+            result
+        });
+        match result {
+            Ok(()) => self.log_ui(format!(
+                "{label} copiee dans le presse-papiers ({chars} caracteres)."
+            )),
+            Err(err) => self.log_issue(
+                &format!("Presse-papiers indisponible: {err}"),
+                "avertissement",
+                "presse_papiers",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    fn action_export_logs(&mut self) {
+        let sections = [
+            ("Journal", log_export_lines(&self.log)),
+            ("Codex", log_export_lines(&self.codex_log)),
+        ];
+        match crate::app_core::export_logs(self.core.workspace(), &sections) {
+            Ok(paths) => {
+                for path in paths {
+                    self.log_ui(format!("Logs exportes: {}", path.display()));
+                }
+            }
+            Err(err) => self.log_issue(
+                &format!("Erreur export logs: {err}"),
+                "erreur",
+                "export_logs",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    fn action_reload_tree(&mut self) {
+        if self.confirm_discard(PendingAction::ReloadTree) {
+            self.reload_tree();
+        }
+    }
+
+    fn reload_tree(&mut self) {
+        self.tree.reload(self.core.workspace());
+        self.file_index = None;
+        self.refresh_git_status();
+        self.log_ui("arborescence rechargee".to_string());
+    }
+
+    /// Env des commandes git si le root est un depot et git dans le PATH ; sinon explique pourquoi.
+    fn git_env(&mut self) -> Option<HashMap<String, String>> {
+        if !is_git_repo(&self.root_dir) {
+            self.log_issue(
+                "Pas de depot git a la racine (.git absent).",
+                "avertissement",
+                "git",
+                LogTarget::Main,
+            );
+            return None;
+        }
+        if self
+            .core
+            .running
+            .iter()
+            .any(|proc| matches!(proc.kind, ProcessKind::Git(_)))
+        {
+            self.log_ui("Commande git deja en cours.".to_string());
+            return None;
+        }
+        let env_map = self.tools_env();
+        if !tool_available("git", Some(&self.root_dir), Some(&env_map)).unwrap_or(false) {
+            self.log_issue(
+                "git introuvable dans le PATH.",
+                "avertissement",
+                "git",
+                LogTarget::Main,
+            );
+            return None;
+        }
+        Some(env_map)
+    }
+
+    fn run_git(
+        &mut self,
+        command: GitCommand,
+        argv: Vec<String>,
+        env_map: HashMap<String, String>,
+    ) {
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "git",
+            LogTarget::Main,
+            ProcessKind::Git(command),
+        );
+    }
+
+    fn action_git_commit(&mut self) {
+        if self.git_env().is_some() {
+            self.open_prompt(PromptKind::GitCommit, "Message du commit (tout indexer)");
+        }
+    }
+
+    /// Sauve les onglets puis indexe tout (`git add -A`) ; le commit suit la fin de l'ajout.
+    fn git_commit(&mut self, message: &str) {
+        if message.is_empty() {
+            self.log_issue(
+                "Message de commit vide.",
+                "avertissement",
+                "git",
+                LogTarget::Main,
+            );
+            return;
+        }
+        for idx in self.tabs.dirty_indices() {
+            self.save_tab(idx);
+        }
+        let Some(env_map) = self.git_env() else {
+            return;
+        };
+        self.git_commit_message = Some(message.to_string());
+        self.run_git(GitCommand::AddAll, git_add_all_argv(), env_map);
+    }
+
+    /// Lignes changees du buffer par rapport au fichier sur disque (sans git), dans le journal.
+    fn action_diff_with_disk(&mut self) {
+        let Some(tab) = self.tabs.active() else {
+            self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "diff",
+                LogTarget::Main,
+            );
+            return;
+        };
+        let path = tab.file.path.clone();
+        let buffer = tab.buffer.lines().join("\n");
+        let disk = match read_text_with_encoding(&path, &tab.file.encoding) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                self.log_issue(
+                    &format!(
+                        "{} n'existe plus sur disque : tout le buffer est nouveau.",
+                        path.display()
+                    ),
+                    "avertissement",
+                    "diff",
+                    LogTarget::Main,
+                );
+                String::new()
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Lecture impossible: {} ({err})", path.display()),
+                    "erreur",
+                    "diff",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let rows = diff_rows(&disk, &buffer, DIFF_CONTEXT);
+        if rows.is_empty() {
+            self.log_ui(format!(
+                "Aucune difference avec le disque: {}",
+                path.display()
+            ));
+            return;
+        }
+        let count = |kind: DiffRowKind| rows.iter().filter(|row| row.kind == kind).count();
+        let (added, removed) = (count(DiffRowKind::Added), count(DiffRowKind::Removed));
+        self.log_ui(format!(
+            "Diff disque -> editeur {} : +{added} -{removed}",
+            path.display()
+        ));
+        for row in &rows {
+            self.push_ansi_log(LogTarget::Main, &row.ansi());
+        }
+    }
+
+    /// Diff unifie de la derniere reponse Codex : apercu dans le journal puis confirmation.
+    fn action_apply_codex_patch(&mut self) {
+        let Some(message) = self.last_assistant_message.clone() else {
+            self.log_issue(
+                "Aucune reponse Codex a appliquer.",
+                "avertissement",
+                "patch",
+                LogTarget::Main,
+            );
+            return;
+        };
+        let current = self.tabs.active().map(|tab| tab.file.path.clone());
+        let open_files: Vec<&OpenFile> = self.tabs.iter().map(|tab| &tab.file).collect();
+        let patch = match self
+            .core
+            .prepare_codex_patch(&message, current.as_deref(), &open_files)
+        {
+            Ok(patch) => patch,
+            Err(err) => {
+                self.log_issue(&err, "avertissement", "patch", LogTarget::Main);
+                return;
+            }
+        };
+        if !self.patch_target_clean(&patch.path) {
+            return;
+        }
+        let rows = diff_rows(&patch.original, &patch.patched, DIFF_CONTEXT);
+        let count = |kind: DiffRowKind| rows.iter().filter(|row| row.kind == kind).count();
+        let (added, removed) = (count(DiffRowKind::Added), count(DiffRowKind::Removed));
+        let relative = patch
+            .path
+            .strip_prefix(&self.root_dir)
+            .unwrap_or(&patch.path)
+            .display()
+            .to_string();
+        self.log_ui(format!(
+            "Apercu du patch Codex {relative} : +{added} -{removed}"
+        ));
+        for row in &rows {
+            self.push_ansi_log(LogTarget::Main, &row.ansi());
+        }
+        self.open_prompt(
+            PromptKind::ApplyCodexPatch(patch),
+            &format!("Appliquer le patch Codex a {relative} (original dans .usbide/trash) ? (o/n)"),
+        );
+    }
+
+    /// Faux (avec un avertissement) si l'onglet du fichier a des modifications non sauvees.
+    fn patch_target_clean(&mut self, path: &Path) -> bool {
+        let dirty = self
+            .tabs
+            .position(path)
+            .and_then(|idx| self.tabs.get(idx))
+            .is_some_and(|tab| tab.file.dirty);
+        if dirty {
+            self.log_issue(
+                &format!(
+                    "Modifications non sauvees dans {} : enregistrer avant d'appliquer le patch.",
+                    path.display()
+                ),
+                "avertissement",
+                "patch",
+                LogTarget::Main,
+            );
+        }
+        !dirty
+    }
+
+    fn apply_codex_patch(&mut self, patch: CodexPatch) {
+        if !self.patch_target_clean(&patch.path) {
+            return;
+        }
+        match self.core.apply_codex_patch(&patch) {
+            Ok(backup) => {
+                self.log_ui(format!(
+                    "Patch Codex applique: {} (original: {})",
+                    patch.path.display(),
+                    backup.display()
+                ));
+                self.sync_tabs_with_disk(std::slice::from_ref(&patch.path));
+            }
+            Err(err) => self.log_issue(&err, "erreur", "patch", LogTarget::Main),
+        }
+    }
+
+    /// `git diff HEAD` du fichier courant, colore dans le journal.
+    fn action_git_diff(&mut self) {
+        let Some(path) = self.current().map(|current| current.path.clone()) else {
+            self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "git",
+                LogTarget::Main,
+            );
+            return;
+        };
+        let Some(env_map) = self.git_env() else {
+            return;
+        };
+        let relative = path.strip_prefix(&self.root_dir).unwrap_or(&path);
+        let argv = git_diff_argv(relative);
+        self.git_diff_lines = 0;
+        self.run_git(GitCommand::Diff, argv, env_map);
+    }
+
+    /// Sonde `--version` des interpreteurs candidats ; les absents sont ignores sans bruit.
+    fn detect_pythons(&mut self) {
+        let env_map = self.portable_env(std::env::vars().collect());
+        for (idx, argv) in self.core.start_python_detection() {
+            match NativeProcessRunner.spawn(&argv, Some(&self.root_dir), Some(&env_map)) {
+                Ok(handle) => self.core.running.push(RunningProcess::new(
+                    handle,
+                    ProcessKind::PythonVersion(idx),
+                    LogTarget::Main,
+                    "python_version",
+                )),
+                Err(_) => self.finish_python_probe(),
+            }
+        }
+    }
+
+    /// Sonde `--version` de node et npm une fois ; version trop ancienne : avertissement.
+    fn detect_node(&mut self) {
+        let env_map = self.codex_env();
+        for (tool, argv) in self.core.start_node_detection() {
+            if let Ok(handle) =
+                NativeProcessRunner.spawn(&argv, Some(&self.root_dir), Some(&env_map))
+            {
+                self.core.running.push(RunningProcess::new(
+                    handle,
+                    ProcessKind::NodeVersion(tool),
+                    LogTarget::Main,
+                    "node_version",
+                ));
+            }
+        }
+    }
+
+    fn finish_node_probe(&mut self, tool: NodeTool) {
+        if let Some(warning) = self.core.node_version_warning(tool) {
+            self.log_issue(&warning, "avertissement", "node", LogTarget::Main);
+        }
+    }
+
+    fn finish_python_probe(&mut self) {
+        match self.core.finish_python_probe() {
+            Some(Ok(message)) => self.log_ui(message),
+            Some(Err(warning)) => {
+                self.log_issue(&warning, "avertissement", "python", LogTarget::Main);
+            }
+            None => {}
+        }
+        self.python_label = self.core.python_status_label();
+    }
+
+    fn choose_python(&mut self, python: &PythonInstall) {
+        self.core.select_python(&python.exe);
+        self.python_label = self.core.python_status_label();
+        self.log_ui(format!("Interpreteur choisi: {}", python.label()));
+        if python.is_python2() {
+            self.log_issue(
+                "Python 2 choisi : les scripts Python 3 echoueront.",
+                "avertissement",
+                "python",
+                LogTarget::Main,
+            );
+        }
+    }
+
+    /// Relance `git status` en arriere-plan si le root est un depot ; sans git, rien.
+    fn refresh_git_status(&mut self) {
+        if !is_git_repo(&self.root_dir) {
+            return;
+        }
+        if self.git_status_running {
+            self.git_status_stale = true;
+            return;
+        }
+        let mut env_map = self.tools_env();
+        if !tool_available("git", Some(&self.root_dir), Some(&env_map)).unwrap_or(false) {
+            return;
+        }
+        // Sans verrou sur l'index : ne bloque pas un git lance dans le shell.
+        env_map.insert("GIT_OPTIONAL_LOCKS".to_string(), "0".to_string());
+        self.git_status_stale = false;
+        self.git_status_lines.clear();
+        self.spawn_process(
+            git_status_argv(),
+            env_map,
+            "git_status",
+            LogTarget::Main,
+            ProcessKind::GitStatus,
+        );
+        self.git_status_running = self
+            .core
+            .running
+            .iter()
+            .any(|proc| proc.kind == ProcessKind::GitStatus);
+    }
+
+    fn action_new_entry(&mut self, is_dir: bool) {
+        let parent = target_dir_for(self.tree.selected_entry(), &self.root_dir);
+        let relative = parent
+            .strip_prefix(&self.root_dir)
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let place = if relative.is_empty() {
+            ".".to_string()
+        } else {
+            relative
+        };
+        if is_dir {
+            self.open_prompt(
+                PromptKind::NewFolder(parent),
+                &format!("Nouveau dossier dans {place}"),
+            );
+        } else {
+            self.open_prompt(
+                PromptKind::NewFile(parent),
+                &format!("Nouveau fichier dans {place}"),
+            );
+        }
+    }
+
+    fn create_tree_entry(&mut self, parent: PathBuf, name: &str, is_dir: bool) {
+        match create_entry(&parent, name, is_dir) {
+            Ok(path) => {
+                let kind = if is_dir { "Dossier" } else { "Fichier" };
+                self.log_ui(format!("{kind} cree: {}", path.display()));
+                self.reload_tree();
+                self.tree.data.expand(&parent);
+                self.tree.select_path(&path);
+            }
+            Err(err) => {
+                self.log_issue(
+                    &err.to_string(),
+                    "erreur",
+                    "creation_fichier",
+                    LogTarget::Main,
+                );
+            }
+        }
+    }
+
+    fn action_rename_entry(&mut self) {
+        let Some(entry) = self.tree.selected_entry().cloned() else {
+            return;
+        };
+        if entry.path == self.root_dir {
+            return;
+        }
+        self.open_prompt_with(
+            PromptKind::Rename(entry.path),
+            &format!("Renommer {}", entry.name),
+            &entry.name,
+        );
+    }
+
+    fn action_delete_entry(&mut self) {
+        let Some(entry) = self.tree.selected_entry().cloned() else {
+            return;
+        };
+        if entry.path == self.root_dir {
+            return;
+        }
+        self.open_prompt(
+            PromptKind::ConfirmDelete(entry.path),
+            &format!("Deplacer {} vers la corbeille ? (o/n)", entry.name),
+        );
+    }
+
+    fn rename_tree_entry(&mut self, path: PathBuf, new_name: &str) {
+        match rename_entry(&path, new_name) {
+            Ok(target) => {
+                self.log_ui(format!(
+                    "Renomme: {} -> {}",
+                    path.display(),
+                    target.display()
+                ));
+                for tab in self.tabs.iter_mut() {
+                    if let Some(remapped) = remap_path(&tab.file.path, &path, &target) {
+                        tab.file.path = remapped;
+                    }
+                }
+                let current = self.current().map(|current| current.path.clone());
+                self.tree.data.set_keep_visible(current);
+                self.refresh_title();
+                self.reload_tree();
+                self.tree.select_path(&target);
+            }
+            Err(err) => {
+                self.log_issue(&err.to_string(), "erreur", "renommage", LogTarget::Main);
+            }
+        }
+    }
+
+    fn delete_tree_entry(&mut self, path: PathBuf) {
+        match move_to_trash(self.core.workspace(), &path) {
+            Ok(target) => {
+                self.log_ui(format!(
+                    "Deplace vers la corbeille: {} -> {}",
+                    path.display(),
+                    target.display()
+                ));
+                let affected: Vec<usize> = (0..self.tabs.len())
+                    .filter(|idx| {
+                        self.tabs
+                            .get(*idx)
+                            .is_some_and(|tab| tab.file.path.starts_with(&path))
+                    })
+                    .collect();
+                // Onglets modifies gardes (fichier absent) : leurs modifications restent a sauver.
+                let (kept, deleted): (Vec<usize>, Vec<usize>) = affected
+                    .into_iter()
+                    .partition(|idx| self.tabs.get(*idx).is_some_and(|tab| tab.file.dirty));
+                for idx in kept {
+                    self.tabs.mark_missing(idx);
+                    if let Some(tab) = self.tabs.get(idx) {
+                        let message = format!(
+                            "Onglet modifie conserve, fichier supprime: {}",
+                            tab.file.path.display()
+                        );
+                        self.log_issue(&message, "avertissement", "suppression", LogTarget::Main);
+                    }
+                }
+                for idx in deleted.iter().rev() {
+                    self.tabs.close(*idx);
+                }
+                if !deleted.is_empty() {
+                    self.on_tab_change();
+                }
+                self.reload_tree();
+            }
+            Err(err) => {
+                self.log_issue(&err.to_string(), "erreur", "suppression", LogTarget::Main);
+            }
+        }
+    }
+
+    fn action_toggle_codex_view(&mut self) {
+        self.codex_view = self.codex_view.next();
+        self.last_codex_message = None;
+        self.codex_log_ui(format!("Mode Codex: {}", self.codex_view.label()));
+        if let Err(err) = self.core.save_codex_view(self.codex_view) {
+            self.log_issue(
+                &format!("Niveau de vue non enregistre: {err}"),
+                "avertissement",
+                "settings",
+                LogTarget::Codex,
+            );
+        }
+    }
+
+    fn action_toggle_codex_sandbox(&mut self) {
+        self.codex_sandbox_mode = next_codex_sandbox_mode(self.codex_sandbox_mode);
+        self.codex_log_ui(format!(
+            "Sandbox Codex: {}",
+            codex_sandbox_label(self.codex_sandbox_mode)
+        ));
+    }
+
+    fn action_toggle_codex_approval(&mut self) {
+        self.codex_approval_policy = next_codex_approval_policy(self.codex_approval_policy);
+        self.codex_log_ui(format!(
+            "Approbations Codex: {}",
+            codex_approval_label(self.codex_approval_policy)
+        ));
+    }
+
+    fn action_toggle_codex_session(&mut self) {
+        self.codex_continue_session = !self.codex_continue_session;
+        let label = if self.codex_continue_session {
+            format!("suite (contexte des {CODEX_CONTEXT_TURNS} derniers echanges)")
+        } else {
+            "nouvelle (sans contexte)".to_string()
+        };
+        self.codex_log_ui(format!("Session Codex: {label}"));
+    }
+
+    fn action_codex_install(&mut self) {
+        let _ = self.install_codex(true, LogTarget::Codex);
+    }
+
+    fn codex_exec_extra_args(&self) -> Vec<String> {
+        codex_exec_extra_args(
+            self.codex_sandbox_supported,
+            self.codex_sandbox_mode,
+            self.codex_approval_supported,
+            self.codex_approval_policy,
+            &self.codex_model,
+        )
+    }
+
+    fn handle_approval_flag_line(&mut self, line: &str) -> bool {
+        if !self.codex_exec_used_approval_flag || !crate::app_core::approval_flag_error(line) {
+            return false;
+        }
+        if self.codex_approval_supported != Some(false) {
+            self.codex_approval_supported = Some(false);
+            self.codex_log_action(
+                "Option --ask-for-approval non supportee par cette version Codex. Relance sans approbations.",
+            );
+        }
+        self.codex_retry_without_approval = true;
+        true
+    }
+
+    fn handle_sandbox_flag_line(&mut self, line: &str) -> bool {
+        if !self.codex_exec_used_sandbox_flag {
+            return false;
+        }
+        if crate::app_core::sandbox_flag_error(line) || crate::app_core::sandbox_value_error(line) {
+            if self.codex_sandbox_supported != Some(false) {
+                self.codex_sandbox_supported = Some(false);
+                self.codex_log_action(
+                    "Option --sandbox non supportee par cette version Codex. Relance sans sandbox (mode par defaut).",
+                );
+            }
+            self.codex_retry_without_sandbox = true;
+            return true;
+        }
+        false
+    }
+
+    /// Tour termine : lance le prompt suivant de la file d'attente.
+    fn run_next_queued_codex(&mut self) {
+        if let Some(prompt) = self.core.next_queued_codex() {
+            self.run_codex(prompt);
+        }
+    }
+
+    /// Vide la file d'attente ; faux si elle l'etait deja.
+    fn clear_codex_queue(&mut self) -> bool {
+        let count = self.core.clear_codex_queue();
+        if count == 0 {
+            return false;
+        }
+        self.codex_log_action(&format!("File d'attente Codex videe ({count} prompts)."));
+        true
+    }
+
+    fn action_codex_stop(&mut self) {
+        let killed = self.core.kill_processes(ProcessKind::CodexExec);
+        let pending =
+            self.core.take_pending_codex_prompt().is_some() | self.core.cancel_codex_retry();
+        self.codex_status_started = None;
+        self.codex_assistant_buffer.clear();
+        self.codex_streamed_text.clear();
+        self.codex_turn_response.clear();
+        self.codex_last_prompt = None;
+        self.codex_retry_without_sandbox = false;
+        self.codex_retry_without_approval = false;
+        let pending = self.clear_codex_queue() | pending;
+        if killed == 0 && !pending {
+            self.codex_log_ui("Aucun Codex en cours.".to_string());
+            return;
+        }
+        self.codex_log_action("Codex interrompu");
+    }
+
+    /// Arret d'urgence : tous les process (installs, builds, Codex, tests...) d'un coup.
+    fn action_kill_all_processes(&mut self) {
+        let (stopped, lingering) = self.core.kill_all_processes();
+        self.core.take_pending_codex_prompt();
+        self.core.clear_codex_queue();
+        self.core.cancel_codex_retry();
+        self.codex_status_started = None;
+        self.codex_caps_running = false;
+        self.codex_assistant_buffer.clear();
+        self.codex_streamed_text.clear();
+        self.codex_turn_response.clear();
+        self.install_progress = None;
+        if stopped == 0 {
+            self.log_ui("Aucun process en cours.".to_string());
+            return;
+        }
+        self.log_issue(
+            &format!("Arret d'urgence : {stopped} process arrete(s)."),
+            "avertissement",
+            "arret_urgence",
+            LogTarget::Main,
+        );
+        if lingering > 0 {
+            self.log_issue(
+                &format!(
+                    "{lingering} process gardent leur sortie ouverte (sous-process encore actif ?)."
+                ),
+                "avertissement",
+                "arret_urgence",
+                LogTarget::Main,
+            );
+        }
+    }
+
+    fn action_codex_login(&mut self) {
+        let env_map = self.codex_env();
+        if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
+            if !self.ensure_node_available(&env_map, LogTarget::Codex) {
+                return;
+            }
+            if !self.install_codex(false, LogTarget::Codex) {
+                return;
+            }
+        }
+        self.codex_log_ui("Login Codex : navigateur/Device auth selon config.".to_string());
+        if !self.codex_device_auth_enabled() {
+            self.codex_log_ui(
+                "Astuce: si le navigateur ne s'ouvre pas, definis USBIDE_CODEX_DEVICE_AUTH=1 puis relance Ctrl+K."
+                    .to_string(),
+            );
+        }
+        let argv = codex_login_argv(
+            Some(&self.root_dir),
+            Some(&env_map),
+            self.codex_device_auth_enabled(),
+        );
+        self.codex_log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "login Codex",
+            LogTarget::Codex,
+            ProcessKind::CodexLogin,
+        );
+    }
+
+    /// `codex logout` dans le CODEX_HOME portable (changement de compte).
+    fn action_codex_logout(&mut self) {
+        let env_map = self.codex_env();
+        if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
+            self.log_issue(
+                "Codex non installe.",
+                "avertissement",
+                "codex_logout",
+                LogTarget::Codex,
+            );
+            return;
+        }
+        self.core.invalidate_codex_status();
+        let argv = codex_logout_argv(Some(&self.root_dir), Some(&env_map));
+        self.codex_log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "deconnexion Codex",
+            LogTarget::Codex,
+            ProcessKind::CodexLogout,
+        );
+    }
+
+    fn action_codex_check(&mut self) {
+        let env_map = self.codex_env();
+        if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
+            if !self.ensure_node_available(&env_map, LogTarget::Codex) {
+                return;
+            }
+            self.log_issue(
+                "Codex non installe.",
+                "avertissement",
+                "codex_status",
+                LogTarget::Codex,
+            );
+            return;
+        }
+        let node_path = node_executable(&self.root_dir, Some(&env_map));
+        let entry_path = codex_entrypoint_js(&codex_install_prefix(&self.root_dir));
+        let resolved = resolve_in_path("codex", &env_map);
+        self.codex_log_ui(format!(
+            "node: {}",
+            node_path
+                .map(|p| p.display().to_string())
+                .unwrap_or("absent".into())
+        ));
+        self.codex_log_ui(format!(
+            "entrypoint: {}",
+            entry_path
+                .map(|p| p.display().to_string())
+                .unwrap_or("absent".into())
+        ));
+        self.codex_log_ui(format!(
+            "codex (PATH): {}",
+            resolved
+                .map(|p| p.display().to_string())
+                .unwrap_or("absent".into())
+        ));
+        let argv = codex_status_argv(Some(&self.root_dir), Some(&env_map));
+        self.codex_log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "verification Codex",
+            LogTarget::Codex,
+            ProcessKind::CodexStatus,
+        );
+    }
+
+    /// Liste des outils dev (Ctrl+D) : disponibilite, cases a cocher et paquets en plus.
+    fn action_dev_tools(&mut self) {
+        self.dev_tools = self.core.dev_tool_entries();
+        self.dev_tools_selected = 0;
+        self.open_prompt(
+            PromptKind::DevTools,
+            "Paquets en plus (virgules) - Espace: cocher, Entree: installer",
+        );
+    }
+
+    fn handle_dev_tools_key(&mut self, key: KeyEvent) {
+        let count = self.dev_tools.len();
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Up => self.dev_tools_selected = self.dev_tools_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.dev_tools_selected =
+                    (self.dev_tools_selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(entry) = self.dev_tools.get_mut(self.dev_tools_selected) {
+                    entry.selected = !entry.selected;
+                }
+            }
+            KeyCode::Enter => {
+                let Some(prompt) = self.prompt.take() else {
+                    return;
+                };
+                let tools = dev_tools_to_install(&self.dev_tools, &prompt.input.value);
+                self.install_dev_tools(tools);
+            }
+            _ => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.input.handle_key(key);
+                }
+            }
+        }
+    }
+
+    /// pip install des paquets choisis dans le prefixe portable des outils.
+    fn install_dev_tools(&mut self, tools: Vec<String>) {
+        if tools.is_empty() {
+            self.log_issue(
+                "Aucun outil selectionne.",
+                "avertissement",
+                "outils_dev",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let env_map = self.tools_env();
+        let prefix = tools_install_prefix(&self.root_dir);
+        let _ = fs::create_dir_all(&prefix);
+        let wheelhouse = match self.core.pip_wheelhouse() {
+            Ok(wheelhouse) => wheelhouse,
+            Err(message) => {
+                self.log_issue(&message, "erreur", "hors_ligne", LogTarget::Main);
+                return;
+            }
+        };
+        let argv =
+            match pip_install_argv(&prefix, &tools, wheelhouse.as_deref(), wheelhouse.is_some()) {
+                Ok(argv) => self.core.with_python(argv),
+                Err(err) => {
+                    self.log_issue(
+                        &format!("Impossible d'installer outils: {err}"),
+                        "erreur",
+                        "outils_dev",
+                        LogTarget::Main,
+                    );
+                    return;
+                }
+            };
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "installation outils dev",
+            LogTarget::Main,
+            ProcessKind::DevTools,
+        );
+    }
+
+    fn action_install_requirements(&mut self) {
+        let req_path = self.root_dir.join("requirements.txt");
+        if !req_path.is_file() {
+            self.log_issue(
+                "Aucun requirements.txt a la racine du workspace.",
+                "avertissement",
+                "requirements",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let env_map = self.tools_env();
+        // Avec un venv actif, les dependances du projet vont dans le venv.
+        let prefix = self
+            .core
+            .active_venv()
+            .unwrap_or_else(|| tools_install_prefix(&self.root_dir));
+        let _ = fs::create_dir_all(&prefix);
+        let wheelhouse = match self.core.pip_wheelhouse() {
+            Ok(wheelhouse) => wheelhouse,
+            Err(message) => {
+                self.log_issue(&message, "erreur", "hors_ligne", LogTarget::Main);
+                return;
+            }
+        };
+        let argv = match pip_install_requirements_argv(
+            &prefix,
+            &req_path,
+            wheelhouse.as_deref(),
+            wheelhouse.is_some(),
+        ) {
+            Ok(argv) => self.core.with_python(argv),
+            Err(err) => {
+                self.log_issue(
+                    &format!("Impossible d'installer les dependances: {err}"),
+                    "erreur",
+                    "requirements",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let source = match wheelhouse.as_deref() {
+            Some(wheels) => format!("wheelhouse {}", wheels.display()),
+            None => "index pip".to_string(),
+        };
+        self.log_ui(format!(
+            "Installation des dependances de {} ({source})",
+            req_path.display()
+        ));
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "installation requirements",
+            LogTarget::Main,
+            ProcessKind::Requirements,
+        );
+    }
+
+    fn action_create_venv(&mut self) {
+        if let Some(venv) = self.core.active_venv() {
+            self.log_ui(format!("Venv deja actif: {}", venv.display()));
+            return;
+        }
+        let venv = self.core.workspace().venv_dir().to_path_buf();
+        let argv = venv_create_argv(&venv, &self.core.base_python());
+        self.log_ui(format!("Creation du venv: {}", venv.display()));
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        let env_map = self.portable_env(std::env::vars().collect());
+        self.spawn_process(
+            argv,
+            env_map,
+            "creation venv",
+            LogTarget::Main,
+            ProcessKind::Venv,
+        );
+    }
+
+    fn action_toggle_build_onefile(&mut self) {
+        self.build_onefile = !self.build_onefile;
+        let label = if self.build_onefile {
+            "onefile (un seul executable)"
+        } else {
+            "onedir (dossier)"
+        };
+        self.log_ui(format!("Build PyInstaller: {label}"));
+    }
+
+    /// Sauve puis formate le fichier .py courant (black ou ruff format, USBIDE_FORMATTER).
+    fn action_format(&mut self) {
+        let path = self.current().map(|current| current.path.clone());
+        let Some(path) = path.filter(|path| {
+            path.extension()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.eq_ignore_ascii_case("py"))
+        }) else {
+            self.log_issue(
+                "Ouvre un fichier .py.",
+                "avertissement",
+                "formatage",
+                LogTarget::Main,
+            );
+            return;
+        };
+        if self
+            .core
+            .running
+            .iter()
+            .any(|proc| proc.kind == ProcessKind::Format)
+        {
+            self.log_ui("Formatage deja en cours.".to_string());
+            return;
+        }
+        self.action_save();
+        if self.current().is_some_and(|current| current.dirty) {
+            return;
+        }
+        let formatter = self.core.settings().formatter();
+        let env_map = self.tools_env();
+        if !tool_available(formatter.as_str(), Some(&self.root_dir), Some(&env_map))
+            .unwrap_or(false)
+        {
+            self.log_issue(
+                &format!(
+                    "{} introuvable : ajoute-le aux outils dev (Ctrl+D) ou change USBIDE_FORMATTER.",
+                    formatter.as_str()
+                ),
+                "avertissement",
+                "formatage",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let argv = match format_argv(formatter, &path) {
+            Ok(argv) => argv,
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur formatage: {err}"),
+                    "erreur",
+                    "formatage",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        self.format_target = Some(path);
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "formatage",
+            LogTarget::Main,
+            ProcessKind::Format,
+        );
+    }
+
+    /// Sauve puis lance ruff et mypy (ceux installes) sur le fichier .py courant.
+    fn action_lint(&mut self) {
+        let path = self.current().map(|current| current.path.clone());
+        let Some(path) = path.filter(|path| {
+            path.extension()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.eq_ignore_ascii_case("py"))
+        }) else {
+            self.log_issue(
+                "Ouvre un fichier .py.",
+                "avertissement",
+                "lint",
+                LogTarget::Main,
+            );
+            return;
+        };
+        if self
+            .core
+            .running
+            .iter()
+            .any(|proc| matches!(proc.kind, ProcessKind::Lint(_)))
+        {
+            self.log_ui("Lint deja en cours.".to_string());
+            return;
+        }
+        self.action_save();
+        if self.current().is_some_and(|current| current.dirty) {
+            return;
+        }
+        let env_map = self.tools_env();
+        let linters: Vec<Linter> = Linter::ALL
+            .into_iter()
+            .filter(|linter| {
+                tool_available(linter.as_str(), Some(&self.root_dir), Some(&env_map))
+                    .unwrap_or(false)
+            })
+            .collect();
+        if linters.is_empty() {
+            self.log_issue(
+                "ruff et mypy introuvables : ajoute-les aux outils dev (Ctrl+D).",
+                "avertissement",
+                "lint",
+                LogTarget::Main,
+            );
+            return;
+        }
+        self.diagnostics.clear_path(&path);
+        self.lint_target = Some(path.clone());
+        for linter in linters {
+            match lint_argv(linter, &path) {
+                Ok(argv) => {
+                    self.log_ui(format!("$ {}", argv.join(" ")));
+                    self.spawn_process(
+                        argv,
+                        env_map.clone(),
+                        "lint",
+                        LogTarget::Main,
+                        ProcessKind::Lint(linter),
+                    );
+                }
+                Err(err) => self.log_issue(
+                    &format!("Erreur lint: {err}"),
+                    "erreur",
+                    "lint",
+                    LogTarget::Main,
+                ),
+            }
+        }
+    }
+
+    /// Ligne de sortie d'un linter : diagnostic rattache au chemin tel qu'ouvert dans les onglets.
+    fn record_lint_line(&mut self, linter: Linter, line: &str) {
+        if let Some(mut diagnostic) = parse_diagnostic(line, linter, &self.root_dir) {
+            if let Ok(path) = diagnostic.path.canonicalize() {
+                diagnostic.path = path;
+            }
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn action_diagnostics(&mut self) {
+        if self.diagnostics.is_empty() {
+            self.log_ui("Aucun diagnostic : lance un lint (Alt+K).".to_string());
+            return;
+        }
+        self.diagnostics_selected = 0;
+        self.open_prompt(PromptKind::Diagnostics, "Diagnostics");
+    }
+
+    /// Ouvre l'assistant si Node, Python ou Codex manquent ; sinon lancement normal.
+    fn show_setup_if_needed(&mut self) {
+        self.setup_missing = self.core.setup_missing();
+        if !self.setup_missing.is_empty() {
+            self.open_prompt(PromptKind::Setup, "Premier lancement");
+        }
+    }
+
+    fn handle_setup_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => self.prompt = None,
+            KeyCode::Char('r' | 'R') => {
+                self.prompt = None;
+                self.show_setup_if_needed();
+                if self.prompt.is_none() {
+                    self.log_ui("Prerequis en place : Node, Python et Codex.".to_string());
+                }
+            }
+            KeyCode::Char('i' | 'I')
+                if self.setup_missing.iter().any(SetupMissing::installable) =>
+            {
+                self.prompt = None;
+                self.action_codex_install();
+            }
+            _ => {}
+        }
+    }
+
+    /// Gestionnaire de fichiers du systeme sur `dir`, sans attendre ; avertit s'il manque.
+    fn open_in_file_manager(&mut self, dir: &Path) {
+        let argv = file_manager_argv(dir);
+        let env_map = self.portable_env(std::env::vars().collect());
+        if resolve_in_path(&argv[0], &env_map).is_none() {
+            self.log_issue(
+                &format!(
+                    "{} introuvable : ouvre {} manuellement.",
+                    argv[0],
+                    dir.display()
+                ),
+                "avertissement",
+                "gestionnaire de fichiers",
+                LogTarget::Main,
+            );
+            return;
+        }
+        self.log_ui(format!("Ouverture de {}", dir.display()));
+        self.spawn_process(
+            argv,
+            env_map,
+            "gestionnaire de fichiers",
+            LogTarget::Main,
+            ProcessKind::OpenFolder,
+        );
+    }
+
+    fn action_clear_caches(&mut self) {
+        let label = cache_usage_label(&self.core.cache_usage());
+        self.open_prompt(
+            PromptKind::ConfirmClearCaches,
+            &format!("Nettoyer caches : {label} ? (o/n)"),
+        );
+    }
+
+    fn clear_caches(&mut self) {
+        let (freed, errors) = self.core.clear_caches();
+        for error in errors {
+            self.log_issue(
+                &format!("Nettoyage du cache incomplet: {error}"),
+                "avertissement",
+                "caches",
+                LogTarget::Main,
+            );
+        }
+        self.log_ui(format!("Caches nettoyes : {} liberes.", format_size(freed)));
+    }
+
+    fn action_python_repl(&mut self) {
+        self.start_repl();
+        self.open_prompt(PromptKind::Repl, ">>>");
+    }
+
+    fn handle_repl_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Char('r') if ctrl => self.restart_repl(),
+            KeyCode::Char('l') if ctrl => self.repl_output.clear(),
+            KeyCode::Enter => {
+                let Some(prompt) = self.prompt.as_mut() else {
+                    return;
+                };
+                let line = std::mem::take(&mut prompt.input.value);
+                prompt.input = InputField::with_value("");
+                self.send_repl_line(line);
+            }
+            _ => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.input.handle_key(key);
+                }
+            }
+        }
+    }
+
+    /// Lance le REPL Python (interpreteur choisi, venv actif) s'il ne tourne pas deja.
+    fn start_repl(&mut self) {
+        if self.core.repl().is_some() {
+            return;
+        }
+        let argv = python_repl_argv(self.core.run_venv().as_deref(), &self.core.base_python());
+        self.push_repl_output(format!("[{}]", argv[0]));
+        let env_map = self.portable_env(std::env::vars().collect());
+        self.spawn_process(
+            argv,
+            env_map,
+            "REPL Python",
+            LogTarget::Main,
+            ProcessKind::Repl,
+        );
+    }
+
+    fn restart_repl(&mut self) {
+        self.core.stop_repl();
+        self.push_repl_output("[REPL redemarre]".to_string());
+        self.start_repl();
+    }
+
+    /// Envoie une ligne (meme vide : fin d'un bloc) au REPL, relance s'il s'est arrete.
+    fn send_repl_line(&mut self, line: String) {
+        if self.core.repl().is_none() {
+            self.start_repl();
+        }
+        self.push_repl_output(format!(">>> {line}"));
+        let result = self.core.repl().map(|handle| handle.write_line(&line));
+        if let Some(Err(err)) = result {
+            self.log_issue(
+                &format!("Envoi au REPL impossible: {err}"),
+                "erreur",
+                "REPL Python",
+                LogTarget::Main,
+            );
+        }
+    }
+
+    fn push_repl_output(&mut self, line: String) {
+        self.repl_output.push(line);
+        if self.repl_output.len() > LOG_LIMIT {
+            let drain = self.repl_output.len() - LOG_LIMIT;
+            self.repl_output.drain(0..drain);
+        }
+    }
+
+    fn action_env_inspector(&mut self) {
+        self.env_scroll = 0;
+        self.open_prompt(PromptKind::EnvInspector, "CLE=valeur");
+    }
+
+    fn handle_env_inspector_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Tab => {
+                self.env_profile = self.env_profile.next();
+                self.env_scroll = 0;
+            }
+            KeyCode::Up => self.env_scroll = self.env_scroll.saturating_sub(1),
+            KeyCode::Down => {
+                let total = self.core.env_entries(self.env_profile).len();
+                self.env_scroll = (self.env_scroll + 1).min(total.saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                let Some(prompt) = self.prompt.as_mut() else {
+                    return;
+                };
+                let raw = std::mem::take(&mut prompt.input.value);
+                prompt.input = InputField::with_value("");
+                match parse_env_assignment(&raw) {
+                    Some((key, value)) => {
+                        self.log_ui(format!("Variable {key} ajoutee pour le prochain process."));
+                        self.core.set_pending_env(key, value);
+                    }
+                    None if raw.trim().is_empty() => {}
+                    None => self.log_ui("Format attendu : CLE=valeur.".to_string()),
+                }
+            }
+            _ => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.input.handle_key(key);
+                }
+            }
+        }
+    }
+
+    fn action_select_python(&mut self) {
+        let pythons = self.core.pythons();
+        if pythons.is_empty() {
+            self.log_ui("Aucun interpreteur Python detecte : detection relancee.".to_string());
+            self.detect_pythons();
+            return;
+        }
+        let current = self.core.python_interpreter();
+        self.python_selected = pythons
+            .iter()
+            .position(|python| python.exe == current)
+            .unwrap_or(0);
+        self.open_prompt(PromptKind::PythonPicker, "Python");
+    }
+
+    fn handle_python_picker_key(&mut self, key: KeyEvent) {
+        let pythons = self.core.pythons();
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Up => self.python_selected = self.python_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.python_selected =
+                    (self.python_selected + 1).min(pythons.len().saturating_sub(1));
+            }
+            KeyCode::Char('r' | 'R') => {
+                self.prompt = None;
+                self.detect_pythons();
+            }
+            KeyCode::Enter => {
+                self.prompt = None;
+                if let Some(python) = pythons.get(self.python_selected) {
+                    self.choose_python(python);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn action_environment_report(&mut self) {
+        self.environment_report = self.core.environment_report();
+        self.open_prompt(PromptKind::EnvironmentReport, "Environnement");
+    }
+
+    fn handle_environment_report_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Char('r' | 'R') => self.environment_report = self.core.environment_report(),
+            KeyCode::Char('c' | 'C') => {
+                let text = self.environment_report.join("\n");
+                self.copy_to_clipboard(text, "Rapport d'environnement");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_diagnostics_key(&mut self, key: KeyEvent) {
+        let count = self.diagnostics.len();
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Up => self.diagnostics_selected = self.diagnostics_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.diagnostics_selected =
+                    (self.diagnostics_selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                let choice = self
+                    .diagnostics
+                    .iter()
+                    .nth(self.diagnostics_selected)
+                    .cloned();
+                self.prompt = None;
+                if let Some(diagnostic) = choice {
+                    self.jump_to_diagnostic(diagnostic);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Ouvre le fichier du diagnostic et place le curseur sur sa ligne et sa colonne.
+    fn jump_to_diagnostic(&mut self, diagnostic: Diagnostic) {
+        self.open_file(diagnostic.path.clone());
+        if self
+            .current()
+            .is_none_or(|current| current.path != diagnostic.path)
+        {
+            return;
+        }
+        let row = diagnostic.line.saturating_sub(1);
+        let col = diagnostic.column.unwrap_or(1).saturating_sub(1);
+        self.editor_mut()
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+        self.focus = Focus::Editor;
+    }
+
+    /// Sauve les onglets modifies puis lance pytest a la racine (USBIDE_PYTEST_ARGS, `--lf`).
+    fn action_run_tests(&mut self, last_failed: bool) {
+        if self.tests_running() {
+            self.log_ui("Tests deja en cours.".to_string());
+            return;
+        }
+        for idx in self.tabs.dirty_indices() {
+            self.save_tab(idx);
+        }
+        let env_map = self.tools_env();
+        if !tool_available("pytest", Some(&self.root_dir), Some(&env_map)).unwrap_or(false) {
+            self.log_issue(
+                "pytest introuvable : ajoute-le aux outils dev (Ctrl+D).",
+                "avertissement",
+                "tests",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let argv = pytest_argv(&pytest_args_from_env(), last_failed);
+        self.pytest_summary = None;
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(argv, env_map, "tests", LogTarget::Main, ProcessKind::Pytest);
+    }
+
+    fn action_stop_tests(&mut self) {
+        if self.core.kill_processes(ProcessKind::Pytest) == 0 {
+            self.log_ui("Aucun test en cours.".to_string());
+            return;
+        }
+        self.log_ui("Tests interrompus.".to_string());
+    }
+
+    fn tests_running(&self) -> bool {
+        self.core
+            .running
+            .iter()
+            .any(|proc| proc.kind == ProcessKind::Pytest)
+    }
+
+    /// Barre d'etat : tests en cours ou resume du dernier passage (et s'il est vert).
+    fn test_status(&self) -> Option<(String, bool)> {
+        if self.tests_running() {
+            return Some(("Tests: en cours...".to_string(), true));
+        }
+        self.pytest_summary
+            .map(|summary| (summary.label(), summary.is_success()))
+    }
+
+    fn action_build_exe(&mut self) {
+        let (path, dirty) = match self.current() {
+            Some(current) => (current.path.clone(), current.dirty),
+            None => {
+                self.log_issue(
+                    "Ouvre un fichier .py.",
+                    "avertissement",
+                    "build_exe",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        let is_py = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.eq_ignore_ascii_case("py"))
+            .unwrap_or(false);
+        if !is_py {
+            self.log_issue(
+                "Ouvre un fichier .py.",
+                "avertissement",
+                "build_exe",
+                LogTarget::Main,
+            );
+            return;
+        }
+        if dirty {
+            self.action_save();
+        }
+        let env_map = self.tools_env();
+        if !pyinstaller_available(Some(&self.root_dir), Some(&env_map))
+            && !self.install_pyinstaller(false)
+        {
+            self.log_issue(
+                "PyInstaller indisponible.",
+                "erreur",
+                "build_exe",
+                LogTarget::Main,
+            );
+            return;
+        }
+        let dist_dir = self.root_dir.join("dist");
+        let _ = fs::create_dir_all(&dist_dir);
+        let argv = match pyinstaller_build_argv(
+            &path,
+            &dist_dir,
+            self.build_onefile,
+            Some(&self.root_dir.join("tmp")),
+            None,
+            None,
+            None,
+        ) {
+            Ok(argv) => argv,
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur build: {err}"),
+                    "erreur",
+                    "build_exe",
+                    LogTarget::Main,
+                );
+                return;
+            }
+        };
+        self.build_output = Some(pyinstaller_output_path(
+            &path,
+            &dist_dir,
+            self.build_onefile,
+            None,
+        ));
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "construction exe",
+            LogTarget::Main,
+            ProcessKind::PyInstallerBuild,
+        );
+    }
+
+    fn install_pyinstaller(&mut self, force: bool) -> bool {
+        let env_map = self.tools_env();
+        if !force && pyinstaller_available(Some(&self.root_dir), Some(&env_map)) {
+            return true;
+        }
+        if !force && self.core.pyinstaller_install_attempted {
+            return false;
+        }
+        self.core.pyinstaller_install_attempted = true;
+        let prefix = tools_install_prefix(&self.root_dir);
+        let _ = fs::create_dir_all(&prefix);
+        let wheelhouse = match self.core.pip_wheelhouse() {
+            Ok(wheelhouse) => wheelhouse,
+            Err(message) => {
+                self.log_issue(&message, "erreur", "hors_ligne", LogTarget::Main);
+                return false;
+            }
+        };
+        let argv =
+            match pyinstaller_install_argv(&prefix, wheelhouse.as_deref(), wheelhouse.is_some()) {
+                Ok(argv) => self.core.with_python(argv),
+                Err(err) => {
+                    self.log_issue(
+                        &format!("Impossible d'installer PyInstaller: {err}"),
+                        "erreur",
+                        "installation_pyinstaller",
+                        LogTarget::Main,
+                    );
+                    return false;
+                }
+            };
+        self.log_ui(format!(
+            "Installation PyInstaller (bin={})",
+            prefix.display()
+        ));
+        self.log_ui(format!("$ {}", argv.join(" ")));
+        self.spawn_process(
+            argv,
+            env_map,
+            "installation PyInstaller",
+            LogTarget::Main,
+            ProcessKind::PyInstallerInstall,
+        );
+        true
+    }
+
+    fn codex_device_auth_enabled(&self) -> bool {
+        self.core.codex_device_auth_enabled()
+    }
+
+    fn codex_auto_install_enabled(&self) -> bool {
+        self.core.codex_auto_install_enabled()
+    }
+
+    fn install_codex(&mut self, force: bool, target: LogTarget) -> bool {
+        let env_map = self.codex_env();
+        if !force && codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
+            return true;
+        }
+        if !force && self.core.codex_install_attempted {
+            self.log_issue(
+                "Installation Codex deja tentee. (Ctrl+I pour forcer)",
+                "avertissement",
+                "installation_codex",
+                target,
+            );
+            return false;
+        }
+        if !force && !self.codex_auto_install_enabled() {
+            self.log_issue(
+                "Auto-install Codex desactive. (Ctrl+I pour installer)",
+                "avertissement",
+                "installation_codex",
+                target,
+            );
+            return false;
+        }
+        if !self.ensure_node_available(&env_map, target) {
+            return false;
+        }
+        for warning in self.core.node_version_warnings() {
+            self.log_issue(&warning, "avertissement", "installation_codex", target);
+        }
+        self.core.codex_install_attempted = true;
+        let (package, network, warning) = self.core.codex_install_source();
+        if let Some(warning) = warning {
+            self.log_issue(&warning, "avertissement", "installation_codex", target);
+        }
+        let prefix = codex_install_prefix(&self.root_dir);
+        if let Err(err) = fs::create_dir_all(&prefix) {
+            self.log_issue(
+                &format!(
+                    "Impossible de creer le dossier d'installation Codex: {} ({err})",
+                    prefix.display()
+                ),
+                "erreur",
+                "installation_codex",
+                target,
+            );
+            return false;
+        }
+        let argv = match codex_install_argv(&self.root_dir, &prefix, &package, network) {
+            Ok(argv) => argv,
+            Err(CodexError::NodeMissing) => {
+                self.log_issue(
+                    "Node portable introuvable. Place node dans tools/node (ex: node.exe). Fallback Node hote possible via USBIDE_CODEX_ALLOW_HOST_NODE=1.",
+                    "erreur",
+                    "installation_codex",
+                    target,
+                );
+                return false;
+            }
+            Err(CodexError::NpmMissing) => {
+                self.log_issue(
+                    "npm-cli.js introuvable. Verifie ton Node portable (npm inclus).",
+                    "erreur",
+                    "installation_codex",
+                    target,
+                );
+                return false;
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Impossible d'installer Codex: {err}"),
+                    "erreur",
+                    "installation_codex",
+                    target,
+                );
+                return false;
+            }
+        };
+        self.push_log(
+            target,
+            format!(
+                "Installation Codex package={package} prefix={}",
+                prefix.display()
+            ),
+            LogKind::Info,
+        );
+        self.push_log(target, format!("$ {}", argv.join(" ")), LogKind::Info);
+        self.spawn_process(
+            argv,
+            env_map,
+            "installation Codex",
+            target,
+            ProcessKind::CodexInstall,
+        )
+    }
+
+    fn change_shell_cwd(&mut self, target: &str) {
+        match resolve_shell_cwd(&self.root_dir, &self.shell_cwd, target) {
+            Some(dir) => {
+                self.shell_cwd = dir;
+                self.log_ui(format!(
+                    "Dossier courant: {}",
+                    shell_cwd_label(&self.root_dir, &self.shell_cwd)
+                ));
+            }
+            None => self.log_issue(
+                &format!("cd refuse: '{target}' introuvable ou hors du workspace."),
+                "avertissement",
+                "commande shell",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    /// Le dossier courant du shell suit la selection de l'arborescence.
+    fn follow_tree_selection(&mut self) {
+        self.shell_cwd = target_dir_for(self.tree.selected_entry(), &self.root_dir);
+    }
+
+    /// Envoie la saisie au stdin de la commande shell en cours (au lieu d'en lancer une).
+    fn send_shell_input(&mut self, line: String) {
+        let Some(result) = self
+            .core
+            .interactive_shell()
+            .map(|handle| handle.write_line(&line))
+        else {
+            return;
+        };
+        match result {
+            Ok(()) => self.log_ui(format!("> {line}")),
+            Err(err) => self.log_issue(
+                &format!("Envoi au process impossible: {err}"),
+                "erreur",
+                "commande shell",
+                LogTarget::Main,
+            ),
+        }
+    }
+
+    fn send_shell_eof(&mut self) {
+        if let Some(handle) = self.core.interactive_shell() {
+            handle.close_stdin();
+            self.log_ui("EOF envoye au process".to_string());
+        }
+    }
+
+    fn run_shell(&mut self, cmd: String) {
+        if cmd.is_empty() {
+            return;
+        }
+        self.log_ui(format!("$ {cmd}"));
+        if let Err(err) = self.cmd_history.push(self.core.workspace(), &cmd) {
+            self.log_issue(
+                &format!("Erreur ecriture historique commandes: {err}"),
+                "erreur",
+                "cmd_history",
+                LogTarget::Main,
+            );
+        }
+        if let Some(target) = parse_cd_command(&cmd) {
+            self.change_shell_cwd(target);
+            return;
+        }
+        if self.core.settings().shell_guard() {
+            let risks = shell_command_risks(&cmd, &self.root_dir, &self.shell_cwd);
+            if !risks.is_empty() {
+                self.log_issue(
+                    &format!("Commande shell a confirmer: {}", risks.join(", ")),
+                    "avertissement",
+                    "garde-fou shell",
+                    LogTarget::Main,
+                );
+                let label = format!(
+                    "Commande risquee ({}) : executer quand meme ? (o/n)",
+                    risks.join(", ")
+                );
+                self.open_prompt(PromptKind::ConfirmShell(cmd), &label);
+                return;
+            }
+        }
+        self.spawn_shell(cmd);
+    }
+
+    fn spawn_shell(&mut self, cmd: String) {
+        let argv = if cfg!(windows) {
+            windows_cmd_argv(&cmd)
+        } else {
+            vec!["sh".to_string(), "-lc".to_string(), cmd]
+        };
+        let env_map = self.portable_env(std::env::vars().collect());
+        if !self.shell_cwd.is_dir() {
+            // Dossier renomme/supprime depuis : retour au root.
+            self.shell_cwd = self.root_dir.clone();
+        }
+        let cwd = self.shell_cwd.clone();
+        self.spawn_process_in(
+            argv,
+            env_map,
+            "commande shell",
+            LogTarget::Main,
+            ProcessKind::Shell,
+            &cwd,
+        );
+    }
+
+    /// Texte selectionne dans l'editeur (None sans selection).
+    fn editor_selection(&self) -> Option<String> {
+        if !self.editor().is_selecting() {
+            return None;
+        }
+        let mut probe = self.editor().clone();
+        probe.copy();
+        Some(probe.yank_text()).filter(|text| !text.is_empty())
+    }
+
+    /// Selection, sinon fichier courant entier (sauf `selection_only`) : (source, contenu).
+    fn codex_context(&self, selection_only: bool) -> Option<(String, String)> {
+        let tab = self.tabs.active()?;
+        let relative = tab
+            .file
+            .path
+            .strip_prefix(&self.root_dir)
+            .unwrap_or(&tab.file.path);
+        match self.editor_selection() {
+            Some(selected) => Some((format!("selection de {}", relative.display()), selected)),
+            None if !selection_only => Some((
+                format!("fichier {}", relative.display()),
+                tab.buffer.lines().join("\n"),
+            )),
+            None => None,
+        }
+    }
+
+    fn action_ask_codex_about_file(&mut self) {
+        let Some((source, content)) = self.codex_context(false) else {
+            self.log_issue(
+                "Aucun fichier ouvert.",
+                "avertissement",
+                "codex",
+                LogTarget::Codex,
+            );
+            return;
+        };
+        let label = format!("Question a Codex ({source}, vide : explication)");
+        self.open_prompt(PromptKind::CodexQuestion(source, content), &label);
+    }
+
+    fn action_explain_selection(&mut self) {
+        let Some((source, content)) = self.codex_context(true) else {
+            self.log_issue(
+                "Aucune selection dans l'editeur.",
+                "avertissement",
+                "codex",
+                LogTarget::Codex,
+            );
+            return;
+        };
+        self.ask_codex_with_context(&source, &content, "");
+    }
+
+    /// Envoie la question avec le contexte joint (question vide : demande d'explication).
+    fn ask_codex_with_context(&mut self, source: &str, content: &str, question: &str) {
+        let question = match question.trim() {
+            "" => "Explique ce code.",
+            question => question,
+        };
+        self.send_codex_prompt(codex_context_prompt(source, content, question));
+    }
+
+    /// Prompt saisi par l'utilisateur : confirmation d'abord en sandbox danger-full-access.
+    fn send_codex_prompt(&mut self, prompt: String) {
+        if prompt.is_empty() {
+            return;
+        }
+        if self.codex_sandbox_mode == CodexSandboxMode::DangerFullAccess
+            && codex_confirm_danger_from_env()
+        {
+            self.open_prompt(
+                PromptKind::ConfirmDangerSandbox(prompt),
+                &format!("{CODEX_DANGER_WARNING} Lancer quand meme ? (o/n)"),
+            );
+            return;
+        }
+        self.run_codex(prompt);
+    }
+
+    fn run_codex(&mut self, prompt: String) {
+        if prompt.is_empty() {
+            return;
+        }
+        if self.core.codex_turn_active() {
+            let display = codex_prompt_display(&prompt);
+            let queued = self.core.queue_codex_prompt(prompt);
+            self.codex_log_action(&format!("En attente ({queued} en file) : {display}"));
+            return;
+        }
+        self.core.cancel_codex_retry();
+        if self.codex_view != CodexView::Raw {
+            self.codex_log_user_message(&codex_prompt_display(&prompt));
+        }
+        let env_map = self.codex_env();
+        if !codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
+            if !self.ensure_node_available(&env_map, LogTarget::Codex) {
+                return;
+            }
+            if self.install_codex(false, LogTarget::Codex) {
+                self.core.set_pending_codex_prompt(prompt);
+            }
+            return;
+        }
+
+        if !self.codex_caps_checked {
+            if self.codex_caps_running {
+                self.core.set_pending_codex_prompt(prompt);
+                return;
+            }
+            self.codex_caps_running = true;
+            self.codex_caps_buffer.clear();
+            let argv = codex_exec_help_argv(Some(&self.root_dir), Some(&env_map));
+            if self.spawn_process(
+                argv,
+                env_map,
+                "codex_caps",
+                LogTarget::Codex,
+                ProcessKind::CodexCaps,
+            ) {
+                self.core.set_pending_codex_prompt(prompt);
+            } else {
+                self.codex_caps_running = false;
+            }
+            return;
+        }
+
+        if self.core.codex_status_cached() {
+            self.codex_last_prompt = Some(prompt.clone());
+            self.spawn_codex_exec(&prompt);
+            return;
+        }
+        self.codex_log_action("Verification de la connexion Codex...");
+        let argv = codex_status_argv(Some(&self.root_dir), Some(&env_map));
+        if self.spawn_process(
+            argv,
+            env_map,
+            "codex_status",
+            LogTarget::Codex,
+            ProcessKind::CodexStatus,
+        ) {
+            self.codex_status_started = Some(Instant::now());
+            self.core.set_pending_codex_prompt(prompt);
+        }
+    }
+
+    fn spawn_process(
+        &mut self,
+        argv: Vec<String>,
+        env_map: HashMap<String, String>,
+        contexte: &str,
+        target: LogTarget,
+        kind: ProcessKind,
+    ) -> bool {
+        let root_dir = self.root_dir.clone();
+        self.spawn_process_in(argv, env_map, contexte, target, kind, &root_dir)
+    }
+
+    fn spawn_process_in(
+        &mut self,
+        argv: Vec<String>,
+        env_map: HashMap<String, String>,
+        contexte: &str,
+        target: LogTarget,
+        kind: ProcessKind,
+        cwd: &Path,
+    ) -> bool {
+        let mut env_map = env_map;
+        let temporaires = self.core.apply_pending_env(kind, &mut env_map);
+        if !temporaires.is_empty() {
+            self.log_ui(format!(
+                "Variables temporaires pour {contexte}: {}",
+                temporaires.join(", ")
+            ));
+        }
+        // Les commandes shell gardent un stdin ouvert (REPL, prompts) alimente par la saisie.
+        let spawned = if matches!(kind, ProcessKind::Shell | ProcessKind::Repl) {
+            NativeProcessRunner.spawn_interactive(&argv, Some(cwd), Some(&env_map))
+        } else {
+            NativeProcessRunner.spawn(&argv, Some(cwd), Some(&env_map))
+        };
+        match spawned {
+            Ok(handle) => {
+                self.core
+                    .running
+                    .push(RunningProcess::new(handle, kind, target, contexte));
+                true
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur execution {contexte}: {err}"),
+                    "erreur",
+                    contexte,
+                    target,
+                );
+                false
+            }
+        }
+    }
+
+    fn drain_process_events(&mut self) {
+        let mut active = std::mem::take(&mut self.core.running);
+        let mut remaining = Vec::new();
+
+        for mut proc in active.drain(..) {
+            let mut finished = false;
+            while let Ok(event) = proc.handle.rx.try_recv() {
+                proc.touch();
+                match event.kind {
+                    ProcEventKind::Line => {
+                        self.handle_process_line(&mut proc, &event.text);
+                    }
+                    ProcEventKind::Exit => {
+                        if let Some(code) = event.returncode
+                            && code != 0
+                        {
+                            let should_log = match proc.kind {
+                                ProcessKind::CodexExec => {
+                                    !(self.codex_retry_without_sandbox
+                                        || self.codex_retry_without_approval)
+                                }
+                                ProcessKind::CodexCaps => false,
+                                // rc=1 : problemes trouves, deja listes en diagnostics.
+                                ProcessKind::Lint(_) => code > 1,
+                                // 1 : tests en echec (resume), 5 : aucun test collecte.
+                                ProcessKind::Pytest => !matches!(code, 1 | 5),
+                                // Hors depot ou git trop ancien : pas de decorations, sans bruit.
+                                ProcessKind::GitStatus
+                                | ProcessKind::PythonVersion(_)
+                                | ProcessKind::NodeVersion(_)
+                                | ProcessKind::Repl
+                                // explorer renvoie 1 meme quand le dossier s'ouvre.
+                                | ProcessKind::OpenFolder => false,
+                                _ => true,
+                            };
+                            if should_log {
+                                self.log_issue(
+                                    &format!("{} terminee en erreur (rc={code}).", proc.contexte),
+                                    "erreur",
+                                    &proc.contexte,
+                                    proc.target,
+                                );
+                            }
+                        } else if event.returncode.is_none() && proc.kind != ProcessKind::CodexCaps
+                        {
+                            self.log_issue(
+                                &format!("{} interrompue: {}", proc.contexte, event.text),
+                                "avertissement",
+                                &proc.contexte,
+                                proc.target,
+                            );
+                        }
+                        self.handle_process_exit(&mut proc, event.returncode);
+                        finished = true;
+                        break;
+                    }
+                }
+            }
+
+            if !finished && proc.timed_out() {
+                // Pas de join : un petit-enfant peut garder les pipes ouverts apres le kill.
+                let _ = proc.handle.kill();
+                self.log_issue(
+                    &proc.timeout_message(),
+                    "erreur",
+                    &proc.contexte,
+                    proc.target,
+                );
+                self.handle_process_exit(&mut proc, Some(PROC_TIMEOUT_EXIT_CODE));
+            } else if finished {
+                proc.handle.join();
+            } else {
+                remaining.push(proc);
+            }
+        }
+
+        let mut spawned = std::mem::take(&mut self.core.running);
+        remaining.append(&mut spawned);
+        self.core.running = remaining;
+    }
+
+    fn handle_process_line(&mut self, proc: &mut RunningProcess, line: &str) {
+        if proc.kind.reports_install_progress()
+            && let Some(progress) = parse_install_progress(line)
+        {
+            self.install_progress = Some((proc.target, progress));
+        }
+        match proc.kind {
+            ProcessKind::CodexExec => self.handle_codex_line(line),
+            ProcessKind::CodexCaps => self.handle_codex_caps_line(line),
+            ProcessKind::Lint(linter) => {
+                self.record_lint_line(linter, line);
+                self.push_ansi_log(proc.target, line);
+            }
+            ProcessKind::GitStatus => self.git_status_lines.push(line.to_string()),
+            ProcessKind::PythonVersion(idx) => self.core.record_python_version(idx, line),
+            ProcessKind::NodeVersion(tool) => self.core.record_node_version(tool, line),
+            ProcessKind::Repl => self.push_repl_output(line.to_string()),
+            ProcessKind::Git(command) => {
+                match command {
+                    GitCommand::Commit => {
+                        if let Some(hash) = parse_commit_hash(line) {
+                            self.git_commit_hash = Some(hash);
+                        }
+                    }
+                    GitCommand::Diff => self.git_diff_lines += 1,
+                    GitCommand::AddAll => {}
+                }
+                self.push_ansi_log(proc.target, line);
+            }
+            ProcessKind::Pytest => {
+                if let Some(summary) = parse_pytest_summary(line) {
+                    self.pytest_summary = Some(summary);
+                }
+                self.push_ansi_log(proc.target, line);
+            }
+            _ => self.push_ansi_log(proc.target, line),
+        }
+    }
+
+    fn handle_process_exit(&mut self, proc: &mut RunningProcess, code: Option<i32>) {
+        proc.remove_temp_file();
+        if proc.kind.reports_install_progress() {
+            self.install_progress = None;
+            if code != Some(0) && self.core.settings().offline() {
+                self.log_issue(
+                    "Mode hors-ligne : paquet absent du wheelhouse (tools/wheels) ou du cache npm (cache/npm).",
+                    "avertissement",
+                    "hors_ligne",
+                    proc.target,
+                );
+            }
+        }
+        match proc.kind {
+            ProcessKind::CodexLogout => {
+                self.core.invalidate_codex_status();
+                if code == Some(0) {
+                    self.codex_log_action(
+                        "Deconnexion Codex effectuee. Refais Login (Ctrl+K) avant le prochain prompt.",
+                    );
+                } else {
+                    self.codex_log_action(
+                        "Echec de la deconnexion Codex : verifie l'etat avec Status (Ctrl+T).",
+                    );
+                }
+            }
+            ProcessKind::CodexStatus => {
+                self.codex_status_started = None;
+                self.core.record_codex_status(code == Some(0));
+                if let Some(prompt) = self.core.take_pending_codex_prompt() {
+                    if code == Some(0) {
+                        self.codex_last_prompt = Some(prompt.clone());
+                        self.spawn_codex_exec(&prompt);
+                    } else {
+                        self.codex_log_action(
+                            "Echec de la verification du login Codex (status en erreur).",
+                        );
+                        self.clear_codex_queue();
+                        self.codex_log_action(
+                            "Si tu n'es pas authentifie, fais Login puis recommence.",
+                        );
+                        self.codex_log_action(
+                            "Si tu es deja authentifie, verifie l'installation et la connexion.",
+                        );
+                        if !self.codex_device_auth_enabled() {
+                            self.codex_log_action(
+                                "Astuce: si le navigateur ne s'ouvre pas, definis USBIDE_CODEX_DEVICE_AUTH=1 puis Ctrl+K.",
+                            );
+                        }
+                    }
+                }
+            }
+            ProcessKind::CodexCaps => {
+                self.codex_caps_running = false;
+                self.codex_caps_checked = true;
+                let lower = self.codex_caps_buffer.to_lowercase();
+                if !lower.is_empty() {
+                    if !lower.contains("--sandbox") {
+                        self.codex_sandbox_supported = Some(false);
+                    }
+                    if !lower.contains("--ask-for-approval") {
+                        self.codex_approval_supported = Some(false);
+                    }
+                    if self.codex_sandbox_supported == Some(false)
+                        || self.codex_approval_supported == Some(false)
+                    {
+                        self.codex_log_action(
+                            "Version Codex ancienne: sandbox/approbations indisponibles. Mets a jour pour un mode agent complet.",
+                        );
+                    }
+                }
+                self.codex_caps_buffer.clear();
+                if let Some(prompt) = self.core.take_pending_codex_prompt() {
+                    self.run_codex(prompt);
+                }
+            }
+            ProcessKind::CodexExec => {
+                if self.codex_view != CodexView::Raw {
+                    self.flush_codex_assistant_buffer();
+                }
+                if self.codex_retry_without_sandbox || self.codex_retry_without_approval {
+                    self.codex_retry_without_sandbox = false;
+                    self.codex_retry_without_approval = false;
+                    if let Some(prompt) = self.codex_last_prompt.clone() {
+                        self.spawn_codex_exec(&prompt);
+                    }
+                } else {
+                    let retry = self
+                        .core
+                        .finish_codex_exec(code == Some(0), self.codex_last_prompt.is_some());
+                    let retrying = matches!(retry, CodexRetry::Scheduled(_));
+                    if let CodexRetry::Scheduled(msg) | CodexRetry::GaveUp(msg) = retry {
+                        self.codex_log_action(&msg);
+                    }
+                    if !retrying {
+                        self.record_codex_turn();
+                        self.run_next_queued_codex();
+                    }
+                }
+            }
+            ProcessKind::GitStatus => {
+                self.git_status_running = false;
+                let lines = std::mem::take(&mut self.git_status_lines);
+                let status = if code == Some(0) {
+                    GitStatusMap::parse(lines.iter().map(String::as_str), &self.root_dir)
+                } else {
+                    GitStatusMap::default()
+                };
+                self.tree.data.set_git_status(status);
+                if self.git_status_stale {
+                    self.refresh_git_status();
+                }
+            }
+            ProcessKind::Git(GitCommand::AddAll) => {
+                if let Some(message) = self.git_commit_message.take()
+                    && code == Some(0)
+                {
+                    self.git_commit_hash = None;
+                    let env_map = self.tools_env();
+                    self.run_git(GitCommand::Commit, git_commit_argv(&message), env_map);
+                } else {
+                    self.refresh_git_status();
+                }
+            }
+            ProcessKind::Git(GitCommand::Commit) => {
+                if code == Some(0) {
+                    match self.git_commit_hash.take() {
+                        Some(hash) => self.log_ui(format!("Commit {hash} cree.")),
+                        None => self.log_ui("Commit cree.".to_string()),
+                    }
+                }
+                self.refresh_git_status();
+            }
+            ProcessKind::Git(GitCommand::Diff) if code == Some(0) && self.git_diff_lines == 0 => {
+                self.log_ui("Aucune difference avec HEAD.".to_string());
+            }
+            // Un `git commit` ou `git checkout` a pu etre lance dans le shell.
+            ProcessKind::Shell => self.refresh_git_status(),
+            ProcessKind::Pytest => match self.pytest_summary {
+                Some(summary) if !summary.is_success() => self.log_issue(
+                    &format!("{} (Alt+Shift+T: relancer les echecs)", summary.label()),
+                    "avertissement",
+                    "tests",
+                    LogTarget::Main,
+                ),
+                Some(summary) => self.log_ui(summary.label()),
+                None if code == Some(5) => self.log_ui("Aucun test trouve.".to_string()),
+                None => {}
+            },
+            ProcessKind::Lint(linter) => {
+                if let Some(path) = self.lint_target.clone() {
+                    let count = self
+                        .diagnostics
+                        .for_path(&path)
+                        .iter()
+                        .filter(|diagnostic| diagnostic.source == linter)
+                        .count();
+                    self.log_ui(format!(
+                        "Lint {} termine: {count} diagnostic(s) (Alt+D: liste).",
+                        linter.as_str()
+                    ));
+                }
+            }
+            ProcessKind::Format => {
+                if let Some(path) = self.format_target.take()
+                    && code == Some(0)
+                    && let Some(idx) = self.tabs.position(&path)
+                {
+                    self.sync_tab_with_disk(idx, &[path]);
+                }
+            }
+            ProcessKind::PyInstallerBuild => {
+                if let Some(output) = self.build_output.take()
+                    && code == Some(0)
+                {
+                    self.log_ui(format!("Build termine: {}", output.display()));
+                    if let Some(dist) = output.parent() {
+                        self.open_prompt(
+                            PromptKind::RevealFolder(dist.to_path_buf()),
+                            &format!(
+                                "Ouvrir {} dans le gestionnaire de fichiers ? (o/n)",
+                                dist.display()
+                            ),
+                        );
+                    }
+                }
+            }
+            ProcessKind::Venv => {
+                self.python_label = self.core.python_status_label();
+                if code == Some(0) {
+                    self.log_ui(format!("Interpreteur actif: {}", self.python_label));
+                    self.detect_pythons();
+                }
+            }
+            ProcessKind::PythonVersion(_) => self.finish_python_probe(),
+            ProcessKind::NodeVersion(tool) => self.finish_node_probe(tool),
+            ProcessKind::Repl => {
+                let rc = code.map_or("?".to_string(), |code| code.to_string());
+                self.push_repl_output(format!("[REPL termine (rc={rc})]"));
+            }
+            ProcessKind::CodexInstall => {
+                let env_map = self.codex_env();
+                if codex_cli_available(Some(&self.root_dir), Some(&env_map)) {
+                    self.codex_log_ui("Codex installe.".to_string());
+                    if let Some(prompt) = self.core.take_pending_codex_prompt() {
+                        self.run_codex(prompt);
+                    }
+                } else if self.core.take_pending_codex_prompt().is_some() {
+                    self.codex_log_action("Echec de l'installation Codex : prompt abandonne.");
+                    self.clear_codex_queue();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_codex_line(&mut self, line: &str) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        if self.handle_sandbox_flag_line(trimmed) || self.handle_approval_flag_line(trimmed) {
+            return;
+        }
+        if self.codex_retry_without_sandbox || self.codex_retry_without_approval {
+            return;
+        }
+        if let Some(translated) = translate_codex_line(trimmed) {
+            if self.codex_view != CodexView::Raw {
+                self.codex_log_action(&translated);
+            } else {
+                self.codex_log_ui(translated);
+            }
+            return;
+        }
+
+        let value: serde_json::Value = match serde_json::from_str(trimmed) {
+            Ok(val) => val,
+            Err(_) => {
+                if self.codex_view != CodexView::Raw {
+                    self.codex_log_action(trimmed);
+                } else {
+                    self.codex_log_output(trimmed.to_string());
+                }
+                return;
+            }
+        };
+
+        let event_type = value
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+        if self.codex_view != CodexView::Raw {
+            if matches!(
+                event_type,
+                "response.output_text.delta" | "response.output_text"
+            ) {
+                let delta = value
+                    .get("delta")
+                    .or_else(|| value.get("text"))
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or("");
+                if !delta.is_empty() {
+                    self.codex_assistant_buffer.push_str(delta);
+                }
+                return;
+            }
+            if matches!(
+                event_type,
+                "response.output_text.done" | "response.output_item.done" | "response.completed"
+            ) {
+                self.flush_codex_assistant_buffer();
+                return;
+            }
+        }
+
+        if event_type == "error" {
+            let msg = value
+                .get("message")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("");
+            self.note_codex_failure(msg);
+            if self.codex_view != CodexView::Raw {
+                if let Some(translated) = translate_codex_line(msg) {
+                    self.codex_log_action(&translated);
+                } else if let Some(status) = extract_status_code(msg) {
+                    self.codex_log_action(&format!("Erreur Codex HTTP {status}."));
+                    if let Some(hint) = codex_hint_for_status(status) {
+                        self.codex_log_action(&hint);
+                    }
+                } else {
+                    self.codex_log_action(
+                        "Erreur Codex: une erreur est survenue. Consulte le journal ou relance.",
+                    );
+                }
+            } else {
+                self.codex_log_ui("Erreur Codex: une erreur est survenue.".to_string());
+            }
+            return;
+        }
+
+        if event_type == "turn.failed" {
+            let msg = value
+                .get("error")
+                .and_then(|err| err.get("message").or_else(|| err.get("text")))
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("");
+            self.note_codex_failure(msg);
+            if self.codex_view != CodexView::Raw {
+                if let Some(translated) = translate_codex_line(msg) {
+                    self.codex_log_action(&translated);
+                } else if let Some(status) = extract_status_code(msg) {
+                    self.codex_log_action(&format!("Tache echouee HTTP {status}."));
+                    if let Some(hint) = codex_hint_for_status(status) {
+                        self.codex_log_action(&hint);
+                    }
+                } else {
+                    self.codex_log_action("Tache echouee: une erreur est survenue.");
+                }
+            } else {
+                self.codex_log_ui("Tache echouee.".to_string());
+            }
+            return;
+        }
+
+        if self.codex_view == CodexView::Raw {
+            // En vue brute, la reponse est quand meme gardee pour l'historique.
+            for item in extract_display_items(&value) {
+                if item.kind == DisplayKind::Assistant {
+                    self.record_codex_response(&item.message);
+                }
+            }
+        }
+        if self.codex_view != CodexView::Raw {
+            for item in extract_display_items(&value) {
+                if !self.codex_view.shows(&item.kind) {
+                    continue;
+                }
+                match item.kind {
+                    DisplayKind::Assistant
+                        if is_streamed_duplicate(&self.codex_streamed_text, &item.message) => {}
+                    DisplayKind::Assistant => self.codex_log_message(&item.message),
+                    DisplayKind::User => self.codex_log_user_message(&item.message),
+                    DisplayKind::Action | DisplayKind::Reasoning | DisplayKind::ToolResult => {
+                        self.codex_log_action(&item.message)
+                    }
+                }
+            }
+        } else if let Some(event_type) = value.get("type").and_then(serde_json::Value::as_str) {
+            self.codex_log_output(format!("[{event_type}] {value}"));
+        } else {
+            self.codex_log_output(value.to_string());
+        }
+    }
+
+    fn handle_codex_caps_line(&mut self, line: &str) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        if !self.codex_caps_buffer.is_empty() {
+            self.codex_caps_buffer.push('\n');
+        }
+        self.codex_caps_buffer.push_str(trimmed);
+    }
+
+    fn codex_log_entry(&mut self, msg: &str, label: &str, kind: LogKind) {
+        let cleaned = msg.trim();
+        if cleaned.is_empty() {
+            return;
+        }
+        let fingerprint = format!("{label}:{cleaned}");
+        if self.last_codex_message.as_deref() == Some(&fingerprint) {
+            return;
+        }
+        self.last_codex_message = Some(fingerprint);
+        self.push_log(LogTarget::Codex, label.to_string(), LogKind::Action);
+        let width = self.last_codex_width.saturating_sub(4) as usize;
+        for (line, code) in crate::codex::wrap_text_blocks(msg, width) {
+            if code {
+                self.push_code_log(line, kind, width);
+            } else if line.is_empty() {
+                self.push_log(LogTarget::Codex, String::new(), LogKind::Info);
+            } else {
+                self.push_log(LogTarget::Codex, line, kind);
+            }
+        }
+        self.push_log(LogTarget::Codex, String::new(), LogKind::Info);
+    }
+
+    fn codex_log_user_message(&mut self, msg: &str) {
+        self.codex_log_entry(msg, "Utilisateur", LogKind::User);
+    }
+
+    fn codex_log_action(&mut self, msg: &str) {
+        self.codex_log_entry(msg, "Action", LogKind::Action);
+    }
+
+    /// Affiche le message reconstitue des deltas et le retient pour ignorer sa repetition.
+    fn flush_codex_assistant_buffer(&mut self) {
+        if self.codex_assistant_buffer.is_empty() {
+            return;
+        }
+        let message = std::mem::take(&mut self.codex_assistant_buffer);
+        self.codex_log_message(&message);
+        if !self.codex_streamed_text.is_empty() {
+            self.codex_streamed_text.push('\n');
+        }
+        self.codex_streamed_text.push_str(&message);
+    }
+
+    fn codex_log_message(&mut self, msg: &str) {
+        self.record_codex_response(msg);
+        let cleaned = msg.trim();
+        if !cleaned.is_empty() {
+            self.last_assistant_message = Some(cleaned.to_string());
+        }
+        self.codex_log_entry(msg, "Assistant", LogKind::Assistant);
+    }
+
+    fn record_codex_response(&mut self, msg: &str) {
+        let cleaned = msg.trim();
+        if cleaned.is_empty() || self.codex_turn_response.ends_with(cleaned) {
+            return;
+        }
+        if !self.codex_turn_response.is_empty() {
+            self.codex_turn_response.push_str("\n\n");
+        }
+        self.codex_turn_response.push_str(cleaned);
+    }
+
+    /// Lance `codex exec` pour le prompt (options sandbox/approbations selon la version).
+    fn spawn_codex_exec(&mut self, prompt: &str) {
+        let env_map = self.codex_env();
+        let extra_args = self.codex_exec_extra_args();
+        self.codex_exec_used_sandbox_flag = extra_args.iter().any(|arg| arg == "--sandbox");
+        self.codex_exec_used_approval_flag =
+            extra_args.iter().any(|arg| arg == "--ask-for-approval");
+        self.codex_turn_response.clear();
+        self.codex_streamed_text.clear();
+        self.core.begin_codex_exec();
+        match codex_exec_argv(
+            &self.codex_exec_prompt(prompt),
+            Some(&self.root_dir),
+            Some(&env_map),
+            true,
+            Some(&extra_args),
+        ) {
+            Ok(argv) => {
+                if self.codex_view == CodexView::Raw {
+                    self.codex_log_ui(format!("$ {}", argv.join(" ")));
+                }
+                self.spawn_process(
+                    argv,
+                    env_map,
+                    "codex_exec",
+                    LogTarget::Codex,
+                    ProcessKind::CodexExec,
+                );
+            }
+            Err(err) => {
+                self.log_issue(
+                    &format!("Erreur Codex: {err}"),
+                    "erreur",
+                    "codex_exec",
+                    LogTarget::Codex,
+                );
+            }
+        }
+    }
+
+    /// Transmet au core le statut HTTP d'une erreur Codex (relance, cache du pre-check).
+    fn note_codex_failure(&mut self, msg: &str) {
+        if let Some(status) = extract_status_code(msg) {
+            self.core.note_codex_http_status(status);
+        }
+    }
+
+    fn poll_codex_retry(&mut self) {
+        if !self.core.codex_retry_due() {
+            return;
+        }
+        if let Some(prompt) = self.codex_last_prompt.clone() {
+            self.spawn_codex_exec(&prompt);
+        }
+    }
+
+    /// Enregistre l'echange termine (prompt utilisateur + reponse) dans l'historique.
+    fn record_codex_turn(&mut self) {
+        let response = std::mem::take(&mut self.codex_turn_response);
+        let Some(prompt) = self.codex_last_prompt.take() else {
+            return;
+        };
+        if response.is_empty() {
+            return;
+        }
+        // Le fichier joint ne va pas dans l'historique (rappel du prompt, contexte de suite).
+        let turn = CodexTurn {
+            prompt: codex_prompt_display(&prompt),
+            response,
+        };
+        if let Err(err) = self.codex_history.push(self.core.workspace(), turn) {
+            self.log_issue(
+                &format!("Erreur ecriture historique Codex: {err}"),
+                "erreur",
+                "codex_history",
+                LogTarget::Codex,
+            );
+        }
+    }
+
+    /// Prompt envoye a Codex : enrichi des derniers echanges si la session est continuee.
+    fn codex_exec_prompt(&self, prompt: &str) -> String {
+        if self.codex_continue_session {
+            self.codex_history.context_prompt(prompt)
+        } else {
+            prompt.to_string()
+        }
+    }
+}
+
+/// Couleurs 0-15 vers les couleurs nommees du terminal (respecte son theme), le reste tel quel.
+fn ansi_color(color: AnsiColor) -> Color {
+    match color {
+        AnsiColor::Indexed(index) => match index {
+            0 => Color::Black,
+            1 => Color::Red,
+            2 => Color::Green,
+            3 => Color::Yellow,
+            4 => Color::Blue,
+            5 => Color::Magenta,
+            6 => Color::Cyan,
+            7 => Color::Gray,
+            8 => Color::DarkGray,
+            9 => Color::LightRed,
+            10 => Color::LightGreen,
+            11 => Color::LightYellow,
+            12 => Color::LightBlue,
+            13 => Color::LightMagenta,
+            14 => Color::LightCyan,
+            15 => Color::White,
+            index => Color::Indexed(index),
+        },
+        AnsiColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+fn ansi_style(ansi: AnsiStyle) -> Style {
+    let mut style = Style::default();
+    if let Some(fg) = ansi.fg {
+        style = style.fg(ansi_color(fg));
+    }
+    if let Some(bg) = ansi.bg {
+        style = style.bg(ansi_color(bg));
+    }
+    for (enabled, modifier) in [
+        (ansi.bold, Modifier::BOLD),
+        (ansi.dim, Modifier::DIM),
+        (ansi.italic, Modifier::ITALIC),
+        (ansi.underline, Modifier::UNDERLINED),
+    ] {
+        if enabled {
+            style = style.add_modifier(modifier);
+        }
+    }
+    style
+}
+
+/// Raccourci du keymap ; une majuscule implique Shift (selon le terminal, SHIFT n'est pas signale).
+fn key_chord(key: KeyEvent) -> Option<KeyChord> {
+    let chord_key = match key.code {
+        KeyCode::Char(ch) => ChordKey::Char(ch),
+        KeyCode::F(number) => ChordKey::F(number),
+        KeyCode::Tab => ChordKey::Tab,
+        KeyCode::BackTab => ChordKey::Tab,
+        _ => return None,
+    };
+    Some(KeyChord::new(
+        key.modifiers.contains(KeyModifiers::CONTROL),
+        key.modifiers.contains(KeyModifiers::ALT),
+        key.modifiers.contains(KeyModifiers::SHIFT) || key.code == KeyCode::BackTab,
+        chord_key,
+    ))
+}
+
+/// Couleur d'une entree de l'arborescence selon son statut git.
+fn git_status_color(status: GitStatus) -> Color {
+    match status {
+        GitStatus::Modified => Color::Yellow,
+        GitStatus::Added | GitStatus::Untracked => Color::Green,
+        GitStatus::Renamed => Color::Cyan,
+        GitStatus::Deleted | GitStatus::Conflict => Color::Red,
+    }
+}
+
+fn tab_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Ratio relu de la session, ramene dans ses bornes (defaut si absent).
+fn session_ratio(value: Option<f32>, (default, min, max): (u16, u16, u16)) -> u16 {
+    value.map_or(default, |value| (value.round() as u16).clamp(min, max))
+}
+
+/// Nouveau decalage (lignes remontees depuis la fin) d'un panneau de log ;
+/// `None` si la touche ne fait pas defiler.
+fn scrolled_rows(rows_up: usize, code: KeyCode, total: usize) -> Option<usize> {
+    const PAGE: usize = 10;
+    let rows_up = match code {
+        KeyCode::Up => rows_up + 1,
+        KeyCode::Down => rows_up.saturating_sub(1),
+        KeyCode::PageUp => rows_up + PAGE,
+        KeyCode::PageDown => rows_up.saturating_sub(PAGE),
+        KeyCode::Home => usize::MAX,
+        KeyCode::End => 0,
+        _ => return None,
+    };
+    Some(rows_up.min(total.saturating_sub(1)))
+}
+
+/// Marqueur de titre tant que le panneau ne suit plus la fin du log.
+fn scrollback_marker(rows_up: usize) -> String {
+    format!(" -- historique (+{rows_up} lignes en dessous) --")
+}
+
+fn kind_style(kind: LogKind) -> Style {
+    match kind {
+        LogKind::Info => Style::default(),
+        LogKind::Warn => Style::default().fg(Color::Yellow),
+        LogKind::Error => Style::default().fg(Color::Red),
+        LogKind::User => Style::default().fg(Color::Blue),
+        LogKind::Assistant => Style::default().fg(Color::Green),
+        LogKind::Action => Style::default().fg(Color::DarkGray),
+    }
+}
+
+/// Style au rendu ; les en-tetes des messages Codex prennent la couleur du message, en gras.
+fn log_style(line: &LogLine) -> Style {
+    match codex_label_kind(&line.text) {
+        Some(kind) if line.kind == LogKind::Action => kind_style(kind).add_modifier(Modifier::BOLD),
+        _ => kind_style(line.kind),
+    }
+}
+
+fn token_style(kind: TokenKind) -> Style {
+    match kind {
+        TokenKind::Keyword => Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD),
+        TokenKind::String => Style::default().fg(Color::Green),
+        TokenKind::Comment => Style::default().fg(Color::DarkGray),
+        TokenKind::Number => Style::default().fg(Color::Cyan),
+    }
+}
+
+fn highlighted_line(
+    lang: Language,
+    line: &str,
+    state: &mut LineState,
+    tab: usize,
+) -> Line<'static> {
+    let expand = |text: &str| text.replace('\t', &" ".repeat(tab));
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for token in highlight_line(lang, line, state) {
+        if token.start > pos {
+            spans.push(Span::raw(expand(&line[pos..token.start])));
+        }
+        spans.push(Span::styled(
+            expand(&line[token.start..token.end]),
+            token_style(token.kind),
+        ));
+        pos = token.end;
+    }
+    if pos < line.len() {
+        spans.push(Span::raw(expand(&line[pos..])));
+    }
+    Line::from(spans)
+}
+
+/// Colonne d'affichage du curseur (tabulations developpees sur `tab` colonnes).
+fn display_width(line: &str, col: usize, tab: usize) -> usize {
+    line.chars()
+        .take(col)
+        .map(|ch| if ch == '\t' { tab } else { 1 })
+        .sum()
+}
+
+/// Nombre de rangees occupees par une ligne repliee a `width` colonnes.
+fn wrapped_rows(line: &str, width: usize, tab: usize) -> usize {
+    display_width(line, usize::MAX, tab) / width + 1
+}
+
+/// Premiere ligne visible en mode repli : la plus proche de `top` qui garde le curseur affiche.
+fn wrapped_top(
+    lines: &[String],
+    top: usize,
+    row: usize,
+    display_col: usize,
+    width: usize,
+    height: usize,
+    tab: usize,
+) -> usize {
+    if row <= top {
+        return row;
+    }
+    let mut used = display_col / width + 1;
+    let mut first = row;
+    while first > top {
+        let rows = wrapped_rows(&lines[first - 1], width, tab);
+        if used + rows > height {
+            break;
+        }
+        used += rows;
+        first -= 1;
+    }
+    first
+}
+
+/// Coupe une ligne stylee en rangees de `width` colonnes (meme decompte que `wrapped_rows`).
+fn wrap_styled_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let mut rows = vec![Line::default()];
+    let mut used = 0;
+    for span in line.spans {
+        let mut chunk = String::new();
+        for ch in span.content.chars() {
+            if used == width {
+                if let Some(last) = rows.last_mut() {
+                    last.push_span(Span::styled(std::mem::take(&mut chunk), span.style));
+                }
+                rows.push(Line::default());
+                used = 0;
+            }
+            chunk.push(ch);
+            used += 1;
+        }
+        if !chunk.is_empty()
+            && let Some(last) = rows.last_mut()
+        {
+            last.push_span(Span::styled(chunk, span.style));
+        }
+    }
+    if used == width {
+        rows.push(Line::default());
+    }
+    rows
+}
+
+/// Decale la fenetre visible juste assez pour garder le curseur affiche.
+fn follow_cursor(offset: usize, cursor: usize, size: usize) -> usize {
+    if size == 0 || cursor < offset {
+        cursor
+    } else if cursor >= offset + size {
+        cursor + 1 - size
+    } else {
+        offset
+    }
+}
+
+fn text_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = text.lines().map(|s| s.to_string()).collect();
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Remplace toutes les occurrences litterales de `query`, ligne par ligne.
+fn replace_all_in_lines(lines: &[String], query: &str, replacement: &str) -> (Vec<String>, usize) {
+    if query.is_empty() {
+        return (lines.to_vec(), 0);
+    }
+    let mut count = 0;
+    let replaced = lines
+        .iter()
+        .map(|line| {
+            count += line.matches(query).count();
+            line.replace(query, replacement)
+        })
+        .collect();
+    (replaced, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::PreviewKind;
+    use std::path::Path;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_env_lock<F: FnOnce()>(f: F) {
+        let _guard = ENV_LOCK.lock().unwrap();
+        f();
+    }
+
+    fn canonical_root(path: &Path) -> PathBuf {
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    fn set_env(key: &str, value: &str) {
+        unsafe {
+            std::env::set_var(key, value);
+        }
+    }
+
+    fn remove_env(key: &str) {
+        unsafe {
+            std::env::remove_var(key);
+        }
+    }
+
+    #[test]
+    fn refresh_title_sans_fichier() {
+        let dir = TempDir::new().unwrap();
+        let app = App::new(dir.path().to_path_buf()).unwrap();
+        let expected_root = canonical_root(dir.path());
+        assert_eq!(app.title, APP_NAME);
+        assert_eq!(app.sub_title, expected_root.display().to_string());
+    }
+
+    #[test]
+    fn refresh_title_avec_fichier_dirty() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.tabs.open(EditorTab {
+            file: OpenFile {
+                path: dir.path().join("main.py"),
+                encoding: "utf-8".to_string(),
+                bom: false,
+                line_ending: crate::fs::LineEnding::Crlf,
+                dirty: true,
+                preview: None,
+                missing_on_disk: false,
+            },
+            buffer: App::make_editor(),
+        });
+        app.refresh_title();
+        assert_eq!(app.title, format!("{APP_NAME} *"));
+        assert!(app.sub_title.contains("main.py"));
+        assert!(app.sub_title.contains("utf-8, CRLF"));
+        assert!(app.sub_title.contains("Ln 1, Col 1"));
+        assert!(app.sub_title.contains("(1 lignes)  0 mots  modifie"));
+
+        app.focus = Focus::Editor;
+        for c in "print(42) # ok !".chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert!(app.sub_title.contains("  4 mots"), "{}", app.sub_title);
+    }
+
+    #[test]
+    fn portable_env_defauts() {
+        let dir = TempDir::new().unwrap();
+        let app = App::new(dir.path().to_path_buf()).unwrap();
+        let env = app.portable_env(HashMap::new());
+        let expected_root = canonical_root(dir.path());
+        assert_eq!(
+            env.get("PIP_CACHE_DIR").unwrap(),
+            &expected_root
+                .join("cache")
+                .join("pip")
+                .display()
+                .to_string()
+        );
+        assert_eq!(
+            env.get("PYTHONPYCACHEPREFIX").unwrap(),
+            &expected_root
+                .join("cache")
+                .join("pycache")
+                .display()
+                .to_string()
+        );
+        assert_eq!(
+            env.get("TEMP").unwrap(),
+            &expected_root.join("tmp").display().to_string()
+        );
+        assert_eq!(
+            env.get("TMP").unwrap(),
+            &expected_root.join("tmp").display().to_string()
+        );
+        assert_eq!(env.get("PYTHONNOUSERSITE").unwrap(), "1");
+        assert_eq!(
+            env.get("CODEX_HOME").unwrap(),
+            &expected_root.join("codex_home").display().to_string()
+        );
+        assert_eq!(
+            env.get("NPM_CONFIG_CACHE").unwrap(),
+            &expected_root
+                .join("cache")
+                .join("npm")
+                .display()
+                .to_string()
+        );
+        assert_eq!(env.get("NPM_CONFIG_UPDATE_NOTIFIER").unwrap(), "false");
+    }
+
+    #[test]
+    fn ensure_portable_dirs_cree_usbide() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let _app = App::new(dir.path().to_path_buf()).unwrap();
+        assert!(root.join(".usbide").is_dir());
+        assert!(root.join(".usbide").join("codex").is_dir());
+        assert!(root.join(".usbide").join("tools").is_dir());
+    }
+
+    #[test]
+    fn sanitize_codex_env_supprime() {
+        let dir = TempDir::new().unwrap();
+        let app = App::new(dir.path().to_path_buf()).unwrap();
+        with_env_lock(|| {
+            let mut env = HashMap::from([
+                ("OPENAI_API_KEY".to_string(), "sk-test".to_string()),
+                ("CODEX_API_KEY".to_string(), "sk-codex".to_string()),
+                (
+                    "OPENAI_BASE_URL".to_string(),
+                    "https://example.com".to_string(),
+                ),
+            ]);
+            remove_env("USBIDE_CODEX_ALLOW_API_KEY");
+            remove_env("USBIDE_CODEX_ALLOW_CUSTOM_BASE");
+            app.core.sanitize_codex_env(&mut env);
+            assert!(!env.contains_key("OPENAI_API_KEY"));
+            assert!(!env.contains_key("CODEX_API_KEY"));
+            assert!(!env.contains_key("OPENAI_BASE_URL"));
+        });
+    }
+
+    #[test]
+    fn sanitize_codex_env_respecte_overrides() {
+        let dir = TempDir::new().unwrap();
+        let app = App::new(dir.path().to_path_buf()).unwrap();
+        with_env_lock(|| {
+            let mut env = HashMap::from([
+                ("OPENAI_API_KEY".to_string(), "sk-test".to_string()),
+                ("CODEX_API_KEY".to_string(), "sk-codex".to_string()),
+                (
+                    "OPENAI_BASE_URL".to_string(),
+                    "https://example.com".to_string(),
+                ),
+            ]);
+            set_env("USBIDE_CODEX_ALLOW_API_KEY", "1");
+            set_env("USBIDE_CODEX_ALLOW_CUSTOM_BASE", "true");
+            app.core.sanitize_codex_env(&mut env);
+            assert_eq!(env.get("OPENAI_API_KEY").unwrap(), "sk-test");
+            assert_eq!(env.get("CODEX_API_KEY").unwrap(), "sk-codex");
+            assert_eq!(env.get("OPENAI_BASE_URL").unwrap(), "https://example.com");
+            remove_env("USBIDE_CODEX_ALLOW_API_KEY");
+            remove_env("USBIDE_CODEX_ALLOW_CUSTOM_BASE");
+        });
+    }
+
+    #[test]
+    fn codex_flags_env() {
+        let dir = TempDir::new().unwrap();
+        let app = App::new(dir.path().to_path_buf()).unwrap();
+        with_env_lock(|| {
+            set_env("USBIDE_CODEX_DEVICE_AUTH", "1");
+            assert!(app.codex_device_auth_enabled());
+            set_env("USBIDE_CODEX_DEVICE_AUTH", "0");
+            assert!(!app.codex_device_auth_enabled());
+            remove_env("USBIDE_CODEX_DEVICE_AUTH");
+            set_env("USBIDE_CODEX_AUTO_INSTALL", "0");
+            assert!(!app.codex_auto_install_enabled());
+            set_env("USBIDE_CODEX_AUTO_INSTALL", "1");
+            assert!(app.codex_auto_install_enabled());
+            remove_env("USBIDE_CODEX_AUTO_INSTALL");
+            set_env("USBIDE_CODEX_SANDBOX", "workspace-write");
+            set_env("USBIDE_CODEX_APPROVAL", "never");
+            set_env("USBIDE_CODEX_MODEL", "o4-mini");
+            let app2 = App::new(dir.path().to_path_buf()).unwrap();
+            assert_eq!(app2.codex_sandbox_mode, CodexSandboxMode::WorkspaceWrite);
+            assert_eq!(app2.codex_approval_policy, CodexApprovalPolicy::Never);
+            assert!(
+                app2.codex_exec_extra_args()
+                    .windows(2)
+                    .any(|pair| pair == ["--model", "o4-mini"])
+            );
+            remove_env("USBIDE_CODEX_SANDBOX");
+            remove_env("USBIDE_CODEX_APPROVAL");
+            remove_env("USBIDE_CODEX_MODEL");
+        });
+    }
+
+    #[test]
+    fn settings_toml_genere_et_prioritaire_apres_env() {
+        let dir = TempDir::new().unwrap();
+        let app = App::new(dir.path().to_path_buf()).unwrap();
+        let path = dir.path().join(".usbide").join("settings.toml");
+        assert!(fs::read_to_string(&path).unwrap().contains("dev_tools = "));
+        drop(app);
+
+        fs::write(
+            &path,
+            "codex_sandbox = \"read-only\"\ncodex_model = \"gpt-5\"\ncodex_allow_api_key = true\nrien = 1\n",
+        )
+        .unwrap();
+        with_env_lock(|| {
+            let app = App::new(dir.path().to_path_buf()).unwrap();
+            assert_eq!(app.codex_sandbox_mode, CodexSandboxMode::ReadOnly);
+            assert!(
+                app.codex_exec_extra_args()
+                    .windows(2)
+                    .any(|pair| pair == ["--model", "gpt-5"])
+            );
+            assert!(app.log.iter().any(
+                |line| line.text.contains("settings.toml ignore") && line.text.contains("rien")
+            ));
+            let mut env = HashMap::from([("OPENAI_API_KEY".to_string(), "sk-test".to_string())]);
+            app.core.sanitize_codex_env(&mut env);
+            assert_eq!(env.get("OPENAI_API_KEY").unwrap(), "sk-test");
+
+            set_env("USBIDE_CODEX_SANDBOX", "danger-full-access");
+            let app = App::new(dir.path().to_path_buf()).unwrap();
+            assert_eq!(app.codex_sandbox_mode, CodexSandboxMode::DangerFullAccess);
+            remove_env("USBIDE_CODEX_SANDBOX");
+        });
+    }
+
+    #[test]
+    fn record_issue_cree_bug_md() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.core
+            .record_issue("erreur", "Erreur test", "test_unitaire", None);
+        let contenu = fs::read_to_string(app.core.workspace().bug_log_path()).unwrap();
+        assert!(contenu.contains("niveau: erreur"));
+        assert!(contenu.contains("contexte: test_unitaire"));
+        assert!(contenu.contains("message: Erreur test"));
+    }
+
+    #[test]
+    fn nouveau_fichier_selectionne_apres_creation() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::create_dir_all(root.join("src")).unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.submit_prompt(PromptKind::NewFile(root.join("src")), "main.py".to_string());
+
+        assert!(root.join("src").join("main.py").is_file());
+        assert_eq!(
+            app.tree.selected_entry().map(|entry| entry.path.clone()),
+            Some(root.join("src").join("main.py"))
+        );
+
+        app.submit_prompt(PromptKind::NewFile(root.join("src")), "main.py".to_string());
+        let contenu = fs::read_to_string(app.core.workspace().bug_log_path()).unwrap();
+        assert!(contenu.contains("Existe deja"));
+    }
+
+    #[test]
+    fn renommer_fichier_ouvert_met_a_jour_chemin() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "print('a')\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.submit_prompt(PromptKind::Rename(root.join("a.py")), "b.py".to_string());
+
+        assert_eq!(
+            app.current().map(|current| current.path.clone()),
+            Some(root.join("b.py"))
+        );
+        assert!(root.join("b.py").is_file());
+    }
+
+    #[test]
+    fn supprimer_fichier_ouvert_ferme_editeur() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "print('a')\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.submit_prompt(PromptKind::ConfirmDelete(root.join("a.py")), String::new());
+
+        assert!(app.current().is_none());
+        assert!(!root.join("a.py").exists());
+        assert!(root.join(".usbide").join("trash").is_dir());
+    }
+
+    #[test]
+    fn supprimer_dossier_garde_onglet_modifie() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::create_dir(root.join("pkg")).unwrap();
+        fs::write(root.join("pkg").join("a.py"), "print('a')\n").unwrap();
+        fs::write(root.join("pkg").join("b.py"), "print('b')\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("pkg").join("b.py"));
+        app.open_file(root.join("pkg").join("a.py"));
+        app.current_mut().unwrap().dirty = true;
+        app.submit_prompt(PromptKind::ConfirmDelete(root.join("pkg")), String::new());
+
+        assert!(!root.join("pkg").exists());
+        assert_eq!(app.tabs.len(), 1);
+        let current = app.current().unwrap();
+        assert_eq!(current.path, root.join("pkg").join("a.py"));
+        assert!(current.dirty && current.missing_on_disk);
+    }
+
+    #[test]
+    fn remplace_toutes_occurrences() {
+        let lines = vec!["a = a + 1".to_string(), "b = 2".to_string()];
+        let (replaced, count) = replace_all_in_lines(&lines, "a", "x");
+        assert_eq!(count, 2);
+        assert_eq!(replaced, vec!["x = x + 1".to_string(), "b = 2".to_string()]);
+        assert_eq!(replace_all_in_lines(&lines, "", "x").1, 0);
+    }
+
+    #[test]
+    fn remplacer_tout_recompte_les_mots() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("notes.md"), "a_b_c_d\nz").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("notes.md"));
+        app.editor_mut().move_cursor(CursorMove::Jump(1, 0));
+        app.refresh_title();
+
+        assert_eq!(app.replace_all("_", " "), 3);
+        app.editor_mut().move_cursor(CursorMove::Jump(0, 7));
+        app.refresh_title();
+        app.handle_editor_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+
+        assert_eq!(app.doc_stats.as_ref().map(|stats| stats.words), Some(5));
+    }
+
+    #[test]
+    fn remplacer_suivant_marque_fichier_modifie() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "x = 1\ny = x.(1)\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+
+        assert!(app.replace_next("x.(1)", "z"));
+        assert_eq!(app.editor().lines(), ["x = 1", "y = z"]);
+        assert!(app.current().unwrap().dirty);
+
+        assert_eq!(app.replace_all("1", "2"), 1);
+        assert_eq!(app.editor().lines(), ["x = 2", "y = z"]);
+    }
+
+    #[test]
+    fn aller_a_la_ligne_deplace_curseur() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "a\nb\nc\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+
+        app.submit_prompt(PromptKind::GotoLine, "2".to_string());
+        assert_eq!(app.editor().cursor(), (1, 0));
+        app.submit_prompt(PromptKind::GotoLine, "42".to_string());
+        assert_eq!(app.editor().cursor(), (2, 0));
+        app.submit_prompt(PromptKind::GotoLine, "x".to_string());
+        assert_eq!(app.editor().cursor(), (2, 0));
+    }
+
+    #[test]
+    fn session_restaure_fichier_et_dossiers() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("main.py"), "print('ok')\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.tree.data.expand(&root.join("src"));
+        app.open_file(root.join("src").join("main.py"));
+        app.save_session();
+
+        let restored = App::new(root.clone()).unwrap();
+        assert_eq!(
+            restored.current().map(|current| current.path.clone()),
+            Some(root.join("src").join("main.py"))
+        );
+        assert!(restored.tree.data.is_expanded(&root.join("src")));
+
+        fs::remove_file(root.join("src").join("main.py")).unwrap();
+        let sans_fichier = App::new(root.clone()).unwrap();
+        assert!(sans_fichier.current().is_none());
+    }
+
+    #[test]
+    fn patch_codex_apercu_puis_application() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("a.py");
+        fs::write(&path, "a = 1\nb = 2\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+        app.last_assistant_message =
+            Some("```diff\n@@ -1,2 +1,2 @@\n a = 1\n-b = 2\n+b = 3\n```".to_string());
+
+        app.current_mut().unwrap().dirty = true;
+        app.run_action(Action::ApplyCodexPatch);
+        assert!(app.prompt.is_none());
+        app.current_mut().unwrap().dirty = false;
+
+        app.run_action(Action::ApplyCodexPatch);
+        assert!(matches!(
+            app.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(PromptKind::ApplyCodexPatch(_))
+        ));
+        assert!(app.log.iter().any(|line| line.text == "+b = 3"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a = 1\nb = 2\n");
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a = 1\nb = 3\n");
+        assert_eq!(app.editor().lines(), ["a = 1", "b = 3"]);
+        assert_eq!(
+            fs::read_dir(app.core.workspace().trash_dir())
+                .unwrap()
+                .count(),
+            1
+        );
+
+        // Le meme patch ne s'applique plus.
+        app.run_action(Action::ApplyCodexPatch);
+        assert!(app.prompt.is_none());
+    }
+
+    #[test]
+    fn fichier_modifie_sur_disque_recharge_si_propre() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("a.py");
+        fs::write(&path, "a = 1\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+
+        fs::write(&path, "a = 2\n").unwrap();
+        app.sync_tabs_with_disk(std::slice::from_ref(&path));
+        assert_eq!(app.editor().lines(), ["a = 2"]);
+
+        app.current_mut().unwrap().dirty = true;
+        fs::write(&path, "a = 3\n").unwrap();
+        app.sync_tabs_with_disk(std::slice::from_ref(&path));
+        assert_eq!(app.editor().lines(), ["a = 2"]);
+        let contenu = fs::read_to_string(app.core.workspace().bug_log_path()).unwrap();
+        assert!(contenu.contains("Fichier modifie sur disque"));
+    }
+
+    #[test]
+    fn onglets_gardent_contenu_et_demandent_avant_fermeture() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "a = 1\n").unwrap();
+        fs::write(root.join("b.py"), "b = 1\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.editor_mut().insert_str("x");
+        app.current_mut().unwrap().dirty = true;
+
+        app.open_file(root.join("b.py"));
+        assert!(app.prompt.is_none());
+        assert_eq!(app.tabs.len(), 2);
+        assert_eq!(app.editor().lines()[0], "b = 1");
+        assert!(app.editor_title().contains("a.py* | [b.py]"));
+
+        let ctrl_tab = KeyEvent::new(KeyCode::Tab, KeyModifiers::CONTROL);
+        app.handle_key(ctrl_tab);
+        assert_eq!(app.current().unwrap().path, root.join("a.py"));
+        assert_eq!(app.editor().lines()[0], "xa = 1");
+        app.open_file(root.join("b.py"));
+        assert_eq!(app.tabs.len(), 2);
+        app.handle_key(KeyEvent::new(
+            KeyCode::BackTab,
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        ));
+        assert_eq!(app.current().unwrap().path, root.join("a.py"));
+
+        let ctrl_w = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+        app.handle_key(ctrl_w);
+        assert!(matches!(
+            app.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(PromptKind::UnsavedChanges(PendingAction::CloseTab(_)))
+        ));
+        app.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+        assert_eq!(fs::read_to_string(root.join("a.py")).unwrap(), "xa = 1\n");
+        assert_eq!(app.tabs.len(), 1);
+        assert_eq!(app.current().unwrap().path, root.join("b.py"));
+
+        app.handle_key(ctrl_w);
+        assert!(app.current().is_none());
+        assert_eq!(app.title, APP_NAME);
+    }
+
+    #[test]
+    fn copie_de_secours_proposee_a_la_reouverture() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("a.py");
+        fs::write(&path, "a = 1\n").unwrap();
+        let old = std::time::SystemTime::now() - Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+        app.editor_mut().insert_str("x");
+        app.current_mut().unwrap().dirty = true;
+        app.autosave_tabs();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a = 1\n");
+        assert!(root.join("a.py.autosave").exists());
+        // Arret brutal : rien n'est sauve, la copie reste sur disque.
+        drop(app);
+
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+        assert!(matches!(
+            app.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(PromptKind::RecoverAutosave(_))
+        ));
+        app.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+        assert_eq!(app.editor().lines()[0], "xa = 1");
+        assert!(app.current().unwrap().dirty);
+
+        app.action_save();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "xa = 1\n");
+        assert!(!root.join("a.py.autosave").exists());
+    }
+
+    #[test]
+    fn fichier_supprime_sur_disque_recree_ou_ferme() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("pkg").join("a.py");
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::write(&path, "a = 1").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+
+        fs::remove_dir_all(root.join("pkg")).unwrap();
+        app.sync_tabs_with_disk(std::slice::from_ref(&path));
+        assert!(app.current().unwrap().dirty);
+        assert!(matches!(
+            app.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(PromptKind::DeletedOnDisk(_))
+        ));
+        app.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a = 1\n");
+        assert!(!app.current().unwrap().dirty);
+
+        fs::remove_file(&path).unwrap();
+        app.sync_tabs_with_disk(std::slice::from_ref(&path));
+        app.handle_key(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE));
+        assert!(app.current().is_none());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn fichier_supprime_detecte_sans_surveillance() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("pkg").join("a.py");
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::write(&path, "a = 1").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+        assert!(app.watcher.is_none());
+
+        // Dossier parent renomme : signale une seule fois.
+        fs::rename(root.join("pkg"), root.join("pkg2")).unwrap();
+        app.check_missing_files();
+        assert!(matches!(
+            app.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(PromptKind::DeletedOnDisk(_))
+        ));
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        app.check_missing_files();
+        assert!(app.prompt.is_none());
+
+        // Evenement de surveillance sur le dossier parent seulement.
+        fs::rename(root.join("pkg2"), root.join("pkg")).unwrap();
+        app.check_missing_files();
+        fs::remove_dir_all(root.join("pkg")).unwrap();
+        app.sync_tabs_with_disk(&[root.join("pkg")]);
+        assert!(app.prompt.is_some());
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+
+        // Sauvegarde apres suppression non encore vue : confirmation avant de recreer.
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        app.save_tab(0);
+        fs::remove_file(&path).unwrap();
+        app.tabs.active_mut().unwrap().file.dirty = true;
+        app.action_save();
+        assert!(!path.exists());
+        assert!(app.prompt.is_some());
+        app.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a = 1\n");
+    }
+
+    #[test]
+    fn faux_binaire_ouvert_quand_meme() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("notes.txt");
+        let bytes: Vec<u8> = "bonjour"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        fs::write(&path, bytes).unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+        let preview = app.current().unwrap().preview.unwrap();
+        assert_eq!(preview.kind, PreviewKind::Hex);
+        assert!(app.editor().lines()[0].starts_with("00000000  62 00 6f 00"));
+        assert!(matches!(
+            app.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(PromptKind::OpenAnyway(_))
+        ));
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+        assert_eq!(app.tabs.len(), 1);
+        assert!(!app.current().unwrap().read_only());
+        assert_eq!(app.current().unwrap().encoding, "utf-16le");
+        assert_eq!(app.editor().lines()[0], "bonjour");
+    }
+
+    #[test]
+    fn apercu_hexadecimal_en_lecture_seule() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("data.bin");
+        fs::write(&path, [0u8, 1, 2, 3]).unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+        app.prompt = None;
+        app.focus = Focus::Editor;
+        let before = app.editor().lines().to_vec();
+        app.handle_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        assert_eq!(app.editor().lines(), before.as_slice());
+        assert_eq!(app.editor().cursor(), (0, 1));
+        assert!(!app.current().unwrap().dirty);
+
+        app.action_toggle_line_ending();
+        assert!(!app.current().unwrap().dirty);
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("lecture seule"))
+        );
+        app.action_save();
+        assert_eq!(fs::read(&path).unwrap(), [0u8, 1, 2, 3]);
+    }
+
+    #[test]
+    fn rouvrir_et_enregistrer_avec_un_encodage() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("notes.txt");
+        fs::write(&path, "café").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+        assert_eq!(app.current().unwrap().encoding, "utf-8");
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::ALT));
+        assert_eq!(
+            app.prompt
+                .as_ref()
+                .map(|prompt| prompt.input.value.as_str()),
+            Some("utf-8")
+        );
+        app.prompt = None;
+        app.submit_prompt(PromptKind::ReopenEncoding, "windows-1252".to_string());
+        assert_eq!(app.editor().lines()[0], "cafÃ©");
+        app.submit_prompt(PromptKind::ReopenEncoding, "klingon".to_string());
+        assert_eq!(app.current().unwrap().encoding, "windows-1252");
+        app.submit_prompt(PromptKind::ReopenEncoding, "UTF-8".to_string());
+        assert_eq!(app.editor().lines()[0], "café");
+
+        app.submit_prompt(PromptKind::SaveEncoding, "latin1".to_string());
+        assert_eq!(fs::read(&path).unwrap(), b"caf\xE9");
+        assert_eq!(app.current().unwrap().encoding, "latin1");
+
+        app.editor_mut().insert_str("日本");
+        app.mark_dirty();
+        app.submit_prompt(PromptKind::SaveEncoding, "windows-1252".to_string());
+        assert!(matches!(
+            app.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(PromptKind::ConfirmLossySave(_))
+        ));
+        app.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+        assert_eq!(fs::read(&path).unwrap(), b"caf\xE9");
+        app.submit_prompt(PromptKind::SaveEncoding, "windows-1252".to_string());
+        app.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "日本café");
+        assert_eq!(app.current().unwrap().encoding, "utf-8");
+    }
+
+    #[test]
+    fn diagnostics_lint_listes_et_cliquables() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "import os\nx: int = 'a'\n").unwrap();
+        fs::write(root.join("b.py"), "y = 1\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("b.py"));
+        app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT));
+        assert!(app.prompt.is_none());
+
+        app.record_lint_line(Linter::Ruff, "a.py:1:8: F401 [*] `os` imported but unused");
+        app.record_lint_line(
+            Linter::Mypy,
+            "a.py:2:10: error: Incompatible types in assignment  [assignment]",
+        );
+        app.record_lint_line(Linter::Mypy, "Found 1 error in 1 file");
+        assert_eq!(app.diagnostics.for_path(&root.join("a.py")).len(), 2);
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT));
+        assert_eq!(
+            app.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(&PromptKind::Diagnostics)
+        );
+        app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
+        assert_eq!(app.current().unwrap().path, root.join("a.py"));
+        assert_eq!(app.editor().cursor(), (1, 9));
+        assert_eq!(app.focus, Focus::Editor);
+    }
+
+    #[test]
+    fn tab_indente_selon_les_parametres() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "x").unwrap();
+        fs::write(root.join("Makefile"), "").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.focus = Focus::Editor;
+        app.editor_mut().move_cursor(CursorMove::End);
+        app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(app.focus, Focus::Editor);
+        assert_eq!(app.editor().lines()[0], "x   ");
+        assert!(app.current().unwrap().dirty);
+
+        app.open_file(root.join("Makefile"));
+        app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(app.editor().lines()[0], "\t");
+        app.handle_key(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+        assert_eq!(app.focus, Focus::Tree);
+    }
+
+    #[test]
+    fn diff_du_buffer_avec_le_disque() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let path = root.join("a.py");
+        fs::write(&path, "a = 1\nb = 2\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(path.clone());
+        app.action_diff_with_disk();
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("Aucune difference"))
+        );
+
+        app.editor_mut().insert_str("# debut\n");
+        app.mark_dirty();
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('D'),
+            KeyModifiers::ALT | KeyModifiers::SHIFT,
+        ));
+        assert!(app.log.iter().any(|line| line.text == "+# debut"));
+        assert!(app.log.iter().any(|line| line.text.contains("+1 -0")));
+
+        fs::remove_file(&path).unwrap();
+        app.action_diff_with_disk();
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("n'existe plus sur disque"))
+        );
+        assert!(app.log.iter().any(|line| line.text.contains("+3 -0")));
+    }
+
+    #[test]
+    fn actions_git_hors_depot_refusees() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "a = 1\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::ALT));
+        assert!(app.prompt.is_none());
+        app.action_git_diff();
+        assert!(app.core.running.is_empty());
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("Pas de depot git"))
+        );
+        app.submit_prompt(PromptKind::GitCommit, String::new());
+        assert!(app.git_commit_message.is_none());
+    }
+
+    #[test]
+    fn quitter_avec_modifs_attend_reponse() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "a = 1\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.current_mut().unwrap().dirty = true;
+
+        let ctrl_q = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert!(!app.handle_key(ctrl_q));
+        assert!(!app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)));
+        assert!(!app.handle_key(ctrl_q));
+        assert!(app.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)));
+        assert_eq!(fs::read_to_string(root.join("a.py")).unwrap(), "a = 1\n");
+    }
+
+    #[test]
+    fn attente_du_precheck_codex_effacee_a_la_fin() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("exit 1")
+        } else {
+            vec!["sh".to_string(), "-c".to_string(), "exit 1".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "codex_status",
+            LogTarget::Codex,
+            ProcessKind::CodexStatus,
+        );
+        app.core
+            .set_pending_codex_prompt("explique main.py".to_string());
+        app.codex_status_started = Some(Instant::now());
+
+        let start = Instant::now();
+        while !app.core.running.is_empty() && start.elapsed() < Duration::from_secs(10) {
+            app.drain_process_events();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(app.codex_status_started.is_none());
+        assert!(app.codex_log.iter().any(|line| {
+            line.text
+                .contains("Echec de la verification du login Codex")
+        }));
+
+        app.codex_status_started = Some(Instant::now());
+        app.handle_key(KeyEvent::new(KeyCode::F(5), KeyModifiers::SHIFT));
+        assert!(app.codex_status_started.is_none());
+    }
+
+    #[test]
+    fn echec_installation_codex_libere_le_tour() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let env_map = app.codex_env();
+        if codex_cli_available(Some(&app.root_dir), Some(&env_map)) {
+            return;
+        }
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("exit 1")
+        } else {
+            vec!["sh".to_string(), "-c".to_string(), "exit 1".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            env_map,
+            "installation Codex",
+            LogTarget::Codex,
+            ProcessKind::CodexInstall,
+        );
+        app.core.codex_install_attempted = true;
+        app.core.set_pending_codex_prompt("premiere".to_string());
+        app.run_codex("seconde".to_string());
+
+        let start = Instant::now();
+        while !app.core.running.is_empty() && start.elapsed() < Duration::from_secs(10) {
+            app.drain_process_events();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!app.core.codex_turn_active());
+        assert_eq!(app.core.codex_queue_len(), 0);
+        assert!(
+            app.codex_log
+                .iter()
+                .any(|line| { line.text.contains("Echec de l'installation Codex") })
+        );
+
+        app.run_codex("nouvelle".to_string());
+        assert!(
+            !app.codex_log
+                .iter()
+                .any(|line| { line.text.contains("En attente") && line.text.contains("nouvelle") })
+        );
+        assert!(!app.core.codex_turn_active());
+    }
+
+    #[test]
+    fn selection_executee_puis_script_supprime() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "x = 1\nif x:\n    print(x)\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.editor_mut().move_cursor(CursorMove::Jump(2, 0));
+        app.editor_mut().start_selection();
+        app.editor_mut().move_cursor(CursorMove::End);
+        app.action_run_selection();
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("(1 lignes selectionnees)"))
+        );
+        let start = Instant::now();
+        while !app.core.running.is_empty() && start.elapsed() < Duration::from_secs(10) {
+            app.drain_process_events();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let leftovers = fs::read_dir(root.join("tmp"))
+            .unwrap()
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("selection_")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn precheck_codex_reussi_mis_en_cache() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("exit 0")
+        } else {
+            vec!["true".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "codex_status",
+            LogTarget::Codex,
+            ProcessKind::CodexStatus,
+        );
+        let start = Instant::now();
+        while !app.core.running.is_empty() && start.elapsed() < Duration::from_secs(10) {
+            app.drain_process_events();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(app.core.codex_status_cached());
+
+        app.handle_codex_line(r#"{"type":"error","message":"unexpected status 429"}"#);
+        assert!(app.core.codex_status_cached());
+        app.handle_codex_line(
+            r#"{"type":"turn.failed","error":{"message":"unexpected status 401 Unauthorized"}}"#,
+        );
+        assert!(!app.core.codex_status_cached());
+    }
+
+    #[test]
+    fn bloc_de_code_codex_style_a_part() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.last_codex_width = 40;
+        app.codex_log_message("Voici :\n```python\npass\npass\n```");
+        let lines: Vec<_> = app
+            .codex_log
+            .iter()
+            .filter(|line| line.kind == LogKind::Assistant)
+            .collect();
+        let texts: Vec<_> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, vec!["Voici :", "```python", "pass", "pass", "```"]);
+        assert!(lines[0].runs.is_empty());
+        let (padded, style) = &lines[2].runs[0];
+        assert_eq!(padded.len(), 36);
+        assert_eq!(style.bg, Some(Color::Black));
+        assert_ne!(lines[1].runs[0].1, *style);
+    }
+
+    #[test]
+    fn deltas_puis_message_final_affiches_une_fois() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.codex_view = CodexView::Normal;
+        for line in [
+            r#"{"type":"response.output_text.delta","delta":"Bonjour, "}"#,
+            r#"{"type":"response.output_text.delta","delta":"voici la reponse."}"#,
+            r#"{"type":"response.output_text.done","text":"Bonjour, voici la reponse."}"#,
+            r#"{"type":"item.completed","item":{"type":"agent_message","text":"Bonjour,  voici la reponse.\n"}}"#,
+            r#"{"type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"voici la reponse."}]}}"#,
+        ] {
+            app.handle_codex_line(line);
+        }
+        let assistant: Vec<_> = app
+            .codex_log
+            .iter()
+            .filter(|line| line.kind == LogKind::Assistant)
+            .map(|line| line.text.as_str())
+            .collect();
+        assert_eq!(assistant, vec!["Bonjour, voici la reponse."]);
+
+        // Un autre message du meme tour reste affiche.
+        app.handle_codex_line(
+            r#"{"type":"item.completed","item":{"type":"agent_message","text":"Autre chose."}}"#,
+        );
+        assert_eq!(
+            app.codex_log
+                .iter()
+                .filter(|line| line.kind == LogKind::Assistant)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn logout_codex_invalide_le_cache_du_precheck() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.core.record_codex_status(true);
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("exit 0")
+        } else {
+            vec!["true".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "deconnexion Codex",
+            LogTarget::Codex,
+            ProcessKind::CodexLogout,
+        );
+        let start = Instant::now();
+        while !app.core.running.is_empty() && start.elapsed() < Duration::from_secs(10) {
+            app.drain_process_events();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!app.core.codex_status_cached());
+        assert!(
+            app.codex_log
+                .iter()
+                .any(|line| line.text.contains("Deconnexion Codex effectuee"))
+        );
+    }
+
+    #[test]
+    fn relance_codex_apres_rate_limit() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.codex_last_prompt = Some("explique main.py".to_string());
+        app.handle_codex_line(
+            r#"{"type":"turn.failed","error":{"message":"unexpected status 429 Too Many Requests"}}"#,
+        );
+        app.handle_codex_line(r#"{"type":"error","message":"unexpected status 401"}"#);
+        assert!(matches!(
+            app.core.finish_codex_exec(false, true),
+            CodexRetry::Scheduled(msg) if msg.starts_with("HTTP 429")
+        ));
+        assert!(app.core.codex_turn_active());
+        app.poll_codex_retry();
+        assert!(app.core.codex_retry_pending());
+        app.handle_key(KeyEvent::new(KeyCode::F(5), KeyModifiers::SHIFT));
+        assert!(!app.core.codex_retry_pending());
+        assert!(!app.core.codex_turn_active());
+    }
+
+    #[test]
+    fn stop_codex_tue_exec_et_reinitialise() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("ping -n 30 127.0.0.1 > nul")
+        } else {
+            vec!["sleep".to_string(), "30".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "codex_exec",
+            LogTarget::Codex,
+            ProcessKind::CodexExec,
+        );
+        app.codex_assistant_buffer.push_str("reponse partielle");
+        app.codex_retry_without_sandbox = true;
+        app.core.set_pending_codex_prompt("suite".to_string());
+
+        app.handle_key(KeyEvent::new(KeyCode::F(5), KeyModifiers::SHIFT));
+
+        assert!(app.core.running.is_empty());
+        assert!(app.codex_assistant_buffer.is_empty());
+        assert!(!app.codex_retry_without_sandbox);
+        assert!(!app.core.codex_turn_active());
+        assert!(
+            app.codex_log
+                .iter()
+                .any(|line| line.text.contains("Codex interrompu"))
+        );
+    }
+
+    #[test]
+    fn historique_codex_rappel_et_contexte() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.codex_last_prompt = Some("explique main.py".to_string());
+        app.codex_log_message("Il affiche bonjour.");
+        app.record_codex_turn();
+        assert!(
+            fs::read_to_string(dir.path().join(".usbide").join("codex_history.jsonl"))
+                .unwrap()
+                .contains("explique main.py")
+        );
+
+        app.focus = Focus::Codex;
+        app.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        assert_eq!(app.codex_input.value, "explique main.py");
+        app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert!(app.codex_input.value.is_empty());
+
+        assert_eq!(app.codex_exec_prompt("et apres ?"), "et apres ?");
+        app.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        let prompt = app.codex_exec_prompt("et apres ?");
+        assert!(prompt.contains("Il affiche bonjour."));
+        assert!(prompt.ends_with("et apres ?"));
+    }
+
+    #[test]
+    fn export_logs_ecrit_dans_usbide_logs() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let mut app = App::new(root.clone()).unwrap();
+        app.codex_log_message("Voici la reponse.");
+        app.handle_key(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL));
+
+        let logs_dir = root.join(".usbide").join("logs");
+        let files: Vec<PathBuf> = fs::read_dir(&logs_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 2);
+        let text_file = files
+            .iter()
+            .find(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .unwrap();
+        let text = fs::read_to_string(text_file).unwrap();
+        assert!(text.contains("[assistant] Voici la reponse."));
+        assert!(!text.contains("] Assistant\n"));
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("Logs exportes"))
+        );
+    }
+
+    #[test]
+    fn historique_commandes_rappel_fleches() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let mut app = App::new(root.clone()).unwrap();
+        app.cmd_history
+            .push(app.core.workspace(), "echo un")
+            .unwrap();
+        app.cmd_history
+            .push(app.core.workspace(), "echo deux")
+            .unwrap();
+        app.focus = Focus::Cmd;
+
+        let up = KeyEvent::new(KeyCode::Up, KeyModifiers::NONE);
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+        app.handle_key(up);
+        assert_eq!(app.cmd_input.value, "echo deux");
+        app.handle_key(up);
+        assert_eq!(app.cmd_input.value, "echo un");
+        app.handle_key(down);
+        app.handle_key(down);
+        assert!(app.cmd_input.value.is_empty());
+
+        let reloaded = App::new(root).unwrap();
+        assert_eq!(reloaded.cmd_history.entries, vec!["echo un", "echo deux"]);
+    }
+
+    #[test]
+    fn shell_suit_arborescence_et_refuse_cd_hors_root() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::create_dir_all(root.join("src")).unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        assert_eq!(app.shell_cwd, root);
+
+        app.run_shell("cd src".to_string());
+        assert_eq!(app.shell_cwd, root.join("src"));
+        app.run_shell("cd ../..".to_string());
+        assert_eq!(app.shell_cwd, root.join("src"));
+        assert!(app.log.iter().any(|line| line.text.contains("cd refuse")));
+
+        app.focus = Focus::Tree;
+        app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(
+            app.shell_cwd,
+            target_dir_for(app.tree.selected_entry(), &root)
+        );
+    }
+
+    #[test]
+    fn garde_fou_shell_demande_confirmation() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(canonical_root(dir.path())).unwrap();
+        app.run_shell("rm -rf ~".to_string());
+        assert!(matches!(
+            &app.prompt,
+            Some(prompt) if prompt.kind == PromptKind::ConfirmShell("rm -rf ~".to_string())
+        ));
+        app.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
+        assert!(app.core.running.is_empty());
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("suppression recursive de ~"))
+        );
+    }
+
+    #[test]
+    fn coloration_python_et_defilement() {
+        let mut state = None;
+        let line = highlighted_line(Language::Python, "\tif x: # note", &mut state, 4);
+        let styled: Vec<(String, Style)> = line
+            .spans
+            .iter()
+            .map(|span| (span.content.to_string(), span.style))
+            .collect();
+        assert_eq!(styled[0], ("    ".to_string(), Style::default()));
+        assert_eq!(
+            styled[1],
+            ("if".to_string(), token_style(TokenKind::Keyword))
+        );
+        assert_eq!(
+            styled.last().unwrap(),
+            &("# note".to_string(), token_style(TokenKind::Comment))
+        );
+
+        assert_eq!(follow_cursor(0, 5, 10), 0);
+        assert_eq!(follow_cursor(0, 12, 10), 3);
+        assert_eq!(follow_cursor(8, 2, 10), 2);
+        assert_eq!(display_width("\tab", 2, 4), 5);
+        assert_eq!(display_width("\tab", 2, 2), 3);
+    }
+
+    #[test]
+    fn sauvegarde_normalise_les_espaces_python() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("a.py"), "x = 1   \ny = 2\n\n\n").unwrap();
+        fs::write(root.join("notes.md"), "ligne  \n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.editor_mut().move_cursor(CursorMove::Jump(1, 3));
+        app.mark_dirty();
+        app.action_save();
+        assert_eq!(
+            fs::read_to_string(root.join("a.py")).unwrap(),
+            "x = 1\ny = 2\n"
+        );
+        assert_eq!(app.editor().cursor(), (1, 3));
+        assert!(!app.current().unwrap().dirty);
+        assert!(app.log.iter().any(|line| line.text.contains("normalises")));
+
+        // Hors de la liste (Markdown : deux espaces = retour a la ligne) : intact.
+        app.open_file(root.join("notes.md"));
+        app.mark_dirty();
+        app.action_save();
+        assert_eq!(
+            fs::read_to_string(root.join("notes.md")).unwrap(),
+            "ligne  "
+        );
+    }
+
+    #[test]
+    fn sauvegarde_conserve_crlf_sauf_forcage() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("win.py"), "a = 1\r\nb = 2\r\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("win.py"));
+        assert_eq!(
+            app.current().unwrap().line_ending,
+            crate::fs::LineEnding::Crlf
+        );
+
+        app.mark_dirty();
+        app.action_save();
+        assert_eq!(
+            fs::read_to_string(root.join("win.py")).unwrap(),
+            "a = 1\r\nb = 2\r\n"
+        );
+
+        app.handle_key(KeyEvent::new(KeyCode::F(6), KeyModifiers::NONE));
+        app.action_save();
+        assert_eq!(
+            fs::read_to_string(root.join("win.py")).unwrap(),
+            "a = 1\nb = 2\n"
+        );
+    }
+
+    #[test]
+    fn repli_des_lignes_et_defilement() {
+        let line = Line::from(vec![
+            Span::raw("abc"),
+            Span::styled("defg", token_style(TokenKind::String)),
+        ]);
+        let rows: Vec<String> = wrap_styled_line(line, 3)
+            .iter()
+            .map(|row| row.spans.iter().map(|span| span.content.as_ref()).collect())
+            .collect();
+        assert_eq!(rows, vec!["abc", "def", "g"]);
+        assert_eq!(wrapped_rows("abcdefg", 3, 4), 3);
+        assert_eq!(
+            wrap_styled_line(Line::raw("abc"), 3).len(),
+            wrapped_rows("abc", 3, 4)
+        );
+
+        let lines: Vec<String> = vec!["x".repeat(10), "y".to_string(), "z".to_string()];
+        // Curseur ligne 2 : la ligne 0 (4 rangees) ne tient plus avec une hauteur de 4.
+        assert_eq!(wrapped_top(&lines, 0, 2, 0, 3, 4, 4), 1);
+        assert_eq!(wrapped_top(&lines, 0, 1, 0, 3, 10, 4), 0);
+        assert_eq!(wrapped_top(&lines, 2, 1, 0, 3, 10, 4), 1);
+    }
+
+    #[test]
+    fn retour_a_la_ligne_persiste_dans_la_session() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        let mut app = App::new(root.clone()).unwrap();
+        assert!(app.word_wrap);
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::ALT));
+        assert!(!app.word_wrap);
+
+        let app = App::new(root).unwrap();
+        assert!(!app.word_wrap);
+    }
+
+    #[test]
+    fn fichier_de_demarrage_ouvert_ou_signale() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join("main.py"), "print('ok')\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+
+        app.open_startup_file(&root.join("absent.py"));
+        assert!(app.current().is_none());
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("Fichier introuvable"))
+        );
+
+        app.open_startup_file(&root.join("main.py"));
+        assert_eq!(app.current().unwrap().path, root.join("main.py"));
+    }
+
+    #[test]
+    fn requirements_absent_signale() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(canonical_root(dir.path())).unwrap();
+        app.handle_key(KeyEvent::new(KeyCode::F(8), KeyModifiers::NONE));
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("Aucun requirements.txt"))
+        );
+        assert!(app.core.running.is_empty());
+    }
+
+    #[test]
+    fn bascule_build_onefile() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(canonical_root(dir.path())).unwrap();
+        assert!(!app.build_onefile);
+        app.handle_key(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::ALT));
+        assert!(app.build_onefile);
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("Build PyInstaller: onefile"))
+        );
+    }
+
+    #[test]
+    fn revele_le_fichier_ouvert_dans_l_arbre() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::create_dir_all(root.join("pkg").join("sub")).unwrap();
+        let file = root.join("pkg").join("sub").join("mod.py");
+        fs::write(&file, "x = 1\n").unwrap();
+        let mut app = App::new(root).unwrap();
+
+        // Sans fichier ouvert : rien a faire.
+        app.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT));
+
+        app.load_file(file.clone());
+        app.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT));
+        assert_eq!(
+            app.tree.selected_entry().map(|entry| &entry.path),
+            Some(&file)
+        );
+    }
+
+    #[test]
+    fn ouverture_rapide_ctrl_p() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::write(root.join("pkg").join("models.py"), "x = 1\n").unwrap();
+        fs::write(root.join("main.py"), "").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        for ch in "pkmod".chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        assert_eq!(app.quick_open_matches(), vec!["pkg/models.py"]);
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        assert!(app.prompt.is_none());
+        assert_eq!(
+            app.current().map(|current| &current.path),
+            Some(&root.join("pkg").join("models.py"))
+        );
+        assert_eq!(app.editor().lines()[0], "x = 1");
+    }
+
+    #[test]
+    fn dotfiles_masques_et_bascule_persistee() {
+        let dir = TempDir::new().unwrap();
+        let root = canonical_root(dir.path());
+        fs::write(root.join(".env"), "A=1\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        let has_env = |app: &App| {
+            app.tree
+                .data
+                .visible()
+                .iter()
+                .any(|entry| entry.name == ".env")
+        };
+        assert!(!has_env(&app));
+
+        app.load_file(root.join(".env"));
+        assert!(has_env(&app));
+        app.action_close_tab();
+        assert!(!has_env(&app));
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::ALT));
+        assert!(has_env(&app));
+        assert!(Session::load(app.core.workspace()).show_hidden);
+    }
+
+    #[test]
+    fn process_muet_tue_apres_timeout() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("ping -n 30 127.0.0.1 > nul")
+        } else {
+            vec!["sleep".to_string(), "30".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "build",
+            LogTarget::Main,
+            ProcessKind::PyInstallerBuild,
+        );
+        app.core.running[0].timeout = Some(Duration::ZERO);
+
+        app.drain_process_events();
+
+        assert!(app.core.running.is_empty());
+        let bug = fs::read_to_string(app.core.workspace().bug_log_path()).unwrap();
+        assert!(bug.contains("processus tue apres timeout"));
+    }
+
+    #[test]
+    fn saisie_envoyee_au_stdin_de_la_commande() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.focus = Focus::Cmd;
+        app.run_shell(if cfg!(windows) { "sort" } else { "cat" }.to_string());
+        assert!(app.core.interactive_shell().is_some());
+
+        for ch in "ligne stdin".chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+
+        let start = Instant::now();
+        while !app.core.running.is_empty() && start.elapsed() < Duration::from_secs(10) {
+            app.drain_process_events();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(app.core.running.is_empty());
+        let texts: Vec<&str> = app.log.iter().map(|line| line.text.as_str()).collect();
+        assert!(texts.contains(&"> ligne stdin"));
+        assert!(texts.contains(&"ligne stdin"));
+    }
+
+    #[test]
+    fn console_repl_garde_le_process_entre_les_saisies() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        // Un `cat` tient lieu d'interpreteur : il renvoie chaque ligne recue.
+        let argv = if cfg!(windows) { "sort" } else { "cat" };
+        app.spawn_process(
+            vec![argv.to_string()],
+            std::env::vars().collect(),
+            "REPL Python",
+            LogTarget::Main,
+            ProcessKind::Repl,
+        );
+        app.open_prompt(PromptKind::Repl, ">>>");
+        for line in ["x = 1", "x"] {
+            for ch in line.chars() {
+                app.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+            }
+            app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        }
+        assert_eq!(app.core.running.len(), 1);
+        if !cfg!(windows) {
+            let start = Instant::now();
+            while !app.repl_output.iter().any(|line| line == "x")
+                && start.elapsed() < Duration::from_secs(10)
+            {
+                app.drain_process_events();
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            assert_eq!(app.repl_output, [">>> x = 1", ">>> x", "x = 1", "x"]);
+        }
+        // La sortie du REPL ne pollue pas le journal.
+        assert!(!app.log.iter().any(|line| line.text == "x = 1"));
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL));
+        assert!(app.repl_output.is_empty());
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
+        assert!(app.core.repl().is_some());
+        assert!(app.core.stop_repl());
+        assert!(app.core.running.is_empty());
+    }
+
+    #[test]
+    fn sortie_ansi_coloree_dans_le_journal() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.push_ansi_log(
+            LogTarget::Main,
+            "\u{1b}[1;31mE501\u{1b}[0m ligne trop longue",
+        );
+        app.push_ansi_log(LogTarget::Main, "sans couleur");
+
+        let colored = &app.log[app.log.len() - 2];
+        assert_eq!(colored.text, "E501 ligne trop longue");
+        assert_eq!(
+            colored.runs[0],
+            (
+                "E501".to_string(),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            )
+        );
+        assert!(app.log.last().unwrap().runs.is_empty());
+    }
+
+    #[test]
+    fn filtre_du_journal_au_rendu() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.log.clear();
+        app.log_ui("info".to_string());
+        app.log_issue("attention", "avertissement", "test", LogTarget::Main);
+        app.log_issue("panne", "erreur", "test", LogTarget::Main);
+        let rendered = |app: &App| -> Vec<String> {
+            app.render_log(LogTarget::Main, 10, 0)
+                .lines
+                .iter()
+                .map(|line| line.to_string())
+                .collect()
+        };
+        assert_eq!(rendered(&app), vec!["info", "attention", "panne"]);
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::ALT));
+        assert_eq!(rendered(&app), vec!["attention", "panne"]);
+        app.handle_key(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::ALT));
+        assert_eq!(rendered(&app), vec!["panne"]);
+        assert_eq!(app.log.len(), 3);
+        assert_eq!(app.codex_log_filter, LogFilter::All);
+    }
+
+    #[test]
+    fn lignes_identiques_regroupees_dans_le_log() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.log.clear();
+        for _ in 0..3 {
+            app.push_ansi_log(LogTarget::Main, "WARNING: retrying");
+        }
+        app.push_ansi_log(LogTarget::Main, ".");
+        app.push_ansi_log(LogTarget::Main, ".");
+        app.log_ui("WARNING: retrying".to_string());
+        let texts: Vec<String> = app.log.iter().map(LogLine::display_text).collect();
+        assert_eq!(
+            texts,
+            ["WARNING: retrying (x3)", ".", ".", "WARNING: retrying"]
+        );
+        let rendered = app.render_log(LogTarget::Main, 10, 0);
+        assert_eq!(rendered.lines[0].to_string(), "WARNING: retrying (x3)");
+    }
+
+    #[test]
+    fn journal_defile_avec_le_focus_log() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.log.clear();
+        for i in 0..30 {
+            app.log_ui(format!("ligne {i}"));
+        }
+        app.focus = Focus::Cmd;
+        app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(app.focus, Focus::Log);
+        let rendered = |app: &App| -> Vec<String> {
+            app.render_log(LogTarget::Main, 2, app.log_scroll.0)
+                .lines
+                .iter()
+                .map(|line| line.to_string())
+                .collect()
+        };
+        for _ in 0..3 {
+            app.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        }
+        assert_eq!(rendered(&app), vec!["ligne 25", "ligne 26"]);
+        // Une nouvelle ligne ne deplace pas la vue.
+        app.log_ui("ligne 30".to_string());
+        assert_eq!(rendered(&app), vec!["ligne 25", "ligne 26"]);
+        app.handle_key(KeyEvent::new(KeyCode::Home, KeyModifiers::NONE));
+        assert_eq!(rendered(&app), vec!["ligne 0"]);
+
+        app.word_wrap = true;
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        assert_eq!(app.log_scroll.1, 0);
+        app.word_wrap = false;
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        assert!(app.log_scroll.1 > 0);
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(app.log_scroll, (0, 0));
+        assert_eq!(rendered(&app), vec!["ligne 29", "ligne 30"]);
+    }
+
+    #[test]
+    fn sortie_codex_defile_par_page() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.codex_log.clear();
+        for i in 0..30 {
+            app.codex_log_ui(format!("sortie {i}"));
+        }
+        app.focus = Focus::Codex;
+        app.handle_key(KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE));
+        assert_eq!(app.codex_log_scroll, 10);
+        assert!(app.codex_input.value.is_empty());
+        // Hors de la fin, la nouvelle sortie ne fait pas defiler la vue.
+        app.codex_log_ui("sortie 30".to_string());
+        assert_eq!(app.codex_log_scroll, 11);
+        let last = app
+            .render_log(LogTarget::Codex, 1, app.codex_log_scroll)
+            .lines[0]
+            .to_string();
+        assert_eq!(last, "sortie 19");
+        app.handle_key(KeyEvent::new(KeyCode::End, KeyModifiers::CONTROL));
+        assert_eq!(app.codex_log_scroll, 0);
+        assert!(scrollback_marker(3).contains("historique"));
+    }
+
+    #[test]
+    fn ctrl_l_vide_le_log_du_panneau_actif() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.log_ui("journal".to_string());
+        app.codex_log_ui("codex".to_string());
+        let ctrl_l = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL);
+
+        app.focus = Focus::Codex;
+        app.handle_key(ctrl_l);
+        assert!(app.log.iter().any(|line| line.text == "journal"));
+        let codex: Vec<&str> = app
+            .codex_log
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect();
+        assert_eq!(codex, vec!["Sortie Codex effacee."]);
+
+        app.focus = Focus::Cmd;
+        app.handle_key(ctrl_l);
+        let journal: Vec<&str> = app.log.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(journal, vec!["Journal efface."]);
+        assert_eq!(app.codex_log.len(), 1);
+
+        app.run_action(Action::ClearCodexLog);
+        assert_eq!(app.log.len(), 1);
+        assert_eq!(app.codex_log[0].text, "Sortie Codex effacee.");
+    }
+
+    #[test]
+    fn recherche_dans_le_journal_defile_jusqu_au_resultat() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.log.clear();
+        for i in 0..20 {
+            let text = if i == 4 || i == 12 {
+                format!("ERREUR {i}")
             } else {
-                self.push_log(LogTarget::Codex, line, line_style);
+                format!("ligne {i}")
+            };
+            app.log_ui(text);
+        }
+        app.focus = Focus::Cmd;
+        app.run_action(Action::FindInLog);
+        for c in "erreur".chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert_eq!(app.log_search.as_ref().unwrap().current, Some(12));
+        assert_eq!(app.log_scroll.0, 7);
+        assert!(app.prompt.as_ref().unwrap().label.ends_with("2/2"));
+        app.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        assert_eq!(app.log_scroll.0, 15);
+        let shown = app.render_log(LogTarget::Main, 1, app.log_scroll.0);
+        assert_eq!(shown.lines[0].to_string(), "ERREUR 4");
+
+        // Casse respectee : "erreur" ne trouve plus rien, le log reste entier.
+        app.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::ALT));
+        assert_eq!(app.log_search.as_ref().unwrap().current, None);
+        assert!(
+            app.prompt
+                .as_ref()
+                .unwrap()
+                .label
+                .ends_with("aucun resultat")
+        );
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(app.log_search.is_none());
+        assert_eq!(app.log.len(), 20);
+    }
+
+    #[test]
+    fn mode_zen_garde_focus_et_curseur() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        fs::write(root.join("a.py"), "x = 1\ny = 2\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.open_file(root.join("a.py"));
+        app.editor_mut().move_cursor(CursorMove::Jump(1, 3));
+        app.focus = Focus::Cmd;
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT));
+        assert_eq!(app.zen_mode, Some(Focus::Cmd));
+        assert_eq!(app.focus, Focus::Editor);
+        app.handle_key(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+        assert_eq!(app.focus, Focus::Editor);
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT));
+        assert_eq!(app.zen_mode, None);
+        assert_eq!(app.focus, Focus::Cmd);
+        assert_eq!(app.editor().cursor(), (1, 3));
+    }
+
+    #[test]
+    fn selecteur_python_choisit_l_interpreteur() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let probes = app.core.start_python_detection();
+        for (idx, argv) in &probes {
+            if argv[0] == "python3" {
+                app.core.record_python_version(*idx, "Python 3.12.1");
             }
+            app.finish_python_probe();
         }
-        self.push_log(LogTarget::Codex, String::new(), Style::default());
+        assert!(app.log.iter().any(|line| line.text.starts_with("Python: ")));
+
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('P'),
+            KeyModifiers::ALT | KeyModifiers::SHIFT,
+        ));
+        assert!(matches!(&app.prompt, Some(prompt) if prompt.kind == PromptKind::PythonPicker));
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
+        assert_eq!(app.python_label, "python3 (3.12.1)");
+        assert_eq!(app.core.base_python(), "python3");
+        assert!(app.core.run_venv().is_none());
     }
 
-    fn codex_log_user_message(&mut self, msg: &str) {
-        self.codex_log_entry(msg, "Utilisateur", "user");
+    #[test]
+    fn decoupage_des_panneaux_borne_et_persiste() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        let mut app = App::new(root.clone()).unwrap();
+        let alt_shift = KeyModifiers::ALT | KeyModifiers::SHIFT;
+        app.handle_key(KeyEvent::new(KeyCode::Right, alt_shift));
+        assert_eq!(app.tree_ratio, TREE_RATIO.0 + RATIO_STEP);
+        for _ in 0..20 {
+            app.handle_key(KeyEvent::new(KeyCode::Down, alt_shift));
+        }
+        assert_eq!(app.editor_ratio, EDITOR_RATIO.2);
+
+        let mut reopened = App::new(root.clone()).unwrap();
+        reopened.restore_session();
+        assert_eq!(reopened.tree_ratio, TREE_RATIO.0 + RATIO_STEP);
+        assert_eq!(reopened.editor_ratio, EDITOR_RATIO.2);
+        assert_eq!(session_ratio(Some(-3.0), TREE_RATIO), TREE_RATIO.1);
+        assert_eq!(session_ratio(None, EDITOR_RATIO), EDITOR_RATIO.0);
     }
 
-    fn codex_log_action(&mut self, msg: &str) {
-        self.codex_log_entry(msg, "Action", "action");
+    #[test]
+    fn inspecteur_environnement_ajoute_une_variable_temporaire() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.action_env_inspector();
+        for c in "USBIDE_ESSAI=42".chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            app.core.pending_env(),
+            [("USBIDE_ESSAI".to_string(), "42".to_string())]
+        );
+        app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(app.env_profile, EnvProfile::Codex);
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(200, 40)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Environnement codex"));
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
     }
 
-    fn codex_log_message(&mut self, msg: &str) {
-        self.codex_log_entry(msg, "Assistant", "assistant");
+    #[test]
+    fn assistant_premier_lancement_liste_et_se_ferme() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        let mut app = App::new(root.clone()).unwrap();
+        app.setup_missing = vec![SetupMissing::Node(root.join("tools").join("node"))];
+        app.open_prompt(PromptKind::Setup, "Premier lancement");
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Node portable introuvable"));
+        assert!(!screen.contains("i: installer Codex"));
+
+        // Rien d'installable : "i" ne fait rien, Echap continue.
+        app.handle_key(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE));
+        assert!(matches!(&app.prompt, Some(prompt) if prompt.kind == PromptKind::Setup));
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::Path;
-    use std::sync::Mutex;
-    use tempfile::TempDir;
+    #[test]
+    fn souris_focus_arbre_et_molette() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        fs::write(root.join("a.py"), "x = 1\n").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let click = |column: u16, row: u16| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
 
-    static ENV_LOCK: Mutex<()> = Mutex::new(());
+        let idx = app
+            .tree
+            .data
+            .visible()
+            .iter()
+            .position(|entry| entry.name == "a.py")
+            .unwrap() as u16;
+        let tree = app.areas.tree;
+        app.handle_mouse(click(tree.x + 2, tree.y + 1 + idx));
+        assert_eq!(app.current().unwrap().path, root.join("a.py"));
 
-    fn with_env_lock<F: FnOnce()>(f: F) {
-        let _guard = ENV_LOCK.lock().unwrap();
-        f();
-    }
+        let log = app.areas.log;
+        app.handle_mouse(click(log.x + 2, log.y + 1));
+        assert_eq!(app.focus, Focus::Log);
 
-    fn canonical_root(path: &Path) -> PathBuf {
-        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+        for i in 0..10 {
+            app.log_ui(format!("ligne {i}"));
+        }
+        app.handle_mouse(MouseEvent {
+            kind: MouseEventKind::ScrollUp,
+            column: log.x + 2,
+            row: log.y + 1,
+            modifiers: KeyModifiers::NONE,
+        });
+        assert_eq!(app.log_scroll.0, 3);
+        assert_eq!(app.codex_log_scroll, 0);
     }
 
-    fn set_env(key: &str, value: &str) {
-        unsafe {
-            std::env::set_var(key, value);
-        }
+    #[test]
+    fn rechargement_garde_la_selection_par_chemin() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        fs::write(root.join("b.py"), "").unwrap();
+        let mut app = App::new(root.clone()).unwrap();
+        app.tree.select_path(&root.join("b.py"));
+        fs::write(root.join("a.py"), "").unwrap();
+        app.reload_tree();
+        assert_eq!(app.tree.selected_entry().unwrap().path, root.join("b.py"));
     }
 
-    fn remove_env(key: &str) {
-        unsafe {
-            std::env::remove_var(key);
-        }
+    #[test]
+    fn copie_reponse_et_sortie_codex_visible() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.codex_log.clear();
+        app.codex_log_message("  premiere  ");
+        app.codex_log_message("```python\nprint('ok')\n```");
+        assert_eq!(
+            app.last_assistant_message.as_deref(),
+            Some("```python\nprint('ok')\n```")
+        );
+
+        app.log_issue("echec codex", "erreur", "test", LogTarget::Codex);
+        assert!(app.visible_codex_log_text().contains("premiere"));
+        app.codex_log_filter = LogFilter::ErrorsOnly;
+        assert_eq!(app.visible_codex_log_text(), "echec codex");
     }
 
     #[test]
-    fn refresh_title_sans_fichier() {
+    fn keymap_du_workspace_remplace_les_defauts() {
         let dir = TempDir::new().unwrap();
-        let app = App::new(dir.path().to_path_buf()).unwrap();
-        let expected_root = canonical_root(dir.path());
-        assert_eq!(app.title, APP_NAME);
-        assert_eq!(app.sub_title, expected_root.display().to_string());
+        fs::create_dir_all(dir.path().join(".usbide")).unwrap();
+        fs::write(
+            dir.path().join(".usbide").join("keymap.toml"),
+            "toggle_word_wrap = \"Ctrl+W\"\nrun = \"Ctrl+Hyper\"\n",
+        )
+        .unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let wrap = app.word_wrap;
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::ALT));
+        assert_eq!(app.word_wrap, wrap);
+        app.handle_key(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL));
+        assert_eq!(app.word_wrap, !wrap);
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("keymap.toml ignore") && line.text.contains("Hyper"))
+        );
+        let f5 = KeyChord::parse("F5").unwrap();
+        assert_eq!(app.keymap.action_for(&f5), Some(Action::Run));
     }
 
     #[test]
-    fn refresh_title_avec_fichier_dirty() {
+    fn palette_de_commandes_lance_l_action() {
         let dir = TempDir::new().unwrap();
         let mut app = App::new(dir.path().to_path_buf()).unwrap();
-        app.current = Some(OpenFile {
-            path: dir.path().join("main.py"),
-            encoding: "utf-8".to_string(),
-            dirty: true,
-        });
-        app.refresh_title();
-        assert_eq!(app.title, format!("{APP_NAME} *"));
-        assert!(app.sub_title.contains("main.py"));
-        assert!(app.sub_title.contains("utf-8"));
+        let wrap = app.word_wrap;
+        app.handle_key(KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE));
+        assert!(matches!(&app.prompt, Some(prompt) if prompt.kind == PromptKind::CommandPalette));
+        assert_eq!(app.palette_matches().len(), Action::ALL.len() - 1);
+
+        for ch in "retour".chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        assert_eq!(app.palette_matches()[0], Action::ToggleWordWrap);
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
+        assert_eq!(app.word_wrap, !wrap);
     }
 
     #[test]
-    fn portable_env_defauts() {
+    fn question_codex_joint_le_fichier_courant() {
         let dir = TempDir::new().unwrap();
-        let app = App::new(dir.path().to_path_buf()).unwrap();
-        let env = app.portable_env(HashMap::new());
-        let expected_root = canonical_root(dir.path());
-        assert_eq!(
-            env.get("PIP_CACHE_DIR").unwrap(),
-            &expected_root
-                .join("cache")
-                .join("pip")
-                .display()
-                .to_string()
-        );
-        assert_eq!(
-            env.get("PYTHONPYCACHEPREFIX").unwrap(),
-            &expected_root
-                .join("cache")
-                .join("pycache")
-                .display()
-                .to_string()
+        fs::write(dir.path().join("a.py"), "print('a')\n").unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.open_file(dir.path().join("a.py"));
+
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('Q'),
+            KeyModifiers::ALT | KeyModifiers::SHIFT,
+        ));
+        assert!(app.prompt.is_none());
+        assert!(
+            app.codex_log
+                .iter()
+                .any(|line| line.text.contains("Aucune selection"))
         );
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT));
+        let Some(prompt) = &app.prompt else {
+            panic!("prompt attendu");
+        };
         assert_eq!(
-            env.get("TEMP").unwrap(),
-            &expected_root.join("tmp").display().to_string()
+            prompt.kind,
+            PromptKind::CodexQuestion("fichier a.py".to_string(), "print('a')".to_string())
         );
+    }
+
+    #[test]
+    fn sandbox_danger_demande_confirmation() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.codex_sandbox_mode = CodexSandboxMode::DangerFullAccess;
+        app.focus = Focus::Codex;
+        for ch in "efface tout".chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let Some(prompt) = &app.prompt else {
+            panic!("confirmation attendue");
+        };
         assert_eq!(
-            env.get("TMP").unwrap(),
-            &expected_root.join("tmp").display().to_string()
+            prompt.kind,
+            PromptKind::ConfirmDangerSandbox("efface tout".to_string())
         );
-        assert_eq!(env.get("PYTHONNOUSERSITE").unwrap(), "1");
-        assert_eq!(
-            env.get("CODEX_HOME").unwrap(),
-            &expected_root.join("codex_home").display().to_string()
+        assert!(prompt.label.contains("danger-full-access"));
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
+        assert!(app.core.running.is_empty());
+        assert!(app.codex_status_started.is_none());
+    }
+
+    #[test]
+    fn rapport_environnement_s_ouvre_et_se_ferme() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('E'),
+            KeyModifiers::ALT | KeyModifiers::SHIFT,
+        ));
+        assert!(
+            matches!(&app.prompt, Some(prompt) if prompt.kind == PromptKind::EnvironmentReport)
         );
-        assert_eq!(
-            env.get("NPM_CONFIG_CACHE").unwrap(),
-            &expected_root
-                .join("cache")
-                .join("npm")
-                .display()
-                .to_string()
+        assert!(
+            app.environment_report
+                .iter()
+                .any(|line| line.starts_with("racine: "))
         );
-        assert_eq!(env.get("NPM_CONFIG_UPDATE_NOTIFIER").unwrap(), "false");
+
+        app.environment_report.clear();
+        app.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE));
+        assert!(!app.environment_report.is_empty());
+        app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
     }
 
     #[test]
-    fn ensure_portable_dirs_cree_usbide() {
+    fn outils_dev_a_cocher_avant_installation() {
         let dir = TempDir::new().unwrap();
-        let root = canonical_root(dir.path());
-        let _app = App::new(dir.path().to_path_buf()).unwrap();
-        assert!(root.join(".usbide").is_dir());
-        assert!(root.join(".usbide").join("codex").is_dir());
-        assert!(root.join(".usbide").join("tools").is_dir());
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+        assert!(matches!(&app.prompt, Some(prompt) if prompt.kind == PromptKind::DevTools));
+        assert!(!app.dev_tools.is_empty());
+
+        for index in 0..app.dev_tools.len() {
+            app.dev_tools[index].selected = true;
+            app.handle_key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+            app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        }
+        assert!(app.dev_tools.iter().all(|entry| !entry.selected));
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(app.prompt.is_none());
+        assert!(app.core.running.is_empty());
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("Aucun outil selectionne."))
+        );
     }
 
     #[test]
-    fn sanitize_codex_env_supprime() {
+    fn progression_installation_dans_le_titre() {
         let dir = TempDir::new().unwrap();
-        let app = App::new(dir.path().to_path_buf()).unwrap();
-        with_env_lock(|| {
-            let mut env = HashMap::from([
-                ("OPENAI_API_KEY".to_string(), "sk-test".to_string()),
-                ("CODEX_API_KEY".to_string(), "sk-codex".to_string()),
-                (
-                    "OPENAI_BASE_URL".to_string(),
-                    "https://example.com".to_string(),
-                ),
-            ]);
-            remove_env("USBIDE_CODEX_ALLOW_API_KEY");
-            remove_env("USBIDE_CODEX_ALLOW_CUSTOM_BASE");
-            app.core.sanitize_codex_env(&mut env);
-            assert!(!env.contains_key("OPENAI_API_KEY"));
-            assert!(!env.contains_key("CODEX_API_KEY"));
-            assert!(!env.contains_key("OPENAI_BASE_URL"));
-        });
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("echo Collecting ruff & ping -n 2 127.0.0.1 > nul")
+        } else {
+            vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "echo Collecting ruff; sleep 1".to_string(),
+            ]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "installation outils dev",
+            LogTarget::Main,
+            ProcessKind::DevTools,
+        );
+        let start = Instant::now();
+        while app.install_progress.is_none() && start.elapsed() < Duration::from_secs(10) {
+            app.drain_process_events();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(
+            app.install_progress_label(LogTarget::Main),
+            Some("pip: collecte ruff")
+        );
+        assert_eq!(app.install_progress_label(LogTarget::Codex), None);
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("Collecting ruff"))
+        );
+
+        while !app.core.running.is_empty() && start.elapsed() < Duration::from_secs(10) {
+            app.drain_process_events();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(app.install_progress.is_none());
     }
 
     #[test]
-    fn sanitize_codex_env_respecte_overrides() {
+    fn arret_d_urgence_tue_tous_les_process() {
         let dir = TempDir::new().unwrap();
-        let app = App::new(dir.path().to_path_buf()).unwrap();
-        with_env_lock(|| {
-            let mut env = HashMap::from([
-                ("OPENAI_API_KEY".to_string(), "sk-test".to_string()),
-                ("CODEX_API_KEY".to_string(), "sk-codex".to_string()),
-                (
-                    "OPENAI_BASE_URL".to_string(),
-                    "https://example.com".to_string(),
-                ),
-            ]);
-            set_env("USBIDE_CODEX_ALLOW_API_KEY", "1");
-            set_env("USBIDE_CODEX_ALLOW_CUSTOM_BASE", "true");
-            app.core.sanitize_codex_env(&mut env);
-            assert_eq!(env.get("OPENAI_API_KEY").unwrap(), "sk-test");
-            assert_eq!(env.get("CODEX_API_KEY").unwrap(), "sk-codex");
-            assert_eq!(env.get("OPENAI_BASE_URL").unwrap(), "https://example.com");
-            remove_env("USBIDE_CODEX_ALLOW_API_KEY");
-            remove_env("USBIDE_CODEX_ALLOW_CUSTOM_BASE");
-        });
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("ping -n 30 127.0.0.1 > nul")
+        } else {
+            vec!["sleep".to_string(), "30".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "installation outils dev",
+            LogTarget::Main,
+            ProcessKind::DevTools,
+        );
+        app.install_progress = Some((LogTarget::Main, "pip: collecte ruff".to_string()));
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('X'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        ));
+        assert!(app.core.running.is_empty());
+        assert!(app.install_progress.is_none());
+        assert!(
+            app.log
+                .iter()
+                .any(|line| line.text.contains("1 process arrete(s)"))
+        );
     }
 
     #[test]
-    fn codex_flags_env() {
+    fn prompt_codex_en_attente_pendant_un_tour() {
         let dir = TempDir::new().unwrap();
-        let app = App::new(dir.path().to_path_buf()).unwrap();
-        with_env_lock(|| {
-            set_env("USBIDE_CODEX_DEVICE_AUTH", "1");
-            assert!(app.codex_device_auth_enabled());
-            set_env("USBIDE_CODEX_DEVICE_AUTH", "0");
-            assert!(!app.codex_device_auth_enabled());
-            remove_env("USBIDE_CODEX_DEVICE_AUTH");
-            set_env("USBIDE_CODEX_AUTO_INSTALL", "0");
-            assert!(!app.codex_auto_install_enabled());
-            set_env("USBIDE_CODEX_AUTO_INSTALL", "1");
-            assert!(app.codex_auto_install_enabled());
-            remove_env("USBIDE_CODEX_AUTO_INSTALL");
-            set_env("USBIDE_CODEX_SANDBOX", "workspace-write");
-            set_env("USBIDE_CODEX_APPROVAL", "never");
-            let app2 = App::new(dir.path().to_path_buf()).unwrap();
-            assert_eq!(app2.codex_sandbox_mode, CodexSandboxMode::WorkspaceWrite);
-            assert_eq!(app2.codex_approval_policy, CodexApprovalPolicy::Never);
-            remove_env("USBIDE_CODEX_SANDBOX");
-            remove_env("USBIDE_CODEX_APPROVAL");
-        });
+        let mut app = App::new(dir.path().to_path_buf()).unwrap();
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("ping -n 30 127.0.0.1 > nul")
+        } else {
+            vec!["sleep".to_string(), "30".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "codex exec",
+            LogTarget::Codex,
+            ProcessKind::CodexExec,
+        );
+        app.run_codex("deuxieme question".to_string());
+        app.run_codex("troisieme question".to_string());
+        assert_eq!(app.core.codex_queue_len(), 2);
+        assert!(app.core.take_pending_codex_prompt().is_none());
+
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('X'),
+            KeyModifiers::ALT | KeyModifiers::SHIFT,
+        ));
+        assert_eq!(app.core.codex_queue_len(), 0);
+        assert!(
+            app.codex_log
+                .iter()
+                .any(|line| line.text.contains("File d'attente Codex videe (2 prompts)"))
+        );
+        app.core.kill_all_processes();
     }
 
     #[test]
-    fn record_issue_cree_bug_md() {
+    fn timeout_codex_sans_sortie_pointe_le_reseau() {
         let dir = TempDir::new().unwrap();
         let mut app = App::new(dir.path().to_path_buf()).unwrap();
-        app.core
-            .record_issue("erreur", "Erreur test", "test_unitaire", None);
-        let contenu = fs::read_to_string(dir.path().join("bug.md")).unwrap();
-        assert!(contenu.contains("niveau: erreur"));
-        assert!(contenu.contains("contexte: test_unitaire"));
-        assert!(contenu.contains("message: Erreur test"));
+        let argv = if cfg!(windows) {
+            windows_cmd_argv("ping -n 30 127.0.0.1 > nul")
+        } else {
+            vec!["sleep".to_string(), "30".to_string()]
+        };
+        app.spawn_process(
+            argv,
+            std::env::vars().collect(),
+            "codex exec",
+            LogTarget::Codex,
+            ProcessKind::CodexExec,
+        );
+        app.core.running[0].timeout = Some(Duration::ZERO);
+
+        app.drain_process_events();
+
+        assert!(app.core.running.is_empty());
+        assert!(
+            app.codex_log
+                .iter()
+                .any(|line| line.text.contains("aucune reponse de Codex"))
+        );
     }
 }
@@ -0,0 +1,99 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::fs::is_autosave_path;
+use crate::workspace::WorkspacePaths;
+
+/// Delai sans nouvel evenement avant de signaler un lot de changements.
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Surveille le root (recursif) et regroupe les evenements par lots (debounce).
+pub struct WorkspaceWatcher {
+    _watcher: RecommendedWatcher,
+    rx: Receiver<notify::Result<notify::Event>>,
+    workspace: WorkspacePaths,
+    changed: BTreeSet<PathBuf>,
+    deadline: Option<Instant>,
+}
+
+impl WorkspaceWatcher {
+    pub fn new(workspace: &WorkspacePaths) -> notify::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(workspace.root_dir(), RecursiveMode::Recursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            workspace: workspace.clone(),
+            changed: BTreeSet::new(),
+            deadline: None,
+        })
+    }
+
+    /// A appeler a chaque tick : renvoie les chemins modifies une fois le lot stabilise.
+    pub fn poll(&mut self) -> Option<Vec<PathBuf>> {
+        while let Ok(event) = self.rx.try_recv() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            for path in event.paths {
+                // .usbide/cache/tmp (session, pip...) et copies de secours bougent en
+                // permanence : on les ignore.
+                if self.workspace.is_internal_path(&path) || is_autosave_path(&path) {
+                    continue;
+                }
+                self.changed.insert(path);
+                self.deadline = Some(Instant::now() + WATCH_DEBOUNCE);
+            }
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.deadline = None;
+                Some(std::mem::take(&mut self.changed).into_iter().collect())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::thread;
+    use tempfile::TempDir;
+
+    fn wait_changes(watcher: &mut WorkspaceWatcher) -> Vec<PathBuf> {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if let Some(changed) = watcher.poll() {
+                return changed;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        Vec::new()
+    }
+
+    #[test]
+    fn signale_fichier_cree_et_ignore_internes() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join(".usbide")).unwrap();
+        let workspace = WorkspacePaths::new(root.clone());
+        let mut watcher = WorkspaceWatcher::new(&workspace).unwrap();
+
+        fs::write(root.join(".usbide").join("session.json"), "{}").unwrap();
+        fs::write(root.join("main.py"), "print('ok')").unwrap();
+
+        let changed = wait_changes(&mut watcher);
+        assert!(changed.contains(&root.join("main.py")));
+        assert!(!changed.iter().any(|path| workspace.is_internal_path(path)));
+    }
+}
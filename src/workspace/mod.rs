@@ -4,7 +4,14 @@ use std::path::{Path, PathBuf};
 
 use thiserror::Error;
 
-use crate::fs::{detect_text_encoding, is_probably_binary, read_text_with_encoding};
+use crate::fs::{
+    HEX_PREVIEW_BYTES, LineEnding, apply_line_ending, decode_text, detect_line_ending,
+    detect_text_encoding, detect_text_encoding_bytes, has_bom, hex_dump, is_probably_binary,
+    read_head, read_text_with_encoding,
+};
+use crate::git::{GitStatus, GitStatusMap};
+use crate::ignore::IgnoreRules;
+use crate::process::venv_python;
 
 const INTERNAL_ROOT_DIRS: [&str; 6] = [".git", ".usbide", "cache", "codex_home", "target", "tmp"];
 
@@ -20,6 +27,14 @@ pub struct WorkspacePaths {
     usbide_dir: PathBuf,
     usbide_codex: PathBuf,
     usbide_tools: PathBuf,
+    usbide_trash: PathBuf,
+    session_path: PathBuf,
+    codex_history_path: PathBuf,
+    usbide_logs: PathBuf,
+    cmd_history_path: PathBuf,
+    keymap_path: PathBuf,
+    settings_path: PathBuf,
+    usbide_venv: PathBuf,
     tools_node: PathBuf,
     tools_wheels: PathBuf,
     dist_dir: PathBuf,
@@ -33,10 +48,18 @@ impl WorkspacePaths {
             cache_npm: root_dir.join("cache").join("npm"),
             tmp_dir: root_dir.join("tmp"),
             codex_home: root_dir.join("codex_home"),
-            bug_log_path: root_dir.join("bug.md"),
+            bug_log_path: root_dir.join(".usbide").join("bug.md"),
             usbide_dir: root_dir.join(".usbide"),
             usbide_codex: root_dir.join(".usbide").join("codex"),
             usbide_tools: root_dir.join(".usbide").join("tools"),
+            usbide_trash: root_dir.join(".usbide").join("trash"),
+            session_path: root_dir.join(".usbide").join("session.json"),
+            codex_history_path: root_dir.join(".usbide").join("codex_history.jsonl"),
+            usbide_logs: root_dir.join(".usbide").join("logs"),
+            cmd_history_path: root_dir.join(".usbide").join("cmd_history"),
+            keymap_path: root_dir.join(".usbide").join("keymap.toml"),
+            settings_path: root_dir.join(".usbide").join("settings.toml"),
+            usbide_venv: root_dir.join(".usbide").join("venv"),
             tools_node: root_dir.join("tools").join("node"),
             tools_wheels: root_dir.join("tools").join("wheels"),
             dist_dir: root_dir.join("dist"),
@@ -60,6 +83,66 @@ impl WorkspacePaths {
         &self.dist_dir
     }
 
+    pub fn trash_dir(&self) -> &Path {
+        &self.usbide_trash
+    }
+
+    pub fn session_path(&self) -> &Path {
+        &self.session_path
+    }
+
+    pub fn codex_history_path(&self) -> &Path {
+        &self.codex_history_path
+    }
+
+    pub fn logs_dir(&self) -> &Path {
+        &self.usbide_logs
+    }
+
+    pub fn cmd_history_path(&self) -> &Path {
+        &self.cmd_history_path
+    }
+
+    pub fn keymap_path(&self) -> &Path {
+        &self.keymap_path
+    }
+
+    pub fn tmp_dir(&self) -> &Path {
+        &self.tmp_dir
+    }
+
+    pub fn settings_path(&self) -> &Path {
+        &self.settings_path
+    }
+
+    pub fn venv_dir(&self) -> &Path {
+        &self.usbide_venv
+    }
+
+    /// Venv utilise pour Python : USBIDE_VENV s'il est valide, sinon `.usbide/venv` une fois cree.
+    pub fn active_venv(&self) -> Option<PathBuf> {
+        select_venv(
+            std::env::var("USBIDE_VENV").ok().as_deref(),
+            &self.usbide_venv,
+        )
+    }
+
+    /// Deplace les caches pip/npm/pycache sous `cache_root` (par defaut `root/cache`).
+    pub fn set_cache_root(&mut self, cache_root: &Path) {
+        self.cache_pip = cache_root.join("pip");
+        self.cache_pycache = cache_root.join("pycache");
+        self.cache_npm = cache_root.join("npm");
+    }
+
+    /// Dossiers de cache nettoyables, avec leur nom court.
+    pub fn cache_dirs(&self) -> [(&'static str, &Path); 3] {
+        [
+            ("pip", &self.cache_pip),
+            ("npm", &self.cache_npm),
+            ("pycache", &self.cache_pycache),
+        ]
+    }
+
     pub fn ensure_portable_dirs(&self) {
         for path in [
             &self.cache_pip,
@@ -102,6 +185,12 @@ impl WorkspacePaths {
             "NPM_CONFIG_UPDATE_NOTIFIER".to_string(),
             "false".to_string(),
         );
+        if let Some(venv) = self.active_venv() {
+            env_map.insert("VIRTUAL_ENV".to_string(), venv.display().to_string());
+            if let Some(bin_dir) = venv_python(&venv).parent() {
+                crate::codex::prepend_path(&mut env_map, bin_dir);
+            }
+        }
         env_map
     }
 
@@ -158,6 +247,8 @@ pub struct TreeEntry {
     pub name: String,
     pub depth: usize,
     pub is_dir: bool,
+    /// Statut git (dossier : le plus important de son contenu), None hors depot.
+    pub git: Option<GitStatus>,
 }
 
 #[derive(Debug, Clone)]
@@ -165,7 +256,8 @@ struct FileNode {
     path: PathBuf,
     name: String,
     is_dir: bool,
-    children: Vec<FileNode>,
+    /// None : dossier pas encore lu (il l'est au premier depliage).
+    children: Option<Vec<FileNode>>,
 }
 
 #[derive(Debug, Clone)]
@@ -173,30 +265,84 @@ pub struct FileTreeData {
     root: FileNode,
     expanded: HashSet<PathBuf>,
     visible: Vec<TreeEntry>,
+    /// Affiche aussi ce que masquent .gitignore/.usbideignore (jamais les dossiers internes).
+    show_ignored: bool,
+    /// Affiche les entrees dont le nom commence par '.' (la racine reste toujours visible).
+    show_hidden: bool,
+    /// Fichier ouvert : reste atteignable (lui et ses parents) meme s'il est cache.
+    keep_visible: Option<PathBuf>,
+    git_status: GitStatusMap,
+    /// Lecture des dossiers a la demande (seuls les dossiers deplies sont lus).
+    workspace: WorkspacePaths,
+    /// Regles d'ignore ; None quand les fichiers ignores sont affiches.
+    rules: Option<IgnoreRules>,
 }
 
 impl FileTreeData {
     pub fn new(workspace: &WorkspacePaths) -> Self {
-        let root = build_tree(workspace.root_dir(), workspace);
+        let root = tree_node(workspace.root_dir().to_path_buf());
         let mut expanded = HashSet::new();
         expanded.insert(root.path.clone());
         let mut tree = Self {
             root,
             expanded,
             visible: Vec::new(),
+            show_ignored: false,
+            show_hidden: false,
+            keep_visible: None,
+            git_status: GitStatusMap::default(),
+            workspace: workspace.clone(),
+            rules: Some(IgnoreRules::load(workspace.root_dir())),
         };
         tree.rebuild_visible();
         tree
     }
 
+    /// Relit le disque en gardant les dossiers deplies qui existent encore.
     pub fn reload(&mut self, workspace: &WorkspacePaths) {
-        let root = build_tree(workspace.root_dir(), workspace);
-        self.root = root;
-        self.expanded.retain(|path| path.exists());
+        self.workspace = workspace.clone();
+        self.rules = (!self.show_ignored).then(|| IgnoreRules::load(workspace.root_dir()));
+        self.root = tree_node(workspace.root_dir().to_path_buf());
+        self.expanded.retain(|path| path.is_dir());
         self.expanded.insert(self.root.path.clone());
         self.rebuild_visible();
     }
 
+    pub fn show_ignored(&self) -> bool {
+        self.show_ignored
+    }
+
+    /// Affiche ou masque les fichiers ignores (relit l'arborescence).
+    pub fn set_show_ignored(&mut self, show: bool, workspace: &WorkspacePaths) {
+        self.show_ignored = show;
+        self.reload(workspace);
+    }
+
+    pub fn show_hidden(&self) -> bool {
+        self.show_hidden
+    }
+
+    pub fn set_show_hidden(&mut self, show: bool) {
+        self.show_hidden = show;
+        self.rebuild_visible();
+    }
+
+    /// Garde `path` (le fichier ouvert) visible meme dans un dossier cache.
+    pub fn set_keep_visible(&mut self, path: Option<PathBuf>) {
+        if self.keep_visible != path {
+            self.keep_visible = path;
+            self.rebuild_visible();
+        }
+    }
+
+    /// Statuts du dernier `git status` : decorent les entrees sans relire le disque.
+    pub fn set_git_status(&mut self, status: GitStatusMap) {
+        if self.git_status != status {
+            self.git_status = status;
+            self.rebuild_visible();
+        }
+    }
+
     pub fn toggle_dir(&mut self, path: &Path) {
         if self.expanded.contains(path) {
             self.expanded.remove(path);
@@ -214,9 +360,101 @@ impl FileTreeData {
         self.expanded.contains(path)
     }
 
+    pub fn expand(&mut self, path: &Path) {
+        if self.expanded.insert(path.to_path_buf()) {
+            self.rebuild_visible();
+        }
+    }
+
+    /// Replie tout sauf la racine.
+    pub fn collapse_all(&mut self) {
+        self.expanded.clear();
+        self.expanded.insert(self.root.path.clone());
+        self.rebuild_visible();
+    }
+
+    /// Deplie chaque dossier jusqu'a `EXPAND_ALL_MAX_DEPTH` ; vrai si des dossiers plus
+    /// profonds sont restes replies.
+    pub fn expand_all(&mut self) -> bool {
+        let mut truncated = false;
+        let source = TreeSource {
+            workspace: &self.workspace,
+            rules: self.rules.as_ref(),
+        };
+        collect_dirs(
+            &mut self.root,
+            0,
+            &source,
+            &mut self.expanded,
+            &mut truncated,
+        );
+        self.rebuild_visible();
+        truncated
+    }
+
+    /// Dossiers deplies (hors racine, toujours depliee), pour la session.
+    pub fn expanded_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .expanded
+            .iter()
+            .filter(|path| **path != self.root.path)
+            .cloned()
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Restaure les dossiers deplies, en ignorant ceux qui n'existent plus.
+    pub fn restore_expanded(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            if path.is_dir() {
+                self.expanded.insert(path.clone());
+            }
+        }
+        self.rebuild_visible();
+    }
+
+    /// Deplie tous les dossiers parents de `path` et renvoie sa position dans la liste visible.
+    pub fn reveal(&mut self, path: &Path) -> Option<usize> {
+        if !path.starts_with(&self.root.path) {
+            return None;
+        }
+        for ancestor in path.ancestors().skip(1) {
+            if !ancestor.starts_with(&self.root.path) {
+                break;
+            }
+            self.expanded.insert(ancestor.to_path_buf());
+        }
+        self.rebuild_visible();
+        self.position_of(path)
+    }
+
+    pub fn position_of(&self, path: &Path) -> Option<usize> {
+        self.visible.iter().position(|entry| entry.path == path)
+    }
+
+    /// Apres un reload : position de `previous`, sinon de son plus proche parent visible.
+    pub fn reselect(&self, previous: &Path) -> Option<usize> {
+        previous
+            .ancestors()
+            .take_while(|path| path.starts_with(&self.root.path))
+            .find_map(|path| self.position_of(path))
+    }
+
     fn rebuild_visible(&mut self) {
+        let source = TreeSource {
+            workspace: &self.workspace,
+            rules: self.rules.as_ref(),
+        };
+        load_expanded(&mut self.root, &self.expanded, &source);
         self.visible.clear();
-        flatten_tree(&self.root, 0, &self.expanded, &mut self.visible);
+        let filter = TreeFilter {
+            expanded: &self.expanded,
+            show_hidden: self.show_hidden,
+            keep_visible: self.keep_visible.as_deref(),
+            git_status: &self.git_status,
+        };
+        flatten_tree(&self.root, 0, &filter, &mut self.visible);
     }
 }
 
@@ -224,7 +462,61 @@ impl FileTreeData {
 pub struct OpenedWorkspaceFile {
     pub path: PathBuf,
     pub encoding: String,
+    /// BOM en tete du fichier, a reecrire a la sauvegarde.
+    pub bom: bool,
+    pub line_ending: LineEnding,
+    /// Texte avec des fins de ligne normalisees en `\n`.
     pub text: String,
+    /// Renseigne si seul un apercu en lecture seule est affiche.
+    pub preview: Option<FilePreview>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewKind {
+    /// Binaire : vue hexadecimale des premiers octets.
+    Hex,
+    /// Texte trop gros pour l'editeur : debut du fichier seulement.
+    Text,
+}
+
+/// Apercu en lecture seule : `shown` premiers octets sur `total`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilePreview {
+    pub kind: PreviewKind,
+    pub shown: u64,
+    pub total: u64,
+}
+
+impl FilePreview {
+    /// Bandeau affiche au-dessus de l'editeur.
+    pub fn banner(&self) -> String {
+        let what = match self.kind {
+            PreviewKind::Hex => "Apercu hexadecimal",
+            PreviewKind::Text => "Apercu texte (fichier trop gros)",
+        };
+        format!(
+            "{what}, lecture seule : {} Ko sur {} Ko",
+            self.shown.div_ceil(1024),
+            self.total.div_ceil(1024)
+        )
+    }
+}
+
+/// Profondeur maximale depliee par `expand_all`.
+pub const EXPAND_ALL_MAX_DEPTH: usize = 8;
+
+/// Taille par defaut au-dela de laquelle un texte s'ouvre en apercu.
+pub const PREVIEW_MAX_BYTES: u64 = 2 * 1024 * 1024;
+
+/// USBIDE_PREVIEW_MAX_BYTES ; absent, 0 ou invalide : `PREVIEW_MAX_BYTES`.
+pub fn parse_preview_max_bytes(raw: Option<&str>) -> u64 {
+    raw.and_then(|raw| raw.trim().parse::<u64>().ok())
+        .filter(|bytes| *bytes > 0)
+        .unwrap_or(PREVIEW_MAX_BYTES)
+}
+
+pub fn preview_max_bytes_from_env() -> u64 {
+    parse_preview_max_bytes(std::env::var("USBIDE_PREVIEW_MAX_BYTES").ok().as_deref())
 }
 
 #[derive(Debug, Error)]
@@ -247,90 +539,437 @@ pub enum OpenWorkspaceFileError {
     },
 }
 
+#[derive(Debug, Error)]
+pub enum WorkspaceOpError {
+    #[error("Nom invalide: {0}")]
+    InvalidName(String),
+    #[error("Existe deja: {0}")]
+    AlreadyExists(PathBuf),
+    #[error("Erreur fichier: {path} ({source})")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// Refuse les noms vides, "." / ".." et tout separateur pour rester dans le dossier cible.
+pub fn validate_entry_name(name: &str) -> Result<&str, WorkspaceOpError> {
+    let trimmed = name.trim();
+    if trimmed.is_empty()
+        || trimmed == "."
+        || trimmed == ".."
+        || trimmed.contains('/')
+        || trimmed.contains('\\')
+    {
+        return Err(WorkspaceOpError::InvalidName(name.to_string()));
+    }
+    Ok(trimmed)
+}
+
+/// Dossier ou creer une entree : le dossier selectionne, sinon le parent du fichier.
+pub fn target_dir_for(entry: Option<&TreeEntry>, root_dir: &Path) -> PathBuf {
+    match entry {
+        Some(entry) if entry.is_dir => entry.path.clone(),
+        Some(entry) => entry
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| root_dir.to_path_buf()),
+        None => root_dir.to_path_buf(),
+    }
+}
+
+pub fn create_entry(parent: &Path, name: &str, is_dir: bool) -> Result<PathBuf, WorkspaceOpError> {
+    let name = validate_entry_name(name)?;
+    let path = parent.join(name);
+    if path.exists() {
+        return Err(WorkspaceOpError::AlreadyExists(path));
+    }
+    let result = if is_dir {
+        fs::create_dir(&path)
+    } else {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map(|_| ())
+    };
+    result.map_err(|source| WorkspaceOpError::Io {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// Cree `relative` (separateurs `/` ou `\\` acceptes) sous `root` avec `content` ; les
+/// dossiers manquants sont crees. Refuse les chemins absolus, `..` et un fichier existant.
+pub fn create_file_under(
+    root: &Path,
+    relative: &str,
+    content: &str,
+) -> Result<PathBuf, WorkspaceOpError> {
+    let mut path = root.to_path_buf();
+    let mut parts = 0;
+    for part in relative.trim().split(['/', '\\']) {
+        if part.is_empty() || part == "." {
+            continue;
+        }
+        if part.contains(':') {
+            return Err(WorkspaceOpError::InvalidName(relative.to_string()));
+        }
+        path.push(validate_entry_name(part)?);
+        parts += 1;
+    }
+    if parts == 0 || relative.trim().starts_with(['/', '\\']) {
+        return Err(WorkspaceOpError::InvalidName(relative.to_string()));
+    }
+    if path.exists() {
+        return Err(WorkspaceOpError::AlreadyExists(path));
+    }
+    let io_err = |source| WorkspaceOpError::Io {
+        path: path.clone(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_err)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(io_err)?;
+    std::io::Write::write_all(&mut file, content.as_bytes()).map_err(io_err)?;
+    Ok(path)
+}
+
+pub fn rename_entry(path: &Path, new_name: &str) -> Result<PathBuf, WorkspaceOpError> {
+    let new_name = validate_entry_name(new_name)?;
+    let parent = path
+        .parent()
+        .ok_or_else(|| WorkspaceOpError::InvalidName(path.display().to_string()))?;
+    let target = parent.join(new_name);
+    if target == path {
+        return Ok(target);
+    }
+    if target.exists() {
+        return Err(WorkspaceOpError::AlreadyExists(target));
+    }
+    fs::rename(path, &target).map_err(|source| WorkspaceOpError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(target)
+}
+
+/// Deplace une entree dans `.usbide/trash` (pas de suppression definitive sur la cle).
+pub fn move_to_trash(workspace: &WorkspacePaths, path: &Path) -> Result<PathBuf, WorkspaceOpError> {
+    let target = trash_target(workspace, path)?;
+    fs::rename(path, &target).map_err(|source| WorkspaceOpError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(target)
+}
+
+/// Copie un fichier dans `.usbide/trash` avant de le reecrire ; l'original reste en place.
+pub fn copy_to_trash(workspace: &WorkspacePaths, path: &Path) -> Result<PathBuf, WorkspaceOpError> {
+    let target = trash_target(workspace, path)?;
+    fs::copy(path, &target).map_err(|source| WorkspaceOpError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(target)
+}
+
+/// Chemin libre `.usbide/trash/<horodatage>_<nom>` pour `path` (dossier cree au besoin).
+fn trash_target(workspace: &WorkspacePaths, path: &Path) -> Result<PathBuf, WorkspaceOpError> {
+    if path == workspace.root_dir() || workspace.is_internal_path(path) {
+        return Err(WorkspaceOpError::InvalidName(path.display().to_string()));
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| WorkspaceOpError::InvalidName(path.display().to_string()))?;
+    let trash = workspace.trash_dir();
+    fs::create_dir_all(trash).map_err(|source| WorkspaceOpError::Io {
+        path: trash.to_path_buf(),
+        source,
+    })?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut target = trash.join(format!("{stamp}_{name}"));
+    let mut counter = 1;
+    while target.exists() {
+        target = trash.join(format!("{stamp}-{counter}_{name}"));
+        counter += 1;
+    }
+    Ok(target)
+}
+
+/// Chemin apres renommage de `from` en `to`, si `path` est `from` ou se trouve dedans.
+pub fn remap_path(path: &Path, from: &Path, to: &Path) -> Option<PathBuf> {
+    path.strip_prefix(from).ok().map(|rest| {
+        if rest.as_os_str().is_empty() {
+            to.to_path_buf()
+        } else {
+            to.join(rest)
+        }
+    })
+}
+
+/// Taille cumulee des fichiers sous `path` (liens symboliques non suivis), 0 s'il manque.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| dir_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
 pub fn open_workspace_file(
     workspace: &WorkspacePaths,
     path: PathBuf,
 ) -> Result<OpenedWorkspaceFile, OpenWorkspaceFileError> {
-    if workspace.is_sensitive_path(&path) {
-        return Err(OpenWorkspaceFileError::Sensitive(path));
-    }
-    if workspace.is_internal_path(&path) {
-        return Err(OpenWorkspaceFileError::Hidden(path));
+    open_workspace_file_checked(workspace, path, true, preview_max_bytes_from_env())
+}
+
+/// "Ouvrir quand meme" un fichier signale binaire : seules les protections restent.
+pub fn open_workspace_file_anyway(
+    workspace: &WorkspacePaths,
+    path: PathBuf,
+) -> Result<OpenedWorkspaceFile, OpenWorkspaceFileError> {
+    open_workspace_file_checked(workspace, path, false, preview_max_bytes_from_env())
+}
+
+/// Apercu hexadecimal en lecture seule d'un binaire (memes protections qu'a l'ouverture).
+pub fn open_workspace_hex_preview(
+    workspace: &WorkspacePaths,
+    path: PathBuf,
+) -> Result<OpenedWorkspaceFile, OpenWorkspaceFileError> {
+    check_openable(workspace, &path)?;
+    let total = file_len(&path)?;
+    let bytes =
+        read_head(&path, HEX_PREVIEW_BYTES).map_err(|source| OpenWorkspaceFileError::Read {
+            path: path.clone(),
+            source,
+        })?;
+    Ok(OpenedWorkspaceFile {
+        path,
+        encoding: "utf-8".to_string(),
+        bom: false,
+        line_ending: LineEnding::Lf,
+        text: hex_dump(&bytes),
+        preview: Some(FilePreview {
+            kind: PreviewKind::Hex,
+            shown: bytes.len() as u64,
+            total,
+        }),
+    })
+}
+
+fn check_openable(workspace: &WorkspacePaths, path: &Path) -> Result<(), OpenWorkspaceFileError> {
+    if workspace.is_sensitive_path(path) {
+        return Err(OpenWorkspaceFileError::Sensitive(path.to_path_buf()));
     }
-    if path.is_dir() {
-        return Err(OpenWorkspaceFileError::Hidden(path));
+    if workspace.is_internal_path(path) || path.is_dir() {
+        return Err(OpenWorkspaceFileError::Hidden(path.to_path_buf()));
     }
+    Ok(())
+}
 
-    match is_probably_binary(&path, 2048) {
-        Ok(true) => return Err(OpenWorkspaceFileError::Binary(path)),
-        Ok(false) => {}
-        Err(source) => {
-            return Err(OpenWorkspaceFileError::Access { path, source });
+fn file_len(path: &Path) -> Result<u64, OpenWorkspaceFileError> {
+    fs::metadata(path)
+        .map(|meta| meta.len())
+        .map_err(|source| OpenWorkspaceFileError::Access {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+fn open_workspace_file_checked(
+    workspace: &WorkspacePaths,
+    path: PathBuf,
+    check_binary: bool,
+    preview_max: u64,
+) -> Result<OpenedWorkspaceFile, OpenWorkspaceFileError> {
+    check_openable(workspace, &path)?;
+    if check_binary {
+        match is_probably_binary(&path, 2048) {
+            Ok(true) => return Err(OpenWorkspaceFileError::Binary(path)),
+            Ok(false) => {}
+            Err(source) => {
+                return Err(OpenWorkspaceFileError::Access { path, source });
+            }
         }
     }
 
-    let encoding = detect_text_encoding(&path);
-    let text = read_text_with_encoding(&path, &encoding).map_err(|source| {
-        OpenWorkspaceFileError::Read {
-            path: path.clone(),
-            source,
+    let bom = has_bom(&path);
+    let total = file_len(&path)?;
+    let read_error = |source| OpenWorkspaceFileError::Read {
+        path: path.clone(),
+        source,
+    };
+    let (encoding, text, preview) = if total > preview_max {
+        // Trop gros pour l'editeur : debut du fichier, coupe a la derniere ligne complete.
+        let bytes = read_head(&path, preview_max as usize).map_err(read_error)?;
+        let encoding = detect_text_encoding_bytes(&path, &bytes);
+        let mut text = decode_text(&bytes, &encoding);
+        if let Some(end) = text.rfind('\n') {
+            text.truncate(end + 1);
         }
-    })?;
+        let preview = FilePreview {
+            kind: PreviewKind::Text,
+            shown: bytes.len() as u64,
+            total,
+        };
+        (encoding, text, Some(preview))
+    } else {
+        let encoding = detect_text_encoding(&path);
+        let text = read_text_with_encoding(&path, &encoding).map_err(read_error)?;
+        (encoding, text, None)
+    };
 
+    let line_ending = detect_line_ending(&text);
     Ok(OpenedWorkspaceFile {
         path,
         encoding,
-        text,
+        bom,
+        line_ending,
+        text: apply_line_ending(&text, LineEnding::Lf),
+        preview,
     })
 }
 
-fn build_tree(path: &Path, workspace: &WorkspacePaths) -> FileNode {
+/// Noeud dont le contenu n'est pas encore lu.
+fn tree_node(path: PathBuf) -> FileNode {
     let name = path
         .file_name()
         .and_then(|s| s.to_str())
         .map(|s| s.to_string())
         .unwrap_or_else(|| path.display().to_string());
     let is_dir = path.is_dir();
-    let mut children = Vec::new();
+    FileNode {
+        path,
+        name,
+        is_dir,
+        children: None,
+    }
+}
 
-    if is_dir && let Ok(read_dir) = fs::read_dir(path) {
-        for entry in read_dir.flatten() {
-            let child_path = entry.path();
-            if !workspace.should_display_in_tree(&child_path) {
-                continue;
-            }
-            children.push(build_tree(&child_path, workspace));
-        }
+/// Ce qu'il faut pour lire un dossier de l'arborescence.
+struct TreeSource<'a> {
+    workspace: &'a WorkspacePaths,
+    rules: Option<&'a IgnoreRules>,
+}
+
+impl TreeSource<'_> {
+    /// Entrees directes de `path` (dossiers d'abord), sans descendre plus bas.
+    fn children(&self, path: &Path) -> Vec<FileNode> {
+        let Ok(read_dir) = fs::read_dir(path) else {
+            return Vec::new();
+        };
+        let mut children: Vec<FileNode> = read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|child| self.workspace.should_display_in_tree(child))
+            .filter(|child| {
+                !self.rules.is_some_and(|rules| {
+                    child
+                        .strip_prefix(self.workspace.root_dir())
+                        .is_ok_and(|relative| rules.is_ignored(relative, child.is_dir()))
+                })
+            })
+            .map(tree_node)
+            .collect();
         children.sort_by_key(|node| (!node.is_dir, node.name.to_lowercase()));
+        children
     }
+}
 
-    FileNode {
-        path: path.to_path_buf(),
-        name,
-        is_dir,
-        children,
+/// Lit les dossiers deplies qui ne l'ont pas encore ete.
+fn load_expanded(node: &mut FileNode, expanded: &HashSet<PathBuf>, source: &TreeSource<'_>) {
+    if !node.is_dir || !expanded.contains(&node.path) {
+        return;
+    }
+    let children = node
+        .children
+        .get_or_insert_with(|| source.children(&node.path));
+    for child in children {
+        load_expanded(child, expanded, source);
     }
 }
 
-fn flatten_tree(
-    node: &FileNode,
+struct TreeFilter<'a> {
+    expanded: &'a HashSet<PathBuf>,
+    show_hidden: bool,
+    keep_visible: Option<&'a Path>,
+    git_status: &'a GitStatusMap,
+}
+
+impl TreeFilter<'_> {
+    fn shows(&self, node: &FileNode) -> bool {
+        self.show_hidden
+            || !node.name.starts_with('.')
+            || self
+                .keep_visible
+                .is_some_and(|path| path.starts_with(&node.path))
+    }
+}
+
+fn collect_dirs(
+    node: &mut FileNode,
     depth: usize,
-    expanded: &HashSet<PathBuf>,
-    out: &mut Vec<TreeEntry>,
+    source: &TreeSource<'_>,
+    out: &mut HashSet<PathBuf>,
+    truncated: &mut bool,
 ) {
+    if !node.is_dir {
+        return;
+    }
+    let children = node
+        .children
+        .get_or_insert_with(|| source.children(&node.path));
+    if depth >= EXPAND_ALL_MAX_DEPTH {
+        *truncated |= children.iter().any(|child| child.is_dir);
+        return;
+    }
+    out.insert(node.path.clone());
+    for child in children {
+        collect_dirs(child, depth + 1, source, out, truncated);
+    }
+}
+
+fn flatten_tree(node: &FileNode, depth: usize, filter: &TreeFilter<'_>, out: &mut Vec<TreeEntry>) {
     out.push(TreeEntry {
         path: node.path.clone(),
         name: node.name.clone(),
         depth,
         is_dir: node.is_dir,
+        git: filter.git_status.get(&node.path, node.is_dir),
     });
-    if node.is_dir && expanded.contains(&node.path) {
-        for child in &node.children {
-            flatten_tree(child, depth + 1, expanded, out);
+    if node.is_dir && filter.expanded.contains(&node.path) {
+        let children = node.children.as_deref().unwrap_or_default();
+        for child in children.iter().filter(|child| filter.shows(child)) {
+            flatten_tree(child, depth + 1, filter, out);
         }
     }
 }
 
+/// Venv externe (USBIDE_VENV) s'il contient un interpreteur, sinon le venv du projet s'il existe.
+pub fn select_venv(external: Option<&str>, project: &Path) -> Option<PathBuf> {
+    let external = external
+        .map(str::trim)
+        .filter(|raw| !raw.is_empty())
+        .map(PathBuf::from);
+    external
+        .into_iter()
+        .chain(std::iter::once(project.to_path_buf()))
+        .find(|venv| venv_python(venv).is_file())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -359,6 +998,196 @@ mod tests {
         assert!(!names.iter().any(|name| name == "codex_home"));
     }
 
+    #[test]
+    fn masque_fichiers_ignores_sauf_si_demande() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("node_modules").join("lib")).unwrap();
+        fs::create_dir_all(root.join("src").join("__pycache__")).unwrap();
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::write(root.join("src").join("main.py"), "").unwrap();
+        fs::write(root.join("src").join("old.pyc"), "").unwrap();
+        fs::write(root.join(".gitignore"), "build/\n").unwrap();
+
+        let workspace = WorkspacePaths::new(root.to_path_buf());
+        let mut tree = FileTreeData::new(&workspace);
+        tree.expand(&root.join("src"));
+        let names = |tree: &FileTreeData| -> Vec<String> {
+            tree.visible()
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect()
+        };
+        let visible = names(&tree);
+        assert!(visible.contains(&"main.py".to_string()));
+        for hidden in ["node_modules", "__pycache__", "build", "old.pyc"] {
+            assert!(!visible.contains(&hidden.to_string()), "{hidden}");
+        }
+
+        tree.set_show_ignored(true, &workspace);
+        let visible = names(&tree);
+        for shown in ["node_modules", "__pycache__", "build", "old.pyc"] {
+            assert!(visible.contains(&shown.to_string()), "{shown}");
+        }
+    }
+
+    #[test]
+    fn reload_garde_dossiers_deplies_et_selection() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src").join("pkg")).unwrap();
+        fs::write(root.join("src").join("pkg").join("mod.py"), "").unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs").join("notes.md"), "").unwrap();
+
+        let workspace = WorkspacePaths::new(root.to_path_buf());
+        let mut tree = FileTreeData::new(&workspace);
+        tree.expand(&root.join("src"));
+        tree.expand(&root.join("src").join("pkg"));
+        tree.expand(&root.join("docs"));
+        fs::write(root.join("src").join("nouveau.py"), "").unwrap();
+        fs::remove_dir_all(root.join("docs")).unwrap();
+        tree.reload(&workspace);
+
+        assert!(tree.is_expanded(&root.join("src")));
+        assert!(tree.is_expanded(&root.join("src").join("pkg")));
+        assert!(!tree.is_expanded(&root.join("docs")));
+        assert!(
+            tree.position_of(&root.join("src").join("nouveau.py"))
+                .is_some()
+        );
+        let module = root.join("src").join("pkg").join("mod.py");
+        assert_eq!(tree.reselect(&module), tree.position_of(&module));
+        // Fichier supprime : la selection remonte sur le dossier parent.
+        assert_eq!(
+            tree.reselect(&root.join("docs").join("notes.md")),
+            tree.position_of(root)
+        );
+    }
+
+    #[test]
+    fn dossiers_lus_au_premier_depliage() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("gros").join("sous")).unwrap();
+        fs::write(root.join("gros").join("a.txt"), "").unwrap();
+
+        let workspace = WorkspacePaths::new(root.to_path_buf());
+        let mut tree = FileTreeData::new(&workspace);
+        let gros = tree.root.children.as_ref().unwrap();
+        assert_eq!(gros.len(), 1);
+        assert!(gros[0].children.is_none());
+        assert!(tree.position_of(&root.join("gros").join("a.txt")).is_none());
+
+        tree.expand(&root.join("gros"));
+        let gros = &tree.root.children.as_ref().unwrap()[0];
+        let enfants = gros.children.as_ref().unwrap();
+        assert_eq!(enfants.len(), 2);
+        assert!(enfants[0].children.is_none());
+        assert!(tree.position_of(&root.join("gros").join("a.txt")).is_some());
+    }
+
+    #[test]
+    fn replier_et_deplier_toute_l_arborescence() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let mut deep = root.to_path_buf();
+        for level in 0..=EXPAND_ALL_MAX_DEPTH {
+            deep = deep.join(format!("n{level}"));
+        }
+        fs::create_dir_all(&deep).unwrap();
+        fs::write(deep.join("fond.py"), "").unwrap();
+
+        let workspace = WorkspacePaths::new(root.to_path_buf());
+        let mut tree = FileTreeData::new(&workspace);
+        assert!(tree.expand_all());
+        assert!(tree.is_expanded(&root.join("n0")));
+        // Le dossier au niveau limite est affiche mais reste replie.
+        let limit = tree
+            .visible()
+            .iter()
+            .find(|entry| entry.depth == EXPAND_ALL_MAX_DEPTH)
+            .unwrap();
+        assert!(limit.is_dir);
+        assert!(!tree.is_expanded(&limit.path));
+        assert!(tree.position_of(&deep).is_none());
+
+        tree.collapse_all();
+        assert!(tree.expanded_paths().is_empty());
+        assert_eq!(tree.visible().len(), 2);
+    }
+
+    #[test]
+    fn decore_entrees_avec_statut_git() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("main.py"), "").unwrap();
+        fs::write(root.join("notes.txt"), "").unwrap();
+
+        let workspace = WorkspacePaths::new(root.to_path_buf());
+        let mut tree = FileTreeData::new(&workspace);
+        tree.expand(&root.join("src"));
+        let status = |tree: &FileTreeData, path: &Path| {
+            tree.visible()
+                .iter()
+                .find(|entry| entry.path == path)
+                .and_then(|entry| entry.git)
+        };
+        assert_eq!(status(&tree, &root.join("src")), None);
+
+        tree.set_git_status(GitStatusMap::parse(
+            [" M src/main.py", "?? notes.txt"],
+            root,
+        ));
+        assert_eq!(status(&tree, &root.join("src")), Some(GitStatus::Modified));
+        assert_eq!(
+            status(&tree, &root.join("src").join("main.py")),
+            Some(GitStatus::Modified)
+        );
+        assert_eq!(
+            status(&tree, &root.join("notes.txt")),
+            Some(GitStatus::Untracked)
+        );
+        // Conserve apres un reload de l'arborescence.
+        tree.reload(&workspace);
+        assert_eq!(
+            status(&tree, &root.join("notes.txt")),
+            Some(GitStatus::Untracked)
+        );
+    }
+
+    #[test]
+    fn masque_dotfiles_sauf_fichier_ouvert() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".config")).unwrap();
+        fs::write(root.join(".config").join("app.toml"), "").unwrap();
+        fs::write(root.join(".env"), "").unwrap();
+        fs::write(root.join("main.py"), "").unwrap();
+
+        let workspace = WorkspacePaths::new(root.to_path_buf());
+        let mut tree = FileTreeData::new(&workspace);
+        let names = |tree: &FileTreeData| -> Vec<String> {
+            tree.visible()
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect()
+        };
+        assert!(!names(&tree).contains(&".env".to_string()));
+        assert!(!names(&tree).contains(&".config".to_string()));
+
+        let opened = root.join(".config").join("app.toml");
+        tree.set_keep_visible(Some(opened.clone()));
+        assert!(tree.reveal(&opened).is_some());
+        assert!(!names(&tree).contains(&".env".to_string()));
+
+        tree.set_keep_visible(None);
+        tree.set_show_hidden(true);
+        assert!(names(&tree).contains(&".env".to_string()));
+        assert_eq!(tree.visible()[0].path, root.to_path_buf());
+    }
+
     #[test]
     fn protege_auth_json() {
         let dir = TempDir::new().unwrap();
@@ -370,6 +1199,71 @@ mod tests {
         let err = open_workspace_file(&workspace, auth).unwrap_err();
 
         assert!(matches!(err, OpenWorkspaceFileError::Sensitive(_)));
+        let auth = dir.path().join("codex_home").join("auth.json");
+        let err = open_workspace_file_anyway(&workspace, auth).unwrap_err();
+        assert!(matches!(err, OpenWorkspaceFileError::Sensitive(_)));
+    }
+
+    #[test]
+    fn ouvrir_quand_meme_un_faux_binaire() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        let bytes: Vec<u8> = "ligne 1\r\nligne 2"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        fs::write(&path, bytes).unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+
+        let err = open_workspace_file(&workspace, path.clone()).unwrap_err();
+        assert!(matches!(err, OpenWorkspaceFileError::Binary(_)));
+        let opened = open_workspace_file_anyway(&workspace, path).unwrap();
+        assert_eq!(opened.encoding, "utf-16le");
+        assert!(!opened.bom);
+        assert_eq!(opened.text, "ligne 1\nligne 2");
+        assert_eq!(opened.line_ending, LineEnding::Crlf);
+    }
+
+    #[test]
+    fn apercus_binaire_et_gros_fichier() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let bin = dir.path().join("image.bin");
+        fs::write(&bin, [0u8, 1, 2, b'A']).unwrap();
+        let opened = open_workspace_hex_preview(&workspace, bin).unwrap();
+        assert!(opened.text.starts_with("00000000  00 01 02 41"));
+        let preview = opened.preview.unwrap();
+        assert_eq!(
+            (preview.kind, preview.shown, preview.total),
+            (PreviewKind::Hex, 4, 4)
+        );
+        assert!(
+            open_workspace_hex_preview(&workspace, dir.path().join("codex_home").join("auth.json"))
+                .is_err()
+        );
+
+        let big = dir.path().join("gros.log");
+        fs::write(&big, "ligne 1\nligne 2\nligne 3\n").unwrap();
+        let opened = open_workspace_file_checked(&workspace, big.clone(), true, 12).unwrap();
+        assert_eq!(opened.text, "ligne 1\n");
+        let preview = opened.preview.unwrap();
+        assert_eq!((preview.kind, preview.total), (PreviewKind::Text, 24));
+        assert!(preview.banner().contains("lecture seule"));
+        let opened = open_workspace_file_checked(&workspace, big, true, 1024).unwrap();
+        assert!(opened.preview.is_none());
+
+        // Encodage devine sur le debut lu, meme coupe au milieu d'un caractere UTF-8.
+        let accents = dir.path().join("accents.log");
+        fs::write(&accents, "é\né\né\n").unwrap();
+        let opened = open_workspace_file_checked(&workspace, accents, true, 4).unwrap();
+        assert_eq!(
+            (opened.encoding.as_str(), opened.text.as_str()),
+            ("utf-8", "é\n")
+        );
+
+        assert_eq!(parse_preview_max_bytes(Some(" 4096 ")), 4096);
+        assert_eq!(parse_preview_max_bytes(Some("0")), PREVIEW_MAX_BYTES);
+        assert_eq!(parse_preview_max_bytes(None), PREVIEW_MAX_BYTES);
     }
 
     #[test]
@@ -388,6 +1282,134 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cree_fichier_et_dossier() {
+        let dir = TempDir::new().unwrap();
+        let file = create_entry(dir.path(), "main.py", false).unwrap();
+        let folder = create_entry(dir.path(), "pkg", true).unwrap();
+
+        assert!(file.is_file());
+        assert!(folder.is_dir());
+        assert!(matches!(
+            create_entry(dir.path(), "main.py", false),
+            Err(WorkspaceOpError::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn refuse_nom_avec_separateur() {
+        let dir = TempDir::new().unwrap();
+        for name in ["", " ", "..", "a/b", "a\\b"] {
+            assert!(matches!(
+                create_entry(dir.path(), name, false),
+                Err(WorkspaceOpError::InvalidName(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn cree_fichier_sous_la_racine() {
+        let dir = TempDir::new().unwrap();
+        let path =
+            create_file_under(dir.path(), "scripts/outils\\extrait.py", "print(1)\n").unwrap();
+        assert_eq!(
+            path,
+            dir.path().join("scripts").join("outils").join("extrait.py")
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "print(1)\n");
+        assert!(matches!(
+            create_file_under(dir.path(), "./scripts/outils/extrait.py", ""),
+            Err(WorkspaceOpError::AlreadyExists(_))
+        ));
+        for name in [
+            "",
+            "/etc/x.py",
+            "\\x.py",
+            "../x.py",
+            "a/../../x.py",
+            "C:\\x.py",
+        ] {
+            assert!(
+                matches!(
+                    create_file_under(dir.path(), name, ""),
+                    Err(WorkspaceOpError::InvalidName(_))
+                ),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn dossier_cible_depuis_selection() {
+        let root = Path::new("/ws");
+        let file = TreeEntry {
+            path: root.join("src").join("main.py"),
+            name: "main.py".to_string(),
+            depth: 2,
+            is_dir: false,
+            git: None,
+        };
+        let folder = TreeEntry {
+            path: root.join("src"),
+            name: "src".to_string(),
+            depth: 1,
+            is_dir: true,
+            git: None,
+        };
+
+        assert_eq!(target_dir_for(Some(&file), root), root.join("src"));
+        assert_eq!(target_dir_for(Some(&folder), root), root.join("src"));
+        assert_eq!(target_dir_for(None, root), root.to_path_buf());
+    }
+
+    #[test]
+    fn renomme_et_refuse_collision() {
+        let dir = TempDir::new().unwrap();
+        let old = dir.path().join("a.py");
+        fs::write(&old, "x").unwrap();
+        fs::write(dir.path().join("c.py"), "y").unwrap();
+
+        let renamed = rename_entry(&old, "b.py").unwrap();
+        assert!(renamed.is_file());
+        assert!(!old.exists());
+        assert!(matches!(
+            rename_entry(&renamed, "c.py"),
+            Err(WorkspaceOpError::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn corbeille_dans_usbide() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let file = dir.path().join("old.py");
+        fs::write(&file, "x").unwrap();
+
+        let trashed = move_to_trash(&workspace, &file).unwrap();
+        assert!(!file.exists());
+        assert!(trashed.starts_with(dir.path().join(".usbide").join("trash")));
+        assert!(trashed.is_file());
+        assert!(move_to_trash(&workspace, dir.path()).is_err());
+
+        fs::write(&file, "y").unwrap();
+        let copie = copy_to_trash(&workspace, &file).unwrap();
+        assert_ne!(copie, trashed);
+        assert_eq!(fs::read_to_string(&copie).unwrap(), "y");
+        assert!(file.is_file());
+    }
+
+    #[test]
+    fn remap_chemin_sous_dossier_renomme() {
+        let from = Path::new("/ws/src");
+        let to = Path::new("/ws/lib");
+        assert_eq!(
+            remap_path(Path::new("/ws/src/main.py"), from, to),
+            Some(PathBuf::from("/ws/lib/main.py"))
+        );
+        assert_eq!(remap_path(from, from, to), Some(to.to_path_buf()));
+        assert_eq!(remap_path(Path::new("/ws/other.py"), from, to), None);
+    }
+
     #[test]
     fn cree_dossiers_portables() {
         let dir = TempDir::new().unwrap();
@@ -399,4 +1421,79 @@ mod tests {
         assert!(dir.path().join("cache").join("pip").is_dir());
         assert!(dir.path().join("codex_home").is_dir());
     }
+
+    #[test]
+    fn venv_externe_puis_projet() {
+        let dir = TempDir::new().unwrap();
+        let project = dir.path().join(".usbide").join("venv");
+        let external = dir.path().join("ext");
+        assert_eq!(select_venv(None, &project), None);
+
+        for venv in [&project, &external] {
+            let python = venv_python(venv);
+            fs::create_dir_all(python.parent().unwrap()).unwrap();
+            fs::write(python, "").unwrap();
+        }
+        assert_eq!(select_venv(None, &project), Some(project.clone()));
+        assert_eq!(
+            select_venv(external.to_str(), &project),
+            Some(external.clone())
+        );
+        assert_eq!(select_venv(Some("/absent"), &project), Some(project));
+    }
+
+    #[test]
+    fn caches_deplacables_et_mesures() {
+        let dir = TempDir::new().unwrap();
+        let mut workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let elsewhere = dir.path().join("ailleurs");
+        workspace.set_cache_root(&elsewhere);
+        workspace.ensure_portable_dirs();
+        assert!(elsewhere.join("npm").is_dir());
+        let env = workspace.portable_env(std::collections::HashMap::new());
+        assert_eq!(
+            env.get("PIP_CACHE_DIR").unwrap(),
+            &elsewhere.join("pip").display().to_string()
+        );
+        fs::create_dir_all(elsewhere.join("pip").join("http")).unwrap();
+        fs::write(elsewhere.join("pip").join("http").join("a"), [0u8; 100]).unwrap();
+        fs::write(elsewhere.join("pip").join("b"), [0u8; 20]).unwrap();
+        assert_eq!(dir_size(&elsewhere.join("pip")), 120);
+        assert_eq!(dir_size(&elsewhere.join("absent")), 0);
+    }
+
+    #[test]
+    fn portable_env_met_le_venv_en_tete_du_path() {
+        let dir = TempDir::new().unwrap();
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let python = venv_python(workspace.venv_dir());
+        fs::create_dir_all(python.parent().unwrap()).unwrap();
+        fs::write(&python, "").unwrap();
+
+        let base = std::collections::HashMap::from([("PATH".to_string(), "/usr/bin".to_string())]);
+        let env = workspace.portable_env(base);
+        let first = std::env::split_paths(&env["PATH"]).next().unwrap();
+        assert_eq!(first, python.parent().unwrap());
+        assert_eq!(
+            env["VIRTUAL_ENV"],
+            workspace.venv_dir().display().to_string()
+        );
+    }
+
+    #[test]
+    fn revele_un_fichier_profond() {
+        let dir = TempDir::new().unwrap();
+        let deep = dir.path().join("a").join("b");
+        fs::create_dir_all(&deep).unwrap();
+        fs::write(deep.join("main.py"), "").unwrap();
+
+        let workspace = WorkspacePaths::new(dir.path().to_path_buf());
+        let mut tree = FileTreeData::new(&workspace);
+        assert!(tree.position_of(&deep.join("main.py")).is_none());
+
+        let idx = tree.reveal(&deep.join("main.py")).unwrap();
+        assert_eq!(tree.visible()[idx].path, deep.join("main.py"));
+        assert!(tree.is_expanded(&dir.path().join("a")));
+        assert!(tree.reveal(Path::new("/ailleurs/x.py")).is_none());
+    }
 }